@@ -14,6 +14,10 @@
 (set-gui-color "statusline-background" "#F0F0F0")
 (set-gui-color "statusline-foreground" "#101010")
 
+;; セッション（auto-save-session は終了時に保存して次回起動時に復元、restore-session は起動時の復元のみ）
+(set-option "auto-save-session" #f)
+(set-option "restore-session" #f)
+
 ;; キーバインド
 (bind-key "C-n" "next-line")
 (bind-key "C-p" "previous-line")
@@ -35,6 +39,9 @@
 (bind-key "Right" "forward-char")
 (bind-key "M-<" "beginning-of-buffer")
 (bind-key "M->" "end-of-buffer")
+(bind-key "M-}" "forward-paragraph")
+(bind-key "M-{" "backward-paragraph")
+(bind-key "M-(" "insert-parentheses")
 (bind-key "Backspace" "delete-backward-char")
 (bind-key "Delete" "delete-char")
 (bind-key "C-d" "delete-char")
@@ -43,7 +50,13 @@
 (bind-key "C-k" "kill-line")
 (bind-key "C-y" "yank")
 (bind-key "M-y" "yank-pop")
+(bind-key "C-q" "quoted-insert")
+(bind-key "M-q" "fill-paragraph")
+(bind-key "M-SPC" "just-one-space")
+(bind-key "M-\\" "delete-horizontal-space")
+(bind-key "M-c" "capitalize-dwim")
 (bind-key "C-g" "keyboard-quit")
+(bind-key "C-u" "universal-argument")
 (bind-key "Enter" "newline")
 (bind-key "Tab" "indent-for-tab-command")
 (bind-key "C-j" "newline-and-indent")
@@ -64,6 +77,7 @@
 (bind-key "C-x b" "switch-to-buffer")
 (bind-key "C-x k" "kill-buffer")
 (bind-key "C-x C-b" "list-buffers")
+(bind-key "C-x C-q" "toggle-read-only")
 (bind-key "C-x 2" "split-window-below")
 (bind-key "C-x 3" "split-window-right")
 (bind-key "C-x 1" "delete-other-windows")
@@ -74,11 +88,19 @@
 (bind-key "C-x <" "scroll-left")
 (bind-key "C-x >" "scroll-right")
 (bind-key "C-x h" "mark-whole-buffer")
+(bind-key "C-x f" "set-fill-column")
+(bind-key "C-x 8 Enter" "insert-char")
+(bind-key "C-x r t" "string-rectangle")
 
 (bind-key "M-g g" "goto-line")
 (bind-key "M-g M-g" "goto-line")
+(bind-key "M-g c" "goto-char")
+(bind-key "M-g Tab" "goto-column")
+(bind-key "M-g n" "next-error")
+(bind-key "M-g p" "previous-error")
 
 (bind-key "M-x" "execute-command")
 (bind-key "M-%" "query-replace")
 (bind-key "C-M-%" "query-replace-regexp")
 (bind-key "M-:" "eval-expression")
+(bind-key "C-x M-:" "repeat-complex-command")
@@ -34,6 +34,7 @@ impl SourceSpan {
 pub enum ReaderErrorKind {
     UnexpectedChar(char),
     UnterminatedString,
+    UnterminatedComment,
     InvalidNumber(String),
     UnexpectedToken(String),
     UnexpectedEof,
@@ -92,6 +93,8 @@ pub struct EvalError {
     pub kind: EvalErrorKind,
     pub span: Option<SourceSpan>,
     pub message: String,
+    /// エラーが通過した呼び出し（内側から順）
+    pub backtrace: Vec<String>,
 }
 
 impl EvalError {
@@ -100,9 +103,15 @@ impl EvalError {
             kind,
             span,
             message: message.into(),
+            backtrace: Vec::new(),
         }
     }
 
+    pub fn with_frame(mut self, frame: impl Into<String>) -> Self {
+        self.backtrace.push(frame.into());
+        self
+    }
+
     pub fn from_reader(err: ReaderError) -> Self {
         Self::new(
             EvalErrorKind::Reader(err.clone()),
@@ -1,28 +1,73 @@
 use crate::alisp::ast::Expr;
 use crate::alisp::error::{EvalError, EvalErrorKind};
-use crate::alisp::primitives::PrimitiveRegistry;
+use crate::alisp::primitives::{Arity, PrimitiveRegistry};
 use crate::alisp::reader;
 use crate::alisp::runtime::{
     closure_ref, collect, define_symbol, extend_env, lookup_env, make_closure, make_rooted_env,
     maybe_collect, set_symbol, value_to_string, Closure, EnvHandle, Function, RuntimeState, Value,
 };
 use crate::alisp::symbol::{SymbolId, SymbolInterner};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// 評価結果（最終値・副作用メッセージ・エラーを個別に保持する）
 #[derive(Debug, Clone)]
 pub struct EvalOutcome {
     pub value: Value,
     pub display: String,
     pub messages: Vec<String>,
+    pub error: Option<EvalError>,
+}
+
+impl EvalOutcome {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn failed(error: EvalError, messages: Vec<String>) -> Self {
+        Self {
+            value: Value::Unit,
+            display: String::new(),
+            messages,
+            error: Some(error),
+        }
+    }
+}
+
+impl fmt::Display for EvalOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(err) => write!(f, "{}", err)?,
+            None => write!(f, "{}", self.display)?,
+        }
+        if !self.messages.is_empty() {
+            write!(f, " ({})", self.messages.join(" | "))?;
+        }
+        Ok(())
+    }
+}
+
+/// describe-function で表示する関数の情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionHelp {
+    pub name: String,
+    /// 組込み関数なら `true`、`define` / `lambda` で作った関数なら `false`
+    pub builtin: bool,
+    pub arity: Arity,
+    /// 引数名（組込み関数では空）
+    pub params: Vec<String>,
+    pub doc: Option<String>,
 }
 
 pub struct Interpreter {
     runtime: RuntimeState,
     global_env: EnvHandle,
     specials: SpecialForms,
-    _primitives: PrimitiveRegistry,
+    primitives: PrimitiveRegistry,
     load_paths: Vec<PathBuf>,
+    /// エラー時のバックトレースに引数を含めた呼び出し式を記録するか
+    debug_on_error: bool,
 }
 
 #[derive(Clone, Copy)]
@@ -36,6 +81,7 @@ struct SpecialForms {
     and_form: SymbolId,
     or_form: SymbolId,
     load: SymbolId,
+    quote: SymbolId,
 }
 
 impl SpecialForms {
@@ -50,6 +96,7 @@ impl SpecialForms {
             and_form: interner.intern("and"),
             or_form: interner.intern("or"),
             load: interner.intern("load"),
+            quote: interner.intern("quote"),
         }
     }
 }
@@ -68,11 +115,32 @@ impl Interpreter {
             runtime,
             global_env,
             specials,
-            _primitives: primitives,
+            primitives,
             load_paths,
+            debug_on_error: false,
         }
     }
 
+    /// エラー時のバックトレースに引数を含めた呼び出し式を記録するか
+    pub fn debug_on_error(&self) -> bool {
+        self.debug_on_error
+    }
+
+    /// 有効にするとバックトレースの各フレームが `(inner 1)` のような呼び出し式になる
+    /// （無効なら関数名のみ）
+    pub fn set_debug_on_error(&mut self, enabled: bool) {
+        self.debug_on_error = enabled;
+    }
+
+    /// グローバル環境を組み込み関数だけの状態に戻す（ホストと load パスはそのまま）
+    ///
+    /// 以前の環境で作られた値は GC のルートから外れて回収される。
+    pub fn reset_environment(&mut self) {
+        self.global_env = make_rooted_env(&mut self.runtime);
+        self.primitives = PrimitiveRegistry::install(&mut self.runtime, self.global_env);
+        collect(&mut self.runtime, &[], &[self.global_env]);
+    }
+
     pub fn set_load_root<P: Into<PathBuf>>(&mut self, root: P) {
         self.load_paths.clear();
         self.load_paths.push(root.into());
@@ -112,6 +180,17 @@ impl Interpreter {
         self.eval_source(source)
     }
 
+    /// 評価結果をエラーも含めて返す（エラー前に出力されたメッセージも保持する）
+    pub fn eval_outcome(&mut self, source: &str) -> EvalOutcome {
+        match self.eval_source(source) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                let messages = self.runtime.drain_messages();
+                EvalOutcome::failed(err, messages)
+            }
+        }
+    }
+
     pub fn runtime_mut(&mut self) -> &mut RuntimeState {
         &mut self.runtime
     }
@@ -120,6 +199,57 @@ impl Interpreter {
         &self.runtime
     }
 
+    /// グローバルに束縛された関数の情報（関数でなければ `None`）
+    pub fn describe_function(&mut self, name: &str) -> Option<FunctionHelp> {
+        let symbol = self.runtime.intern(name);
+        let function = match lookup_env(&self.runtime, self.global_env, symbol)? {
+            Value::Function(function) => function,
+            _ => return None,
+        };
+        let help = match function {
+            Function::Builtin(_) => {
+                let doc = self.primitives.doc(symbol);
+                FunctionHelp {
+                    name: name.to_string(),
+                    builtin: true,
+                    arity: doc.map_or(Arity::AtLeast(0), |doc| doc.arity),
+                    params: Vec::new(),
+                    doc: doc.map(|doc| doc.doc.to_string()),
+                }
+            }
+            Function::Lambda(handle) => {
+                let closure = closure_ref(&self.runtime, handle);
+                FunctionHelp {
+                    name: name.to_string(),
+                    builtin: false,
+                    arity: Arity::Exact(closure.params.len()),
+                    params: closure
+                        .params
+                        .iter()
+                        .map(|param| self.runtime.resolve(*param).unwrap_or("?").to_string())
+                        .collect(),
+                    doc: closure.doc.clone(),
+                }
+            }
+        };
+        Some(help)
+    }
+
+    /// グローバルに束縛された関数名の一覧（名前順）
+    pub fn function_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .runtime
+            .heap
+            .env_ref(self.global_env)
+            .bindings
+            .iter()
+            .filter(|(_, value)| matches!(value, Value::Function(_)))
+            .filter_map(|(symbol, _)| self.runtime.resolve(*symbol).map(str::to_string))
+            .collect();
+        names.sort();
+        names
+    }
+
     fn eval_source(&mut self, source: &str) -> Result<EvalOutcome, EvalError> {
         let forms =
             reader::parse(source, &mut self.runtime.interner).map_err(EvalError::from_reader)?;
@@ -138,6 +268,7 @@ impl Interpreter {
             value: last_value,
             display,
             messages,
+            error: None,
         })
     }
 
@@ -146,7 +277,7 @@ impl Interpreter {
             Expr::Integer(i) => Ok(Value::Integer(*i)),
             Expr::Float(f) => Ok(Value::Float(*f)),
             Expr::Boolean(b) => Ok(Value::Boolean(*b)),
-            Expr::String(s) => Ok(self.runtime.alloc_string_value(s.clone())),
+            Expr::String(s) => Ok(self.runtime.intern_string_value(s.clone())),
             Expr::Symbol(sym) => lookup_env(&self.runtime, env, *sym).ok_or_else(|| {
                 EvalError::new(
                     EvalErrorKind::NameNotFound(*sym),
@@ -163,7 +294,7 @@ impl Interpreter {
 
     fn eval_list(&mut self, list: &[Expr], env: EnvHandle) -> Result<Value, EvalError> {
         if list.is_empty() {
-            return Ok(Value::Unit);
+            return Ok(Value::Nil);
         }
         let head = &list[0];
         if let Some(sym) = head.as_symbol() {
@@ -194,6 +325,9 @@ impl Interpreter {
             if sym == self.specials.load {
                 return self.eval_load(&list[1..], env);
             }
+            if sym == self.specials.quote {
+                return self.eval_quote(&list[1..]);
+            }
         }
         self.eval_call(list, env)
     }
@@ -412,6 +546,31 @@ impl Interpreter {
         Ok(Value::Boolean(false))
     }
 
+    /// 空リストと自己評価するアトムのみクォートできる（シンボルやリストの値型は未実装）
+    fn eval_quote(&mut self, exprs: &[Expr]) -> Result<Value, EvalError> {
+        if exprs.len() != 1 {
+            return Err(EvalError::new(
+                EvalErrorKind::ArityMismatch {
+                    expected: 1,
+                    found: exprs.len(),
+                },
+                None,
+                "quote は1つの引数が必要です",
+            ));
+        }
+        match &exprs[0] {
+            Expr::List(items) if items.is_empty() => Ok(Value::Nil),
+            expr @ (Expr::Integer(_) | Expr::Float(_) | Expr::Boolean(_) | Expr::String(_)) => {
+                self.eval_expr(expr, self.global_env)
+            }
+            _ => Err(EvalError::new(
+                EvalErrorKind::Runtime("quote できるのは空リストとアトムのみです".into()),
+                None,
+                "quote できるのは空リストとアトムのみです",
+            )),
+        }
+    }
+
     fn eval_load(&mut self, exprs: &[Expr], env: EnvHandle) -> Result<Value, EvalError> {
         if exprs.len() != 1 {
             return Err(EvalError::new(
@@ -478,21 +637,50 @@ impl Interpreter {
             args.push(self.eval_expr(arg, env)?);
         }
         maybe_collect(&mut self.runtime, &args, &[env, self.global_env]);
-        match callee {
+        let result = match callee {
             Value::Function(Function::Builtin(func)) => func(&mut self.runtime, env, &args),
             Value::Function(Function::Lambda(handle)) => {
                 let closure = closure_ref(&self.runtime, handle).clone();
                 self.apply_closure(closure, &args)
             }
-            other => Err(EvalError::new(
-                EvalErrorKind::TypeMismatch {
-                    expected: "function",
-                    found: other.type_name(),
-                },
-                None,
-                "関数ではない値を呼び出しました",
-            )),
+            other => {
+                return Err(EvalError::new(
+                    EvalErrorKind::TypeMismatch {
+                        expected: "function",
+                        found: other.type_name(),
+                    },
+                    None,
+                    "関数ではない値を呼び出しました",
+                ))
+            }
+        };
+        result.map_err(|err| {
+            let name = list[0]
+                .as_symbol()
+                .and_then(|sym| self.runtime.resolve(sym))
+                .unwrap_or("<lambda>");
+            if self.debug_on_error {
+                err.with_frame(self.call_frame(name, &args))
+            } else {
+                err.with_frame(name)
+            }
+        })
+    }
+
+    /// バックトレース用の呼び出し式（文字列の引数は引用符付きで表示）
+    fn call_frame(&self, name: &str, args: &[Value]) -> String {
+        let mut frame = format!("({}", name);
+        for arg in args {
+            frame.push(' ');
+            match arg {
+                Value::String(_) => {
+                    frame.push_str(&format!("{:?}", value_to_string(&self.runtime, arg)))
+                }
+                _ => frame.push_str(&value_to_string(&self.runtime, arg)),
+            }
         }
+        frame.push(')');
+        frame
     }
 
     fn apply_closure(&mut self, closure: Closure, args: &[Value]) -> Result<Value, EvalError> {
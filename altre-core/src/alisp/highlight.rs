@@ -0,0 +1,113 @@
+//! alisp ソースのシンタックスハイライト
+//!
+//! リーダーと共有する字句走査（`reader::scan`）で、特殊形式・文字列・コメント・数値の範囲を求める
+
+use crate::alisp::indent::SPECIAL_FORMS;
+use crate::alisp::reader::{scan, LexemeKind};
+
+/// ハイライトの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    /// 特殊形式
+    Keyword,
+    /// 文字列リテラル
+    String,
+    /// コメント（`;` 行コメント・`#| |#` ブロックコメント・`#;` 式コメントの接頭辞）
+    Comment,
+    /// 数値
+    Number,
+}
+
+/// ハイライトする範囲（文字位置 `start..end`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SyntaxKind,
+}
+
+/// ソース全体のハイライト範囲を先頭から順に返す（範囲は重ならない）
+pub fn syntax_spans(source: &str) -> Vec<SyntaxSpan> {
+    let chars: Vec<char> = source.chars().collect();
+    scan(source)
+        .into_iter()
+        .filter_map(|lexeme| {
+            let kind = match lexeme.kind {
+                LexemeKind::String => SyntaxKind::String,
+                LexemeKind::LineComment | LexemeKind::BlockComment | LexemeKind::DatumComment => {
+                    SyntaxKind::Comment
+                }
+                LexemeKind::Atom => {
+                    let token: String = chars[lexeme.start..lexeme.end].iter().collect();
+                    token_kind(&token)?
+                }
+                _ => return None,
+            };
+            Some(SyntaxSpan {
+                start: lexeme.start,
+                end: lexeme.end,
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn token_kind(token: &str) -> Option<SyntaxKind> {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(SyntaxKind::Number)
+    } else if SPECIAL_FORMS.iter().any(|(name, _)| *name == token) {
+        Some(SyntaxKind::Keyword)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(String, SyntaxKind)> {
+        let chars: Vec<char> = source.chars().collect();
+        syntax_spans(source)
+            .into_iter()
+            .map(|span| (chars[span.start..span.end].iter().collect(), span.kind))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_keywords_strings_comments_and_numbers() {
+        assert_eq!(
+            kinds("(define x \"a\\\"b\") ; note\n(if (< x -1) 2.5 #| c #| d |# |#)"),
+            vec![
+                ("define".to_string(), SyntaxKind::Keyword),
+                ("\"a\\\"b\"".to_string(), SyntaxKind::String),
+                ("; note".to_string(), SyntaxKind::Comment),
+                ("if".to_string(), SyntaxKind::Keyword),
+                ("-1".to_string(), SyntaxKind::Number),
+                ("2.5".to_string(), SyntaxKind::Number),
+                ("#| c #| d |# |#".to_string(), SyntaxKind::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn datum_comment_prefix_does_not_hide_the_rest_of_the_line() {
+        assert_eq!(
+            kinds("#;(if 1) \"s\""),
+            vec![
+                ("#;".to_string(), SyntaxKind::Comment),
+                ("if".to_string(), SyntaxKind::Keyword),
+                ("1".to_string(), SyntaxKind::Number),
+                ("\"s\"".to_string(), SyntaxKind::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyword_inside_longer_symbol_is_not_highlighted() {
+        assert!(kinds("(define-key iffy)")
+            .iter()
+            .all(|(_, kind)| *kind != SyntaxKind::Keyword));
+    }
+}
@@ -0,0 +1,228 @@
+//! alisp ソースのインデント計算
+//!
+//! 行頭を囲む式の構造から、その行を字下げすべき桁を求める
+
+use crate::alisp::reader::{scan, LexemeKind};
+use std::path::Path;
+
+/// 特殊形式と、本体を2桁下げする場合の本体に先立つ引数の個数
+///
+/// `None` の形式は関数呼び出しと同じく引数に揃える。シンタックスハイライトもこの一覧を使う。
+pub const SPECIAL_FORMS: &[(&str, Option<usize>)] = &[
+    ("define", Some(1)),
+    ("lambda", Some(1)),
+    ("let", Some(1)),
+    ("when", Some(1)),
+    ("unless", Some(1)),
+    ("begin", Some(0)),
+    ("cond", Some(0)),
+    ("if", None),
+    ("set!", None),
+    ("and", None),
+    ("or", None),
+    ("load", None),
+    ("quote", None),
+];
+
+/// 本体のインデント幅
+const BODY_INDENT: usize = 2;
+/// 本体より前の引数を改行した場合のインデント幅
+const DISTINGUISHED_INDENT: usize = 4;
+
+/// alisp のソースファイルか（拡張子 `.al`）
+pub fn is_alisp_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "al")
+}
+
+/// 開き括弧ごとの解析状態
+#[derive(Debug, Clone)]
+struct OpenForm {
+    /// 開き括弧の桁
+    column: usize,
+    /// 先頭要素のシンボル名（シンボル以外なら `None`）
+    head: Option<String>,
+    /// 読み終えた要素数
+    elements: usize,
+    /// 先頭要素と同じ行にある2番目の要素の桁
+    first_arg_column: Option<usize>,
+    /// 開き括弧の行
+    line: usize,
+    /// 外側の要素としての開始桁（クォート接頭辞を含む）
+    element_column: usize,
+    /// `#;` で読み飛ばされる式か
+    commented: bool,
+    /// 開き括弧の時点で残っていた外側の `#;` の数
+    outer_skips: usize,
+}
+
+/// 指定行（0始まり）の適切なインデント桁を計算
+///
+/// リーダーと共有する字句走査で区切るので、文字列・コメントの中の括弧は数えない。
+pub fn indent_column(text: &str, line: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(
+            chars
+                .iter()
+                .enumerate()
+                .filter(|(_, &ch)| ch == '\n')
+                .map(|(index, _)| index + 1),
+        )
+        .collect();
+    let target = line_starts.get(line).copied().unwrap_or(usize::MAX);
+    let position = |offset: usize| {
+        let line = line_starts.partition_point(|&start| start <= offset) - 1;
+        (line, offset - line_starts[line])
+    };
+
+    let mut stack: Vec<OpenForm> = Vec::new();
+    let mut quote: Option<usize> = None;
+    let mut skips = 0usize;
+
+    for lexeme in scan(text) {
+        if lexeme.start >= target {
+            break;
+        }
+        let (current_line, column) = position(lexeme.start);
+        let starts_element = matches!(
+            lexeme.kind,
+            LexemeKind::Open(_) | LexemeKind::Atom | LexemeKind::String
+        );
+        let quoted = starts_element && quote.is_some();
+        let element_column = if starts_element {
+            quote.take().unwrap_or(column)
+        } else {
+            column
+        };
+        let commented = starts_element && skips > 0;
+        if commented {
+            skips -= 1;
+        }
+
+        match lexeme.kind {
+            LexemeKind::Open(_) => {
+                stack.push(OpenForm {
+                    column,
+                    head: None,
+                    elements: 0,
+                    first_arg_column: None,
+                    line: current_line,
+                    element_column,
+                    commented,
+                    outer_skips: skips,
+                });
+                skips = 0;
+            }
+            LexemeKind::Close(_) => {
+                if let Some(form) = stack.pop() {
+                    skips = form.outer_skips;
+                    if !form.commented {
+                        finish_element(&mut stack, None, form.element_column, form.line);
+                    }
+                }
+            }
+            LexemeKind::Atom if !commented => {
+                let name: String = chars[lexeme.start..lexeme.end].iter().collect();
+                let symbol = (!quoted).then_some(name);
+                finish_element(&mut stack, symbol, element_column, current_line);
+            }
+            LexemeKind::String if !commented => {
+                finish_element(&mut stack, None, element_column, current_line);
+            }
+            LexemeKind::Quote => quote = quote.or(Some(column)),
+            LexemeKind::DatumComment => skips += 1,
+            _ => {}
+        }
+    }
+
+    let Some(form) = stack.last() else {
+        return 0;
+    };
+    let Some(head) = form.head.as_deref() else {
+        // 先頭がシンボルでない（束縛リストなど）場合は先頭要素に揃える
+        return form.column + 1;
+    };
+
+    if let Some(&(_, Some(distinguished))) = SPECIAL_FORMS.iter().find(|(name, _)| *name == head) {
+        let args_read = form.elements.saturating_sub(1);
+        return if args_read < distinguished {
+            form.column + DISTINGUISHED_INDENT
+        } else {
+            form.column + BODY_INDENT
+        };
+    }
+
+    form.first_arg_column.unwrap_or(form.column + 1)
+}
+
+/// 要素を読み終えたことを直近の開き括弧へ記録する
+fn finish_element(stack: &mut [OpenForm], symbol: Option<String>, column: usize, line: usize) {
+    let Some(form) = stack.last_mut() else {
+        return;
+    };
+    match form.elements {
+        0 => form.head = symbol.filter(|name| !is_number(name)),
+        1 if line == form.line => form.first_arg_column = Some(column),
+        _ => {}
+    }
+    form.elements += 1;
+}
+
+fn is_number(token: &str) -> bool {
+    token.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_level_lines_are_not_indented() {
+        assert_eq!(indent_column("(foo)\nbar", 1), 0);
+        assert_eq!(indent_column("", 0), 0);
+    }
+
+    #[test]
+    fn function_calls_align_with_first_argument() {
+        assert_eq!(indent_column("(foo bar\nbaz)", 1), 5);
+        assert_eq!(indent_column("(foo\nbar)", 1), 1);
+        assert_eq!(indent_column("  (message \"a\"\n\"b\")", 1), 11);
+    }
+
+    #[test]
+    fn special_forms_indent_body() {
+        assert_eq!(indent_column("(define (f x)\n(+ x 1))", 1), 2);
+        assert_eq!(indent_column("(let ((a 1)\n(b 2))\na)", 1), 6);
+        assert_eq!(indent_column("(let ((a 1))\na)", 1), 2);
+        assert_eq!(indent_column("(lambda\n(x) x)", 1), 4);
+        assert_eq!(indent_column("(cond\n(a b))", 1), 2);
+    }
+
+    #[test]
+    fn strings_and_comments_are_skipped() {
+        assert_eq!(indent_column("(foo \"(\" ; )\nbar)", 1), 5);
+        assert_eq!(indent_column("(define x ; (\n1)", 1), 2);
+        assert_eq!(indent_column("(foo #| ( |#\nbar)", 1), 1);
+        assert_eq!(indent_column("(foo #| #| ) |# ( |# bar\nbaz)", 1), 21);
+        assert_eq!(indent_column("(define #| (\n |# x\n1)", 2), 2);
+    }
+
+    #[test]
+    fn datum_comments_do_not_count_as_elements() {
+        assert_eq!(indent_column("(foo #;(a b) bar\nbaz)", 1), 13);
+        assert_eq!(indent_column("(define #;x y\n1)", 1), 2);
+        assert_eq!(indent_column("(let #; #;(a) b\n((c 1)) c)", 1), 4);
+    }
+
+    #[test]
+    fn quote_prefix_belongs_to_its_element() {
+        assert_eq!(indent_column("(foo '(a b)\nbar)", 1), 5);
+        assert_eq!(indent_column("('define x\ny)", 1), 1);
+    }
+
+    #[test]
+    fn alisp_paths_are_detected_by_extension() {
+        assert!(is_alisp_path(Path::new("init.al")));
+        assert!(!is_alisp_path(Path::new("main.rs")));
+    }
+}
@@ -6,24 +6,33 @@ pub struct MinibufferOutcome {
     pub output: String,
     pub messages: Vec<String>,
     pub is_error: bool,
+    /// エラーが通過した呼び出し（内側から順）。debug-on-error が無効なら空
+    pub backtrace: Vec<String>,
 }
 
 pub fn eval_in_minibuffer(interpreter: &mut Interpreter, source: &str) -> MinibufferOutcome {
-    match interpreter.eval(source) {
-        Ok(EvalOutcome {
-            display, messages, ..
-        }) => MinibufferOutcome {
+    let EvalOutcome {
+        display,
+        messages,
+        error,
+        ..
+    } = interpreter.eval_outcome(source);
+    match error {
+        None => MinibufferOutcome {
             output: format!("=> {}", display),
             messages,
             is_error: false,
+            backtrace: Vec::new(),
+        },
+        Some(err) => MinibufferOutcome {
+            output: format_eval_error(&err, &interpreter.runtime().interner),
+            messages,
+            is_error: true,
+            backtrace: if interpreter.debug_on_error() {
+                err.backtrace
+            } else {
+                Vec::new()
+            },
         },
-        Err(err) => {
-            let message = format_eval_error(&err, &interpreter.runtime().interner);
-            MinibufferOutcome {
-                output: message,
-                messages: Vec::new(),
-                is_error: true,
-            }
-        }
     }
 }
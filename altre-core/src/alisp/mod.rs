@@ -3,13 +3,17 @@
 mod ast;
 pub mod error;
 mod evaluator;
+pub mod highlight;
+pub mod indent;
 pub mod integration;
 mod primitives;
 pub mod reader;
 mod runtime;
+pub mod sexp;
 mod symbol;
 
 pub use error::{EvalError, ReaderError};
-pub use evaluator::{EvalOutcome, Interpreter};
-pub use runtime::HostBridge;
+pub use evaluator::{EvalOutcome, FunctionHelp, Interpreter};
+pub use primitives::{Arity, BuiltinDoc};
+pub use runtime::{HostBridge, OptionValue};
 pub use symbol::{SymbolId, SymbolInterner};
@@ -1,7 +1,36 @@
 use crate::alisp::error::{EvalError, EvalErrorKind};
 use crate::alisp::runtime::EnvHandle;
-use crate::alisp::runtime::{define_symbol, value_to_string, Function, RuntimeState, Value};
+use crate::alisp::runtime::{
+    define_symbol, value_to_string, Function, OptionValue, RuntimeState, Value,
+};
 use crate::alisp::symbol::SymbolId;
+use std::collections::HashMap;
+use std::fmt;
+
+/// 関数が受け取る引数の個数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// ちょうど n 個
+    Exact(usize),
+    /// n 個以上
+    AtLeast(usize),
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::AtLeast(n) => write!(f, "{} 以上", n),
+        }
+    }
+}
+
+/// 組込み関数の説明（describe-function で表示する）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinDoc {
+    pub arity: Arity,
+    pub doc: &'static str,
+}
 
 /// インタプリタ初期化時に登録した組込み関数のシンボルを保持する。
 /// 現在は再利用シナリオが未実装のため未参照だが、将来的に再バインドや
@@ -21,46 +50,150 @@ pub struct PrimitiveRegistry {
     pub floor: SymbolId,
     pub ceil: SymbolId,
     pub not: SymbolId,
+    pub null_p: SymbolId,
     pub print: SymbolId,
     pub type_of: SymbolId,
     pub string_append: SymbolId,
     pub string_length: SymbolId,
     pub bind_key: SymbolId,
     pub set_gui_color: SymbolId,
+    pub set_option: SymbolId,
+    docs: HashMap<SymbolId, BuiltinDoc>,
 }
 
 impl PrimitiveRegistry {
     pub fn install(runtime: &mut RuntimeState, env: EnvHandle) -> Self {
+        let mut docs = HashMap::new();
         macro_rules! register {
-            ($name:expr, $func:expr) => {{
+            ($name:expr, $func:expr, $arity:expr, $doc:expr) => {{
                 let sym = runtime.intern($name);
                 define_symbol(runtime, env, sym, Value::Function(Function::Builtin($func)));
+                docs.insert(
+                    sym,
+                    BuiltinDoc {
+                        arity: $arity,
+                        doc: $doc,
+                    },
+                );
                 sym
             }};
         }
 
         Self {
-            add: register!("+", numeric_add),
-            sub: register!("-", numeric_sub),
-            mul: register!("*", numeric_mul),
-            div: register!("/", numeric_div),
-            eq: register!("=", numeric_eq),
-            lt: register!("<", numeric_lt),
-            lte: register!("<=", numeric_lte),
-            gt: register!(">", numeric_gt),
-            gte: register!(">=", numeric_gte),
-            abs: register!("abs", numeric_abs),
-            floor: register!("floor", numeric_floor),
-            ceil: register!("ceil", numeric_ceil),
-            not: register!("not", boolean_not),
-            print: register!("print", primitive_print),
-            type_of: register!("type-of", primitive_type_of),
-            string_append: register!("string-append", primitive_string_append),
-            string_length: register!("string-length", primitive_string_length),
-            bind_key: register!("bind-key", primitive_bind_key),
-            set_gui_color: register!("set-gui-color", primitive_set_gui_color),
+            add: register!("+", numeric_add, Arity::AtLeast(1), "数値を加算する"),
+            sub: register!(
+                "-",
+                numeric_sub,
+                Arity::AtLeast(1),
+                "数値を減算する。引数が1つなら符号を反転する"
+            ),
+            mul: register!("*", numeric_mul, Arity::AtLeast(1), "数値を乗算する"),
+            div: register!(
+                "/",
+                numeric_div,
+                Arity::AtLeast(1),
+                "数値を除算する。0 による除算はエラー"
+            ),
+            eq: register!(
+                "=",
+                numeric_eq,
+                Arity::Exact(2),
+                "2つの数値が等しければ #t を返す"
+            ),
+            lt: register!(
+                "<",
+                numeric_lt,
+                Arity::Exact(2),
+                "第1引数が第2引数より小さければ #t を返す"
+            ),
+            lte: register!(
+                "<=",
+                numeric_lte,
+                Arity::Exact(2),
+                "第1引数が第2引数以下なら #t を返す"
+            ),
+            gt: register!(
+                ">",
+                numeric_gt,
+                Arity::Exact(2),
+                "第1引数が第2引数より大きければ #t を返す"
+            ),
+            gte: register!(
+                ">=",
+                numeric_gte,
+                Arity::Exact(2),
+                "第1引数が第2引数以上なら #t を返す"
+            ),
+            abs: register!("abs", numeric_abs, Arity::Exact(1), "数値の絶対値を返す"),
+            floor: register!(
+                "floor",
+                numeric_floor,
+                Arity::Exact(1),
+                "小数点以下を切り捨てる"
+            ),
+            ceil: register!(
+                "ceil",
+                numeric_ceil,
+                Arity::Exact(1),
+                "小数点以下を切り上げる"
+            ),
+            not: register!("not", boolean_not, Arity::Exact(1), "真偽値を反転する"),
+            null_p: register!(
+                "null?",
+                primitive_null_p,
+                Arity::Exact(1),
+                "空リスト（nil / ()）なら #t を返す"
+            ),
+            print: register!(
+                "print",
+                primitive_print,
+                Arity::Exact(1),
+                "値を表示して () を返す"
+            ),
+            type_of: register!(
+                "type-of",
+                primitive_type_of,
+                Arity::Exact(1),
+                "値の型名を文字列で返す"
+            ),
+            string_append: register!(
+                "string-append",
+                primitive_string_append,
+                Arity::AtLeast(1),
+                "文字列を連結する"
+            ),
+            string_length: register!(
+                "string-length",
+                primitive_string_length,
+                Arity::Exact(1),
+                "文字列の長さ（Unicode スカラ値の数）を返す"
+            ),
+            bind_key: register!(
+                "bind-key",
+                primitive_bind_key,
+                Arity::Exact(2),
+                "キーシーケンスにコマンドを割り当てる"
+            ),
+            set_gui_color: register!(
+                "set-gui-color",
+                primitive_set_gui_color,
+                Arity::Exact(2),
+                "GUI の配色項目に色を設定する"
+            ),
+            set_option: register!(
+                "set-option",
+                primitive_set_option,
+                Arity::Exact(2),
+                "エディタの設定項目に値を設定する"
+            ),
+            docs,
         }
     }
+
+    /// 組込み関数の説明
+    pub fn doc(&self, symbol: SymbolId) -> Option<&BuiltinDoc> {
+        self.docs.get(&symbol)
+    }
 }
 
 fn ensure_arity(args: &[Value], expected: usize) -> Result<(), EvalError> {
@@ -359,6 +492,16 @@ fn primitive_print(
     Ok(Value::Unit)
 }
 
+/// 空リスト（`nil` / `()`）なら #t を返す
+fn primitive_null_p(
+    _runtime: &mut RuntimeState,
+    _env: EnvHandle,
+    args: &[Value],
+) -> Result<Value, EvalError> {
+    ensure_arity(args, 1)?;
+    Ok(Value::Boolean(matches!(args[0], Value::Nil)))
+}
+
 fn primitive_type_of(
     runtime: &mut RuntimeState,
     _env: EnvHandle,
@@ -435,3 +578,39 @@ fn primitive_set_gui_color(
 
     Ok(Value::Unit)
 }
+
+fn primitive_set_option(
+    runtime: &mut RuntimeState,
+    _env: EnvHandle,
+    args: &[Value],
+) -> Result<Value, EvalError> {
+    ensure_arity(args, 2)?;
+    let name = expect_string(runtime, &args[0])?.to_string();
+    let value = match &args[1] {
+        Value::Boolean(b) => OptionValue::Boolean(*b),
+        Value::Integer(i) => OptionValue::Integer(*i),
+        Value::String(handle) => OptionValue::String(runtime.heap.string_ref(*handle).to_string()),
+        other => {
+            return Err(EvalError::new(
+                EvalErrorKind::TypeMismatch {
+                    expected: "boolean, integer or string",
+                    found: other.type_name(),
+                },
+                None,
+                "設定値には真偽値・整数・文字列を指定してください",
+            ))
+        }
+    };
+    let host = runtime.host_mut().ok_or_else(|| {
+        EvalError::new(
+            EvalErrorKind::Runtime("ホストが未設定です".into()),
+            None,
+            "ホストが未設定です",
+        )
+    })?;
+
+    host.set_option(&name, &value)
+        .map_err(|msg| EvalError::new(EvalErrorKind::Runtime(msg.clone()), None, msg))?;
+
+    Ok(Value::Unit)
+}
@@ -2,6 +2,10 @@ use crate::alisp::ast::Expr;
 use crate::alisp::error::{ReaderError, ReaderErrorKind, SourceLocation, SourceSpan};
 use crate::alisp::symbol::SymbolInterner;
 
+mod scan;
+
+pub use scan::{scan, Lexeme, LexemeKind};
+
 #[derive(Debug)]
 pub struct Reader {
     chars: Vec<char>,
@@ -23,7 +27,7 @@ impl Reader {
     pub fn parse(mut self, interner: &mut SymbolInterner) -> Result<Vec<Expr>, ReaderError> {
         let mut forms = Vec::new();
         loop {
-            self.skip_whitespace_and_comments();
+            self.skip_whitespace_and_comments(interner)?;
             if self.is_eof() {
                 break;
             }
@@ -33,13 +37,15 @@ impl Reader {
     }
 
     fn read_form(&mut self, interner: &mut SymbolInterner) -> Result<Expr, ReaderError> {
-        self.skip_whitespace_and_comments();
+        self.skip_whitespace_and_comments(interner)?;
         let ch = self.peek_char().ok_or_else(|| {
             self.error_here(ReaderErrorKind::UnexpectedEof, "入力が途中で終了しました")
         })?;
         if ch == '(' {
             self.consume_char();
             self.read_list(interner)
+        } else if ch == '\'' {
+            self.read_quote(interner)
         } else if ch == '"' {
             self.read_string()
         } else if ch == '#' {
@@ -56,7 +62,7 @@ impl Reader {
     fn read_list(&mut self, interner: &mut SymbolInterner) -> Result<Expr, ReaderError> {
         let mut elements = Vec::new();
         loop {
-            self.skip_whitespace_and_comments();
+            self.skip_whitespace_and_comments(interner)?;
             match self.peek_char() {
                 Some(')') => {
                     self.consume_char();
@@ -76,6 +82,30 @@ impl Reader {
         Ok(Expr::List(elements))
     }
 
+    /// `'x` を `(quote x)` として読む
+    fn read_quote(&mut self, interner: &mut SymbolInterner) -> Result<Expr, ReaderError> {
+        let start = self.current_location();
+        self.consume_char(); // '\''
+        self.skip_whitespace_and_comments(interner)?;
+        match self.peek_char() {
+            None => Err(self.error_at(
+                start,
+                ReaderErrorKind::UnexpectedEof,
+                "' の後に式がありません",
+            )),
+            Some(')') => Err(self.error_at(
+                start,
+                ReaderErrorKind::UnexpectedChar(')'),
+                "' の後に式がありません",
+            )),
+            Some(_) => {
+                let quoted = self.read_form(interner)?;
+                let quote = interner.intern("quote");
+                Ok(Expr::List(vec![Expr::Symbol(quote), quoted]))
+            }
+        }
+    }
+
     fn read_string(&mut self) -> Result<Expr, ReaderError> {
         let start = self.current_location();
         self.consume_char(); // opening quote
@@ -239,17 +269,17 @@ impl Reader {
             ));
         }
         if buf == "nil" {
-            return Err(self.error_at(
-                start,
-                ReaderErrorKind::UnexpectedToken(buf),
-                "nil は v0 では利用できません",
-            ));
+            // nil は空リスト `()` と同じ値として読む
+            return Ok(Expr::List(Vec::new()));
         }
         let id = interner.intern(&buf);
         Ok(Expr::Symbol(id))
     }
 
-    fn skip_whitespace_and_comments(&mut self) -> bool {
+    fn skip_whitespace_and_comments(
+        &mut self,
+        interner: &mut SymbolInterner,
+    ) -> Result<bool, ReaderError> {
         let mut progressed = false;
         loop {
             while let Some(ch) = self.peek_char() {
@@ -271,9 +301,69 @@ impl Reader {
                 }
                 continue;
             }
+            if self.peek_char() == Some('#') && self.peek_next() == Some('|') {
+                progressed = true;
+                self.skip_block_comment()?;
+                continue;
+            }
+            if self.peek_char() == Some('#') && self.peek_next() == Some(';') {
+                progressed = true;
+                self.skip_datum_comment(interner)?;
+                continue;
+            }
             break;
         }
-        progressed
+        Ok(progressed)
+    }
+
+    /// `#;` に続く1つの式を読み捨てる
+    fn skip_datum_comment(&mut self, interner: &mut SymbolInterner) -> Result<(), ReaderError> {
+        let start = self.current_location();
+        self.consume_char(); // '#'
+        self.consume_char(); // ';'
+        self.skip_whitespace_and_comments(interner)?;
+        match self.peek_char() {
+            None => Err(self.error_at(
+                start,
+                ReaderErrorKind::UnexpectedEof,
+                "#; の後に式がありません",
+            )),
+            Some(')') => Err(self.error_at(
+                start,
+                ReaderErrorKind::UnexpectedChar(')'),
+                "#; の後に式がありません",
+            )),
+            Some(_) => self.read_form(interner).map(|_| ()),
+        }
+    }
+
+    /// `#| ... |#` のブロックコメントを読み飛ばす（入れ子に対応）
+    fn skip_block_comment(&mut self) -> Result<(), ReaderError> {
+        let start = self.current_location();
+        self.consume_char(); // '#'
+        self.consume_char(); // '|'
+        let mut depth = 1usize;
+        while let Some(ch) = self.consume_char() {
+            match (ch, self.peek_char()) {
+                ('#', Some('|')) => {
+                    self.consume_char();
+                    depth += 1;
+                }
+                ('|', Some('#')) => {
+                    self.consume_char();
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error_at(
+            start,
+            ReaderErrorKind::UnterminatedComment,
+            "ブロックコメントが閉じられていません",
+        ))
     }
 
     fn consume_char(&mut self) -> Option<char> {
@@ -333,3 +423,145 @@ fn is_symbol_char(ch: char) -> bool {
 pub fn parse(source: &str, interner: &mut SymbolInterner) -> Result<Vec<Expr>, ReaderError> {
     Reader::new(source).parse(interner)
 }
+
+/// ソース上の位置の字句的な文脈
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxContext {
+    /// 通常のコード
+    Code,
+    /// 文字列リテラルの内側
+    String,
+    /// コメント（`;` 行コメント・`#| |#` ブロックコメント）の内側
+    Comment,
+}
+
+/// `offset` 文字目の位置が文字列・コメントの内側かを判定
+///
+/// [`scan`] の字句規則（エスケープ、入れ子のブロックコメント、`#;` 式コメント）で判定する。
+pub fn syntax_context_at(source: &str, offset: usize) -> SyntaxContext {
+    for lexeme in scan(source) {
+        if lexeme.start >= offset {
+            break;
+        }
+        // 行コメントは改行の手前まで、文字列とブロックコメントは閉じる区切りの手前まで
+        let inside = match lexeme.kind {
+            LexemeKind::LineComment => offset <= lexeme.end,
+            LexemeKind::String | LexemeKind::BlockComment => {
+                offset < lexeme.end || !lexeme.terminated
+            }
+            _ => false,
+        };
+        if inside {
+            return match lexeme.kind {
+                LexemeKind::String => SyntaxContext::String,
+                _ => SyntaxContext::Comment,
+            };
+        }
+    }
+    SyntaxContext::Code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(source: &str) -> Result<Vec<Expr>, ReaderError> {
+        let mut interner = SymbolInterner::new();
+        parse(source, &mut interner)
+    }
+
+    #[test]
+    fn nil_and_empty_list_read_as_empty_list() {
+        let forms = read("nil ()").unwrap();
+        assert_eq!(forms, vec![Expr::List(Vec::new()), Expr::List(Vec::new())]);
+    }
+
+    #[test]
+    fn quote_prefix_reads_as_quote_form() {
+        let mut interner = SymbolInterner::new();
+        let forms = parse("'()", &mut interner).unwrap();
+        let quote = interner.intern("quote");
+        assert_eq!(
+            forms,
+            vec![Expr::List(vec![
+                Expr::Symbol(quote),
+                Expr::List(Vec::new())
+            ])]
+        );
+        assert!(parse("'", &mut interner).is_err());
+    }
+
+    #[test]
+    fn block_comment_is_skipped() {
+        let forms = read("#| comment (not read) |# 1 #|x|#2").unwrap();
+        assert_eq!(forms, vec![Expr::Integer(1), Expr::Integer(2)]);
+    }
+
+    #[test]
+    fn nested_block_comment_is_skipped() {
+        let forms = read("(1 #| outer #| inner |# still outer |# 2)").unwrap();
+        assert_eq!(
+            forms,
+            vec![Expr::List(vec![Expr::Integer(1), Expr::Integer(2)])]
+        );
+    }
+
+    #[test]
+    fn datum_comment_skips_next_form() {
+        let mut interner = SymbolInterner::new();
+        let forms = parse("(a #;b c)", &mut interner).unwrap();
+        let a = interner.intern("a");
+        let c = interner.intern("c");
+        assert_eq!(
+            forms,
+            vec![Expr::List(vec![Expr::Symbol(a), Expr::Symbol(c)])]
+        );
+    }
+
+    #[test]
+    fn nested_datum_comments_skip_forms() {
+        let forms = read("#;(1 2) 3 #; #;4 5 6 (7 #;(8 #;9) 10)").unwrap();
+        assert_eq!(
+            forms,
+            vec![
+                Expr::Integer(3),
+                Expr::Integer(6),
+                Expr::List(vec![Expr::Integer(7), Expr::Integer(10)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn datum_comment_without_form_is_error() {
+        let err = read("1 #;").unwrap_err();
+        assert_eq!(err.kind, ReaderErrorKind::UnexpectedEof);
+        assert!(read("(1 #;)").is_err());
+    }
+
+    #[test]
+    fn unterminated_block_comment_reports_opening_span() {
+        let err = read("1\n  #| open #| inner |#").unwrap_err();
+        assert_eq!(err.kind, ReaderErrorKind::UnterminatedComment);
+        assert_eq!(err.span.start, SourceLocation::new(2, 3));
+    }
+
+    #[test]
+    fn syntax_context_tracks_strings_and_comments() {
+        let source = "(a \"b\\\"c\" d) ; e\n#| f #| g |# h |# i";
+        let context = |needle: &str| {
+            let byte = source.find(needle).unwrap();
+            syntax_context_at(source, source[..byte].chars().count())
+        };
+        assert_eq!(context("a"), SyntaxContext::Code);
+        assert_eq!(context("b"), SyntaxContext::String);
+        assert_eq!(context("c"), SyntaxContext::String);
+        assert_eq!(context("d"), SyntaxContext::Code);
+        assert_eq!(context("e"), SyntaxContext::Comment);
+        assert_eq!(context("#|"), SyntaxContext::Code);
+        assert_eq!(context("f"), SyntaxContext::Comment);
+        assert_eq!(context("h"), SyntaxContext::Comment);
+        assert_eq!(context("i"), SyntaxContext::Code);
+        assert_eq!(syntax_context_at("\"open", 100), SyntaxContext::String);
+        assert_eq!(syntax_context_at("#;(a)", 3), SyntaxContext::Code);
+    }
+}
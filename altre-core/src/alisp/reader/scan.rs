@@ -0,0 +1,203 @@
+//! エディタ向けの字句走査
+//!
+//! 括弧の補完・インデント・ハイライト・文脈判定が共有する。
+//! 文字列のエスケープ、`;` 行コメント、入れ子の `#| |#` ブロックコメント、
+//! `#;` 式コメントをリーダーと同じ規則で区切り、読めない入力でも末尾まで走査する。
+
+/// 字句の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexemeKind {
+    /// 開き括弧（`(` `[` `{`）
+    Open(char),
+    /// 閉じ括弧（`)` `]` `}`）
+    Close(char),
+    /// クォート接頭辞 `'`
+    Quote,
+    /// 式コメント接頭辞 `#;`（続く1式を読み飛ばす）
+    DatumComment,
+    /// シンボル・数値などの区切りまでの並び
+    Atom,
+    /// 文字列リテラル（両端の `"` を含む）
+    String,
+    /// `;` から行末の手前まで
+    LineComment,
+    /// `#|` から対応する `|#` まで
+    BlockComment,
+}
+
+/// 字句の範囲（文字位置 `start..end`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lexeme {
+    pub kind: LexemeKind,
+    pub start: usize,
+    pub end: usize,
+    /// 文字列・ブロックコメントが閉じているか（それ以外は常に `true`）
+    pub terminated: bool,
+}
+
+/// ソース全体を先頭から字句に区切る（空白は含まない）
+pub fn scan(source: &str) -> Vec<Lexeme> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut lexemes = Vec::new();
+    let mut index = 0usize;
+
+    while index < chars.len() {
+        let start = index;
+        let ch = chars[index];
+        let next = chars.get(index + 1).copied();
+        let mut terminated = true;
+        let kind = match (ch, next) {
+            _ if ch.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            ('(' | '[' | '{', _) => {
+                index += 1;
+                LexemeKind::Open(ch)
+            }
+            (')' | ']' | '}', _) => {
+                index += 1;
+                LexemeKind::Close(ch)
+            }
+            ('\'', _) => {
+                index += 1;
+                LexemeKind::Quote
+            }
+            ('"', _) => {
+                (index, terminated) = string_end(&chars, index);
+                LexemeKind::String
+            }
+            (';', _) => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                LexemeKind::LineComment
+            }
+            ('#', Some('|')) => {
+                (index, terminated) = block_comment_end(&chars, index);
+                LexemeKind::BlockComment
+            }
+            ('#', Some(';')) => {
+                index += 2;
+                LexemeKind::DatumComment
+            }
+            _ => {
+                index += 1;
+                while index < chars.len() && !is_delimiter(chars[index]) {
+                    index += 1;
+                }
+                LexemeKind::Atom
+            }
+        };
+        lexemes.push(Lexeme {
+            kind,
+            start,
+            end: index,
+            terminated,
+        });
+    }
+    lexemes
+}
+
+fn is_delimiter(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, '(' | ')' | '[' | ']' | '{' | '}' | '\'' | '"' | ';')
+}
+
+/// `start` の `"` から閉じる `"` の直後まで（閉じていなければ末尾まで）
+fn string_end(chars: &[char], start: usize) -> (usize, bool) {
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index] {
+            '\\' => index += 2,
+            '"' => return (index + 1, true),
+            _ => index += 1,
+        }
+    }
+    (chars.len(), false)
+}
+
+/// `start` の `#|` から入れ子を数えて対応する `|#` の直後まで（閉じていなければ末尾まで）
+fn block_comment_end(chars: &[char], start: usize) -> (usize, bool) {
+    let mut depth = 0usize;
+    let mut index = start;
+    while index < chars.len() {
+        match (chars[index], chars.get(index + 1).copied()) {
+            ('#', Some('|')) => {
+                depth += 1;
+                index += 2;
+            }
+            ('|', Some('#')) => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    return (index, true);
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    (chars.len(), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(String, LexemeKind)> {
+        let chars: Vec<char> = source.chars().collect();
+        scan(source)
+            .into_iter()
+            .map(|lexeme| {
+                (
+                    chars[lexeme.start..lexeme.end].iter().collect(),
+                    lexeme.kind,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_brackets_atoms_strings_and_comments() {
+        assert_eq!(
+            kinds("(a 'b \"c\\\"(\") ; d (\n#| e #| ( |# |#[1]"),
+            vec![
+                ("(".to_string(), LexemeKind::Open('(')),
+                ("a".to_string(), LexemeKind::Atom),
+                ("'".to_string(), LexemeKind::Quote),
+                ("b".to_string(), LexemeKind::Atom),
+                ("\"c\\\"(\"".to_string(), LexemeKind::String),
+                (")".to_string(), LexemeKind::Close(')')),
+                ("; d (".to_string(), LexemeKind::LineComment),
+                ("#| e #| ( |# |#".to_string(), LexemeKind::BlockComment),
+                ("[".to_string(), LexemeKind::Open('[')),
+                ("1".to_string(), LexemeKind::Atom),
+                ("]".to_string(), LexemeKind::Close(']')),
+            ]
+        );
+    }
+
+    #[test]
+    fn datum_comment_prefix_is_not_a_line_comment() {
+        assert_eq!(
+            kinds("#;(a) b"),
+            vec![
+                ("#;".to_string(), LexemeKind::DatumComment),
+                ("(".to_string(), LexemeKind::Open('(')),
+                ("a".to_string(), LexemeKind::Atom),
+                (")".to_string(), LexemeKind::Close(')')),
+                ("b".to_string(), LexemeKind::Atom),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_and_block_comment_run_to_end() {
+        let lexemes = scan("\"open #| (");
+        assert_eq!(lexemes.len(), 1);
+        assert_eq!(lexemes[0].end, 10);
+        assert!(!lexemes[0].terminated);
+        let lexemes = scan("#| a #| b |#");
+        assert_eq!(lexemes[0].kind, LexemeKind::BlockComment);
+        assert!(!lexemes[0].terminated);
+    }
+}
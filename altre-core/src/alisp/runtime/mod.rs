@@ -1,6 +1,7 @@
 use crate::alisp::ast::Expr;
 use crate::alisp::error::{EvalError, EvalErrorKind};
 use crate::alisp::symbol::{SymbolId, SymbolInterner};
+use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct StringHandle(usize);
@@ -19,6 +20,8 @@ pub enum Value {
     String(StringHandle),
     Function(Function),
     Unit,
+    /// 空リスト（`nil` / `()`）。副作用のみの結果を表す `Unit` とは区別する
+    Nil,
 }
 
 impl Value {
@@ -30,6 +33,7 @@ impl Value {
             Value::String(_) => "string",
             Value::Function(_) => "function",
             Value::Unit => "unit",
+            Value::Nil => "list",
         }
     }
 
@@ -47,7 +51,7 @@ impl std::fmt::Debug for Value {
             Value::Boolean(false) => write!(f, "#f"),
             Value::String(_) => write!(f, "<string>"),
             Value::Function(func) => write!(f, "{:?}", func),
-            Value::Unit => write!(f, "()"),
+            Value::Unit | Value::Nil => write!(f, "()"),
         }
     }
 }
@@ -65,6 +69,8 @@ pub struct Closure {
     pub params: Vec<SymbolId>,
     pub body: Vec<Expr>,
     pub env: EnvHandle,
+    /// 本体の先頭に置いた文字列（後に式が続く場合のみ）
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -91,6 +97,8 @@ pub struct GcHeap {
     entries: Vec<Option<HeapEntry>>,
     allocated: usize,
     next_gc_threshold: usize,
+    intern_strings: bool,
+    interned: HashMap<String, StringHandle>,
 }
 
 impl GcHeap {
@@ -99,9 +107,36 @@ impl GcHeap {
             entries: Vec::new(),
             allocated: 0,
             next_gc_threshold: 128,
+            intern_strings: true,
+            interned: HashMap::new(),
         }
     }
 
+    pub fn string_interning(&self) -> bool {
+        self.intern_strings
+    }
+
+    pub fn set_string_interning(&mut self, enabled: bool) {
+        self.intern_strings = enabled;
+        if !enabled {
+            self.interned.clear();
+        }
+    }
+
+    /// 不変の文字列を確保する（インターン有効時は同じ内容のハンドルを共有）
+    pub fn intern_string(&mut self, value: String) -> StringHandle {
+        if !self.intern_strings {
+            return self.alloc_string(value);
+        }
+        if let Some(handle) = self.interned.get(&value) {
+            return *handle;
+        }
+        let handle = self.alloc_string(value.clone());
+        self.interned.insert(value, handle);
+        handle
+    }
+
+    /// 常に新しい文字列を確保する（可変文字列や計算結果向け）
     pub fn alloc_string(&mut self, value: String) -> StringHandle {
         let handle = StringHandle(self.entries.len());
         self.entries.push(Some(HeapEntry {
@@ -209,6 +244,9 @@ impl GcHeap {
                 }
             }
         }
+        let entries = &self.entries;
+        self.interned
+            .retain(|_, handle| matches!(entries.get(handle.0), Some(Some(_))));
         self.allocated = self.entries.iter().filter(|e| e.is_some()).count();
     }
 
@@ -273,11 +311,19 @@ impl GcHeap {
             Value::String(handle) => self.mark_string(*handle),
             Value::Function(Function::Lambda(handle)) => self.mark_closure(*handle),
             Value::Function(Function::Builtin(_)) => {}
-            Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Unit => {}
+            Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Unit | Value::Nil => {}
         }
     }
 }
 
+/// `set-option` でホストへ渡す設定値
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+}
+
 pub trait HostBridge {
     fn bind_key(
         &mut self,
@@ -289,6 +335,11 @@ pub trait HostBridge {
         let _ = (_component, _color);
         Err("GUIカラー設定は未実装です".to_string())
     }
+
+    fn set_option(&mut self, _name: &str, _value: &OptionValue) -> std::result::Result<(), String> {
+        let _ = (_name, _value);
+        Err("設定の変更は未実装です".to_string())
+    }
 }
 
 pub struct RuntimeState {
@@ -321,6 +372,11 @@ impl RuntimeState {
         Value::String(handle)
     }
 
+    pub fn intern_string_value(&mut self, value: String) -> Value {
+        let handle = self.heap.intern_string(value);
+        Value::String(handle)
+    }
+
     pub fn emit_message(&mut self, message: impl Into<String>) {
         self.messages.push(message.into());
     }
@@ -351,7 +407,7 @@ pub fn value_to_string(runtime: &RuntimeState, value: &Value) -> String {
         Value::String(handle) => runtime.heap.string_ref(*handle).to_string(),
         Value::Function(Function::Builtin(_)) => "<builtin>".to_string(),
         Value::Function(Function::Lambda(_)) => "<lambda>".to_string(),
-        Value::Unit => "()".to_string(),
+        Value::Unit | Value::Nil => "()".to_string(),
     }
 }
 
@@ -425,7 +481,16 @@ pub fn make_closure(
     body: Vec<Expr>,
     env: EnvHandle,
 ) -> ClosureHandle {
-    runtime.heap.alloc_closure(Closure { params, body, env })
+    let doc = match body.as_slice() {
+        [Expr::String(doc), _, ..] => Some(doc.clone()),
+        _ => None,
+    };
+    runtime.heap.alloc_closure(Closure {
+        params,
+        body,
+        env,
+        doc,
+    })
 }
 
 pub fn closure_ref(runtime: &RuntimeState, handle: ClosureHandle) -> &Closure {
@@ -447,3 +512,65 @@ impl std::fmt::Debug for Function {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_handle(value: &Value) -> StringHandle {
+        match value {
+            Value::String(handle) => *handle,
+            other => panic!("expected string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interned_literals_share_handle() {
+        let mut heap = GcHeap::new();
+        let first = heap.intern_string("hello".to_string());
+        let second = heap.intern_string("hello".to_string());
+        assert_eq!(first, second);
+        assert_eq!(heap.entries.len(), 1);
+
+        let other = heap.intern_string("world".to_string());
+        assert_ne!(first, other);
+
+        let fresh = heap.alloc_string("hello".to_string());
+        assert_ne!(first, fresh);
+        assert_eq!(heap.string_ref(fresh), "hello");
+    }
+
+    #[test]
+    fn interning_can_be_disabled() {
+        let mut heap = GcHeap::new();
+        heap.set_string_interning(false);
+        let first = heap.intern_string("hello".to_string());
+        let second = heap.intern_string("hello".to_string());
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn gc_collects_unreachable_strings_and_forgets_interned_entries() {
+        let mut runtime = RuntimeState::new();
+        let env = make_rooted_env(&mut runtime);
+        let kept = runtime.intern_string_value("kept".to_string());
+        let dropped = string_handle(&runtime.intern_string_value("dropped".to_string()));
+        let plain = string_handle(&runtime.alloc_string_value("plain".to_string()));
+
+        collect(&mut runtime, std::slice::from_ref(&kept), &[env]);
+
+        assert_eq!(runtime.heap.string_ref(string_handle(&kept)), "kept");
+        assert!(runtime.heap.entries[dropped.0].is_none());
+        assert!(runtime.heap.entries[plain.0].is_none());
+        assert!(!runtime.heap.interned.contains_key("dropped"));
+
+        // 回収済みの内容は新しいハンドルとして確保し直される
+        let again = string_handle(&runtime.intern_string_value("dropped".to_string()));
+        assert_ne!(again, dropped);
+        assert_eq!(runtime.heap.string_ref(again), "dropped");
+        assert_eq!(
+            string_handle(&runtime.intern_string_value("kept".to_string())),
+            string_handle(&kept)
+        );
+    }
+}
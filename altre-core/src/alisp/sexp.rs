@@ -0,0 +1,71 @@
+//! S式の括弧の走査
+//!
+//! リーダーの字句走査で区切り、文字列リテラルとコメントの中の括弧は数えない
+
+use crate::alisp::reader::{scan, LexemeKind};
+
+/// `pos`（文字位置）より前で閉じていない括弧を、内側から順に閉じる文字列を返す
+pub fn closing_brackets(text: &str, pos: usize) -> String {
+    let mut stack: Vec<char> = Vec::new();
+
+    for lexeme in scan(text) {
+        if lexeme.start >= pos {
+            break;
+        }
+        match lexeme.kind {
+            LexemeKind::Open('(') => stack.push(')'),
+            LexemeKind::Open('[') => stack.push(']'),
+            LexemeKind::Open(_) => stack.push('}'),
+            LexemeKind::Close(ch) => {
+                // 対応しない閉じ括弧はそこまでの入れ子を打ち切る
+                if let Some(index) = stack.iter().rposition(|&closer| closer == ch) {
+                    stack.truncate(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stack.into_iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_at_end(text: &str) -> String {
+        closing_brackets(text, text.chars().count())
+    }
+
+    #[test]
+    fn closes_nested_openers_innermost_first() {
+        assert_eq!(close_at_end("(define (f x"), "))");
+        assert_eq!(close_at_end("(let ((a 1)\n  (b [2 {3"), "}])))");
+    }
+
+    #[test]
+    fn balanced_text_needs_nothing() {
+        assert_eq!(close_at_end("(foo (bar))"), "");
+        assert_eq!(close_at_end(""), "");
+    }
+
+    #[test]
+    fn strings_and_comments_are_ignored() {
+        assert_eq!(close_at_end("(message \"(((\\\"\" ; ((\n"), ")");
+        assert_eq!(close_at_end("(foo #| ( |#"), ")");
+        assert_eq!(close_at_end("(foo #| #| [ |# ( |# bar"), ")");
+    }
+
+    #[test]
+    fn datum_comment_brackets_still_need_closing() {
+        assert_eq!(close_at_end("(a #;(b c) d"), ")");
+        assert_eq!(close_at_end("(a #;(b"), "))");
+    }
+
+    #[test]
+    fn only_text_before_position_is_scanned() {
+        let text = "(a (b) (c";
+        assert_eq!(closing_brackets(text, 3), ")");
+        assert_eq!(closing_brackets(text, 6), ")");
+    }
+}
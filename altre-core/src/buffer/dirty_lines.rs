@@ -0,0 +1,103 @@
+//! 変更行の追跡
+//!
+//! 編集イベントから、最後にクリアしてから変更された行番号（0始まり）の集合を保つ
+
+use std::collections::BTreeSet;
+
+/// 変更された行の集合
+///
+/// 行番号は常に現在のテキストでの番号。改行の挿入・削除に合わせて既存の記録をずらす。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyLines {
+    lines: BTreeSet<usize>,
+}
+
+impl DirtyLines {
+    /// 空の集合を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 変更行が存在しないか
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// 変更行の一覧（昇順）
+    pub fn lines(&self) -> Vec<usize> {
+        self.lines.iter().copied().collect()
+    }
+
+    /// 指定行が変更されているか
+    pub fn contains(&self, line: usize) -> bool {
+        self.lines.contains(&line)
+    }
+
+    /// 記録を消去
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// `line` への挿入（`newlines` 個の改行を含む）を反映
+    pub fn on_insert(&mut self, line: usize, newlines: usize) {
+        if newlines > 0 {
+            self.lines = self
+                .lines
+                .iter()
+                .map(|&dirty| {
+                    if dirty > line {
+                        dirty + newlines
+                    } else {
+                        dirty
+                    }
+                })
+                .collect();
+        }
+        self.lines.extend(line..=line + newlines);
+    }
+
+    /// `line` からの削除（`newlines` 個の改行を含む）を反映
+    pub fn on_delete(&mut self, line: usize, newlines: usize) {
+        if newlines > 0 {
+            let last = line + newlines;
+            self.lines = self
+                .lines
+                .iter()
+                .filter(|&&dirty| dirty <= line || dirty > last)
+                .map(|&dirty| {
+                    if dirty > last {
+                        dirty - newlines
+                    } else {
+                        dirty
+                    }
+                })
+                .collect();
+        }
+        self.lines.insert(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_marks_new_lines_and_shifts_later_ones() {
+        let mut dirty = DirtyLines::new();
+        dirty.on_insert(5, 0);
+        dirty.on_insert(1, 2);
+        assert_eq!(dirty.lines(), vec![1, 2, 3, 7]);
+    }
+
+    #[test]
+    fn delete_merges_joined_lines() {
+        let mut dirty = DirtyLines::new();
+        dirty.on_insert(2, 0);
+        dirty.on_insert(6, 0);
+        dirty.on_delete(1, 2);
+        assert_eq!(dirty.lines(), vec![1, 4]);
+
+        dirty.clear();
+        assert!(dirty.is_empty());
+    }
+}
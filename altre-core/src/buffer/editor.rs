@@ -4,8 +4,11 @@
 
 use crate::buffer::{
     cursor::CursorPosition,
+    dirty_lines::DirtyLines,
+    folding::{fold_range_at, FoldState, FoldToggle},
     gap_buffer::GapBuffer,
     navigation::{NavigationAction, NavigationError, NavigationSystem},
+    overlay::OverlaySet,
 };
 use crate::error::{EditError, Result};
 use std::time::Instant;
@@ -89,10 +92,22 @@ pub struct TextEditor {
     cursor: CursorPosition,
     /// マーク位置（文字インデックス）
     mark: Option<usize>,
+    /// マークがアクティブ（リージョンとして扱う）か
+    mark_active: bool,
     /// ナビゲーションシステム
     navigation: NavigationSystem,
     /// 変更通知システム
     change_notifier: ChangeNotifier,
+    /// 折りたたみ状態
+    folds: FoldState,
+    /// オーバーレイ
+    overlays: OverlaySet,
+    /// 最後にクリアしてから変更された行
+    dirty_lines: DirtyLines,
+    /// 上下移動で目標とする桁（水平移動・編集・カーソル設定でリセット）
+    goal_column: Option<usize>,
+    /// 読み取り専用か（挿入・削除はエラーになる）
+    read_only: bool,
     /// 最後の操作時刻（パフォーマンス監視用）
     last_operation_time: Instant,
 }
@@ -104,8 +119,14 @@ impl TextEditor {
             buffer: GapBuffer::new(),
             cursor: CursorPosition::new(),
             mark: None,
+            mark_active: false,
             navigation: NavigationSystem::new(),
             change_notifier: ChangeNotifier::new(),
+            folds: FoldState::new(),
+            overlays: OverlaySet::new(),
+            dirty_lines: DirtyLines::new(),
+            goal_column: None,
+            read_only: false,
             last_operation_time: Instant::now(),
         }
     }
@@ -116,17 +137,45 @@ impl TextEditor {
             buffer: GapBuffer::from_str(s),
             cursor: CursorPosition::new(),
             mark: None,
+            mark_active: false,
             navigation: NavigationSystem::new(),
             change_notifier: ChangeNotifier::new(),
+            folds: FoldState::new(),
+            overlays: OverlaySet::new(),
+            dirty_lines: DirtyLines::new(),
+            goal_column: None,
+            read_only: false,
             last_operation_time: Instant::now(),
         }
     }
 
+    /// 読み取り専用か
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// 読み取り専用にするかを設定
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(EditError::ReadOnly.into());
+        }
+        Ok(())
+    }
+
     /// バッファの内容を文字列として取得
     pub fn to_string(&self) -> String {
         self.buffer.to_string()
     }
 
+    /// バッファの行数を取得（末尾の改行の後ろの空行も含む）
+    pub fn line_count(&self) -> usize {
+        self.buffer.line_count()
+    }
+
     /// カーソル位置を取得
     pub fn cursor(&self) -> &CursorPosition {
         &self.cursor
@@ -141,6 +190,7 @@ impl TextEditor {
     pub fn set_cursor(&mut self, position: CursorPosition) {
         let old_position = self.cursor;
         self.cursor = position;
+        self.goal_column = None;
         self.clamp_cursor_position();
         let _ = self.sync_navigation_cursor();
 
@@ -161,7 +211,9 @@ impl TextEditor {
             let old_position = editor.cursor;
 
             editor.cursor.char_pos = target;
+            editor.goal_column = None;
             editor.sync_cursor_with_buffer();
+            editor.folds.expand_containing(editor.cursor.line);
 
             editor.change_notifier.notify(ChangeEvent::CursorMove {
                 old_position,
@@ -175,8 +227,21 @@ impl TextEditor {
         result
     }
 
+    /// 指定行（1始まり）の行頭にカーソルを移動（行数を超える行は最終行にする）
+    pub fn move_cursor_to_line(&mut self, line: usize) -> Result<()> {
+        let text = self.buffer.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+        let index = line.clamp(1, lines.len()) - 1;
+        let char_pos = lines[..index]
+            .iter()
+            .map(|line| line.chars().count() + 1)
+            .sum();
+        self.move_cursor_to_char(char_pos)
+    }
+
     /// 単語を前方に削除し、削除文字列を返す
     pub fn delete_word_forward(&mut self) -> Result<String> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -195,7 +260,7 @@ impl TextEditor {
                 .map_err(|_| EditError::BufferError("単語削除失敗".to_string()))?;
 
             editor.sync_cursor_with_buffer();
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: start,
                 content: deleted.clone(),
             });
@@ -210,6 +275,7 @@ impl TextEditor {
 
     /// 単語を後方に削除し、削除文字列を返す
     pub fn delete_word_backward(&mut self) -> Result<String> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -229,7 +295,7 @@ impl TextEditor {
 
             editor.cursor.char_pos = start;
             editor.sync_cursor_with_buffer();
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: start,
                 content: deleted.clone(),
             });
@@ -244,6 +310,7 @@ impl TextEditor {
 
     /// カーソル位置から行末（改行を含む）まで削除
     pub fn kill_line_forward(&mut self) -> Result<String> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -271,7 +338,7 @@ impl TextEditor {
 
             editor.cursor.char_pos = start;
             editor.sync_cursor_with_buffer();
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: start,
                 content: deleted.clone(),
             });
@@ -462,18 +529,139 @@ impl TextEditor {
         Ok(())
     }
 
+    /// 編集を折りたたみ状態・オーバーレイ・変更行へ反映してから通知
+    fn notify_edit(&mut self, event: ChangeEvent) {
+        self.goal_column = None;
+        let (position, content, inserted) = match &event {
+            ChangeEvent::Insert { position, content } => (*position, content, true),
+            ChangeEvent::Delete { position, content } => (*position, content, false),
+            ChangeEvent::CursorMove { .. } => {
+                self.change_notifier.notify(event);
+                return;
+            }
+        };
+        let len = content.chars().count();
+        let newlines = content.matches('\n').count();
+        let line = self.line_of_position(position);
+        if inserted {
+            self.overlays.on_insert(position, len);
+            self.dirty_lines.on_insert(line, newlines);
+            if !self.folds.is_empty() {
+                self.folds.on_insert(line, newlines);
+            }
+        } else {
+            self.overlays.on_delete(position, position + len);
+            self.dirty_lines.on_delete(line, newlines);
+            if !self.folds.is_empty() {
+                self.folds.on_delete(line, newlines);
+            }
+        }
+        self.change_notifier.notify(event);
+    }
+
+    /// 文字位置を含む行番号（行頭位置の索引から求める）
+    fn line_of_position(&mut self, char_pos: usize) -> usize {
+        self.buffer
+            .line_starts()
+            .partition_point(|&start| start <= char_pos)
+            .saturating_sub(1)
+    }
+
+    /// オーバーレイを取得
+    pub fn overlays(&self) -> &OverlaySet {
+        &self.overlays
+    }
+
+    /// オーバーレイを変更
+    pub fn overlays_mut(&mut self) -> &mut OverlaySet {
+        &mut self.overlays
+    }
+
+    /// オーバーレイを設定（バッファ切り替え時の復元用）
+    pub fn set_overlays(&mut self, overlays: OverlaySet) {
+        self.overlays = overlays;
+    }
+
+    /// 最後に `clear_dirty` してから変更された行（0始まり、昇順）
+    pub fn dirty_lines(&self) -> Vec<usize> {
+        self.dirty_lines.lines()
+    }
+
+    /// 変更行の記録を消去
+    pub fn clear_dirty(&mut self) {
+        self.dirty_lines.clear();
+    }
+
+    /// 折りたたみ状態を取得
+    pub fn folds(&self) -> &FoldState {
+        &self.folds
+    }
+
+    /// 折りたたみ状態を設定（バッファ切り替え時の復元用）
+    pub fn set_folds(&mut self, folds: FoldState) {
+        self.folds = folds;
+    }
+
+    /// カーソル行の折りたたみを切り替える
+    pub fn toggle_fold_at_cursor(&mut self) -> FoldToggle {
+        let line = self.cursor.line;
+        if let Some(range) = self.folds.unfold_at(line) {
+            return FoldToggle::Expanded(range);
+        }
+
+        match fold_range_at(&self.buffer.to_string(), line) {
+            Some(range) => {
+                self.folds.fold(range);
+                FoldToggle::Folded(range)
+            }
+            None => FoldToggle::NoBlock,
+        }
+    }
+
+    /// 隠れた行にカーソルが入った場合の補正
+    ///
+    /// 行移動では折りたたみを飛び越え、それ以外の移動では折りたたみを展開する。
+    fn skip_folded_lines(&mut self, action: NavigationAction, text: &str) {
+        let fold = match self.folds.hiding_fold(self.cursor.line) {
+            Some(fold) => fold,
+            None => return,
+        };
+
+        let line_count = text.split('\n').count();
+        let target_line = match action {
+            NavigationAction::MoveLineDown if fold.end_line + 1 < line_count => fold.end_line + 1,
+            NavigationAction::MoveLineDown | NavigationAction::MoveLineUp => fold.start_line,
+            _ => {
+                self.folds.expand_containing(self.cursor.line);
+                return;
+            }
+        };
+
+        self.cursor = cursor_at_line_column(text, target_line, self.cursor.column);
+    }
+
     /// ナビゲーション操作の実行
     pub fn navigate(
         &mut self,
         action: NavigationAction,
     ) -> std::result::Result<bool, NavigationError> {
         let text = self.buffer.to_string();
+        let vertical = matches!(
+            action,
+            NavigationAction::MoveLineUp | NavigationAction::MoveLineDown
+        );
+        let goal = self.goal_column.unwrap_or(self.cursor.column);
         self.navigation.set_cursor(self.cursor);
+        if vertical {
+            self.navigation.set_preferred_column(goal);
+        }
         let moved = self.navigation.navigate(&text, action)?;
+        self.goal_column = vertical.then_some(goal);
         if moved {
             let new_cursor = *self.navigation.cursor();
             let old_position = self.cursor;
             self.cursor = new_cursor;
+            self.skip_folded_lines(action, &text);
             let _ = self.sync_navigation_cursor();
             self.change_notifier.notify(ChangeEvent::CursorMove {
                 old_position,
@@ -483,6 +671,47 @@ impl TextEditor {
         Ok(moved)
     }
 
+    /// 上下移動で目標としている桁（直前が上下移動でなければ `None`）
+    pub fn goal_column(&self) -> Option<usize> {
+        self.goal_column
+    }
+
+    /// カーソルが空でない行の行末にあれば最後の文字の位置へ戻す
+    ///
+    /// `forward` なら次の行があるときは次の行頭へ進める。上下移動の目標桁は保つ。
+    /// 行末にカーソルを置かない設定で移動後に使い、動かしたら `true` を返す。
+    pub fn step_off_line_end(&mut self, forward: bool) -> bool {
+        let line = self.cursor.line;
+        let Some((line_start, line_end)) = self.buffer.line_range(line) else {
+            return false;
+        };
+        let line_len = line_end - line_start;
+        if line_len == 0 || self.cursor.column < line_len {
+            return false;
+        }
+
+        let old_position = self.cursor;
+        self.cursor = if forward && line_end < self.buffer.len_chars() {
+            CursorPosition {
+                char_pos: line_end + 1,
+                line: line + 1,
+                column: 0,
+            }
+        } else {
+            CursorPosition {
+                char_pos: line_end - 1,
+                line,
+                column: line_len - 1,
+            }
+        };
+        let _ = self.sync_navigation_cursor();
+        self.change_notifier.notify(ChangeEvent::CursorMove {
+            old_position,
+            new_position: self.cursor,
+        });
+        true
+    }
+
     /// 挿入後のカーソル位置更新
     fn update_cursor_after_insert(&mut self, inserted: &str) {
         for ch in inserted.chars() {
@@ -516,6 +745,7 @@ impl TextEditor {
 impl EditOperations for TextEditor {
     /// 文字を挿入
     fn insert_char(&mut self, ch: char) -> Result<()> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -545,7 +775,7 @@ impl EditOperations for TextEditor {
             }
 
             // 5. 変更通知
-            editor.change_notifier.notify(ChangeEvent::Insert {
+            editor.notify_edit(ChangeEvent::Insert {
                 position: cursor_pos,
                 content: ch.to_string(),
             });
@@ -562,6 +792,7 @@ impl EditOperations for TextEditor {
 
     /// 文字列を挿入
     fn insert_str(&mut self, s: &str) -> Result<()> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -588,7 +819,7 @@ impl EditOperations for TextEditor {
             editor.update_cursor_after_insert(&normalized);
 
             // 変更通知
-            editor.change_notifier.notify(ChangeEvent::Insert {
+            editor.notify_edit(ChangeEvent::Insert {
                 position: cursor_pos,
                 content: normalized,
             });
@@ -610,6 +841,7 @@ impl EditOperations for TextEditor {
 
     /// Backspace削除（カーソル前削除）
     fn delete_backward(&mut self) -> Result<char> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -639,7 +871,7 @@ impl EditOperations for TextEditor {
             }
 
             // 変更通知
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: pos,
                 content: deleted_char.to_string(),
             });
@@ -656,6 +888,7 @@ impl EditOperations for TextEditor {
 
     /// Delete削除（カーソル後削除）
     fn delete_forward(&mut self) -> Result<char> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -673,7 +906,7 @@ impl EditOperations for TextEditor {
             // カーソル位置は変更なし（文字が削除されたため相対的に正しい位置）
 
             // 変更通知
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: pos,
                 content: deleted_char.to_string(),
             });
@@ -690,6 +923,7 @@ impl EditOperations for TextEditor {
 
     /// 改行を挿入
     fn insert_newline(&mut self) -> Result<()> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -708,7 +942,7 @@ impl EditOperations for TextEditor {
             editor.cursor.column = 0;
 
             // 変更通知
-            editor.change_notifier.notify(ChangeEvent::Insert {
+            editor.notify_edit(ChangeEvent::Insert {
                 position: cursor_pos,
                 content: "\n".to_string(),
             });
@@ -725,6 +959,7 @@ impl EditOperations for TextEditor {
 
     /// 範囲削除
     fn delete_range(&mut self, start: usize, end: usize) -> Result<String> {
+        self.ensure_writable()?;
         self.start_performance_measurement();
 
         let result = self.safe_execute(|editor| {
@@ -753,7 +988,7 @@ impl EditOperations for TextEditor {
             editor.sync_cursor_with_buffer();
 
             // 変更通知
-            editor.change_notifier.notify(ChangeEvent::Delete {
+            editor.notify_edit(ChangeEvent::Delete {
                 position: start,
                 content: deleted_text.clone(),
             });
@@ -775,6 +1010,28 @@ impl Default for TextEditor {
     }
 }
 
+/// 行・列からカーソル位置を求める（列は行長でクランプ）
+fn cursor_at_line_column(text: &str, line: usize, column: usize) -> CursorPosition {
+    let mut char_pos = 0usize;
+    for (index, content) in text.split('\n').enumerate() {
+        let length = content.chars().count();
+        if index == line {
+            let column = column.min(length);
+            return CursorPosition {
+                char_pos: char_pos + column,
+                line,
+                column,
+            };
+        }
+        char_pos += length + 1;
+    }
+    CursorPosition {
+        char_pos: text.chars().count(),
+        line,
+        column: 0,
+    }
+}
+
 fn is_word_char(ch: char) -> bool {
     ch.is_alphanumeric() || ch == '_'
 }
@@ -840,14 +1097,37 @@ fn word_boundary_backward(chars: &[char], end: usize) -> usize {
 }
 
 impl TextEditor {
-    /// マークを現在のカーソル位置に設定
+    /// マークを現在のカーソル位置に設定してアクティブにする
     pub fn set_mark(&mut self) {
         self.mark = Some(self.cursor.char_pos);
+        self.mark_active = true;
+    }
+
+    /// マークを指定位置（文字インデックス、末尾を越える値は末尾）に設定してアクティブにする
+    pub fn set_mark_at(&mut self, char_pos: usize) {
+        self.mark = Some(char_pos.min(self.buffer.len_chars()));
+        self.mark_active = true;
     }
 
     /// マークを消去
     pub fn clear_mark(&mut self) {
         self.mark = None;
+        self.mark_active = false;
+    }
+
+    /// マークがアクティブか
+    pub fn is_mark_active(&self) -> bool {
+        self.mark_active
+    }
+
+    /// マークのアクティブ状態を設定（マークがなければ何もしない）
+    pub fn set_mark_active(&mut self, active: bool) {
+        self.mark_active = active && self.mark.is_some();
+    }
+
+    /// マーク位置を残したまま非アクティブにする
+    pub fn deactivate_mark(&mut self) {
+        self.mark_active = false;
     }
 
     /// マーク位置を取得
@@ -855,8 +1135,11 @@ impl TextEditor {
         self.mark
     }
 
-    /// 選択範囲（マークとポイント）を取得
+    /// 選択範囲（アクティブなマークとポイント）を取得
     pub fn selection_range(&self) -> Option<(usize, usize)> {
+        if !self.mark_active {
+            return None;
+        }
         let mark = self.mark?;
         let cursor = self.cursor.char_pos;
         if mark == cursor {
@@ -868,6 +1151,16 @@ impl TextEditor {
         }
     }
 
+    /// マークとポイントの間を開始・終了の順に並べた範囲
+    ///
+    /// [`selection_range`](Self::selection_range) と異なり、マークが非アクティブでも、
+    /// 範囲が空でも返す。マークがなければ `None`。
+    pub fn region(&self) -> Option<(usize, usize)> {
+        let mark = self.mark?;
+        let cursor = self.cursor.char_pos;
+        Some((mark.min(cursor), mark.max(cursor)))
+    }
+
     /// 範囲テキストを取得
     pub fn get_text_range(&self, start: usize, end: usize) -> Result<String> {
         self.buffer
@@ -914,12 +1207,14 @@ impl TextEditor {
         let cursor_pos = self.cursor.char_pos;
         self.move_cursor_to_char(mark)?;
         self.mark = Some(cursor_pos);
+        self.mark_active = true;
         Ok(())
     }
 
     /// バッファ全体を選択
     pub fn mark_entire_buffer(&mut self) -> Result<()> {
         self.mark = Some(0);
+        self.mark_active = true;
         let len = self.buffer.len_chars();
         self.move_cursor_to_char(len)
     }
@@ -1089,6 +1384,27 @@ mod tests {
         assert_eq!(editor.cursor.char_pos, 4);
     }
 
+    #[test]
+    fn test_read_only_rejects_edits() {
+        let mut editor = TextEditor::from_str("abc");
+        editor.set_read_only(true);
+        editor.move_cursor_to_char(1).unwrap();
+
+        assert!(editor.insert_char('x').is_err());
+        assert!(editor.insert_str("xy").is_err());
+        assert!(editor.delete_backward().is_err());
+        assert!(editor.delete_forward().is_err());
+        assert!(editor.insert_newline().is_err());
+        assert!(editor.delete_range(0, 3).is_err());
+        assert!(editor.kill_line_forward().is_err());
+        assert_eq!(editor.to_string(), "abc");
+        assert_eq!(editor.cursor().char_pos, 1);
+
+        editor.set_read_only(false);
+        editor.insert_char('x').unwrap();
+        assert_eq!(editor.to_string(), "axbc");
+    }
+
     #[test]
     fn test_delete_range() {
         let mut editor = TextEditor::from_str("hello world");
@@ -1125,4 +1441,164 @@ mod tests {
         editor.cursor.char_pos = 1;
         assert!(editor.delete_forward().is_err());
     }
+
+    #[test]
+    fn test_fold_skips_hidden_lines_on_line_motion() {
+        let mut editor = TextEditor::from_str("head\n  a\n  b\ntail");
+        assert!(matches!(
+            editor.toggle_fold_at_cursor(),
+            FoldToggle::Folded(range) if range.start_line == 0 && range.end_line == 2
+        ));
+
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!(editor.cursor().line, 3);
+
+        editor.navigate(NavigationAction::MoveLineUp).unwrap();
+        assert_eq!(editor.cursor().line, 0);
+        assert!(editor.folds().is_hidden(1));
+    }
+
+    #[test]
+    fn test_fold_expands_when_cursor_enters_or_edits() {
+        let mut editor = TextEditor::from_str("head\n  a\n  b\ntail");
+        editor.toggle_fold_at_cursor();
+        editor.navigate(NavigationAction::MoveLineEnd).unwrap();
+        editor.navigate(NavigationAction::MoveCharForward).unwrap();
+        assert_eq!(editor.cursor().line, 1);
+        assert!(editor.folds().is_empty());
+
+        editor.move_cursor_to_char(0).unwrap();
+        editor.toggle_fold_at_cursor();
+        editor.delete_range(6, 7).unwrap();
+        assert!(editor.folds().is_empty());
+    }
+
+    #[test]
+    fn test_fold_toggle_expands_existing_fold() {
+        let mut editor = TextEditor::from_str("head\n  body\ntail");
+        editor.toggle_fold_at_cursor();
+        assert!(matches!(
+            editor.toggle_fold_at_cursor(),
+            FoldToggle::Expanded(_)
+        ));
+        assert!(editor.folds().is_empty());
+
+        editor.navigate(NavigationAction::MoveBufferEnd).unwrap();
+        assert_eq!(editor.toggle_fold_at_cursor(), FoldToggle::NoBlock);
+    }
+
+    #[test]
+    fn test_goal_column_survives_short_lines() {
+        let mut editor = TextEditor::from_str("long line here\nab\n\nanother long line");
+        editor.move_cursor_to_char(10).unwrap();
+
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!(editor.cursor().column, 2);
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!(editor.cursor().column, 0);
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!((editor.cursor().line, editor.cursor().column), (3, 10));
+        assert_eq!(editor.goal_column(), Some(10));
+
+        editor.navigate(NavigationAction::MoveLineUp).unwrap();
+        editor.navigate(NavigationAction::MoveLineUp).unwrap();
+        editor.navigate(NavigationAction::MoveLineUp).unwrap();
+        assert_eq!((editor.cursor().line, editor.cursor().column), (0, 10));
+    }
+
+    #[test]
+    fn test_goal_column_resets_on_horizontal_motion_and_edit() {
+        let mut editor = TextEditor::from_str("abcdef\nab\nabcdef");
+        editor.move_cursor_to_char(5).unwrap();
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        editor.navigate(NavigationAction::MoveCharBackward).unwrap();
+        assert_eq!(editor.goal_column(), None);
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!(editor.cursor().column, 1);
+
+        editor.move_cursor_to_char(5).unwrap();
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        editor.insert_char('x').unwrap();
+        assert_eq!(editor.goal_column(), None);
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+        assert_eq!(editor.cursor().column, 3);
+    }
+
+    #[test]
+    fn dirty_lines_follow_edits_across_lines() {
+        let mut editor = TextEditor::from_str("a\nb\nc\nd\ne");
+        assert!(editor.dirty_lines().is_empty());
+
+        // 3行目 "c" の末尾に改行を2つ挿入
+        editor.move_cursor_to_char(5).unwrap();
+        editor.insert_str("x\ny\n").unwrap();
+        assert_eq!(editor.dirty_lines(), vec![2, 3, 4]);
+
+        // 1行目と2行目をつなげると、後続の記録が1行ずれる
+        editor.delete_range(1, 2).unwrap();
+        assert_eq!(editor.to_string(), "ab\ncx\ny\n\nd\ne");
+        assert_eq!(editor.dirty_lines(), vec![0, 1, 2, 3]);
+
+        editor.clear_dirty();
+        assert!(editor.dirty_lines().is_empty());
+
+        // 最終行の編集はその行だけを記録する
+        editor
+            .move_cursor_to_char(editor.to_string().chars().count())
+            .unwrap();
+        editor.insert_char('!').unwrap();
+        assert_eq!(editor.dirty_lines(), vec![5]);
+    }
+
+    #[test]
+    fn move_cursor_to_line_goes_to_line_start() {
+        let mut editor = TextEditor::from_str("one\ntwo\nthree");
+        editor.move_cursor_to_char(6).unwrap();
+
+        editor.move_cursor_to_line(1).unwrap();
+        assert_eq!(editor.cursor().char_pos, 0);
+        assert_eq!((editor.cursor().line, editor.cursor().column), (0, 0));
+
+        editor.move_cursor_to_line(2).unwrap();
+        assert_eq!(editor.cursor().char_pos, 4);
+        assert_eq!((editor.cursor().line, editor.cursor().column), (1, 0));
+    }
+
+    #[test]
+    fn move_cursor_to_line_clamps_beyond_last_line() {
+        let mut editor = TextEditor::from_str("one\ntwo\nthree");
+
+        editor.move_cursor_to_line(10).unwrap();
+        assert_eq!(editor.cursor().char_pos, 8);
+        assert_eq!((editor.cursor().line, editor.cursor().column), (2, 0));
+
+        let mut trailing = TextEditor::from_str("one\n");
+        trailing.move_cursor_to_line(5).unwrap();
+        assert_eq!((trailing.cursor().line, trailing.cursor().column), (1, 0));
+    }
+
+    #[test]
+    fn set_mark_at_defines_normalized_region() {
+        let mut editor = TextEditor::from_str("hello world");
+        assert_eq!(editor.region(), None);
+
+        editor.set_mark_at(8);
+        editor.move_cursor_to_char(2).unwrap();
+        assert!(editor.is_mark_active());
+        assert_eq!(editor.mark(), Some(8));
+        assert_eq!(editor.region(), Some((2, 8)));
+        assert_eq!(editor.selection_range(), Some((2, 8)));
+
+        editor.move_cursor_to_char(11).unwrap();
+        assert_eq!(editor.region(), Some((8, 11)));
+
+        // 非アクティブでも、空でも範囲を返す
+        editor.deactivate_mark();
+        assert_eq!(editor.region(), Some((8, 11)));
+        assert_eq!(editor.selection_range(), None);
+        editor.set_mark_at(100);
+        assert_eq!(editor.mark(), Some(11));
+        assert_eq!(editor.region(), Some((11, 11)));
+        assert_eq!(editor.selection_range(), None);
+    }
 }
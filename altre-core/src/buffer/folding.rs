@@ -0,0 +1,325 @@
+//! 折りたたみ（フォールディング）
+//!
+//! インデントや括弧の対応からブロック範囲を求め、行単位の折りたたみ状態を管理する
+
+use serde::{Deserialize, Serialize};
+
+/// 折りたたみ範囲（行番号は0始まり）
+///
+/// `start_line` は見出しとして表示され続け、`start_line + 1..=end_line` が隠される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldRange {
+    /// 見出し行
+    pub start_line: usize,
+    /// 隠す範囲の最終行
+    pub end_line: usize,
+}
+
+impl FoldRange {
+    /// 新しい範囲を作成
+    pub fn new(start_line: usize, end_line: usize) -> Self {
+        Self {
+            start_line,
+            end_line,
+        }
+    }
+
+    /// 指定行がこの範囲によって隠されるか
+    pub fn hides(&self, line: usize) -> bool {
+        line > self.start_line && line <= self.end_line
+    }
+
+    /// 隠される行数
+    pub fn hidden_line_count(&self) -> usize {
+        self.end_line.saturating_sub(self.start_line)
+    }
+}
+
+/// 折りたたみ操作の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldToggle {
+    /// 新たに折りたたんだ
+    Folded(FoldRange),
+    /// 既存の折りたたみを展開した
+    Expanded(FoldRange),
+    /// 折りたためるブロックがない
+    NoBlock,
+}
+
+/// バッファごとの折りたたみ状態
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FoldState {
+    folds: Vec<FoldRange>,
+}
+
+impl FoldState {
+    /// 空の状態を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存済みの範囲から復元（`line_count` 行に収まらない範囲は捨てる）
+    pub fn restore(folds: &[FoldRange], line_count: usize) -> Self {
+        let mut state = Self::new();
+        for fold in folds {
+            if fold.end_line < line_count {
+                state.fold(*fold);
+            }
+        }
+        state
+    }
+
+    /// 折りたたみが存在しないか
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    /// 折りたたみ中の範囲一覧（見出し行順）
+    pub fn folds(&self) -> &[FoldRange] {
+        &self.folds
+    }
+
+    /// 範囲を折りたたむ
+    pub fn fold(&mut self, range: FoldRange) {
+        if range.hidden_line_count() == 0 {
+            return;
+        }
+        self.folds
+            .retain(|fold| fold.start_line != range.start_line);
+        self.folds.push(range);
+        self.folds.sort_by_key(|fold| fold.start_line);
+    }
+
+    /// 指定行を見出しとする折りたたみを展開
+    pub fn unfold_at(&mut self, start_line: usize) -> Option<FoldRange> {
+        let index = self
+            .folds
+            .iter()
+            .position(|fold| fold.start_line == start_line)?;
+        Some(self.folds.remove(index))
+    }
+
+    /// 指定行を見出しとする折りたたみを取得
+    pub fn fold_at(&self, start_line: usize) -> Option<FoldRange> {
+        self.folds
+            .iter()
+            .copied()
+            .find(|fold| fold.start_line == start_line)
+    }
+
+    /// 指定行が隠されているか
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.folds.iter().any(|fold| fold.hides(line))
+    }
+
+    /// 指定行を隠している最も外側の折りたたみ
+    pub fn hiding_fold(&self, line: usize) -> Option<FoldRange> {
+        self.folds.iter().copied().find(|fold| fold.hides(line))
+    }
+
+    /// 指定行を隠している折りたたみをすべて展開
+    pub fn expand_containing(&mut self, line: usize) -> bool {
+        let before = self.folds.len();
+        self.folds.retain(|fold| !fold.hides(line));
+        before != self.folds.len()
+    }
+
+    /// すべて展開
+    pub fn clear(&mut self) {
+        self.folds.clear();
+    }
+
+    /// `line` への挿入（`newlines` 個の改行を含む）を反映
+    pub fn on_insert(&mut self, line: usize, newlines: usize) {
+        self.folds.retain_mut(|fold| {
+            if fold.hides(line) || (newlines > 0 && fold.start_line == line) {
+                return false;
+            }
+            if fold.start_line > line {
+                fold.start_line += newlines;
+                fold.end_line += newlines;
+            }
+            true
+        });
+    }
+
+    /// `line` からの削除（`newlines` 個の改行を含む）を反映
+    pub fn on_delete(&mut self, line: usize, newlines: usize) {
+        let last = line + newlines;
+        self.folds.retain_mut(|fold| {
+            let touches_hidden = line <= fold.end_line && last > fold.start_line;
+            let touches_header = newlines > 0 && (line..=last).contains(&fold.start_line);
+            if touches_hidden || touches_header {
+                return false;
+            }
+            if fold.start_line > last {
+                fold.start_line -= newlines;
+                fold.end_line -= newlines;
+            }
+            true
+        });
+    }
+}
+
+/// 指定行を見出しとする折りたたみ範囲を計算
+///
+/// 行内で閉じていない括弧があれば対応する括弧まで、なければインデントの深い後続行までを範囲とする。
+pub fn fold_range_at(text: &str, line: usize) -> Option<FoldRange> {
+    bracket_fold_range(text, line).or_else(|| indent_fold_range(text, line))
+}
+
+/// インデントに基づく折りたたみ範囲を計算
+pub fn indent_fold_range(text: &str, line: usize) -> Option<FoldRange> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let header = lines.get(line)?;
+    if header.trim().is_empty() {
+        return None;
+    }
+
+    let header_indent = indent_width(header);
+    let mut end_line = None;
+    for (index, candidate) in lines.iter().enumerate().skip(line + 1) {
+        if candidate.trim().is_empty() {
+            continue;
+        }
+        if indent_width(candidate) <= header_indent {
+            break;
+        }
+        end_line = Some(index);
+    }
+
+    end_line.map(|end| FoldRange::new(line, end))
+}
+
+/// 括弧の対応に基づく折りたたみ範囲を計算
+pub fn bracket_fold_range(text: &str, line: usize) -> Option<FoldRange> {
+    let mut current_line = 0usize;
+    let mut stack: Vec<usize> = Vec::new();
+    let mut opener_depth: Option<usize> = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for ch in text.chars() {
+        if ch == '\n' {
+            if current_line == line && opener_depth.is_none() {
+                // 見出し行で閉じていない括弧のうち最も外側のものを対象にする
+                let base = stack.iter().position(|&l| l == line)?;
+                opener_depth = Some(base);
+            }
+            current_line += 1;
+            continue;
+        }
+
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => in_string = true,
+            '(' | '[' | '{' => stack.push(current_line),
+            ')' | ']' | '}' => {
+                if stack.pop().is_none() {
+                    continue;
+                }
+                if let Some(depth) = opener_depth {
+                    if stack.len() == depth {
+                        return (current_line > line).then(|| FoldRange::new(line, current_line));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|ch| *ch == ' ' || *ch == '\t')
+        .map(|ch| if ch == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indent_range_covers_deeper_lines() {
+        let text = "fn main() {\n    let a = 1;\n\n    let b = 2;\n}\nnext";
+        assert_eq!(indent_fold_range(text, 0), Some(FoldRange::new(0, 3)));
+        assert_eq!(indent_fold_range(text, 1), None);
+        assert_eq!(indent_fold_range(text, 4), None);
+    }
+
+    #[test]
+    fn bracket_range_matches_closing_line() {
+        let text = "(defun foo ()\n  (bar)\n  (baz))\n(next)";
+        assert_eq!(bracket_fold_range(text, 0), Some(FoldRange::new(0, 2)));
+        assert_eq!(bracket_fold_range(text, 1), None);
+        assert_eq!(bracket_fold_range(text, 3), None);
+    }
+
+    #[test]
+    fn bracket_range_ignores_brackets_in_strings() {
+        let text = "(message \"(\"\n  x)\ny";
+        assert_eq!(bracket_fold_range(text, 0), Some(FoldRange::new(0, 1)));
+    }
+
+    #[test]
+    fn fold_range_prefers_brackets() {
+        let text = "(let ((a 1))\n(body))\nrest";
+        assert_eq!(fold_range_at(text, 0), Some(FoldRange::new(0, 1)));
+        let plain = "heading\n  child\n  child\nnext";
+        assert_eq!(fold_range_at(plain, 0), Some(FoldRange::new(0, 2)));
+    }
+
+    #[test]
+    fn indent_range_counts_tabs_and_nested_blocks() {
+        let text = "a\n\tb\n\t\tc\n\td\ne";
+        assert_eq!(indent_fold_range(text, 0), Some(FoldRange::new(0, 3)));
+        assert_eq!(indent_fold_range(text, 1), Some(FoldRange::new(1, 2)));
+        assert_eq!(indent_fold_range(text, 4), None);
+    }
+
+    #[test]
+    fn bracket_range_handles_nesting_and_unclosed_blocks() {
+        let text = "{\n  [\n    1,\n  ]\n}";
+        assert_eq!(bracket_fold_range(text, 0), Some(FoldRange::new(0, 4)));
+        assert_eq!(bracket_fold_range(text, 1), Some(FoldRange::new(1, 3)));
+        assert_eq!(bracket_fold_range("(open\n  never closed", 0), None);
+    }
+
+    #[test]
+    fn restore_drops_out_of_range_folds() {
+        let saved = [FoldRange::new(0, 2), FoldRange::new(4, 9)];
+        let state = FoldState::restore(&saved, 6);
+        assert_eq!(state.folds(), &[FoldRange::new(0, 2)]);
+    }
+
+    #[test]
+    fn edits_shift_or_expand_folds() {
+        let mut state = FoldState::new();
+        state.fold(FoldRange::new(2, 4));
+
+        state.on_insert(0, 1);
+        assert_eq!(state.folds(), &[FoldRange::new(3, 5)]);
+
+        state.on_delete(0, 1);
+        assert_eq!(state.folds(), &[FoldRange::new(2, 4)]);
+
+        state.on_insert(2, 0);
+        assert_eq!(state.folds(), &[FoldRange::new(2, 4)]);
+
+        state.on_insert(3, 0);
+        assert!(state.is_empty());
+    }
+}
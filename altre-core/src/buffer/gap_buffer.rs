@@ -137,6 +137,17 @@ impl GapBuffer {
         self.len_bytes()
     }
 
+    /// 行数を取得（末尾の改行の後ろの空行も1行として数える）
+    pub fn line_count(&self) -> usize {
+        let newlines = self
+            .prefix_bytes()
+            .iter()
+            .chain(self.suffix_bytes())
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        newlines + 1
+    }
+
     /// 空かどうかを判定
     pub fn is_empty(&self) -> bool {
         self.len_bytes() == 0
@@ -168,12 +179,9 @@ impl GapBuffer {
             return Err(BufferError::InvalidCursorPosition { position: end });
         }
 
-        let byte_start = if start == 0 { 0 } else { char_indices[start].0 };
-        let byte_end = if end == char_indices.len() {
-            text.len()
-        } else {
-            char_indices[end].0
-        };
+        let byte_offset = |pos: usize| char_indices.get(pos).map_or(text.len(), |(byte, _)| *byte);
+        let byte_start = byte_offset(start);
+        let byte_end = byte_offset(end);
 
         Ok(text[byte_start..byte_end].to_string())
     }
@@ -411,6 +419,32 @@ impl GapBuffer {
             .unwrap_or_else(|| vec![0])
     }
 
+    /// 行の開始位置（文字単位）を複製せずに取得（末尾の改行の後ろの空行は含まない）
+    pub fn line_starts(&mut self) -> &[usize] {
+        if self.char_cache.is_none() {
+            let _ = self.char_to_byte_pos_internal(self.len_chars());
+        }
+        self.char_cache
+            .as_ref()
+            .map_or(&[0], |cache| cache.line_starts.as_slice())
+    }
+
+    /// `line` 行目（0始まり）の開始・終了位置（文字単位、改行は含まない）
+    ///
+    /// 末尾の改行の後ろの空行も1行として数える。行がなければ `None`。
+    pub fn line_range(&mut self, line: usize) -> Option<(usize, usize)> {
+        let total = self.len_chars();
+        let ends_with_newline = total > 0 && matches!(self.char_at(total - 1), Ok('\n'));
+        let starts = self.line_starts();
+        match (starts.get(line), starts.get(line + 1)) {
+            (Some(&start), Some(&next)) => Some((start, next - 1)),
+            (Some(&start), None) if ends_with_newline => Some((start, total - 1)),
+            (Some(&start), None) => Some((start, total)),
+            (None, _) if ends_with_newline && line == starts.len() => Some((total, total)),
+            _ => None,
+        }
+    }
+
     /// ギャップを指定位置に移動（内部用）
     fn move_gap_to_internal(&mut self, pos: usize) -> std::result::Result<(), BufferError> {
         if pos > self.len_bytes() {
@@ -664,6 +698,14 @@ mod tests {
         assert_eq!(gap_buffer.char_len(), 5);
     }
 
+    #[test]
+    fn test_empty_range_at_end() {
+        let mut gap_buffer = GapBuffer::from_str("abc");
+        assert_eq!(gap_buffer.substring(3, 3).unwrap(), "");
+        assert_eq!(gap_buffer.delete_range(3, 3).unwrap(), "");
+        assert_eq!(gap_buffer.to_string(), "abc");
+    }
+
     #[test]
     fn test_delete_char() {
         let mut gap_buffer = GapBuffer::from_str("Hello");
@@ -680,6 +722,32 @@ mod tests {
         assert_eq!(gap_buffer.get_text(), "aef");
     }
 
+    #[test]
+    fn line_range_counts_trailing_empty_line() {
+        let mut gap_buffer = GapBuffer::from_str("ab\ncde\n");
+        assert_eq!(gap_buffer.line_starts(), &[0, 3]);
+        assert_eq!(gap_buffer.line_range(0), Some((0, 2)));
+        assert_eq!(gap_buffer.line_range(1), Some((3, 6)));
+        assert_eq!(gap_buffer.line_range(2), Some((7, 7)));
+        assert_eq!(gap_buffer.line_range(3), None);
+
+        let mut gap_buffer = GapBuffer::from_str("x");
+        assert_eq!(gap_buffer.line_range(0), Some((0, 1)));
+        assert_eq!(gap_buffer.line_range(1), None);
+    }
+
+    #[test]
+    fn line_count_matches_line_range() {
+        assert_eq!(GapBuffer::new().line_count(), 1);
+        assert_eq!(GapBuffer::from_str("x").line_count(), 1);
+        assert_eq!(GapBuffer::from_str("ab\ncde\n").line_count(), 3);
+
+        // ギャップをまたいでも数える
+        let mut gap_buffer = GapBuffer::from_str("a\nb\nc");
+        gap_buffer.insert(2, '\n').unwrap();
+        assert_eq!(gap_buffer.line_count(), 4);
+    }
+
     #[test]
     fn test_line_start_positions() {
         let mut gap_buffer = GapBuffer::from_str("line1\nline2\nline3");
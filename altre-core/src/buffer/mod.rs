@@ -3,20 +3,26 @@
 //! テキストデータの管理、編集操作、カーソル位置管理を提供
 
 pub mod cursor;
+pub mod dirty_lines;
 pub mod editor;
+pub mod folding;
 pub mod gap_buffer;
 pub mod navigation;
 pub mod operations;
+pub mod overlay;
 
 // 公開API
 pub use crate::error::EditError;
 pub use cursor::CursorPosition;
+pub use dirty_lines::DirtyLines;
 pub use editor::{ChangeEvent, ChangeListener, EditOperations, TextEditor};
+pub use folding::{FoldRange, FoldState, FoldToggle};
 pub use gap_buffer::GapBuffer;
 pub use navigation::{
     NavigationAction, NavigationError, NavigationSystem, Position as NavigationPosition,
 };
 pub use operations::EditOperation;
+pub use overlay::{resolve_overlays, Overlay, OverlayId, OverlayKind, OverlaySet, OverlaySpan};
 
 use crate::error::Result;
 use std::collections::HashMap;
@@ -36,6 +36,8 @@ pub enum NavigationAction {
     MoveBufferEnd,
     MoveWordForward,
     MoveWordBackward,
+    MoveParagraphForward,
+    MoveParagraphBackward,
 }
 
 /// 行・列を含むカーソル位置。
@@ -446,6 +448,11 @@ impl NavigationSystem {
         self.extended.set(cursor);
     }
 
+    /// 上下移動で目標とする桁を設定
+    pub fn set_preferred_column(&mut self, column: usize) {
+        self.extended.preferred_column = Some(column);
+    }
+
     /// テキストとアクションに基づいてカーソルを移動する。
     pub fn navigate(
         &mut self,
@@ -519,6 +526,8 @@ impl NavigationSystem {
             NavigationAction::MoveBufferEnd => self.move_buffer_end(snapshot),
             NavigationAction::MoveWordForward => self.move_word_forward(snapshot),
             NavigationAction::MoveWordBackward => self.move_word_backward(snapshot),
+            NavigationAction::MoveParagraphForward => self.move_paragraph_forward(snapshot),
+            NavigationAction::MoveParagraphBackward => self.move_paragraph_backward(snapshot),
         }?;
 
         if moved {
@@ -776,6 +785,76 @@ impl NavigationSystem {
         self.extended.clear_preferred_column();
         Ok(moved)
     }
+
+    /// 次の段落の終わり（段落直後の空行の先頭、なければバッファ末尾）へ移動
+    ///
+    /// 連続した空行はひとつの区切りとして扱う。
+    fn move_paragraph_forward(&mut self, snapshot: &TextSnapshot) -> Result<bool, NavigationError> {
+        let line_count = snapshot.line_count();
+        let mut line = self.cursor.line;
+        while line < line_count && is_blank_line(snapshot, line) {
+            line += 1;
+        }
+        while line < line_count && !is_blank_line(snapshot, line) {
+            line += 1;
+        }
+
+        if line >= line_count {
+            let original = self.cursor.char_pos;
+            self.move_buffer_end(snapshot)?;
+            return Ok(self.cursor.char_pos != original);
+        }
+        self.move_to_line_start(snapshot, line)
+    }
+
+    /// 前の段落の始まり（段落直前の空行の先頭、なければバッファ先頭）へ移動
+    ///
+    /// 連続した空行はひとつの区切りとして扱う。
+    fn move_paragraph_backward(
+        &mut self,
+        snapshot: &TextSnapshot,
+    ) -> Result<bool, NavigationError> {
+        if self.cursor.char_pos == 0 {
+            return Ok(false);
+        }
+
+        // 行頭にいる場合は前の行から探す
+        let mut line = if self.cursor.column == 0 {
+            self.cursor.line.saturating_sub(1)
+        } else {
+            self.cursor.line
+        };
+        while line > 0 && is_blank_line(snapshot, line) {
+            line -= 1;
+        }
+        while line > 0 && !is_blank_line(snapshot, line) {
+            line -= 1;
+        }
+        self.move_to_line_start(snapshot, line)
+    }
+
+    fn move_to_line_start(
+        &mut self,
+        snapshot: &TextSnapshot,
+        line: usize,
+    ) -> Result<bool, NavigationError> {
+        let pos = snapshot
+            .line_start(line)
+            .ok_or_else(|| NavigationError::Internal("invalid line index".into()))?;
+        let moved = pos != self.cursor.char_pos;
+        self.cursor.char_pos = pos;
+        self.cursor.line = line;
+        self.cursor.column = 0;
+        self.extended.clear_preferred_column();
+        Ok(moved)
+    }
+}
+
+/// 空白文字だけの行（空行を含む）か
+fn is_blank_line(snapshot: &TextSnapshot, line: usize) -> bool {
+    let start = snapshot.line_start(line).unwrap_or(0);
+    let len = snapshot.line_length(line).unwrap_or(0);
+    (start..start + len).all(|idx| snapshot.char_at(idx).is_some_and(char::is_whitespace))
 }
 
 fn is_word_char(ch: char) -> bool {
@@ -854,6 +933,49 @@ mod tests {
         assert_eq!(nav.cursor().char_pos, 5);
     }
 
+    #[test]
+    fn paragraph_movement_treats_consecutive_blank_lines_as_one_boundary() {
+        let mut nav = NavigationSystem::new();
+        let text = "one\ntwo\n\n\nthree\n  \nfour\nfive";
+
+        // 段落の直後の空行へ
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphForward)
+            .unwrap());
+        assert_eq!((nav.cursor().line, nav.cursor().column), (2, 0));
+
+        // 連続した空行を越えて次の段落の直後（空白だけの行）へ
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphForward)
+            .unwrap());
+        assert_eq!((nav.cursor().line, nav.cursor().column), (5, 0));
+
+        // 最後の段落の後はバッファ末尾
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphForward)
+            .unwrap());
+        assert_eq!(nav.cursor().char_pos, text.chars().count());
+        assert!(!nav
+            .navigate(text, NavigationAction::MoveParagraphForward)
+            .unwrap());
+
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphBackward)
+            .unwrap());
+        assert_eq!(nav.cursor().line, 5);
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphBackward)
+            .unwrap());
+        assert_eq!(nav.cursor().line, 3);
+        assert!(nav
+            .navigate(text, NavigationAction::MoveParagraphBackward)
+            .unwrap());
+        assert_eq!(nav.cursor().char_pos, 0);
+        assert!(!nav
+            .navigate(text, NavigationAction::MoveParagraphBackward)
+            .unwrap());
+    }
+
     #[test]
     fn line_navigation() {
         let mut nav = NavigationSystem::new();
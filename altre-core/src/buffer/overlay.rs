@@ -0,0 +1,351 @@
+//! オーバーレイ（範囲に付与する表示属性）
+//!
+//! 文字位置の範囲に種類と優先度を持たせ、編集に追従させる。重なった範囲は優先度の高いものが表示される
+
+use std::collections::BinaryHeap;
+
+/// オーバーレイの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayKind {
+    /// TODO/FIXME などのキーワード
+    Keyword,
+    /// マークによる選択範囲
+    Selection,
+    /// 対応する括弧
+    MatchingParen,
+    /// 検索・置換のマッチ
+    Search,
+    /// 現在のマッチ
+    CurrentMatch,
+}
+
+impl OverlayKind {
+    /// 種類ごとの既定の優先度
+    pub fn default_priority(self) -> i32 {
+        match self {
+            OverlayKind::Keyword => 5,
+            OverlayKind::Selection => 10,
+            OverlayKind::MatchingParen => 15,
+            OverlayKind::Search => 20,
+            OverlayKind::CurrentMatch => 30,
+        }
+    }
+}
+
+/// オーバーレイの識別子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverlayId(u64);
+
+/// 文字位置の範囲 `start..end` に付与する属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlay {
+    /// 開始位置（文字）
+    pub start: usize,
+    /// 終了位置（文字、排他的）
+    pub end: usize,
+    /// 種類
+    pub kind: OverlayKind,
+    /// 優先度（大きいほど手前に表示）
+    pub priority: i32,
+}
+
+impl Overlay {
+    /// 種類の既定優先度でオーバーレイを作成
+    pub fn new(start: usize, end: usize, kind: OverlayKind) -> Self {
+        Self {
+            start,
+            end,
+            kind,
+            priority: kind.default_priority(),
+        }
+    }
+
+    /// 優先度を指定
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// 重なりを解消した表示用の範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlaySpan {
+    /// 開始位置（文字）
+    pub start: usize,
+    /// 終了位置（文字、排他的）
+    pub end: usize,
+    /// 表示する種類
+    pub kind: OverlayKind,
+}
+
+/// バッファに付与されたオーバーレイの集合
+#[derive(Debug, Clone, Default)]
+pub struct OverlaySet {
+    entries: Vec<(OverlayId, Overlay)>,
+    next_id: u64,
+}
+
+impl OverlaySet {
+    /// 空の集合を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// オーバーレイが存在しないか
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// オーバーレイを追加（空の範囲は追加しない）
+    pub fn add(&mut self, overlay: Overlay) -> Option<OverlayId> {
+        if overlay.start >= overlay.end {
+            return None;
+        }
+        let id = OverlayId(self.next_id);
+        self.next_id += 1;
+        self.entries.push((id, overlay));
+        Some(id)
+    }
+
+    /// オーバーレイを削除
+    pub fn remove(&mut self, id: OverlayId) -> Option<Overlay> {
+        let index = self.entries.iter().position(|(entry, _)| *entry == id)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// 指定したオーバーレイを取得
+    pub fn get(&self, id: OverlayId) -> Option<&Overlay> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| *entry == id)
+            .map(|(_, overlay)| overlay)
+    }
+
+    /// 指定した種類のオーバーレイをすべて削除
+    pub fn remove_kind(&mut self, kind: OverlayKind) {
+        self.entries.retain(|(_, overlay)| overlay.kind != kind);
+    }
+
+    /// すべて削除
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 追加順に走査
+    pub fn iter(&self) -> impl Iterator<Item = &Overlay> {
+        self.entries.iter().map(|(_, overlay)| overlay)
+    }
+
+    /// `position` への `len` 文字の挿入を反映
+    ///
+    /// 開始位置での挿入は範囲の前へ、終了位置での挿入は範囲の外へ入る。
+    pub fn on_insert(&mut self, position: usize, len: usize) {
+        for (_, overlay) in &mut self.entries {
+            if overlay.start >= position {
+                overlay.start += len;
+            }
+            if overlay.end > position {
+                overlay.end += len;
+            }
+        }
+    }
+
+    /// `start..end` の削除を反映（空になった範囲は取り除く）
+    pub fn on_delete(&mut self, start: usize, end: usize) {
+        let len = end.saturating_sub(start);
+        let shift = |pos: usize| {
+            if pos <= start {
+                pos
+            } else if pos >= end {
+                pos - len
+            } else {
+                start
+            }
+        };
+        self.entries.retain_mut(|(_, overlay)| {
+            overlay.start = shift(overlay.start);
+            overlay.end = shift(overlay.end);
+            overlay.start < overlay.end
+        });
+    }
+}
+
+/// 重なったオーバーレイを優先度に従って重なりのない範囲へ分解する
+///
+/// 同じ優先度では後に並んだものを優先する。境界を先頭から 1 度走査し、
+/// 有効なオーバーレイを優先度順のヒープに保つ（終わったものは先頭に来たときに取り除く）。
+pub fn resolve_overlays<'a>(overlays: impl IntoIterator<Item = &'a Overlay>) -> Vec<OverlaySpan> {
+    let overlays: Vec<&Overlay> = overlays
+        .into_iter()
+        .filter(|overlay| overlay.start < overlay.end)
+        .collect();
+    if overlays.is_empty() {
+        return Vec::new();
+    }
+    let mut bounds: Vec<usize> = overlays
+        .iter()
+        .flat_map(|overlay| [overlay.start, overlay.end])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+    let mut by_start: Vec<usize> = (0..overlays.len()).collect();
+    by_start.sort_by_key(|&index| overlays[index].start);
+
+    let mut active: BinaryHeap<(i32, usize)> = BinaryHeap::new();
+    let mut next = 0usize;
+    let mut spans: Vec<OverlaySpan> = Vec::new();
+    for window in bounds.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        while let Some(&index) = by_start.get(next) {
+            if overlays[index].start > start {
+                break;
+            }
+            active.push((overlays[index].priority, index));
+            next += 1;
+        }
+        while active
+            .peek()
+            .is_some_and(|&(_, index)| overlays[index].end <= start)
+        {
+            active.pop();
+        }
+        let Some(&(_, winner)) = active.peek() else {
+            continue;
+        };
+        let kind = overlays[winner].kind;
+        match spans.last_mut() {
+            Some(last) if last.end == start && last.kind == kind => last.end = end,
+            _ => spans.push(OverlaySpan { start, end, kind }),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_shift_with_edits_before_them() {
+        let mut set = OverlaySet::new();
+        let id = set.add(Overlay::new(5, 8, OverlayKind::Search)).unwrap();
+
+        set.on_insert(0, 3);
+        assert_eq!(
+            (set.get(id).unwrap().start, set.get(id).unwrap().end),
+            (8, 11)
+        );
+
+        set.on_insert(11, 2);
+        assert_eq!(
+            (set.get(id).unwrap().start, set.get(id).unwrap().end),
+            (8, 11)
+        );
+
+        set.on_insert(9, 1);
+        assert_eq!(
+            (set.get(id).unwrap().start, set.get(id).unwrap().end),
+            (8, 12)
+        );
+
+        set.on_delete(0, 4);
+        assert_eq!(
+            (set.get(id).unwrap().start, set.get(id).unwrap().end),
+            (4, 8)
+        );
+
+        set.on_delete(6, 10);
+        assert_eq!(
+            (set.get(id).unwrap().start, set.get(id).unwrap().end),
+            (4, 6)
+        );
+
+        set.on_delete(3, 7);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn higher_priority_wins_where_overlays_overlap() {
+        let selection = Overlay::new(0, 10, OverlayKind::Selection);
+        let search = Overlay::new(3, 6, OverlayKind::Search);
+        let spans = resolve_overlays([&selection, &search]);
+        assert_eq!(
+            spans,
+            vec![
+                OverlaySpan {
+                    start: 0,
+                    end: 3,
+                    kind: OverlayKind::Selection
+                },
+                OverlaySpan {
+                    start: 3,
+                    end: 6,
+                    kind: OverlayKind::Search
+                },
+                OverlaySpan {
+                    start: 6,
+                    end: 10,
+                    kind: OverlayKind::Selection
+                },
+            ]
+        );
+
+        let raised = selection.with_priority(50);
+        let spans = resolve_overlays([&raised, &search]);
+        assert_eq!(
+            spans,
+            vec![OverlaySpan {
+                start: 0,
+                end: 10,
+                kind: OverlayKind::Selection
+            }]
+        );
+    }
+
+    #[test]
+    fn equal_priority_prefers_later_overlay() {
+        let first = Overlay::new(0, 4, OverlayKind::Search);
+        let second = Overlay::new(2, 6, OverlayKind::Selection).with_priority(20);
+        let spans = resolve_overlays([&first, &second]);
+        assert_eq!(
+            spans,
+            vec![
+                OverlaySpan {
+                    start: 0,
+                    end: 2,
+                    kind: OverlayKind::Search
+                },
+                OverlaySpan {
+                    start: 2,
+                    end: 6,
+                    kind: OverlayKind::Selection
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lower_overlay_resumes_after_higher_one_ends() {
+        let keyword = Overlay::new(0, 12, OverlayKind::Keyword);
+        let selection = Overlay::new(2, 10, OverlayKind::Selection);
+        let current = Overlay::new(4, 6, OverlayKind::CurrentMatch);
+        let gap = Overlay::new(20, 22, OverlayKind::Search);
+        let spans: Vec<(usize, usize, OverlayKind)> =
+            resolve_overlays([&current, &gap, &selection, &keyword])
+                .into_iter()
+                .map(|span| (span.start, span.end, span.kind))
+                .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 2, OverlayKind::Keyword),
+                (2, 4, OverlayKind::Selection),
+                (4, 6, OverlayKind::CurrentMatch),
+                (6, 10, OverlayKind::Selection),
+                (10, 12, OverlayKind::Keyword),
+                (20, 22, OverlayKind::Search),
+            ]
+        );
+        assert!(resolve_overlays(&[]).is_empty());
+    }
+}
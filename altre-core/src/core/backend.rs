@@ -2,26 +2,65 @@
 //!
 //! アプリケーション全体の状態管理とメインループを実装
 
-use crate::alisp::{HostBridge, Interpreter};
-use crate::buffer::{CursorPosition, EditOperations, NavigationAction, TextEditor};
-use crate::editor::{edit_utils, HistoryCommandKind, HistoryManager, HistoryStack, KillRing};
+use crate::alisp::highlight::syntax_spans;
+use crate::alisp::reader::{syntax_context_at, SyntaxContext};
+use crate::alisp::{
+    indent as alisp_indent, sexp as alisp_sexp, HostBridge, Interpreter, OptionValue,
+};
+use crate::buffer::{
+    resolve_overlays, CursorPosition, EditOperations, FoldState, FoldToggle, NavigationAction,
+    Overlay, OverlayKind, OverlaySet, TextEditor,
+};
+use crate::core::command_registry::CommandRegistry;
+use crate::core::options::EditorOptions;
+use crate::core::session::{default_session_path, SessionBuffer, SessionData};
+use crate::editor::auto_completion::is_word_char;
+use crate::editor::{
+    edit_utils, AutoCompletion, CompletionPopup, HistoryCommandKind, HistoryManager, HistoryStack,
+    KillRing,
+};
 use crate::error::{AltreError, FileError, Result};
-use crate::file::{expand_path, operations::FileOperationManager, FileBuffer};
+use crate::file::{
+    expand_path, expand_wildcards, has_wildcards, operations::FileOperationManager, same_file_path,
+    split_path_position, CodingSystem, FileBuffer, FileChangeTracker, FileInfo, FileSaver,
+};
 use crate::input::commands::{Command, CommandProcessor};
-use crate::input::keybinding::{Action, Key, KeyProcessResult, ModernKeyMap};
+use crate::input::keybinding::{Action, Key, KeyMapError, KeyProcessResult, ModernKeyMap};
 use crate::minibuffer::{MinibufferAction, MinibufferSystem, SystemEvent, SystemResponse};
 use crate::search::{
-    HighlightKind, QueryReplaceController, ReplaceProgress, ReplaceSummary, SearchController,
-    SearchDirection, SearchHighlight, SearchUiState,
+    count_matches, filter_lines, occur_matches, HighlightKind, QueryReplaceController,
+    ReplaceProgress, ReplaceSummary, SearchController, SearchDirection, SearchHighlight,
+    SearchUiState,
+};
+use crate::ui::{
+    buffer_to_html, is_problematic_char, ControlCharDisplay, GuiThemeConfig, GuiThemeKey,
+    KeywordHighlighter, LineWrap, ParenHighlightMode, ParenHighlighter, SplitOrientation,
+    ThemeManager, ViewportState, WindowManager, WrapColumn, WrapMode,
 };
-use crate::ui::{GuiThemeConfig, GuiThemeKey, SplitOrientation, ViewportState, WindowManager};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 const DEFAULT_TAB_WIDTH: usize = 4;
+/// 行の中央寄せなどで使う桁（Emacs の `fill-column` の既定値）
+const DEFAULT_FILL_COLUMN: usize = 70;
+/// ページスクロール時に前画面から持ち越す行数（Emacs の `next-screen-context-lines`）
+const DEFAULT_NEXT_SCREEN_CONTEXT_LINES: usize = 2;
+/// 分割挿入に切り替える文字数のしきい値
+const DEFAULT_LARGE_INSERT_THRESHOLD: usize = 1024 * 1024;
+/// 分割挿入で1周期に挿入する文字数
+const LARGE_INSERT_CHUNK_CHARS: usize = 64 * 1024;
+/// 数引数で文字を繰り返し挿入できる上限
+const MAX_REPEAT_INSERT_COUNT: usize = 8 * 1024 * 1024;
+/// describe-function の結果を表示するバッファ名
+const HELP_BUFFER_NAME: &str = "*Help*";
+/// debug-on-error で alisp のバックトレースを表示するバッファ名
+const BACKTRACE_BUFFER_NAME: &str = "*Backtrace*";
+/// occur の一致箇所を一覧表示するバッファ名
+const OCCUR_BUFFER_NAME: &str = "*Occur*";
 
 /// デバッグ出力マクロ
 macro_rules! debug_log {
@@ -38,6 +77,25 @@ struct OpenBuffer {
     file: FileBuffer,
     cursor: CursorPosition,
     history: HistoryStack,
+    folds: FoldState,
+    overlays: OverlaySet,
+    /// `cd` で設定した既定ディレクトリ
+    default_directory: Option<PathBuf>,
+    /// C-x f で設定した fill-column（未設定なら全体の既定値）
+    fill_column: Option<usize>,
+    /// occur の一覧バッファなら、一覧の元になった検索
+    occur: Option<OccurSource>,
+}
+
+/// occur バッファの一覧を作り直すための情報
+#[derive(Clone)]
+struct OccurSource {
+    /// 一致を探したバッファの ID
+    buffer_id: usize,
+    /// 検索語
+    pattern: String,
+    /// 一覧の各行（見出し行の次から）に対応する一致の開始位置（文字単位）
+    positions: Vec<usize>,
 }
 
 impl OpenBuffer {
@@ -47,6 +105,11 @@ impl OpenBuffer {
             cursor: CursorPosition::new(),
             file,
             history: HistoryStack::new(),
+            folds: FoldState::new(),
+            overlays: OverlaySet::new(),
+            default_directory: None,
+            fill_column: None,
+            occur: None,
         }
     }
 
@@ -61,6 +124,16 @@ impl OpenBuffer {
     fn is_modified(&self) -> bool {
         self.file.is_modified()
     }
+
+    /// 既定ディレクトリ（未設定ならファイルのあるディレクトリ）
+    fn default_directory(&self) -> Option<PathBuf> {
+        self.default_directory.clone().or_else(|| {
+            self.path()
+                .and_then(|path| path.parent())
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map(Path::to_path_buf)
+        })
+    }
 }
 
 #[derive(Default)]
@@ -83,6 +156,40 @@ impl ReplaceSession {
     }
 }
 
+/// バッファで Tab キーを押したときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TabBehavior {
+    /// `indent-for-tab-command` を実行する
+    #[default]
+    Indent,
+    /// タブ文字をそのまま挿入する
+    InsertTab,
+}
+
+/// 保存に成功したときのメッセージ表示
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SaveMessageMode {
+    /// 保存先を含むメッセージを表示する
+    #[default]
+    Verbose,
+    /// 表示しない（エラーは表示する）
+    Silent,
+    /// 書式を指定して表示する（`{path}` と `{name}` を保存先で置き換える）
+    Format(String),
+}
+
+/// 既に開いているファイルを find-file で開き直したときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReopenFileBehavior {
+    /// 既存のバッファに切り替える
+    #[default]
+    Switch,
+    /// ディスクから読み直す（未保存の変更があれば破棄してよいか確認する）
+    Reload,
+    /// 既存のバッファに切り替え、既に開いていることを警告として表示する
+    Warn,
+}
+
 /// メインアプリケーション構造体
 ///
 /// 全てのコンポーネントを統合し、アプリケーションのライフサイクルを管理
@@ -97,6 +204,8 @@ pub struct Backend {
     minibuffer: MinibufferSystem,
     /// キーマップ
     keymap: Rc<RefCell<ModernKeyMap>>,
+    /// 外部から登録したコマンド
+    command_registry: Rc<RefCell<CommandRegistry>>,
     /// コマンドプロセッサー
     command_processor: CommandProcessor,
     /// 検索コントローラ
@@ -115,6 +224,70 @@ pub struct Backend {
     last_yank_range: Option<(usize, usize)>,
     /// ウィンドウ管理
     window_manager: WindowManager,
+    /// 他のウィンドウに表示中の非カレントバッファ（描画用）
+    other_editors: HashMap<usize, TextEditor>,
+    /// 検索結果などからの移動先を別ウィンドウに表示するか
+    jump_in_other_window: bool,
+    /// 行番号プロンプトの移動先を別ウィンドウにするか
+    goto_line_in_other_window: bool,
+    /// バッファでの Tab キーの動作
+    tab_behavior: TabBehavior,
+    /// タブストップの間隔（桁数）
+    tab_width: usize,
+    /// center-line で中央寄せの基準にする桁
+    fill_column: usize,
+    /// tabify で行頭の空白だけを変換するか（既定は有効）
+    tabify_leading_only: bool,
+    /// 最終行での C-n で改行を追加するか（既定は無効）
+    next_line_add_newlines: bool,
+    /// 空でない行の最後の文字の後ろ（行末）にカーソルを置けるか（既定は有効）
+    virtual_end_of_line: bool,
+    /// alisp の文字列・コメント内でも自動字下げするか（既定は無効）
+    alisp_indent_in_literals: bool,
+    /// リージョンがアクティブなとき、入力やヤンクでリージョンを置き換えるか（既定は無効）
+    delete_selection: bool,
+    /// 行を折り返す単位
+    wrap_mode: WrapMode,
+    /// 行を折り返す桁（ウィンドウ幅または fill-column）
+    wrap_column: WrapColumn,
+    /// fill-column で折り返すとき本文を中央に寄せるか
+    wrap_centered: bool,
+    /// 長い行を折り返さずに切り詰めて表示するか（既定は有効。無効なら折り返す）
+    truncate_lines: bool,
+    /// 制御文字と幅 0 の見えない文字の表示方法
+    control_char_display: ControlCharDisplay,
+    /// 描画と HTML 書き出しに使うテーマ名
+    theme_name: String,
+    /// find-problematic-chars が直前に移動した位置（次の検索はその文字を飛ばす）
+    problematic_char_found_at: Option<usize>,
+    /// TODO/FIXME などのキーワードハイライト
+    keyword_highlighter: KeywordHighlighter,
+    /// 対応する括弧のハイライト
+    paren_highlighter: ParenHighlighter,
+    /// モードラインを隠して画面全体で編集する集中モードか
+    distraction_free: bool,
+    /// 集中モードでミニバッファ領域も隠すか（既定は無効）
+    distraction_free_hides_minibuffer: bool,
+    /// ファイルに紐づかないバッファの既定ディレクトリ（未設定ならカレントディレクトリ）
+    initial_directory: Option<PathBuf>,
+    /// 保存成功時のメッセージ表示
+    save_message_mode: SaveMessageMode,
+    /// 存在しないファイルを開くとき作成を確認するか（既定は無効）
+    confirm_new_file: bool,
+    /// find-file でワイルドカードを展開するか（既定は有効）
+    find_file_wildcards: bool,
+    /// 開いているファイルを開き直したときの動作
+    reopen_file_behavior: ReopenFileBehavior,
+    /// 書き込み禁止のファイルのバッファで読み取り専用を解除するとき、権限の変更を確認するか（既定は無効）
+    chmod_on_toggle_read_only: bool,
+    /// init.al の `set-option` で変更する設定（セッションの自動保存・復元など）
+    options: Rc<RefCell<EditorOptions>>,
+    /// セッションファイルの場所（既定は `~/.altre/session.json`）
+    session_path: Option<PathBuf>,
+    /// y/n 確認の回答待ちの操作
+    pending_confirmation: Option<PendingConfirmation>,
+    /// C-q の直後で次のキーをそのまま挿入する状態か
+    quoted_insert_pending: bool,
     /// GUI 向けのカラーテーマ
     gui_theme: Rc<RefCell<GuiThemeConfig>>,
     /// 開いているバッファ一覧
@@ -123,14 +296,91 @@ pub struct Backend {
     current_buffer_id: Option<usize>,
     /// 直前にアクティブだったバッファID
     last_buffer_id: Option<usize>,
+    /// 表示したバッファIDの履歴（先頭が最も新しい）
+    buffer_visit_order: Vec<usize>,
     /// バッファID払い出し用カウンタ
     next_buffer_id: usize,
     /// `C-l` の再配置サイクル
     recenter_step: u8,
+    /// ページスクロールで重ねて表示する行数
+    next_screen_context_lines: usize,
+    /// スクロール量（0: ページ単位、正: 指定行数、負: カーソルを中央に保つ）
+    scroll_step: isize,
+    /// 分割挿入に切り替える文字数のしきい値
+    large_insert_threshold: usize,
+    /// C-u で入力中の数引数
+    prefix_argument: Option<PrefixArgument>,
+    /// 分割挿入中のテキスト
+    pending_insert: Option<PendingInsert>,
+    /// アイドル時の自動補完
+    auto_completion: AutoCompletion,
     /// Undo/Redo 管理
     history: HistoryManager,
 }
 
+/// C-u による数引数の入力状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PrefixArgument {
+    /// C-u を押した回数（数字がなければ 4 のべき乗を値にする）
+    presses: u32,
+    /// 入力された数字
+    digits: Option<usize>,
+    /// 続く数字を数引数として読むか
+    reading_digits: bool,
+}
+
+impl PrefixArgument {
+    fn new() -> Self {
+        Self {
+            presses: 1,
+            digits: None,
+            reading_digits: true,
+        }
+    }
+
+    /// 数引数の値
+    fn count(&self) -> usize {
+        self.digits
+            .unwrap_or_else(|| 4usize.saturating_pow(self.presses))
+    }
+
+    /// エコー領域に表示する入力途中の表記
+    fn label(&self) -> String {
+        match self.digits {
+            Some(digits) => format!("C-u {}-", digits),
+            None => format!("{}-", vec!["C-u"; self.presses as usize].join(" ")),
+        }
+    }
+}
+
+/// 分割して挿入中の大きなテキスト
+#[derive(Debug)]
+struct PendingInsert {
+    /// 改行を正規化した挿入テキスト
+    text: String,
+    /// 挿入済みのバイト位置
+    offset: usize,
+    /// 挿入開始位置（文字）
+    start: usize,
+    /// 挿入済みの文字数
+    inserted_chars: usize,
+    /// 総文字数
+    total_chars: usize,
+    /// delete-selection モードで置き換えたリージョンの文字列（取り消し時に戻す）
+    replaced: String,
+}
+
+/// y/n 確認の回答を待っている操作
+#[derive(Debug, Clone)]
+enum PendingConfirmation {
+    /// 存在しないファイルの新規作成（find-file の入力そのまま）
+    CreateFile(String),
+    /// 未保存の変更を破棄して開いているファイルを読み直す（find-file の入力そのまま）
+    ReloadFile(String),
+    /// 書き込み禁止のファイルに書き込み権限を与えてバッファの読み取り専用を解除する
+    MakeFileWritable(PathBuf),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum KillContext {
     None,
@@ -144,6 +394,25 @@ enum KillMerge {
     Prepend,
 }
 
+/// 再配置でカーソル行を置くウィンドウ内の位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecenterPlacement {
+    Center,
+    Top,
+    Bottom,
+}
+
+impl RecenterPlacement {
+    /// カーソル行をこの位置に置くときのウィンドウ先頭行
+    fn top_line(self, cursor_line: usize, height: usize) -> usize {
+        match self {
+            RecenterPlacement::Center => cursor_line.saturating_sub(height / 2),
+            RecenterPlacement::Top => cursor_line,
+            RecenterPlacement::Bottom => cursor_line.saturating_add(1).saturating_sub(height),
+        }
+    }
+}
+
 /// 描画用のメタデータ
 #[derive(Debug, Clone)]
 pub struct RenderMetadata {
@@ -151,15 +420,47 @@ pub struct RenderMetadata {
     pub status_label: String,
     /// バッファが未保存かどうか
     pub is_modified: bool,
+    /// 文字コードと改行コード（例: `UTF-8/LF`）
+    pub coding_label: String,
     /// 検索・置換・選択ハイライト
     pub highlights: Vec<SearchHighlight>,
     /// 検索UI状態
     pub search_ui: Option<SearchUiState>,
+    /// 自動補完ポップアップ
+    pub completion_popup: Option<CompletionPopup>,
+    /// 行を折り返す単位
+    pub wrap_mode: WrapMode,
+    /// 行を折り返す桁
+    pub wrap_column: WrapColumn,
+    /// fill-column で折り返すとき本文を中央に寄せるか
+    pub wrap_centered: bool,
+    /// 長い行を折り返さずに切り詰めて表示するか
+    pub truncate_lines: bool,
+    /// 制御文字と幅 0 の見えない文字の表示方法
+    pub control_char_display: ControlCharDisplay,
+    /// 描画に使うテーマ名
+    pub theme_name: String,
+    /// モードラインを表示するか
+    pub mode_line_visible: bool,
+    /// 入力中以外もミニバッファ領域を表示するか
+    pub minibuffer_visible: bool,
+}
+
+impl RenderMetadata {
+    /// テキストエリアの折り返し設定（切り詰めて表示するなら `None`）
+    pub fn line_wrap(&self) -> Option<LineWrap> {
+        (!self.truncate_lines).then_some(LineWrap {
+            mode: self.wrap_mode,
+            column: self.wrap_column,
+            centered: self.wrap_centered,
+        })
+    }
 }
 
 /// レンダラーへ引き渡す参照群
 pub struct RenderView<'a> {
     pub editor: &'a TextEditor,
+    pub other_editors: &'a HashMap<usize, TextEditor>,
     pub minibuffer: &'a MinibufferSystem,
     pub window_manager: &'a mut WindowManager,
 }
@@ -167,12 +468,18 @@ pub struct RenderView<'a> {
 impl Backend {
     /// 新しいアプリケーションインスタンスを作成
     pub fn new() -> Result<Self> {
+        Self::with_command_registry(CommandRegistry::new())
+    }
+
+    /// 登録コマンドを持たせて作成（初期設定の `bind-key` から登録コマンドを割り当てられる）
+    pub fn with_command_registry(registry: CommandRegistry) -> Result<Self> {
         let mut app = Backend {
             running: true,
             initialized: true,
             editor: TextEditor::new(),
             minibuffer: MinibufferSystem::new(),
             keymap: Rc::new(RefCell::new(ModernKeyMap::new())),
+            command_registry: Rc::new(RefCell::new(registry)),
             command_processor: CommandProcessor::new(),
             search: SearchController::new(),
             replace: ReplaceSession::new(),
@@ -182,15 +489,57 @@ impl Backend {
             kill_context: KillContext::None,
             last_yank_range: None,
             window_manager: WindowManager::new(),
+            other_editors: HashMap::new(),
+            jump_in_other_window: true,
+            goto_line_in_other_window: false,
+            tab_behavior: TabBehavior::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
+            fill_column: DEFAULT_FILL_COLUMN,
+            tabify_leading_only: true,
+            next_line_add_newlines: false,
+            virtual_end_of_line: true,
+            alisp_indent_in_literals: false,
+            delete_selection: false,
+            wrap_mode: WrapMode::default(),
+            wrap_column: WrapColumn::default(),
+            wrap_centered: false,
+            truncate_lines: true,
+            control_char_display: ControlCharDisplay::default(),
+            theme_name: "dark".to_string(),
+            problematic_char_found_at: None,
+            keyword_highlighter: KeywordHighlighter::new(),
+            paren_highlighter: ParenHighlighter::new(),
+            distraction_free: false,
+            distraction_free_hides_minibuffer: false,
+            initial_directory: None,
+            save_message_mode: SaveMessageMode::default(),
+            confirm_new_file: false,
+            find_file_wildcards: true,
+            reopen_file_behavior: ReopenFileBehavior::default(),
+            chmod_on_toggle_read_only: false,
+            options: Rc::new(RefCell::new(EditorOptions::new())),
+            session_path: default_session_path(),
+            pending_confirmation: None,
+            quoted_insert_pending: false,
             gui_theme: Rc::new(RefCell::new(GuiThemeConfig::new())),
             buffers: Vec::new(),
             current_buffer_id: None,
             last_buffer_id: None,
+            buffer_visit_order: Vec::new(),
             next_buffer_id: 0,
             recenter_step: 0,
+            next_screen_context_lines: DEFAULT_NEXT_SCREEN_CONTEXT_LINES,
+            scroll_step: 0,
+            large_insert_threshold: DEFAULT_LARGE_INSERT_THRESHOLD,
+            prefix_argument: None,
+            pending_insert: None,
+            auto_completion: AutoCompletion::new(),
             history: HistoryManager::new(),
         };
         app.history.bind_editor(&mut app.editor);
+        for name in app.command_registry.borrow().names() {
+            app.minibuffer.add_command(name);
+        }
 
         app.initialize_default_buffer()?;
         app.load_initial_configuration()?;
@@ -216,18 +565,28 @@ impl Backend {
     /// 描画に必要なメタデータを取得
     pub fn render_metadata(&self) -> RenderMetadata {
         let search_ui = self.search.ui_state().cloned();
-        let mut highlights = Vec::new();
-        highlights.extend_from_slice(self.search.highlights());
-        highlights.extend(self.replace.highlights.iter().cloned());
-        highlights.extend(self.selection_highlights());
+        let highlights = self.overlay_highlights();
 
         let (status_label, is_modified) = self.status_line_data();
 
         RenderMetadata {
             status_label,
             is_modified,
+            coding_label: self
+                .current_buffer()
+                .map(|buffer| buffer.file.coding_label())
+                .unwrap_or_default(),
             highlights,
             search_ui,
+            completion_popup: self.auto_completion.popup().cloned(),
+            wrap_mode: self.wrap_mode,
+            wrap_column: self.wrap_column,
+            wrap_centered: self.wrap_centered,
+            truncate_lines: self.truncate_lines,
+            control_char_display: self.control_char_display,
+            theme_name: self.theme_name.clone(),
+            mode_line_visible: !self.distraction_free,
+            minibuffer_visible: !(self.distraction_free && self.distraction_free_hides_minibuffer),
         }
     }
 
@@ -235,6 +594,7 @@ impl Backend {
     pub fn render_view(&mut self) -> RenderView<'_> {
         RenderView {
             editor: &self.editor,
+            other_editors: &self.other_editors,
             minibuffer: &self.minibuffer,
             window_manager: &mut self.window_manager,
         }
@@ -314,35 +674,90 @@ impl Backend {
             ));
         }
 
-        let target_index = line.saturating_sub(1);
-        let text = self.editor.to_string();
-        let chars: Vec<char> = text.chars().collect();
-
-        let mut char_pos = chars.len();
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.editor.move_cursor_to_line(line)?;
+        self.ensure_cursor_visible();
+        // 行数を超える指定は最終行に丸められるため、実際に移動した行を表示する
+        self.show_info_message(format!("{} 行目へ移動", self.editor.cursor().line + 1));
+        Ok(())
+    }
 
-        if target_index == 0 {
-            char_pos = 0;
-        } else {
-            let mut current_line = 0usize;
-            for (idx, ch) in chars.iter().enumerate() {
-                if *ch == '\n' {
-                    current_line += 1;
-                    if current_line == target_index {
-                        char_pos = idx + 1;
-                        break;
-                    }
-                }
-            }
+    /// 指定した文字位置（1始まり）へ移動（バッファ末尾を超える位置は末尾へ）
+    pub fn goto_char(&mut self, position: usize) -> Result<()> {
+        if position == 0 {
+            return Err(AltreError::Application(
+                "文字位置は1以上を指定してください".to_string(),
+            ));
         }
 
+        let char_pos = (position - 1).min(self.editor.to_string().chars().count());
         self.reset_kill_context();
         self.reset_recenter_cycle();
         self.editor.move_cursor_to_char(char_pos)?;
         self.ensure_cursor_visible();
-        self.show_info_message(format!("{} 行目へ移動", line));
+        self.show_info_message(format!("{} 文字目へ移動", char_pos + 1));
+        Ok(())
+    }
+
+    /// 現在行の指定した表示桁（1始まり）へ移動
+    ///
+    /// タブと全角文字は表示幅で数える。行の表示幅が足りなければ行末へ、
+    /// 桁が文字の途中に当たればその文字の後ろへ移動する。
+    pub fn goto_column(&mut self, column: usize) -> Result<()> {
+        if column == 0 {
+            return Err(AltreError::Application(
+                "表示桁は1以上を指定してください".to_string(),
+            ));
+        }
+
+        let cursor = *self.editor.cursor();
+        let text = self.editor.to_string();
+        let line = text.split('\n').nth(cursor.line).unwrap_or("");
+        let (index, reached) =
+            edit_utils::char_index_at_display_column(line, column - 1, self.tab_width);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.editor
+            .move_cursor_to_char(cursor.char_pos - cursor.column + index)?;
+        self.ensure_cursor_visible();
+        if reached + 1 == column {
+            self.show_info_message(format!("{} 桁目へ移動", column));
+        } else {
+            self.show_info_message(format!(
+                "{} 桁目へ移動しました（{} 桁目は指定できません）",
+                reached + 1,
+                column
+            ));
+        }
         Ok(())
     }
 
+    /// カーソルの表示桁（0始まり。タブと全角文字は表示幅で数える）
+    fn cursor_display_column(&self) -> usize {
+        let cursor = self.editor.cursor();
+        let text = self.editor.to_string();
+        let line = text.split('\n').nth(cursor.line).unwrap_or("");
+        let prefix: String = line.chars().take(cursor.column).collect();
+        edit_utils::display_width(&prefix, self.tab_width)
+    }
+
+    fn start_goto_column_prompt(&mut self) {
+        let text = self.editor.to_string();
+        let line = text
+            .split('\n')
+            .nth(self.editor.cursor().line)
+            .unwrap_or("");
+        let current = self.cursor_display_column();
+        let line_end = edit_utils::display_width(line, self.tab_width);
+        if let Err(err) = self.minibuffer.start_goto_column(current + 1, line_end + 1) {
+            self.show_error_message(AltreError::Application(format!(
+                "ミニバッファの初期化に失敗しました: {}",
+                err
+            )));
+        }
+    }
+
     /// カーソル位置を取得
     pub fn get_cursor_position(&self) -> &CursorPosition {
         self.editor.cursor()
@@ -352,7 +767,6 @@ impl Backend {
         let id = self.allocate_buffer_id();
         let file_buffer = FileBuffer::new_empty("*scratch*".to_string());
         self.buffers.push(OpenBuffer::new(id, file_buffer));
-        self.current_buffer_id = Some(id);
         self.load_buffer_by_id(id, false)?;
         Ok(())
     }
@@ -368,6 +782,8 @@ impl Backend {
         interpreter.runtime_mut().set_host(Box::new(KeymapHost::new(
             Rc::clone(&self.keymap),
             Rc::clone(&self.gui_theme),
+            Rc::clone(&self.command_registry),
+            Rc::clone(&self.options),
         )));
         interpreter.set_load_root(default_root.clone());
 
@@ -386,6 +802,8 @@ impl Backend {
         interpreter.eval_file(&default_init).map_err(|err| {
             AltreError::Application(format!("デフォルト設定の読み込みに失敗しました: {}", err))
         })?;
+        let default_conflicts = self.keymap.borrow_mut().take_conflicts();
+        debug_log!(self, "default init key conflicts: {:?}", default_conflicts);
 
         if let Some(user_init) = Self::user_init_path() {
             if user_init.exists() {
@@ -403,10 +821,32 @@ impl Backend {
                 interpreter.set_load_root(default_root);
             }
         }
+        self.report_keybinding_conflicts();
+        // init ファイルでの定義を M-: の評価からも使えるよう、同じ環境を引き継ぐ
+        self.minibuffer.set_alisp_interpreter(interpreter);
 
         Ok(())
     }
 
+    /// 既存の割り当てを上書きしたキーをメッセージで知らせる（割り当て自体は有効）
+    fn report_keybinding_conflicts(&mut self) {
+        let conflicts = self.keymap.borrow_mut().take_conflicts();
+        if conflicts.is_empty() {
+            return;
+        }
+        let sequences: Vec<String> = conflicts
+            .iter()
+            .map(|conflict| match conflict {
+                KeyMapError::Conflict(sequence) => sequence.to_string(),
+                other => other.to_string(),
+            })
+            .collect();
+        self.show_info_message(format!(
+            "既存のキー割り当てを上書きしました: {}",
+            sequences.join(", ")
+        ));
+    }
+
     fn allocate_buffer_id(&mut self) -> usize {
         let id = self.next_buffer_id;
         self.next_buffer_id = self.next_buffer_id.saturating_add(1);
@@ -424,7 +864,7 @@ impl Backend {
     fn find_buffer_id_by_path(&self, path: &Path) -> Option<usize> {
         self.buffers
             .iter()
-            .find(|buffer| buffer.path().map_or(false, |p| p == path))
+            .find(|buffer| buffer.path().is_some_and(|p| same_file_path(p, path)))
             .map(|buffer| buffer.id)
     }
 
@@ -480,6 +920,8 @@ impl Backend {
                 buffer.file.content = self.editor.to_string();
                 buffer.cursor = *self.editor.cursor();
                 buffer.history = self.history.stack().clone();
+                buffer.folds = self.editor.folds().clone();
+                buffer.overlays = self.editor.overlays().clone();
             }
         }
     }
@@ -497,13 +939,15 @@ impl Backend {
             AltreError::Application(format!("バッファID {} が見つかりません", id))
         })?;
 
-        let (content, cursor, file_clone, history_clone) = {
+        let (content, cursor, file_clone, history_clone, folds, overlays) = {
             let buffer = &self.buffers[index];
             (
                 buffer.file.content.clone(),
                 buffer.cursor,
                 buffer.file.clone(),
                 buffer.history.clone(),
+                buffer.folds.clone(),
+                buffer.overlays.clone(),
             )
         };
 
@@ -513,9 +957,14 @@ impl Backend {
             }
         }
 
-        self.current_buffer_id = Some(id);
-        self.editor = TextEditor::from_str(&content);
+        let previous_id = self.current_buffer_id.replace(id);
+        self.buffer_visit_order.retain(|&visited| visited != id);
+        self.buffer_visit_order.insert(0, id);
+        let previous_editor = std::mem::replace(&mut self.editor, TextEditor::from_str(&content));
+        self.editor.set_read_only(file_clone.read_only);
         self.editor.set_cursor(cursor);
+        self.editor.set_folds(folds);
+        self.editor.set_overlays(overlays);
         self.history.replace_stack(history_clone, &mut self.editor);
         self.command_processor.set_current_buffer(file_clone);
         self.command_processor
@@ -525,6 +974,17 @@ impl Backend {
             *viewport = ViewportState::new();
         }
 
+        // 他のウィンドウに残る直前のバッファは描画用に保持する
+        let focused = self.window_manager.focused_window();
+        self.window_manager.set_buffer(focused, Some(id));
+        self.other_editors.remove(&id);
+        if let Some(previous_id) = previous_id {
+            self.other_editors.insert(previous_id, previous_editor);
+        }
+        let windows = &self.window_manager;
+        self.other_editors
+            .retain(|&buffer_id, _| windows.displays_buffer(buffer_id));
+
         self.recenter_step = 0;
         self.ensure_cursor_visible();
         Ok(())
@@ -564,11 +1024,31 @@ impl Backend {
             .collect()
     }
 
+    /// 現在のバッファを除いて最も最近表示したバッファ名
     fn last_buffer_name(&self) -> Option<String> {
-        self.last_buffer_id
-            .and_then(|id| self.find_buffer_index(id))
-            .and_then(|index| self.buffers.get(index))
-            .map(|buffer| buffer.name().to_string())
+        self.buffer_visit_order
+            .iter()
+            .filter(|&&id| Some(id) != self.current_buffer_id)
+            .find_map(|&id| self.find_buffer_index(id))
+            .map(|index| self.buffers[index].name().to_string())
+    }
+
+    /// バッファ名を最近表示した順に並べる（現在のバッファは末尾）
+    fn buffer_names_by_recency(&self) -> Vec<String> {
+        let unvisited = self
+            .buffers
+            .iter()
+            .map(|buffer| buffer.id)
+            .filter(|id| !self.buffer_visit_order.contains(id));
+        self.buffer_visit_order
+            .iter()
+            .copied()
+            .chain(unvisited)
+            .filter(|&id| Some(id) != self.current_buffer_id)
+            .chain(self.current_buffer_id)
+            .filter_map(|id| self.find_buffer_index(id))
+            .map(|index| self.buffers[index].name().to_string())
+            .collect()
     }
 
     pub fn switch_buffer(&mut self, name: &str) -> Result<()> {
@@ -635,10 +1115,14 @@ impl Backend {
 
         let removed_name = self.buffers[index].name().to_string();
         self.buffers.remove(index);
+        self.other_editors.remove(&target_id);
+        self.window_manager.replace_buffer(target_id, None);
 
         if self.last_buffer_id == Some(target_id) {
             self.last_buffer_id = None;
         }
+        self.buffer_visit_order
+            .retain(|&visited| visited != target_id);
 
         if removing_current {
             self.current_buffer_id = None;
@@ -658,1865 +1142,8476 @@ impl Backend {
         Ok(())
     }
 
-    fn show_buffer_list(&mut self) {
-        let lines = self.buffer_display_lines();
-        if lines.is_empty() {
-            self.show_info_message("バッファはありません");
-        } else {
-            self.show_info_message(lines.join("\n"));
+    /// 現在のバッファの内容を複製した新しいバッファを作成して切り替える
+    /// C-x C-q: 現在のバッファの読み取り専用を切り替える
+    ///
+    /// `chmod_on_toggle_read_only` が有効で、書き込み禁止のファイルのバッファを書き込み可能にするときは
+    /// 先にファイルの権限を変更するか y/n で確認する。
+    fn toggle_read_only(&mut self) {
+        if !self.editor.is_read_only() {
+            self.set_current_read_only(true);
+            self.show_info_message("読み取り専用にしました");
+            return;
         }
-    }
 
-    fn open_file_at_path(&mut self, path_input: &str) -> Result<String> {
-        let expanded_path = expand_path(path_input)
-            .map_err(|err| AltreError::Application(format!("パス展開エラー: {}", err)))?;
+        let protected_path = self
+            .current_buffer()
+            .and_then(|buffer| buffer.path().cloned())
+            .filter(|path| FileInfo::is_write_protected(path));
+        if let (true, Some(path)) = (self.chmod_on_toggle_read_only, protected_path) {
+            let question = format!(
+                "{} は書き込み禁止です。書き込み可能にしますか？",
+                path.display()
+            );
+            self.pending_confirmation = Some(PendingConfirmation::MakeFileWritable(path));
+            if let Err(err) = self.minibuffer.start_yes_or_no(&question) {
+                self.pending_confirmation = None;
+                self.show_error_message(err);
+            }
+            return;
+        }
 
-        if let Some(existing_id) = self.find_buffer_id_by_path(&expanded_path) {
-            self.load_buffer_by_id(existing_id, true)?;
-            return Ok(format!(
-                "既存のバッファに切り替えました: {}",
-                expanded_path.display()
-            ));
+        self.set_current_read_only(false);
+        self.show_info_message("読み取り専用を解除しました");
+    }
+
+    fn set_current_read_only(&mut self, read_only: bool) {
+        self.editor.set_read_only(read_only);
+        if let Some(index) = self.current_buffer_index() {
+            self.buffers[index].file.read_only = read_only;
         }
+    }
 
-        let mut file_manager = FileOperationManager::new();
-        let file_buffer = match file_manager.open_file(expanded_path.clone()) {
-            Ok(buffer) => buffer,
-            Err(AltreError::File(FileError::NotFound { .. })) => file_manager
-                .create_new_file_buffer(expanded_path.clone())
-                .map_err(|err| AltreError::Application(format!("ファイル操作エラー: {}", err)))?,
-            Err(err) => return Err(err),
+    fn clone_current_buffer(&mut self) -> Result<()> {
+        self.persist_current_buffer_state();
+        let (base_name, content, cursor) = {
+            let buffer = self.current_buffer().ok_or_else(|| {
+                AltreError::Application("カレントバッファが存在しません".to_string())
+            })?;
+            (
+                Self::strip_buffer_name_suffix(buffer.name()).to_string(),
+                buffer.file.content.clone(),
+                buffer.cursor,
+            )
         };
 
+        let name = self.generate_unique_buffer_name(&base_name);
+        let mut file_buffer = FileBuffer::new_empty(name.clone());
+        file_buffer.change_tracker = FileChangeTracker::new(&content);
+        file_buffer.content = content;
+
         let id = self.allocate_buffer_id();
-        self.buffers.push(OpenBuffer::new(id, file_buffer));
+        let mut clone = OpenBuffer::new(id, file_buffer);
+        clone.cursor = cursor;
+        self.buffers.push(clone);
 
         self.load_buffer_by_id(id, true)?;
+        self.show_info_message(format!("バッファを複製しました: {}", name));
+        Ok(())
+    }
 
-        Ok(format!("ファイルを開きました: {}", expanded_path.display()))
+    /// `name<N>` 形式の末尾番号を取り除いたバッファ名を返す
+    fn strip_buffer_name_suffix(name: &str) -> &str {
+        if let Some(stripped) = name.strip_suffix('>') {
+            if let Some(open) = stripped.rfind('<') {
+                let digits = &stripped[open + 1..];
+                if open > 0 && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                    return &name[..open];
+                }
+            }
+        }
+        name
     }
 
-    fn current_viewport_mut(&mut self) -> &mut ViewportState {
-        self.window_manager
-            .focused_viewport_mut()
-            .expect("フォーカスウィンドウが存在しません")
+    /// 既存のバッファ名と衝突しない `name<N>` 形式の名前を生成
+    fn generate_unique_buffer_name(&self, base: &str) -> String {
+        if self.find_buffer_index_by_name(base).is_none() {
+            return base.to_string();
+        }
+        (2usize..)
+            .map(|n| format!("{}<{}>", base, n))
+            .find(|candidate| self.find_buffer_index_by_name(candidate).is_none())
+            .expect("一意なバッファ名を生成できません")
     }
 
-    fn current_viewport(&self) -> &ViewportState {
-        self.window_manager
-            .focused_viewport()
-            .expect("フォーカスウィンドウが存在しません")
+    /// 開いているファイル・ウィンドウ構成・アクティブバッファをセッションファイルへ保存
+    pub fn save_session(&mut self, path: &Path) -> Result<()> {
+        self.persist_current_buffer_state();
+        let buffers = self
+            .buffers
+            .iter()
+            .filter_map(|buffer| {
+                buffer.path().map(|path| SessionBuffer {
+                    path: path.clone(),
+                    cursor: buffer.cursor.char_pos,
+                    folds: buffer.folds.folds().to_vec(),
+                })
+            })
+            .collect();
+        let active_buffer = self
+            .current_buffer()
+            .and_then(|buffer| buffer.path().cloned());
+        let window_buffers = self
+            .window_manager
+            .leaf_order()
+            .into_iter()
+            .map(|window| {
+                self.window_manager
+                    .buffer(window)
+                    .and_then(|id| self.find_buffer_index(id))
+                    .and_then(|index| self.buffers[index].path().cloned())
+            })
+            .collect();
+        let session = SessionData::new(
+            buffers,
+            active_buffer,
+            self.window_manager.layout_snapshot(),
+            window_buffers,
+            self.window_manager.focused_index(),
+        );
+        session.save(path)
     }
 
-    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        // ミニバッファのメッセージ表示があれば先に消去
-        if self.minibuffer.is_message_displayed() {
-            let key = Key::from(key_event);
-            if let Err(err) = self.minibuffer.handle_event(SystemEvent::KeyInput(key)) {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの処理に失敗しました: {}",
+    /// セッションファイルから復元し、見つからず読み飛ばしたファイルの警告を返す
+    pub fn restore_session(&mut self, path: &Path) -> Result<Vec<String>> {
+        let session = SessionData::load(path)?;
+        let mut warnings = Vec::new();
+
+        for entry in &session.buffers {
+            if !entry.path.exists() {
+                warnings.push(format!(
+                    "ファイルが見つからないため復元をスキップしました: {}",
+                    entry.path.display()
+                ));
+                continue;
+            }
+            let path_text = entry.path.to_string_lossy().to_string();
+            match self.open_file_at_path(&path_text) {
+                Ok(_) => {
+                    self.editor.move_cursor_to_char(entry.cursor)?;
+                    let line_count = self.editor.to_string().split('\n').count();
+                    let mut folds = FoldState::restore(&entry.folds, line_count);
+                    folds.expand_containing(self.editor.cursor().line);
+                    self.editor.set_folds(folds);
+                    self.persist_current_buffer_state();
+                }
+                Err(err) => warnings.push(format!(
+                    "ファイルを復元できません: {} ({})",
+                    entry.path.display(),
                     err
-                )));
-                return Ok(());
+                )),
             }
         }
 
-        // ミニバッファがインタラクティブな場合の処理
-        if self.minibuffer.is_active() {
-            return self.handle_minibuffer_key(key_event);
-        }
-
-        if self.replace.controller.is_active() {
-            if self.handle_replace_key(key_event)? {
-                return Ok(());
+        // 各ウィンドウのファイルを先頭のウィンドウから順に読み込み、フォーカスを保存時の位置へ戻す
+        self.window_manager = WindowManager::from_layout(&session.layout, 0);
+        self.other_editors.clear();
+        for index in 0..self.window_manager.window_count() {
+            if let Some(id) = session
+                .window_buffers
+                .get(index)
+                .and_then(|path| path.as_deref())
+                .and_then(|path| self.find_buffer_id_by_path(path))
+            {
+                self.load_buffer_by_id(id, true)?;
+                let focused = self.window_manager.focused_window();
+                self.window_manager.set_buffer(focused, Some(id));
             }
+            self.window_manager.focus_next();
         }
-
-        // 検索モードがアクティブな場合は専用処理
-        if self.search.is_active() {
-            self.handle_search_key(key_event);
-            return Ok(());
+        for _ in 0..session.focused_window {
+            self.window_manager.focus_next();
         }
 
-        // 検索開始キー（C-s/C-r）を優先的に処理
-        if self.try_start_search(&key_event) {
-            return Ok(());
+        if let Some(active_id) = session
+            .active_buffer
+            .as_deref()
+            .and_then(|path| self.find_buffer_id_by_path(path))
+        {
+            self.load_buffer_by_id(active_id, true)?;
         }
+        let focused = self.window_manager.focused_window();
+        self.window_manager
+            .set_buffer(focused, self.current_buffer_id);
+        self.ensure_cursor_visible();
 
-        // 特殊キー処理（C-g, ESCなど）
-        if self.handle_special_keys(&key_event) {
-            return Ok(());
-        }
+        Ok(warnings)
+    }
 
-        // 新しいキーマップシステムを使用してキーを処理
-        let result = {
-            let mut keymap = self.keymap.borrow_mut();
-            keymap.process_key_event(key_event)
-        };
+    fn save_default_session(&mut self) -> Result<()> {
+        let path = self.session_path.clone().ok_or_else(|| {
+            AltreError::Application("セッションファイルの保存先が決定できません".to_string())
+        })?;
+        self.save_session(&path)?;
+        self.show_info_message(format!("セッションを保存しました: {}", path.display()));
+        Ok(())
+    }
 
-        match result {
-            KeyProcessResult::Action(action) => {
-                // アクション実行時にプレフィックス状態をクリア
-                self.current_prefix = None;
-                self.handle_action(action)?;
-            }
-            KeyProcessResult::PartialMatch => {
-                // プレフィックスキーの場合、状態を記録（ミニバッファは使わない）
-                let prefix = self
-                    .keymap
-                    .borrow()
-                    .current_prefix_label()
-                    .map(|s| s.to_string());
-                self.current_prefix = prefix;
+    /// 既定のセッションファイルから復元し、結果をミニバッファへ表示
+    pub fn restore_default_session(&mut self) {
+        let path = match self.session_path.clone() {
+            Some(path) => path,
+            None => {
+                self.show_error_message(AltreError::Application(
+                    "セッションファイルの場所が決定できません".to_string(),
+                ));
+                return;
             }
-            KeyProcessResult::NoMatch => {
-                // 緊急終了のフォールバック
-                if key_event.modifiers.contains(KeyModifiers::CONTROL)
-                    && key_event.code == KeyCode::Char('c')
-                {
-                    self.shutdown();
-                } else {
-                    self.show_info_message(format!(
-                        "未対応のキー: {}",
-                        Self::format_key_event(&key_event)
-                    ));
-                }
+        };
+
+        match self.restore_session(&path) {
+            Ok(warnings) if warnings.is_empty() => {
+                self.show_info_message(format!("セッションを復元しました: {}", path.display()))
             }
+            Ok(warnings) => self.show_info_message(warnings.join("\n")),
+            Err(err) => self.show_error_message(err),
         }
+    }
 
-        Ok(())
+    /// 自動保存か起動時の復元が有効で、セッションファイルがあれば復元する
+    pub fn restore_session_on_startup(&mut self) {
+        let exists = self.session_path.as_deref().is_some_and(Path::exists);
+        let options = self.options.borrow().clone();
+        if (options.auto_save_session || options.restore_session) && exists {
+            self.restore_default_session();
+        }
     }
 
-    /// 特殊キーの処理（キーマップを迂回）
-    fn handle_special_keys(&mut self, key_event: &KeyEvent) -> bool {
-        match (key_event.code, key_event.modifiers) {
-            // C-g: キーシーケンスのキャンセル（無反応）
-            (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
-                self.keymap.borrow_mut().reset_partial_match();
-                self.current_prefix = None;
-                self.keyboard_quit();
-                true
-            }
-            // ESC: キーシーケンスのキャンセル（無反応）
-            (KeyCode::Esc, _) => {
-                self.keymap.borrow_mut().reset_partial_match();
-                self.current_prefix = None;
-                true
-            }
-            _ => false,
+    /// 自動保存が有効なら終了前にセッションを保存する（失敗しても終了は止めない）
+    fn auto_save_session_on_exit(&mut self) {
+        if !self.auto_save_session() {
+            return;
+        }
+        let Some(path) = self.session_path.clone() else {
+            return;
+        };
+        if let Err(err) = self.save_session(&path) {
+            self.show_error_message(err);
         }
     }
 
-    fn try_start_search(&mut self, key_event: &KeyEvent) -> bool {
-        if self.keymap.borrow().is_partial_match() {
-            return false;
+    fn show_buffer_list(&mut self) {
+        let lines = self.buffer_display_lines();
+        if lines.is_empty() {
+            self.show_info_message("バッファはありません");
+        } else {
+            self.show_info_message(lines.join("\n"));
         }
+    }
 
-        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-            match key_event.code {
-                KeyCode::Char('s') | KeyCode::Char('S') => {
-                    self.keymap.borrow_mut().reset_partial_match();
-                    self.current_prefix = None;
-                    self.search
-                        .start(&mut self.editor, SearchDirection::Forward);
-                    return true;
+    /// ミニバッファから入力されたファイルを開く
+    ///
+    /// `confirm_new_file` が有効で、開いていない存在しないファイルなら先に作成を確認する。
+    fn find_file_interactively(&mut self, path_input: &str) {
+        if self.reopen_file_behavior == ReopenFileBehavior::Reload {
+            if let Some(id) = self.open_buffer_id_for_input(path_input) {
+                self.persist_current_buffer_state();
+                let modified = self
+                    .find_buffer_index(id)
+                    .is_some_and(|index| self.buffers[index].is_modified());
+                if !modified {
+                    match self.reload_file_at_path(path_input) {
+                        Ok(message) => self.show_info_message(message),
+                        Err(err) => self.show_error_message(err),
+                    }
+                    return;
                 }
-                KeyCode::Char('r') | KeyCode::Char('R') => {
-                    self.keymap.borrow_mut().reset_partial_match();
-                    self.current_prefix = None;
-                    self.search
-                        .start(&mut self.editor, SearchDirection::Backward);
-                    return true;
+                self.pending_confirmation =
+                    Some(PendingConfirmation::ReloadFile(path_input.to_string()));
+                if let Err(err) = self
+                    .minibuffer
+                    .start_yes_or_no("未保存の変更を破棄してファイルを読み直しますか？")
+                {
+                    self.pending_confirmation = None;
+                    self.show_error_message(err);
                 }
-                _ => {}
+                return;
             }
         }
 
-        false
-    }
-
-    fn handle_search_key(&mut self, key_event: KeyEvent) {
-        use KeyModifiers as KM;
-
-        let modifiers = key_event.modifiers;
-
-        match key_event.code {
-            KeyCode::Char('s') | KeyCode::Char('S') if modifiers.contains(KM::CONTROL) => {
-                self.search.repeat_forward(&mut self.editor);
-            }
-            KeyCode::Char('r') | KeyCode::Char('R') if modifiers.contains(KM::CONTROL) => {
-                self.search.repeat_backward(&mut self.editor);
-            }
-            KeyCode::Char('w') | KeyCode::Char('W') if modifiers.contains(KM::CONTROL) => {
-                self.search.add_word_at_cursor(&mut self.editor);
-            }
-            KeyCode::Char('g') | KeyCode::Char('G') if modifiers.contains(KM::CONTROL) => {
-                self.search.cancel(&mut self.editor);
-            }
-            KeyCode::Enter => {
-                self.search.accept();
-            }
-            KeyCode::Backspace => {
-                self.search.delete_char(&mut self.editor);
-            }
-            KeyCode::Esc => {
-                self.search.cancel(&mut self.editor);
-            }
-            KeyCode::Char(ch) => {
-                if modifiers.contains(KM::CONTROL) || modifiers.contains(KM::ALT) {
-                    // 未対応の制御入力はキャンセル扱い
-                    if modifiers.contains(KM::CONTROL) && (ch == 'g' || ch == 'G') {
-                        self.search.cancel(&mut self.editor);
-                    }
-                } else {
-                    self.search.input_char(&mut self.editor, ch);
+        if self.reopen_file_behavior == ReopenFileBehavior::Warn {
+            if let Some(id) = self.open_buffer_id_for_input(path_input) {
+                let existing = self
+                    .find_buffer_index(id)
+                    .and_then(|index| self.buffers[index].path().cloned());
+                match self.open_file_at_path(path_input) {
+                    Ok(_) => self.show_error_message(AltreError::Application(format!(
+                        "既に開いているファイルです。既存のバッファに切り替えました: {}",
+                        existing.map_or_else(String::new, |path| path.display().to_string())
+                    ))),
+                    Err(err) => self.show_error_message(err),
                 }
+                return;
             }
-            _ => {}
         }
-    }
 
-    fn handle_replace_key(&mut self, key_event: KeyEvent) -> Result<bool> {
-        use KeyCode::*;
-        use KeyModifiers as KM;
+        if self.confirm_new_file && self.is_new_file_input(path_input) {
+            self.pending_confirmation =
+                Some(PendingConfirmation::CreateFile(path_input.to_string()));
+            if let Err(err) = self
+                .minibuffer
+                .start_yes_or_no("ファイルが存在しません。作成しますか？")
+            {
+                self.pending_confirmation = None;
+                self.show_error_message(err);
+            }
+            return;
+        }
 
-        if !self.replace.controller.is_active() {
-            return Ok(false);
+        match self.open_file_at_path(path_input) {
+            Ok(message) => self.show_info_message(message),
+            Err(err) => self.show_error_message(err),
         }
+    }
 
-        match (key_event.code, key_event.modifiers) {
-            (Char('y'), KM::NONE) | (Char('Y'), KM::NONE) | (Char(' '), KM::NONE) => {
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.begin_history(HistoryCommandKind::Other);
-                match self.replace.controller.accept_current(&mut self.editor) {
-                    Ok(progress) => {
-                        self.end_history(true);
-                        self.after_replace_progress(progress);
-                    }
-                    Err(err) => {
-                        self.end_history(false);
-                        self.show_error_message(err);
-                    }
+    fn handle_confirmation_answer(&mut self, answer: bool) {
+        match self.pending_confirmation.take() {
+            Some(PendingConfirmation::CreateFile(path)) if answer => {
+                match self.open_file_at_path(&path) {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
                 }
-                Ok(true)
             }
-            (Char('n'), KM::NONE) | (Char('N'), KM::NONE) => {
-                let progress = self.replace.controller.skip_current();
-                self.after_replace_progress(progress);
-                Ok(true)
-            }
-            (Delete, _) | (Backspace, _) => {
-                let progress = self.replace.controller.skip_current();
-                self.after_replace_progress(progress);
-                Ok(true)
+            Some(PendingConfirmation::CreateFile(_)) => {
+                self.show_info_message("ファイルの作成を取り消しました");
             }
-            (Char('!'), KM::NONE) => {
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.begin_history(HistoryCommandKind::Other);
-                match self.replace.controller.accept_all(&mut self.editor) {
-                    Ok(progress) => {
-                        self.end_history(true);
-                        self.after_replace_progress(progress);
-                    }
-                    Err(err) => {
-                        self.end_history(false);
-                        self.show_error_message(err);
-                    }
+            Some(PendingConfirmation::ReloadFile(path)) if answer => {
+                match self.reload_file_at_path(&path) {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
                 }
-                Ok(true)
-            }
-            (Enter, KM::NONE) | (Char('q'), KM::NONE) | (Char('Q'), KM::NONE) => {
-                let summary = self.replace.controller.finish();
-                self.finish_replace_session(summary);
-                Ok(true)
             }
-            (Char('g'), modifiers) if modifiers.contains(KM::CONTROL) => {
-                self.begin_history(HistoryCommandKind::Other);
-                match self.replace.controller.cancel(&mut self.editor) {
-                    Ok(summary) => {
-                        self.end_history(true);
-                        self.finish_replace_session(summary);
-                    }
-                    Err(err) => {
-                        self.end_history(false);
-                        self.show_error_message(err);
+            Some(PendingConfirmation::ReloadFile(path)) => match self.open_file_at_path(&path) {
+                Ok(_) => {
+                    self.show_info_message("読み直しを取り消し、既存のバッファに切り替えました")
+                }
+                Err(err) => self.show_error_message(err),
+            },
+            Some(PendingConfirmation::MakeFileWritable(path)) if answer => {
+                match FileInfo::make_writable(&path) {
+                    Ok(()) => {
+                        self.set_current_read_only(false);
+                        self.show_info_message(format!(
+                            "ファイルを書き込み可能にしました: {}",
+                            path.display()
+                        ));
                     }
+                    Err(err) => self.show_error_message(AltreError::Application(format!(
+                        "ファイルの権限を変更できないため読み取り専用のままにします: {}",
+                        err
+                    ))),
                 }
-                Ok(true)
             }
-            _ => {
-                self.show_info_message(
-                    "yで置換、nでスキップ、!で残りを置換、qで終了、C-gでキャンセル",
-                );
-                Ok(true)
+            Some(PendingConfirmation::MakeFileWritable(_)) => {
+                self.set_current_read_only(false);
+                self.show_info_message("ファイルの権限は変えずに読み取り専用を解除しました");
             }
+            None => {}
         }
     }
 
-    fn start_query_replace_session(
-        &mut self,
-        pattern: String,
-        replacement: String,
-        use_regex: bool,
-    ) -> Result<()> {
-        if pattern.is_empty() {
-            self.show_error_message(AltreError::Application("置換パターンが空です".to_string()));
-            return Ok(());
-        }
-
-        if self.replace.controller.is_active() {
-            let summary = self.replace.controller.finish();
-            self.finish_replace_session(summary);
-        }
-
-        if self.search.is_active() {
-            self.search.cancel(&mut self.editor);
+    /// 開いていない存在しないファイルを指す入力か
+    fn is_new_file_input(&self, path_input: &str) -> bool {
+        if self.wildcard_pattern(path_input).is_some() {
+            return false;
         }
+        let (path_part, _) = split_find_file_input(path_input);
+        expand_path(path_part)
+            .is_ok_and(|path| !path.exists() && self.find_buffer_id_by_path(&path).is_none())
+    }
 
-        let case_sensitive = pattern.chars().any(|c| c.is_uppercase());
-        let snapshot = self.editor.to_string();
+    /// find-file の入力が既に開いているファイルを指すなら、そのバッファ ID
+    fn open_buffer_id_for_input(&self, path_input: &str) -> Option<usize> {
+        let (path_part, _) = split_find_file_input(path_input);
+        expand_path(path_part)
+            .ok()
+            .and_then(|path| self.find_buffer_id_by_path(&path))
+    }
 
-        let start = if use_regex {
-            match self.replace.controller.start_regex(
-                &snapshot,
-                pattern.clone(),
-                replacement.clone(),
-                case_sensitive,
-            ) {
-                Ok(info) => info,
-                Err(err) => {
-                    self.show_error_message(AltreError::Application(format!(
-                        "正規表現エラー: {}",
-                        err
-                    )));
-                    self.replace.reset();
-                    return Ok(());
-                }
-            }
-        } else {
-            self.replace.controller.start_literal(
-                &snapshot,
-                pattern.clone(),
-                replacement.clone(),
-                case_sensitive,
+    /// 開いているファイルへ切り替えてディスクから読み直す（位置指定があれば移動）
+    ///
+    /// 未保存の変更は確認済みとして破棄する。文字コードはバッファのものを使う。
+    /// 変更がなくディスク上の内容とも同じなら、取り消し履歴とカーソルを残すため読み直さない。
+    fn reload_file_at_path(&mut self, path_input: &str) -> Result<String> {
+        let (_, position) = split_find_file_input(path_input);
+        let id = self
+            .open_buffer_id_for_input(path_input)
+            .ok_or_else(|| AltreError::Application("バッファが見つかりません".to_string()))?;
+        self.load_buffer_by_id(id, true)?;
+        let index = self
+            .current_buffer_index()
+            .ok_or_else(|| AltreError::Application("バッファがありません".to_string()))?;
+        let buffer = &self.buffers[index];
+        let message = if !buffer.is_modified() && buffer.file.matches_disk() {
+            format!(
+                "ファイルは変更されていません: {}",
+                buffer
+                    .path()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
             )
+        } else {
+            let coding = buffer.file.coding;
+            let path = self.reread_buffer(index, coding)?;
+            format!("ファイルを読み直しました: {}", path)
         };
-
-        if start.total_matches == 0 || !self.replace.controller.is_active() {
-            self.replace.highlights.clear();
-            self.show_info_message("置換対象が見つかりません");
-            return Ok(());
-        }
-
-        if let Some((start_pos, _)) = self.replace.controller.current_range() {
-            let _ = self.editor.move_cursor_to_char(start_pos);
+        if let Some((line, column)) = position {
+            self.jump_to_line_column(line, column)?;
         }
-
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        self.update_replace_view();
-        Ok(())
+        Ok(message)
     }
 
-    fn after_replace_progress(&mut self, progress: ReplaceProgress) {
-        if !self.replace.controller.is_active() || progress.finished {
-            let summary = self.replace.controller.finish();
-            self.finish_replace_session(summary);
-        } else {
-            self.update_replace_view();
+    /// ファイルを開く（`path:line` / `path:line:col` 形式なら指定位置へ移動）
+    fn open_file_at_path(&mut self, path_input: &str) -> Result<String> {
+        if let Some(pattern) = self.wildcard_pattern(path_input) {
+            return self.open_wildcard_matches(path_input, &pattern);
         }
-    }
-
-    fn update_replace_view(&mut self) {
-        if !self.replace.controller.is_active() {
-            self.replace.highlights.clear();
-            self.minibuffer.set_status_message(None);
-            return;
+        let (path_part, position) = split_find_file_input(path_input);
+        let message = self.open_file_without_position(path_part)?;
+        if let Some((line, column)) = position {
+            self.jump_to_line_column(line, column)?;
         }
+        Ok(message)
+    }
 
-        let snapshot = self.editor.to_string();
-        self.replace.highlights = self.replace.controller.highlights(&snapshot);
-        if let Some((start_pos, _)) = self.replace.controller.current_range() {
-            let _ = self.editor.move_cursor_to_char(start_pos);
+    /// 入力をワイルドカードとして展開すべきなら展開済みのパターンを返す
+    fn wildcard_pattern(&self, path_input: &str) -> Option<PathBuf> {
+        if !self.find_file_wildcards || !has_wildcards(path_input) {
+            return None;
         }
-        self.minibuffer
-            .set_status_message(self.replace_prompt_message(&snapshot));
-        self.ensure_cursor_visible();
+        expand_path(path_input)
+            .ok()
+            .filter(|pattern| !pattern.exists())
     }
 
-    fn replace_prompt_message(&self, snapshot: &str) -> Option<String> {
-        let (original, replacement, index, total) =
-            self.replace.controller.current_preview(snapshot)?;
-        let label = if self.replace.controller.is_regex() {
-            "Regex replace"
-        } else {
-            "Query replace"
+    /// ワイルドカードに一致するファイルをすべて開き、最初のファイルへ切り替える
+    fn open_wildcard_matches(&mut self, path_input: &str, pattern: &Path) -> Result<String> {
+        let matches = expand_wildcards(pattern);
+        let Some(first) = matches.first() else {
+            return Ok(format!("一致するファイルがありません: {}", path_input));
         };
-        Some(format!(
-            "{} {}/{}: \"{}\" → \"{}\" (y=Yes, n=No, !=All, q=Quit)",
-            label,
-            index,
-            total,
-            Self::preview_fragment(&original),
-            Self::preview_fragment(&replacement)
+
+        for path in &matches {
+            self.open_file_without_position(&path.to_string_lossy())?;
+        }
+        if let Some(id) = self.find_buffer_id_by_path(first) {
+            self.load_buffer_by_id(id, true)?;
+        }
+        Ok(format!(
+            "{} 個のファイルを開きました: {}",
+            matches.len(),
+            path_input
         ))
     }
 
-    fn finish_replace_session(&mut self, summary: ReplaceSummary) {
-        self.replace.reset();
-        self.minibuffer.set_status_message(None);
-        if summary.cancelled {
-            self.show_info_message(format!(
-                "置換をキャンセルしました（置換 {} 件、スキップ {} 件）",
-                summary.replaced, summary.skipped
-            ));
-        } else {
-            self.show_info_message(format!(
-                "置換完了: {} 件置換、{} 件スキップ",
-                summary.replaced, summary.skipped
+    fn open_file_without_position(&mut self, path_input: &str) -> Result<String> {
+        let expanded_path = expand_path(path_input)
+            .map_err(|err| AltreError::Application(format!("パス展開エラー: {}", err)))?;
+
+        if let Some(existing_id) = self.find_buffer_id_by_path(&expanded_path) {
+            self.load_buffer_by_id(existing_id, true)?;
+            return Ok(format!(
+                "既存のバッファに切り替えました: {}",
+                expanded_path.display()
             ));
         }
-        self.ensure_cursor_visible();
-    }
 
-    fn preview_fragment(input: &str) -> String {
-        const MAX_LEN: usize = 24;
+        let mut file_manager = FileOperationManager::new();
+        let file_buffer = match file_manager.open_file(expanded_path.clone()) {
+            Ok(buffer) => buffer,
+            Err(AltreError::File(FileError::NotFound { .. })) => file_manager
+                .create_new_file_buffer(expanded_path.clone())
+                .map_err(|err| AltreError::Application(format!("ファイル操作エラー: {}", err)))?,
+            Err(err) => return Err(err),
+        };
 
-        if input.is_empty() {
-            return "<empty>".to_string();
+        let id = self.allocate_buffer_id();
+        self.buffers.push(OpenBuffer::new(id, file_buffer));
+
+        self.load_buffer_by_id(id, true)?;
+
+        Ok(format!("ファイルを開きました: {}", expanded_path.display()))
+    }
+
+    /// 現在のファイルを指定した文字コードで読み直す
+    ///
+    /// 未保存の変更がある場合は読み直さない。undo 履歴と折りたたみは破棄する。
+    pub fn revert_buffer_with_coding_system(&mut self, name: &str) -> Result<String> {
+        let coding = CodingSystem::from_name(name).ok_or_else(|| {
+            let supported: Vec<&str> = CodingSystem::ALL.iter().map(|c| c.name()).collect();
+            AltreError::Application(format!(
+                "未対応の文字コードです: {}（対応: {}）",
+                name.trim(),
+                supported.join(", ")
+            ))
+        })?;
+        self.persist_current_buffer_state();
+        let index = self
+            .current_buffer_index()
+            .ok_or_else(|| AltreError::Application("バッファがありません".to_string()))?;
+        if self.buffers[index].is_modified() {
+            return Err(AltreError::Application(
+                "未保存の変更があるため読み直せません".to_string(),
+            ));
         }
 
-        let mut result = String::new();
-        for (idx, ch) in input.chars().enumerate() {
-            if idx >= MAX_LEN {
-                result.push('…');
-                break;
-            }
-            let display = match ch {
-                '\n' => '↵',
-                '\t' => '⇥',
-                _ => ch,
+        let path = self.reread_buffer(index, coding)?;
+        Ok(format!("{} で読み直しました: {}", coding.name(), path))
+    }
+
+    /// 現在のバッファ（`index`）のファイルを `coding` でディスクから読み直し、パスの表示を返す
+    ///
+    /// undo 履歴と折りたたみは破棄し、カーソルは先頭へ戻す。
+    fn reread_buffer(&mut self, index: usize, coding: CodingSystem) -> Result<String> {
+        let mut file = self.buffers[index].file.clone();
+        file.revert_with_coding(coding)?;
+        let path = file
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+
+        self.editor = TextEditor::from_str(&file.content);
+        self.editor.set_read_only(file.read_only);
+        self.history
+            .replace_stack(HistoryStack::new(), &mut self.editor);
+        self.command_processor.set_current_buffer(file.clone());
+        self.command_processor.sync_editor_content(&file.content);
+        let buffer = &mut self.buffers[index];
+        buffer.file = file;
+        buffer.cursor = CursorPosition::new();
+        buffer.history = HistoryStack::new();
+        buffer.folds = FoldState::new();
+        buffer.overlays = OverlaySet::new();
+        self.ensure_cursor_visible();
+        Ok(path)
+    }
+
+    /// エディタコマンド・登録コマンド・alisp 関数の説明文を作る
+    fn describe_function_text(&mut self, name: &str) -> Result<String> {
+        let name = name.trim();
+        let command = Command::from_string(name);
+        let (kind, action, arity, doc) = if !matches!(command, Command::Unknown(_)) {
+            (
+                "エディタコマンド",
+                Action::from_command(&command),
+                "なし".to_string(),
+                Some(command.description().to_string()),
+            )
+        } else if let Some(description) = self
+            .command_registry
+            .borrow()
+            .description(name)
+            .map(str::to_string)
+        {
+            (
+                "登録コマンド",
+                Some(Action::CustomCommand(name.to_string())),
+                "なし".to_string(),
+                Some(description).filter(|doc| !doc.is_empty()),
+            )
+        } else if let Some(help) = self.minibuffer.describe_alisp_function(name) {
+            let arity = if help.params.is_empty() {
+                help.arity.to_string()
+            } else {
+                format!("{} ({})", help.arity, help.params.join(" "))
             };
-            result.push(display);
-        }
-        result
+            let kind = if help.builtin {
+                "alisp の組込み関数"
+            } else {
+                "alisp の関数"
+            };
+            (kind, None, arity, help.doc)
+        } else {
+            return Err(AltreError::Application(format!(
+                "関数が見つかりません: {}",
+                name
+            )));
+        };
+
+        let keys = action
+            .map(|action| self.keymap.borrow().sequences_for_action(&action))
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let keys = if keys.is_empty() {
+            "なし".to_string()
+        } else {
+            keys.join(", ")
+        };
+        Ok(format!(
+            "{} は{}です。\n\nキー: {}\n引数: {}\n\n{}\n",
+            name,
+            kind,
+            keys,
+            arity,
+            doc.unwrap_or_else(|| "（説明はありません）".to_string())
+        ))
     }
 
-    fn handle_action(&mut self, action: Action) -> Result<()> {
-        match action.to_command() {
-            Some(command) => self.execute_command(command),
+    /// `*Help*` などの表示用バッファに文章を表示して切り替える（既存の内容は置き換える）
+    fn show_text_buffer(&mut self, name: &str, text: String) -> Result<()> {
+        let index = match self.find_buffer_index_by_name(name) {
+            Some(index) => index,
             None => {
-                self.show_error_message(AltreError::Application(
-                    "アクションをコマンドに変換できませんでした".to_string(),
-                ));
-                Ok(())
+                let id = self.allocate_buffer_id();
+                let file_buffer = FileBuffer::new_empty(name.to_string());
+                self.buffers.push(OpenBuffer::new(id, file_buffer));
+                self.buffers.len() - 1
             }
+        };
+
+        let buffer = &mut self.buffers[index];
+        buffer.file.change_tracker = FileChangeTracker::new(&text);
+        buffer.file.content = text;
+        buffer.cursor = CursorPosition::new();
+        buffer.history = HistoryStack::new();
+        buffer.folds = FoldState::new();
+        buffer.overlays = OverlaySet::new();
+        let id = buffer.id;
+
+        if self.current_buffer_id == Some(id) {
+            let file = self.buffers[index].file.clone();
+            self.editor = TextEditor::from_str(&file.content);
+            self.history
+                .replace_stack(HistoryStack::new(), &mut self.editor);
+            self.command_processor.set_current_buffer(file.clone());
+            self.command_processor.sync_editor_content(&file.content);
+            self.ensure_cursor_visible();
+            Ok(())
+        } else {
+            self.load_buffer_by_id(id, true)
         }
     }
 
-    fn execute_command(&mut self, command: Command) -> Result<()> {
-        match command {
-            Command::FindFile => self.start_find_file_prompt(),
-            Command::ForwardChar => {
-                self.navigate(NavigationAction::MoveCharForward);
-                Ok(())
-            }
-            Command::BackwardChar => {
-                self.navigate(NavigationAction::MoveCharBackward);
-                Ok(())
-            }
-            Command::GotoLine => {
-                self.start_goto_line_prompt()?;
-                Ok(())
+    /// 指定行・桁（1始まり）へ移動。範囲外の行・桁はバッファ内に収める
+    fn jump_to_line_column(&mut self, line: usize, column: Option<usize>) -> Result<()> {
+        let text = self.editor.to_string();
+        let starts = line_start_offsets(&text);
+        let index = line.clamp(1, starts.len()) - 1;
+        let line_len = text.split('\n').nth(index).map_or(0, |l| l.chars().count());
+        let column = column.unwrap_or(1).clamp(1, line_len + 1) - 1;
+
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.editor.move_cursor_to_char(starts[index] + column)?;
+        self.ensure_cursor_visible();
+        Ok(())
+    }
+
+    fn current_viewport_mut(&mut self) -> &mut ViewportState {
+        self.window_manager
+            .focused_viewport_mut()
+            .expect("フォーカスウィンドウが存在しません")
+    }
+
+    fn current_viewport(&self) -> &ViewportState {
+        self.window_manager
+            .focused_viewport()
+            .expect("フォーカスウィンドウが存在しません")
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        // 分割挿入中は C-g によるキャンセルのみ受け付ける
+        if self.pending_insert.is_some() {
+            if key_event.code == KeyCode::Char('g')
+                && key_event.modifiers.contains(KeyModifiers::CONTROL)
+            {
+                self.cancel_pending_insert();
+            } else {
+                self.show_info_message("挿入処理中です（C-g でキャンセル）");
             }
-            Command::ForwardWord => {
-                self.navigate(NavigationAction::MoveWordForward);
-                Ok(())
+            return Ok(());
+        }
+
+        // ミニバッファのメッセージ表示があれば先に消去
+        if self.minibuffer.is_message_displayed() {
+            let key = Key::from(key_event);
+            if let Err(err) = self.minibuffer.handle_event(SystemEvent::KeyInput(key)) {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの処理に失敗しました: {}",
+                    err
+                )));
+                return Ok(());
             }
-            Command::BackwardWord => {
-                self.navigate(NavigationAction::MoveWordBackward);
-                Ok(())
+        }
+
+        // C-q の直後のキーは解釈せずに挿入する
+        if self.quoted_insert_pending {
+            self.quoted_insert_pending = false;
+            self.insert_quoted_key(&key_event);
+            return Ok(());
+        }
+
+        // ミニバッファがインタラクティブな場合の処理
+        if self.minibuffer.is_active() {
+            if is_quoted_insert_key(&key_event) {
+                self.quoted_insert_pending = true;
+                return Ok(());
             }
-            Command::NextLine => {
-                self.navigate(NavigationAction::MoveLineDown);
-                Ok(())
+            return self.handle_minibuffer_key(key_event);
+        }
+
+        // 補完ポップアップ表示中は選択・確定・取り消しを優先
+        if self.auto_completion.popup().is_some() && self.handle_completion_popup_key(&key_event) {
+            return Ok(());
+        }
+
+        if self.replace.controller.is_active() {
+            if self.handle_replace_key(key_event)? {
+                return Ok(());
             }
-            Command::PreviousLine => {
-                self.navigate(NavigationAction::MoveLineUp);
-                Ok(())
+        }
+
+        // 検索モードがアクティブな場合は専用処理
+        if self.search.is_active() {
+            self.handle_search_key(key_event);
+            return Ok(());
+        }
+
+        // 検索開始キー（C-s/C-r）を優先的に処理
+        if self.try_start_search(&key_event) {
+            return Ok(());
+        }
+
+        // 特殊キー処理（C-g, ESCなど）
+        if self.handle_special_keys(&key_event) {
+            return Ok(());
+        }
+
+        // C-u に続く数字は数引数として読む
+        if self.read_prefix_argument_digit(&key_event) {
+            return Ok(());
+        }
+
+        if key_event.code == KeyCode::Tab
+            && key_event.modifiers.is_empty()
+            && self.tab_behavior == TabBehavior::InsertTab
+            && !self.keymap.borrow().is_partial_match()
+        {
+            self.insert_literal_char('\t');
+            return Ok(());
+        }
+
+        // occur バッファでは Enter で一致箇所へ移動し、g で一覧を作り直す
+        if self.current_buffer_is_occur()
+            && key_event.modifiers.is_empty()
+            && !self.keymap.borrow().is_partial_match()
+        {
+            match key_event.code {
+                KeyCode::Enter => return self.execute_command(Command::OccurGotoOccurrence),
+                KeyCode::Char('g') => return self.execute_command(Command::OccurRefresh),
+                _ => {}
             }
-            Command::InsertChar(ch) => {
-                self.begin_history(HistoryCommandKind::InsertChar);
-                let result = self.editor.insert_char(ch);
-                let success = result.is_ok();
-                if let Err(err) = result {
-                    self.show_error_message(err);
+        }
+
+        // 新しいキーマップシステムを使用してキーを処理
+        let result = {
+            let mut keymap = self.keymap.borrow_mut();
+            keymap.process_key_event(key_event)
+        };
+
+        match result {
+            KeyProcessResult::Action(action) => {
+                // アクション実行時にプレフィックス状態をクリア
+                self.current_prefix = None;
+                let prefix_argument = if action == Action::UniversalArgument {
+                    None
+                } else {
+                    self.prefix_argument.take()
+                };
+                match (action, prefix_argument) {
+                    (Action::InsertChar(ch), Some(argument)) => {
+                        self.insert_repeated_char(ch, argument.count())
+                    }
+                    (Action::SetFillColumn, Some(argument)) => match argument.digits {
+                        Some(column) => self.set_buffer_fill_column(column),
+                        None => {
+                            let current = self.current_fill_column();
+                            if let Err(err) = self.minibuffer.start_set_fill_column(current) {
+                                self.show_error_message(AltreError::Application(format!(
+                                    "ミニバッファの初期化に失敗しました: {}",
+                                    err
+                                )));
+                            }
+                        }
+                    },
+                    (Action::GotoColumn, Some(argument)) => match argument.digits {
+                        Some(column) => {
+                            if let Err(err) = self.goto_column(column) {
+                                self.show_error_message(err);
+                            }
+                        }
+                        None => self.start_goto_column_prompt(),
+                    },
+                    (action, _) => self.handle_action(action)?,
                 }
-                self.end_history(success);
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                Ok(())
             }
-            Command::DeleteBackwardChar => {
-                self.begin_history(HistoryCommandKind::DeleteBackward);
-                let result = self.editor.delete_backward();
-                let success = result.is_ok();
-                if let Err(err) = result {
-                    self.show_error_message(err);
+            KeyProcessResult::PartialMatch => {
+                // プレフィックスキーの場合、状態を記録（ミニバッファは使わない）
+                let prefix = self
+                    .keymap
+                    .borrow()
+                    .current_prefix_label()
+                    .map(|s| s.to_string());
+                self.current_prefix = prefix;
+            }
+            KeyProcessResult::NoMatch => {
+                // 緊急終了のフォールバック
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && key_event.code == KeyCode::Char('c')
+                {
+                    self.shutdown();
+                } else {
+                    self.show_info_message(format!(
+                        "未対応のキー: {}",
+                        Self::format_key_event(&key_event)
+                    ));
                 }
-                self.end_history(success);
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                Ok(())
             }
-            Command::DeleteChar => {
-                self.begin_history(HistoryCommandKind::Other);
-                let result = self.editor.delete_forward();
-                let success = result.is_ok();
-                if let Err(err) = result {
-                    self.show_error_message(err);
-                }
-                self.end_history(success);
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                Ok(())
+        }
+
+        Ok(())
+    }
+
+    /// 数引数の入力中なら数字キーを値に加える
+    fn read_prefix_argument_digit(&mut self, key_event: &KeyEvent) -> bool {
+        let Some(argument) = self.prefix_argument.as_mut() else {
+            return false;
+        };
+        let digit = match key_event.code {
+            KeyCode::Char(ch) if !key_event.modifiers.intersects(!KeyModifiers::SHIFT) => {
+                ch.to_digit(10)
             }
-            Command::InsertNewline => {
-                self.begin_history(HistoryCommandKind::Other);
-                let result = self.editor.insert_newline();
-                let success = result.is_ok();
-                if let Err(err) = result {
-                    self.show_error_message(err);
-                }
-                self.end_history(success);
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                Ok(())
+            _ => None,
+        };
+        let Some(digit) = digit.filter(|_| argument.reading_digits) else {
+            return false;
+        };
+        let value = argument
+            .digits
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit as usize);
+        argument.digits = Some(value);
+        let label = argument.label();
+        self.show_info_message(label);
+        true
+    }
+
+    /// C-u: 数引数の入力を始める（続けて押すと 4 倍）
+    fn universal_argument(&mut self) {
+        let argument = match self.prefix_argument.take() {
+            None => PrefixArgument::new(),
+            Some(argument) if argument.digits.is_some() => PrefixArgument {
+                reading_digits: false,
+                ..argument
+            },
+            Some(argument) => PrefixArgument {
+                presses: argument.presses + 1,
+                ..argument
+            },
+        };
+        self.show_info_message(argument.label());
+        self.prefix_argument = Some(argument);
+    }
+
+    /// 文字を `count` 回挿入する（1 回の履歴操作。多い場合は分割挿入）
+    fn insert_repeated_char(&mut self, ch: char, count: usize) {
+        if self.pending_insert.is_some() {
+            self.show_info_message("挿入処理中です（C-g でキャンセル）");
+            return;
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        if count == 0 {
+            return;
+        }
+        if count > MAX_REPEAT_INSERT_COUNT {
+            self.show_error_message(AltreError::Application(format!(
+                "数引数が大きすぎます（上限 {}）",
+                MAX_REPEAT_INSERT_COUNT
+            )));
+            return;
+        }
+        if self.replaces_active_region() {
+            self.begin_history(HistoryCommandKind::Other);
+            let result = self
+                .delete_region_for_input()
+                .and_then(|_| self.editor.insert_str(&ch.to_string().repeat(count)));
+            if let Err(err) = &result {
+                self.show_error_message(err.clone());
+            }
+            self.end_history(result.is_ok());
+            self.ensure_cursor_visible();
+            return;
+        }
+        self.insert_text_as_one_step(ch.to_string().repeat(count));
+    }
+
+    /// 特殊キーの処理（キーマップを迂回）
+    fn handle_special_keys(&mut self, key_event: &KeyEvent) -> bool {
+        match (key_event.code, key_event.modifiers) {
+            // C-g: 有効な状態を一つ取り消す（プレフィックスの解除も keyboard_quit に任せる）
+            (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
+                self.keyboard_quit();
+                true
             }
-            Command::IndentForTab => {
-                self.indent_for_tab();
-                Ok(())
+            // ESC: キーシーケンスのキャンセル（無反応）
+            (KeyCode::Esc, _) => {
+                self.keymap.borrow_mut().reset_partial_match();
+                self.current_prefix = None;
+                true
             }
-            Command::NewlineAndIndent => {
-                self.newline_and_indent();
-                Ok(())
+            _ => false,
+        }
+    }
+
+    fn try_start_search(&mut self, key_event: &KeyEvent) -> bool {
+        if self.keymap.borrow().is_partial_match() {
+            return false;
+        }
+
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            // C-M-s / C-M-r: 正規表現検索
+            let regex = key_event.modifiers.contains(KeyModifiers::ALT);
+            let direction = match key_event.code {
+                KeyCode::Char('s') | KeyCode::Char('S') => Some(SearchDirection::Forward),
+                KeyCode::Char('r') | KeyCode::Char('R') => Some(SearchDirection::Backward),
+                _ => None,
+            };
+            if let (true, Some(direction)) = (regex, direction) {
+                self.keymap.borrow_mut().reset_partial_match();
+                self.current_prefix = None;
+                self.search.start_regex(&mut self.editor, direction);
+                return true;
             }
-            Command::OpenLine => {
-                self.open_line();
-                Ok(())
+            match key_event.code {
+                KeyCode::Char('s') | KeyCode::Char('S') => {
+                    self.keymap.borrow_mut().reset_partial_match();
+                    self.current_prefix = None;
+                    self.search
+                        .start(&mut self.editor, SearchDirection::Forward);
+                    return true;
+                }
+                KeyCode::Char('r') | KeyCode::Char('R') => {
+                    self.keymap.borrow_mut().reset_partial_match();
+                    self.current_prefix = None;
+                    self.search
+                        .start(&mut self.editor, SearchDirection::Backward);
+                    return true;
+                }
+                _ => {}
             }
-            Command::KillWordForward => {
-                self.kill_word_forward();
-                Ok(())
+        }
+
+        false
+    }
+
+    fn handle_search_key(&mut self, key_event: KeyEvent) {
+        use KeyModifiers as KM;
+
+        let modifiers = key_event.modifiers;
+
+        match key_event.code {
+            KeyCode::Char('s') | KeyCode::Char('S') if modifiers.contains(KM::CONTROL) => {
+                self.search.repeat_forward(&mut self.editor);
             }
-            Command::KillWordBackward => {
-                self.kill_word_backward();
-                Ok(())
+            KeyCode::Char('r') | KeyCode::Char('R') if modifiers.contains(KM::CONTROL) => {
+                self.search.repeat_backward(&mut self.editor);
             }
-            Command::KillLine => {
-                self.kill_line_forward();
-                Ok(())
+            KeyCode::Char('w') | KeyCode::Char('W') if modifiers.contains(KM::CONTROL) => {
+                self.search.add_word_at_cursor(&mut self.editor);
             }
-            Command::Yank => {
-                self.yank();
-                Ok(())
+            KeyCode::Char('r') if modifiers.contains(KM::ALT) => {
+                self.search.toggle_regex(&mut self.editor);
             }
-            Command::YankPop => {
-                self.yank_pop();
-                Ok(())
+            KeyCode::Char(' ') if modifiers.contains(KM::ALT) => {
+                self.search.toggle_lax_whitespace(&mut self.editor);
             }
-            Command::KeyboardQuit => {
-                self.keyboard_quit();
-                Ok(())
+            KeyCode::Char('p') if modifiers.contains(KM::ALT) => {
+                self.search.history_prev(&mut self.editor);
             }
-            Command::Undo => {
-                if let Err(err) = self.undo_edit() {
-                    self.show_error_message(err);
-                }
-                Ok(())
+            KeyCode::Char('c') if modifiers.contains(KM::ALT) => {
+                self.search.toggle_case_sensitivity(&mut self.editor);
             }
-            Command::Redo => {
-                if let Err(err) = self.redo_edit() {
-                    self.show_error_message(err);
-                }
-                Ok(())
+            KeyCode::Char('n') if modifiers.contains(KM::ALT) => {
+                self.search.history_next(&mut self.editor);
             }
-            Command::SetMark => {
-                self.set_mark_command();
-                Ok(())
+            KeyCode::Char('g') | KeyCode::Char('G') if modifiers.contains(KM::CONTROL) => {
+                self.search.cancel(&mut self.editor);
             }
-            Command::KillRegion => self.kill_region(),
-            Command::CopyRegion => self.copy_region(),
-            Command::ExchangePointAndMark => self.exchange_point_and_mark(),
-            Command::MarkBuffer => self.mark_entire_buffer(),
-            Command::ScrollPageDown => {
-                self.scroll_page_down();
-                Ok(())
+            KeyCode::Enter => {
+                self.search.accept();
             }
-            Command::ScrollPageUp => {
-                self.scroll_page_up();
-                Ok(())
+            KeyCode::Backspace => {
+                self.search.delete_char(&mut self.editor);
             }
-            Command::Recenter => {
-                self.recenter_view();
-                Ok(())
+            KeyCode::Esc => {
+                self.search.cancel(&mut self.editor);
             }
-            Command::ScrollLeft => {
-                self.scroll_left();
-                Ok(())
+            KeyCode::Char(ch) => {
+                if modifiers.contains(KM::CONTROL) || modifiers.contains(KM::ALT) {
+                    // 未対応の制御入力はキャンセル扱い
+                    if modifiers.contains(KM::CONTROL) && (ch == 'g' || ch == 'G') {
+                        self.search.cancel(&mut self.editor);
+                    }
+                } else {
+                    self.search.input_char(&mut self.editor, ch);
+                }
             }
-            Command::ScrollRight => {
-                self.scroll_right();
-                Ok(())
+            _ => {}
+        }
+    }
+
+    fn handle_replace_key(&mut self, key_event: KeyEvent) -> Result<bool> {
+        use KeyCode::*;
+        use KeyModifiers as KM;
+
+        if !self.replace.controller.is_active() {
+            return Ok(false);
+        }
+
+        match (key_event.code, key_event.modifiers) {
+            (Char('y'), KM::NONE) | (Char('Y'), KM::NONE) | (Char(' '), KM::NONE) => {
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.begin_history(HistoryCommandKind::Other);
+                match self.replace.controller.accept_current(&mut self.editor) {
+                    Ok(progress) => {
+                        self.end_history(true);
+                        self.after_replace_progress(progress);
+                    }
+                    Err(err) => {
+                        self.end_history(false);
+                        self.show_error_message(err);
+                    }
+                }
+                Ok(true)
             }
-            Command::SplitWindowBelow => {
-                self.split_window(SplitOrientation::Horizontal);
-                Ok(())
+            (Char('n'), KM::NONE) | (Char('N'), KM::NONE) => {
+                let progress = self.replace.controller.skip_current();
+                self.after_replace_progress(progress);
+                Ok(true)
             }
-            Command::SplitWindowRight => {
-                self.split_window(SplitOrientation::Vertical);
-                Ok(())
+            (Delete, _) | (Backspace, _) => {
+                let progress = self.replace.controller.skip_current();
+                self.after_replace_progress(progress);
+                Ok(true)
             }
-            Command::DeleteOtherWindows => {
-                self.delete_other_windows();
-                Ok(())
+            (Char('!'), KM::NONE) => {
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.begin_history(HistoryCommandKind::Other);
+                match self.replace.controller.accept_all(&mut self.editor) {
+                    Ok(progress) => {
+                        self.end_history(true);
+                        self.after_replace_progress(progress);
+                    }
+                    Err(err) => {
+                        self.end_history(false);
+                        self.show_error_message(err);
+                    }
+                }
+                Ok(true)
             }
-            Command::DeleteWindow => {
-                self.delete_current_window();
-                Ok(())
+            (Enter, KM::NONE) | (Char('q'), KM::NONE) | (Char('Q'), KM::NONE) => {
+                let summary = self.replace.controller.finish();
+                self.finish_replace_session(summary);
+                Ok(true)
             }
-            Command::OtherWindow => {
-                self.focus_next_window();
+            (Char('g'), modifiers) if modifiers.contains(KM::CONTROL) => {
+                self.begin_history(HistoryCommandKind::Other);
+                match self.replace.controller.cancel(&mut self.editor) {
+                    Ok(summary) => {
+                        self.end_history(true);
+                        self.finish_replace_session(summary);
+                    }
+                    Err(err) => {
+                        self.end_history(false);
+                        self.show_error_message(err);
+                    }
+                }
+                Ok(true)
+            }
+            _ => {
+                self.show_info_message(
+                    "yで置換、nでスキップ、!で残りを置換、qで終了、C-gでキャンセル",
+                );
+                Ok(true)
+            }
+        }
+    }
+
+    fn start_query_replace_session(
+        &mut self,
+        pattern: String,
+        replacement: String,
+        use_regex: bool,
+    ) -> Result<()> {
+        if pattern.is_empty() {
+            self.show_error_message(AltreError::Application("置換パターンが空です".to_string()));
+            return Ok(());
+        }
+
+        if self.replace.controller.is_active() {
+            let summary = self.replace.controller.finish();
+            self.finish_replace_session(summary);
+        }
+
+        if self.search.is_active() {
+            self.search.cancel(&mut self.editor);
+        }
+
+        let case_sensitive = pattern.chars().any(|c| c.is_uppercase());
+        let snapshot = self.editor.to_string();
+
+        let start = if use_regex {
+            match self.replace.controller.start_regex(
+                &snapshot,
+                pattern.clone(),
+                replacement.clone(),
+                case_sensitive,
+            ) {
+                Ok(info) => info,
+                Err(err) => {
+                    self.show_error_message(AltreError::Application(format!(
+                        "正規表現エラー: {}",
+                        err
+                    )));
+                    self.replace.reset();
+                    return Ok(());
+                }
+            }
+        } else {
+            self.replace.controller.start_literal(
+                &snapshot,
+                pattern.clone(),
+                replacement.clone(),
+                case_sensitive,
+            )
+        };
+
+        if start.total_matches == 0 || !self.replace.controller.is_active() {
+            self.replace.highlights.clear();
+            self.show_info_message("置換対象が見つかりません");
+            return Ok(());
+        }
+
+        if let Some((start_pos, _)) = self.replace.controller.current_range() {
+            let _ = self.editor.move_cursor_to_char(start_pos);
+        }
+
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.update_replace_view();
+        Ok(())
+    }
+
+    fn after_replace_progress(&mut self, progress: ReplaceProgress) {
+        if !self.replace.controller.is_active() || progress.finished {
+            let summary = self.replace.controller.finish();
+            self.finish_replace_session(summary);
+        } else {
+            self.update_replace_view();
+        }
+    }
+
+    fn update_replace_view(&mut self) {
+        if !self.replace.controller.is_active() {
+            self.replace.highlights.clear();
+            self.minibuffer.set_status_message(None);
+            return;
+        }
+
+        let snapshot = self.editor.to_string();
+        self.replace.highlights = self.replace.controller.highlights(&snapshot);
+        if let Some((start_pos, _)) = self.replace.controller.current_range() {
+            let _ = self.editor.move_cursor_to_char(start_pos);
+        }
+        self.minibuffer
+            .set_status_message(self.replace_prompt_message(&snapshot));
+        self.ensure_cursor_visible();
+    }
+
+    fn replace_prompt_message(&self, snapshot: &str) -> Option<String> {
+        let (original, replacement, index, total) =
+            self.replace.controller.current_preview(snapshot)?;
+        let label = if self.replace.controller.is_regex() {
+            "Regex replace"
+        } else {
+            "Query replace"
+        };
+        Some(format!(
+            "{} {}/{}: \"{}\" → \"{}\" (y=Yes, n=No, !=All, q=Quit)",
+            label,
+            index,
+            total,
+            Self::preview_fragment(&original),
+            Self::preview_fragment(&replacement)
+        ))
+    }
+
+    fn finish_replace_session(&mut self, summary: ReplaceSummary) {
+        self.replace.reset();
+        self.minibuffer.set_status_message(None);
+        if summary.cancelled {
+            self.show_info_message(format!(
+                "置換をキャンセルしました（置換 {} 件、スキップ {} 件）",
+                summary.replaced, summary.skipped
+            ));
+        } else {
+            self.show_info_message(format!(
+                "置換完了: {} 件置換、{} 件スキップ",
+                summary.replaced, summary.skipped
+            ));
+        }
+        self.ensure_cursor_visible();
+    }
+
+    fn preview_fragment(input: &str) -> String {
+        const MAX_LEN: usize = 24;
+
+        if input.is_empty() {
+            return "<empty>".to_string();
+        }
+
+        let mut result = String::new();
+        for (idx, ch) in input.chars().enumerate() {
+            if idx >= MAX_LEN {
+                result.push('…');
+                break;
+            }
+            let display = match ch {
+                '\n' => '↵',
+                '\t' => '⇥',
+                _ => ch,
+            };
+            result.push(display);
+        }
+        result
+    }
+
+    fn handle_action(&mut self, action: Action) -> Result<()> {
+        match action.to_command() {
+            Some(command) => self.execute_command(command),
+            None => {
+                self.show_error_message(AltreError::Application(
+                    "アクションをコマンドに変換できませんでした".to_string(),
+                ));
                 Ok(())
             }
-            Command::SwitchToBuffer => {
-                let buffers = self.buffer_names();
-                let initial = self.last_buffer_name();
-                self.minibuffer
-                    .start_switch_buffer(&buffers, initial.as_deref())?;
+        }
+    }
+
+    fn execute_command(&mut self, command: Command) -> Result<()> {
+        if !matches!(command, Command::InsertChar(_)) {
+            self.auto_completion.cancel();
+        }
+        self.paren_highlighter.on_command();
+
+        match command {
+            Command::FindFile => self.start_find_file_prompt(),
+            Command::ForwardChar => {
+                self.navigate(NavigationAction::MoveCharForward);
                 Ok(())
             }
-            Command::KillBuffer => {
-                let buffers = self.buffer_names();
-                let current_name = self
-                    .current_buffer()
-                    .map(|buffer| buffer.name().to_string());
-                self.minibuffer
-                    .start_kill_buffer(&buffers, current_name.as_deref())?;
+            Command::BackwardChar => {
+                self.navigate(NavigationAction::MoveCharBackward);
                 Ok(())
             }
-            Command::ListBuffers => {
-                self.show_buffer_list();
+            Command::GotoLine => {
+                self.goto_line_in_other_window = false;
+                self.start_goto_line_prompt()?;
                 Ok(())
             }
-            Command::WriteFile => {
-                // C-x C-w 実行時は常にファイルパスを確認
-                if let Some(buffer) = self.current_buffer() {
-                    let suggested = if let Some(ref path) = buffer.file.path {
-                        path.file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("untitled")
-                            .to_string()
-                    } else if buffer.file.name.trim().is_empty() {
-                        "untitled".to_string()
-                    } else {
-                        buffer.file.name.clone()
-                    };
-                    self.start_save_as_prompt(&suggested)?;
-                } else {
-                    self.start_save_as_prompt("untitled")?;
+            Command::GotoLineOtherWindow => {
+                self.goto_line_in_other_window = true;
+                self.start_goto_line_prompt()?;
+                Ok(())
+            }
+            Command::GotoChar => {
+                let current = self.editor.cursor().char_pos + 1;
+                let total = self.editor.to_string().chars().count() + 1;
+                if let Err(err) = self.minibuffer.start_goto_char(current, total) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::GotoColumn => {
+                self.start_goto_column_prompt();
+                Ok(())
+            }
+            Command::NextError | Command::PreviousError => {
+                // エラー位置を持つバッファ（コンパイル結果など）はまだない
+                self.show_info_message("エラー位置を含むバッファがありません");
+                Ok(())
+            }
+            Command::ScrollLineToTop => {
+                let current_line = self.editor.cursor().line + 1;
+                let total_lines = self.total_line_count();
+                if let Err(err) = self
+                    .minibuffer
+                    .start_scroll_line_to_top(current_line, total_lines)
+                {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
                 }
                 Ok(())
             }
+            Command::ForwardWord => {
+                self.navigate(NavigationAction::MoveWordForward);
+                Ok(())
+            }
+            Command::BackwardWord => {
+                self.navigate(NavigationAction::MoveWordBackward);
+                Ok(())
+            }
+            Command::NextLine => {
+                self.next_line();
+                Ok(())
+            }
+            Command::PreviousLine => {
+                self.navigate(NavigationAction::MoveLineUp);
+                Ok(())
+            }
+            Command::InsertChar(ch) => {
+                let replaces_region = self.replaces_active_region();
+                self.begin_history(if replaces_region {
+                    HistoryCommandKind::Other
+                } else {
+                    HistoryCommandKind::InsertChar
+                });
+                let result = self
+                    .delete_region_for_input()
+                    .and_then(|_| self.editor.insert_char(ch));
+                let success = result.is_ok();
+                if let Err(err) = result {
+                    self.show_error_message(err);
+                }
+                self.end_history(success);
+                if success && matches!(ch, ')' | ']' | '}') {
+                    self.paren_highlighter
+                        .on_insert(&self.editor.to_string(), self.editor.cursor().char_pos);
+                }
+                if success && is_word_char(ch) {
+                    self.auto_completion.notify_input();
+                } else {
+                    self.auto_completion.cancel();
+                }
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                Ok(())
+            }
+            Command::DeleteBackwardChar => {
+                self.begin_history(HistoryCommandKind::DeleteBackward);
+                let result = self.editor.delete_backward();
+                let success = result.is_ok();
+                if let Err(err) = result {
+                    self.show_error_message(err);
+                }
+                self.end_history(success);
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                Ok(())
+            }
+            Command::DeleteChar => {
+                self.begin_history(HistoryCommandKind::Other);
+                let result = self.editor.delete_forward();
+                let success = result.is_ok();
+                if let Err(err) = result {
+                    self.show_error_message(err);
+                }
+                self.end_history(success);
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                Ok(())
+            }
+            Command::InsertNewline => {
+                self.begin_history(HistoryCommandKind::Other);
+                let result = self
+                    .delete_region_for_input()
+                    .and_then(|_| self.editor.insert_newline());
+                let success = result.is_ok();
+                if let Err(err) = result {
+                    self.show_error_message(err);
+                }
+                self.end_history(success);
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                Ok(())
+            }
+            Command::IndentForTab => {
+                self.indent_for_tab();
+                Ok(())
+            }
+            Command::NewlineAndIndent => {
+                self.newline_and_indent();
+                Ok(())
+            }
+            Command::OpenLine => {
+                self.open_line();
+                Ok(())
+            }
+            Command::KillWordForward => {
+                self.kill_word_forward();
+                Ok(())
+            }
+            Command::KillWordBackward => {
+                self.kill_word_backward();
+                Ok(())
+            }
+            Command::KillLine => {
+                self.kill_line_forward();
+                Ok(())
+            }
+            Command::Yank => {
+                self.yank();
+                Ok(())
+            }
+            Command::YankPop => {
+                self.yank_pop();
+                Ok(())
+            }
+            Command::KeyboardQuit => {
+                self.keyboard_quit();
+                Ok(())
+            }
+            Command::Undo => {
+                if let Err(err) = self.undo_edit() {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Command::Redo => {
+                if let Err(err) = self.redo_edit() {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Command::SetMark => {
+                self.set_mark_command();
+                Ok(())
+            }
+            Command::KillRegion => self.kill_region(),
+            Command::CopyRegion => self.copy_region(),
+            Command::CopyWholeBuffer => {
+                self.copy_whole_buffer();
+                Ok(())
+            }
+            Command::CopyBufferPath => {
+                self.copy_buffer_path();
+                Ok(())
+            }
+            Command::ExchangePointAndMark => self.exchange_point_and_mark(),
+            Command::MarkBuffer => self.mark_entire_buffer(),
+            Command::ScrollPageDown => {
+                self.scroll_page_down();
+                Ok(())
+            }
+            Command::ScrollPageUp => {
+                self.scroll_page_up();
+                Ok(())
+            }
+            Command::Recenter => {
+                self.recenter_view();
+                Ok(())
+            }
+            Command::RecenterTop => {
+                self.reset_recenter_cycle();
+                self.recenter_at(RecenterPlacement::Top);
+                Ok(())
+            }
+            Command::RecenterCenter => {
+                self.reset_recenter_cycle();
+                self.recenter_at(RecenterPlacement::Center);
+                Ok(())
+            }
+            Command::RecenterBottom => {
+                self.reset_recenter_cycle();
+                self.recenter_at(RecenterPlacement::Bottom);
+                Ok(())
+            }
+            Command::ScrollLeft => {
+                self.scroll_left();
+                Ok(())
+            }
+            Command::ScrollRight => {
+                self.scroll_right();
+                Ok(())
+            }
+            Command::ToggleFold => {
+                self.toggle_fold();
+                Ok(())
+            }
+            Command::QuotedInsert => {
+                self.quoted_insert_pending = true;
+                self.show_info_message("C-q-");
+                Ok(())
+            }
+            Command::JustOneSpace => {
+                self.replace_horizontal_space(" ");
+                Ok(())
+            }
+            Command::DeleteHorizontalSpace => {
+                self.replace_horizontal_space("");
+                Ok(())
+            }
+            Command::CloseParens => {
+                self.close_parens();
+                Ok(())
+            }
+            Command::ToggleAutoCompletion => {
+                let enabled = !self.auto_completion_enabled();
+                self.set_auto_completion_enabled(enabled);
+                if enabled {
+                    self.show_info_message("自動補完を有効にしました");
+                } else {
+                    self.show_info_message("自動補完を無効にしました");
+                }
+                Ok(())
+            }
+            Command::SplitWindowBelow => {
+                self.split_window(SplitOrientation::Horizontal);
+                Ok(())
+            }
+            Command::SplitWindowRight => {
+                self.split_window(SplitOrientation::Vertical);
+                Ok(())
+            }
+            Command::DeleteOtherWindows => {
+                self.delete_other_windows();
+                Ok(())
+            }
+            Command::DeleteWindow => {
+                self.delete_current_window();
+                Ok(())
+            }
+            Command::OtherWindow => {
+                self.focus_next_window();
+                Ok(())
+            }
+            Command::UniversalArgument => {
+                self.universal_argument();
+                Ok(())
+            }
+            Command::SwapWindowBuffers => {
+                self.swap_window_buffers();
+                Ok(())
+            }
+            Command::OtherWindowSameBuffer => {
+                self.window_manager
+                    .set_buffer(self.window_manager.focused_window(), self.current_buffer_id);
+                if self.window_manager.focus_next_showing_same_buffer() {
+                    self.sync_focused_window_buffer();
+                    self.ensure_cursor_visible();
+                } else {
+                    self.show_info_message("同じバッファを表示している他のウィンドウがありません");
+                }
+                Ok(())
+            }
+            Command::SwitchToBuffer => {
+                let buffers = self.buffer_names_by_recency();
+                let default = self.last_buffer_name();
+                self.minibuffer
+                    .start_switch_buffer(&buffers, None, default.as_deref())?;
+                Ok(())
+            }
+            Command::KillBuffer => {
+                let buffers = self.buffer_names();
+                let current_name = self
+                    .current_buffer()
+                    .map(|buffer| buffer.name().to_string());
+                self.minibuffer
+                    .start_kill_buffer(&buffers, current_name.as_deref())?;
+                Ok(())
+            }
+            Command::ListBuffers => {
+                self.show_buffer_list();
+                Ok(())
+            }
+            Command::CloneBuffer => self.clone_current_buffer(),
+            Command::ToggleReadOnly => {
+                self.toggle_read_only();
+                Ok(())
+            }
+            Command::ToggleWordWrap => {
+                self.wrap_mode = self.wrap_mode.toggled();
+                self.show_info_message(match self.wrap_mode {
+                    WrapMode::Word => "単語境界で折り返します",
+                    WrapMode::Char => "文字境界で折り返します",
+                });
+                Ok(())
+            }
+            Command::ToggleTruncateLines => {
+                self.truncate_lines = !self.truncate_lines;
+                self.ensure_cursor_visible();
+                self.show_info_message(if self.truncate_lines {
+                    "長い行を切り詰めて表示します"
+                } else {
+                    "長い行を折り返して表示します"
+                });
+                Ok(())
+            }
+            Command::CycleControlCharDisplay => {
+                self.control_char_display = self.control_char_display.next();
+                self.show_info_message(match self.control_char_display {
+                    ControlCharDisplay::Caret => "制御文字を ^A の形式で表示します",
+                    ControlCharDisplay::Replacement => "制御文字を置換文字で表示します",
+                    ControlCharDisplay::CodePoint => {
+                        "制御文字と見えない文字を <200B> の形式で表示します"
+                    }
+                });
+                Ok(())
+            }
+            Command::ToggleDistractionFree => {
+                self.distraction_free = !self.distraction_free;
+                self.show_info_message(if self.distraction_free {
+                    "集中モードを有効にしました"
+                } else {
+                    "集中モードを無効にしました"
+                });
+                Ok(())
+            }
+            Command::ToggleKeywordHighlight => {
+                let enabled = !self.keyword_highlighter.is_enabled();
+                self.keyword_highlighter.set_enabled(enabled);
+                self.show_info_message(if enabled {
+                    "キーワードのハイライトを有効にしました"
+                } else {
+                    "キーワードのハイライトを無効にしました"
+                });
+                Ok(())
+            }
+            Command::ExportHtml => {
+                match self.export_html() {
+                    Ok((path, false)) => {
+                        self.show_info_message(format!("HTML を書き出しました: {}", path.display()))
+                    }
+                    Ok((path, true)) => self.show_info_message(format!(
+                        "既存の HTML を上書きしました: {}",
+                        path.display()
+                    )),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Command::ToggleLazyHighlight => {
+                let enabled = !self.search.lazy_highlight();
+                self.search.set_lazy_highlight(enabled);
+                self.show_info_message(if enabled {
+                    "検索中にすべての一致をハイライトします"
+                } else {
+                    "検索中は現在の一致だけをハイライトします"
+                });
+                Ok(())
+            }
+            Command::ToggleCharCase => {
+                self.toggle_char_case();
+                Ok(())
+            }
+            Command::CenterLine => {
+                self.center_lines();
+                Ok(())
+            }
+            Command::SetFillColumn => {
+                let column = self.cursor_display_column();
+                self.set_buffer_fill_column(column);
+                Ok(())
+            }
+            Command::FillParagraph => {
+                self.fill_paragraph();
+                Ok(())
+            }
+            Command::InsertPair(open) => {
+                self.insert_pair(open);
+                Ok(())
+            }
+            Command::Tabify | Command::Untabify => {
+                self.convert_region_tabs(matches!(command, Command::Tabify));
+                Ok(())
+            }
+            Command::CapitalizeDwim => {
+                self.capitalize_dwim();
+                Ok(())
+            }
+            Command::RepeatComplex => {
+                let buffers = self.buffer_names_by_recency();
+                let total_lines = self.total_line_count();
+                self.minibuffer
+                    .start_repeat_command(&buffers, total_lines)?;
+                Ok(())
+            }
+            Command::WriteFile => {
+                // C-x C-w 実行時は常にファイルパスを確認
+                if let Some(buffer) = self.current_buffer() {
+                    let suggested = if let Some(ref path) = buffer.file.path {
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("untitled")
+                            .to_string()
+                    } else if buffer.file.name.trim().is_empty() {
+                        "untitled".to_string()
+                    } else {
+                        buffer.file.name.clone()
+                    };
+                    self.start_save_as_prompt(&suggested)?;
+                } else {
+                    self.start_save_as_prompt("untitled")?;
+                }
+                Ok(())
+            }
+
+            Command::IsearchForward => {
+                self.search
+                    .start(&mut self.editor, SearchDirection::Forward);
+                Ok(())
+            }
+            Command::IsearchForwardFromTop => {
+                self.search
+                    .start_from(&mut self.editor, SearchDirection::Forward, 0);
+                Ok(())
+            }
+            Command::IsearchForwardRegexp => {
+                self.search
+                    .start_regex(&mut self.editor, SearchDirection::Forward);
+                Ok(())
+            }
+            Command::IsearchBackwardRegexp => {
+                self.search
+                    .start_regex(&mut self.editor, SearchDirection::Backward);
+                Ok(())
+            }
+            Command::CountMatches | Command::CountMatchesLiteral => {
+                let is_regex = matches!(command, Command::CountMatches);
+                if let Err(err) = self.minibuffer.start_count_matches(is_regex, None) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::Occur => {
+                if let Err(err) = self.minibuffer.start_occur(None) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::OccurGotoOccurrence => {
+                if let Err(err) = self.occur_goto_occurrence() {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Command::OccurRefresh => {
+                match self.occur_refresh() {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Command::FlushLines | Command::KeepLines => {
+                let keep = matches!(command, Command::KeepLines);
+                if let Err(err) = self.minibuffer.start_filter_lines(keep, None) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::StringRectangle => {
+                if self.editor.selection_range().is_none() {
+                    self.show_info_message("マークが設定されていません");
+                } else if let Err(err) = self.minibuffer.start_string_rectangle() {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::FindProblematicChars => {
+                self.find_problematic_char();
+                Ok(())
+            }
+            Command::ResetAlispEnv => {
+                self.minibuffer.reset_alisp_environment();
+                self.show_info_message("alisp の環境を初期化しました");
+                Ok(())
+            }
+            Command::ToggleDebugOnError => {
+                let enabled = !self.minibuffer.debug_on_error();
+                self.minibuffer.set_debug_on_error(enabled);
+                self.show_info_message(if enabled {
+                    "debug-on-error を有効にしました"
+                } else {
+                    "debug-on-error を無効にしました"
+                });
+                Ok(())
+            }
+            Command::DescribeFunction => {
+                let commands = self.minibuffer.available_commands().to_vec();
+                if let Err(err) = self.minibuffer.start_describe_function(&commands) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::InsertCharByCode => {
+                if let Err(err) = self.minibuffer.start_insert_char() {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::RevertBufferWithCodingSystem => {
+                if let Err(err) = self.minibuffer.start_coding_system(None) {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::CdBuffer => {
+                let initial_path = self.default_directory_input();
+                if let Err(err) = self
+                    .minibuffer
+                    .start_change_directory(Some(initial_path.as_str()))
+                {
+                    self.show_error_message(AltreError::Application(format!(
+                        "ミニバッファの初期化に失敗しました: {}",
+                        err
+                    )));
+                }
+                Ok(())
+            }
+            Command::SaveSession => {
+                if let Err(err) = self.save_default_session() {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Command::RestoreSession => {
+                self.restore_default_session();
+                Ok(())
+            }
+            Command::SaveAllBuffers => {
+                self.persist_current_buffer_state();
+
+                let original_id = self.current_buffer_id;
+                let mut saved_count = 0usize;
+
+                for idx in 0..self.buffers.len() {
+                    let buffer_clone = self.buffers[idx].file.clone();
+                    self.command_processor.set_current_buffer(buffer_clone);
+                    let content = self.buffers[idx].file.content.clone();
+                    self.command_processor.sync_editor_content(&content);
+
+                    let result = self.command_processor.execute(Command::SaveBuffer);
+                    if result.success {
+                        if let Some(updated) = self.command_processor.current_buffer().cloned() {
+                            self.buffers[idx].file = updated;
+                        }
+                        saved_count += 1;
+                    } else if let Some(msg) = result.message {
+                        self.show_error_message(AltreError::Application(msg));
+                        return Ok(());
+                    }
+                }
+
+                if let Some(id) = original_id {
+                    if let Some(index) = self.find_buffer_index(id) {
+                        let buffer_clone = self.buffers[index].file.clone();
+                        self.command_processor.set_current_buffer(buffer_clone);
+                        self.command_processor
+                            .sync_editor_content(&self.editor.to_string());
+                    }
+                }
+
+                self.report_save(format!("{} 個のバッファを保存しました", saved_count), None);
+                Ok(())
+            }
+
+            Command::SaveBuffer => {
+                self.persist_current_buffer_state();
+
+                if let Some(index) = self.current_buffer_index() {
+                    let needs_path = self.buffers[index].file.path.is_none();
+                    if needs_path {
+                        let suggested = if self.buffers[index].file.name.trim().is_empty() {
+                            "untitled".to_string()
+                        } else {
+                            self.buffers[index].file.name.clone()
+                        };
+                        self.start_save_as_prompt(&suggested)?;
+                    } else {
+                        let buffer_clone = self.buffers[index].file.clone();
+                        self.command_processor.set_current_buffer(buffer_clone);
+                        self.command_processor
+                            .sync_editor_content(&self.editor.to_string());
+
+                        let (result, written_path) = self.command_processor.save_current_buffer();
+                        if result.success {
+                            if let Some(updated) = self.command_processor.current_buffer().cloned()
+                            {
+                                self.buffers[index].file = updated;
+                            }
+                            if let Some(msg) = result.message {
+                                self.report_save(msg, written_path.as_deref());
+                            }
+                        } else if let Some(msg) = result.message {
+                            self.show_error_message(AltreError::Application(msg));
+                        }
+                    }
+                } else {
+                    self.start_save_as_prompt("untitled")?;
+                }
+                Ok(())
+            }
+            Command::SaveBuffersKillTerminal | Command::Quit => {
+                self.persist_current_buffer_state();
+                self.auto_save_session_on_exit();
+                self.shutdown();
+                Ok(())
+            }
+            Command::ExecuteCommand => self.start_execute_command_prompt(),
+            Command::EvalExpression => self.start_eval_expression_prompt(),
+            Command::QueryReplace => self.start_query_replace_prompt(false),
+            Command::RegexQueryReplace => self.start_query_replace_prompt(true),
+            Command::MoveLineStart => {
+                self.navigate(NavigationAction::MoveLineStart);
+                Ok(())
+            }
+            Command::MoveLineEnd => {
+                self.navigate(NavigationAction::MoveLineEnd);
+                Ok(())
+            }
+            Command::ForwardParagraph => {
+                self.navigate(NavigationAction::MoveParagraphForward);
+                Ok(())
+            }
+            Command::BackwardParagraph => {
+                self.navigate(NavigationAction::MoveParagraphBackward);
+                Ok(())
+            }
+            Command::MoveBufferStart => {
+                self.navigate(NavigationAction::MoveBufferStart);
+                Ok(())
+            }
+            Command::MoveBufferEnd => {
+                self.navigate(NavigationAction::MoveBufferEnd);
+                Ok(())
+            }
+            Command::Unknown(name) => {
+                if let Err(err) = self.run_custom_command(&name) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// カーソル以降の一致数（バッファ全体と異なれば併記）を表すメッセージ
+    fn count_matches_message(&self, pattern: &str, is_regex: bool) -> Result<String> {
+        let text = self.editor.to_string();
+        let (after_point, total) =
+            count_matches(&text, pattern, is_regex, self.editor.cursor().char_pos)
+                .map_err(|err| AltreError::Application(format!("正規表現が不正です: {}", err)))?;
+        Ok(if after_point == total {
+            format!("{} 件の一致", after_point)
+        } else {
+            format!("{} 件の一致（バッファ全体では {} 件）", after_point, total)
+        })
+    }
+
+    /// `source_id` のバッファで検索語に一致する箇所を `行:桁: 行の内容` の形で *Occur* バッファに並べる
+    ///
+    /// 一覧は読み取り専用で、元のバッファと検索語を覚えておき `occur-refresh` で作り直せる。
+    fn occur(&mut self, source_id: usize, pattern: &str) -> Result<String> {
+        let index = self.find_buffer_index(source_id).ok_or_else(|| {
+            AltreError::Application("一致を探したバッファがありません".to_string())
+        })?;
+        let source_name = self.buffers[index].name().to_string();
+        let text = if self.current_buffer_id == Some(source_id) {
+            self.editor.to_string()
+        } else {
+            self.buffers[index].file.content.clone()
+        };
+
+        let matches = occur_matches(&text, pattern);
+        if matches.is_empty() && !self.current_buffer_is_occur() {
+            return Ok(format!("\"{}\" に一致する箇所はありません", pattern));
+        }
+
+        let lines: Vec<&str> = text.split('\n').collect();
+        let mut listing = format!(
+            "{} 件の一致: \"{}\"（{}）",
+            matches.len(),
+            pattern,
+            source_name
+        );
+        for m in &matches {
+            listing.push_str(&format!(
+                "\n{}:{}: {}",
+                m.line + 1,
+                m.column + 1,
+                lines.get(m.line).copied().unwrap_or("")
+            ));
+        }
+
+        self.show_text_buffer(OCCUR_BUFFER_NAME, listing)?;
+        if let Some(occur_index) = self.current_buffer_index() {
+            self.buffers[occur_index].occur = Some(OccurSource {
+                buffer_id: source_id,
+                pattern: pattern.to_string(),
+                positions: matches.iter().map(|m| m.start).collect(),
+            });
+        }
+        self.set_current_read_only(true);
+        Ok(format!("{} 件の一致", matches.len()))
+    }
+
+    fn current_buffer_is_occur(&self) -> bool {
+        self.current_buffer()
+            .is_some_and(|buffer| buffer.occur.is_some())
+    }
+
+    fn current_occur_source(&self) -> Result<OccurSource> {
+        self.current_buffer()
+            .and_then(|buffer| buffer.occur.clone())
+            .ok_or_else(|| AltreError::Application("*Occur* バッファではありません".to_string()))
+    }
+
+    /// *Occur* バッファのカーソル行の一致箇所へ、元のバッファに切り替えて移動する
+    ///
+    /// `jump_in_other_window` が有効なら一覧を表示しているウィンドウを残し、別ウィンドウで開く。
+    fn occur_goto_occurrence(&mut self) -> Result<()> {
+        let source = self.current_occur_source()?;
+        let position = self
+            .editor
+            .cursor()
+            .line
+            .checked_sub(1)
+            .and_then(|index| source.positions.get(index).copied())
+            .ok_or_else(|| AltreError::Application("一致箇所の行ではありません".to_string()))?;
+        if self.find_buffer_index(source.buffer_id).is_none() {
+            return Err(AltreError::Application(
+                "一致を探したバッファがありません".to_string(),
+            ));
+        }
+
+        if self.jump_in_other_window {
+            self.select_other_window();
+        }
+        self.load_buffer_by_id(source.buffer_id, true)?;
+        let len = self.editor.to_string().chars().count();
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.editor.move_cursor_to_char(position.min(len))?;
+        self.ensure_cursor_visible();
+        Ok(())
+    }
+
+    /// 元のバッファの現在の内容で *Occur* バッファの一覧を作り直す
+    fn occur_refresh(&mut self) -> Result<String> {
+        let source = self.current_occur_source()?;
+        self.occur(source.buffer_id, &source.pattern)
+    }
+
+    /// リージョンに掛かる行（なければカーソル行以降）を正規表現で絞り込み、1回の取り消し単位にする
+    fn filter_lines(&mut self, pattern: &str, keep: bool) -> Result<String> {
+        let text = self.editor.to_string();
+        let line_starts = line_start_offsets(&text);
+        let text_len = text.chars().count();
+        let line_start_of =
+            |pos: usize| line_starts[line_starts.partition_point(|&s| s <= pos) - 1];
+        let region = self.editor.selection_range();
+        let (start, end) = match region {
+            // 行頭で終わるリージョンはその行を含めない
+            Some((start, end)) if end > start && line_starts.binary_search(&end).is_ok() => {
+                (line_start_of(start), end)
+            }
+            Some((start, end)) => (
+                line_start_of(start),
+                line_starts
+                    .get(line_starts.partition_point(|&s| s <= end))
+                    .copied()
+                    .unwrap_or(text_len),
+            ),
+            None => (line_start_of(self.editor.cursor().char_pos), text_len),
+        };
+
+        let segment: String = text.chars().skip(start).take(end - start).collect();
+        let (kept, removed) = filter_lines(&segment, pattern, keep)
+            .map_err(|err| AltreError::Application(format!("正規表現が不正です: {}", err)))?;
+        if removed == 0 {
+            return Ok("削除する行はありません".to_string());
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self
+            .editor
+            .replace_range_span(start, end, &kept)
+            .and_then(|_| self.editor.move_cursor_to_char(start));
+        self.end_history(result.is_ok());
+        result?;
+        if region.is_some() {
+            self.editor.clear_mark();
+        }
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+        Ok(format!("{} 行を削除しました", removed))
+    }
+
+    /// 登録コマンドを実行
+    fn run_custom_command(&mut self, name: &str) -> Result<()> {
+        let handler = self
+            .command_registry
+            .borrow()
+            .handler(name)
+            .ok_or_else(|| AltreError::Application(format!("不明なコマンド: {}", name)))?;
+        let mut handler = handler.try_borrow_mut().map_err(|_| {
+            AltreError::Application(format!(
+                "実行中のコマンドは再帰的に呼び出せません: {}",
+                name
+            ))
+        })?;
+        (*handler)(self)
+    }
+
+    fn record_kill(&mut self, text: String, merge: KillMerge) {
+        if text.is_empty() {
+            self.reset_kill_context();
+            return;
+        }
+
+        match merge {
+            KillMerge::Append => {
+                if matches!(self.kill_context, KillContext::Kill) {
+                    self.kill_ring.append_to_front(&text);
+                } else {
+                    self.kill_ring.push(text);
+                }
+            }
+            KillMerge::Prepend => {
+                if matches!(self.kill_context, KillContext::Kill) {
+                    self.kill_ring.prepend_to_front(&text);
+                } else {
+                    self.kill_ring.push(text);
+                }
+            }
+        }
+
+        self.kill_context = KillContext::Kill;
+        self.last_yank_range = None;
+    }
+
+    fn indent_for_tab(&mut self) {
+        if self.in_alisp_mode() {
+            let cursor = *self.editor.cursor();
+            if !self.alisp_indent_allowed_at(cursor.char_pos - cursor.column) {
+                return;
+            }
+            self.begin_history(HistoryCommandKind::Other);
+            let result = self.reindent_current_line();
+            self.finish_indent(result);
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let insertion = self.tab_insertion_string();
+        let result = self.editor.insert_str(&insertion);
+        let success = result.is_ok();
+
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    fn newline_and_indent(&mut self) {
+        if self.in_alisp_mode() {
+            self.begin_history(HistoryCommandKind::Other);
+            if !self.alisp_indent_allowed_at(self.editor.cursor().char_pos) {
+                let result = self.editor.insert_newline();
+                self.finish_indent(result);
+                return;
+            }
+            let result = self
+                .editor
+                .insert_newline()
+                .and_then(|_| self.reindent_current_line());
+            self.finish_indent(result);
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let indent = self.current_line_indent();
+        let mut success = false;
+
+        match self.editor.insert_newline() {
+            Ok(()) => {
+                let result = if indent.is_empty() {
+                    Ok(())
+                } else {
+                    self.editor.insert_str(&indent)
+                };
+
+                if let Err(err) = result {
+                    self.show_error_message(err);
+                } else {
+                    success = true;
+                }
+            }
+            Err(err) => {
+                self.show_error_message(err);
+            }
+        }
+
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    fn open_line(&mut self) {
+        self.begin_history(HistoryCommandKind::Other);
+        let cursor_before = *self.editor.cursor();
+        let mut success = false;
+
+        match self.editor.insert_newline() {
+            Ok(()) => {
+                self.editor.set_cursor(cursor_before);
+                success = true;
+            }
+            Err(err) => {
+                self.show_error_message(err);
+            }
+        }
+
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// C-q に続くキーを文字としてミニバッファまたはバッファへ挿入する
+    fn insert_quoted_key(&mut self, key_event: &KeyEvent) {
+        let Some(ch) = quoted_char(key_event) else {
+            self.show_info_message("このキーは挿入できません");
+            return;
+        };
+        if self.minibuffer.is_active() {
+            let key = Key::from(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+            if let Err(err) = self.minibuffer.handle_event(SystemEvent::KeyInput(key)) {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの処理に失敗しました: {}",
+                    err
+                )));
+            }
+            return;
+        }
+        self.insert_literal_char(ch);
+    }
+
+    /// 文字を1回の履歴操作として挿入する（`insert_char` が弾く制御文字も受け付ける）
+    fn insert_literal_char(&mut self, ch: char) {
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.insert_str(ch.encode_utf8(&mut [0; 4]));
+        let success = result.is_ok();
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// カーソルを囲む空白・タブを `replacement` に置き換える
+    fn replace_horizontal_space(&mut self, replacement: &str) {
+        let (start, end) = edit_utils::horizontal_space_range(
+            &self.editor.to_string(),
+            self.editor.cursor().char_pos,
+        );
+        let unchanged = self
+            .editor
+            .get_text_range(start, end)
+            .is_ok_and(|current| current == replacement);
+
+        if unchanged {
+            if let Err(err) = self.editor.move_cursor_to_char(end) {
+                self.show_error_message(err);
+            }
+        } else {
+            self.begin_history(HistoryCommandKind::Other);
+            let result = self.editor.replace_range_span(start, end, replacement);
+            let success = result.is_ok();
+            if let Err(err) = result {
+                self.show_error_message(err);
+            }
+            self.end_history(success);
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// カーソル位置の文字の大文字・小文字を反転して次の文字へ進む
+    fn toggle_char_case(&mut self) {
+        let pos = self.editor.cursor().char_pos;
+        let Some(ch) = self.editor.to_string().chars().nth(pos) else {
+            self.show_info_message("バッファの末尾です");
+            return;
+        };
+
+        let toggled = edit_utils::toggle_char_case(ch);
+        let result = if toggled == ch.to_string() {
+            self.editor.move_cursor_to_char(pos + 1)
+        } else {
+            self.begin_history(HistoryCommandKind::Other);
+            let result = self
+                .editor
+                .replace_range_span(pos, pos + 1, &toggled)
+                .map(|_| ());
+            self.end_history(result.is_ok());
+            result
+        };
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// リージョンがあればその中の単語を、なければ次の単語を先頭大文字にする
+    fn capitalize_dwim(&mut self) {
+        let text = self.editor.to_string();
+        let cursor = self.editor.cursor().char_pos;
+        let region = self.editor.selection_range();
+        let (start, end) = region.unwrap_or((cursor, edit_utils::next_word_end(&text, cursor)));
+        let original: String = text.chars().skip(start).take(end - start).collect();
+        let capitalized = edit_utils::capitalize_words(&original);
+
+        if capitalized != original {
+            self.begin_history(HistoryCommandKind::Other);
+            let mut result = self
+                .editor
+                .replace_range_span(start, end, &capitalized)
+                .map(|_| ());
+            if region.is_some() && result.is_ok() {
+                // 置換でずれたマークとポイントを元の向きで張り直す
+                let new_end = start + capitalized.chars().count();
+                let (mark_pos, point_pos) = if cursor == start {
+                    (new_end, start)
+                } else {
+                    (start, new_end)
+                };
+                self.editor.set_mark_at(mark_pos);
+                result = self.editor.move_cursor_to_char(point_pos);
+            }
+            if let Err(err) = &result {
+                self.show_error_message(err.clone());
+            }
+            self.end_history(result.is_ok());
+        } else if region.is_none() {
+            if let Err(err) = self.editor.move_cursor_to_char(end) {
+                self.show_error_message(err);
+            }
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// リージョンの空白をタブにまとめる（`tabify`）か、タブを空白に展開する
+    ///
+    /// 桁はリージョン先頭の行の行頭から数える。
+    fn convert_region_tabs(&mut self, tabify: bool) {
+        let Some((start, end)) = self.editor.selection_range() else {
+            self.show_info_message("リージョンが選択されていません");
+            return;
+        };
+        let text = self.editor.to_string();
+        let line_starts = line_start_offsets(&text);
+        let start = line_starts[line_starts.partition_point(|&s| s <= start) - 1];
+        let original: String = text.chars().skip(start).take(end - start).collect();
+        let converted = if tabify {
+            edit_utils::tabify(&original, self.tab_width, self.tabify_leading_only)
+        } else {
+            edit_utils::untabify(&original, self.tab_width)
+        };
+        if converted == original {
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.replace_range_span(start, end, &converted);
+        if let Err(err) = &result {
+            self.show_error_message(err.clone());
+        }
+        self.end_history(result.is_ok());
+        if result.is_ok() {
+            self.editor.clear_mark();
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// カレントバッファの fill-column を設定してミニバッファへ知らせる
+    fn set_buffer_fill_column(&mut self, column: usize) {
+        let previous = self.current_fill_column();
+        if let Some(index) = self.current_buffer_index() {
+            self.buffers[index].fill_column = Some(column);
+        }
+        self.show_info_message(format!(
+            "fill-column を {} に設定しました（以前は {}）",
+            column, previous
+        ));
+    }
+
+    /// カーソルのある段落（空行で区切られた範囲）を fill-column の幅で詰め直す
+    fn fill_paragraph(&mut self) {
+        let text = self.editor.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+        let line_starts = line_start_offsets(&text);
+        let cursor = self.editor.cursor().char_pos;
+        let cursor_line = line_starts.partition_point(|&s| s <= cursor) - 1;
+        let is_blank = |index: usize| lines[index].trim().is_empty();
+        if is_blank(cursor_line) {
+            return;
+        }
+        let mut first = cursor_line;
+        while first > 0 && !is_blank(first - 1) {
+            first -= 1;
+        }
+        let mut last = cursor_line;
+        while last + 1 < lines.len() && !is_blank(last + 1) {
+            last += 1;
+        }
+        let start = line_starts[first];
+        let end = line_starts[last] + lines[last].chars().count();
+        let original: String = text.chars().skip(start).take(end - start).collect();
+        let filled = edit_utils::fill_paragraph(&original, self.current_fill_column());
+        if filled == original {
+            return;
+        }
+
+        // カーソルは手前にある空白以外の文字数が同じ位置へ移す
+        let words_before = original
+            .chars()
+            .take(cursor - start)
+            .filter(|ch| !ch.is_whitespace())
+            .count();
+        let mut seen = 0;
+        let offset = filled
+            .chars()
+            .position(|ch| {
+                if ch.is_whitespace() {
+                    return false;
+                }
+                seen += 1;
+                seen > words_before
+            })
+            .unwrap_or_else(|| filled.chars().count());
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self
+            .editor
+            .replace_range_span(start, end, &filled)
+            .and_then(|_| self.editor.move_cursor_to_char(start + offset));
+        if let Err(err) = &result {
+            self.show_error_message(err.clone());
+        }
+        self.end_history(result.is_ok());
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// 現在行（リージョンがあれば含まれる各行）を fill-column の中央に寄せる
+    fn center_lines(&mut self) {
+        let fill_column = self.current_fill_column();
+        let text = self.editor.to_string();
+        let line_starts = line_start_offsets(&text);
+        let cursor = self.editor.cursor().char_pos;
+        let region = self.editor.selection_range();
+        let (from, to) = region.unwrap_or((cursor, cursor));
+        let first = line_starts.partition_point(|&s| s <= from) - 1;
+        let mut last = line_starts.partition_point(|&s| s <= to) - 1;
+        // 行頭で終わるリージョンはその行を含めない
+        if last > first && to == line_starts[last] {
+            last -= 1;
+        }
+        let start = line_starts[first];
+        let end = line_starts
+            .get(last + 1)
+            .map(|&next| next - 1)
+            .unwrap_or_else(|| text.chars().count());
+        let original: String = text.chars().skip(start).take(end - start).collect();
+        let centered = original
+            .split('\n')
+            .map(|line| edit_utils::center_line(line, fill_column))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if centered == original {
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let mut result = self
+            .editor
+            .replace_range_span(start, end, &centered)
+            .map(|_| ());
+        if result.is_ok() && region.is_none() {
+            // カーソルは行の内容に対する位置を保つ
+            let indent_of = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+            let offset = (cursor - start).saturating_sub(indent_of(&original));
+            let new_indent = indent_of(&centered);
+            let content_len = centered.chars().count() - new_indent;
+            result = self
+                .editor
+                .move_cursor_to_char(start + new_indent + offset.min(content_len));
+        }
+        if let Err(err) = &result {
+            self.show_error_message(err.clone());
+        }
+        self.end_history(result.is_ok());
+        if region.is_some() {
+            self.editor.clear_mark();
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// カーソルより後ろにある次の制御文字・幅 0 の見えない文字へ移動する
+    fn find_problematic_char(&mut self) {
+        let cursor = self.editor.cursor().char_pos;
+        let from = if self.problematic_char_found_at == Some(cursor) {
+            cursor + 1
+        } else {
+            cursor
+        };
+        let found = self
+            .editor
+            .to_string()
+            .chars()
+            .enumerate()
+            .skip(from)
+            .find(|&(_, ch)| is_problematic_char(ch));
+        let Some((position, ch)) = found else {
+            self.show_info_message("これ以降に制御文字や見えない文字はありません");
+            return;
+        };
+
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        if let Err(err) = self.editor.move_cursor_to_char(position) {
+            self.show_error_message(err);
+            return;
+        }
+        self.problematic_char_found_at = Some(position);
+        self.ensure_cursor_visible();
+        self.show_info_message(format!("U+{:04X} があります", ch as u32));
+    }
+
+    /// マークとカーソルを対角とする矩形の各行を `replacement` で置き換える
+    ///
+    /// 桁は表示幅で数え、矩形の左端に届かない行は空白で埋める。幅 0 の矩形なら各行に挿入する。
+    /// 1 回の undo で戻せ、カーソルは最後の行に入れた文字列の後ろへ移動する。
+    fn string_rectangle(&mut self, replacement: &str) -> Result<()> {
+        let (from, to) = self
+            .editor
+            .selection_range()
+            .ok_or_else(|| AltreError::Application("マークが設定されていません".to_string()))?;
+        let text = self.editor.to_string();
+        let line_starts = line_start_offsets(&text);
+        let line_of = |pos: usize| line_starts.partition_point(|&s| s <= pos) - 1;
+        let column_of = |pos: usize| {
+            let prefix: String = text
+                .chars()
+                .skip(line_starts[line_of(pos)])
+                .take(pos - line_starts[line_of(pos)])
+                .collect();
+            edit_utils::display_width(&prefix, self.tab_width)
+        };
+        let (first, last) = (line_of(from), line_of(to));
+        let (from_column, to_column) = (column_of(from), column_of(to));
+        let (left, right) = (from_column.min(to_column), from_column.max(to_column));
+
+        let start = line_starts[first];
+        let end = line_starts
+            .get(last + 1)
+            .map(|&next| next - 1)
+            .unwrap_or_else(|| text.chars().count());
+        let original: String = text.chars().skip(start).take(end - start).collect();
+        let replaced =
+            edit_utils::replace_rectangle(&original, left, right, replacement, self.tab_width);
+        let last_line = replaced.rsplit('\n').next().unwrap_or("");
+        let last_line_start = start + replaced.chars().count() - last_line.chars().count();
+        let (head, _) = edit_utils::char_index_at_display_column(last_line, left, self.tab_width);
+        let cursor = last_line_start + head + replacement.chars().count();
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self
+            .editor
+            .replace_range_span(start, end, &replaced)
+            .and_then(|_| self.editor.move_cursor_to_char(cursor));
+        self.end_history(result.is_ok());
+        self.editor.clear_mark();
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+        result
+    }
+
+    /// カーソルより前で閉じていない括弧をまとめて閉じる
+    fn close_parens(&mut self) {
+        let closers =
+            alisp_sexp::closing_brackets(&self.editor.to_string(), self.editor.cursor().char_pos);
+        if closers.is_empty() {
+            self.show_info_message("閉じていない括弧はありません");
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.insert_str(&closers);
+        let success = result.is_ok();
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// リージョンを区切り文字の組で囲んで閉じ文字の後へ移動する
+    /// （リージョンがなければ空の組を挿入してその間へ移動する）
+    fn insert_pair(&mut self, open: char) {
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '"' => '"',
+            other => {
+                self.show_error_message(AltreError::Application(format!(
+                    "対応する閉じ文字がありません: {}",
+                    other
+                )));
+                return;
+            }
+        };
+        let cursor = self.editor.cursor().char_pos;
+        let region = self.editor.selection_range();
+        let (start, end) = region.unwrap_or((cursor, cursor));
+        let target = if region.is_some() { end + 2 } else { start + 1 };
+
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self
+            .editor
+            .move_cursor_to_char(end)
+            .and_then(|_| self.editor.insert_str(&close.to_string()))
+            .and_then(|_| self.editor.move_cursor_to_char(start))
+            .and_then(|_| self.editor.insert_str(&open.to_string()))
+            .and_then(|_| self.editor.move_cursor_to_char(target));
+        if let Err(err) = &result {
+            self.show_error_message(err.clone());
+        }
+        self.end_history(result.is_ok());
+        if region.is_some() {
+            self.editor.clear_mark();
+        }
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    /// 現在のバッファを `<ファイル名>.html` に書き出す（alisp ならシンタックスハイライト付き）
+    ///
+    /// ファイルのないバッファは既定ディレクトリに `<バッファ名>.html` として書き出す。
+    /// 書き出したパスと、既存のファイルを上書きしたかを返す。
+    fn export_html(&mut self) -> Result<(PathBuf, bool)> {
+        let buffer = self
+            .current_buffer()
+            .ok_or_else(|| AltreError::Application("書き出すバッファがありません".to_string()))?;
+        let name = buffer.name().to_string();
+        let target = match buffer.path() {
+            Some(path) => {
+                let mut target = path.clone().into_os_string();
+                target.push(".html");
+                PathBuf::from(target)
+            }
+            None => self
+                .default_directory()
+                .unwrap_or_default()
+                .join(format!("{}.html", name.trim_matches('*'))),
+        };
+
+        let text = self.editor.to_string();
+        let spans = if self.in_alisp_mode() {
+            syntax_spans(&text)
+        } else {
+            Vec::new()
+        };
+        let mut themes = ThemeManager::new();
+        themes.set_theme(&self.theme_name);
+        let html = buffer_to_html(
+            &name,
+            &text,
+            &spans,
+            themes.current_theme(),
+            &self.gui_theme.borrow(),
+        );
+        let overwritten = target.exists();
+        FileSaver::new().save_file(&target, &html)?;
+        Ok((target, overwritten))
+    }
+
+    /// 現在のバッファが alisp のソースか
+    fn in_alisp_mode(&self) -> bool {
+        self.current_buffer()
+            .and_then(|buffer| buffer.path())
+            .is_some_and(|path| alisp_indent::is_alisp_path(path))
+    }
+
+    /// 指定位置で alisp の自動字下げを行うか（文字列・コメント内は設定に従う）
+    fn alisp_indent_allowed_at(&self, char_pos: usize) -> bool {
+        self.alisp_indent_in_literals
+            || syntax_context_at(&self.editor.to_string(), char_pos) == SyntaxContext::Code
+    }
+
+    /// 現在行の字下げを alisp の規則に合わせる（行頭の空白内にあるカーソルは字下げ後へ移す）
+    fn reindent_current_line(&mut self) -> Result<()> {
+        let cursor = *self.editor.cursor();
+        let text = self.editor.to_string();
+        let line_text = text.split('\n').nth(cursor.line).unwrap_or("");
+        let current_indent = line_text
+            .chars()
+            .take_while(|ch| matches!(ch, ' ' | '\t'))
+            .count();
+        let target = alisp_indent::indent_column(&text, cursor.line);
+        let line_start = cursor.char_pos - cursor.column;
+
+        if current_indent != target {
+            self.editor.replace_range_span(
+                line_start,
+                line_start + current_indent,
+                &" ".repeat(target),
+            )?;
+        }
+        let column = if cursor.column <= current_indent {
+            target
+        } else {
+            cursor.column - current_indent + target
+        };
+        self.editor.move_cursor_to_char(line_start + column)
+    }
+
+    fn finish_indent(&mut self, result: Result<()>) {
+        let success = result.is_ok();
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    fn current_line_indent(&self) -> String {
+        let cursor = *self.editor.cursor();
+        let text = self.editor.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+
+        let line_content = if cursor.line < lines.len() {
+            lines[cursor.line]
+        } else {
+            lines.last().copied().unwrap_or("")
+        };
+
+        line_content
+            .chars()
+            .take_while(|ch| matches!(ch, ' ' | '\t'))
+            .collect()
+    }
+
+    fn tab_insertion_string(&self) -> String {
+        let cursor = *self.editor.cursor();
+        let text = self.editor.to_string();
+        let line_content = text.split('\n').nth(cursor.line).unwrap_or("");
+        let spaces =
+            edit_utils::spaces_to_next_tab_stop(line_content, cursor.column, self.tab_width);
+        " ".repeat(spaces)
+    }
+
+    fn kill_word_forward(&mut self) {
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.delete_word_forward();
+        let success = result.is_ok();
+        match result {
+            Ok(text) => {
+                self.record_kill(text, KillMerge::Append);
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+            }
+            Err(err) => self.show_error_message(err),
+        }
+        self.end_history(success);
+    }
+
+    fn kill_word_backward(&mut self) {
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.delete_word_backward();
+        let success = result.is_ok();
+        match result {
+            Ok(text) => {
+                self.record_kill(text, KillMerge::Prepend);
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+            }
+            Err(err) => self.show_error_message(err),
+        }
+        self.end_history(success);
+    }
+
+    fn kill_line_forward(&mut self) {
+        self.begin_history(HistoryCommandKind::Other);
+        let result = self.editor.kill_line_forward();
+        let success = result.is_ok();
+        match result {
+            Ok(text) => {
+                self.record_kill(text, KillMerge::Append);
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+            }
+            Err(err) => self.show_error_message(err),
+        }
+        self.end_history(success);
+    }
+
+    fn set_mark_command(&mut self) {
+        self.editor.set_mark();
+        self.show_info_message("マークを設定しました");
+        self.reset_recenter_cycle();
+    }
+
+    fn kill_region(&mut self) -> Result<()> {
+        self.begin_history(HistoryCommandKind::Other);
+        let result = if let Some((start, end)) = self.editor.selection_range() {
+            match self.editor.delete_range_span(start, end) {
+                Ok(text) => {
+                    if text.is_empty() {
+                        self.show_info_message("選択範囲が空です");
+                    } else {
+                        self.record_kill(text, KillMerge::Append);
+                        self.kill_context = KillContext::Kill;
+                        self.last_yank_range = None;
+                    }
+                    self.editor.clear_mark();
+                    self.reset_recenter_cycle();
+                    self.ensure_cursor_visible();
+                    Ok(())
+                }
+                Err(err) => {
+                    self.show_error_message(err.clone());
+                    Err(err)
+                }
+            }
+        } else {
+            self.show_info_message("リージョンが選択されていません");
+            Ok(())
+        };
+        self.end_history(result.is_ok());
+        result
+    }
+
+    /// delete-selection モードで、次の入力がアクティブなリージョンを置き換えるか
+    fn replaces_active_region(&self) -> bool {
+        self.delete_selection && self.editor.selection_range().is_some()
+    }
+
+    /// delete-selection モードなら、入力の前にアクティブなリージョンを削除する
+    ///
+    /// 続く挿入と同じ履歴操作の中で呼び、1 回の undo で両方を戻せるようにする。
+    fn delete_region_for_input(&mut self) -> Result<()> {
+        if !self.replaces_active_region() {
+            return Ok(());
+        }
+        if let Some((start, end)) = self.editor.selection_range() {
+            self.editor.delete_range_span(start, end)?;
+        }
+        self.editor.clear_mark();
+        Ok(())
+    }
+
+    fn copy_region(&mut self) -> Result<()> {
+        if let Some((start, end)) = self.editor.selection_range() {
+            let text = self.editor.get_text_range(start, end)?;
+            if text.is_empty() {
+                self.show_info_message("選択範囲が空です");
+            } else {
+                self.record_kill(text, KillMerge::Append);
+                self.kill_context = KillContext::Kill;
+                self.last_yank_range = None;
+                self.show_info_message("リージョンをコピーしました");
+            }
+            self.editor.deactivate_mark();
+        } else {
+            self.show_info_message("リージョンが選択されていません");
+        }
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+        Ok(())
+    }
+
+    fn copy_whole_buffer(&mut self) {
+        let text = self.editor.to_string();
+        if text.is_empty() {
+            self.show_info_message("バッファが空です");
+            return;
+        }
+        let count = text.chars().count();
+        self.push_kill_entry(text);
+        self.show_info_message(format!("バッファ全体（{} 文字）をコピーしました", count));
+    }
+
+    fn copy_buffer_path(&mut self) {
+        let Some(path) = self
+            .current_buffer()
+            .and_then(|buffer| buffer.path())
+            .map(|path| path.display().to_string())
+        else {
+            self.show_info_message("このバッファにはファイルパスがありません");
+            return;
+        };
+        self.push_kill_entry(path.clone());
+        self.show_info_message(format!("パスをコピーしました: {}", path));
+    }
+
+    /// 直前のキルに連結せず、新しいエントリとしてキルリングに積む
+    fn push_kill_entry(&mut self, text: String) {
+        self.reset_kill_context();
+        self.kill_ring.push(text);
+    }
+
+    fn exchange_point_and_mark(&mut self) -> Result<()> {
+        if self.editor.mark().is_none() {
+            self.show_info_message("マークが設定されていません");
+            return Ok(());
+        }
+        self.editor.swap_cursor_and_mark()?;
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+        Ok(())
+    }
+
+    fn mark_entire_buffer(&mut self) -> Result<()> {
+        self.editor.mark_entire_buffer()?;
+        self.show_info_message("バッファ全体を選択しました");
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+        Ok(())
+    }
+
+    fn yank(&mut self) {
+        let Some(text) = self.kill_ring.yank() else {
+            self.show_info_message("キルリングが空です");
+            self.reset_kill_context();
+            return;
+        };
+
+        self.begin_history(HistoryCommandKind::Other);
+        if let Err(err) = self.delete_region_for_input() {
+            self.reset_kill_context();
+            self.show_error_message(err);
+            self.end_history(false);
+            return;
+        }
+
+        let start = self.editor.cursor().char_pos;
+        let len = text.chars().count();
+
+        match self.editor.insert_str(&text) {
+            Ok(_) => {
+                self.kill_context = KillContext::Yank;
+                self.last_yank_range = Some((start, len));
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                self.end_history(true);
+            }
+            Err(err) => {
+                self.reset_kill_context();
+                self.show_error_message(err);
+                self.end_history(false);
+            }
+        }
+    }
+
+    fn yank_pop(&mut self) {
+        if !matches!(self.kill_context, KillContext::Yank) {
+            self.show_info_message("直前のコマンドがヤンクではありません");
+            self.reset_kill_context();
+            return;
+        }
+
+        let Some((start, previous_len)) = self.last_yank_range else {
+            self.show_info_message("ヤンク範囲が不明です");
+            self.reset_kill_context();
+            return;
+        };
+
+        if let Err(err) = self.editor.move_cursor_to_char(start) {
+            self.reset_kill_context();
+            self.show_error_message(err);
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        if let Err(err) = self.editor.delete_range(start, start + previous_len) {
+            self.reset_kill_context();
+            self.show_error_message(err);
+            self.end_history(false);
+            return;
+        }
+
+        let Some(next_text) = self.kill_ring.rotate() else {
+            self.reset_kill_context();
+            self.show_info_message("キルリングが空です");
+            return;
+        };
+
+        let new_len = next_text.chars().count();
+        if let Err(err) = self.editor.insert_str(&next_text) {
+            self.reset_kill_context();
+            self.show_error_message(err);
+            self.end_history(false);
+            return;
+        }
+
+        self.kill_context = KillContext::Yank;
+        self.last_yank_range = Some((start, new_len));
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+        self.end_history(true);
+    }
+
+    fn undo_edit(&mut self) -> Result<()> {
+        match self.history.undo(&mut self.editor) {
+            Ok(true) => {
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                self.persist_current_buffer_state();
+                Ok(())
+            }
+            Ok(false) => {
+                self.show_info_message("取り消す操作はありません");
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn redo_edit(&mut self) -> Result<()> {
+        match self.history.redo(&mut self.editor) {
+            Ok(true) => {
+                self.reset_kill_context();
+                self.reset_recenter_cycle();
+                self.ensure_cursor_visible();
+                self.persist_current_buffer_state();
+                Ok(())
+            }
+            Ok(false) => {
+                self.show_info_message("やり直す操作はありません");
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// 有効な状態のうち最も内側のものを一つだけ取り消す
+    ///
+    /// 優先順位はプロンプト > 置換 > 検索 > マーク > プレフィックスキー。
+    /// マークを解除するときは入力途中のプレフィックスキーも合わせて取り消す。
+    fn keyboard_quit(&mut self) {
+        self.prefix_argument = None;
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        if self.minibuffer.is_active() {
+            self.minibuffer.deactivate();
+            self.pending_confirmation = None;
+        } else if self.replace.controller.is_active() {
+            self.begin_history(HistoryCommandKind::Other);
+            match self.replace.controller.cancel(&mut self.editor) {
+                Ok(summary) => {
+                    self.end_history(true);
+                    self.finish_replace_session(summary);
+                }
+                Err(err) => {
+                    self.end_history(false);
+                    self.show_error_message(err);
+                }
+            }
+            self.ensure_cursor_visible();
+            return;
+        } else if self.search.is_active() {
+            self.search.cancel(&mut self.editor);
+        } else if self.editor.is_mark_active() {
+            self.editor.deactivate_mark();
+            self.reset_key_sequence();
+        } else {
+            self.reset_key_sequence();
+        }
+        self.show_info_message("キャンセルしました");
+        self.ensure_cursor_visible();
+    }
+
+    fn reset_key_sequence(&mut self) {
+        self.keymap.borrow_mut().reset_partial_match();
+        self.current_prefix = None;
+    }
+
+    fn reset_kill_context(&mut self) {
+        self.kill_context = KillContext::None;
+        self.last_yank_range = None;
+    }
+
+    fn begin_history(&mut self, kind: HistoryCommandKind) {
+        self.history.begin_command(kind, &self.editor);
+    }
+
+    fn end_history(&mut self, success: bool) {
+        self.history.end_command(&self.editor, success);
+    }
+
+    fn reset_recenter_cycle(&mut self) {
+        self.recenter_step = 0;
+    }
+
+    fn buffer_metrics(&self) -> (usize, usize) {
+        let content = self.editor.to_string();
+        if content.is_empty() {
+            return (1, 0);
+        }
+
+        let mut lines = 0usize;
+        let mut max_columns = 0usize;
+        for line in content.lines() {
+            lines += 1;
+            let columns = line.chars().count();
+            if columns > max_columns {
+                max_columns = columns;
+            }
+        }
+
+        (lines.max(1), max_columns)
+    }
+
+    /// 表示中のウィンドウに見えている行の範囲（折りたたまれた行は高さに数えない）
+    fn visible_line_range(&self, line_count: usize) -> std::ops::Range<usize> {
+        let viewport = self.current_viewport();
+        let top = viewport.top_line.min(line_count.saturating_sub(1));
+        let folds = self.editor.folds();
+        let mut end = top;
+        let mut shown = 0usize;
+        while end < line_count && shown < viewport.height.max(1) {
+            if !folds.is_hidden(end) {
+                shown += 1;
+            }
+            end += 1;
+        }
+        top..end
+    }
+
+    /// バッファのオーバーレイに検索・置換・選択範囲を重ね、優先度で解決した行単位のハイライト
+    ///
+    /// 表示中の行に掛かるオーバーレイだけを解決する。
+    fn overlay_highlights(&self) -> Vec<SearchHighlight> {
+        let selection = self.editor.selection_range();
+        if self.editor.overlays().is_empty()
+            && self.search.highlights().is_empty()
+            && self.replace.highlights.is_empty()
+            && selection.is_none()
+            && !self.keyword_highlighter.is_enabled()
+            && self.paren_highlighter.mode() == ParenHighlightMode::Off
+        {
+            return Vec::new();
+        }
+
+        let text = self.editor.to_string();
+        let line_starts = line_start_offsets(&text);
+        let text_len = text.chars().count();
+        let visible = self.visible_line_range(line_starts.len());
+        let visible_start = line_starts[visible.start];
+        let visible_end = line_starts
+            .get(visible.end)
+            .copied()
+            .unwrap_or(text_len + 1);
+
+        let mut overlays: Vec<Overlay> = self.editor.overlays().iter().copied().collect();
+        overlays.extend(
+            self.search
+                .highlights()
+                .iter()
+                .chain(self.replace.highlights.iter())
+                .filter(|highlight| visible.contains(&highlight.line))
+                .filter_map(|highlight| {
+                    let line_start = *line_starts.get(highlight.line)?;
+                    let kind = match highlight.kind {
+                        HighlightKind::Selection => OverlayKind::Selection,
+                        HighlightKind::Keyword => OverlayKind::Keyword,
+                        HighlightKind::MatchingParen => OverlayKind::MatchingParen,
+                        HighlightKind::Search if highlight.is_current => OverlayKind::CurrentMatch,
+                        HighlightKind::Search => OverlayKind::Search,
+                    };
+                    Some(Overlay::new(
+                        line_start + highlight.start_column,
+                        line_start + highlight.end_column,
+                        kind,
+                    ))
+                }),
+        );
+        if let Some((start, end)) = selection {
+            overlays.push(Overlay::new(start, end, OverlayKind::Selection));
+        }
+        if self.keyword_highlighter.is_enabled() {
+            // 表示される行だけを走査する
+            for (line_text, &line_start) in text
+                .split('\n')
+                .zip(&line_starts)
+                .skip(visible.start)
+                .take(visible.len())
+            {
+                overlays.extend(
+                    self.keyword_highlighter
+                        .find_in_line(line_text)
+                        .into_iter()
+                        .map(|(start, end)| {
+                            Overlay::new(line_start + start, line_start + end, OverlayKind::Keyword)
+                        }),
+                );
+            }
+        }
+
+        if let Some((open, close)) = self
+            .paren_highlighter
+            .highlight(&text, self.editor.cursor().char_pos)
+        {
+            overlays.push(Overlay::new(open, open + 1, OverlayKind::MatchingParen));
+            overlays.push(Overlay::new(close, close + 1, OverlayKind::MatchingParen));
+        }
+        overlays.retain(|overlay| overlay.start < visible_end && overlay.end > visible_start);
+
+        let mut highlights = Vec::new();
+        for span in resolve_overlays(&overlays) {
+            let (kind, is_current) = match span.kind {
+                OverlayKind::Keyword => (HighlightKind::Keyword, false),
+                OverlayKind::MatchingParen => (HighlightKind::MatchingParen, false),
+                OverlayKind::Selection => (HighlightKind::Selection, false),
+                OverlayKind::Search => (HighlightKind::Search, false),
+                OverlayKind::CurrentMatch => (HighlightKind::Search, true),
+            };
+            let first_line = line_starts.partition_point(|&offset| offset <= span.start) - 1;
+            for (line, &line_start) in line_starts
+                .iter()
+                .enumerate()
+                .take(visible.end)
+                .skip(first_line.max(visible.start))
+            {
+                if line_start >= span.end {
+                    break;
+                }
+                let line_end = line_starts.get(line + 1).map_or(text_len, |next| next - 1);
+                let start = span.start.max(line_start);
+                let end = span.end.min(line_end);
+                if end > start {
+                    highlights.push(SearchHighlight {
+                        line,
+                        start_column: start - line_start,
+                        end_column: end - line_start,
+                        is_current,
+                        kind,
+                    });
+                }
+            }
+        }
+        highlights
+    }
+
+    fn status_line_data(&self) -> (String, bool) {
+        if let Some(buffer) = self.current_buffer() {
+            let label = if let Some(path) = buffer.path() {
+                path.display().to_string()
+            } else if buffer.name().trim().is_empty() {
+                "[未保存] *scratch*".to_string()
+            } else {
+                format!("[未保存] {}", buffer.name())
+            };
+            (label, buffer.is_modified())
+        } else {
+            ("[バッファなし]".to_string(), false)
+        }
+    }
+
+    fn ensure_cursor_visible(&mut self) {
+        let (total_lines, max_columns) = self.buffer_metrics();
+        let cursor_line = self.editor.cursor().line;
+        let cursor_column = self.editor.cursor().column;
+
+        {
+            let viewport = self.current_viewport_mut();
+            viewport.clamp_vertical(total_lines);
+
+            let height = viewport.height.max(1);
+            if cursor_line < viewport.top_line {
+                viewport.top_line = cursor_line;
+            } else if cursor_line >= viewport.top_line + height {
+                viewport.top_line = cursor_line + 1 - height;
+            }
+
+            viewport.clamp_vertical(total_lines);
+        }
+
+        if let Some(wrap) = self.line_wrap() {
+            self.scroll_to_wrapped_cursor(wrap);
+            return;
+        }
+
+        {
+            let viewport = self.current_viewport_mut();
+            if cursor_column < viewport.scroll_x {
+                viewport.scroll_x = cursor_column;
+            } else if cursor_column >= viewport.scroll_x + viewport.width {
+                viewport.scroll_x = cursor_column + 1 - viewport.width;
+            }
+
+            viewport.clamp_horizontal(max_columns);
+        }
+    }
+
+    /// 折り返し表示中の設定（切り詰めて表示するなら `None`）
+    fn line_wrap(&self) -> Option<LineWrap> {
+        (!self.truncate_lines).then_some(LineWrap {
+            mode: self.wrap_mode,
+            column: self.wrap_column,
+            centered: self.wrap_centered,
+        })
+    }
+
+    /// 折り返した表示行でカーソルが画面の下にはみ出さないよう表示開始行を進める
+    fn scroll_to_wrapped_cursor(&mut self, wrap: LineWrap) {
+        let cursor = *self.editor.cursor();
+        let text = self.editor.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+        let viewport = self.current_viewport();
+        let (width, height) = (viewport.width, viewport.height.max(1));
+        let folds = self.editor.folds();
+        let rows_of = |line: usize| -> usize {
+            if folds.is_hidden(line) {
+                0
+            } else {
+                lines
+                    .get(line)
+                    .map_or(1, |text| wrap.wrap_points(text, width).len() + 1)
+            }
+        };
+        let cursor_offset = lines
+            .get(cursor.line)
+            .map_or(0, |text| wrap.position_of(text, cursor.column, width).0);
+
+        let mut top = viewport.top_line.min(cursor.line);
+        let mut rows: usize = (top..cursor.line).map(rows_of).sum::<usize>() + cursor_offset + 1;
+        while rows > height && top < cursor.line {
+            rows -= rows_of(top);
+            top += 1;
+        }
+        let viewport = self.current_viewport_mut();
+        viewport.top_line = top;
+        viewport.scroll_x = 0;
+    }
+
+    fn move_cursor_vertical(&mut self, delta: isize) {
+        if delta > 0 {
+            for _ in 0..delta {
+                match self.editor.navigate(NavigationAction::MoveLineDown) {
+                    Ok(true) => {}
+                    _ => break,
+                }
+            }
+        } else {
+            for _ in 0..delta.unsigned_abs() {
+                match self.editor.navigate(NavigationAction::MoveLineUp) {
+                    Ok(true) => {}
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    fn move_cursor_horizontal(&mut self, delta: isize) {
+        if delta > 0 {
+            for _ in 0..delta {
+                match self.editor.navigate(NavigationAction::MoveCharForward) {
+                    Ok(true) => {}
+                    _ => break,
+                }
+            }
+        } else {
+            for _ in 0..delta.unsigned_abs() {
+                match self.editor.navigate(NavigationAction::MoveCharBackward) {
+                    Ok(true) => {}
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    /// ページスクロール時に重ねて表示する行数を取得
+    pub fn next_screen_context_lines(&self) -> usize {
+        self.next_screen_context_lines
+    }
+
+    /// ページスクロール時に重ねて表示する行数を設定
+    pub fn set_next_screen_context_lines(&mut self, lines: usize) {
+        self.next_screen_context_lines = lines;
+    }
+
+    /// スクロールコマンドの移動量を取得
+    pub fn scroll_step(&self) -> isize {
+        self.scroll_step
+    }
+
+    /// スクロールコマンドの移動量を設定
+    ///
+    /// 0 はページ単位（`next-screen-context-lines` 行を重ねる）、正の値はその行数ずつ、
+    /// 負の値はページ単位でカーソルを移し、カーソル行が画面中央に来るようスクロールする。
+    pub fn set_scroll_step(&mut self, step: isize) {
+        self.scroll_step = step;
+    }
+
+    /// 分割挿入に切り替える文字数のしきい値を取得
+    pub fn large_insert_threshold(&self) -> usize {
+        self.large_insert_threshold
+    }
+
+    /// 分割挿入に切り替える文字数のしきい値を設定
+    pub fn set_large_insert_threshold(&mut self, chars: usize) {
+        self.large_insert_threshold = chars;
+    }
+
+    /// 分割挿入中かどうか
+    pub fn is_inserting(&self) -> bool {
+        self.pending_insert.is_some()
+    }
+
+    /// ペーストされたテキストを挿入
+    ///
+    /// しきい値を超える場合は分割挿入を開始し、`process_pending_insert` で少しずつ挿入する。
+    pub fn paste_text(&mut self, text: &str) {
+        if self.pending_insert.is_some() {
+            self.show_info_message("挿入処理中です（C-g でキャンセル）");
+            return;
+        }
+
+        // プロンプトや isearch の入力中はバッファではなくその入力へ送る
+        if self.minibuffer.insert_text(text) {
+            return;
+        }
+        if self.search.is_active() {
+            for ch in text.chars().filter(|ch| !matches!(ch, '\n' | '\r')) {
+                self.search.input_char(&mut self.editor, ch);
+            }
+            self.ensure_cursor_visible();
+            return;
+        }
+
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.insert_text_as_one_step(text);
+    }
+
+    /// テキストを 1 回の履歴操作として挿入する（しきい値を超える場合は分割挿入を始める）
+    fn insert_text_as_one_step(&mut self, text: String) {
+        let total_chars = text.chars().count();
+        if total_chars == 0 {
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let replaced = match self.editor.selection_range() {
+            Some((start, end)) if self.replaces_active_region() => {
+                self.editor.get_text_range(start, end).unwrap_or_default()
+            }
+            _ => String::new(),
+        };
+        if let Err(err) = self.delete_region_for_input() {
+            self.end_history(false);
+            self.show_error_message(err);
+            return;
+        }
+        if total_chars <= self.large_insert_threshold {
+            match self.editor.insert_str(&text) {
+                Ok(_) => self.end_history(true),
+                Err(err) => {
+                    self.end_history(false);
+                    self.show_error_message(err);
+                }
+            }
+            self.ensure_cursor_visible();
+            return;
+        }
+
+        self.pending_insert = Some(PendingInsert {
+            text,
+            offset: 0,
+            start: self.editor.cursor().char_pos,
+            inserted_chars: 0,
+            total_chars,
+            replaced,
+        });
+        self.show_insert_progress(0, total_chars);
+    }
+
+    /// 分割挿入を1チャンク進める（イベントループの各周期で呼び出す）
+    pub fn process_pending_insert(&mut self) {
+        let Some(mut pending) = self.pending_insert.take() else {
+            return;
+        };
+
+        let rest = &pending.text[pending.offset..];
+        let chunk_end = rest
+            .char_indices()
+            .nth(LARGE_INSERT_CHUNK_CHARS)
+            .map(|(index, _)| pending.offset + index)
+            .unwrap_or(pending.text.len());
+        let chunk = &pending.text[pending.offset..chunk_end];
+        let chunk_chars = chunk.chars().count();
+
+        if let Err(err) = self.editor.insert_str(chunk) {
+            self.rollback_pending_insert(&pending);
+            self.show_error_message(err);
+            return;
+        }
+
+        pending.offset = chunk_end;
+        pending.inserted_chars += chunk_chars;
+
+        if pending.offset >= pending.text.len() {
+            self.end_history(true);
+            self.ensure_cursor_visible();
+            self.show_info_message(format!("{} 文字を挿入しました", pending.total_chars));
+        } else {
+            self.show_insert_progress(pending.inserted_chars, pending.total_chars);
+            self.pending_insert = Some(pending);
+        }
+    }
+
+    fn cancel_pending_insert(&mut self) {
+        let Some(pending) = self.pending_insert.take() else {
+            return;
+        };
+        self.keymap.borrow_mut().reset_partial_match();
+        self.current_prefix = None;
+        self.rollback_pending_insert(&pending);
+        self.show_info_message("挿入をキャンセルしました");
+    }
+
+    /// 途中まで挿入したテキストを取り除き、履歴にも残さない
+    fn rollback_pending_insert(&mut self, pending: &PendingInsert) {
+        let end = pending.start + pending.inserted_chars;
+        if pending.inserted_chars > 0 {
+            if let Err(err) = self.editor.delete_range_span(pending.start, end) {
+                self.show_error_message(err);
+            }
+        }
+        if let Err(err) = self.editor.move_cursor_to_char(pending.start) {
+            self.show_error_message(err);
+        }
+        if !pending.replaced.is_empty() {
+            if let Err(err) = self.editor.insert_str(&pending.replaced) {
+                self.show_error_message(err);
+            }
+        }
+        self.end_history(false);
+        self.ensure_cursor_visible();
+    }
+
+    fn show_insert_progress(&mut self, inserted: usize, total: usize) {
+        let percent = inserted * 100 / total.max(1);
+        self.show_info_message(format!("挿入中... {}%（C-g でキャンセル）", percent));
+    }
+
+    /// アイドル時の自動補完が有効かどうか
+    pub fn auto_completion_enabled(&self) -> bool {
+        self.auto_completion.is_enabled()
+    }
+
+    /// アイドル時の自動補完を有効・無効にする
+    pub fn set_auto_completion_enabled(&mut self, enabled: bool) {
+        self.auto_completion.set_enabled(enabled);
+    }
+
+    /// 自動補完を表示するまでの待ち時間を設定
+    pub fn set_auto_completion_delay(&mut self, delay: std::time::Duration) {
+        self.auto_completion.set_delay(delay);
+    }
+
+    /// アイドルタイマーを処理（イベントループの各周期で呼び出す）
+    pub fn process_idle_timers(&mut self) {
+        // ミニバッファや検索の操作中は補完を出さない
+        if self.minibuffer.is_active() || self.search.is_active() || self.pending_insert.is_some() {
+            self.auto_completion.cancel();
+            return;
+        }
+        let cursor = self.editor.cursor().char_pos;
+        self.auto_completion.poll(&self.editor.to_string(), cursor);
+    }
+
+    /// 補完ポップアップへのキー入力（処理した場合は `true`）
+    fn handle_completion_popup_key(&mut self, key_event: &KeyEvent) -> bool {
+        if !key_event.modifiers.is_empty() {
+            self.auto_completion.dismiss();
+            // C-g はポップアップを閉じるだけで他の状態は残す
+            return key_event.code == KeyCode::Char('g')
+                && key_event.modifiers.contains(KeyModifiers::CONTROL);
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.auto_completion.dismiss();
+            }
+            KeyCode::Down => {
+                if let Some(popup) = self.auto_completion.popup_mut() {
+                    popup.select_next();
+                }
+            }
+            KeyCode::Up => {
+                if let Some(popup) = self.auto_completion.popup_mut() {
+                    popup.select_previous();
+                }
+            }
+            KeyCode::Enter | KeyCode::Tab => self.accept_completion(),
+            _ => {
+                self.auto_completion.dismiss();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 選択中の候補で入力中の単語を置き換える
+    fn accept_completion(&mut self) {
+        let Some(popup) = self.auto_completion.popup().cloned() else {
+            return;
+        };
+        self.auto_completion.cancel();
+        let Some(candidate) = popup.selected_candidate() else {
+            return;
+        };
+
+        let cursor = self.editor.cursor().char_pos;
+        self.begin_history(HistoryCommandKind::Other);
+        match self
+            .editor
+            .replace_range_span(popup.start, cursor, candidate)
+        {
+            Ok(_) => self.end_history(true),
+            Err(err) => {
+                self.end_history(false);
+                self.show_error_message(err);
+            }
+        }
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn page_scroll_step(&self) -> usize {
+        let height = self.current_viewport().height.max(1);
+        height.saturating_sub(self.next_screen_context_lines).max(1)
+    }
+
+    /// スクロールコマンド1回で動かす行数
+    fn scroll_command_step(&self) -> usize {
+        match self.scroll_step {
+            step if step > 0 => step as usize,
+            _ => self.page_scroll_step(),
+        }
+    }
+
+    fn scroll_page_down(&mut self) {
+        if self.scroll_step < 0 {
+            self.scroll_keeping_point_centered(self.page_scroll_step() as isize);
+            return;
+        }
+        let (total_lines, _) = self.buffer_metrics();
+        let height = self.current_viewport().height.max(1);
+        let step = self.scroll_command_step();
+        let old_top = self.current_viewport().top_line;
+        let max_top = total_lines.saturating_sub(height);
+        let new_top = (old_top + step).min(max_top);
+        let delta = new_top.saturating_sub(old_top);
+        {
+            let viewport = self.current_viewport_mut();
+            viewport.top_line = new_top;
+        }
+        if delta > 0 {
+            self.move_cursor_vertical(delta as isize);
+        }
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn scroll_page_up(&mut self) {
+        if self.scroll_step < 0 {
+            self.scroll_keeping_point_centered(-(self.page_scroll_step() as isize));
+            return;
+        }
+        let step = self.scroll_command_step();
+        let old_top = self.current_viewport().top_line;
+        let new_top = old_top.saturating_sub(step);
+        let delta = old_top.saturating_sub(new_top);
+        {
+            let viewport = self.current_viewport_mut();
+            viewport.top_line = new_top;
+        }
+        if delta > 0 {
+            self.move_cursor_vertical(-(delta as isize));
+        }
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    /// カーソルを `delta` 行動かし、カーソル行が画面中央に来るようスクロール
+    ///
+    /// バッファの先頭・末尾では画面が余らない位置で止まるため、中央からずれる。
+    fn scroll_keeping_point_centered(&mut self, delta: isize) {
+        self.move_cursor_vertical(delta);
+        let (total_lines, _) = self.buffer_metrics();
+        let height = self.current_viewport().height.max(1);
+        let max_top = total_lines.saturating_sub(height);
+        let top = self
+            .editor
+            .cursor()
+            .line
+            .saturating_sub(height / 2)
+            .min(max_top);
+        self.current_viewport_mut().top_line = top;
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn recenter_view(&mut self) {
+        let placement = match self.recenter_step % 3 {
+            0 => RecenterPlacement::Center,
+            1 => RecenterPlacement::Top,
+            _ => RecenterPlacement::Bottom,
+        };
+        self.recenter_at(placement);
+        self.recenter_step = (self.recenter_step + 1) % 3;
+    }
+
+    /// カーソル行をウィンドウの指定位置に置く（先頭行はバッファ末尾で画面が余らない位置まで）
+    fn recenter_at(&mut self, placement: RecenterPlacement) {
+        let (total_lines, _) = self.buffer_metrics();
+        let height = self.current_viewport().height.max(1);
+        let cursor_line = self.editor.cursor().line;
+        let max_top = total_lines.saturating_sub(height);
+
+        let desired_top = placement.top_line(cursor_line, height);
+        {
+            let viewport = self.current_viewport_mut();
+            viewport.top_line = desired_top.min(max_top);
+        }
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    /// 指定行（1始まり）がウィンドウ先頭になるようスクロール
+    ///
+    /// 先頭行はバッファ末尾で画面が余らない位置までに制限する。カーソルは画面外に出る場合のみ画面端へ移す。
+    pub fn scroll_line_to_top(&mut self, line: usize) {
+        let (total_lines, _) = self.buffer_metrics();
+        let height = self.current_viewport().height.max(1);
+        let max_top = total_lines.saturating_sub(height);
+        let top = line.saturating_sub(1).min(max_top);
+        self.current_viewport_mut().top_line = top;
+
+        let cursor_line = self.editor.cursor().line;
+        let bottom = top + height - 1;
+        if cursor_line < top {
+            self.move_cursor_vertical((top - cursor_line) as isize);
+        } else if cursor_line > bottom {
+            self.move_cursor_vertical(-((cursor_line - bottom) as isize));
+        }
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn toggle_fold(&mut self) {
+        match self.editor.toggle_fold_at_cursor() {
+            FoldToggle::Folded(range) => self.show_info_message(format!(
+                "{} 行を折りたたみました",
+                range.hidden_line_count()
+            )),
+            FoldToggle::Expanded(_) => self.show_info_message("折りたたみを展開しました"),
+            FoldToggle::NoBlock => self.show_info_message("折りたためるブロックがありません"),
+        }
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn horizontal_scroll_step(&self) -> usize {
+        (self.current_viewport().width / 2).max(1)
+    }
+
+    fn scroll_left(&mut self) {
+        let step = self.horizontal_scroll_step();
+        {
+            let viewport = self.current_viewport_mut();
+            viewport.scroll_x = viewport.scroll_x.saturating_add(step);
+        }
+        self.move_cursor_horizontal(step as isize);
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn scroll_right(&mut self) {
+        let step = self.horizontal_scroll_step();
+        let current_scroll = self.current_viewport().scroll_x;
+        if current_scroll > 0 {
+            let delta = current_scroll.min(step);
+            {
+                let viewport = self.current_viewport_mut();
+                viewport.scroll_x -= delta;
+            }
+            self.move_cursor_horizontal(-(delta as isize));
+        }
+        self.reset_recenter_cycle();
+        self.reset_kill_context();
+        self.ensure_cursor_visible();
+    }
+
+    fn start_find_file_prompt(&mut self) -> Result<()> {
+        let initial_path = self.default_directory_input();
+
+        // ミニバッファでファイル検索を開始
+        match self.minibuffer.start_find_file(Some(&initial_path)) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_execute_command_prompt(&mut self) -> Result<()> {
+        match self.minibuffer.start_execute_command() {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_eval_expression_prompt(&mut self) -> Result<()> {
+        match self.minibuffer.start_eval_expression() {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_query_replace_prompt(&mut self, is_regex: bool) -> Result<()> {
+        let mut initial_pattern: Option<String> = None;
+
+        if self.search.is_active() {
+            if let Some(pattern) = self.search.current_pattern() {
+                if !pattern.is_empty() {
+                    initial_pattern = Some(pattern.to_string());
+                }
+            }
+            self.search.accept();
+        } else if let Some(pattern) = if is_regex {
+            self.search.last_regex_pattern()
+        } else {
+            self.search.last_pattern()
+        } {
+            if !pattern.is_empty() {
+                initial_pattern = Some(pattern.to_string());
+            }
+        }
+
+        if initial_pattern.is_none() {
+            if let Ok(Some(selection)) = self.editor.selection_text() {
+                if !selection.is_empty() {
+                    initial_pattern = Some(selection);
+                }
+            }
+        }
+
+        let action = MinibufferAction::QueryReplace {
+            is_regex,
+            initial: initial_pattern,
+        };
+
+        match self.minibuffer.handle_event(SystemEvent::Action(action)) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_save_as_prompt(&mut self, suggested_name: &str) -> Result<()> {
+        let initial_path = match self.default_directory() {
+            Some(dir) => dir.join(suggested_name),
+            None => PathBuf::from(suggested_name),
+        };
+
+        let initial_string = initial_path.display().to_string();
+
+        match self
+            .minibuffer
+            .start_write_file(Some(initial_string.as_str()))
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_goto_line_prompt(&mut self) -> Result<()> {
+        let current_line = self.editor.cursor().line + 1;
+        let total_lines = self.total_line_count();
+
+        match self.minibuffer.start_goto_line(current_line, total_lines) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファの初期化に失敗しました: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_minibuffer_key(&mut self, key_event: KeyEvent) -> Result<()> {
+        let key: Key = key_event.into();
+
+        match self.minibuffer.handle_event(SystemEvent::KeyInput(key)) {
+            Ok(SystemResponse::FileOperation(file_op)) => {
+                use crate::minibuffer::FileOperation;
+                match file_op {
+                    FileOperation::Open(path) => {
+                        debug_log!(self, "Opening file via minibuffer: {}", path);
+                        self.find_file_interactively(&path);
+                    }
+                    FileOperation::SaveAs(path) => {
+                        self.persist_current_buffer_state();
+                        if let Some(index) = self.current_buffer_index() {
+                            if let Some(current) = self.buffers.get(index) {
+                                self.command_processor
+                                    .set_current_buffer(current.file.clone());
+                            }
+                            self.command_processor
+                                .sync_editor_content(&self.editor.to_string());
+                            let result = self.command_processor.save_buffer_as(path.clone());
+                            if result.success {
+                                if let Some(updated) =
+                                    self.command_processor.current_buffer().cloned()
+                                {
+                                    if let Some(buffer) = self.buffers.get_mut(index) {
+                                        buffer.file = updated;
+                                        buffer.cursor = *self.editor.cursor();
+                                        buffer.history = self.history.stack().clone();
+                                    }
+                                }
+                                if let Some(msg) = result.message {
+                                    let written_path = self
+                                        .current_buffer()
+                                        .and_then(|buffer| buffer.path().cloned());
+                                    self.report_save(msg, written_path.as_deref());
+                                }
+                                self.ensure_cursor_visible();
+                            } else if let Some(msg) = result.message {
+                                self.show_error_message(AltreError::Application(msg));
+                            }
+                        }
+                    }
+                    _ => {
+                        self.show_info_message("未実装のファイル操作です");
+                    }
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::ExecuteCommand(cmd)) => {
+                if cmd == "goto-line" {
+                    self.start_goto_line_prompt()
+                } else {
+                    match Command::from_string(&cmd) {
+                        Command::Unknown(name)
+                            if !self.command_registry.borrow().contains(&name) =>
+                        {
+                            self.show_info_message(format!("コマンド実行: {}", cmd));
+                            Ok(())
+                        }
+                        command => self.execute_command(command),
+                    }
+                }
+            }
+            Ok(SystemResponse::SwitchBuffer(name)) => {
+                let target = if name.trim().is_empty() {
+                    self.last_buffer_name()
+                } else {
+                    Some(name)
+                };
+
+                if let Some(buffer_name) = target {
+                    if let Err(err) = self.switch_to_buffer_by_name(&buffer_name) {
+                        self.show_error_message(err);
+                    }
+                } else {
+                    self.show_error_message(AltreError::Application(
+                        "切り替えるバッファが見つかりません".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::KillBuffer(name)) => {
+                let trimmed = name.trim();
+                let target = if trimmed.is_empty() {
+                    None
+                } else {
+                    Some(trimmed)
+                };
+                if let Err(err) = self.kill_buffer_by_name(target) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::ListBuffers) => {
+                self.show_buffer_list();
+                Ok(())
+            }
+            Ok(SystemResponse::GotoLine(line)) => {
+                if std::mem::take(&mut self.goto_line_in_other_window) {
+                    self.select_other_window();
+                }
+                if let Err(err) = self.goto_line(line) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::ScrollLineToTop(line)) => {
+                self.scroll_line_to_top(line);
+                Ok(())
+            }
+            Ok(SystemResponse::YesOrNo(answer)) => {
+                self.handle_confirmation_answer(answer);
+                Ok(())
+            }
+            Ok(SystemResponse::GotoChar(position)) => {
+                if let Err(err) = self.goto_char(position) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::GotoColumn(column)) => {
+                if let Err(err) = self.goto_column(column) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::SetFillColumn(column)) => {
+                self.set_buffer_fill_column(column);
+                Ok(())
+            }
+            Ok(SystemResponse::Backtrace { message, frames }) => {
+                let mut text = format!("{}\n\nバックトレース（内側から順）:\n", message);
+                for frame in frames {
+                    text.push_str(&format!("  {}\n", frame));
+                }
+                if let Err(err) = self.show_text_buffer(BACKTRACE_BUFFER_NAME, text) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::DescribeFunction(name)) => {
+                match self.describe_function_text(&name) {
+                    Ok(text) => {
+                        if let Err(err) = self.show_text_buffer(HELP_BUFFER_NAME, text) {
+                            self.show_error_message(err);
+                        }
+                    }
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::InsertChar(code)) => {
+                match parse_codepoint(&code) {
+                    Some(ch) => self.insert_literal_char(ch),
+                    None => self.show_error_message(AltreError::Application(format!(
+                        "無効なコードポイントです: {}",
+                        code.trim()
+                    ))),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::RevertWithCodingSystem(name)) => {
+                match self.revert_buffer_with_coding_system(&name) {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::CountMatches { pattern, is_regex }) => {
+                match self.count_matches_message(&pattern, is_regex) {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::Occur(pattern)) => {
+                let result = match self.current_buffer_id {
+                    Some(id) => self.occur(id, &pattern),
+                    None => Err(AltreError::Application(
+                        "一致を探すバッファがありません".to_string(),
+                    )),
+                };
+                match result {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::FilterLines { pattern, keep }) => {
+                match self.filter_lines(&pattern, keep) {
+                    Ok(message) => self.show_info_message(message),
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::StringRectangle(text)) => {
+                if let Err(err) = self.string_rectangle(&text) {
+                    self.show_error_message(err);
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::ChangeDirectory(path)) => {
+                match self.change_default_directory(&path) {
+                    Ok(directory) => {
+                        self.show_info_message(format!("既定ディレクトリ: {}", directory.display()))
+                    }
+                    Err(err) => self.show_error_message(err),
+                }
+                Ok(())
+            }
+            Ok(SystemResponse::QueryReplace {
+                pattern,
+                replacement,
+                is_regex,
+            }) => {
+                self.start_query_replace_session(pattern, replacement, is_regex)?;
+                Ok(())
+            }
+            Ok(SystemResponse::Quit) => self.execute_command(Command::SaveBuffersKillTerminal),
+            Ok(SystemResponse::Continue) | Ok(SystemResponse::None) => {
+                // 確認のプロンプトが答えずに閉じられたら保留中の確認も破棄する
+                if !self.minibuffer.is_active() {
+                    self.pending_confirmation = None;
+                }
+                Ok(())
+            }
+            Err(err) => {
+                self.show_error_message(AltreError::Application(format!(
+                    "ミニバッファエラー: {}",
+                    err
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    fn navigate(&mut self, action: NavigationAction) {
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        let before = self.editor.cursor().char_pos;
+        match self.editor.navigate(action) {
+            Ok(true) => {
+                if !self.virtual_end_of_line {
+                    // 前へ進む移動は行末で止めず次の行頭へ送る（C-e や上下移動は行内に留める）
+                    let forward = matches!(
+                        action,
+                        NavigationAction::MoveCharForward
+                            | NavigationAction::MoveWordForward
+                            | NavigationAction::MoveParagraphForward
+                            | NavigationAction::MoveBufferEnd
+                    );
+                    self.editor.step_off_line_end(forward);
+                    if self.editor.cursor().char_pos == before {
+                        self.show_info_message("これ以上移動できません");
+                    }
+                }
+                self.ensure_cursor_visible();
+            }
+            Ok(false) => self.show_info_message("これ以上移動できません"),
+            Err(err) => self.show_error_message(err.into()),
+        }
+    }
+
+    /// 1行下へ移動する（最終行では `next_line_add_newlines` に従い改行を追加）
+    fn next_line(&mut self) {
+        let on_last_line = self.editor.cursor().line + 1 >= self.total_line_count();
+        if !(self.next_line_add_newlines && on_last_line) {
+            self.navigate(NavigationAction::MoveLineDown);
+            return;
+        }
+
+        self.begin_history(HistoryCommandKind::Other);
+        let end = self.editor.to_string().chars().count();
+        let result = self
+            .editor
+            .move_cursor_to_char(end)
+            .and_then(|_| self.editor.insert_newline());
+        let success = match result {
+            Ok(()) => true,
+            Err(err) => {
+                self.show_error_message(err);
+                false
+            }
+        };
+        self.end_history(success);
+        self.reset_kill_context();
+        self.reset_recenter_cycle();
+        self.ensure_cursor_visible();
+    }
+
+    fn split_window(&mut self, orientation: SplitOrientation) {
+        self.window_manager.split_focused(orientation);
+        self.ensure_cursor_visible();
+    }
+
+    fn delete_other_windows(&mut self) {
+        match self.window_manager.delete_others() {
+            Ok(()) => {
+                self.ensure_cursor_visible();
+            }
+            Err(err) => {
+                self.show_error_message(AltreError::Application(err.to_string()));
+            }
+        }
+    }
+
+    fn delete_current_window(&mut self) {
+        match self.window_manager.delete_focused() {
+            Ok(()) => {
+                self.sync_focused_window_buffer();
+                self.ensure_cursor_visible();
+            }
+            Err(err) => {
+                self.show_error_message(AltreError::Application(err.to_string()));
+            }
+        }
+    }
+
+    fn focus_next_window(&mut self) {
+        self.window_manager.focus_next();
+        self.sync_focused_window_buffer();
+        self.ensure_cursor_visible();
+    }
+
+    /// フォーカス中ウィンドウに割り当てられたバッファをカレントにする
+    fn sync_focused_window_buffer(&mut self) {
+        let focused = self.window_manager.focused_window();
+        match self.window_manager.focused_buffer() {
+            Some(id)
+                if Some(id) != self.current_buffer_id && self.find_buffer_index(id).is_some() =>
+            {
+                let viewport = self.current_viewport().clone();
+                if let Err(err) = self.load_buffer_by_id(id, true) {
+                    self.show_error_message(err);
+                    return;
+                }
+                *self.current_viewport_mut() = viewport;
+            }
+            _ => self
+                .window_manager
+                .set_buffer(focused, self.current_buffer_id),
+        }
+    }
+
+    /// フォーカス中のウィンドウと次のウィンドウで表示するバッファを入れ替える
+    ///
+    /// ウィンドウの配置はそのままで、スクロール位置はバッファと一緒に移る。
+    fn swap_window_buffers(&mut self) {
+        let order = self.window_manager.leaf_order();
+        if order.len() <= 1 {
+            return;
+        }
+        let focused = self.window_manager.focused_window();
+        let index = order.iter().position(|&id| id == focused).unwrap_or(0);
+        let other = order[(index + 1) % order.len()];
+        let other_buffer = self.window_manager.buffer(other);
+
+        let focused_viewport = self.current_viewport().clone();
+        let other_viewport = self
+            .window_manager
+            .viewport(other)
+            .cloned()
+            .unwrap_or_default();
+        self.window_manager
+            .set_buffer(other, self.current_buffer_id);
+        if let Some(viewport) = self.window_manager.viewport_mut(other) {
+            viewport.top_line = focused_viewport.top_line;
+            viewport.scroll_x = focused_viewport.scroll_x;
+        }
+        if let Some(id) = other_buffer {
+            if let Err(err) = self.load_buffer_by_id(id, true) {
+                self.show_error_message(err);
+                return;
+            }
+        }
+        // 大きさは各ウィンドウのまま、スクロール位置だけを入れ替える
+        *self.current_viewport_mut() = ViewportState {
+            top_line: other_viewport.top_line,
+            scroll_x: other_viewport.scroll_x,
+            ..focused_viewport
+        };
+        self.ensure_cursor_visible();
+    }
+
+    /// 別のウィンドウへフォーカスを移す（ウィンドウが1つなら分割する）
+    fn select_other_window(&mut self) {
+        if self.window_manager.window_count() <= 1 {
+            self.window_manager
+                .split_focused(SplitOrientation::Horizontal);
+        }
+        self.focus_next_window();
+    }
+
+    /// バッファでの Tab キーの動作を取得
+    pub fn tab_behavior(&self) -> TabBehavior {
+        self.tab_behavior
+    }
+
+    /// バッファでの Tab キーの動作を設定
+    pub fn set_tab_behavior(&mut self, behavior: TabBehavior) {
+        self.tab_behavior = behavior;
+    }
+
+    /// タブストップの間隔を取得
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// タブストップの間隔を設定（0 は 1 として扱う）
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.tab_width = width.max(1);
+    }
+
+    /// fill-column の既定値（C-x f で設定していないバッファで使う）を取得
+    pub fn fill_column(&self) -> usize {
+        self.fill_column
+    }
+
+    /// fill-column の既定値を設定
+    pub fn set_fill_column(&mut self, column: usize) {
+        self.fill_column = column;
+    }
+
+    /// カレントバッファで center-line・fill-paragraph が使う fill-column
+    pub fn current_fill_column(&self) -> usize {
+        self.current_buffer()
+            .and_then(|buffer| buffer.fill_column)
+            .unwrap_or(self.fill_column)
+    }
+
+    /// tabify で行頭の空白だけを変換するかを取得
+    pub fn tabify_leading_only(&self) -> bool {
+        self.tabify_leading_only
+    }
+
+    /// tabify で行頭の空白だけを変換するかを設定（無効なら行中の空白も変換する）
+    pub fn set_tabify_leading_only(&mut self, enabled: bool) {
+        self.tabify_leading_only = enabled;
+    }
+
+    /// インクリメンタル検索で検索語の空白を緩く扱うか（検索開始時の既定値）
+    pub fn isearch_lax_whitespace(&self) -> bool {
+        self.search.lax_whitespace_default()
+    }
+
+    /// インクリメンタル検索で検索語の空白を緩く扱うかを設定（検索中は M-SPC で切り替え）
+    pub fn set_isearch_lax_whitespace(&mut self, enabled: bool) {
+        self.search.set_lax_whitespace_default(enabled);
+    }
+
+    /// インクリメンタル検索の失敗時に、一致する最長の先頭部分の行を示すか（既定は有効）
+    pub fn isearch_failure_hint(&self) -> bool {
+        self.search.failure_hint()
+    }
+
+    /// インクリメンタル検索の失敗時に、一致する最長の先頭部分の行を示すかを設定
+    pub fn set_isearch_failure_hint(&mut self, enabled: bool) {
+        self.search.set_failure_hint(enabled);
+    }
+
+    /// インクリメンタル検索で現在の一致以外もハイライトするか（既定は有効）
+    pub fn isearch_lazy_highlight(&self) -> bool {
+        self.search.lazy_highlight()
+    }
+
+    /// インクリメンタル検索で現在の一致以外もハイライトするかを設定
+    pub fn set_isearch_lazy_highlight(&mut self, enabled: bool) {
+        self.search.set_lazy_highlight(enabled);
+    }
+
+    /// 現在のバッファの undo 履歴が保持しているテキスト量（バイト）
+    pub fn undo_memory_usage(&self) -> usize {
+        self.history.stack().memory_usage()
+    }
+
+    /// 現在のバッファの undo 履歴が保持するテキスト量の上限を設定
+    pub fn set_undo_memory_limit(&mut self, limit: usize) {
+        self.history.stack_mut().set_memory_limit(limit);
+    }
+
+    /// alisp の文字列・コメント内でも自動字下げするかを取得
+    pub fn alisp_indent_in_literals(&self) -> bool {
+        self.alisp_indent_in_literals
+    }
+
+    /// alisp の文字列・コメント内でも自動字下げするかを設定
+    pub fn set_alisp_indent_in_literals(&mut self, enabled: bool) {
+        self.alisp_indent_in_literals = enabled;
+    }
+
+    /// delete-selection モード（入力やヤンクでアクティブなリージョンを置き換える）が有効かを取得
+    pub fn delete_selection(&self) -> bool {
+        self.delete_selection
+    }
+
+    /// delete-selection モードを設定
+    pub fn set_delete_selection(&mut self, enabled: bool) {
+        self.delete_selection = enabled;
+    }
+
+    /// 行を折り返す単位を取得
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
+    /// 行を折り返す単位を設定
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) {
+        self.wrap_mode = mode;
+    }
+
+    /// 行を折り返す桁を取得
+    pub fn wrap_column(&self) -> WrapColumn {
+        self.wrap_column
+    }
+
+    /// 行を折り返す桁を設定（`WrapColumn::FillColumn` なら広いウィンドウでも指定桁で折り返す）
+    pub fn set_wrap_column(&mut self, column: WrapColumn) {
+        self.wrap_column = column;
+    }
+
+    /// 長い行を切り詰めて表示するかを取得
+    pub fn truncate_lines(&self) -> bool {
+        self.truncate_lines
+    }
+
+    /// 長い行を切り詰めて表示するかを設定（偽なら折り返す）
+    pub fn set_truncate_lines(&mut self, truncate: bool) {
+        self.truncate_lines = truncate;
+    }
+
+    /// 制御文字と幅 0 の見えない文字の表示方法を取得
+    pub fn control_char_display(&self) -> ControlCharDisplay {
+        self.control_char_display
+    }
+
+    /// 制御文字と幅 0 の見えない文字の表示方法を設定
+    pub fn set_control_char_display(&mut self, display: ControlCharDisplay) {
+        self.control_char_display = display;
+    }
+
+    /// 描画と HTML 書き出しに使うテーマ名を取得
+    pub fn theme_name(&self) -> &str {
+        &self.theme_name
+    }
+
+    /// 描画と HTML 書き出しに使うテーマ名を設定（未知の名前なら偽を返し変更しない）
+    pub fn set_theme_name(&mut self, name: &str) -> bool {
+        if !ThemeManager::new().set_theme(name) {
+            return false;
+        }
+        self.theme_name = name.to_string();
+        true
+    }
+
+    /// fill-column で折り返すとき本文を中央に寄せるかを取得
+    pub fn wrap_centered(&self) -> bool {
+        self.wrap_centered
+    }
+
+    /// fill-column で折り返すとき本文を中央に寄せるかを設定
+    pub fn set_wrap_centered(&mut self, centered: bool) {
+        self.wrap_centered = centered;
+    }
+
+    /// 集中モード（モードラインを隠す）が有効かを取得
+    pub fn distraction_free(&self) -> bool {
+        self.distraction_free
+    }
+
+    /// 集中モード（モードラインを隠す）を設定
+    pub fn set_distraction_free(&mut self, enabled: bool) {
+        self.distraction_free = enabled;
+    }
+
+    /// 集中モードでミニバッファ領域も隠すかを取得
+    pub fn distraction_free_hides_minibuffer(&self) -> bool {
+        self.distraction_free_hides_minibuffer
+    }
+
+    /// 集中モードでミニバッファ領域も隠すかを設定
+    ///
+    /// 隠していても入力中は表示し、メッセージはテキストエリアの下端に重ねて表示する。
+    pub fn set_distraction_free_hides_minibuffer(&mut self, hidden: bool) {
+        self.distraction_free_hides_minibuffer = hidden;
+    }
+
+    /// 存在しないファイルを開くとき作成を確認するかを取得
+    pub fn confirm_new_file(&self) -> bool {
+        self.confirm_new_file
+    }
+
+    /// 存在しないファイルを開くとき作成を確認するかを設定
+    ///
+    /// 有効にすると find-file で存在しないパスを指定したとき y/n で確認し、n なら何も作らない。
+    pub fn set_confirm_new_file(&mut self, enabled: bool) {
+        self.confirm_new_file = enabled;
+    }
+
+    /// find-file でワイルドカードを展開するかを取得
+    pub fn find_file_wildcards(&self) -> bool {
+        self.find_file_wildcards
+    }
+
+    /// find-file でワイルドカードを展開するかを設定
+    ///
+    /// 有効（既定）なら `src/*.rs` のように `*` `?` `[...]` を含む入力で一致するファイルを
+    /// すべて開き、最初のファイルへ切り替える。その名前のファイルが実在するときは展開しない。
+    pub fn set_find_file_wildcards(&mut self, enabled: bool) {
+        self.find_file_wildcards = enabled;
+    }
+
+    /// 開いているファイルを find-file で開き直したときの動作を取得
+    pub fn reopen_file_behavior(&self) -> ReopenFileBehavior {
+        self.reopen_file_behavior
+    }
+
+    /// 開いているファイルを find-file で開き直したときの動作を設定
+    ///
+    /// `Reload` ならディスクから読み直す。未保存の変更があるときは y/n で確認し、
+    /// n なら読み直さずに既存のバッファへ切り替える。`Warn` なら切り替えたうえで警告を表示する。
+    /// シンボリックリンクなど別の表記で同じファイルを指定しても既に開いているものとして扱う。
+    pub fn set_reopen_file_behavior(&mut self, behavior: ReopenFileBehavior) {
+        self.reopen_file_behavior = behavior;
+    }
+
+    /// 書き込み禁止のファイルで読み取り専用を解除するとき権限の変更を確認するかを取得
+    pub fn chmod_on_toggle_read_only(&self) -> bool {
+        self.chmod_on_toggle_read_only
+    }
+
+    /// 書き込み禁止のファイルで読み取り専用を解除するとき権限の変更を確認するかを設定
+    ///
+    /// 有効なら C-x C-q で書き込み可能にするときに y/n で確認し、y ならファイルの所有者に
+    /// 書き込み権限を与える。権限を変更できなかったときはバッファを読み取り専用のままにする。
+    /// n なら権限は変えずにバッファだけ書き込み可能にする。
+    pub fn set_chmod_on_toggle_read_only(&mut self, enabled: bool) {
+        self.chmod_on_toggle_read_only = enabled;
+    }
+
+    /// 終了時のセッション自動保存が有効かを取得
+    pub fn auto_save_session(&self) -> bool {
+        self.options.borrow().auto_save_session
+    }
+
+    /// 終了時のセッション自動保存を設定
+    ///
+    /// 有効にすると C-x C-c で終了するときセッションファイルへ保存し、
+    /// 起動時の `restore_session_on_startup` で前回のバッファとウィンドウ構成を復元する。
+    pub fn set_auto_save_session(&mut self, enabled: bool) {
+        self.options.borrow_mut().auto_save_session = enabled;
+    }
+
+    /// 起動時に前回のセッションを復元するかを取得
+    pub fn restore_session_at_startup(&self) -> bool {
+        self.options.borrow().restore_session
+    }
+
+    /// 起動時に前回のセッションを復元するかを設定（終了時の保存は `set_auto_save_session`）
+    pub fn set_restore_session_at_startup(&mut self, enabled: bool) {
+        self.options.borrow_mut().restore_session = enabled;
+    }
+
+    /// セッションファイルの場所を取得
+    pub fn session_path(&self) -> Option<&Path> {
+        self.session_path.as_deref()
+    }
+
+    /// セッションファイルの場所を設定（save-session / restore-session も同じ場所を使う）
+    pub fn set_session_path(&mut self, path: impl Into<PathBuf>) {
+        self.session_path = Some(path.into());
+    }
+
+    /// バッファ名の補完にあいまい一致を使うかを取得
+    pub fn fuzzy_buffer_completion(&self) -> bool {
+        self.minibuffer.fuzzy_buffer_completion()
+    }
+
+    /// バッファ名の補完方式を設定
+    ///
+    /// 有効（既定）なら入力の文字を順に含む名前を候補にし、無効なら前方一致だけにする。
+    pub fn set_fuzzy_buffer_completion(&mut self, enabled: bool) {
+        self.minibuffer.set_fuzzy_buffer_completion(enabled);
+    }
+
+    /// ミニバッファ履歴で再利用した入力を先頭へ移すかを取得
+    pub fn history_move_to_front(&self) -> bool {
+        self.minibuffer.history_move_to_front()
+    }
+
+    /// ミニバッファ履歴で再利用した入力を先頭へ移すかを設定
+    ///
+    /// 有効（既定）なら同じ入力は1件だけ残して最新へ移す。無効なら直前と同じ入力だけを省き、
+    /// 入力した順序を保つ。どちらでも C-p / C-n は表示中と同じ入力を飛ばす。
+    pub fn set_history_move_to_front(&mut self, enabled: bool) {
+        self.minibuffer.set_history_move_to_front(enabled);
+    }
+
+    /// 保存成功時のメッセージ表示を取得
+    pub fn save_message_mode(&self) -> &SaveMessageMode {
+        &self.save_message_mode
+    }
+
+    /// 保存成功時のメッセージ表示を設定
+    pub fn set_save_message_mode(&mut self, mode: SaveMessageMode) {
+        self.save_message_mode = mode;
+    }
+
+    /// 保存成功のメッセージを設定に従って表示
+    ///
+    /// `written_path` は実際に書き込んだファイル。書き込みがなかった場合は書式指定でも既定のメッセージを出す。
+    fn report_save(&mut self, message: String, written_path: Option<&Path>) {
+        let message = match (&self.save_message_mode, written_path) {
+            (SaveMessageMode::Silent, _) => return,
+            (SaveMessageMode::Format(format), Some(path)) => {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                format
+                    .replace("{path}", &path.display().to_string())
+                    .replace("{name}", &name)
+            }
+            _ => message,
+        };
+        self.show_info_message(message);
+    }
+
+    /// 現在のバッファの既定ディレクトリ
+    ///
+    /// `cd` による設定、ファイルのあるディレクトリ、初期ディレクトリ、カレントディレクトリの順に決まる。
+    pub fn default_directory(&self) -> Option<PathBuf> {
+        self.current_buffer()
+            .and_then(OpenBuffer::default_directory)
+            .or_else(|| self.initial_directory.clone())
+            .or_else(|| env::current_dir().ok())
+    }
+
+    /// ファイルに紐づかないバッファの既定ディレクトリを設定
+    pub fn set_initial_directory(&mut self, directory: Option<PathBuf>) {
+        self.initial_directory = directory;
+    }
+
+    /// 現在のバッファの既定ディレクトリを変更（相対パスは現在の既定ディレクトリから解決）
+    pub fn change_default_directory(&mut self, input: &str) -> Result<PathBuf> {
+        let requested = PathBuf::from(input.trim());
+        let joined = match self.default_directory() {
+            Some(base) if requested.is_relative() && !input.trim().starts_with('~') => {
+                base.join(requested)
+            }
+            _ => requested,
+        };
+        let directory = expand_path(&joined)?;
+        if !directory.is_dir() {
+            return Err(AltreError::Application(format!(
+                "ディレクトリではありません: {}",
+                directory.display()
+            )));
+        }
+        let index = self
+            .current_buffer_index()
+            .ok_or_else(|| AltreError::Application("バッファがありません".to_string()))?;
+        self.buffers[index].default_directory = Some(directory.clone());
+        Ok(directory)
+    }
+
+    /// ミニバッファに表示する既定ディレクトリ（末尾スラッシュ付き）
+    fn default_directory_input(&self) -> String {
+        let directory = self
+            .default_directory()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|| "~/".to_string());
+        if directory.ends_with('/') {
+            directory
+        } else {
+            format!("{}/", directory)
+        }
+    }
+
+    /// コマンドを登録（M-x から名前で実行できる）
+    pub fn register_command<F>(
+        &mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut Backend) -> Result<()> + 'static,
+    {
+        let name = name.into();
+        self.command_registry
+            .borrow_mut()
+            .register(name.clone(), description, handler)?;
+        self.minibuffer.add_command(name);
+        Ok(())
+    }
+
+    /// 登録コマンドがあるか
+    pub fn has_registered_command(&self, name: &str) -> bool {
+        self.command_registry.borrow().contains(name)
+    }
+
+    /// 組み込みまたは登録コマンドを名前で実行
+    pub fn execute_command_by_name(&mut self, name: &str) -> Result<()> {
+        match Command::from_string(name) {
+            Command::Unknown(name) => self.run_custom_command(&name),
+            command => self.execute_command(command),
+        }
+    }
+
+    /// キーワードハイライトの設定を取得
+    pub fn keyword_highlighter(&self) -> &KeywordHighlighter {
+        &self.keyword_highlighter
+    }
+
+    /// キーワードハイライトの設定を変更
+    pub fn keyword_highlighter_mut(&mut self) -> &mut KeywordHighlighter {
+        &mut self.keyword_highlighter
+    }
+
+    /// 対応括弧ハイライトの設定を取得
+    pub fn paren_highlighter(&self) -> &ParenHighlighter {
+        &self.paren_highlighter
+    }
+
+    /// 対応括弧ハイライトの設定を変更
+    pub fn paren_highlighter_mut(&mut self) -> &mut ParenHighlighter {
+        &mut self.paren_highlighter
+    }
+
+    /// 最終行での C-n で改行を追加するかを取得
+    pub fn next_line_add_newlines(&self) -> bool {
+        self.next_line_add_newlines
+    }
+
+    /// 最終行での C-n で改行を追加するかを設定
+    pub fn set_next_line_add_newlines(&mut self, enabled: bool) {
+        self.next_line_add_newlines = enabled;
+    }
+
+    /// 行末（最後の文字の後ろ）にカーソルを置けるかを取得
+    pub fn virtual_end_of_line(&self) -> bool {
+        self.virtual_end_of_line
+    }
+
+    /// 行末（最後の文字の後ろ）にカーソルを置けるかを設定
+    ///
+    /// 有効（既定）なら C-e で行末へ移動し、そこで入力すると行に追記する。
+    /// 無効なら空でない行ではカーソルを最後の文字までに留め、C-e・左右移動・上下移動・
+    /// 単語移動の移動先を最後の文字に寄せる（C-f で行末を越えるときは次の行頭へ進む）。
+    /// 入力はカーソル位置に挿入されるため、最後の文字の前に入る。
+    pub fn set_virtual_end_of_line(&mut self, enabled: bool) {
+        self.virtual_end_of_line = enabled;
+    }
+
+    /// 検索結果などからの移動先を別ウィンドウに表示するかを取得
+    pub fn jump_in_other_window(&self) -> bool {
+        self.jump_in_other_window
+    }
+
+    /// 検索結果などからの移動先を別ウィンドウに表示するかを設定
+    pub fn set_jump_in_other_window(&mut self, enabled: bool) {
+        self.jump_in_other_window = enabled;
+    }
+
+    pub fn process_minibuffer_timer(&mut self) {
+        if let Err(err) = self.minibuffer.handle_event(SystemEvent::Update) {
+            eprintln!("minibuffer update error: {}", err);
+        }
+    }
+
+    fn show_info_message<S: Into<String>>(&mut self, message: S) {
+        if let Err(err) = self.minibuffer.show_info(message.into()) {
+            eprintln!("minibuffer info error: {}", err);
+        }
+    }
+
+    fn show_error_message(&mut self, error: AltreError) {
+        if let Err(mini_err) = self.minibuffer.show_error(error.to_string()) {
+            eprintln!("minibuffer error: {}", mini_err);
+        }
+    }
+
+    /// キーイベントを人間が読みやすい形式に変換
+    fn format_key_event(key_event: &KeyEvent) -> String {
+        let mut parts = Vec::new();
+
+        // 修飾キーを追加（Shiftは特殊文字以外では通常表示しない）
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            parts.push("C");
+        }
+        if key_event.modifiers.contains(KeyModifiers::ALT) {
+            parts.push("M");
+        }
+
+        // 基本キーを追加
+        let key_name = match key_event.code {
+            KeyCode::Char(c) => {
+                if c.is_ascii_control() {
+                    // 制御文字の場合
+                    format!("C-{}", (c as u8 + b'A' - 1) as char)
+                } else if c.is_uppercase() && key_event.modifiers.contains(KeyModifiers::SHIFT) {
+                    // 大文字のShift表示
+                    format!("S-{}", c.to_lowercase())
+                } else {
+                    // 通常の文字
+                    c.to_string()
+                }
+            }
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::Enter => "RET".to_string(),
+            KeyCode::Left => "左".to_string(),
+            KeyCode::Right => "右".to_string(),
+            KeyCode::Up => "上".to_string(),
+            KeyCode::Down => "下".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PageUp".to_string(),
+            KeyCode::PageDown => "PageDown".to_string(),
+            KeyCode::Tab => "TAB".to_string(),
+            KeyCode::BackTab => "S-TAB".to_string(),
+            KeyCode::Delete => "DEL".to_string(),
+            KeyCode::Insert => "INS".to_string(),
+            KeyCode::Esc => "ESC".to_string(),
+            KeyCode::Backspace => "BS".to_string(),
+            KeyCode::CapsLock => "CapsLock".to_string(),
+            KeyCode::ScrollLock => "ScrollLock".to_string(),
+            KeyCode::NumLock => "NumLock".to_string(),
+            KeyCode::PrintScreen => "PrintScreen".to_string(),
+            KeyCode::Pause => "Pause".to_string(),
+            KeyCode::Menu => "Menu".to_string(),
+            KeyCode::KeypadBegin => "Keypad-Begin".to_string(),
+            _ => format!("未知のキー"),
+        };
+
+        if parts.is_empty() {
+            key_name
+        } else {
+            format!("{}-{}", parts.join("-"), key_name)
+        }
+    }
+}
+
+struct KeymapHost {
+    keymap: Rc<RefCell<ModernKeyMap>>,
+    gui_theme: Rc<RefCell<GuiThemeConfig>>,
+    commands: Rc<RefCell<CommandRegistry>>,
+    options: Rc<RefCell<EditorOptions>>,
+}
+
+impl KeymapHost {
+    fn new(
+        keymap: Rc<RefCell<ModernKeyMap>>,
+        gui_theme: Rc<RefCell<GuiThemeConfig>>,
+        commands: Rc<RefCell<CommandRegistry>>,
+        options: Rc<RefCell<EditorOptions>>,
+    ) -> Self {
+        Self {
+            keymap,
+            gui_theme,
+            commands,
+            options,
+        }
+    }
+}
+
+impl HostBridge for KeymapHost {
+    fn bind_key(
+        &mut self,
+        key_sequence: &str,
+        command_name: &str,
+    ) -> std::result::Result<(), String> {
+        let command = Command::from_string(command_name);
+        match command {
+            Command::Unknown(name) if self.commands.borrow().contains(&name) => self
+                .keymap
+                .borrow_mut()
+                .bind_action_sequence(key_sequence, Action::CustomCommand(name))
+                .map_err(|err| err.to_string()),
+            Command::Unknown(_) => Err(format!("未知のコマンドです: {}", command_name)),
+            other => {
+                let mut keymap = self.keymap.borrow_mut();
+                keymap
+                    .bind_command_sequence(key_sequence, &other)
+                    .map_err(|err| err.to_string())
+            }
+        }
+    }
+
+    fn set_gui_color(&mut self, component: &str, color: &str) -> std::result::Result<(), String> {
+        let key = GuiThemeKey::from_str(component)
+            .ok_or_else(|| format!("未知のGUIカラーキーです: {}", component))?;
+        let mut theme = self.gui_theme.borrow_mut();
+        theme.set_color(key, color)
+    }
+
+    fn set_option(&mut self, name: &str, value: &OptionValue) -> std::result::Result<(), String> {
+        self.options.borrow_mut().set(name, value)
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::new().expect("アプリケーションの初期化に失敗しました")
+    }
+}
+
+/// 各行の先頭の文字位置
+fn line_start_offsets(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(
+            text.chars()
+                .enumerate()
+                .filter(|(_, ch)| *ch == '\n')
+                .map(|(index, _)| index + 1),
+        )
+        .collect()
+}
+
+/// find-file の入力をパスと移動先（`path:line` / `path:line:col`）に分ける
+fn split_find_file_input(path_input: &str) -> (&str, Option<(usize, Option<usize>)>) {
+    match split_path_position(path_input) {
+        // コロンを含む実在のファイル名はそのままパスとして扱う
+        Some(_) if expand_path(path_input).is_ok_and(|path| path.exists()) => (path_input, None),
+        Some((path, line, column)) => (path, Some((line, column))),
+        None => (path_input, None),
+    }
+}
+
+fn is_quoted_insert_key(key_event: &KeyEvent) -> bool {
+    key_event.code == KeyCode::Char('q') && key_event.modifiers == KeyModifiers::CONTROL
+}
+
+/// C-q に続くキーが表す文字（C-a などは制御文字になる）
+fn quoted_char(key_event: &KeyEvent) -> Option<char> {
+    match key_event.code {
+        KeyCode::Tab => Some('\t'),
+        KeyCode::Enter => Some('\r'),
+        KeyCode::Esc => Some('\u{1b}'),
+        KeyCode::Backspace => Some('\u{7f}'),
+        KeyCode::Char(ch) if key_event.modifiers.contains(KeyModifiers::CONTROL) => match ch {
+            '?' => Some('\u{7f}'),
+            ' ' | '@' => Some('\0'),
+            _ if ch.is_ascii_alphabetic() || ('['..='_').contains(&ch) => {
+                Some(((ch.to_ascii_uppercase() as u8) & 0x1f) as char)
+            }
+            _ => None,
+        },
+        KeyCode::Char(ch) => Some(ch),
+        _ => None,
+    }
+}
+
+/// `insert-char` で名前指定できる Unicode 文字（大文字小文字は区別しない）
+const UNICODE_CHAR_NAMES: &[(&str, char)] = &[
+    ("NO-BREAK SPACE", '\u{00A0}'),
+    ("COPYRIGHT SIGN", '©'),
+    ("REGISTERED SIGN", '®'),
+    ("DEGREE SIGN", '°'),
+    ("PLUS-MINUS SIGN", '±'),
+    ("MICRO SIGN", 'µ'),
+    ("MULTIPLICATION SIGN", '×'),
+    ("DIVISION SIGN", '÷'),
+    ("LATIN SMALL LETTER A WITH GRAVE", 'à'),
+    ("LATIN SMALL LETTER E WITH ACUTE", 'é'),
+    ("LATIN SMALL LETTER E WITH GRAVE", 'è'),
+    ("LATIN SMALL LETTER U WITH DIAERESIS", 'ü'),
+    ("LATIN SMALL LETTER N WITH TILDE", 'ñ'),
+    ("LATIN SMALL LETTER SHARP S", 'ß'),
+    ("GREEK SMALL LETTER ALPHA", 'α'),
+    ("GREEK SMALL LETTER BETA", 'β'),
+    ("GREEK SMALL LETTER LAMDA", 'λ'),
+    ("GREEK SMALL LETTER PI", 'π'),
+    ("EN DASH", '–'),
+    ("EM DASH", '—'),
+    ("LEFT SINGLE QUOTATION MARK", '‘'),
+    ("RIGHT SINGLE QUOTATION MARK", '’'),
+    ("LEFT DOUBLE QUOTATION MARK", '“'),
+    ("RIGHT DOUBLE QUOTATION MARK", '”'),
+    ("BULLET", '•'),
+    ("HORIZONTAL ELLIPSIS", '…'),
+    ("EURO SIGN", '€'),
+    ("TRADE MARK SIGN", '™'),
+    ("LEFTWARDS ARROW", '←'),
+    ("UPWARDS ARROW", '↑'),
+    ("RIGHTWARDS ARROW", '→'),
+    ("DOWNWARDS ARROW", '↓'),
+    ("INFINITY", '∞'),
+    ("NOT EQUAL TO", '≠'),
+    ("LESS-THAN OR EQUAL TO", '≤'),
+    ("GREATER-THAN OR EQUAL TO", '≥'),
+    ("CHECK MARK", '✓'),
+    ("IDEOGRAPHIC SPACE", '\u{3000}'),
+    ("ZERO WIDTH SPACE", '\u{200B}'),
+];
+
+/// 16進数のコードポイント（`U+` や `#x`、`0x` の接頭辞も可）または
+/// [`UNICODE_CHAR_NAMES`] にある文字名を文字に変換
+fn parse_codepoint(input: &str) -> Option<char> {
+    let trimmed = input.trim();
+    if let Some(&(_, ch)) = UNICODE_CHAR_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(trimmed))
+    {
+        return Some(ch);
+    }
+    let digits = ["U+", "u+", "#x", "0x", "0X"]
+        .iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))
+        .unwrap_or(trimmed);
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ParenHighlightMode;
+
+    #[test]
+    fn kill_line_removes_text_without_messages() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("hello\nworld").unwrap();
+        app.move_cursor_to_start().unwrap();
+
+        app.handle_action(Action::KillLine).unwrap();
+
+        assert_eq!(app.editor.to_string(), "world");
+        let viewport = app
+            .window_manager
+            .focused_viewport()
+            .expect("focused viewport");
+        assert_eq!(viewport.top_line, 0);
+        assert_eq!(viewport.scroll_x, 0);
+    }
+
+    fn app_with_lines(lines: usize, height: usize) -> Backend {
+        let mut app = Backend::new().expect("app init");
+        let text = (0..lines)
+            .map(|i| format!("line{}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        app.insert_str(&text).unwrap();
+        app.move_cursor_to_start().unwrap();
+        app.current_viewport_mut().update_dimensions(height, 80);
+        app.current_viewport_mut().top_line = 0;
+        app
+    }
+
+    #[test]
+    fn paragraph_keys_move_across_paragraphs_and_scroll() {
+        let mut app = Backend::new().expect("app init");
+        let text = (0..4)
+            .map(|p| vec![format!("para{}", p); 10].join("\n"))
+            .collect::<Vec<_>>()
+            .join("\n\n\n");
+        app.insert_str(&text).unwrap();
+        app.move_cursor_to_start().unwrap();
+        app.current_viewport_mut().update_dimensions(10, 80);
+        app.current_viewport_mut().top_line = 0;
+
+        press(&mut app, KeyCode::Char('}'), KeyModifiers::ALT);
+        assert_eq!(app.editor.cursor().line, 10);
+        press(&mut app, KeyCode::Char('}'), KeyModifiers::ALT);
+        assert_eq!(app.editor.cursor().line, 22);
+        let viewport = app.current_viewport();
+        assert!(viewport.top_line <= 22 && 22 < viewport.top_line + viewport.height);
+
+        press(&mut app, KeyCode::Char('{'), KeyModifiers::ALT);
+        assert_eq!(app.editor.cursor().line, 11);
+        press(&mut app, KeyCode::Char('{'), KeyModifiers::ALT);
+        assert_eq!(app.editor.cursor().char_pos, 0);
+        assert_eq!(app.current_viewport().top_line, 0);
+    }
+
+    #[test]
+    fn recenter_commands_place_cursor_line_directly() {
+        let mut app = app_with_lines(100, 20);
+        app.editor.move_cursor_to_char(0).unwrap();
+        for _ in 0..50 {
+            app.navigate(NavigationAction::MoveLineDown);
+        }
+        assert_eq!(app.editor.cursor().line, 50);
+
+        app.execute_command(Command::RecenterTop).unwrap();
+        assert_eq!(app.current_viewport().top_line, 50);
+        app.execute_command(Command::RecenterBottom).unwrap();
+        assert_eq!(app.current_viewport().top_line, 31);
+        app.execute_command(Command::from_string("recenter-center"))
+            .unwrap();
+        assert_eq!(app.current_viewport().top_line, 40);
+
+        // 直接指定した後の C-l は中央から循環を始める
+        app.execute_command(Command::RecenterTop).unwrap();
+        app.handle_action(Action::Recenter).unwrap();
+        assert_eq!(app.current_viewport().top_line, 40);
+        app.handle_action(Action::Recenter).unwrap();
+        assert_eq!(app.current_viewport().top_line, 50);
+    }
+
+    #[test]
+    fn recenter_commands_clamp_near_buffer_edges() {
+        let mut app = app_with_lines(30, 20);
+        app.execute_command(Command::RecenterBottom).unwrap();
+        assert_eq!(app.current_viewport().top_line, 0);
+        app.execute_command(Command::RecenterCenter).unwrap();
+        assert_eq!(app.current_viewport().top_line, 0);
+
+        for _ in 0..25 {
+            app.navigate(NavigationAction::MoveLineDown);
+        }
+        app.execute_command(Command::RecenterTop).unwrap();
+        assert_eq!(app.current_viewport().top_line, 10);
+    }
+
+    #[test]
+    fn scroll_page_down_keeps_context_lines() {
+        let mut app = app_with_lines(100, 20);
+        assert_eq!(app.next_screen_context_lines(), 2);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.current_viewport().top_line, 18);
+        assert_eq!(app.editor.cursor().line, 18);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.current_viewport().top_line, 36);
+    }
+
+    #[test]
+    fn scroll_page_up_is_symmetric() {
+        let mut app = app_with_lines(100, 20);
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        app.handle_action(Action::ScrollPageDown).unwrap();
+
+        app.handle_action(Action::ScrollPageUp).unwrap();
+        assert_eq!(app.current_viewport().top_line, 18);
+
+        app.handle_action(Action::ScrollPageUp).unwrap();
+        assert_eq!(app.current_viewport().top_line, 0);
+    }
+
+    #[test]
+    fn scroll_page_clamps_to_buffer_bounds() {
+        let mut app = app_with_lines(30, 20);
+        app.set_next_screen_context_lines(0);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.current_viewport().top_line, 10);
+
+        app.handle_action(Action::ScrollPageUp).unwrap();
+        assert_eq!(app.current_viewport().top_line, 0);
+    }
+
+    #[test]
+    fn positive_scroll_step_scrolls_fixed_lines() {
+        let mut app = app_with_lines(30, 20);
+        app.set_scroll_step(4);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.current_viewport().top_line, 4);
+        assert_eq!(app.editor.cursor().line, 4);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.current_viewport().top_line, 10);
+
+        app.handle_action(Action::ScrollPageUp).unwrap();
+        assert_eq!(app.current_viewport().top_line, 6);
+    }
+
+    #[test]
+    fn negative_scroll_step_keeps_point_centered_except_at_edges() {
+        let mut app = app_with_lines(100, 20);
+        app.set_scroll_step(-1);
+
+        app.handle_action(Action::ScrollPageDown).unwrap();
+        assert_eq!(app.editor.cursor().line, 18);
+        assert_eq!(app.current_viewport().top_line, 8);
+
+        for _ in 0..5 {
+            app.handle_action(Action::ScrollPageDown).unwrap();
+        }
+        assert_eq!(app.editor.cursor().line, 99);
+        assert_eq!(app.current_viewport().top_line, 80);
+
+        for _ in 0..6 {
+            app.handle_action(Action::ScrollPageUp).unwrap();
+        }
+        assert_eq!(app.editor.cursor().line, 0);
+        assert_eq!(app.current_viewport().top_line, 0);
+    }
+
+    #[test]
+    fn clone_buffer_edits_are_independent() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("original").unwrap();
+
+        app.handle_action(Action::CloneBuffer).unwrap();
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*<2>"));
+        assert_eq!(app.get_buffer_content(), "original");
+
+        app.insert_str(" clone").unwrap();
+        app.switch_buffer("*scratch*").unwrap();
+        assert_eq!(app.get_buffer_content(), "original");
+
+        app.switch_buffer("*scratch*<2>").unwrap();
+        assert_eq!(app.get_buffer_content(), "original clone");
+    }
+
+    #[test]
+    fn clone_buffer_names_avoid_collisions() {
+        let mut app = Backend::new().expect("app init");
+
+        app.handle_action(Action::CloneBuffer).unwrap();
+        app.handle_action(Action::CloneBuffer).unwrap();
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*<3>"));
+
+        app.switch_buffer("*scratch*").unwrap();
+        app.handle_action(Action::CloneBuffer).unwrap();
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*<4>"));
+        assert_eq!(
+            app.buffer_names(),
+            vec!["*scratch*", "*scratch*<2>", "*scratch*<3>", "*scratch*<4>"]
+        );
+    }
+
+    fn rename_current_buffer(app: &mut Backend, name: &str) {
+        let index = app.current_buffer_index().unwrap();
+        app.buffers[index].file.name = name.to_string();
+    }
+
+    #[test]
+    fn switch_buffer_completions_are_ordered_by_recency() {
+        let mut app = Backend::new().expect("app init");
+        rename_current_buffer(&mut app, "main.rs");
+        app.handle_action(Action::CloneBuffer).unwrap();
+        rename_current_buffer(&mut app, "notes.md");
+        app.handle_action(Action::CloneBuffer).unwrap();
+        rename_current_buffer(&mut app, "Makefile");
+        app.switch_buffer("main.rs").unwrap();
+        app.switch_buffer("notes.md").unwrap();
+
+        app.handle_action(Action::SwitchBuffer).unwrap();
+        assert_eq!(
+            app.minibuffer.completions(),
+            ["main.rs", "Makefile", "notes.md"]
+        );
+        assert!(app.minibuffer.current_input().is_empty());
+        assert!(app.minibuffer.current_prompt().contains("default main.rs"));
+
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("main.rs"));
+
+        app.kill_buffer(Some("notes.md")).unwrap();
+        app.handle_action(Action::SwitchBuffer).unwrap();
+        assert_eq!(app.minibuffer.completions(), ["Makefile", "main.rs"]);
+    }
+
+    #[test]
+    fn switch_buffer_fuzzy_input_narrows_candidates() {
+        let mut app = Backend::new().expect("app init");
+        rename_current_buffer(&mut app, "main.rs");
+        app.handle_action(Action::CloneBuffer).unwrap();
+        rename_current_buffer(&mut app, "domain.rs");
+        app.handle_action(Action::CloneBuffer).unwrap();
+        rename_current_buffer(&mut app, "notes.md");
+
+        app.handle_action(Action::SwitchBuffer).unwrap();
+        replace_minibuffer_input(&mut app, "mnr");
+        assert_eq!(app.minibuffer.completions(), ["domain.rs", "main.rs"]);
+        replace_minibuffer_input(&mut app, "ain");
+        assert_eq!(app.minibuffer.completions(), ["domain.rs", "main.rs"]);
+        replace_minibuffer_input(&mut app, "ma");
+        assert_eq!(app.minibuffer.completions(), ["main.rs", "domain.rs"]);
+
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("main.rs"));
+
+        app.set_fuzzy_buffer_completion(false);
+        app.handle_action(Action::SwitchBuffer).unwrap();
+        replace_minibuffer_input(&mut app, "ain");
+        assert!(app.minibuffer.completions().is_empty());
+    }
+
+    #[test]
+    fn toggle_fold_state_is_kept_per_buffer() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("head\n  body\ntail").unwrap();
+        app.move_cursor_to_start().unwrap();
+
+        app.handle_action(Action::ToggleFold).unwrap();
+        assert!(app.editor.folds().is_hidden(1));
+
+        app.handle_action(Action::CloneBuffer).unwrap();
+        assert!(app.editor.folds().is_empty());
+
+        app.switch_buffer("*scratch*").unwrap();
+        assert!(app.editor.folds().is_hidden(1));
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineDown))
+            .unwrap();
+        assert_eq!(app.editor.cursor().line, 2);
+    }
+
+    #[test]
+    fn session_restores_buffers_and_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        std::fs::write(&first, "one\n").unwrap();
+        std::fs::write(&second, "two\n").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.open_file(first.to_str().unwrap()).unwrap();
+        app.open_file(second.to_str().unwrap()).unwrap();
+        app.handle_action(Action::SplitWindowVertically).unwrap();
+        app.handle_action(Action::FocusOtherWindow).unwrap();
+        app.save_session(&session_path).unwrap();
+
+        let mut restored = Backend::new().expect("app init");
+        let warnings = restored.restore_session(&session_path).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            restored.buffer_names(),
+            vec!["*scratch*", "first.txt", "second.txt"]
+        );
+        assert_eq!(
+            restored.current_buffer_name().as_deref(),
+            Some("second.txt")
+        );
+        assert_eq!(restored.window_manager.window_count(), 2);
+        assert_eq!(
+            restored.window_manager.layout_snapshot(),
+            app.window_manager.layout_snapshot()
+        );
+        assert_eq!(restored.window_manager.focused_index(), 1);
+    }
+
+    #[test]
+    fn session_restores_file_shown_in_each_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        std::fs::write(&first, "one\n").unwrap();
+        std::fs::write(&second, "two\n").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.open_file(first.to_str().unwrap()).unwrap();
+        app.handle_action(Action::SplitWindowHorizontally).unwrap();
+        app.handle_action(Action::FocusOtherWindow).unwrap();
+        app.open_file(second.to_str().unwrap()).unwrap();
+        app.handle_action(Action::FocusOtherWindow).unwrap();
+        app.save_session(&session_path).unwrap();
+
+        let mut restored = Backend::new().expect("app init");
+        restored.restore_session(&session_path).unwrap();
+        let windows = restored.window_manager.leaf_order();
+        let buffer_name = |app: &Backend, window| {
+            app.window_manager
+                .buffer(window)
+                .and_then(|id| app.find_buffer_index(id))
+                .map(|index| app.buffers[index].name().to_string())
+        };
+        assert_eq!(
+            buffer_name(&restored, windows[0]).as_deref(),
+            Some("first.txt")
+        );
+        assert_eq!(
+            buffer_name(&restored, windows[1]).as_deref(),
+            Some("second.txt")
+        );
+        assert_eq!(restored.window_manager.focused_index(), 0);
+        assert_eq!(restored.current_buffer_name().as_deref(), Some("first.txt"));
+        let second_id = restored.window_manager.buffer(windows[1]).unwrap();
+        assert_eq!(
+            restored
+                .other_editors
+                .get(&second_id)
+                .map(|editor| editor.to_string()),
+            Some("two\n".to_string())
+        );
+    }
+
+    #[test]
+    fn set_option_in_init_file_enables_session_restore() {
+        let app = Backend::new().expect("app init");
+        assert!(!app.auto_save_session());
+        let mut interpreter = Interpreter::new();
+        interpreter.runtime_mut().set_host(Box::new(KeymapHost::new(
+            Rc::clone(&app.keymap),
+            Rc::clone(&app.gui_theme),
+            Rc::clone(&app.command_registry),
+            Rc::clone(&app.options),
+        )));
+        interpreter
+            .eval("(set-option \"auto-save-session\" #t)")
+            .unwrap();
+        interpreter
+            .eval("(set-option \"restore-session\" #t)")
+            .unwrap();
+        assert!(app.auto_save_session());
+        assert!(app.restore_session_at_startup());
+        assert!(interpreter
+            .eval("(set-option \"no-such-option\" #t)")
+            .is_err());
+        assert!(interpreter
+            .eval("(set-option \"auto-save-session\" \"yes\")")
+            .is_err());
+    }
+
+    #[test]
+    fn restore_session_option_restores_without_saving_on_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "text").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.open_file(file.to_str().unwrap()).unwrap();
+        app.save_session(&session_path).unwrap();
+
+        let mut restored = Backend::new().expect("app init");
+        restored.set_session_path(&session_path);
+        restored.set_restore_session_at_startup(true);
+        restored.restore_session_on_startup();
+        assert_eq!(restored.current_buffer_name().as_deref(), Some("file.txt"));
+
+        std::fs::remove_file(&session_path).unwrap();
+        restored.execute_command(Command::Quit).unwrap();
+        assert!(!session_path.exists());
+    }
+
+    #[test]
+    fn auto_save_session_on_exit_restores_on_startup() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        std::fs::write(&first, "one\n").unwrap();
+        std::fs::write(&second, "two\n").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.set_session_path(&session_path);
+        app.set_auto_save_session(true);
+        app.open_file(first.to_str().unwrap()).unwrap();
+        app.open_file(second.to_str().unwrap()).unwrap();
+        app.handle_action(Action::SplitWindowHorizontally).unwrap();
+        app.handle_action(Action::FocusOtherWindow).unwrap();
+        app.execute_command(Command::SaveBuffersKillTerminal)
+            .unwrap();
+        assert!(!app.is_running());
+        assert!(session_path.exists());
+
+        let mut restored = Backend::new().expect("app init");
+        restored.set_session_path(&session_path);
+        restored.set_auto_save_session(true);
+        restored.restore_session_on_startup();
+        assert_eq!(
+            restored.buffer_names(),
+            vec!["*scratch*", "first.txt", "second.txt"]
+        );
+        assert_eq!(
+            restored.current_buffer_name().as_deref(),
+            Some("second.txt")
+        );
+        assert_eq!(
+            restored.window_manager.layout_snapshot(),
+            app.window_manager.layout_snapshot()
+        );
+        assert_eq!(restored.window_manager.focused_index(), 1);
+    }
+
+    #[test]
+    fn auto_save_session_disabled_writes_nothing_on_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "text").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.set_session_path(&session_path);
+        app.open_file(file.to_str().unwrap()).unwrap();
+        app.execute_command(Command::Quit).unwrap();
+        assert!(!app.is_running());
+        assert!(!session_path.exists());
+
+        let mut fresh = Backend::new().expect("app init");
+        fresh.set_session_path(&session_path);
+        fresh.restore_session_on_startup();
+        assert_eq!(fresh.buffer_names(), vec!["*scratch*"]);
+    }
+
+    #[test]
+    fn execute_command_quit_saves_session_like_kill_terminal() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "text").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.set_session_path(&session_path);
+        app.set_auto_save_session(true);
+        app.open_file(file.to_str().unwrap()).unwrap();
+        app.handle_action(Action::ExecuteCommand).unwrap();
+        replace_minibuffer_input(&mut app, "quit");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(!app.is_running());
+        assert!(session_path.exists());
+    }
+
+    #[test]
+    fn session_restores_folds() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("folded.txt");
+        std::fs::write(&file, "head\n  body\n  more\ntail\n").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.open_file(file.to_str().unwrap()).unwrap();
+        app.move_cursor_to_start().unwrap();
+        app.handle_action(Action::ToggleFold).unwrap();
+        app.save_session(&session_path).unwrap();
+
+        let mut restored = Backend::new().expect("app init");
+        restored.restore_session(&session_path).unwrap();
+        assert_eq!(
+            restored.current_buffer_name().as_deref(),
+            Some("folded.txt")
+        );
+        assert_eq!(
+            restored.editor.folds().folds(),
+            &[crate::buffer::FoldRange::new(0, 2)]
+        );
+    }
+
+    #[test]
+    fn session_restore_skips_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let removed = dir.path().join("removed.txt");
+        std::fs::write(&kept, "kept").unwrap();
+        std::fs::write(&removed, "removed").unwrap();
+        let session_path = dir.path().join("session.json");
+
+        let mut app = Backend::new().expect("app init");
+        app.open_file(kept.to_str().unwrap()).unwrap();
+        app.open_file(removed.to_str().unwrap()).unwrap();
+        app.save_session(&session_path).unwrap();
+        std::fs::remove_file(&removed).unwrap();
+
+        let mut restored = Backend::new().expect("app init");
+        let warnings = restored.restore_session(&session_path).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("removed.txt"));
+        assert_eq!(restored.buffer_names(), vec!["*scratch*", "kept.txt"]);
+    }
+
+    fn open_alisp_buffer(dir: &tempfile::TempDir, content: &str) -> Backend {
+        let path = dir.path().join("sample.al");
+        std::fs::write(&path, content).unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+        app
+    }
+
+    #[test]
+    fn tab_in_alisp_buffer_aligns_nested_forms() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = open_alisp_buffer(&dir, "(define (f x)\n(let ((a 1)\n(b 2))\n(+ a\nb)))");
+        for line in 2..=5 {
+            app.goto_line(line).unwrap();
+            app.handle_action(Action::IndentForTab).unwrap();
+        }
+        assert_eq!(
+            app.editor.to_string(),
+            "(define (f x)\n  (let ((a 1)\n        (b 2))\n    (+ a\n       b)))"
+        );
+        assert_eq!(app.editor.cursor().column, 7);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(
+            app.editor.to_string(),
+            "(define (f x)\n  (let ((a 1)\n        (b 2))\n    (+ a\nb)))"
+        );
+    }
+
+    #[test]
+    fn newline_and_indent_in_alisp_buffer_uses_form_indent() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = open_alisp_buffer(&dir, "(when ok");
+        app.handle_action(Action::Navigate(NavigationAction::MoveBufferEnd))
+            .unwrap();
+        app.handle_action(Action::NewlineAndIndent).unwrap();
+        assert_eq!(app.editor.to_string(), "(when ok\n  ");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "(when ok");
+    }
+
+    #[test]
+    fn quoted_insert_inserts_tab_and_control_characters() {
+        let mut app = Backend::new().expect("app init");
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "\t\u{1}");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "\t");
+    }
+
+    #[test]
+    fn quoted_insert_puts_literal_tab_into_prompt() {
+        let mut app = Backend::new().expect("app init");
+        app.minibuffer.start_execute_command().unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        assert!(app.minibuffer.is_active());
+        assert_eq!(app.minibuffer.current_input(), "\t");
+        assert!(app.editor.to_string().is_empty());
+    }
+
+    fn insert_char_by_code(app: &mut Backend, code: &str) {
+        press(app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(app, KeyCode::Char('8'), KeyModifiers::NONE);
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.minibuffer.is_active());
+        replace_minibuffer_input(app, code);
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn insert_char_inserts_bmp_and_astral_codepoints() {
+        let mut app = Backend::new().expect("app init");
+        insert_char_by_code(&mut app, "3042");
+        insert_char_by_code(&mut app, "U+1F600");
+        assert_eq!(app.editor.to_string(), "あ😀");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "あ");
+    }
+
+    #[test]
+    fn describe_function_shows_command_binding_and_doc() {
+        let mut app = Backend::new().expect("app init");
+        let text = app.describe_function_text("forward-paragraph").unwrap();
+        assert!(text.contains("エディタコマンド"), "{text}");
+        assert!(text.contains("キー: M-}"), "{text}");
+        assert!(
+            text.contains(Command::ForwardParagraph.description()),
+            "{text}"
+        );
+
+        app.register_command("say-hello", "", |_| Ok(())).unwrap();
+        let text = app.describe_function_text("say-hello").unwrap();
+        assert!(text.contains("キー: なし"), "{text}");
+        assert!(text.contains("（説明はありません）"), "{text}");
+
+        let text = app.describe_function_text("string-append").unwrap();
+        assert!(text.contains("alisp の組込み関数"), "{text}");
+        assert!(text.contains("引数: 1 以上"), "{text}");
+
+        assert!(app.describe_function_text("no-such-function").is_err());
+    }
+
+    #[test]
+    fn describe_function_prompt_opens_help_buffer() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("draft").unwrap();
+        for name in ["undo", "center-line"] {
+            app.execute_command(Command::DescribeFunction).unwrap();
+            assert!(app.minibuffer.is_active());
+            replace_minibuffer_input(&mut app, name);
+            press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+            assert_eq!(app.current_buffer_name().as_deref(), Some(HELP_BUFFER_NAME));
+            assert!(app.editor.to_string().starts_with(&format!("{} は", name)));
+        }
+        assert_eq!(app.buffer_names(), vec!["*scratch*", HELP_BUFFER_NAME]);
+
+        app.switch_buffer("*scratch*").unwrap();
+        assert_eq!(app.editor.to_string(), "draft");
+    }
+
+    fn eval_in_minibuffer(app: &mut Backend, source: &str) {
+        app.execute_command(Command::EvalExpression).unwrap();
+        replace_minibuffer_input(app, source);
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn eval_definitions_persist_until_reset() {
+        let mut app = Backend::new().expect("app init");
+        eval_in_minibuffer(&mut app, "(define base 40)");
+        eval_in_minibuffer(&mut app, "(define (plus-base x) (+ base x))");
+        eval_in_minibuffer(&mut app, "(plus-base 2)");
+        assert_eq!(displayed_message(&app), Some((false, "=> 42".to_string())));
+
+        app.execute_command(Command::from_string("reset-alisp-env"))
+            .unwrap();
+        eval_in_minibuffer(&mut app, "base");
+        assert!(matches!(displayed_message(&app), Some((true, _))));
+        eval_in_minibuffer(&mut app, "(+ 1 2)");
+        assert_eq!(displayed_message(&app), Some((false, "=> 3".to_string())));
+    }
+
+    #[test]
+    fn eval_shares_environment_with_init_file() {
+        let mut app = Backend::new().expect("app init");
+        // init ファイルと同じホストにつながっているので bind-key が効く
+        eval_in_minibuffer(&mut app, "(bind-key \"M-o\" \"center-line\")");
+        assert!(!matches!(displayed_message(&app), Some((true, _))));
+        app.set_fill_column(10);
+        app.insert_str("ab").unwrap();
+        press(&mut app, KeyCode::Char('o'), KeyModifiers::ALT);
+        assert_eq!(app.editor.to_string(), "    ab");
+    }
 
-            Command::SaveAllBuffers => {
-                self.persist_current_buffer_state();
+    #[test]
+    fn debug_on_error_shows_backtrace_buffer() {
+        let mut app = Backend::new().expect("app init");
+        eval_in_minibuffer(&mut app, "(define (half x) (/ x 0))");
+        eval_in_minibuffer(&mut app, "(half 4)");
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+
+        app.execute_command(Command::ToggleDebugOnError).unwrap();
+        eval_in_minibuffer(&mut app, "(half 4)");
+        assert_eq!(
+            app.current_buffer_name().as_deref(),
+            Some(BACKTRACE_BUFFER_NAME)
+        );
+        let text = app.editor.to_string();
+        assert!(text.contains("0 で除算"), "{text}");
+        assert!(text.contains("  (/ 4 0)\n  (half 4)\n"), "{text}");
+        let (is_error, _) = displayed_message(&app).expect("error message");
+        assert!(is_error);
+    }
 
-                let original_id = self.current_buffer_id;
-                let mut saved_count = 0usize;
+    #[test]
+    fn insert_char_accepts_prefixed_codepoint_and_name() {
+        let mut app = Backend::new().expect("app init");
+        insert_char_by_code(&mut app, "U+00E9");
+        assert_eq!(app.editor.to_string(), "é");
 
-                for idx in 0..self.buffers.len() {
-                    let buffer_clone = self.buffers[idx].file.clone();
-                    self.command_processor.set_current_buffer(buffer_clone);
-                    let content = self.buffers[idx].file.content.clone();
-                    self.command_processor.sync_editor_content(&content);
+        insert_char_by_code(&mut app, "em dash");
+        assert_eq!(app.editor.to_string(), "é—");
 
-                    let result = self.command_processor.execute(Command::SaveBuffer);
-                    if result.success {
-                        if let Some(updated) = self.command_processor.current_buffer().cloned() {
-                            self.buffers[idx].file = updated;
-                        }
-                        saved_count += 1;
-                    } else if let Some(msg) = result.message {
-                        self.show_error_message(AltreError::Application(msg));
-                        return Ok(());
-                    }
-                }
+        insert_char_by_code(&mut app, "NO SUCH CHARACTER");
+        let (is_error, _) = displayed_message(&app).expect("error message");
+        assert!(is_error);
+        assert_eq!(app.editor.to_string(), "é—");
+    }
 
-                if let Some(id) = original_id {
-                    if let Some(index) = self.find_buffer_index(id) {
-                        let buffer_clone = self.buffers[index].file.clone();
-                        self.command_processor.set_current_buffer(buffer_clone);
-                        self.command_processor
-                            .sync_editor_content(&self.editor.to_string());
-                    }
-                }
+    #[test]
+    fn insert_char_rejects_invalid_codepoint() {
+        let mut app = Backend::new().expect("app init");
+        for code in ["D800", "110000", "zz"] {
+            insert_char_by_code(&mut app, code);
+            let (is_error, message) = displayed_message(&app).expect("error message");
+            assert!(is_error);
+            assert!(message.contains(code), "{message}");
+        }
+        assert!(app.editor.to_string().is_empty());
+    }
 
-                self.show_info_message(format!("{} 個のバッファを保存しました", saved_count));
-                Ok(())
-            }
+    #[test]
+    fn tab_behavior_can_insert_literal_tab() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("ab").unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "ab  ");
+
+        app.set_tab_behavior(TabBehavior::InsertTab);
+        app.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "ab  \t");
+    }
 
-            Command::SaveBuffer => {
-                self.persist_current_buffer_state();
+    #[test]
+    fn just_one_space_collapses_spaces_and_tabs() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo \t  bar").unwrap();
+        app.editor.move_cursor_to_char(5).unwrap();
+        app.handle_action(Action::JustOneSpace).unwrap();
+        assert_eq!(app.editor.to_string(), "foo bar");
+        assert_eq!(app.editor.cursor().char_pos, 4);
+
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.handle_action(Action::JustOneSpace).unwrap();
+        assert_eq!(app.editor.to_string(), "f oo bar");
+
+        app.handle_action(Action::Undo).unwrap();
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "foo \t  bar");
+    }
 
-                if let Some(index) = self.current_buffer_index() {
-                    let needs_path = self.buffers[index].file.path.is_none();
-                    if needs_path {
-                        let suggested = if self.buffers[index].file.name.trim().is_empty() {
-                            "untitled".to_string()
-                        } else {
-                            self.buffers[index].file.name.clone()
-                        };
-                        self.start_save_as_prompt(&suggested)?;
-                    } else {
-                        let buffer_clone = self.buffers[index].file.clone();
-                        self.command_processor.set_current_buffer(buffer_clone);
-                        self.command_processor
-                            .sync_editor_content(&self.editor.to_string());
+    #[test]
+    fn delete_horizontal_space_removes_surrounding_whitespace() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("a\n\t  b  c").unwrap();
+        app.editor.move_cursor_to_char(3).unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('\\'), KeyModifiers::ALT))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "a\nb  c");
+        assert_eq!(app.editor.cursor().char_pos, 2);
+
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.handle_action(Action::DeleteHorizontalSpace).unwrap();
+        assert_eq!(app.editor.to_string(), "a\nbc");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "a\nb  c");
+    }
 
-                        let result = self.command_processor.execute(Command::SaveBuffer);
-                        if result.success {
-                            if let Some(updated) = self.command_processor.current_buffer().cloned()
-                            {
-                                self.buffers[index].file = updated;
-                            }
-                            if let Some(msg) = result.message {
-                                self.show_info_message(msg);
-                            }
-                        } else if let Some(msg) = result.message {
-                            self.show_error_message(AltreError::Application(msg));
-                        }
-                    }
-                } else {
-                    let current_dir = std::env::current_dir()
-                        .map(|p| p.display().to_string())
-                        .unwrap_or_else(|_| "~/".to_string());
+    #[test]
+    fn insert_pair_wraps_region_as_one_step() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo bar baz").unwrap();
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(7).unwrap();
+        press(&mut app, KeyCode::Char('('), KeyModifiers::ALT);
+        assert_eq!(app.editor.to_string(), "foo (bar) baz");
+        assert_eq!(app.editor.cursor().char_pos, 9);
+        assert!(app.editor.selection_range().is_none());
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "foo bar baz");
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(3).unwrap();
+        app.execute_command(Command::from_string("insert-quotes"))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "\"foo\" bar baz");
+    }
 
-                    let initial_path = if current_dir.ends_with('/') {
-                        format!("{}untitled", current_dir)
-                    } else {
-                        format!("{}/untitled", current_dir)
-                    };
+    #[test]
+    fn insert_pair_without_region_inserts_empty_pair() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("ab").unwrap();
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.execute_command(Command::InsertPair('[')).unwrap();
+        assert_eq!(app.editor.to_string(), "a[]b");
+        assert_eq!(app.editor.cursor().char_pos, 2);
+
+        app.execute_command(Command::from_string("insert-braces"))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "a[{}]b");
+        assert_eq!(app.editor.cursor().char_pos, 3);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "a[]b");
+    }
 
-                    self.start_save_as_prompt(&initial_path)?;
-                }
-                Ok(())
-            }
-            Command::SaveBuffersKillTerminal | Command::Quit => {
-                self.persist_current_buffer_state();
-                self.shutdown();
-                Ok(())
-            }
-            Command::ExecuteCommand => self.start_execute_command_prompt(),
-            Command::EvalExpression => self.start_eval_expression_prompt(),
-            Command::QueryReplace => self.start_query_replace_prompt(false),
-            Command::RegexQueryReplace => self.start_query_replace_prompt(true),
-            Command::MoveLineStart => {
-                self.navigate(NavigationAction::MoveLineStart);
-                Ok(())
-            }
-            Command::MoveLineEnd => {
-                self.navigate(NavigationAction::MoveLineEnd);
-                Ok(())
-            }
-            Command::MoveBufferStart => {
-                self.navigate(NavigationAction::MoveBufferStart);
-                Ok(())
-            }
-            Command::MoveBufferEnd => {
-                self.navigate(NavigationAction::MoveBufferEnd);
-                Ok(())
-            }
-            _ => {
-                self.show_info_message(format!("未実装のコマンド: {:?}", command));
-                Ok(())
-            }
-        }
+    #[test]
+    fn close_parens_balances_unclosed_openers() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("(define (f x)\n  (let ((a \")\"").unwrap();
+        app.handle_action(Action::CloseParens).unwrap();
+        assert_eq!(
+            app.editor.to_string(),
+            "(define (f x)\n  (let ((a \")\"))))"
+        );
+
+        app.handle_action(Action::CloseParens).unwrap();
+        assert_eq!(
+            app.editor.to_string(),
+            "(define (f x)\n  (let ((a \")\"))))"
+        );
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "(define (f x)\n  (let ((a \")\"");
     }
 
-    fn record_kill(&mut self, text: String, merge: KillMerge) {
-        if text.is_empty() {
-            self.reset_kill_context();
-            return;
-        }
+    fn press_ctrl_g(app: &mut Backend) {
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL))
+            .unwrap();
+    }
 
-        match merge {
-            KillMerge::Append => {
-                if matches!(self.kill_context, KillContext::Kill) {
-                    self.kill_ring.append_to_front(&text);
-                } else {
-                    self.kill_ring.push(text);
-                }
-            }
-            KillMerge::Prepend => {
-                if matches!(self.kill_context, KillContext::Kill) {
-                    self.kill_ring.prepend_to_front(&text);
-                } else {
-                    self.kill_ring.push(text);
-                }
-            }
-        }
+    #[test]
+    fn keyboard_quit_cancels_prompt_then_replace_then_mark() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo foo").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.start_query_replace_session("foo".to_string(), "bar".to_string(), false)
+            .unwrap();
+        app.minibuffer.start_execute_command().unwrap();
+        assert!(app.minibuffer.is_active());
+
+        press_ctrl_g(&mut app);
+        assert!(!app.minibuffer.is_active());
+        assert!(app.replace.controller.is_active());
+        assert!(app.editor.mark().is_some());
+
+        press_ctrl_g(&mut app);
+        assert!(!app.replace.controller.is_active());
+        assert!(app.editor.mark().is_some());
+        assert_eq!(app.editor.to_string(), "foo foo");
+
+        press_ctrl_g(&mut app);
+        assert!(!app.editor.is_mark_active());
+        assert!(app.editor.mark().is_some());
+    }
 
-        self.kill_context = KillContext::Kill;
-        self.last_yank_range = None;
+    #[test]
+    fn keyboard_quit_cancels_search_then_mark_then_prefix() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("hello world").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(app.keymap.borrow().is_partial_match());
+        app.search.start(&mut app.editor, SearchDirection::Forward);
+
+        press_ctrl_g(&mut app);
+        assert!(!app.search.is_active());
+        assert!(app.editor.mark().is_some());
+        assert!(app.keymap.borrow().is_partial_match());
+
+        press_ctrl_g(&mut app);
+        assert!(!app.editor.is_mark_active());
+        assert!(!app.keymap.borrow().is_partial_match());
+        assert!(app.current_prefix.is_none());
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .unwrap();
+        press_ctrl_g(&mut app);
+        assert!(!app.keymap.borrow().is_partial_match());
+        assert!(app.current_prefix.is_none());
     }
 
-    fn indent_for_tab(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let insertion = self.tab_insertion_string();
-        let result = self.editor.insert_str(&insertion);
-        let success = result.is_ok();
+    #[test]
+    fn large_paste_can_be_cancelled_midway() {
+        let mut app = Backend::new().expect("app init");
+        app.set_large_insert_threshold(16);
+        app.insert_str("base").unwrap();
+        let text = "x\n".repeat(LARGE_INSERT_CHUNK_CHARS * 2);
 
-        if let Err(err) = result {
-            self.show_error_message(err);
-        }
+        app.paste_text(&text);
+        assert!(app.is_inserting());
+        app.process_pending_insert();
+        assert!(app.is_inserting());
 
-        self.end_history(success);
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
-    }
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL))
+            .unwrap();
 
-    fn newline_and_indent(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let indent = self.current_line_indent();
-        let mut success = false;
+        assert!(!app.is_inserting());
+        assert_eq!(app.editor.to_string(), "base");
+        assert_eq!(app.editor.cursor().char_pos, 4);
 
-        match self.editor.insert_newline() {
-            Ok(()) => {
-                let result = if indent.is_empty() {
-                    Ok(())
-                } else {
-                    self.editor.insert_str(&indent)
-                };
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "base");
+    }
 
-                if let Err(err) = result {
-                    self.show_error_message(err);
-                } else {
-                    success = true;
-                }
-            }
-            Err(err) => {
-                self.show_error_message(err);
-            }
+    #[test]
+    fn paste_during_prompt_or_isearch_goes_to_that_input() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo bar foo").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        app.execute_command(Command::ExecuteCommand).unwrap();
+        app.paste_text("goto-\r\nline");
+        assert_eq!(app.minibuffer.current_input(), "goto-line");
+        assert_eq!(app.editor.to_string(), "foo bar foo");
+        press_ctrl_g(&mut app);
+
+        app.search.start(&mut app.editor, SearchDirection::Forward);
+        app.paste_text("bar\n");
+        assert_eq!(app.search.current_pattern(), Some("bar"));
+        assert_eq!(app.editor.to_string(), "foo bar foo");
+        assert_eq!(app.editor.cursor().char_pos, 4);
+    }
+
+    #[test]
+    fn chunked_paste_matches_direct_insert() {
+        let text = "あいう\r\nabc\n".repeat(LARGE_INSERT_CHUNK_CHARS / 4);
+
+        let mut direct = Backend::new().expect("app init");
+        direct.paste_text(&text);
+        assert!(!direct.is_inserting());
+
+        let mut chunked = Backend::new().expect("app init");
+        chunked.set_large_insert_threshold(16);
+        chunked.paste_text(&text);
+        let mut ticks = 0;
+        while chunked.is_inserting() {
+            chunked.process_pending_insert();
+            ticks += 1;
         }
 
-        self.end_history(success);
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
+        assert!(ticks > 1);
+        assert_eq!(chunked.editor.to_string(), direct.editor.to_string());
+        assert_eq!(
+            chunked.editor.cursor().char_pos,
+            direct.editor.cursor().char_pos
+        );
+
+        chunked.handle_action(Action::Undo).unwrap();
+        assert_eq!(chunked.editor.to_string(), "");
     }
 
-    fn open_line(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let cursor_before = *self.editor.cursor();
-        let mut success = false;
+    #[test]
+    fn isearch_lax_whitespace_setting_and_toggle() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo\tbar").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.set_isearch_lax_whitespace(true);
 
-        match self.editor.insert_newline() {
-            Ok(()) => {
-                self.editor.set_cursor(cursor_before);
-                success = true;
-            }
-            Err(err) => {
-                self.show_error_message(err);
-            }
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        for ch in "foo bar".chars() {
+            press(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
         }
+        assert_eq!(app.search.ui_state().unwrap().total_matches, 1);
 
-        self.end_history(success);
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
+        press(&mut app, KeyCode::Char(' '), KeyModifiers::ALT);
+        assert!(app.search.ui_state().unwrap().is_error());
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        app.set_isearch_lax_whitespace(false);
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert!(!app.search.is_lax_whitespace());
     }
 
-    fn current_line_indent(&self) -> String {
-        let cursor = *self.editor.cursor();
-        let text = self.editor.to_string();
-        let lines: Vec<&str> = text.split('\n').collect();
+    #[test]
+    fn toggle_truncate_lines_wraps_and_keeps_wrapped_cursor_visible() {
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(app.render_metadata().line_wrap(), None);
+
+        app.execute_command(Command::ToggleTruncateLines).unwrap();
+        assert_eq!(
+            app.render_metadata().line_wrap(),
+            Some(LineWrap {
+                mode: WrapMode::Word,
+                column: WrapColumn::Window,
+                centered: false,
+            })
+        );
 
-        let line_content = if cursor.line < lines.len() {
-            lines[cursor.line]
-        } else {
-            lines.last().copied().unwrap_or("")
-        };
+        app.current_viewport_mut().update_dimensions(2, 10);
+        app.insert_str("top\naaaa bbbb cccc dddd").unwrap();
+        app.ensure_cursor_visible();
+        // 2行目は 2 表示行に折り返され、カーソルは 2 つ目の表示行にある
+        assert_eq!(app.current_viewport().top_line, 1);
+        assert_eq!(app.current_viewport().scroll_x, 0);
 
-        line_content
-            .chars()
-            .take_while(|ch| matches!(ch, ' ' | '\t'))
-            .collect()
+        app.execute_command(Command::ToggleTruncateLines).unwrap();
+        assert_eq!(app.render_metadata().line_wrap(), None);
     }
 
-    fn tab_insertion_string(&self) -> String {
-        let cursor = *self.editor.cursor();
-        let text = self.editor.to_string();
-        let line_content = text.split('\n').nth(cursor.line).unwrap_or("");
-        let spaces =
-            edit_utils::spaces_to_next_tab_stop(line_content, cursor.column, DEFAULT_TAB_WIDTH);
-        " ".repeat(spaces)
+    #[test]
+    fn render_metadata_carries_wrap_column_setting() {
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(app.render_metadata().wrap_column, WrapColumn::Window);
+
+        app.set_wrap_column(WrapColumn::FillColumn(20));
+        app.set_wrap_centered(true);
+        let metadata = app.render_metadata();
+        assert_eq!(metadata.wrap_column, WrapColumn::FillColumn(20));
+        assert!(metadata.wrap_centered);
+
+        let line = "one two three four five six seven";
+        for window_width in [40, 120] {
+            assert_eq!(
+                metadata
+                    .wrap_column
+                    .wrap_points(line, window_width, metadata.wrap_mode),
+                vec![19]
+            );
+        }
+        assert_eq!(metadata.wrap_column.left_margin(120, true), 50);
+
+        app.set_truncate_lines(false);
+        let wrap = app.render_metadata().line_wrap().expect("折り返し表示");
+        assert_eq!(wrap.column, WrapColumn::FillColumn(20));
+        assert_eq!(wrap.left_margin(120), 50);
     }
 
-    fn kill_word_forward(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let result = self.editor.delete_word_forward();
-        let success = result.is_ok();
-        match result {
-            Ok(text) => {
-                self.record_kill(text, KillMerge::Append);
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-            }
-            Err(err) => self.show_error_message(err),
-        }
-        self.end_history(success);
+    #[test]
+    fn find_problematic_chars_moves_to_next_invisible_char() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("ab\u{200B}c\nd\x01e").unwrap();
+        app.move_cursor_to_start().unwrap();
+
+        app.execute_command_by_name("find-problematic-chars")
+            .unwrap();
+        assert_eq!(app.editor.cursor().char_pos, 2);
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "U+200B があります".to_string()))
+        );
+
+        app.execute_command(Command::FindProblematicChars).unwrap();
+        assert_eq!(app.editor.cursor().line, 1);
+        assert_eq!(app.editor.cursor().column, 1);
+
+        app.execute_command(Command::FindProblematicChars).unwrap();
+        assert_eq!(app.editor.cursor().column, 1);
+        assert_eq!(
+            displayed_message(&app),
+            Some((
+                false,
+                "これ以降に制御文字や見えない文字はありません".to_string()
+            ))
+        );
     }
 
-    fn kill_word_backward(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let result = self.editor.delete_word_backward();
-        let success = result.is_ok();
-        match result {
-            Ok(text) => {
-                self.record_kill(text, KillMerge::Prepend);
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-            }
-            Err(err) => self.show_error_message(err),
-        }
-        self.end_history(success);
+    #[test]
+    fn find_problematic_chars_finds_char_at_point_first() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("\u{FEFF}ab\u{200B}").unwrap();
+        app.move_cursor_to_start().unwrap();
+
+        app.execute_command(Command::FindProblematicChars).unwrap();
+        assert_eq!(app.editor.cursor().char_pos, 0);
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "U+FEFF があります".to_string()))
+        );
+
+        app.execute_command(Command::FindProblematicChars).unwrap();
+        assert_eq!(app.editor.cursor().char_pos, 3);
     }
 
-    fn kill_line_forward(&mut self) {
-        self.begin_history(HistoryCommandKind::Other);
-        let result = self.editor.kill_line_forward();
-        let success = result.is_ok();
-        match result {
-            Ok(text) => {
-                self.record_kill(text, KillMerge::Append);
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-            }
-            Err(err) => self.show_error_message(err),
-        }
-        self.end_history(success);
+    #[test]
+    fn cycle_control_char_display_reaches_code_point_notation() {
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(
+            app.render_metadata().control_char_display,
+            ControlCharDisplay::Caret
+        );
+        app.execute_command_by_name("cycle-control-char-display")
+            .unwrap();
+        assert_eq!(
+            app.render_metadata().control_char_display,
+            ControlCharDisplay::Replacement
+        );
+        app.execute_command(Command::CycleControlCharDisplay)
+            .unwrap();
+        assert_eq!(
+            app.render_metadata().control_char_display,
+            ControlCharDisplay::CodePoint
+        );
+        app.execute_command(Command::CycleControlCharDisplay)
+            .unwrap();
+        assert_eq!(app.control_char_display(), ControlCharDisplay::Caret);
     }
 
-    fn set_mark_command(&mut self) {
-        self.editor.set_mark();
-        self.show_info_message("マークを設定しました");
-        self.reset_recenter_cycle();
+    #[test]
+    fn string_rectangle_replaces_columns_across_ragged_lines() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("abcdef\nab\n\nabcdefg").unwrap();
+        app.editor.move_cursor_to_char(2).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(15).unwrap();
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('r'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('t'), KeyModifiers::NONE);
+        assert_eq!(app.minibuffer.current_prompt(), "String rectangle: ");
+        replace_minibuffer_input(&mut app, "XY");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        assert_eq!(app.editor.to_string(), "abXYef\nabXY\n  XY\nabXYefg");
+        assert_eq!(app.editor.cursor().line, 3);
+        assert_eq!(app.editor.cursor().column, 4);
+        assert!(!app.editor.is_mark_active());
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "abcdef\nab\n\nabcdefg");
     }
 
-    fn kill_region(&mut self) -> Result<()> {
-        self.begin_history(HistoryCommandKind::Other);
-        let result = if let Some((start, end)) = self.editor.selection_range() {
-            match self.editor.delete_range_span(start, end) {
-                Ok(text) => {
-                    if text.is_empty() {
-                        self.show_info_message("選択範囲が空です");
-                    } else {
-                        self.record_kill(text, KillMerge::Append);
-                        self.kill_context = KillContext::Kill;
-                        self.last_yank_range = None;
-                    }
-                    self.editor.clear_mark();
-                    self.reset_recenter_cycle();
-                    self.ensure_cursor_visible();
-                    Ok(())
-                }
-                Err(err) => {
-                    self.show_error_message(err.clone());
-                    Err(err)
-                }
-            }
-        } else {
-            self.show_info_message("リージョンが選択されていません");
-            Ok(())
-        };
-        self.end_history(result.is_ok());
-        result
+    #[test]
+    fn string_rectangle_with_zero_width_inserts_at_column() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("ab\ncd").unwrap();
+        app.execute_command(Command::StringRectangle).unwrap();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "マークが設定されていません".to_string()))
+        );
+
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.execute_command(Command::StringRectangle).unwrap();
+        replace_minibuffer_input(&mut app, "|");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "a|b\nc|d");
     }
 
-    fn copy_region(&mut self) -> Result<()> {
-        if let Some((start, end)) = self.editor.selection_range() {
-            let text = self.editor.get_text_range(start, end)?;
-            if text.is_empty() {
-                self.show_info_message("選択範囲が空です");
-            } else {
-                self.record_kill(text, KillMerge::Append);
-                self.kill_context = KillContext::Kill;
-                self.last_yank_range = None;
-                self.show_info_message("リージョンをコピーしました");
-            }
-        } else {
-            self.show_info_message("リージョンが選択されていません");
-        }
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
-        Ok(())
+    #[test]
+    fn delete_selection_replaces_region_when_typing() {
+        let mut app = Backend::new().expect("app init");
+        app.set_delete_selection(true);
+        app.insert_str("hello world").unwrap();
+        app.editor.move_cursor_to_char(6).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(11).unwrap();
+
+        type_text(&mut app, "there");
+        assert_eq!(app.editor.to_string(), "hello there");
+        assert!(!app.editor.is_mark_active());
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(5).unwrap();
+        app.handle_action(Action::InsertNewline).unwrap();
+        assert_eq!(app.editor.to_string(), "\n there");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "hello there");
     }
 
-    fn exchange_point_and_mark(&mut self) -> Result<()> {
-        if self.editor.mark().is_none() {
-            self.show_info_message("マークが設定されていません");
-            return Ok(());
-        }
-        self.editor.swap_cursor_and_mark()?;
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
-        Ok(())
+    #[test]
+    fn delete_selection_first_char_replaces_region_in_one_undo_step() {
+        let mut app = Backend::new().expect("app init");
+        app.set_delete_selection(true);
+        app.insert_str("abc").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(3).unwrap();
+
+        app.handle_action(Action::InsertChar('x')).unwrap();
+        assert_eq!(app.editor.to_string(), "x");
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "abc");
     }
 
-    fn mark_entire_buffer(&mut self) -> Result<()> {
-        self.editor.mark_entire_buffer()?;
-        self.show_info_message("バッファ全体を選択しました");
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
-        Ok(())
+    #[test]
+    fn delete_selection_yank_replaces_region() {
+        let mut app = Backend::new().expect("app init");
+        app.set_delete_selection(true);
+        app.insert_str("one two").unwrap();
+        app.kill_ring.push("three".to_string());
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(7).unwrap();
+
+        app.execute_command(Command::Yank).unwrap();
+        assert_eq!(app.editor.to_string(), "one three");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "one two");
+
+        // 端末からの貼り付けもリージョンを置き換える
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(7).unwrap();
+        app.paste_text("four");
+        assert_eq!(app.editor.to_string(), "one four");
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "one two");
+
+        // 分割挿入を取り消すと置き換えたリージョンも戻る
+        app.set_large_insert_threshold(2);
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(7).unwrap();
+        app.paste_text(&"x".repeat(LARGE_INSERT_CHUNK_CHARS * 2));
+        assert!(app.is_inserting());
+        press_ctrl_g(&mut app);
+        assert!(!app.is_inserting());
+        assert_eq!(app.editor.to_string(), "one two");
     }
 
-    fn yank(&mut self) {
-        let Some(text) = self.kill_ring.yank() else {
-            self.show_info_message("キルリングが空です");
-            self.reset_kill_context();
-            return;
-        };
+    #[test]
+    fn delete_selection_is_disabled_by_default() {
+        let mut app = Backend::new().expect("app init");
+        assert!(!app.delete_selection());
+        app.insert_str("abc").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(3).unwrap();
+
+        app.handle_action(Action::InsertChar('x')).unwrap();
+        assert_eq!(app.editor.to_string(), "abcx");
+
+        // 有効でも移動はリージョンを削除しない
+        app.set_delete_selection(true);
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharBackward))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "abcx");
+    }
 
-        let start = self.editor.cursor().char_pos;
-        let len = text.chars().count();
+    #[test]
+    fn center_line_centers_short_line_in_one_undo_step() {
+        let mut app = Backend::new().expect("app init");
+        app.set_fill_column(20);
+        app.insert_str("first\n  title  \nlast").unwrap();
+        app.editor.move_cursor_to_char(9).unwrap();
 
-        self.begin_history(HistoryCommandKind::Other);
-        match self.editor.insert_str(&text) {
-            Ok(_) => {
-                self.kill_context = KillContext::Yank;
-                self.last_yank_range = Some((start, len));
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                self.end_history(true);
-            }
-            Err(err) => {
-                self.reset_kill_context();
-                self.show_error_message(err);
-                self.end_history(false);
-            }
-        }
+        app.execute_command(Command::CenterLine).unwrap();
+        assert_eq!(app.editor.to_string(), "first\n       title\nlast");
+        // カーソルは "title" の "i" の前に残る
+        assert_eq!(app.editor.cursor().char_pos, 14);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "first\n  title  \nlast");
     }
 
-    fn yank_pop(&mut self) {
-        if !matches!(self.kill_context, KillContext::Yank) {
-            self.show_info_message("直前のコマンドがヤンクではありません");
-            self.reset_kill_context();
-            return;
-        }
+    #[test]
+    fn center_line_with_region_centers_each_line_and_skips_wide_ones() {
+        let mut app = Backend::new().expect("app init");
+        app.set_fill_column(10);
+        app.insert_str("ab\nthis line is too wide\nxyzw\nrest")
+            .unwrap();
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(30).unwrap();
+
+        app.execute_command(Command::CenterLine).unwrap();
+        assert_eq!(
+            app.editor.to_string(),
+            "    ab\nthis line is too wide\n   xyzw\nrest"
+        );
+
+        let mut wide = Backend::new().expect("app init");
+        wide.set_fill_column(10);
+        wide.insert_str("this line is too wide").unwrap();
+        wide.execute_command(Command::CenterLine).unwrap();
+        assert_eq!(wide.editor.to_string(), "this line is too wide");
+        assert!(!wide.history.stack().can_undo());
+    }
 
-        let Some((start, previous_len)) = self.last_yank_range else {
-            self.show_info_message("ヤンク範囲が不明です");
-            self.reset_kill_context();
-            return;
-        };
+    #[test]
+    fn set_fill_column_from_cursor_column_is_per_buffer() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("hello world").unwrap();
+        app.editor.move_cursor_to_char(5).unwrap();
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::NONE);
+        assert_eq!(app.current_fill_column(), 5);
+        assert_eq!(app.fill_column(), DEFAULT_FILL_COLUMN);
+        assert_eq!(
+            displayed_message(&app),
+            Some((
+                false,
+                "fill-column を 5 に設定しました（以前は 70）".to_string()
+            ))
+        );
+
+        app.show_text_buffer(HELP_BUFFER_NAME, String::new())
+            .unwrap();
+        assert_eq!(app.current_fill_column(), DEFAULT_FILL_COLUMN);
+        app.switch_to_buffer_by_name("*scratch*").unwrap();
+        assert_eq!(app.current_fill_column(), 5);
+    }
 
-        if let Err(err) = self.editor.move_cursor_to_char(start) {
-            self.reset_kill_context();
-            self.show_error_message(err);
-            return;
-        }
+    #[test]
+    fn set_fill_column_from_cursor_uses_display_column() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("\t日本x").unwrap();
+        app.editor.move_cursor_to_char(3).unwrap();
+        app.execute_command(Command::SetFillColumn).unwrap();
+        assert_eq!(app.current_fill_column(), app.tab_width() + 4);
+    }
 
-        self.begin_history(HistoryCommandKind::Other);
-        if let Err(err) = self.editor.delete_range(start, start + previous_len) {
-            self.reset_kill_context();
-            self.show_error_message(err);
-            self.end_history(false);
-            return;
-        }
+    #[test]
+    fn set_fill_column_from_prefix_argument_or_prompt() {
+        let mut app = Backend::new().expect("app init");
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('3'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('0'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::NONE);
+        assert_eq!(app.current_fill_column(), 30);
+
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::NONE);
+        assert_eq!(app.minibuffer.current_input(), "30");
+        replace_minibuffer_input(&mut app, "12");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_fill_column(), 12);
+    }
 
-        let Some(next_text) = self.kill_ring.rotate() else {
-            self.reset_kill_context();
-            self.show_info_message("キルリングが空です");
-            return;
-        };
+    #[test]
+    fn goto_column_counts_tabs_and_wide_chars_by_display_width() {
+        let mut app = Backend::new().expect("app init");
+        app.set_tab_width(4);
+        app.insert_str("first\n\tあx").unwrap();
+
+        app.goto_column(5).unwrap();
+        assert_eq!(app.editor.cursor().line, 1);
+        assert_eq!(app.editor.cursor().column, 1);
+        app.goto_column(7).unwrap();
+        assert_eq!(app.editor.cursor().column, 2);
+        // 全角文字の途中の桁はその文字の後ろへ
+        app.goto_column(6).unwrap();
+        assert_eq!(app.editor.cursor().column, 2);
+        // タブの途中の桁はタブの後ろへ
+        app.goto_column(2).unwrap();
+        assert_eq!(app.editor.cursor().column, 1);
+        // 行の表示幅を超える桁は行末へ
+        app.goto_column(20).unwrap();
+        assert_eq!(app.editor.cursor().column, 3);
+        assert_eq!(app.editor.cursor().line, 1);
+        assert!(app.goto_column(0).is_err());
+    }
 
-        let new_len = next_text.chars().count();
-        if let Err(err) = self.editor.insert_str(&next_text) {
-            self.reset_kill_context();
-            self.show_error_message(err);
-            self.end_history(false);
-            return;
-        }
+    #[test]
+    fn goto_column_from_prompt_or_prefix_argument() {
+        let mut app = Backend::new().expect("app init");
+        app.set_tab_width(4);
+        app.insert_str("\tabc").unwrap();
+
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+        assert_eq!(app.minibuffer.current_input(), "8");
+        replace_minibuffer_input(&mut app, "6");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.cursor().column, 2);
+
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('1'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+        assert_eq!(app.editor.cursor().column, 0);
+    }
 
-        self.kill_context = KillContext::Yank;
-        self.last_yank_range = Some((start, new_len));
-        self.reset_recenter_cycle();
-        self.ensure_cursor_visible();
-        self.end_history(true);
+    #[test]
+    fn fill_paragraph_uses_buffer_fill_column() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("intro\n\naaa bbb ccc\nddd eee fff\n\nrest")
+            .unwrap();
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('8'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::NONE);
+        // "ccc" の "c" の前
+        app.editor.move_cursor_to_char(15).unwrap();
+
+        press(&mut app, KeyCode::Char('q'), KeyModifiers::ALT);
+        assert_eq!(
+            app.editor.to_string(),
+            "intro\n\naaa bbb\nccc ddd\neee fff\n\nrest"
+        );
+        assert_eq!(app.editor.cursor().char_pos, 15);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(
+            app.editor.to_string(),
+            "intro\n\naaa bbb ccc\nddd eee fff\n\nrest"
+        );
     }
 
-    fn undo_edit(&mut self) -> Result<()> {
-        match self.history.undo(&mut self.editor) {
-            Ok(true) => {
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                self.persist_current_buffer_state();
-                Ok(())
-            }
-            Ok(false) => {
-                self.show_info_message("取り消す操作はありません");
-                Ok(())
-            }
-            Err(err) => Err(err),
-        }
+    #[test]
+    fn universal_argument_repeats_char_as_one_undo_step() {
+        let mut app = Backend::new().expect("app init");
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('5'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "aaaaa");
+        assert_eq!(app.history.stack().undo_len(), 1);
+
+        press(&mut app, KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "aaaaab");
+
+        app.history.undo(&mut app.editor).unwrap();
+        assert_eq!(app.editor.to_string(), "aaaaa");
+        app.history.undo(&mut app.editor).unwrap();
+        assert_eq!(app.editor.to_string(), "");
     }
 
-    fn redo_edit(&mut self) -> Result<()> {
-        match self.history.redo(&mut self.editor) {
-            Ok(true) => {
-                self.reset_kill_context();
-                self.reset_recenter_cycle();
-                self.ensure_cursor_visible();
-                self.persist_current_buffer_state();
-                Ok(())
-            }
-            Ok(false) => {
-                self.show_info_message("やり直す操作はありません");
-                Ok(())
-            }
-            Err(err) => Err(err),
+    #[test]
+    fn universal_argument_without_digits_multiplies_by_four() {
+        let mut app = Backend::new().expect("app init");
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('*'), KeyModifiers::SHIFT);
+        assert_eq!(app.editor.to_string(), "*".repeat(16));
+
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), format!("{}x", "*".repeat(16)));
+    }
+
+    #[test]
+    fn universal_argument_huge_count_is_chunked_into_one_undo_step() {
+        let mut app = Backend::new().expect("app init");
+        app.set_large_insert_threshold(16);
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        let count = LARGE_INSERT_CHUNK_CHARS * 3;
+        for digit in count.to_string().chars() {
+            press(&mut app, KeyCode::Char(digit), KeyModifiers::NONE);
+        }
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::NONE);
+        assert!(app.is_inserting());
+
+        let mut ticks = 0;
+        while app.is_inserting() {
+            app.process_pending_insert();
+            ticks += 1;
         }
+
+        assert!(ticks > 1);
+        assert_eq!(app.editor.to_string(), "a".repeat(count));
+        assert_eq!(app.history.stack().undo_len(), 1);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "");
     }
 
-    fn keyboard_quit(&mut self) {
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        let mut replaced_session = false;
-        if self.replace.controller.is_active() {
-            self.begin_history(HistoryCommandKind::Other);
-            match self.replace.controller.cancel(&mut self.editor) {
-                Ok(summary) => {
-                    self.end_history(true);
-                    self.finish_replace_session(summary);
-                    replaced_session = true;
-                }
-                Err(err) => {
-                    self.end_history(false);
-                    self.show_error_message(err);
-                }
-            }
+    #[test]
+    fn universal_argument_over_limit_reports_error_without_inserting() {
+        let mut app = Backend::new().expect("app init");
+        for _ in 0..40 {
+            press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
         }
-        if self.search.is_active() {
-            self.search.cancel(&mut self.editor);
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "");
+        assert!(matches!(
+            displayed_message(&app),
+            Some((true, message)) if message.contains("数引数が大きすぎます")
+        ));
+
+        app.set_delete_selection(true);
+        app.insert_str("keep").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(4).unwrap();
+        press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        for digit in "99999999999999999999999".chars() {
+            press(&mut app, KeyCode::Char(digit), KeyModifiers::NONE);
         }
-        self.editor.clear_mark();
-        if !replaced_session {
-            self.show_info_message("キャンセルしました");
+        press(&mut app, KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "keep");
+    }
+
+    #[test]
+    fn occur_goto_occurrence_opens_source_in_other_window() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("one\nfoo two\nthree").unwrap();
+        run_with_input(&mut app, "occur", "foo");
+        let results_window = app.window_manager.focused_window();
+        let results_id = app.current_buffer_id.unwrap();
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        assert_eq!(app.window_manager.window_count(), 2);
+        assert_ne!(app.window_manager.focused_window(), results_window);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+        assert_eq!(app.editor.cursor().char_pos, 4);
+        assert_eq!(app.window_manager.buffer(results_window), Some(results_id));
+
+        app.handle_action(Action::FocusOtherWindow).unwrap();
+        assert_eq!(app.window_manager.focused_window(), results_window);
+        assert_eq!(
+            app.current_buffer_name().as_deref(),
+            Some(OCCUR_BUFFER_NAME)
+        );
+    }
+
+    #[test]
+    fn occur_goto_occurrence_can_replace_current_window() {
+        let mut app = Backend::new().expect("app init");
+        app.set_jump_in_other_window(false);
+        app.insert_str("one\nfoo two").unwrap();
+        run_with_input(&mut app, "occur", "foo");
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        assert_eq!(app.window_manager.window_count(), 1);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+        assert_eq!(app.editor.cursor().char_pos, 4);
+    }
+
+    fn app_with_mock_completion() -> (Backend, crate::editor::MockClock) {
+        let clock = crate::editor::MockClock::new();
+        let mut app = Backend::new().expect("app init");
+        app.auto_completion = AutoCompletion::with_clock(Box::new(clock.clone()));
+        (app, clock)
+    }
+
+    fn type_text(app: &mut Backend, text: &str) {
+        for ch in text.chars() {
+            app.handle_action(Action::InsertChar(ch)).unwrap();
         }
-        self.ensure_cursor_visible();
     }
 
-    fn reset_kill_context(&mut self) {
-        self.kill_context = KillContext::None;
-        self.last_yank_range = None;
+    #[test]
+    fn idle_completion_is_disabled_by_default() {
+        let (mut app, clock) = app_with_mock_completion();
+        app.insert_str("forward ").unwrap();
+        type_text(&mut app, "fo");
+
+        clock.advance(std::time::Duration::from_secs(1));
+        app.process_idle_timers();
+
+        assert!(!app.auto_completion_enabled());
+        assert!(app.render_metadata().completion_popup.is_none());
     }
 
-    fn begin_history(&mut self, kind: HistoryCommandKind) {
-        self.history.begin_command(kind, &self.editor);
+    #[test]
+    fn idle_completion_waits_for_pause_and_accepts_selection() {
+        let (mut app, clock) = app_with_mock_completion();
+        app.set_auto_completion_enabled(true);
+        app.insert_str("forward format ").unwrap();
+        type_text(&mut app, "fo");
+
+        app.process_idle_timers();
+        assert!(app.render_metadata().completion_popup.is_none());
+
+        clock.advance(app.auto_completion.delay());
+        app.process_idle_timers();
+        let popup = app.render_metadata().completion_popup.expect("popup shown");
+        assert_eq!(popup.candidates, vec!["format", "forward"]);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+
+        assert_eq!(app.editor.to_string(), "forward format forward");
+        assert!(app.render_metadata().completion_popup.is_none());
     }
 
-    fn end_history(&mut self, success: bool) {
-        self.history.end_command(&self.editor, success);
+    #[test]
+    fn idle_completion_is_dismissed_by_escape_and_suppressed_in_minibuffer() {
+        let (mut app, clock) = app_with_mock_completion();
+        app.set_auto_completion_enabled(true);
+        app.insert_str("forward ").unwrap();
+        type_text(&mut app, "fo");
+        clock.advance(app.auto_completion.delay());
+        app.process_idle_timers();
+        assert!(app.render_metadata().completion_popup.is_some());
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert!(app.render_metadata().completion_popup.is_none());
+        assert_eq!(app.editor.to_string(), "forward fo");
+
+        type_text(&mut app, "r");
+        app.handle_action(Action::ExecuteCommand).unwrap();
+        clock.advance(app.auto_completion.delay());
+        app.process_idle_timers();
+        assert!(app.minibuffer.is_active());
+        assert!(app.render_metadata().completion_popup.is_none());
     }
 
-    fn reset_recenter_cycle(&mut self) {
-        self.recenter_step = 0;
+    #[test]
+    fn overlapping_highlights_are_resolved_by_priority() {
+        let mut app = Backend::new().unwrap();
+        app.current_viewport_mut().update_dimensions(10, 80);
+        app.insert_str("abc\ndef").unwrap();
+        app.editor.move_cursor_to_char(1).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(6).unwrap();
+        app.editor
+            .overlays_mut()
+            .add(Overlay::new(2, 5, OverlayKind::Search));
+
+        let spans: Vec<(usize, usize, usize, HighlightKind)> = app
+            .render_metadata()
+            .highlights
+            .iter()
+            .map(|h| (h.line, h.start_column, h.end_column, h.kind))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 1, 2, HighlightKind::Selection),
+                (0, 2, 3, HighlightKind::Search),
+                (1, 0, 1, HighlightKind::Search),
+                (1, 1, 2, HighlightKind::Selection),
+            ]
+        );
     }
 
-    fn buffer_metrics(&self) -> (usize, usize) {
-        let content = self.editor.to_string();
-        if content.is_empty() {
-            return (1, 0);
-        }
+    fn press(app: &mut Backend, code: KeyCode, modifiers: KeyModifiers) {
+        app.handle_key_event(KeyEvent::new(code, modifiers))
+            .unwrap();
+    }
 
-        let mut lines = 0usize;
-        let mut max_columns = 0usize;
-        for line in content.lines() {
-            lines += 1;
-            let columns = line.chars().count();
-            if columns > max_columns {
-                max_columns = columns;
-            }
+    fn replace_minibuffer_input(app: &mut Backend, text: &str) {
+        for _ in 0..app.minibuffer.current_input().chars().count() {
+            press(app, KeyCode::Backspace, KeyModifiers::NONE);
+        }
+        for ch in text.chars() {
+            press(app, KeyCode::Char(ch), KeyModifiers::NONE);
         }
+    }
 
-        (lines.max(1), max_columns)
+    #[test]
+    fn repeat_complex_command_offers_last_find_file_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        std::fs::write(&first, "first").unwrap();
+        std::fs::write(&second, "second").unwrap();
+        let mut app = Backend::new().unwrap();
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char(':'), KeyModifiers::ALT);
+        assert!(app.minibuffer.is_message_displayed());
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+
+        app.handle_action(Action::FileOpen).unwrap();
+        replace_minibuffer_input(&mut app, first.to_str().unwrap());
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "first");
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char(':'), KeyModifiers::ALT);
+        assert!(app.minibuffer.is_active());
+        assert_eq!(app.minibuffer.current_input(), first.to_str().unwrap());
+        assert!(app.minibuffer.current_prompt().starts_with("Redo "));
+
+        replace_minibuffer_input(&mut app, second.to_str().unwrap());
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "second");
+        assert!(app.buffer_names().contains(&"first.txt".to_string()));
     }
 
-    fn selection_highlights(&self) -> Vec<SearchHighlight> {
-        let Some((start, end)) = self.editor.selection_range() else {
-            return Vec::new();
-        };
+    #[test]
+    fn virtual_end_of_line_allows_appending_at_line_end() {
+        let mut app = Backend::new().unwrap();
+        assert!(app.virtual_end_of_line());
+        app.insert_str("abc\nde").unwrap();
+        app.move_cursor_to_start().unwrap();
 
-        if start == end {
-            return Vec::new();
-        }
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineEnd))
+            .unwrap();
+        assert_eq!(app.editor.cursor().column, 3);
+        type_text(&mut app, "x");
+        assert_eq!(app.editor.to_string(), "abcx\nde");
 
-        let (start_line, start_col) = self.editor.position_to_line_column(start);
-        let (end_line, end_col) = self.editor.position_to_line_column(end);
-        let text = self.editor.to_string();
-        let lines: Vec<&str> = text.split('\n').collect();
-        let mut highlights = Vec::new();
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineDown))
+            .unwrap();
+        assert_eq!(app.editor.cursor().column, 2);
+    }
 
-        let push_highlight = |line: usize, s: usize, e: usize, list: &mut Vec<SearchHighlight>| {
-            if e > s {
-                list.push(SearchHighlight {
-                    line,
-                    start_column: s,
-                    end_column: e,
-                    is_current: false,
-                    kind: HighlightKind::Selection,
-                });
-            }
-        };
+    #[test]
+    fn without_virtual_end_of_line_point_clamps_to_last_char() {
+        let mut app = Backend::new().unwrap();
+        app.set_virtual_end_of_line(false);
+        app.insert_str("abc\nde\n").unwrap();
+        app.move_cursor_to_start().unwrap();
 
-        if start_line == end_line {
-            push_highlight(start_line, start_col, end_col, &mut highlights);
-            return highlights;
-        }
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineEnd))
+            .unwrap();
+        assert_eq!(app.editor.cursor().column, 2);
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineDown))
+            .unwrap();
+        assert_eq!(app.editor.cursor().line, 1);
+        assert_eq!(app.editor.cursor().column, 1);
+
+        // 最後の文字から C-f で次の行頭へ、C-b で前の行の最後の文字へ
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharForward))
+            .unwrap();
+        assert_eq!(app.editor.cursor().line, 2);
+        assert_eq!(app.editor.cursor().column, 0);
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharBackward))
+            .unwrap();
+        assert_eq!(app.editor.cursor().line, 1);
+        assert_eq!(app.editor.cursor().column, 1);
 
-        let first_line_len = lines
-            .get(start_line)
-            .map(|l| l.chars().count())
-            .unwrap_or(0);
-        push_highlight(start_line, start_col, first_line_len, &mut highlights);
+        app.move_cursor_to_start().unwrap();
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineEnd))
+            .unwrap();
+        type_text(&mut app, "x");
+        assert_eq!(app.editor.to_string(), "abxc\nde\n");
+    }
+
+    #[test]
+    fn without_virtual_end_of_line_forward_word_crosses_line_end() {
+        let mut app = Backend::new().unwrap();
+        app.set_virtual_end_of_line(false);
+        app.insert_str("foo bar\nbaz qux").unwrap();
+        app.move_cursor_to_start().unwrap();
 
-        for line in (start_line + 1)..end_line {
-            let len = lines.get(line).map(|l| l.chars().count()).unwrap_or(0);
-            push_highlight(line, 0, len, &mut highlights);
+        let mut stops = Vec::new();
+        for _ in 0..4 {
+            app.handle_action(Action::Navigate(NavigationAction::MoveWordForward))
+                .unwrap();
+            let cursor = app.editor.cursor();
+            stops.push((cursor.line, cursor.column));
         }
+        // 行末に達した M-f は次の行頭へ進み、同じ位置で止まり続けない
+        assert_eq!(stops, vec![(0, 3), (1, 0), (1, 3), (1, 6)]);
+    }
 
-        push_highlight(end_line, 0, end_col, &mut highlights);
+    #[test]
+    fn next_line_at_last_line_adds_newline_only_when_enabled() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("first\nlast").unwrap();
+        assert!(!app.next_line_add_newlines());
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineDown))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "first\nlast");
+
+        app.set_next_line_add_newlines(true);
+        app.editor.move_cursor_to_char(7).unwrap();
+        app.handle_action(Action::Navigate(NavigationAction::MoveLineDown))
+            .unwrap();
+        assert_eq!(app.editor.to_string(), "first\nlast\n");
+        assert_eq!(app.editor.cursor().line, 2);
+        assert_eq!(app.editor.cursor().column, 0);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "first\nlast");
+    }
 
-        highlights
+    #[test]
+    fn deactivated_mark_is_kept_for_exchange_but_not_highlighted() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("hello world").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.handle_action(Action::SetMark).unwrap();
+        app.handle_action(Action::Navigate(NavigationAction::MoveWordForward))
+            .unwrap();
+        assert!(app.editor.is_mark_active());
+        assert_eq!(app.render_metadata().highlights.len(), 1);
+
+        press_ctrl_g(&mut app);
+        assert!(!app.editor.is_mark_active());
+        assert!(app.render_metadata().highlights.is_empty());
+
+        app.handle_action(Action::CopyRegion).unwrap();
+        assert!(app.kill_ring.is_empty());
+
+        app.handle_action(Action::ExchangePointAndMark).unwrap();
+        assert_eq!(app.editor.cursor().char_pos, 0);
+        assert_eq!(app.editor.mark(), Some(5));
+        assert!(app.editor.is_mark_active());
+
+        app.handle_action(Action::CopyRegion).unwrap();
+        assert!(!app.editor.is_mark_active());
+        assert_eq!(app.editor.mark(), Some(5));
     }
 
-    fn status_line_data(&self) -> (String, bool) {
-        if let Some(buffer) = self.current_buffer() {
-            let label = if let Some(path) = buffer.path() {
-                path.display().to_string()
-            } else if buffer.name().trim().is_empty() {
-                "[未保存] *scratch*".to_string()
-            } else {
-                format!("[未保存] {}", buffer.name())
-            };
-            (label, buffer.is_modified())
-        } else {
-            ("[バッファなし]".to_string(), false)
+    #[test]
+    fn toggle_char_case_flips_and_advances() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("aBé").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        for _ in 0..3 {
+            app.handle_action(Action::ToggleCharCase).unwrap();
         }
+        assert_eq!(app.editor.to_string(), "AbÉ");
+        assert_eq!(app.editor.cursor().char_pos, 3);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "Abé");
     }
 
-    fn ensure_cursor_visible(&mut self) {
-        let (total_lines, max_columns) = self.buffer_metrics();
-        let cursor_line = self.editor.cursor().line;
-        let cursor_column = self.editor.cursor().column;
+    #[test]
+    fn capitalize_dwim_uses_region_or_next_word() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("hello big WORLD").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        app.handle_action(Action::CapitalizeDwim).unwrap();
+        assert_eq!(app.editor.to_string(), "Hello big WORLD");
+        assert_eq!(app.editor.cursor().char_pos, 5);
+
+        app.handle_action(Action::SetMark).unwrap();
+        app.editor.move_cursor_to_char(15).unwrap();
+        app.handle_action(Action::CapitalizeDwim).unwrap();
+        assert_eq!(app.editor.to_string(), "Hello Big World");
+        assert_eq!(app.editor.selection_range(), Some((5, 15)));
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "Hello big WORLD");
+    }
 
-        {
-            let viewport = self.current_viewport_mut();
-            viewport.clamp_vertical(total_lines);
+    #[test]
+    fn copy_whole_buffer_pushes_content_as_new_kill() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("line one\nline two").unwrap();
+        app.handle_action(Action::SetMark).unwrap();
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.handle_action(Action::KillRegion).unwrap();
+
+        app.handle_action(Action::CopyWholeBuffer).unwrap();
+        assert_eq!(app.kill_ring.front().map(String::as_str), Some("line"));
+        assert_eq!(app.kill_ring.len(), 2);
+        assert_eq!(app.editor.to_string(), "line");
+    }
 
-            let height = viewport.height.max(1);
-            if cursor_line < viewport.top_line {
-                viewport.top_line = cursor_line;
-            } else if cursor_line >= viewport.top_line + height {
-                viewport.top_line = cursor_line + 1 - height;
-            }
+    #[test]
+    fn copy_buffer_path_requires_file_backed_buffer() {
+        let mut app = Backend::new().unwrap();
+        app.handle_action(Action::CopyBufferPath).unwrap();
+        assert!(app.kill_ring.is_empty());
+        let (is_error, message) = displayed_message(&app).expect("info message");
+        assert!(!is_error);
+        assert!(message.contains("ファイルパスがありません"), "{message}");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "x").unwrap();
+        app.open_file(path.to_str().unwrap()).unwrap();
+        app.handle_action(Action::CopyBufferPath).unwrap();
+        let expected = path.display().to_string();
+        assert_eq!(app.kill_ring.front(), Some(&expected));
+    }
 
-            viewport.clamp_vertical(total_lines);
+    fn select_whole_buffer(app: &mut Backend) {
+        app.editor.move_cursor_to_char(0).unwrap();
+        app.handle_action(Action::SetMark).unwrap();
+        let end = app.editor.to_string().chars().count();
+        app.editor.move_cursor_to_char(end).unwrap();
+    }
 
-            if cursor_column < viewport.scroll_x {
-                viewport.scroll_x = cursor_column;
-            } else if cursor_column >= viewport.scroll_x + viewport.width {
-                viewport.scroll_x = cursor_column + 1 - viewport.width;
-            }
+    #[test]
+    fn tabify_converts_leading_whitespace_by_default() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("        a  b\n  \t  c      d").unwrap();
 
-            viewport.clamp_horizontal(max_columns);
-        }
+        select_whole_buffer(&mut app);
+        app.handle_action(Action::Tabify).unwrap();
+        assert_eq!(app.editor.to_string(), "\t\ta  b\n\t  c      d");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "        a  b\n  \t  c      d");
+
+        app.set_tabify_leading_only(false);
+        select_whole_buffer(&mut app);
+        app.handle_action(Action::Tabify).unwrap();
+        assert_eq!(app.editor.to_string(), "\t\ta  b\n\t  c\t\t d");
     }
 
-    fn move_cursor_vertical(&mut self, delta: isize) {
-        if delta > 0 {
-            for _ in 0..delta {
-                match self.editor.navigate(NavigationAction::MoveLineDown) {
-                    Ok(true) => {}
-                    _ => break,
-                }
-            }
-        } else {
-            for _ in 0..delta.unsigned_abs() {
-                match self.editor.navigate(NavigationAction::MoveLineUp) {
-                    Ok(true) => {}
-                    _ => break,
-                }
-            }
-        }
+    #[test]
+    fn tabify_keeps_region_when_buffer_is_read_only() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("        a").unwrap();
+        select_whole_buffer(&mut app);
+        app.editor.set_read_only(true);
+
+        app.handle_action(Action::Tabify).unwrap();
+        assert_eq!(app.editor.to_string(), "        a");
+        assert!(app.editor.mark().is_some());
+        assert!(matches!(displayed_message(&app), Some((true, _))));
     }
 
-    fn move_cursor_horizontal(&mut self, delta: isize) {
-        if delta > 0 {
-            for _ in 0..delta {
-                match self.editor.navigate(NavigationAction::MoveCharForward) {
-                    Ok(true) => {}
-                    _ => break,
-                }
-            }
-        } else {
-            for _ in 0..delta.unsigned_abs() {
-                match self.editor.navigate(NavigationAction::MoveCharBackward) {
-                    Ok(true) => {}
-                    _ => break,
-                }
-            }
-        }
+    #[test]
+    fn untabify_expands_tabs_in_region_with_tab_width() {
+        let mut app = Backend::new().unwrap();
+        app.insert_str("\tx\n a\tb\n\tkeep").unwrap();
+        app.set_tab_width(8);
+
+        // リージョンは 2 行目の途中から始まっても行頭から桁を数える
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.handle_action(Action::SetMark).unwrap();
+        app.editor.move_cursor_to_char(8).unwrap();
+        app.handle_action(Action::Untabify).unwrap();
+        assert_eq!(app.editor.to_string(), "\tx\n a      b\n\tkeep");
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "\tx\n a\tb\n\tkeep");
     }
 
-    fn scroll_page_down(&mut self) {
-        let (total_lines, _) = self.buffer_metrics();
-        let height = self.current_viewport().height.max(1);
-        let step = height.saturating_sub(1).max(1);
-        let old_top = self.current_viewport().top_line;
-        let max_top = total_lines.saturating_sub(height);
-        let new_top = (old_top + step).min(max_top);
-        let delta = new_top.saturating_sub(old_top);
-        {
-            let viewport = self.current_viewport_mut();
-            viewport.top_line = new_top;
-        }
-        if delta > 0 {
-            self.move_cursor_vertical(delta as isize);
-        }
-        self.reset_recenter_cycle();
-        self.reset_kill_context();
-        self.ensure_cursor_visible();
+    #[test]
+    fn isearch_counter_follows_incremental_input() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("abc abd abx").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        let counter = |app: &Backend| {
+            app.render_metadata()
+                .search_ui
+                .and_then(|search| search.match_counter())
+        };
+
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(counter(&app), None);
+
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(counter(&app).as_deref(), Some("1/3"));
+
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(counter(&app).as_deref(), Some("2/3"));
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(counter(&app).as_deref(), Some("1/1"));
+
+        press(&mut app, KeyCode::Char('z'), KeyModifiers::NONE);
+        assert_eq!(counter(&app).as_deref(), Some("0/0 failing"));
+
+        press(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(counter(&app).as_deref(), Some("1/1"));
     }
 
-    fn scroll_page_up(&mut self) {
-        let height = self.current_viewport().height.max(1);
-        let step = height.saturating_sub(1).max(1);
-        let old_top = self.current_viewport().top_line;
-        let new_top = old_top.saturating_sub(step);
-        let delta = old_top.saturating_sub(new_top);
-        {
-            let viewport = self.current_viewport_mut();
-            viewport.top_line = new_top;
-        }
-        if delta > 0 {
-            self.move_cursor_vertical(-(delta as isize));
-        }
-        self.reset_recenter_cycle();
-        self.reset_kill_context();
-        self.ensure_cursor_visible();
+    #[test]
+    fn highlights_are_limited_to_visible_lines() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("a0\na1\na2\na3\na4\na5").unwrap();
+        app.current_viewport_mut().update_dimensions(2, 80);
+        app.current_viewport_mut().top_line = 2;
+        app.editor
+            .overlays_mut()
+            .add(Overlay::new(0, 17, OverlayKind::Search));
+        let lines: Vec<usize> = app
+            .render_metadata()
+            .highlights
+            .iter()
+            .map(|highlight| highlight.line)
+            .collect();
+        assert_eq!(lines, vec![2, 3]);
     }
 
-    fn recenter_view(&mut self) {
-        let (total_lines, _) = self.buffer_metrics();
-        let height = self.current_viewport().height.max(1);
-        let cursor_line = self.editor.cursor().line;
-        let max_top = total_lines.saturating_sub(height);
+    #[test]
+    fn keyword_highlights_cover_only_visible_lines() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str(&"TODO\n".repeat(6)).unwrap();
+        app.current_viewport_mut().update_dimensions(2, 80);
+        app.current_viewport_mut().top_line = 2;
+        app.execute_command(Command::ToggleKeywordHighlight)
+            .unwrap();
+        let lines: Vec<usize> = app
+            .render_metadata()
+            .highlights
+            .iter()
+            .map(|highlight| highlight.line)
+            .collect();
+        assert_eq!(lines, vec![2, 3]);
+    }
 
-        let desired_top = match self.recenter_step % 3 {
-            0 => cursor_line.saturating_sub(height / 2),
-            1 => cursor_line,
-            _ => cursor_line.saturating_add(1).saturating_sub(height),
-        };
+    #[test]
+    fn editor_overlays_follow_their_buffer() {
+        let mut app = Backend::new().expect("app init");
+        app.current_viewport_mut().update_dimensions(10, 80);
+        app.insert_str("scratch text").unwrap();
+        app.editor
+            .overlays_mut()
+            .add(Overlay::new(0, 7, OverlayKind::Search));
+
+        app.show_text_buffer(HELP_BUFFER_NAME, "other text".to_string())
+            .unwrap();
+        assert!(app.editor.overlays().is_empty());
+        assert!(app.render_metadata().highlights.is_empty());
+
+        app.switch_to_buffer_by_name("*scratch*").unwrap();
+        assert!(!app.editor.overlays().is_empty());
+        assert_eq!(app.render_metadata().highlights.len(), 1);
+    }
 
-        {
-            let viewport = self.current_viewport_mut();
-            viewport.top_line = desired_top.min(max_top);
-        }
-        self.recenter_step = (self.recenter_step + 1) % 3;
-        self.reset_kill_context();
-        self.ensure_cursor_visible();
+    #[test]
+    fn keyword_highlights_are_added_when_enabled() {
+        let mut app = Backend::new().expect("app init");
+        app.current_viewport_mut().update_dimensions(10, 80);
+        app.insert_str("x TODO y\nFIXME NOTE").unwrap();
+        let spans = |app: &Backend| -> Vec<(usize, usize, usize, HighlightKind)> {
+            app.render_metadata()
+                .highlights
+                .iter()
+                .map(|h| (h.line, h.start_column, h.end_column, h.kind))
+                .collect()
+        };
+        assert!(spans(&app).is_empty());
+
+        app.execute_command(Command::ToggleKeywordHighlight)
+            .unwrap();
+        assert!(app.keyword_highlighter().is_enabled());
+        assert_eq!(
+            spans(&app),
+            vec![
+                (0, 2, 6, HighlightKind::Keyword),
+                (1, 0, 5, HighlightKind::Keyword),
+                (1, 6, 10, HighlightKind::Keyword),
+            ]
+        );
+
+        app.keyword_highlighter_mut().set_keywords(["NOTE"]);
+        app.editor.move_cursor_to_char(10).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(17).unwrap();
+        assert_eq!(
+            spans(&app),
+            vec![
+                (1, 1, 8, HighlightKind::Selection),
+                (1, 8, 10, HighlightKind::Keyword),
+            ]
+        );
     }
 
-    fn horizontal_scroll_step(&self) -> usize {
-        (self.current_viewport().width / 2).max(1)
+    #[test]
+    fn newline_inside_alisp_string_is_not_indented() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = open_alisp_buffer(&dir, "(message \"ab\")");
+        app.editor.move_cursor_to_char(11).unwrap();
+        app.handle_action(Action::NewlineAndIndent).unwrap();
+        assert_eq!(app.editor.to_string(), "(message \"a\nb\")");
+
+        app.handle_action(Action::IndentForTab).unwrap();
+        assert_eq!(app.editor.to_string(), "(message \"a\nb\")");
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveBufferEnd))
+            .unwrap();
+        app.handle_action(Action::DeleteChar(
+            crate::input::keybinding::DeleteDirection::Backward,
+        ))
+        .unwrap();
+        app.handle_action(Action::NewlineAndIndent).unwrap();
+        assert_eq!(app.editor.to_string(), "(message \"a\nb\"\n         ");
     }
 
-    fn scroll_left(&mut self) {
-        let step = self.horizontal_scroll_step();
-        {
-            let viewport = self.current_viewport_mut();
-            viewport.scroll_x = viewport.scroll_x.saturating_add(step);
-        }
-        self.move_cursor_horizontal(step as isize);
-        self.reset_recenter_cycle();
-        self.reset_kill_context();
-        self.ensure_cursor_visible();
+    #[test]
+    fn newline_inside_alisp_comment_follows_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = open_alisp_buffer(&dir, "(when ok ; note");
+        app.handle_action(Action::Navigate(NavigationAction::MoveBufferEnd))
+            .unwrap();
+        app.handle_action(Action::NewlineAndIndent).unwrap();
+        assert_eq!(app.editor.to_string(), "(when ok ; note\n");
+
+        app.handle_action(Action::Undo).unwrap();
+        app.set_alisp_indent_in_literals(true);
+        app.handle_action(Action::NewlineAndIndent).unwrap();
+        assert_eq!(app.editor.to_string(), "(when ok ; note\n  ");
     }
 
-    fn scroll_right(&mut self) {
-        let step = self.horizontal_scroll_step();
-        let current_scroll = self.current_viewport().scroll_x;
-        if current_scroll > 0 {
-            let delta = current_scroll.min(step);
-            {
-                let viewport = self.current_viewport_mut();
-                viewport.scroll_x -= delta;
-            }
-            self.move_cursor_horizontal(-(delta as isize));
-        }
-        self.reset_recenter_cycle();
-        self.reset_kill_context();
-        self.ensure_cursor_visible();
+    #[test]
+    fn goto_line_beyond_last_line_reports_reached_line() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("a\nb\nc").unwrap();
+
+        app.goto_line(2).unwrap();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "2 行目へ移動".to_string()))
+        );
+
+        app.goto_line(100).unwrap();
+        assert_eq!(app.editor.cursor().line, 2);
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "3 行目へ移動".to_string()))
+        );
     }
 
-    fn start_find_file_prompt(&mut self) -> Result<()> {
-        // カレントディレクトリを取得
-        let current_dir = env::current_dir()
-            .map(|p| p.display().to_string())
-            .unwrap_or_else(|_| "~/".to_string());
-
-        // ディレクトリパスに末尾スラッシュを追加
-        let initial_path = if current_dir.ends_with('/') {
-            current_dir
-        } else {
-            format!("{}/", current_dir)
-        };
+    #[test]
+    fn scroll_line_to_top_keeps_point_when_visible() {
+        let mut app = app_with_lines(100, 20);
+        app.goto_line(36).unwrap();
 
-        // ミニバッファでファイル検索を開始
-        match self.minibuffer.start_find_file(Some(&initial_path)) {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+        app.scroll_line_to_top(31);
+        assert_eq!(app.current_viewport().top_line, 30);
+        assert_eq!(app.editor.cursor().line, 35);
+
+        app.scroll_line_to_top(95);
+        assert_eq!(app.current_viewport().top_line, 80);
+        assert_eq!(app.editor.cursor().line, 80);
+
+        app.scroll_line_to_top(1);
+        assert_eq!(app.current_viewport().top_line, 0);
+        assert_eq!(app.editor.cursor().line, 19);
     }
 
-    fn start_execute_command_prompt(&mut self) -> Result<()> {
-        match self.minibuffer.start_execute_command() {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+    #[test]
+    fn scroll_line_to_top_prompts_with_current_line() {
+        let mut app = app_with_lines(100, 20);
+        app.goto_line(8).unwrap();
+
+        app.execute_command(Command::ScrollLineToTop).unwrap();
+        assert_eq!(app.minibuffer.current_input(), "8");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_viewport().top_line, 7);
+
+        app.execute_command(Command::ScrollLineToTop).unwrap();
+        replace_minibuffer_input(&mut app, "40");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_viewport().top_line, 39);
+        assert_eq!(app.editor.cursor().line, 39);
     }
 
-    fn start_eval_expression_prompt(&mut self) -> Result<()> {
-        match self.minibuffer.start_eval_expression() {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+    #[test]
+    fn mg_c_goes_to_char_position() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("abc\ndef").unwrap();
+
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::NONE);
+        assert_eq!(app.minibuffer.current_input(), "8");
+        replace_minibuffer_input(&mut app, "6");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.cursor().char_pos, 5);
+        assert_eq!(app.editor.cursor().line, 1);
+
+        app.goto_char(100).unwrap();
+        assert_eq!(app.editor.cursor().char_pos, 7);
+        assert!(app.goto_char(0).is_err());
+
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "エラー位置を含むバッファがありません".to_string()))
+        );
     }
 
-    fn start_query_replace_prompt(&mut self, is_regex: bool) -> Result<()> {
-        let mut initial_pattern: Option<String> = None;
+    fn open_nested_file(dir: &tempfile::TempDir) -> (Backend, PathBuf) {
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        let path = nested.join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+        (app, nested)
+    }
 
-        if self.search.is_active() {
-            if let Some(pattern) = self.search.current_pattern() {
-                if !pattern.is_empty() {
-                    initial_pattern = Some(pattern.to_string());
-                }
+    #[test]
+    fn find_file_and_write_file_start_in_buffer_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+
+        app.execute_command(Command::FindFile).unwrap();
+        assert_eq!(
+            app.minibuffer.current_input(),
+            format!("{}/", nested.display())
+        );
+        press_ctrl_g(&mut app);
+
+        app.execute_command(Command::WriteFile).unwrap();
+        assert_eq!(
+            app.minibuffer.current_input(),
+            nested.join("notes.txt").display().to_string()
+        );
+    }
+
+    #[test]
+    fn cd_changes_default_directory_of_current_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+        let parent = nested.parent().unwrap().to_path_buf();
+
+        app.execute_command(Command::CdBuffer).unwrap();
+        replace_minibuffer_input(&mut app, "..");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.default_directory(), Some(parent.clone()));
+
+        app.execute_command(Command::FindFile).unwrap();
+        assert_eq!(
+            app.minibuffer.current_input(),
+            format!("{}/", parent.display())
+        );
+        press_ctrl_g(&mut app);
+
+        assert!(app.change_default_directory("notes.txt").is_err());
+        assert!(app.change_default_directory("missing").is_err());
+        assert_eq!(app.default_directory(), Some(parent));
+    }
+
+    fn displayed_message(app: &Backend) -> Option<(bool, String)> {
+        match &app.minibuffer.minibuffer_state().mode {
+            crate::minibuffer::MinibufferMode::InfoDisplay { message, .. } => {
+                Some((false, message.clone()))
             }
-            self.search.accept();
-        } else if let Some(pattern) = self.search.last_pattern() {
-            if !pattern.is_empty() {
-                initial_pattern = Some(pattern.to_string());
+            crate::minibuffer::MinibufferMode::ErrorDisplay { message, .. } => {
+                Some((true, message.clone()))
             }
+            _ => None,
         }
+    }
 
-        if initial_pattern.is_none() {
-            if let Ok(Some(selection)) = self.editor.selection_text() {
-                if !selection.is_empty() {
-                    initial_pattern = Some(selection);
-                }
-            }
-        }
+    #[test]
+    fn silent_save_hides_success_but_keeps_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+        app.set_save_message_mode(SaveMessageMode::Silent);
+
+        type_text(&mut app, "x");
+        let before = displayed_message(&app);
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert_eq!(displayed_message(&app), before);
+        assert_eq!(
+            std::fs::read_to_string(nested.join("notes.txt")).unwrap(),
+            "xhello"
+        );
+
+        std::fs::remove_dir_all(&nested).unwrap();
+        type_text(&mut app, "y");
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert!(matches!(displayed_message(&app), Some((true, _))));
+    }
 
-        let action = MinibufferAction::QueryReplace {
-            is_regex,
-            initial: initial_pattern,
+    #[test]
+    fn save_message_format_uses_written_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, _nested) = open_nested_file(&dir);
+        app.set_save_message_mode(SaveMessageMode::Format("Wrote {name}".to_string()));
+
+        type_text(&mut app, "x");
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "Wrote notes.txt".to_string()))
+        );
+
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "変更なし".to_string()))
+        );
+    }
+
+    #[test]
+    fn export_html_writes_highlighted_file_next_to_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("init.al");
+        std::fs::write(&path, "(define a \"<x>\")").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+
+        app.execute_command(Command::ExportHtml).unwrap();
+        let exported = dir.path().join("init.al.html");
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(!is_error);
+        assert!(message.contains("init.al.html"));
+        let html = std::fs::read_to_string(&exported).unwrap();
+        assert!(html.contains("<title>init.al</title>"));
+        assert!(html.contains("\">define</span> a <span style=\""));
+        assert!(html.contains("&quot;&lt;x&gt;&quot;</span>)</pre>"));
+    }
+
+    #[test]
+    fn export_html_uses_active_theme_and_reports_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("init.al");
+        std::fs::write(&path, "(define a 1)").unwrap();
+        let exported = dir.path().join("init.al.html");
+        std::fs::write(&exported, "old").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+        assert!(!app.set_theme_name("no-such-theme"));
+        assert!(app.set_theme_name("light"));
+        assert_eq!(app.render_metadata().theme_name, "light");
+
+        app.execute_command(Command::ExportHtml).unwrap();
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(!is_error);
+        assert!(message.starts_with("既存の HTML を上書きしました"));
+        let html = std::fs::read_to_string(&exported).unwrap();
+        // light テーマの特殊形式の色
+        assert!(html.contains("<span style=\"color:#0000ee;font-weight:bold\">define</span>"));
+    }
+
+    #[test]
+    fn save_buffer_skips_write_when_disk_already_matches() {
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+        let path = nested.join("notes.txt");
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let set_old_mtime = || {
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
         };
+        let mtime = || std::fs::metadata(&path).unwrap().modified().unwrap();
+
+        type_text(&mut app, "x");
+        std::fs::write(&path, "xhello").unwrap();
+        set_old_mtime();
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "変更なし".to_string()))
+        );
+        assert_eq!(mtime(), old);
+        assert!(!app.current_buffer().unwrap().is_modified());
+
+        type_text(&mut app, "y");
+        app.execute_command(Command::SaveBuffer).unwrap();
+        assert_ne!(mtime(), old);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "xyhello");
+    }
 
-        match self.minibuffer.handle_event(SystemEvent::Action(action)) {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
+    #[test]
+    fn other_window_same_buffer_skips_windows_on_other_buffers() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+        let shared = app.current_buffer_id.unwrap();
+        app.execute_command(Command::SplitWindowBelow).unwrap();
+        app.execute_command(Command::SplitWindowBelow).unwrap();
+
+        let other_path = nested.join("other.txt");
+        std::fs::write(&other_path, "other").unwrap();
+        assert_eq!(app.window_manager.window_count(), 3);
+        app.execute_command(Command::OtherWindow).unwrap();
+        app.execute_command(Command::OtherWindow).unwrap();
+        let other_window = app.window_manager.focused_window();
+        app.open_file(other_path.to_str().unwrap()).unwrap();
+        assert_ne!(app.current_buffer_id, Some(shared));
+        app.execute_command(Command::OtherWindow).unwrap();
+        assert_eq!(app.current_buffer_id, Some(shared));
+
+        for _ in 0..4 {
+            app.execute_command(Command::OtherWindowSameBuffer).unwrap();
+            assert_ne!(app.window_manager.focused_window(), other_window);
+            assert_eq!(app.current_buffer_id, Some(shared));
         }
     }
 
-    fn start_save_as_prompt(&mut self, suggested_name: &str) -> Result<()> {
-        let initial_path = env::current_dir()
-            .map(|dir| dir.join(suggested_name))
-            .unwrap_or_else(|_| std::path::PathBuf::from(suggested_name.to_string()));
+    #[test]
+    fn swap_window_buffers_exchanges_buffers_between_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, nested) = open_nested_file(&dir);
+        let first = app.current_buffer_id.unwrap();
+        app.execute_command(Command::SwapWindowBuffers).unwrap();
+        assert_eq!(app.current_buffer_id, Some(first));
+
+        app.execute_command(Command::SplitWindowBelow).unwrap();
+        let top = app.window_manager.focused_window();
+        let other_path = nested.join("other.txt");
+        std::fs::write(&other_path, "other").unwrap();
+        app.execute_command(Command::OtherWindow).unwrap();
+        let bottom = app.window_manager.focused_window();
+        app.open_file(other_path.to_str().unwrap()).unwrap();
+        let second = app.current_buffer_id.unwrap();
+        app.execute_command(Command::OtherWindow).unwrap();
+        assert_eq!(app.window_manager.focused_window(), top);
+
+        app.execute_command(Command::SwapWindowBuffers).unwrap();
+        assert_eq!(app.window_manager.focused_window(), top);
+        assert_eq!(app.window_manager.buffer(top), Some(second));
+        assert_eq!(app.window_manager.buffer(bottom), Some(first));
+        assert_eq!(app.current_buffer_id, Some(second));
+        assert_eq!(app.editor.to_string(), "other");
+
+        app.execute_command(Command::OtherWindow).unwrap();
+        assert_eq!(app.current_buffer_id, Some(first));
+    }
 
-        let initial_string = initial_path.display().to_string();
+    #[test]
+    fn open_file_with_line_suffix_jumps_to_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        let content: Vec<String> = (1..=20).map(|n| format!("line {}", n)).collect();
+        std::fs::write(&path, content.join("\n")).unwrap();
+        let path = path.to_str().unwrap().to_string();
+        let mut app = Backend::new().expect("app init");
 
-        match self
-            .minibuffer
-            .start_write_file(Some(initial_string.as_str()))
-        {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+        app.open_file(&format!("{}:10", path)).unwrap();
+        assert_eq!(app.current_buffer().unwrap().name(), "file.txt");
+        assert_eq!(
+            (app.editor.cursor().line, app.editor.cursor().column),
+            (9, 0)
+        );
+
+        app.open_file(&format!("{}:3:4", path)).unwrap();
+        assert_eq!(
+            (app.editor.cursor().line, app.editor.cursor().column),
+            (2, 3)
+        );
+
+        app.open_file(&format!("{}:999:999", path)).unwrap();
+        assert_eq!(
+            (app.editor.cursor().line, app.editor.cursor().column),
+            (19, 7)
+        );
+        assert_eq!(app.buffers.len(), 2);
     }
 
-    fn start_goto_line_prompt(&mut self) -> Result<()> {
-        let current_line = self.editor.cursor().line + 1;
-        let total_lines = self.total_line_count();
+    #[test]
+    fn open_file_keeps_existing_colon_filename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("build:12");
+        std::fs::write(&path, "a\nb").unwrap();
+        let mut app = Backend::new().expect("app init");
 
-        match self.minibuffer.start_goto_line(current_line, total_lines) {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファの初期化に失敗しました: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+        app.open_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(app.current_buffer().unwrap().name(), "build:12");
+        assert_eq!(app.editor.to_string(), "a\nb");
+        assert_eq!(app.editor.cursor().line, 0);
     }
 
-    fn handle_minibuffer_key(&mut self, key_event: KeyEvent) -> Result<()> {
-        let key: Key = key_event.into();
+    #[test]
+    fn revert_with_coding_system_redecodes_garbled_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("utf16.txt");
+        let utf16: Vec<u8> = "hello\nworld"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        std::fs::write(&path, &utf16).unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+        assert!(app.editor.to_string().contains('\0'));
+
+        app.execute_command(Command::RevertBufferWithCodingSystem)
+            .unwrap();
+        replace_minibuffer_input(&mut app, "utf-16le");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "hello\nworld");
+        assert_eq!(
+            app.current_buffer().unwrap().file.coding,
+            CodingSystem::Utf16Le
+        );
+
+        type_text(&mut app, "!");
+        app.execute_command(Command::SaveBuffer).unwrap();
+        let expected: Vec<u8> = "!hello\nworld"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+
+        assert!(app.revert_buffer_with_coding_system("euc-jp").is_err());
+        type_text(&mut app, "?");
+        assert!(app.revert_buffer_with_coding_system("utf-8").is_err());
+    }
 
-        match self.minibuffer.handle_event(SystemEvent::KeyInput(key)) {
-            Ok(SystemResponse::FileOperation(file_op)) => {
-                use crate::minibuffer::FileOperation;
-                match file_op {
-                    FileOperation::Open(path) => {
-                        debug_log!(self, "Opening file via minibuffer: {}", path);
-                        match self.open_file_at_path(&path) {
-                            Ok(message) => self.show_info_message(message),
-                            Err(err) => self.show_error_message(err),
-                        }
-                    }
-                    FileOperation::SaveAs(path) => {
-                        self.persist_current_buffer_state();
-                        if let Some(index) = self.current_buffer_index() {
-                            if let Some(current) = self.buffers.get(index) {
-                                self.command_processor
-                                    .set_current_buffer(current.file.clone());
-                            }
-                            self.command_processor
-                                .sync_editor_content(&self.editor.to_string());
-                            let result = self.command_processor.save_buffer_as(path.clone());
-                            if result.success {
-                                if let Some(updated) =
-                                    self.command_processor.current_buffer().cloned()
-                                {
-                                    if let Some(buffer) = self.buffers.get_mut(index) {
-                                        buffer.file = updated;
-                                        buffer.cursor = *self.editor.cursor();
-                                        buffer.history = self.history.stack().clone();
-                                    }
-                                }
-                                if let Some(msg) = result.message {
-                                    self.show_info_message(msg);
-                                }
-                                self.ensure_cursor_visible();
-                            } else if let Some(msg) = result.message {
-                                self.show_error_message(AltreError::Application(msg));
-                            }
-                        }
-                    }
-                    _ => {
-                        self.show_info_message("未実装のファイル操作です");
-                    }
-                }
-                Ok(())
-            }
-            Ok(SystemResponse::ExecuteCommand(cmd)) => {
-                if cmd == "goto-line" {
-                    self.start_goto_line_prompt()
-                } else {
-                    self.show_info_message(format!("コマンド実行: {}", cmd));
-                    Ok(())
-                }
-            }
-            Ok(SystemResponse::SwitchBuffer(name)) => {
-                let target = if name.trim().is_empty() {
-                    self.last_buffer_name()
-                } else {
-                    Some(name)
-                };
+    #[test]
+    fn shift_jis_file_is_detected_and_redecoded_after_garbling() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sjis.txt");
+        // "日本語\nあ" の Shift_JIS
+        let sjis = [0x93, 0xFA, 0x96, 0x7B, 0x8C, 0xEA, 0x0A, 0x82, 0xA0];
+        std::fs::write(&path, sjis).unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.open_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(app.editor.to_string(), "日本語\nあ");
+        assert_eq!(app.render_metadata().coding_label, "Shift_JIS/LF");
+
+        app.revert_buffer_with_coding_system("latin-1").unwrap();
+        assert_ne!(app.editor.to_string(), "日本語\nあ");
+        app.revert_buffer_with_coding_system("shift_jis").unwrap();
+        assert_eq!(app.editor.to_string(), "日本語\nあ");
+
+        type_text(&mut app, "い");
+        app.execute_command(Command::SaveBuffer).unwrap();
+        let mut expected = vec![0x82, 0xA2];
+        expected.extend_from_slice(&sjis);
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+    }
 
-                if let Some(buffer_name) = target {
-                    if let Err(err) = self.switch_to_buffer_by_name(&buffer_name) {
-                        self.show_error_message(err);
-                    }
-                } else {
-                    self.show_error_message(AltreError::Application(
-                        "切り替えるバッファが見つかりません".to_string(),
-                    ));
-                }
-                Ok(())
-            }
-            Ok(SystemResponse::KillBuffer(name)) => {
-                let trimmed = name.trim();
-                let target = if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed)
-                };
-                if let Err(err) = self.kill_buffer_by_name(target) {
-                    self.show_error_message(err);
-                }
-                Ok(())
-            }
-            Ok(SystemResponse::ListBuffers) => {
-                self.show_buffer_list();
-                Ok(())
-            }
-            Ok(SystemResponse::GotoLine(line)) => {
-                if let Err(err) = self.goto_line(line) {
-                    self.show_error_message(err);
-                }
-                Ok(())
-            }
-            Ok(SystemResponse::QueryReplace {
-                pattern,
-                replacement,
-                is_regex,
-            }) => {
-                self.start_query_replace_session(pattern, replacement, is_regex)?;
-                Ok(())
-            }
-            Ok(SystemResponse::Quit) => {
-                self.shutdown();
-                Ok(())
-            }
-            Ok(SystemResponse::Continue) | Ok(SystemResponse::None) => {
-                // 継続または何もしない
-                Ok(())
-            }
-            Err(err) => {
-                self.show_error_message(AltreError::Application(format!(
-                    "ミニバッファエラー: {}",
-                    err
-                )));
-                Ok(())
-            }
-        }
+    #[test]
+    fn status_info_reports_coding_and_line_ending() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("latin1.txt");
+        std::fs::write(&path, "cafe\r\nnaive\r\n").unwrap();
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(app.render_metadata().coding_label, "UTF-8/LF");
+
+        app.open_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(app.render_metadata().coding_label, "UTF-8/CRLF");
+
+        std::fs::write(&path, b"caf\xe9\r\nna\xefve\r\n").unwrap();
+        app.revert_buffer_with_coding_system("latin-1").unwrap();
+        assert_eq!(app.editor.to_string(), "café\nnaïve\n");
+        assert_eq!(app.render_metadata().coding_label, "Latin-1/CRLF");
+    }
+
+    fn paren_spans(app: &Backend) -> Vec<(usize, usize, usize)> {
+        app.render_metadata()
+            .highlights
+            .iter()
+            .filter(|h| h.kind == HighlightKind::MatchingParen)
+            .map(|h| (h.line, h.start_column, h.end_column))
+            .collect()
     }
 
-    fn navigate(&mut self, action: NavigationAction) {
-        self.reset_kill_context();
-        self.reset_recenter_cycle();
-        match self.editor.navigate(action) {
-            Ok(true) => {
-                self.ensure_cursor_visible();
-            }
-            Ok(false) => self.show_info_message("これ以上移動できません"),
-            Err(err) => self.show_error_message(err.into()),
-        }
+    #[test]
+    fn matching_paren_flashes_after_typing_closer() {
+        let mut app = Backend::new().expect("app init");
+        type_text(&mut app, "(a [b]");
+        assert_eq!(paren_spans(&app), vec![(0, 3, 4), (0, 5, 6)]);
+
+        type_text(&mut app, " c");
+        assert!(paren_spans(&app).is_empty());
+
+        type_text(&mut app, ")");
+        assert_eq!(paren_spans(&app), vec![(0, 0, 1), (0, 8, 9)]);
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharBackward))
+            .unwrap();
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharForward))
+            .unwrap();
+        assert!(paren_spans(&app).is_empty());
     }
 
-    fn split_window(&mut self, orientation: SplitOrientation) {
-        self.window_manager.split_focused(orientation);
-        self.ensure_cursor_visible();
+    #[test]
+    fn matching_paren_follows_cursor_in_always_mode() {
+        let mut app = Backend::new().expect("app init");
+        app.current_viewport_mut().update_dimensions(10, 80);
+        app.insert_str(
+            "(a)
+{b}",
+        )
+        .unwrap();
+        app.paren_highlighter_mut()
+            .set_mode(ParenHighlightMode::Always);
+        assert_eq!(paren_spans(&app), vec![(1, 0, 1), (1, 2, 3)]);
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveBufferStart))
+            .unwrap();
+        assert_eq!(paren_spans(&app), vec![(0, 0, 1), (0, 2, 3)]);
+
+        app.handle_action(Action::Navigate(NavigationAction::MoveCharForward))
+            .unwrap();
+        assert!(paren_spans(&app).is_empty());
+
+        app.paren_highlighter_mut()
+            .set_mode(ParenHighlightMode::Off);
+        type_text(&mut app, ")");
+        assert!(paren_spans(&app).is_empty());
     }
 
-    fn delete_other_windows(&mut self) {
-        match self.window_manager.delete_others() {
-            Ok(()) => {
-                self.ensure_cursor_visible();
-            }
-            Err(err) => {
-                self.show_error_message(AltreError::Application(err.to_string()));
-            }
+    fn app_with_greeting_command() -> Backend {
+        let mut registry = CommandRegistry::new();
+        registry
+            .register("insert-greeting", "挨拶を挿入", |app: &mut Backend| {
+                app.insert_str("hello")
+            })
+            .unwrap();
+        Backend::with_command_registry(registry).expect("app init")
+    }
+
+    #[test]
+    fn registered_command_runs_from_execute_command() {
+        let mut app = app_with_greeting_command();
+        assert!(app
+            .minibuffer
+            .available_commands()
+            .contains(&"insert-greeting".to_string()));
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::ALT);
+        replace_minibuffer_input(&mut app, "insert-greeting");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "hello");
+
+        app.register_command("fail-always", "", |_: &mut Backend| {
+            Err(AltreError::Application("失敗しました".to_string()))
+        })
+        .unwrap();
+        app.execute_command(Command::from_string("fail-always"))
+            .unwrap();
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(is_error);
+        assert!(message.contains("失敗しました"));
+        assert!(app.execute_command_by_name("no-such-command").is_err());
+        assert!(app
+            .register_command("save-buffer", "", |_: &mut Backend| Ok(()))
+            .is_err());
+    }
+
+    #[test]
+    fn registered_command_can_be_bound_with_bind_key() {
+        let mut app = app_with_greeting_command();
+        let mut interpreter = Interpreter::new();
+        interpreter.runtime_mut().set_host(Box::new(KeymapHost::new(
+            Rc::clone(&app.keymap),
+            Rc::clone(&app.gui_theme),
+            Rc::clone(&app.command_registry),
+            Rc::clone(&app.options),
+        )));
+        interpreter
+            .eval("(bind-key \"C-x j\" \"insert-greeting\")")
+            .unwrap();
+        assert!(interpreter
+            .eval("(bind-key \"C-x y\" \"unregistered-command\")")
+            .is_err());
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "hello");
+    }
+
+    #[test]
+    fn bind_key_over_existing_binding_is_reported() {
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(displayed_message(&app), None);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.runtime_mut().set_host(Box::new(KeymapHost::new(
+            Rc::clone(&app.keymap),
+            Rc::clone(&app.gui_theme),
+            Rc::clone(&app.command_registry),
+            Rc::clone(&app.options),
+        )));
+        interpreter
+            .eval("(bind-key \"C-f\" \"forward-char\")")
+            .unwrap();
+        interpreter
+            .eval("(bind-key \"C-d\" \"forward-char\")")
+            .unwrap();
+        app.report_keybinding_conflicts();
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "既存のキー割り当てを上書きしました: C-d".to_string()))
+        );
+
+        // 上書き後の割り当てが有効
+        app.insert_str("ab").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(app.editor.to_string(), "ab");
+        assert_eq!(app.editor.cursor().char_pos, 1);
+    }
+
+    #[test]
+    fn meta_r_toggles_regex_during_isearch() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo fo fooo").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        for ch in "fo+".chars() {
+            press(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
         }
+        assert_eq!(app.search.ui_state().unwrap().total_matches, 0);
+
+        press(&mut app, KeyCode::Char('r'), KeyModifiers::ALT);
+        assert!(app.search.is_active());
+        let ui = app.search.ui_state().unwrap();
+        assert_eq!(ui.total_matches, 3);
+        assert!(ui.regex);
+
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(!app.search.is_regex());
+        assert_eq!(app.search.last_regex_pattern(), Some("fo+"));
+        assert_eq!(app.search.last_pattern(), None);
     }
 
-    fn delete_current_window(&mut self) {
-        match self.window_manager.delete_focused() {
-            Ok(()) => {
-                self.ensure_cursor_visible();
+    #[test]
+    fn meta_p_and_meta_n_recall_search_history_during_isearch() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("one two three").unwrap();
+        for pattern in ["one", "two"] {
+            app.editor.move_cursor_to_char(0).unwrap();
+            press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+            while app.search.current_pattern().is_some() {
+                press(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
             }
-            Err(err) => {
-                self.show_error_message(AltreError::Application(err.to_string()));
+            for ch in pattern.chars() {
+                press(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
             }
+            press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
         }
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(app.search.current_pattern(), Some("two"));
+        press(&mut app, KeyCode::Char('p'), KeyModifiers::ALT);
+        assert_eq!(app.search.current_pattern(), Some("one"));
+        assert_eq!(app.editor.cursor().char_pos, 0);
+        press(&mut app, KeyCode::Char('n'), KeyModifiers::ALT);
+        assert_eq!(app.search.current_pattern(), Some("two"));
+        assert_eq!(app.editor.cursor().char_pos, 4);
+        assert!(app.search.is_active());
     }
 
-    fn focus_next_window(&mut self) {
-        self.window_manager.focus_next();
-        self.ensure_cursor_visible();
+    #[test]
+    fn toggle_lazy_highlight_limits_search_highlights_to_current_match() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("x x x x").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        app.execute_command(Command::ToggleLazyHighlight).unwrap();
+        assert!(!app.isearch_lazy_highlight());
+        assert_eq!(
+            displayed_message(&app),
+            Some((
+                false,
+                "検索中は現在の一致だけをハイライトします".to_string()
+            ))
+        );
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(app.search.highlights().len(), 1);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        app.set_isearch_lazy_highlight(true);
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(app.search.highlights().len(), 4);
     }
 
-    pub fn process_minibuffer_timer(&mut self) {
-        if let Err(err) = self.minibuffer.handle_event(SystemEvent::Update) {
-            eprintln!("minibuffer update error: {}", err);
-        }
+    #[test]
+    fn meta_c_toggles_case_sensitivity_during_isearch() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("Ab ab").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(app.search.ui_state().unwrap().total_matches, 2);
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::ALT);
+        let ui = app.search.ui_state().unwrap();
+        assert!(ui.case_sensitive);
+        assert_eq!(ui.total_matches, 1);
+        assert!(app.search.is_active());
     }
 
-    fn show_info_message<S: Into<String>>(&mut self, message: S) {
-        if let Err(err) = self.minibuffer.show_info(message.into()) {
-            eprintln!("minibuffer info error: {}", err);
+    #[test]
+    fn control_meta_s_starts_regex_isearch() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("x1 y22 z3").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        press(
+            &mut app,
+            KeyCode::Char('s'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        );
+        assert!(app.search.is_regex());
+        for ch in "[0-9]+".chars() {
+            press(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
         }
+        let ui = app.search.ui_state().unwrap();
+        assert_eq!(ui.prompt_label, "I-search regexp");
+        assert_eq!(ui.total_matches, 3);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.editor.cursor().char_pos, 1);
+
+        press(
+            &mut app,
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        );
+        let ui = app.search.ui_state().unwrap();
+        assert!(ui.regex);
+        assert_eq!(ui.direction, SearchDirection::Backward);
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+
+        app.execute_command(Command::IsearchForwardRegexp).unwrap();
+        assert!(app.search.is_regex());
     }
 
-    fn show_error_message(&mut self, error: AltreError) {
-        if let Err(mini_err) = self.minibuffer.show_error(error.to_string()) {
-            eprintln!("minibuffer error: {}", mini_err);
-        }
+    fn run_with_input(app: &mut Backend, command: &str, pattern: &str) -> Option<(bool, String)> {
+        press(app, KeyCode::Char('x'), KeyModifiers::ALT);
+        replace_minibuffer_input(app, command);
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+        replace_minibuffer_input(app, pattern);
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+        displayed_message(app)
     }
 
-    /// キーイベントを人間が読みやすい形式に変換
-    fn format_key_event(key_event: &KeyEvent) -> String {
-        let mut parts = Vec::new();
+    #[test]
+    fn how_many_counts_from_point_without_moving() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo bar fooo\nfo.o").unwrap();
+        app.editor.move_cursor_to_char(2).unwrap();
+
+        assert_eq!(
+            run_with_input(&mut app, "how-many", "fo+"),
+            Some((false, "2 件の一致（バッファ全体では 3 件）".to_string()))
+        );
+        assert_eq!(app.editor.cursor().char_pos, 2);
+
+        assert_eq!(
+            run_with_input(&mut app, "how-many-literal", "fo."),
+            Some((false, "1 件の一致".to_string()))
+        );
+        assert_eq!(
+            run_with_input(&mut app, "count-matches", "fo."),
+            Some((false, "2 件の一致（バッファ全体では 3 件）".to_string()))
+        );
+
+        let (is_error, message) = run_with_input(&mut app, "how-many", "(").unwrap();
+        assert!(is_error);
+        assert!(message.contains("正規表現が不正です"));
+        assert_eq!(app.editor.cursor().char_pos, 2);
+    }
 
-        // 修飾キーを追加（Shiftは特殊文字以外では通常表示しない）
-        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-            parts.push("C");
-        }
-        if key_event.modifiers.contains(KeyModifiers::ALT) {
-            parts.push("M");
-        }
+    #[test]
+    fn occur_lists_matches_and_jumps_to_selected_one() {
+        let mut app = Backend::new().expect("app init");
+        app.set_jump_in_other_window(false);
+        app.insert_str("foo bar\nbaz\n  foo foo").unwrap();
+        app.editor.move_cursor_to_char(0).unwrap();
+
+        assert_eq!(
+            run_with_input(&mut app, "occur", "foo"),
+            Some((false, "3 件の一致".to_string()))
+        );
+        assert_eq!(
+            app.current_buffer_name().as_deref(),
+            Some(OCCUR_BUFFER_NAME)
+        );
+        assert_eq!(
+            app.editor.to_string(),
+            "3 件の一致: \"foo\"（*scratch*）\n1:1: foo bar\n3:3:   foo foo\n3:7:   foo foo"
+        );
+        assert!(app.editor.is_read_only());
+        assert_eq!(app.buffer_names(), vec!["*scratch*", OCCUR_BUFFER_NAME]);
+
+        app.editor.move_cursor_to_char(0).unwrap();
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+        assert_eq!(app.editor.cursor().char_pos, 18);
+        assert_eq!(app.editor.to_string(), "foo bar\nbaz\n  foo foo");
+    }
 
-        // 基本キーを追加
-        let key_name = match key_event.code {
-            KeyCode::Char(c) => {
-                if c.is_ascii_control() {
-                    // 制御文字の場合
-                    format!("C-{}", (c as u8 + b'A' - 1) as char)
-                } else if c.is_uppercase() && key_event.modifiers.contains(KeyModifiers::SHIFT) {
-                    // 大文字のShift表示
-                    format!("S-{}", c.to_lowercase())
-                } else {
-                    // 通常の文字
-                    c.to_string()
-                }
-            }
-            KeyCode::F(n) => format!("F{}", n),
-            KeyCode::Enter => "RET".to_string(),
-            KeyCode::Left => "左".to_string(),
-            KeyCode::Right => "右".to_string(),
-            KeyCode::Up => "上".to_string(),
-            KeyCode::Down => "下".to_string(),
-            KeyCode::Home => "Home".to_string(),
-            KeyCode::End => "End".to_string(),
-            KeyCode::PageUp => "PageUp".to_string(),
-            KeyCode::PageDown => "PageDown".to_string(),
-            KeyCode::Tab => "TAB".to_string(),
-            KeyCode::BackTab => "S-TAB".to_string(),
-            KeyCode::Delete => "DEL".to_string(),
-            KeyCode::Insert => "INS".to_string(),
-            KeyCode::Esc => "ESC".to_string(),
-            KeyCode::Backspace => "BS".to_string(),
-            KeyCode::CapsLock => "CapsLock".to_string(),
-            KeyCode::ScrollLock => "ScrollLock".to_string(),
-            KeyCode::NumLock => "NumLock".to_string(),
-            KeyCode::PrintScreen => "PrintScreen".to_string(),
-            KeyCode::Pause => "Pause".to_string(),
-            KeyCode::Menu => "Menu".to_string(),
-            KeyCode::KeypadBegin => "Keypad-Begin".to_string(),
-            _ => format!("未知のキー"),
+    #[test]
+    fn occur_refresh_recomputes_from_source_buffer() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("foo\nbar").unwrap();
+        run_with_input(&mut app, "occur", "foo");
+        assert_eq!(app.editor.to_string().lines().count(), 2);
+
+        app.switch_to_buffer_by_name("*scratch*").unwrap();
+        app.editor.move_cursor_to_char(7).unwrap();
+        app.insert_str("\nfoo again").unwrap();
+        app.switch_to_buffer_by_name(OCCUR_BUFFER_NAME).unwrap();
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(
+            app.editor.to_string(),
+            "2 件の一致: \"foo\"（*scratch*）\n1:1: foo\n3:1: foo again"
+        );
+        assert!(app.editor.is_read_only());
+
+        app.switch_to_buffer_by_name("*scratch*").unwrap();
+        assert_eq!(
+            run_with_input(&mut app, "occur", "nothing"),
+            Some((false, "\"nothing\" に一致する箇所はありません".to_string()))
+        );
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+    }
+
+    #[test]
+    fn flush_and_keep_lines_filter_from_point_as_one_undo_step() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("keep\nfoo 1\nbar\nfoo 22\nbaz\n").unwrap();
+        app.editor.move_cursor_to_char(7).unwrap();
+
+        assert_eq!(
+            run_with_input(&mut app, "flush-lines", "^foo"),
+            Some((false, "2 行を削除しました".to_string()))
+        );
+        assert_eq!(app.editor.to_string(), "keep\nbar\nbaz\n");
+        assert_eq!(app.editor.cursor().char_pos, 5);
+
+        app.handle_action(Action::Undo).unwrap();
+        assert_eq!(app.editor.to_string(), "keep\nfoo 1\nbar\nfoo 22\nbaz\n");
+
+        app.editor.move_cursor_to_char(5).unwrap();
+        run_with_input(&mut app, "keep-lines", "\\d{2}|ba");
+        assert_eq!(app.editor.to_string(), "keep\nbar\nfoo 22\nbaz\n");
+    }
+
+    #[test]
+    fn keep_lines_limits_to_region() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("a1\nb2\na3\nb4\n").unwrap();
+        app.editor.move_cursor_to_char(4).unwrap();
+        app.editor.set_mark();
+        app.editor.move_cursor_to_char(9).unwrap();
+
+        assert_eq!(
+            run_with_input(&mut app, "keep-lines", "^a"),
+            Some((false, "1 行を削除しました".to_string()))
+        );
+        assert_eq!(app.editor.to_string(), "a1\na3\nb4\n");
+        assert!(app.editor.selection_range().is_none());
+
+        assert_eq!(
+            run_with_input(&mut app, "flush-lines", "zzz"),
+            Some((false, "削除する行はありません".to_string()))
+        );
+    }
+
+    #[test]
+    fn distraction_free_hides_mode_line_and_optionally_minibuffer() {
+        let mut app = Backend::new().expect("app init");
+        let chrome = |app: &Backend| {
+            let metadata = app.render_metadata();
+            (metadata.mode_line_visible, metadata.minibuffer_visible)
         };
+        assert_eq!(chrome(&app), (true, true));
 
-        if parts.is_empty() {
-            key_name
-        } else {
-            format!("{}-{}", parts.join("-"), key_name)
+        app.execute_command(Command::ToggleDistractionFree).unwrap();
+        assert!(app.distraction_free());
+        assert_eq!(chrome(&app), (false, true));
+
+        app.set_distraction_free_hides_minibuffer(true);
+        assert_eq!(chrome(&app), (false, false));
+
+        app.execute_command(Command::ToggleDistractionFree).unwrap();
+        assert_eq!(chrome(&app), (true, true));
+    }
+
+    fn find_file_via_minibuffer(app: &mut Backend, path: &Path) {
+        app.execute_command(Command::FindFile).unwrap();
+        replace_minibuffer_input(app, &path.display().to_string());
+        press(app, KeyCode::Enter, KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn reopen_file_switches_to_existing_buffer_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "disk").unwrap();
+        let mut app = Backend::new().expect("app init");
+        assert_eq!(app.reopen_file_behavior(), ReopenFileBehavior::Switch);
+
+        find_file_via_minibuffer(&mut app, &path);
+        type_text(&mut app, "edited ");
+        std::fs::write(&path, "changed on disk").unwrap();
+        find_file_via_minibuffer(&mut app, &dir.path().join("other.txt"));
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.editor.to_string(), "edited disk");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn different_paths_to_same_file_share_one_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("real")).unwrap();
+        let path = dir.path().join("real").join("notes.txt");
+        std::fs::write(&path, "disk").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("alias")).unwrap();
+        std::os::unix::fs::symlink(&path, dir.path().join("link.txt")).unwrap();
+        let mut app = Backend::new().expect("app init");
+
+        find_file_via_minibuffer(&mut app, &path);
+        let buffers = app.buffers.len();
+        find_file_via_minibuffer(&mut app, &dir.path().join("other.txt"));
+
+        for alias in [
+            dir.path().join("alias").join("notes.txt"),
+            dir.path().join("link.txt"),
+            dir.path().join("real").join(".").join("notes.txt"),
+        ] {
+            find_file_via_minibuffer(&mut app, &alias);
+            assert_eq!(app.buffers.len(), buffers + 1);
+            assert_eq!(app.current_buffer_name().as_deref(), Some("notes.txt"));
         }
     }
-}
 
-struct KeymapHost {
-    keymap: Rc<RefCell<ModernKeyMap>>,
-    gui_theme: Rc<RefCell<GuiThemeConfig>>,
-}
+    #[test]
+    fn reopen_file_in_warn_mode_switches_and_warns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "disk").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.set_reopen_file_behavior(ReopenFileBehavior::Warn);
+
+        find_file_via_minibuffer(&mut app, &path);
+        type_text(&mut app, "edited ");
+        find_file_via_minibuffer(&mut app, &dir.path().join("other.txt"));
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("notes.txt"));
+        assert_eq!(app.editor.to_string(), "edited disk");
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(is_error);
+        assert!(message.contains("既に開いているファイルです"));
+    }
 
-impl KeymapHost {
-    fn new(keymap: Rc<RefCell<ModernKeyMap>>, gui_theme: Rc<RefCell<GuiThemeConfig>>) -> Self {
-        Self { keymap, gui_theme }
+    #[test]
+    fn reopen_file_in_reload_mode_confirms_before_discarding_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "disk").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.set_reopen_file_behavior(ReopenFileBehavior::Reload);
+
+        find_file_via_minibuffer(&mut app, &path);
+        type_text(&mut app, "edited ");
+        std::fs::write(&path, "changed on disk").unwrap();
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert_eq!(
+            app.minibuffer.current_prompt(),
+            "未保存の変更を破棄してファイルを読み直しますか？ (y/n) "
+        );
+        press(&mut app, KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "edited disk");
+
+        find_file_via_minibuffer(&mut app, &path);
+        press(&mut app, KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "changed on disk");
+        assert!(!app.current_buffer().unwrap().is_modified());
+        assert!(!app.history.stack().can_undo());
+
+        // 変更がなければ確認せずに読み直す
+        std::fs::write(&path, "third").unwrap();
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.editor.to_string(), "third");
     }
-}
 
-impl HostBridge for KeymapHost {
-    fn bind_key(
-        &mut self,
-        key_sequence: &str,
-        command_name: &str,
-    ) -> std::result::Result<(), String> {
-        let command = Command::from_string(command_name);
-        match command {
-            Command::Unknown(_) => Err(format!("未知のコマンドです: {}", command_name)),
-            other => {
-                let mut keymap = self.keymap.borrow_mut();
-                keymap
-                    .bind_command_sequence(key_sequence, &other)
-                    .map_err(|err| err.to_string())
-            }
+    #[test]
+    fn reopen_file_in_reload_mode_keeps_unchanged_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "disk").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.set_reopen_file_behavior(ReopenFileBehavior::Reload);
+
+        find_file_via_minibuffer(&mut app, &path);
+        type_text(&mut app, "x");
+        app.execute_command(Command::SaveBuffer).unwrap();
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.editor.to_string(), "xdisk");
+        assert_eq!(app.editor.cursor().char_pos, 1);
+        assert!(app.history.stack().can_undo());
+        assert!(matches!(
+            displayed_message(&app),
+            Some((false, message)) if message.starts_with("ファイルは変更されていません")
+        ));
+    }
+
+    #[test]
+    fn find_file_with_wildcard_opens_all_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.rs", "a.rs", "notes.txt"] {
+            std::fs::write(dir.path().join(name), name).unwrap();
         }
+        let mut app = Backend::new().expect("app init");
+        // 確認を有効にしていてもワイルドカードは新規ファイル扱いしない
+        app.set_confirm_new_file(true);
+
+        find_file_via_minibuffer(&mut app, &dir.path().join("*.rs"));
+        assert!(app.pending_confirmation.is_none());
+        let names = app.buffer_names();
+        assert!(names.contains(&"a.rs".to_string()));
+        assert!(names.contains(&"b.rs".to_string()));
+        assert!(!names.contains(&"notes.txt".to_string()));
+        assert_eq!(app.current_buffer_name().as_deref(), Some("a.rs"));
+        assert_eq!(app.editor.to_string(), "a.rs");
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(!is_error);
+        assert!(message.starts_with("2 個のファイルを開きました"));
     }
 
-    fn set_gui_color(&mut self, component: &str, color: &str) -> std::result::Result<(), String> {
-        let key = GuiThemeKey::from_str(component)
-            .ok_or_else(|| format!("未知のGUIカラーキーです: {}", component))?;
-        let mut theme = self.gui_theme.borrow_mut();
-        theme.set_color(key, color)
+    #[test]
+    fn find_file_with_unmatched_wildcard_opens_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "").unwrap();
+        let mut app = Backend::new().expect("app init");
+        let buffers = app.buffers.len();
+
+        find_file_via_minibuffer(&mut app, &dir.path().join("*.md"));
+        assert_eq!(app.buffers.len(), buffers);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*scratch*"));
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(!is_error);
+        assert!(message.starts_with("一致するファイルがありません"));
+
+        // 展開を無効にすると入力どおりの名前のファイルを開く
+        app.set_find_file_wildcards(false);
+        find_file_via_minibuffer(&mut app, &dir.path().join("*.md"));
+        assert_eq!(app.buffers.len(), buffers + 1);
+        assert_eq!(app.current_buffer_name().as_deref(), Some("*.md"));
     }
-}
 
-impl Default for Backend {
-    fn default() -> Self {
-        Self::new().expect("アプリケーションの初期化に失敗しました")
+    #[cfg(unix)]
+    fn open_write_protected_file(app: &mut Backend, dir: &Path) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("locked.txt");
+        std::fs::write(&path, "abc").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o444)).unwrap();
+        find_file_via_minibuffer(app, &path);
+        path
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn toggle_read_only_blocks_and_allows_edits() {
+        let mut app = Backend::new().expect("app init");
+        app.insert_str("abc").unwrap();
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert!(app.editor.is_read_only());
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "読み取り専用にしました".to_string()))
+        );
+        press(&mut app, KeyCode::Char('z'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "abc");
+
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        assert!(!app.editor.is_read_only());
+        press(&mut app, KeyCode::Char('z'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "abcz");
+    }
 
+    #[cfg(unix)]
     #[test]
-    fn kill_line_removes_text_without_messages() {
+    fn toggle_read_only_offers_chmod_for_write_protected_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
         let mut app = Backend::new().expect("app init");
-        app.insert_str("hello\nworld").unwrap();
-        app.move_cursor_to_start().unwrap();
+        app.set_chmod_on_toggle_read_only(true);
+        let path = open_write_protected_file(&mut app, dir.path());
+        assert!(app.editor.is_read_only());
+
+        // 別のバッファを経由しても読み取り専用のまま
+        app.switch_buffer("*scratch*").unwrap();
+        assert!(!app.editor.is_read_only());
+        app.switch_buffer("locked.txt").unwrap();
+        assert!(app.editor.is_read_only());
+
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        assert_eq!(
+            app.minibuffer.current_prompt(),
+            format!(
+                "{} は書き込み禁止です。書き込み可能にしますか？ (y/n) ",
+                path.display()
+            )
+        );
+        press(&mut app, KeyCode::Char('y'), KeyModifiers::NONE);
+        assert!(!app.editor.is_read_only());
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(!is_error);
+        assert!(message.starts_with("ファイルを書き込み可能にしました"));
+        press(&mut app, KeyCode::Char('z'), KeyModifiers::NONE);
+        assert_eq!(app.editor.to_string(), "zabc");
+    }
 
-        app.handle_action(Action::KillLine).unwrap();
+    #[cfg(unix)]
+    #[test]
+    fn toggle_read_only_keeps_buffer_read_only_when_chmod_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.set_chmod_on_toggle_read_only(true);
+        let path = open_write_protected_file(&mut app, dir.path());
+
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        // 確認中にファイルが消えると権限を変更できない
+        std::fs::remove_file(&path).unwrap();
+        press(&mut app, KeyCode::Char('y'), KeyModifiers::NONE);
+        assert!(app.editor.is_read_only());
+        let (is_error, message) = displayed_message(&app).unwrap();
+        assert!(is_error);
+        assert!(message.contains("ファイルの権限を変更できないため読み取り専用のままにします"));
+    }
 
-        assert_eq!(app.editor.to_string(), "world");
-        let viewport = app
-            .window_manager
-            .focused_viewport()
-            .expect("focused viewport");
-        assert_eq!(viewport.top_line, 0);
-        assert_eq!(viewport.scroll_x, 0);
+    #[cfg(unix)]
+    #[test]
+    fn toggle_read_only_without_chmod_leaves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = Backend::new().expect("app init");
+        let path = open_write_protected_file(&mut app, dir.path());
+
+        // 既定では確認せずバッファだけ書き込み可能にする
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        assert!(app.pending_confirmation.is_none());
+        assert!(!app.editor.is_read_only());
+
+        // 確認で n と答えた場合も権限は変えない
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        app.set_chmod_on_toggle_read_only(true);
+        app.execute_command(Command::ToggleReadOnly).unwrap();
+        press(&mut app, KeyCode::Char('n'), KeyModifiers::NONE);
+        assert!(!app.editor.is_read_only());
+        assert_eq!(
+            displayed_message(&app),
+            Some((
+                false,
+                "ファイルの権限は変えずに読み取り専用を解除しました".to_string()
+            ))
+        );
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o444);
+    }
+
+    #[test]
+    fn confirm_new_file_declined_creates_no_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("typo.txt");
+        let mut app = Backend::new().expect("app init");
+        app.set_confirm_new_file(true);
+        let buffers = app.buffers.len();
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert_eq!(
+            app.minibuffer.current_prompt(),
+            "ファイルが存在しません。作成しますか？ (y/n) "
+        );
+        // y/n 以外は受け付けずに確認を続ける
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert!(app.minibuffer.is_active());
+
+        press(&mut app, KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(app.buffers.len(), buffers);
+        assert!(app.find_buffer_id_by_path(&path).is_none());
+        assert_eq!(
+            displayed_message(&app),
+            Some((false, "ファイルの作成を取り消しました".to_string()))
+        );
+    }
+
+    #[test]
+    fn confirm_new_file_cancelled_with_ctrl_g_discards_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("typo.txt");
+        let mut app = Backend::new().expect("app init");
+        app.set_confirm_new_file(true);
+        let buffers = app.buffers.len();
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.pending_confirmation.is_some());
+        press(&mut app, KeyCode::Char('g'), KeyModifiers::CONTROL);
+        assert!(!app.minibuffer.is_active());
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.buffers.len(), buffers);
+        assert!(app.find_buffer_id_by_path(&path).is_none());
+    }
+
+    #[test]
+    fn confirm_new_file_accepted_creates_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.txt");
+        let existing = dir.path().join("existing.txt");
+        std::fs::write(&existing, "hello").unwrap();
+        let mut app = Backend::new().expect("app init");
+        app.set_confirm_new_file(true);
+
+        // 存在するファイルは確認せずに開く
+        find_file_via_minibuffer(&mut app, &existing);
+        assert!(app.find_buffer_id_by_path(&existing).is_some());
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.find_buffer_id_by_path(&path).is_none());
+        press(&mut app, KeyCode::Char('y'), KeyModifiers::NONE);
+        assert!(app.find_buffer_id_by_path(&path).is_some());
+        assert_eq!(app.editor.to_string(), "");
+    }
+
+    #[test]
+    fn new_file_is_created_without_confirmation_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.txt");
+        let mut app = Backend::new().expect("app init");
+
+        find_file_via_minibuffer(&mut app, &path);
+        assert!(app.find_buffer_id_by_path(&path).is_some());
     }
 }
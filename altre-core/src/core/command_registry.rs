@@ -0,0 +1,132 @@
+//! 外部から登録するコマンド
+//!
+//! 組み込みの `Command` にない名前のコマンドを、埋め込み側のクレートがクロージャとして登録する。
+//! 登録したコマンドは M-x から名前で実行でき、`bind-key` でキーにも割り当てられる
+
+use crate::core::Backend;
+use crate::error::{AltreError, Result};
+use crate::input::commands::Command;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// 登録コマンドの本体
+pub type CommandHandler = Box<dyn FnMut(&mut Backend) -> Result<()>>;
+
+struct RegisteredCommand {
+    description: String,
+    handler: Rc<RefCell<CommandHandler>>,
+}
+
+/// 名前付きの登録コマンドの集合
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, RegisteredCommand>,
+}
+
+impl CommandRegistry {
+    /// 空の集合を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コマンドを登録（同名の登録は置き換える）
+    ///
+    /// 空の名前と組み込みコマンドの名前は登録できない。
+    pub fn register<F>(
+        &mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut Backend) -> Result<()> + 'static,
+    {
+        let name = name.into();
+        if name.trim().is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(AltreError::Application(format!(
+                "コマンド名が不正です: '{}'",
+                name
+            )));
+        }
+        if !matches!(Command::from_string(&name), Command::Unknown(_)) {
+            return Err(AltreError::Application(format!(
+                "組み込みコマンドと同じ名前は登録できません: {}",
+                name
+            )));
+        }
+        self.commands.insert(
+            name,
+            RegisteredCommand {
+                description: description.into(),
+                handler: Rc::new(RefCell::new(Box::new(handler))),
+            },
+        );
+        Ok(())
+    }
+
+    /// 登録を解除
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    /// 登録済みか
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// 登録済みのコマンド名（名前順）
+    pub fn names(&self) -> Vec<String> {
+        self.commands.keys().cloned().collect()
+    }
+
+    /// コマンドの説明
+    pub fn description(&self, name: &str) -> Option<&str> {
+        self.commands
+            .get(name)
+            .map(|command| command.description.as_str())
+    }
+
+    /// 実行用にコマンド本体を取得（実行中も登録の変更を妨げない）
+    pub(crate) fn handler(&self, name: &str) -> Option<Rc<RefCell<CommandHandler>>> {
+        self.commands
+            .get(name)
+            .map(|command| Rc::clone(&command.handler))
+    }
+}
+
+impl fmt::Debug for CommandRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandRegistry")
+            .field("commands", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_builtin_and_malformed_names() {
+        let mut registry = CommandRegistry::new();
+        assert!(registry.register("forward-char", "", |_| Ok(())).is_err());
+        assert!(registry.register("", "", |_| Ok(())).is_err());
+        assert!(registry.register("two words", "", |_| Ok(())).is_err());
+        assert!(registry.names().is_empty());
+    }
+
+    #[test]
+    fn registers_and_unregisters_by_name() {
+        let mut registry = CommandRegistry::new();
+        registry.register("my-command", "説明", |_| Ok(())).unwrap();
+        assert!(registry.contains("my-command"));
+        assert_eq!(registry.description("my-command"), Some("説明"));
+        assert_eq!(registry.names(), vec!["my-command".to_string()]);
+
+        assert!(registry.unregister("my-command"));
+        assert!(!registry.unregister("my-command"));
+        assert!(registry.handler("my-command").is_none());
+    }
+}
@@ -1,3 +1,10 @@
 pub mod backend;
+pub mod command_registry;
+pub mod options;
+pub mod session;
 
-pub use backend::{Backend, RenderMetadata, RenderView};
+pub use backend::{
+    Backend, RenderMetadata, RenderView, ReopenFileBehavior, SaveMessageMode, TabBehavior,
+};
+pub use command_registry::{CommandHandler, CommandRegistry};
+pub use options::EditorOptions;
@@ -0,0 +1,63 @@
+//! init.al の `set-option` で変更する設定
+//!
+//! 設定名と値の型を確かめてから反映する。未知の設定名や型の違いはエラーにする
+
+use crate::alisp::OptionValue;
+
+/// `set-option` で変更できる設定
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorOptions {
+    /// 終了時にセッションを保存し、次回起動時に復元する
+    pub auto_save_session: bool,
+    /// 起動時に前回のセッションを復元する（終了時には保存しない）
+    pub restore_session: bool,
+}
+
+impl EditorOptions {
+    /// 既定の設定を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定名（`auto-save-session` など）の値を変更
+    pub fn set(&mut self, name: &str, value: &OptionValue) -> Result<(), String> {
+        let target = match name {
+            "auto-save-session" => &mut self.auto_save_session,
+            "restore-session" => &mut self.restore_session,
+            _ => return Err(format!("未知の設定です: {}", name)),
+        };
+        match value {
+            OptionValue::Boolean(enabled) => {
+                *target = *enabled;
+                Ok(())
+            }
+            _ => Err(format!("設定 {} には #t か #f を指定してください", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_known_boolean_options_and_rejects_others() {
+        let mut options = EditorOptions::new();
+        options
+            .set("auto-save-session", &OptionValue::Boolean(true))
+            .unwrap();
+        options
+            .set("restore-session", &OptionValue::Boolean(true))
+            .unwrap();
+        assert!(options.auto_save_session);
+        assert!(options.restore_session);
+
+        assert!(options
+            .set("no-such-option", &OptionValue::Boolean(true))
+            .is_err());
+        assert!(options
+            .set("auto-save-session", &OptionValue::Integer(1))
+            .is_err());
+        assert!(options.auto_save_session);
+    }
+}
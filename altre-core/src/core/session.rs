@@ -0,0 +1,109 @@
+//! セッション保存・復元
+//!
+//! 開いているファイル、ウィンドウ分割と各ウィンドウのファイル、アクティブバッファを JSON として保存する
+
+use crate::buffer::FoldRange;
+use crate::error::{AltreError, Result};
+use crate::ui::WindowLayout;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// セッションファイルの形式バージョン
+const SESSION_VERSION: u32 = 1;
+
+/// セッションに記録するバッファ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionBuffer {
+    /// ファイルパス
+    pub path: PathBuf,
+    /// カーソルの文字位置
+    pub cursor: usize,
+    /// 折りたたみ中の範囲
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folds: Vec<FoldRange>,
+}
+
+/// セッション全体
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionData {
+    /// 形式バージョン
+    pub version: u32,
+    /// ファイルに紐づくバッファ（開いた順）
+    pub buffers: Vec<SessionBuffer>,
+    /// アクティブだったバッファのパス
+    pub active_buffer: Option<PathBuf>,
+    /// ウィンドウ分割構成
+    pub layout: WindowLayout,
+    /// 各ウィンドウに表示していたファイル（レイアウト順。ファイルでないバッファは `None`）
+    #[serde(default)]
+    pub window_buffers: Vec<Option<PathBuf>>,
+    /// フォーカスしていたウィンドウの位置（レイアウト順）
+    pub focused_window: usize,
+}
+
+impl SessionData {
+    /// 新しいセッションデータを作成
+    pub fn new(
+        buffers: Vec<SessionBuffer>,
+        active_buffer: Option<PathBuf>,
+        layout: WindowLayout,
+        window_buffers: Vec<Option<PathBuf>>,
+        focused_window: usize,
+    ) -> Self {
+        Self {
+            version: SESSION_VERSION,
+            buffers,
+            active_buffer,
+            layout,
+            window_buffers,
+            focused_window,
+        }
+    }
+
+    /// ファイルへ書き出す
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|err| {
+            AltreError::Application(format!("セッションの変換に失敗しました: {}", err))
+        })?;
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(|err| {
+                    AltreError::Application(format!("セッションの保存先を作成できません: {}", err))
+                })?;
+            }
+        }
+        fs::write(path, json).map_err(|err| {
+            AltreError::Application(format!("セッションの保存に失敗しました: {}", err))
+        })
+    }
+
+    /// ファイルから読み込む
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|err| {
+            AltreError::Application(format!(
+                "セッションファイルを読み込めません: {} ({})",
+                path.display(),
+                err
+            ))
+        })?;
+        let data: SessionData = serde_json::from_str(&json).map_err(|err| {
+            AltreError::Application(format!("セッションファイルの形式が不正です: {}", err))
+        })?;
+        if data.version != SESSION_VERSION {
+            return Err(AltreError::Application(format!(
+                "未対応のセッション形式です: version {}",
+                data.version
+            )));
+        }
+        Ok(data)
+    }
+}
+
+/// 既定のセッションファイルパス（`~/.altre/session.json`）
+pub fn default_session_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    Some(PathBuf::from(home).join(".altre").join("session.json"))
+}
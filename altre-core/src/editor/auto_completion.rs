@@ -0,0 +1,404 @@
+//! アイドル時の自動補完
+//!
+//! 入力が一定時間止まったときに、バッファ内の単語（dabbrev 相当）から補完候補を提示する
+
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// 既定の待ち時間
+pub const DEFAULT_IDLE_DELAY: Duration = Duration::from_millis(500);
+/// 表示する候補の最大数
+const MAX_CANDIDATES: usize = 8;
+/// 補完を始める最小の入力文字数
+const MIN_PREFIX_CHARS: usize = 2;
+
+/// 時刻の取得元
+pub trait Clock {
+    /// 現在時刻
+    fn now(&self) -> Instant;
+}
+
+/// 実時間の時計
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// テスト用に手動で進める時計（複製しても同じ時刻を共有する）
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl MockClock {
+    /// 現在時刻から開始する時計を作成
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    /// 時刻を進める
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// 最後の入力から一定時間経過したことを検出するタイマー
+pub struct IdleTimer {
+    clock: Box<dyn Clock>,
+    delay: Duration,
+    armed_at: Option<Instant>,
+}
+
+impl IdleTimer {
+    /// 新しいタイマーを作成
+    pub fn new(clock: Box<dyn Clock>, delay: Duration) -> Self {
+        Self {
+            clock,
+            delay,
+            armed_at: None,
+        }
+    }
+
+    /// 待ち時間を取得
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// 待ち時間を設定
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// 現在時刻から計測を始める
+    pub fn arm(&mut self) {
+        self.armed_at = Some(self.clock.now());
+    }
+
+    /// 計測を止める
+    pub fn disarm(&mut self) {
+        self.armed_at = None;
+    }
+
+    /// 計測中かどうか
+    pub fn is_armed(&self) -> bool {
+        self.armed_at.is_some()
+    }
+
+    /// 待ち時間を過ぎていれば `true` を返して計測を止める
+    pub fn fire(&mut self) -> bool {
+        match self.armed_at {
+            Some(armed_at) if self.clock.now().duration_since(armed_at) >= self.delay => {
+                self.armed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for IdleTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdleTimer")
+            .field("delay", &self.delay)
+            .field("armed_at", &self.armed_at)
+            .finish()
+    }
+}
+
+/// 補完ポップアップの状態
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionPopup {
+    /// 補完対象の先頭位置（文字）
+    pub start: usize,
+    /// 入力済みの接頭辞
+    pub prefix: String,
+    /// 候補一覧
+    pub candidates: Vec<String>,
+    /// 選択中の候補
+    pub selected: usize,
+}
+
+impl CompletionPopup {
+    /// 選択中の候補
+    pub fn selected_candidate(&self) -> Option<&str> {
+        self.candidates.get(self.selected).map(String::as_str)
+    }
+
+    /// 次の候補を選択（末尾で先頭へ戻る）
+    pub fn select_next(&mut self) {
+        if !self.candidates.is_empty() {
+            self.selected = (self.selected + 1) % self.candidates.len();
+        }
+    }
+
+    /// 前の候補を選択（先頭で末尾へ戻る）
+    pub fn select_previous(&mut self) {
+        if !self.candidates.is_empty() {
+            self.selected = (self.selected + self.candidates.len() - 1) % self.candidates.len();
+        }
+    }
+}
+
+/// アイドル時自動補完の状態（既定では無効）
+#[derive(Debug)]
+pub struct AutoCompletion {
+    enabled: bool,
+    timer: IdleTimer,
+    popup: Option<CompletionPopup>,
+}
+
+impl AutoCompletion {
+    /// 実時間の時計で作成
+    pub fn new() -> Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+
+    /// 指定した時計で作成
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
+        Self {
+            enabled: false,
+            timer: IdleTimer::new(clock, DEFAULT_IDLE_DELAY),
+            popup: None,
+        }
+    }
+
+    /// 有効かどうか
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 有効・無効を切り替える
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.cancel();
+        }
+    }
+
+    /// 待ち時間を取得
+    pub fn delay(&self) -> Duration {
+        self.timer.delay()
+    }
+
+    /// 待ち時間を設定
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.timer.set_delay(delay);
+    }
+
+    /// 識別子の入力を通知（表示中のポップアップは閉じて計測し直す）
+    pub fn notify_input(&mut self) {
+        self.popup = None;
+        if self.enabled {
+            self.timer.arm();
+        }
+    }
+
+    /// 計測とポップアップをすべて取り消す
+    pub fn cancel(&mut self) {
+        self.timer.disarm();
+        self.popup = None;
+    }
+
+    /// ポップアップを閉じる（閉じた場合は `true`）
+    pub fn dismiss(&mut self) -> bool {
+        self.popup.take().is_some()
+    }
+
+    /// 表示中のポップアップ
+    pub fn popup(&self) -> Option<&CompletionPopup> {
+        self.popup.as_ref()
+    }
+
+    /// 表示中のポップアップへの可変参照
+    pub fn popup_mut(&mut self) -> Option<&mut CompletionPopup> {
+        self.popup.as_mut()
+    }
+
+    /// 待ち時間を過ぎていれば候補を集めてポップアップを表示する
+    pub fn poll(&mut self, text: &str, cursor: usize) -> bool {
+        if !self.enabled || !self.timer.fire() {
+            return false;
+        }
+
+        let Some((start, prefix)) = prefix_before(text, cursor) else {
+            return false;
+        };
+        if prefix.chars().count() < MIN_PREFIX_CHARS {
+            return false;
+        }
+
+        let candidates = buffer_word_candidates(text, cursor, &prefix, MAX_CANDIDATES);
+        if candidates.is_empty() {
+            return false;
+        }
+
+        self.popup = Some(CompletionPopup {
+            start,
+            prefix,
+            candidates,
+            selected: 0,
+        });
+        true
+    }
+}
+
+impl Default for AutoCompletion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 識別子を構成する文字か
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-'
+}
+
+/// カーソル直前の識別子（開始位置と文字列）
+pub fn prefix_before(text: &str, cursor: usize) -> Option<(usize, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    let cursor = cursor.min(chars.len());
+    let start = chars[..cursor]
+        .iter()
+        .rposition(|&ch| !is_word_char(ch))
+        .map_or(0, |index| index + 1);
+    (start < cursor).then(|| (start, chars[start..cursor].iter().collect()))
+}
+
+/// バッファ内の単語から接頭辞に一致する候補を集める
+///
+/// カーソルより前の単語を近い順に、続いて後ろの単語を近い順に並べ、重複は除く。
+pub fn buffer_word_candidates(
+    text: &str,
+    cursor: usize,
+    prefix: &str,
+    limit: usize,
+) -> Vec<String> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut word = String::new();
+    let mut word_start = 0usize;
+
+    for (index, ch) in text.chars().chain(std::iter::once(' ')).enumerate() {
+        if is_word_char(ch) {
+            if word.is_empty() {
+                word_start = index;
+            }
+            word.push(ch);
+            continue;
+        }
+        if word.is_empty() {
+            continue;
+        }
+        let word_end = index;
+        let candidate = std::mem::take(&mut word);
+        // 入力中の単語自体は候補にしない
+        if (word_start..=word_end).contains(&cursor) {
+            continue;
+        }
+        if candidate.starts_with(prefix) && candidate != prefix {
+            if word_end < cursor {
+                before.push(candidate);
+            } else {
+                after.push(candidate);
+            }
+        }
+    }
+
+    let mut candidates: Vec<String> = Vec::new();
+    for candidate in before.into_iter().rev().chain(after) {
+        if candidates.len() >= limit {
+            break;
+        }
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_are_sorted_by_distance_and_deduplicated() {
+        let text = "format fold foo\nfo\nforward fold";
+        let cursor = "format fold foo\nfo".chars().count();
+        let candidates = buffer_word_candidates(text, cursor, "fo", 8);
+        assert_eq!(candidates, vec!["foo", "fold", "format", "forward"]);
+    }
+
+    #[test]
+    fn candidates_respect_limit_and_exclude_prefix() {
+        let text = "ab abc abd abe ab";
+        let candidates = buffer_word_candidates(text, text.chars().count(), "ab", 2);
+        assert_eq!(candidates, vec!["abe", "abd"]);
+    }
+
+    #[test]
+    fn prefix_before_cursor_uses_word_chars() {
+        assert_eq!(
+            prefix_before("(define-key", 11),
+            Some((1, "define-key".to_string()))
+        );
+        assert_eq!(prefix_before("foo ", 4), None);
+        assert_eq!(prefix_before("日本語", 2), Some((0, "日本".to_string())));
+    }
+
+    #[test]
+    fn idle_timer_fires_once_after_delay() {
+        let clock = MockClock::new();
+        let mut timer = IdleTimer::new(Box::new(clock.clone()), Duration::from_millis(300));
+        assert!(!timer.fire());
+
+        timer.arm();
+        clock.advance(Duration::from_millis(299));
+        assert!(!timer.fire());
+
+        clock.advance(Duration::from_millis(1));
+        assert!(timer.fire());
+        assert!(!timer.fire());
+    }
+
+    #[test]
+    fn poll_requires_enabled_and_idle_time() {
+        let clock = MockClock::new();
+        let mut completion = AutoCompletion::with_clock(Box::new(clock.clone()));
+        let text = "foobar fo";
+        let cursor = text.chars().count();
+
+        completion.notify_input();
+        clock.advance(DEFAULT_IDLE_DELAY);
+        assert!(!completion.poll(text, cursor));
+
+        completion.set_enabled(true);
+        completion.notify_input();
+        assert!(!completion.poll(text, cursor));
+        clock.advance(DEFAULT_IDLE_DELAY);
+        assert!(completion.poll(text, cursor));
+
+        let popup = completion.popup().unwrap();
+        assert_eq!(popup.start, 7);
+        assert_eq!(popup.selected_candidate(), Some("foobar"));
+    }
+}
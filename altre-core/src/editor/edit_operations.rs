@@ -348,6 +348,242 @@ pub mod utils {
         }
     }
 
+    /// タブストップをまたぐ空白の連続をタブにまとめる（各行の先頭を桁 0 とし、桁は表示幅で数える）
+    ///
+    /// `leading_only` なら行頭の空白だけを変換する。1 文字だけの空白はそのまま残す。
+    pub fn tabify(text: &str, tab_width: usize, leading_only: bool) -> String {
+        if tab_width == 0 {
+            return text.to_string();
+        }
+        let next_stop = |column: usize| (column / tab_width + 1) * tab_width;
+        let mut result = String::with_capacity(text.len());
+        for (index, line) in text.split('\n').enumerate() {
+            if index > 0 {
+                result.push('\n');
+            }
+            let chars: Vec<char> = line.chars().collect();
+            let mut column = 0;
+            let mut i = 0;
+            while i < chars.len() {
+                if !matches!(chars[i], ' ' | '\t') {
+                    if leading_only {
+                        result.extend(&chars[i..]);
+                        break;
+                    }
+                    result.push(chars[i]);
+                    column += unicode_width::UnicodeWidthChar::width(chars[i]).unwrap_or(1);
+                    i += 1;
+                    continue;
+                }
+
+                let run_start = i;
+                let start_column = column;
+                while i < chars.len() && matches!(chars[i], ' ' | '\t') {
+                    column = if chars[i] == '\t' {
+                        next_stop(column)
+                    } else {
+                        column + 1
+                    };
+                    i += 1;
+                }
+                if i - run_start < 2 {
+                    result.extend(&chars[run_start..i]);
+                    continue;
+                }
+                let mut filled = start_column;
+                while next_stop(filled) <= column {
+                    result.push('\t');
+                    filled = next_stop(filled);
+                }
+                result.extend(std::iter::repeat_n(' ', column - filled));
+            }
+        }
+        result
+    }
+
+    /// タブを次のタブストップまでの空白に展開する（各行の先頭を桁 0 とする）
+    pub fn untabify(text: &str, tab_width: usize) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut column = 0;
+        for ch in text.chars() {
+            match ch {
+                '\t' if tab_width > 0 => {
+                    let width = tab_width - column % tab_width;
+                    result.extend(std::iter::repeat_n(' ', width));
+                    column += width;
+                }
+                '\n' => {
+                    result.push(ch);
+                    column = 0;
+                }
+                _ => {
+                    result.push(ch);
+                    column += unicode_width::UnicodeWidthChar::width(ch).unwrap_or(1);
+                }
+            }
+        }
+        result
+    }
+
+    /// 行内で表示幅 `target` に達する位置を返す（`(文字位置, その位置の表示幅)`）
+    ///
+    /// タブは次のタブストップまで、その他の文字は表示幅で数える。`target` が文字の途中に
+    /// 当たるときはその文字の後ろ、行の表示幅が足りないときは行末を返す。
+    pub fn char_index_at_display_column(
+        line: &str,
+        target: usize,
+        tab_width: usize,
+    ) -> (usize, usize) {
+        let mut column = 0;
+        let mut index = 0;
+        for ch in line.chars() {
+            if column >= target {
+                break;
+            }
+            column += match ch {
+                '\t' if tab_width > 0 => tab_width - column % tab_width,
+                _ => unicode_width::UnicodeWidthChar::width(ch).unwrap_or(1),
+            };
+            index += 1;
+        }
+        (index, column)
+    }
+
+    /// 行の表示幅（タブは次のタブストップまでとして数える）
+    pub fn display_width(line: &str, tab_width: usize) -> usize {
+        char_index_at_display_column(line, usize::MAX, tab_width).1
+    }
+
+    /// 各行の表示桁 `left` から `right` までを `replacement` で置き換える（矩形の置換）
+    ///
+    /// `left` に届かない短い行は空白で埋めてから文字列を足す。`left == right` なら挿入になる。
+    /// 境界が文字の途中に当たるときはその文字の後ろを境界とする。
+    pub fn replace_rectangle(
+        text: &str,
+        left: usize,
+        right: usize,
+        replacement: &str,
+        tab_width: usize,
+    ) -> String {
+        text.split('\n')
+            .map(|line| {
+                let (start, reached) = char_index_at_display_column(line, left, tab_width);
+                let (end, _) = char_index_at_display_column(line, right, tab_width);
+                let mut result: String = line.chars().take(start).collect();
+                result.extend(std::iter::repeat_n(' ', left.saturating_sub(reached)));
+                result.push_str(replacement);
+                result.extend(line.chars().skip(end));
+                result
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 行の内容を桁 `fill_column` の中央に寄せる（行頭を空白で埋め、行末の空白は除く）
+    ///
+    /// 内容が空の行と、内容の表示幅が `fill_column` を超える行はそのまま返す。
+    pub fn center_line(line: &str, fill_column: usize) -> String {
+        let content = line.trim();
+        let width = unicode_width::UnicodeWidthStr::width(content);
+        if content.is_empty() || width > fill_column {
+            return line.to_string();
+        }
+        let indent = (fill_column - width) / 2;
+        format!("{}{}", " ".repeat(indent), content)
+    }
+
+    /// 段落の単語を詰め直し、各行の表示幅が `fill_column` を超えないように折り返す
+    ///
+    /// 先頭行のインデントを全行に付ける。`fill_column` より長い単語はそのまま1行に置く。
+    pub fn fill_paragraph(paragraph: &str, fill_column: usize) -> String {
+        let indent: String = paragraph
+            .chars()
+            .take_while(|&ch| ch == ' ' || ch == '\t')
+            .collect();
+        let indent_width = indent.chars().count();
+        let mut lines = Vec::new();
+        let mut current = String::new();
+        let mut width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = unicode_width::UnicodeWidthStr::width(word);
+            if !current.is_empty() && width + 1 + word_width > fill_column {
+                lines.push(std::mem::take(&mut current));
+            }
+            if current.is_empty() {
+                current.push_str(&indent);
+                current.push_str(word);
+                width = indent_width + word_width;
+            } else {
+                current.push(' ');
+                current.push_str(word);
+                width += 1 + word_width;
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        lines.join("\n")
+    }
+
+    /// 指定位置を囲む空白・タブの範囲（文字位置）を求める
+    pub fn horizontal_space_range(text: &str, pos: usize) -> (usize, usize) {
+        let chars: Vec<char> = text.chars().collect();
+        let pos = pos.min(chars.len());
+        let is_space = |ch: char| ch == ' ' || ch == '\t';
+
+        let start = chars[..pos]
+            .iter()
+            .rposition(|&ch| !is_space(ch))
+            .map_or(0, |index| index + 1);
+        let end = chars[pos..]
+            .iter()
+            .position(|&ch| !is_space(ch))
+            .map_or(chars.len(), |index| pos + index);
+        (start, end)
+    }
+
+    /// 文字の大文字・小文字を反転（大小の区別がない文字はそのまま）
+    pub fn toggle_char_case(ch: char) -> String {
+        if ch.is_lowercase() {
+            ch.to_uppercase().collect()
+        } else if ch.is_uppercase() {
+            ch.to_lowercase().collect()
+        } else {
+            ch.to_string()
+        }
+    }
+
+    /// 各単語の先頭を大文字、残りを小文字にする
+    pub fn capitalize_words(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut in_word = false;
+        for ch in text.chars() {
+            if is_word_boundary(ch) {
+                in_word = false;
+                result.push(ch);
+            } else if in_word {
+                result.extend(ch.to_lowercase());
+            } else {
+                in_word = true;
+                result.extend(ch.to_uppercase());
+            }
+        }
+        result
+    }
+
+    /// 指定位置以降で最初に現れる単語の終了位置
+    pub fn next_word_end(text: &str, pos: usize) -> usize {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = pos.min(chars.len());
+        while i < chars.len() && is_word_boundary(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && !is_word_boundary(chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
     /// UTF-8文字境界での安全な範囲チェック
     pub fn safe_char_range(text: &str, start: usize, end: usize) -> Result<(usize, usize)> {
         let char_count = text.chars().count();
@@ -389,6 +625,91 @@ mod tests {
     use super::utils::*;
     use super::*;
 
+    #[test]
+    fn test_horizontal_space_range() {
+        assert_eq!(horizontal_space_range("foo  \t bar", 5), (3, 7));
+        assert_eq!(horizontal_space_range("foo bar", 1), (1, 1));
+        assert_eq!(horizontal_space_range("a\n\t  b", 2), (2, 5));
+        assert_eq!(horizontal_space_range("a   ", 4), (1, 4));
+    }
+
+    #[test]
+    fn test_tabify_and_untabify() {
+        assert_eq!(tabify("        a  b", 4, true), "\t\ta  b");
+        assert_eq!(tabify("  \t  x", 4, true), "\t  x");
+        assert_eq!(tabify("ab      c", 4, false), "ab\t\tc");
+        assert_eq!(tabify("ab c", 4, false), "ab c");
+        assert_eq!(tabify("      y\n   z", 4, true), "\t  y\n   z");
+
+        assert_eq!(untabify("\ta\tb", 4), "    a   b");
+        assert_eq!(untabify("  \tx\n\ty", 4), "    x\n    y");
+        assert_eq!(untabify(&tabify("ab      c", 4, false), 4), "ab      c");
+
+        // 全角文字は表示幅で桁を数える
+        assert_eq!(tabify("あ      c", 4, false), "あ\t\tc");
+        assert_eq!(untabify("あ\tc", 4), "あ  c");
+    }
+
+    #[test]
+    fn test_char_index_at_display_column() {
+        assert_eq!(char_index_at_display_column("a\tb", 0, 4), (0, 0));
+        assert_eq!(char_index_at_display_column("a\tb", 4, 4), (2, 4));
+        assert_eq!(char_index_at_display_column("a\tb", 2, 4), (2, 4));
+        assert_eq!(char_index_at_display_column("あいx", 2, 4), (1, 2));
+        assert_eq!(char_index_at_display_column("あいx", 3, 4), (2, 4));
+        assert_eq!(char_index_at_display_column("ab", 10, 4), (2, 2));
+    }
+
+    #[test]
+    fn test_replace_rectangle() {
+        assert_eq!(
+            replace_rectangle("abcdef\nab\nabcd", 1, 3, "XY", 4),
+            "aXYdef\naXY\naXYd"
+        );
+        // 左端に届かない行は空白で埋める
+        assert_eq!(
+            replace_rectangle("abcd\n\na", 2, 3, "-", 4),
+            "ab-d\n  -\na -"
+        );
+        // 幅 0 の矩形は挿入
+        assert_eq!(replace_rectangle("ab\ncd", 1, 1, "|", 4), "a|b\nc|d");
+        assert_eq!(replace_rectangle("\tx", 4, 5, "y", 4), "\ty");
+    }
+
+    #[test]
+    fn test_fill_paragraph() {
+        assert_eq!(
+            fill_paragraph("aaa bbb\nccc   ddd eee", 11),
+            "aaa bbb ccc\nddd eee"
+        );
+        assert_eq!(fill_paragraph("  aa bb cc", 7), "  aa bb\n  cc");
+        assert_eq!(
+            fill_paragraph("short averyverylongword x", 8),
+            "short\naveryverylongword\nx"
+        );
+        assert_eq!(fill_paragraph("あい うえ", 5), "あい\nうえ");
+    }
+
+    #[test]
+    fn test_center_line() {
+        assert_eq!(center_line("abcd", 10), "   abcd");
+        assert_eq!(center_line("\t  abcd   ", 10), "   abcd");
+        assert_eq!(center_line("あい", 10), "   あい");
+        assert_eq!(center_line("  too wide line", 8), "  too wide line");
+        assert_eq!(center_line("   ", 10), "   ");
+    }
+
+    #[test]
+    fn test_case_conversion_helpers() {
+        assert_eq!(toggle_char_case('a'), "A");
+        assert_eq!(toggle_char_case('Ä'), "ä");
+        assert_eq!(toggle_char_case('ß'), "SS");
+        assert_eq!(toggle_char_case('あ'), "あ");
+        assert_eq!(capitalize_words("hELLO wORLD-élan"), "Hello World-Élan");
+        assert_eq!(next_word_end("  (foo bar", 0), 6);
+        assert_eq!(next_word_end("foo", 3), 3);
+    }
+
     #[test]
     fn test_edit_context_creation() {
         let context = EditContext::new();
@@ -13,11 +13,29 @@ pub enum HistoryCommandKind {
     Other,
 }
 
+/// 1バッファあたりの履歴が保持するテキスト量の既定上限（バイト）
+pub const DEFAULT_UNDO_MEMORY_LIMIT: usize = 8 * 1024 * 1024;
+
 /// 編集履歴スタック
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct HistoryStack {
     undo: Vec<HistoryEntry>,
     redo: Vec<HistoryEntry>,
+    /// undo/redo に保持している編集テキストの合計（バイト）
+    memory_usage: usize,
+    /// `memory_usage` の上限（超えると古い undo から捨てる）
+    memory_limit: usize,
+}
+
+impl Default for HistoryStack {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            memory_usage: 0,
+            memory_limit: DEFAULT_UNDO_MEMORY_LIMIT,
+        }
+    }
 }
 
 impl HistoryStack {
@@ -25,6 +43,27 @@ impl HistoryStack {
         Self::default()
     }
 
+    /// 保持している編集テキストの合計（バイト）
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// 保持するテキスト量の上限（バイト）
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    /// 上限を設定し、超えていれば古い履歴を捨てる
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = limit;
+        self.trim_to_limit();
+    }
+
+    /// 保持している undo エントリ数
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
     pub fn can_undo(&self) -> bool {
         !self.undo.is_empty()
     }
@@ -34,33 +73,62 @@ impl HistoryStack {
     }
 
     pub fn push(&mut self, mut entry: HistoryEntry) {
+        self.clear_redo();
+        self.memory_usage += entry.memory_size();
         if let Some(last) = self.undo.last_mut() {
             if last.try_merge_with(&entry) {
                 last.merge_with(entry);
-                self.redo.clear();
+                self.trim_to_limit();
                 return;
             }
         }
         entry.compact();
         self.undo.push(entry);
-        self.redo.clear();
+        self.trim_to_limit();
     }
 
     pub fn take_for_undo(&mut self) -> Option<HistoryEntry> {
-        self.undo.pop()
+        let entry = self.undo.pop()?;
+        self.memory_usage -= entry.memory_size();
+        Some(entry)
     }
 
     pub fn push_redo(&mut self, entry: HistoryEntry) {
+        self.memory_usage += entry.memory_size();
         self.redo.push(entry);
     }
 
     pub fn take_for_redo(&mut self) -> Option<HistoryEntry> {
-        self.redo.pop()
+        let entry = self.redo.pop()?;
+        self.memory_usage -= entry.memory_size();
+        Some(entry)
     }
 
     pub fn push_without_clearing(&mut self, entry: HistoryEntry) {
+        self.memory_usage += entry.memory_size();
         self.undo.push(entry);
     }
+
+    fn clear_redo(&mut self) {
+        for entry in self.redo.drain(..) {
+            self.memory_usage -= entry.memory_size();
+        }
+    }
+
+    /// 上限を超えている間、最も古い undo エントリから捨てる
+    ///
+    /// undo は新しい順に適用するため、末尾側の連鎖は壊れない。直近のエントリは常に残す。
+    fn trim_to_limit(&mut self) {
+        let mut excess = self.memory_usage.saturating_sub(self.memory_limit);
+        let mut removed = 0;
+        while excess > 0 && removed + 1 < self.undo.len() {
+            let size = self.undo[removed].memory_size();
+            excess = excess.saturating_sub(size);
+            self.memory_usage -= size;
+            removed += 1;
+        }
+        self.undo.drain(..removed);
+    }
 }
 
 /// 履歴エントリ
@@ -128,6 +196,16 @@ impl HistoryEntry {
         }
     }
 
+    /// 保持している編集テキストの量（バイト）
+    pub fn memory_size(&self) -> usize {
+        self.operations
+            .iter()
+            .map(|op| match op {
+                AtomicEdit::Insert { text, .. } | AtomicEdit::Delete { text, .. } => text.len(),
+            })
+            .sum()
+    }
+
     fn is_simple_insert(&self) -> bool {
         matches!(self.operations.as_slice(), [AtomicEdit::Insert { .. }])
     }
@@ -454,3 +532,90 @@ fn apply_redo(editor: &mut TextEditor, entry: &HistoryEntry) -> Result<()> {
     editor.set_cursor(entry.cursor_after.position);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_entry(position: usize, text: &str) -> HistoryEntry {
+        let snapshot = CursorSnapshot {
+            position: CursorPosition::new(),
+            mark: None,
+        };
+        HistoryEntry {
+            command_kind: HistoryCommandKind::Other,
+            operations: vec![AtomicEdit::Insert {
+                position,
+                text: text.to_string(),
+            }],
+            cursor_before: snapshot.clone(),
+            cursor_after: snapshot,
+        }
+    }
+
+    #[test]
+    fn memory_limit_trims_oldest_entries() {
+        let mut stack = HistoryStack::new();
+        stack.set_memory_limit(250);
+        let chunk = "x".repeat(100);
+        for index in 0..5 {
+            stack.push(insert_entry(index * 100, &chunk));
+        }
+
+        assert_eq!(stack.undo_len(), 2);
+        assert_eq!(stack.memory_usage(), 200);
+
+        let newest = stack.take_for_undo().unwrap();
+        assert!(matches!(
+            newest.operations.as_slice(),
+            [AtomicEdit::Insert { position: 400, .. }]
+        ));
+        stack.push_redo(newest);
+        assert_eq!(stack.memory_usage(), 200);
+
+        stack.push(insert_entry(0, "y"));
+        assert_eq!(stack.memory_usage(), 101);
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn newest_entry_is_kept_even_when_over_limit() {
+        let mut stack = HistoryStack::new();
+        stack.push(insert_entry(0, "small"));
+        stack.set_memory_limit(10);
+        stack.push(insert_entry(5, &"z".repeat(50)));
+        assert_eq!(stack.undo_len(), 1);
+        assert_eq!(stack.memory_usage(), 50);
+    }
+
+    #[test]
+    fn undo_still_works_after_trimming() {
+        let mut editor = TextEditor::new();
+        let mut manager = HistoryManager::new();
+        manager.bind_editor(&mut editor);
+        manager.stack_mut().set_memory_limit(64);
+
+        for index in 0..10 {
+            manager.begin_command(HistoryCommandKind::Other, &editor);
+            editor
+                .insert_str(&format!("{}{}", index, "-".repeat(29)))
+                .unwrap();
+            manager.end_command(&editor, true);
+        }
+        assert!(manager.stack().memory_usage() <= 64);
+        assert_eq!(manager.stack().undo_len(), 2);
+
+        assert!(manager.undo(&mut editor).unwrap());
+        assert!(manager.undo(&mut editor).unwrap());
+        assert!(!manager.undo(&mut editor).unwrap());
+        assert_eq!(editor.to_string().chars().count(), 8 * 30);
+        assert!(editor
+            .to_string()
+            .ends_with(&format!("7{}", "-".repeat(29))));
+
+        assert!(manager.redo(&mut editor).unwrap());
+        assert!(editor
+            .to_string()
+            .ends_with(&format!("8{}", "-".repeat(29))));
+    }
+}
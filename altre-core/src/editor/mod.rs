@@ -2,6 +2,7 @@
 //!
 //! 基本編集機能の統合モジュール
 
+pub mod auto_completion;
 pub mod change_notifier;
 pub mod edit_operations;
 pub mod history;
@@ -10,6 +11,9 @@ pub mod kill_ring;
 pub mod text_editor;
 
 // 公開API
+pub use auto_completion::{
+    AutoCompletion, Clock, CompletionPopup, IdleTimer, MockClock, SystemClock,
+};
 pub use change_notifier::{
     AdvancedChangeNotifier, BatchInfo, ChangeNotifierStats, ExtendedChangeEvent,
     ExtendedChangeListener, ListenerId, ViewportInfo,
@@ -18,7 +22,10 @@ pub use edit_operations::{
     utils as edit_utils, EditContext, EditMetrics, EditMode, ExtendedEditOperations,
     OperationResult,
 };
-pub use history::{AtomicEdit, HistoryCommandKind, HistoryEntry, HistoryManager, HistoryStack};
+pub use history::{
+    AtomicEdit, HistoryCommandKind, HistoryEntry, HistoryManager, HistoryStack,
+    DEFAULT_UNDO_MEMORY_LIMIT,
+};
 pub use input_buffer::{InputBuffer, InputBufferError, InputBufferStats};
 pub use kill_ring::KillRing;
 pub use text_editor::TextEditor;
@@ -5,7 +5,8 @@
 
 use crate::logging::{LogLevel, Logger};
 use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, Instant, SystemTime};
 use thiserror::Error;
 
@@ -78,6 +79,9 @@ pub enum FileError {
     #[error("Permission denied: {path}")]
     PermissionDenied { path: String },
 
+    #[error("Directory is not writable: {path}")]
+    DirectoryNotWritable { path: String },
+
     #[error("Invalid path: {path}")]
     InvalidPath { path: String },
 
@@ -200,6 +204,13 @@ impl ErrorMessageCatalog {
                 level: Error,
             },
         );
+        entries.insert(
+            "directory_not_writable",
+            MessageEntry {
+                text: "保存先ディレクトリに書き込み権限がありません",
+                level: Error,
+            },
+        );
         entries.insert(
             "invalid_path",
             MessageEntry {
@@ -456,6 +467,9 @@ impl ErrorDisplay {
             AltreError::File(FileError::PermissionDenied { path }) => {
                 Some(catalog.compose("permission_denied", Some(path.clone())))
             }
+            AltreError::File(FileError::DirectoryNotWritable { path }) => {
+                Some(catalog.compose("directory_not_writable", Some(path.clone())))
+            }
             AltreError::File(FileError::InvalidPath { path }) => {
                 Some(catalog.compose("invalid_path", Some(path.clone())))
             }
@@ -578,6 +592,7 @@ fn log_error_internal(logger: &Logger, error: &AltreError, context: Option<&str>
 
 /// 致命的エラー処理（QA Q11の回答）
 pub fn handle_fatal_error(error: &AltreError, context: &str) -> ! {
+    let _ = restore_terminal_state();
     let logger = Logger::for_development();
     log_error_internal(&logger, error, Some(context));
 
@@ -603,15 +618,63 @@ pub fn setup_panic_handler() {
             "Unknown panic payload"
         };
 
+        // 端末が raw モードのままだとメッセージが崩れるため、先に元へ戻す
+        let _ = restore_terminal_state();
+
         let logger = Logger::for_development();
         let context = format!("{}:{}", location.file(), location.line());
         logger.log_fatal_with_trace(format!("PANIC: {}", message), Some(&context));
+        eprintln!("altre: パニックが発生しました: {} ({})", message, context);
 
         // 致命的エラーとして即座に終了（QA Q11の回答）
         std::process::exit(1);
     }));
 }
 
+/// 端末の状態を元に戻す処理
+pub type TerminalRestore = fn() -> Result<()>;
+
+static TERMINAL_RESTORE: Mutex<Option<TerminalRestore>> = Mutex::new(None);
+
+/// パニック時やエラー終了時に呼ぶ端末の復元処理を登録
+pub fn set_terminal_restore(restore: TerminalRestore) {
+    *terminal_restore_slot() = Some(restore);
+}
+
+/// 登録した復元処理を実行せずに解除
+pub fn clear_terminal_restore() {
+    terminal_restore_slot().take();
+}
+
+/// 登録した復元処理を実行して解除（未登録なら何もしない）
+pub fn restore_terminal_state() -> Result<()> {
+    let restore = terminal_restore_slot().take();
+    match restore {
+        Some(restore) => restore(),
+        None => Ok(()),
+    }
+}
+
+fn terminal_restore_slot() -> std::sync::MutexGuard<'static, Option<TerminalRestore>> {
+    // パニック中でも復元できるよう、毒化したロックもそのまま使う
+    TERMINAL_RESTORE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// イベントループから抜けてきたエラーを報告（端末を戻してから標準エラーへ出力）
+pub fn report_error(error: &AltreError, context: &str) {
+    report_error_to(&mut std::io::stderr(), error, context);
+}
+
+/// 出力先を指定してエラーを報告
+pub fn report_error_to(out: &mut dyn Write, error: &AltreError, context: &str) {
+    let _ = restore_terminal_state();
+    let logger = Logger::for_development();
+    log_error_internal(&logger, error, Some(context));
+    let _ = writeln!(out, "altre: {}", ErrorDisplay::new(error).message);
+}
+
 /// エラーコンテキスト付与のためのトレイト
 pub trait ErrorContext<T> {
     fn with_context_info(self, operation: &str, location: &str) -> Result<T>;
@@ -734,6 +797,9 @@ pub enum EditError {
 
     #[error("Operation cancelled")]
     Cancelled,
+
+    #[error("Buffer is read-only")]
+    ReadOnly,
 }
 
 // EditError から AltreError への変換
@@ -773,6 +839,13 @@ impl From<std::str::Utf8Error> for AltreError {
     }
 }
 
+/// 端末復元処理の登録はプロセス全体で共有されるため、触れるテストを直列化する
+#[cfg(test)]
+pub(crate) fn terminal_restore_test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -838,6 +911,18 @@ mod tests {
         assert!(display.message.contains("/tmp/test"));
     }
 
+    #[test]
+    fn test_error_message_catalog_directory_not_writable() {
+        let error = AltreError::File(FileError::DirectoryNotWritable {
+            path: "/tmp/readonly".to_string(),
+        });
+        let display = ErrorDisplay::new(&error);
+
+        assert_eq!(display.level, ErrorLevel::Error);
+        assert!(display.message.contains("書き込み権限がありません"));
+        assert!(display.message.contains("/tmp/readonly"));
+    }
+
     #[test]
     fn test_error_report_generation() {
         let error = AltreError::Application("テストエラー".to_string());
@@ -848,4 +933,59 @@ mod tests {
         assert!(formatted.contains("unit_test"));
         assert!(formatted.contains("テストエラー"));
     }
+
+    static RESTORE_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn counting_restore() -> Result<()> {
+        RESTORE_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 出力時点で端末が復元済みだったかを記録する
+    struct RecordingWriter {
+        restored_before_write: Option<bool>,
+        output: Vec<u8>,
+    }
+
+    impl Write for RecordingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.restored_before_write
+                .get_or_insert(RESTORE_CALLS.load(std::sync::atomic::Ordering::SeqCst) > 0);
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn terminal_restore_runs_once_and_report_restores_before_writing() {
+        let _guard = terminal_restore_test_lock();
+        RESTORE_CALLS.store(0, std::sync::atomic::Ordering::SeqCst);
+
+        set_terminal_restore(counting_restore);
+        let mut writer = RecordingWriter {
+            restored_before_write: None,
+            output: Vec::new(),
+        };
+        let error = AltreError::Application("ループで失敗".to_string());
+        report_error_to(&mut writer, &error, "unit_test");
+
+        assert_eq!(writer.restored_before_write, Some(true));
+        assert!(String::from_utf8(writer.output)
+            .unwrap()
+            .contains("ループで失敗"));
+        assert_eq!(RESTORE_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // 一度実行した復元処理は解除される
+        restore_terminal_state().unwrap();
+        assert_eq!(RESTORE_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        set_terminal_restore(counting_restore);
+        clear_terminal_restore();
+        restore_terminal_state().unwrap();
+        assert_eq!(RESTORE_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
 }
@@ -114,6 +114,36 @@ impl FileInfo {
         }
     }
 
+    /// ファイルの権限が書き込み禁止になっているか（存在しなければ false）
+    ///
+    /// 実際に開けるかではなく権限のビットで判定する。
+    pub fn is_write_protected(path: &Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().readonly())
+    }
+
+    /// ファイルの所有者に書き込み権限を与える
+    pub fn make_writable(path: &Path) -> Result<()> {
+        let to_error = |e: std::io::Error| {
+            let path = path.display().to_string();
+            AltreError::File(match e.kind() {
+                ErrorKind::NotFound => FileError::NotFound { path },
+                ErrorKind::PermissionDenied => FileError::PermissionDenied { path },
+                _ => FileError::Io {
+                    message: e.to_string(),
+                },
+            })
+        };
+        let mut permissions = std::fs::metadata(path).map_err(to_error)?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            permissions.set_mode(permissions.mode() | 0o200);
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(path, permissions).map_err(to_error)
+    }
+
     /// 新規ファイル作成可能性チェック
     fn can_create_file(path: &Path) -> Result<bool> {
         if let Some(parent) = path.parent() {
@@ -181,15 +211,28 @@ impl FileChangeTracker {
 }
 
 /// 改行コードスタイル
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub enum LineEndingStyle {
-    Lf,    // \n (Unix)
+    #[default]
+    Lf, // \n (Unix)
     Crlf,  // \r\n (Windows)
     Cr,    // \r (Classic Mac)
     Mixed, // 混在
     None,  // 改行なし
 }
 
+impl LineEndingStyle {
+    /// モードライン向けの短い表記（改行がなければ保存時の LF を示す）
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEndingStyle::Lf | LineEndingStyle::None => "LF",
+            LineEndingStyle::Crlf => "CRLF",
+            LineEndingStyle::Cr => "CR",
+            LineEndingStyle::Mixed => "Mixed",
+        }
+    }
+}
+
 /// 改行コード処理
 pub struct LineEndingProcessor;
 
@@ -259,6 +302,155 @@ impl EncodingProcessor {
     }
 }
 
+/// ファイルの文字コード
+///
+/// Shift_JIS の変換は `encoding_rs` の表（WHATWG の Shift_JIS）を使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodingSystem {
+    /// UTF-8（既定）
+    #[default]
+    Utf8,
+    /// UTF-16 リトルエンディアン
+    Utf16Le,
+    /// UTF-16 ビッグエンディアン
+    Utf16Be,
+    /// ISO-8859-1
+    Latin1,
+    /// Shift_JIS
+    ShiftJis,
+}
+
+impl CodingSystem {
+    /// 対応している文字コード
+    pub const ALL: [CodingSystem; 5] = [
+        CodingSystem::Utf8,
+        CodingSystem::Utf16Le,
+        CodingSystem::Utf16Be,
+        CodingSystem::Latin1,
+        CodingSystem::ShiftJis,
+    ];
+
+    /// 文字コード名
+    pub fn name(self) -> &'static str {
+        match self {
+            CodingSystem::Utf8 => "utf-8",
+            CodingSystem::Utf16Le => "utf-16le",
+            CodingSystem::Utf16Be => "utf-16be",
+            CodingSystem::Latin1 => "latin-1",
+            CodingSystem::ShiftJis => "shift_jis",
+        }
+    }
+
+    /// モードライン向けの短い表記
+    pub fn label(self) -> &'static str {
+        match self {
+            CodingSystem::Utf8 => "UTF-8",
+            CodingSystem::Utf16Le => "UTF-16LE",
+            CodingSystem::Utf16Be => "UTF-16BE",
+            CodingSystem::Latin1 => "Latin-1",
+            CodingSystem::ShiftJis => "Shift_JIS",
+        }
+    }
+
+    /// 名前から文字コードを取得（大文字小文字・別名を許容）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(CodingSystem::Utf8),
+            "utf-16le" | "utf16le" => Some(CodingSystem::Utf16Le),
+            "utf-16be" | "utf16be" => Some(CodingSystem::Utf16Be),
+            "latin-1" | "latin1" | "iso-8859-1" => Some(CodingSystem::Latin1),
+            "shift_jis" | "shift-jis" | "sjis" | "cp932" => Some(CodingSystem::ShiftJis),
+            _ => None,
+        }
+    }
+
+    /// ファイルのバイト列から文字コードを推定する
+    ///
+    /// UTF-16 の BOM、UTF-8、Shift_JIS の順に試し、どれでも読めなければ `None`。
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0xFF, 0xFE]) {
+            return Some(CodingSystem::Utf16Le);
+        }
+        if bytes.starts_with(&[0xFE, 0xFF]) {
+            return Some(CodingSystem::Utf16Be);
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Some(CodingSystem::Utf8);
+        }
+        encoding_rs::SHIFT_JIS
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|_| CodingSystem::ShiftJis)
+    }
+
+    /// バイト列を文字列へ変換（先頭の BOM は取り除く）
+    pub fn decode(self, bytes: &[u8]) -> Result<String> {
+        let decode_error = |message: String| AltreError::File(FileError::Encoding { message });
+        match self {
+            CodingSystem::Utf8 => std::str::from_utf8(bytes)
+                .map(|text| EncodingProcessor::remove_bom(text).to_string())
+                .map_err(|err| decode_error(format!("UTF-8 として読み込めません: {}", err))),
+            CodingSystem::Utf16Le | CodingSystem::Utf16Be => {
+                if !bytes.len().is_multiple_of(2) {
+                    return Err(decode_error("UTF-16 のバイト数が奇数です".to_string()));
+                }
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|pair| {
+                        if self == CodingSystem::Utf16Le {
+                            u16::from_le_bytes([pair[0], pair[1]])
+                        } else {
+                            u16::from_be_bytes([pair[0], pair[1]])
+                        }
+                    })
+                    .collect();
+                String::from_utf16(&units)
+                    .map(|text| EncodingProcessor::remove_bom(&text).to_string())
+                    .map_err(|err| decode_error(format!("UTF-16 として読み込めません: {}", err)))
+            }
+            CodingSystem::Latin1 => Ok(bytes.iter().map(|&byte| char::from(byte)).collect()),
+            CodingSystem::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(|text| text.into_owned())
+                .ok_or_else(|| decode_error("Shift_JIS として読み込めません".to_string())),
+        }
+    }
+
+    /// 文字列をバイト列へ変換
+    pub fn encode(self, text: &str) -> Result<Vec<u8>> {
+        match self {
+            CodingSystem::Utf8 => Ok(text.as_bytes().to_vec()),
+            CodingSystem::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            CodingSystem::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            CodingSystem::Latin1 => text
+                .chars()
+                .map(|ch| {
+                    u8::try_from(u32::from(ch)).map_err(|_| {
+                        AltreError::File(FileError::Encoding {
+                            message: format!("latin-1 で表せない文字があります: {:?}", ch),
+                        })
+                    })
+                })
+                .collect(),
+            CodingSystem::ShiftJis => {
+                let (bytes, _, unmappable) = encoding_rs::SHIFT_JIS.encode(text);
+                if unmappable {
+                    let ch = text
+                        .chars()
+                        .find(|ch| {
+                            let mut buf = [0u8; 4];
+                            encoding_rs::SHIFT_JIS.encode(ch.encode_utf8(&mut buf)).2
+                        })
+                        .unwrap_or('?');
+                    return Err(AltreError::File(FileError::Encoding {
+                        message: format!("shift_jis で表せない文字があります: {:?}", ch),
+                    }));
+                }
+                Ok(bytes.into_owned())
+            }
+        }
+    }
+}
+
 /// ファイルメタデータ管理
 #[derive(Debug, Clone)]
 pub struct FileMetadata {
@@ -422,4 +614,54 @@ mod tests {
             assert_eq!(file_info.path, target_file); // リンク先パスが取得される
         }
     }
+
+    #[test]
+    fn coding_systems_round_trip() {
+        let text = "caf\u{e9} \u{3042}";
+        for coding in [
+            CodingSystem::Utf8,
+            CodingSystem::Utf16Le,
+            CodingSystem::Utf16Be,
+        ] {
+            let bytes = coding.encode(text).unwrap();
+            assert_eq!(coding.decode(&bytes).unwrap(), text, "{}", coding.name());
+        }
+
+        assert_eq!(
+            CodingSystem::Latin1.decode(&[0x63, 0xE9]).unwrap(),
+            "c\u{e9}"
+        );
+        assert!(CodingSystem::Latin1.encode("\u{3042}").is_err());
+        assert!(CodingSystem::Utf8.decode(&[0xE9]).is_err());
+        assert_eq!(
+            CodingSystem::from_name("ISO-8859-1"),
+            Some(CodingSystem::Latin1)
+        );
+        assert_eq!(
+            CodingSystem::from_name("Shift_JIS"),
+            Some(CodingSystem::ShiftJis)
+        );
+        assert_eq!(CodingSystem::from_name("euc-jp"), None);
+    }
+
+    #[test]
+    fn shift_jis_round_trips_and_is_detected() {
+        let sjis = [0x93, 0xFA, 0x96, 0x7B, 0x8C, 0xEA, 0x0A, 0x82, 0xA0, 0x41];
+        let text = CodingSystem::ShiftJis.decode(&sjis).unwrap();
+        assert_eq!(text, "日本語\nあA");
+        assert_eq!(CodingSystem::ShiftJis.encode(&text).unwrap(), sjis);
+
+        assert_eq!(CodingSystem::detect(&sjis), Some(CodingSystem::ShiftJis));
+        assert_eq!(
+            CodingSystem::detect("日本語".as_bytes()),
+            Some(CodingSystem::Utf8)
+        );
+        assert_eq!(
+            CodingSystem::detect(&[0xFF, 0xFE, 0x41, 0x00]),
+            Some(CodingSystem::Utf16Le)
+        );
+
+        assert!(CodingSystem::ShiftJis.encode("caf\u{e9}").is_err());
+        assert!(CodingSystem::ShiftJis.decode(&[0x82]).is_err());
+    }
 }
@@ -15,12 +15,15 @@ pub mod path;
 
 // 基本公開API（既存互換）
 pub use io::{read_file, write_file, FileOperations};
-pub use path::{expand_path, normalize_path, PathProcessor};
+pub use path::{
+    expand_path, expand_wildcards, has_wildcards, normalize_path, same_file_path,
+    split_path_position, wildcard_match, PathProcessor,
+};
 
 // 新しい公開API
 pub use completion::{CompletionDisplay, CompletionResult, PathCompletion};
 pub use metadata::{
-    EncodingProcessor, FileChangeTracker, FileInfo, FileMetadata, LineEndingProcessor,
-    LineEndingStyle,
+    CodingSystem, EncodingProcessor, FileChangeTracker, FileInfo, FileMetadata,
+    LineEndingProcessor, LineEndingStyle,
 };
 pub use operations::{FileBuffer, FileOperationManager, FileReader, FileSaver};
@@ -3,7 +3,9 @@
 //! ファイルオープン、保存、バッファ管理の実装
 
 use crate::error::{AltreError, FileError, Result};
-use crate::file::metadata::{EncodingProcessor, FileChangeTracker, FileInfo, LineEndingProcessor};
+use crate::file::metadata::{
+    CodingSystem, FileChangeTracker, FileInfo, LineEndingProcessor, LineEndingStyle,
+};
 use std::path::{Path, PathBuf};
 
 /// ファイル操作用デバッグマクロ
@@ -25,11 +27,31 @@ impl FileReader {
 
     /// ファイル内容を読み込み
     pub fn read_file(&self, path: &Path) -> Result<String> {
+        self.read_file_with_line_ending(path)
+            .map(|(content, _)| content)
+    }
+
+    /// ファイル内容を読み込み、LF に統一する前の改行コードも返す
+    pub fn read_file_with_line_ending(&self, path: &Path) -> Result<(String, LineEndingStyle)> {
+        self.read_file_with_coding(path)
+            .map(|(content, line_ending, _)| (content, line_ending))
+    }
+
+    /// ファイル内容を文字コードを推定して読み込み、改行コードと文字コードも返す
+    pub fn read_file_with_coding(
+        &self,
+        path: &Path,
+    ) -> Result<(String, LineEndingStyle, CodingSystem)> {
         let file_info = FileInfo::analyze(path)?;
 
         // 存在チェック
         if !file_info.exists {
-            return Ok(String::new()); // 新規ファイル
+            // 新規ファイル
+            return Ok((
+                String::new(),
+                LineEndingStyle::default(),
+                CodingSystem::default(),
+            ));
         }
 
         // ファイル種別チェック
@@ -47,19 +69,27 @@ impl FileReader {
         }
 
         // ファイル読み込み
-        let content = std::fs::read_to_string(path).map_err(|e| {
+        let bytes = std::fs::read(path).map_err(|e| {
             AltreError::File(FileError::Io {
                 message: e.to_string(),
             })
         })?;
 
-        // BOM除去
-        let without_bom = EncodingProcessor::remove_bom(&content);
+        // 文字コード判定（BOM は decode で除去される）
+        let coding = CodingSystem::detect(&bytes).ok_or_else(|| {
+            AltreError::File(FileError::Encoding {
+                message:
+                    "文字コードを判定できません（UTF-8・UTF-16・Shift_JIS のいずれでもありません）"
+                        .to_string(),
+            })
+        })?;
+        let content = coding.decode(&bytes)?;
 
         // 改行コード統一
-        let normalized_content = LineEndingProcessor::normalize_to_lf(without_bom);
+        let line_ending = LineEndingProcessor::detect_line_endings(&content);
+        let normalized_content = LineEndingProcessor::normalize_to_lf(&content);
 
-        Ok(normalized_content)
+        Ok((normalized_content, line_ending, coding))
     }
 
     /// ファイル内容の検証
@@ -97,15 +127,37 @@ impl FileSaver {
 
     /// ファイルを保存
     pub fn save_file(&self, path: &Path, content: &str) -> Result<()> {
+        self.save_file_with_coding(path, content, CodingSystem::Utf8)
+            .map(|_| ())
+    }
+
+    /// 文字コードを指定してファイルを保存し、実際に書き込んだかを返す
+    ///
+    /// ディスク上の内容と同じなら書き込まずに `false` を返す。
+    pub fn save_file_with_coding(
+        &self,
+        path: &Path,
+        content: &str,
+        coding: CodingSystem,
+    ) -> Result<bool> {
         // バックアップなし（QA Q16の回答）
 
         file_debug_log!(self, "save_file called with path: {}", path.display());
         file_debug_log!(self, "content length: {}", content.len());
 
+        // 書き込み可否の事前確認
+        self.check_writable(path)?;
+
         // LF改行コード統一
-        let save_content = LineEndingProcessor::ensure_lf_endings(content);
+        let save_content = coding.encode(&LineEndingProcessor::ensure_lf_endings(content))?;
         file_debug_log!(self, "normalized content length: {}", save_content.len());
 
+        // ディスク上の内容と同じなら書き込まない（更新時刻を変えず、監視側に通知しない）
+        if Self::disk_content_equals(path, &save_content) {
+            file_debug_log!(self, "content unchanged on disk, skipping write");
+            return Ok(false);
+        }
+
         // アトミック保存実装
         let result = if self.atomic_save {
             file_debug_log!(self, "using atomic save");
@@ -120,18 +172,61 @@ impl FileSaver {
             Err(e) => file_debug_log!(self, "save operation failed: {}", e),
         }
 
-        result
+        result.map(|_| true)
+    }
+
+    /// 保存した場合に書き込む内容がディスク上のファイルと同一か
+    pub fn matches_disk(&self, path: &Path, content: &str, coding: CodingSystem) -> bool {
+        coding
+            .encode(&LineEndingProcessor::ensure_lf_endings(content))
+            .is_ok_and(|encoded| Self::disk_content_equals(path, &encoded))
+    }
+
+    fn disk_content_equals(path: &Path, content: &[u8]) -> bool {
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.len() == content.len() as u64)
+            && std::fs::read(path).is_ok_and(|on_disk| on_disk == content)
+    }
+
+    /// 保存先ファイルと親ディレクトリの書き込み権限を確認
+    fn check_writable(&self, path: &Path) -> Result<()> {
+        if let Ok(metadata) = std::fs::metadata(path) {
+            if metadata.is_file() && metadata.permissions().readonly() {
+                return Err(AltreError::File(FileError::PermissionDenied {
+                    path: path.display().to_string(),
+                }));
+            }
+        }
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        match std::fs::metadata(parent) {
+            Ok(metadata) if metadata.is_dir() && metadata.permissions().readonly() => {
+                Err(AltreError::File(FileError::DirectoryNotWritable {
+                    path: parent.display().to_string(),
+                }))
+            }
+            _ => Ok(()),
+        }
     }
 
     /// アトミック保存（一時ファイル経由）
-    fn atomic_save_impl(&self, path: &Path, content: &str) -> Result<()> {
+    fn atomic_save_impl(&self, path: &Path, content: &[u8]) -> Result<()> {
         let temp_path = self.generate_temp_path(path)?;
         file_debug_log!(self, "atomic_save: temp_path: {}", temp_path.display());
 
         // 一時ファイルに書き込み
         file_debug_log!(self, "atomic_save: writing to temp file");
-        std::fs::write(&temp_path, content.as_bytes()).map_err(|e| {
+        std::fs::write(&temp_path, content).map_err(|e| {
             file_debug_log!(self, "atomic_save: write to temp failed: {}", e);
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                // 一時ファイルを作成できないのはディレクトリの権限不足
+                let parent = temp_path.parent().unwrap_or(Path::new("."));
+                return AltreError::File(FileError::DirectoryNotWritable {
+                    path: parent.display().to_string(),
+                });
+            }
             AltreError::File(FileError::Io {
                 message: e.to_string(),
             })
@@ -160,8 +255,8 @@ impl FileSaver {
     }
 
     /// 直接保存
-    fn direct_save_impl(&self, path: &Path, content: &str) -> Result<()> {
-        std::fs::write(path, content.as_bytes()).map_err(|e| {
+    fn direct_save_impl(&self, path: &Path, content: &[u8]) -> Result<()> {
+        std::fs::write(path, content).map_err(|e| {
             AltreError::File(FileError::Io {
                 message: e.to_string(),
             })
@@ -219,6 +314,10 @@ pub struct FileBuffer {
     pub file_info: Option<FileInfo>,
     /// 読み取り専用フラグ
     pub read_only: bool,
+    /// 読み書きに使う文字コード
+    pub coding: CodingSystem,
+    /// 読み込んだファイルの改行コード（保存後は LF）
+    pub line_ending: LineEndingStyle,
 }
 
 impl FileBuffer {
@@ -226,19 +325,25 @@ impl FileBuffer {
     pub fn from_file(path: PathBuf) -> Result<Self> {
         let file_info = FileInfo::analyze(&path)?;
 
-        let content = if file_info.exists {
-            FileReader::new().read_file(&path)?
+        let (content, line_ending, coding) = if file_info.exists {
+            FileReader::new().read_file_with_coding(&path)?
         } else {
-            String::new()
+            (
+                String::new(),
+                LineEndingStyle::default(),
+                CodingSystem::default(),
+            )
         };
 
         Ok(FileBuffer {
             name: Self::generate_buffer_name(&path),
+            read_only: FileInfo::is_write_protected(&path),
             path: Some(path),
             content: content.clone(),
             change_tracker: FileChangeTracker::new(&content),
             file_info: Some(file_info),
-            read_only: false,
+            coding,
+            line_ending,
         })
     }
 
@@ -251,6 +356,8 @@ impl FileBuffer {
             change_tracker: FileChangeTracker::new(""),
             file_info: None,
             read_only: false,
+            coding: CodingSystem::default(),
+            line_ending: LineEndingStyle::default(),
         }
     }
 
@@ -267,8 +374,13 @@ impl FileBuffer {
         self.change_tracker.is_modified(&self.content)
     }
 
-    /// 保存処理
-    pub fn save(&mut self) -> Result<()> {
+    /// 文字コードと改行コードの表記（例: `UTF-8/LF`）
+    pub fn coding_label(&self) -> String {
+        format!("{}/{}", self.coding.label(), self.line_ending.label())
+    }
+
+    /// 保存処理（ディスク上の内容と同じで書き込まなかったときは `false` を返す）
+    pub fn save(&mut self) -> Result<bool> {
         let path = self.path.as_ref().ok_or_else(|| {
             AltreError::File(FileError::InvalidPath {
                 path: "No file associated with buffer".to_string(),
@@ -276,24 +388,55 @@ impl FileBuffer {
         })?;
 
         // 保存実行
-        FileSaver::new().save_file(path, &self.content)?;
+        let written = FileSaver::new().save_file_with_coding(path, &self.content, self.coding)?;
 
         // 変更状態リセット
         self.change_tracker.mark_saved(&self.content);
+        self.line_ending = LineEndingStyle::Lf;
 
-        Ok(())
+        Ok(written)
+    }
+
+    /// 保存しても書き込む内容がディスク上のファイルと変わらないか
+    pub fn matches_disk(&self) -> bool {
+        self.path
+            .as_ref()
+            .is_some_and(|path| FileSaver::new().matches_disk(path, &self.content, self.coding))
     }
 
     /// 別名で保存
     pub fn save_as(&mut self, path: PathBuf) -> Result<()> {
         NewFileHandler::handle_new_file(&path)?;
         self.set_path(path.clone());
-        FileSaver::new().save_file(&path, &self.content)?;
+        FileSaver::new().save_file_with_coding(&path, &self.content, self.coding)?;
         self.change_tracker.mark_saved(&self.content);
+        self.line_ending = LineEndingStyle::Lf;
         self.refresh_file_info()?;
         Ok(())
     }
 
+    /// ファイルを指定した文字コードで読み直す（未保存の変更は破棄される）
+    pub fn revert_with_coding(&mut self, coding: CodingSystem) -> Result<()> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            AltreError::File(FileError::InvalidPath {
+                path: "No file associated with buffer".to_string(),
+            })
+        })?;
+        let bytes = std::fs::read(path).map_err(|e| {
+            AltreError::File(FileError::Io {
+                message: e.to_string(),
+            })
+        })?;
+        let decoded = coding.decode(&bytes)?;
+        let content = LineEndingProcessor::normalize_to_lf(&decoded);
+
+        self.change_tracker = FileChangeTracker::new(&content);
+        self.content = content;
+        self.coding = coding;
+        self.line_ending = LineEndingProcessor::detect_line_endings(&decoded);
+        self.refresh_file_info()
+    }
+
     /// ファイル情報更新
     pub fn refresh_file_info(&mut self) -> Result<()> {
         if let Some(path) = &self.path {
@@ -351,11 +494,11 @@ impl FileOperationManager {
         FileBuffer::from_file(path)
     }
 
-    /// バッファを保存
-    pub fn save_buffer(&mut self, buffer: &mut FileBuffer) -> Result<()> {
+    /// バッファを保存し、実際に書き込んだかを返す
+    pub fn save_buffer(&mut self, buffer: &mut FileBuffer) -> Result<bool> {
         // 変更チェック
         if !buffer.is_modified() {
-            return Ok(()); // 変更なし
+            return Ok(false); // 変更なし
         }
 
         buffer.save()
@@ -396,6 +539,8 @@ impl FileOperationManager {
             change_tracker: FileChangeTracker::new(""),
             file_info: None,
             read_only: false,
+            coding: CodingSystem::default(),
+            line_ending: LineEndingStyle::default(),
         })
     }
 }
@@ -525,4 +670,72 @@ mod tests {
             assert_eq!(content, "target content");
         }
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_into_read_only_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked_dir = temp_dir.path().join("locked");
+        fs::create_dir(&locked_dir).unwrap();
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let result = FileSaver::new().save_file(&locked_dir.join("new.txt"), "content");
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        match result {
+            Err(AltreError::File(FileError::DirectoryNotWritable { path })) => {
+                assert_eq!(path, locked_dir.display().to_string());
+            }
+            other => panic!("Expected DirectoryNotWritable, got {:?}", other),
+        }
+        assert!(!locked_dir.join("new.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_over_read_only_file_is_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("readonly.txt");
+        fs::write(&test_file, "original").unwrap();
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o444)).unwrap();
+
+        let result = FileSaver::new().save_file(&test_file, "changed");
+
+        assert!(matches!(
+            result,
+            Err(AltreError::File(FileError::PermissionDenied { .. }))
+        ));
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_save_skips_write_when_disk_content_is_identical() {
+        use std::time::{Duration, SystemTime};
+
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("same.txt");
+        fs::write(&test_file, "same\n").unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&test_file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let mtime = || fs::metadata(&test_file).unwrap().modified().unwrap();
+
+        let saver = FileSaver::new();
+        assert!(saver.matches_disk(&test_file, "same\r\n", CodingSystem::Utf8));
+        saver.save_file(&test_file, "same\n").unwrap();
+        assert_eq!(mtime(), old);
+
+        assert!(!saver.matches_disk(&test_file, "changed\n", CodingSystem::Utf8));
+        saver.save_file(&test_file, "changed\n").unwrap();
+        assert_ne!(mtime(), old);
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "changed\n");
+    }
 }
@@ -152,6 +152,165 @@ pub fn expand_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
     DefaultPathProcessor::to_absolute(normalized)
 }
 
+/// `path:line` / `path:line:col` 形式の末尾を分離する
+///
+/// 行・桁は1始まり。末尾が数値でなければ `None` を返す。
+pub fn split_path_position(input: &str) -> Option<(&str, usize, Option<usize>)> {
+    let parse = |text: &str| {
+        (!text.is_empty() && text.chars().all(|ch| ch.is_ascii_digit()))
+            .then(|| text.parse::<usize>().ok())
+            .flatten()
+    };
+
+    let (rest, last) = input.rsplit_once(':')?;
+    let last = parse(last)?;
+    if let Some((path, line)) = rest.rsplit_once(':') {
+        if let Some(line) = parse(line) {
+            return (!path.is_empty()).then_some((path, line, Some(last)));
+        }
+    }
+    (!rest.is_empty()).then_some((rest, last, None))
+}
+
+/// ワイルドカード（`*` `?` `[...]`）を含むか
+pub fn has_wildcards(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+/// ファイル名がワイルドカードのパターンに一致するか
+///
+/// `*` は任意の文字列、`?` は任意の1文字、`[abc]` `[a-z]` `[!a]` は文字クラスに一致する。
+/// `.` で始まる名前は、パターンも `.` で始まるときだけ一致する。
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_chars(&pattern, &name)
+}
+
+/// 最後の `*` の位置だけを覚えて戻る2ポインタ法で照合する（入力長の積に比例する時間）
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // 直前の `*` の次のパターン位置と、その `*` が読み飛ばした名前の終端
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p + 1, n));
+            p += 1;
+        } else if let Some(len) = match_one(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // `*` にもう1文字読ませてやり直す
+            star = Some((star_p, star_n + 1));
+            p = star_p;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+/// パターン先頭の1文字分（`?`・文字クラス・通常の文字）が `ch` に一致すれば、そのパターンの長さを返す
+fn match_one(pattern: &[char], ch: char) -> Option<usize> {
+    match pattern.first()? {
+        '?' => Some(1),
+        '[' => match match_class(&pattern[1..]) {
+            Some((class, rest)) => class(ch).then_some(1 + rest),
+            // 閉じ括弧のない `[` は文字そのものとして扱う
+            None => (ch == '[').then_some(1),
+        },
+        &expected => (expected == ch).then_some(1),
+    }
+}
+
+/// `[` の後ろの文字クラスを解析し、判定関数と `]` の次までの長さを返す
+fn match_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool + '_, usize)> {
+    let negated = matches!(pattern.first(), Some('!') | Some('^'));
+    let start = usize::from(negated);
+    // 先頭の `]` は文字として扱う
+    let close = pattern
+        .iter()
+        .skip(start + 1)
+        .position(|&ch| ch == ']')
+        .map(|pos| pos + start + 1)?;
+    let members = &pattern[start..close];
+    let class = move |ch: char| {
+        let mut matched = false;
+        let mut index = 0;
+        while index < members.len() {
+            if index + 2 < members.len() && members[index + 1] == '-' {
+                matched |= (members[index]..=members[index + 2]).contains(&ch);
+                index += 3;
+            } else {
+                matched |= members[index] == ch;
+                index += 1;
+            }
+        }
+        matched != negated
+    };
+    Some((class, close + 1))
+}
+
+/// ワイルドカードを含むパスを展開し、一致する通常ファイルを名前順に返す
+///
+/// ディレクトリ部分にもワイルドカードを使える。読めないディレクトリは無視する。
+pub fn expand_wildcards<P: AsRef<Path>>(pattern: P) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::new()];
+    for component in pattern.as_ref().components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !matches!(component, Component::Normal(_)) || !has_wildcards(&part) {
+            for candidate in &mut candidates {
+                candidate.push(component.as_os_str());
+            }
+            continue;
+        }
+
+        let mut next = Vec::new();
+        for dir in &candidates {
+            let read_from = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir.as_path()
+            };
+            let Ok(entries) = std::fs::read_dir(read_from) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if wildcard_match(&part, &name.to_string_lossy()) {
+                    next.push(dir.join(name));
+                }
+            }
+        }
+        candidates = next;
+    }
+
+    let mut files: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+/// 2 つのパスが同じファイルを指すか（シンボリックリンクを解決して比べる）
+///
+/// どちらかが存在せず解決できないときは、パスの文字列どうしを比べる。
+pub fn same_file_path<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    if a == b {
+        return true;
+    }
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
 /// パス正規化の便利関数
 pub fn normalize_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
     DefaultPathProcessor::normalize_path(path)
@@ -251,6 +410,87 @@ pub fn relative_path<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<P
 mod tests {
     use super::*;
 
+    #[test]
+    fn split_path_position_parses_line_and_column() {
+        assert_eq!(
+            split_path_position("src/main.rs:10"),
+            Some(("src/main.rs", 10, None))
+        );
+        assert_eq!(
+            split_path_position("src/main.rs:10:5"),
+            Some(("src/main.rs", 10, Some(5)))
+        );
+        assert_eq!(split_path_position("a:b:3"), Some(("a:b", 3, None)));
+        assert_eq!(split_path_position("notes.txt"), None);
+        assert_eq!(split_path_position("notes:txt"), None);
+        assert_eq!(split_path_position(":10"), None);
+        assert_eq!(split_path_position("file:+1"), None);
+    }
+
+    #[test]
+    fn wildcard_match_supports_star_question_and_classes() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(!wildcard_match("*.rs", "main.rsx"));
+        assert!(wildcard_match("a?c", "abc"));
+        assert!(!wildcard_match("a?c", "ac"));
+        assert!(wildcard_match("[ab]*", "beta"));
+        assert!(!wildcard_match("[!ab]*", "beta"));
+        assert!(wildcard_match("file[0-9].txt", "file7.txt"));
+        assert!(wildcard_match("x[", "x["));
+        assert!(!wildcard_match("*", ".hidden"));
+        assert!(wildcard_match(".*", ".hidden"));
+        assert!(wildcard_match("a*b*c", "axxbyybzc"));
+        assert!(!wildcard_match("a*b*c", "axxbyyb"));
+        assert!(wildcard_match("*[0-9]?", "log12"));
+    }
+
+    #[test]
+    fn wildcard_match_with_many_stars_finishes_quickly() {
+        let name = "a".repeat(64);
+        let pattern = format!("{}b", "a*".repeat(32));
+        assert!(!wildcard_match(&pattern, &name));
+        assert!(wildcard_match(&"a*".repeat(32), &name));
+    }
+
+    #[test]
+    fn expand_wildcards_returns_sorted_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.rs", "a.rs", "c.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        std::fs::create_dir(dir.path().join("sub.rs")).unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("nested").join("d.rs"), "").unwrap();
+
+        assert_eq!(
+            expand_wildcards(dir.path().join("*.rs")),
+            vec![dir.path().join("a.rs"), dir.path().join("b.rs")]
+        );
+        assert_eq!(
+            expand_wildcards(dir.path().join("n*").join("*.rs")),
+            vec![dir.path().join("nested").join("d.rs")]
+        );
+        assert!(expand_wildcards(dir.path().join("*.md")).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn same_file_path_resolves_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real.txt");
+        let link = dir.path().join("link.txt");
+        std::fs::write(&real, "").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        assert!(same_file_path(&real, &link));
+        assert!(same_file_path(&real, &real));
+        assert!(!same_file_path(&real, dir.path().join("missing.txt")));
+        assert!(same_file_path(
+            dir.path().join("missing.txt"),
+            dir.path().join("missing.txt")
+        ));
+    }
+
     #[test]
     fn test_normalize_path() {
         let path = PathBuf::from("./a/../b/./c");
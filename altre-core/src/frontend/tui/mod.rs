@@ -1,7 +1,7 @@
 use crate::core::{Backend, RenderMetadata, RenderView};
-use crate::error::{AltreError, Result, UiError};
-use crate::ui::{AdvancedRenderer, StatusLineInfo};
-use crossterm::event::{self, Event};
+use crate::error::{self, AltreError, Result, UiError};
+use crate::ui::{AdvancedRenderer, StatusLineInfo, WindowEditors};
+use crossterm::event::{self, DisableBracketedPaste, EnableBracketedPaste, Event};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
@@ -17,29 +17,28 @@ pub struct TuiApplication {
 
 impl TuiApplication {
     pub fn new() -> Result<Self> {
-        let backend = Backend::new()?;
+        let mut backend = Backend::new()?;
+        // init.al の `(set-option "auto-save-session" #t)` などで有効にしていれば前回のセッションを復元
+        backend.restore_session_on_startup();
         let renderer = AdvancedRenderer::new();
         Ok(Self { backend, renderer })
     }
 
     pub fn run(&mut self) -> Result<()> {
-        enter_terminal()?;
-
-        let backend = CrosstermBackend::new(stdout());
-        let mut terminal =
-            Terminal::new(backend).map_err(|err| terminal_error("terminal init", err))?;
-        terminal
-            .hide_cursor()
-            .map_err(|err| terminal_error("hide cursor", err))?;
-
-        let loop_result = self.event_loop(&mut terminal);
-        let show_cursor_result = terminal
-            .show_cursor()
-            .map_err(|err| terminal_error("show cursor", err));
-        drop(terminal);
-        let cleanup_result = leave_terminal();
-
-        loop_result.and(show_cursor_result).and(cleanup_result)
+        run_in_terminal(enter_terminal, leave_terminal, || {
+            let backend = CrosstermBackend::new(stdout());
+            let mut terminal =
+                Terminal::new(backend).map_err(|err| terminal_error("terminal init", err))?;
+            terminal
+                .hide_cursor()
+                .map_err(|err| terminal_error("hide cursor", err))?;
+
+            let loop_result = self.event_loop(&mut terminal);
+            let show_cursor_result = terminal
+                .show_cursor()
+                .map_err(|err| terminal_error("show cursor", err));
+            loop_result.and(show_cursor_result)
+        })
     }
 
     fn event_loop<B: ratatui::backend::Backend>(
@@ -48,15 +47,22 @@ impl TuiApplication {
     ) -> Result<()> {
         while self.backend.is_running() {
             self.backend.process_minibuffer_timer();
+            self.backend.process_pending_insert();
+            self.backend.process_idle_timers();
             self.render(terminal)?;
 
-            if event::poll(Duration::from_millis(16))
-                .map_err(|err| terminal_error("event poll", err))?
-            {
+            // 分割挿入中は待たずに次のチャンクへ進む
+            let timeout = if self.backend.is_inserting() {
+                Duration::ZERO
+            } else {
+                Duration::from_millis(16)
+            };
+            if event::poll(timeout).map_err(|err| terminal_error("event poll", err))? {
                 match event::read().map_err(|err| terminal_error("event read", err))? {
                     Event::Key(key_event) => self.backend.handle_key_event(key_event)?,
+                    Event::Paste(text) => self.backend.paste_text(&text),
                     Event::Resize(_, _) => {}
-                    Event::Mouse(_) | Event::FocusGained | Event::FocusLost | Event::Paste(_) => {}
+                    Event::Mouse(_) | Event::FocusGained | Event::FocusLost => {}
                 }
             }
         }
@@ -66,17 +72,31 @@ impl TuiApplication {
 
     fn render<B: ratatui::backend::Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         let metadata: RenderMetadata = self.backend.render_metadata();
+        self.renderer
+            .set_completion_popup(metadata.completion_popup.clone());
+        self.renderer
+            .set_chrome_visible(metadata.mode_line_visible, metadata.minibuffer_visible);
+        self.renderer.set_line_wrap(metadata.line_wrap());
+        self.renderer
+            .set_control_char_display(metadata.control_char_display);
+        self.renderer
+            .theme_manager()
+            .set_theme(&metadata.theme_name);
         let view: RenderView<'_> = self.backend.render_view();
 
         let status_info = StatusLineInfo {
             file_label: metadata.status_label.as_str(),
             is_modified: metadata.is_modified,
+            coding_label: metadata.coding_label.as_str(),
         };
 
         self.renderer
             .render(
                 terminal,
-                view.editor,
+                WindowEditors {
+                    current: view.editor,
+                    others: Some(view.other_editors),
+                },
                 view.window_manager,
                 view.minibuffer,
                 metadata.search_ui.as_ref(),
@@ -87,17 +107,32 @@ impl TuiApplication {
     }
 }
 
+/// 端末を準備して `body` を実行し、成否にかかわらず端末を元に戻す
+///
+/// 実行中は復元処理を登録しておき、パニックしても端末が raw モードのまま残らないようにする。
+fn run_in_terminal<T>(
+    enter: fn() -> Result<()>,
+    leave: error::TerminalRestore,
+    body: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    enter()?;
+    error::set_terminal_restore(leave);
+    let result = body();
+    let cleanup_result = error::restore_terminal_state();
+    result.and_then(|value| cleanup_result.map(|_| value))
+}
+
 fn enter_terminal() -> Result<()> {
     enable_raw_mode().map_err(|err| terminal_error("enable raw mode", err))?;
     let mut out = stdout();
-    execute!(out, EnterAlternateScreen)
+    execute!(out, EnterAlternateScreen, EnableBracketedPaste)
         .map_err(|err| terminal_error("enter alternate screen", err))?;
     Ok(())
 }
 
 fn leave_terminal() -> Result<()> {
     let mut out = stdout();
-    execute!(out, LeaveAlternateScreen)
+    execute!(out, DisableBracketedPaste, LeaveAlternateScreen)
         .map_err(|err| terminal_error("leave alternate screen", err))?;
     disable_raw_mode().map_err(|err| terminal_error("disable raw mode", err))?;
     Ok(())
@@ -108,3 +143,60 @@ fn terminal_error(context: &str, err: impl std::fmt::Display) -> AltreError {
         component: format!("{}: {}", context, err),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static EVENTS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    fn record(event: &'static str) {
+        EVENTS.lock().unwrap().push(event);
+    }
+
+    fn fake_enter() -> Result<()> {
+        record("enter");
+        Ok(())
+    }
+
+    fn fake_leave() -> Result<()> {
+        record("leave");
+        Ok(())
+    }
+
+    #[test]
+    fn loop_error_restores_terminal_before_returning() {
+        let _guard = error::terminal_restore_test_lock();
+        EVENTS.lock().unwrap().clear();
+
+        let result: Result<()> = run_in_terminal(fake_enter, fake_leave, || {
+            record("loop");
+            Err(AltreError::Application("ループで失敗".to_string()))
+        });
+
+        assert!(matches!(result, Err(AltreError::Application(_))));
+        assert_eq!(*EVENTS.lock().unwrap(), vec!["enter", "loop", "leave"]);
+
+        // 復元済みなので、後から報告しても二重には戻さない
+        error::report_error_to(&mut Vec::new(), &result.unwrap_err(), "test");
+        assert_eq!(EVENTS.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn restore_is_registered_while_the_loop_runs() {
+        let _guard = error::terminal_restore_test_lock();
+        EVENTS.lock().unwrap().clear();
+
+        let value = run_in_terminal(fake_enter, fake_leave, || {
+            // パニックハンドラが呼ぶのと同じ経路で復元できる
+            error::restore_terminal_state()?;
+            record("loop");
+            Ok(7)
+        })
+        .unwrap();
+
+        assert_eq!(value, 7);
+        assert_eq!(*EVENTS.lock().unwrap(), vec!["enter", "leave", "loop"]);
+    }
+}
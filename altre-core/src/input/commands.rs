@@ -4,7 +4,9 @@
 
 use crate::buffer::{EditOperations, NavigationAction, TextEditor};
 use crate::editor::{edit_utils, KillRing};
+use crate::error::{AltreError, ErrorDisplay};
 use crate::file::{expand_path, FileBuffer, FileOperationManager};
+use std::path::PathBuf;
 /// コマンド実行の結果
 #[derive(Debug, Clone)]
 pub struct CommandResult {
@@ -109,35 +111,79 @@ pub enum Command {
     NewlineAndIndent,
     OpenLine,
     GotoLine,
+    GotoLineOtherWindow,
+    ScrollLineToTop,
+    GotoChar,
+    GotoColumn,
+    NextError,
+    PreviousError,
     KillWordForward,
     KillWordBackward,
     KillLine,
     Yank,
     YankPop,
     KeyboardQuit,
+    UniversalArgument,
     Undo,
     Redo,
     SetMark,
     KillRegion,
     CopyRegion,
+    CopyWholeBuffer,
+    CopyBufferPath,
     ExchangePointAndMark,
     MarkBuffer,
     ScrollPageDown,
     ScrollPageUp,
     Recenter,
+    RecenterTop,
+    RecenterCenter,
+    RecenterBottom,
     ScrollLeft,
     ScrollRight,
+    ToggleFold,
+    ToggleAutoCompletion,
+    CloseParens,
+    QuotedInsert,
+    InsertCharByCode,
+    InsertPair(char),
+    DescribeFunction,
+    ToggleDebugOnError,
+    ResetAlispEnv,
+    JustOneSpace,
+    DeleteHorizontalSpace,
+    RepeatComplex,
+    ToggleCharCase,
+    Tabify,
+    Untabify,
+    CenterLine,
+    SetFillColumn,
+    FillParagraph,
+    CapitalizeDwim,
+    ToggleWordWrap,
+    ToggleTruncateLines,
+    CycleControlCharDisplay,
+    ToggleKeywordHighlight,
+    ToggleLazyHighlight,
+    ToggleDistractionFree,
 
     // ファイル操作
     FindFile,
     SaveBuffer,
     WriteFile,      // C-x C-w (別名保存)
     SaveAllBuffers, // C-x s (全バッファ保存)
+    CdBuffer,
+    RevertBufferWithCodingSystem,
+    ExportHtml,
+    SaveSession,
+    RestoreSession,
 
     // バッファ操作
     SwitchToBuffer, // C-x b
     KillBuffer,     // C-x k
     ListBuffers,    // C-x C-b
+    CloneBuffer,
+    ToggleReadOnly, // C-x C-q
 
     // ウィンドウ操作
     SplitWindowBelow,   // C-x 2
@@ -145,6 +191,8 @@ pub enum Command {
     DeleteOtherWindows, // C-x 1
     DeleteWindow,       // C-x 0
     OtherWindow,        // C-x o
+    OtherWindowSameBuffer,
+    SwapWindowBuffers,
 
     // アプリケーション制御
     SaveBuffersKillTerminal,
@@ -154,9 +202,24 @@ pub enum Command {
     MoveLineEnd,
     MoveBufferStart,
     MoveBufferEnd,
+    ForwardParagraph,
+    BackwardParagraph,
     EvalExpression,
     QueryReplace,
     RegexQueryReplace,
+    CountMatches,
+    CountMatchesLiteral,
+    Occur,
+    OccurGotoOccurrence,
+    OccurRefresh,
+    IsearchForward,
+    IsearchForwardFromTop,
+    IsearchForwardRegexp,
+    IsearchBackwardRegexp,
+    FlushLines,
+    KeepLines,
+    StringRectangle,
+    FindProblematicChars,
 
     // 未知のコマンド
     Unknown(String),
@@ -179,36 +242,85 @@ impl Command {
             "newline-and-indent" => Command::NewlineAndIndent,
             "open-line" => Command::OpenLine,
             "goto-line" => Command::GotoLine,
+            "goto-line-other-window" => Command::GotoLineOtherWindow,
+            "scroll-line-to-top" => Command::ScrollLineToTop,
+            "goto-char" => Command::GotoChar,
+            "goto-column" | "move-to-column" => Command::GotoColumn,
+            "next-error" => Command::NextError,
+            "previous-error" => Command::PreviousError,
             "kill-word" => Command::KillWordForward,
             "backward-kill-word" => Command::KillWordBackward,
             "kill-line" => Command::KillLine,
             "yank" => Command::Yank,
             "yank-pop" => Command::YankPop,
             "keyboard-quit" => Command::KeyboardQuit,
+            "universal-argument" => Command::UniversalArgument,
             "undo" => Command::Undo,
             "redo" => Command::Redo,
             "set-mark-command" => Command::SetMark,
             "kill-region" => Command::KillRegion,
             "copy-region-as-kill" => Command::CopyRegion,
+            "copy-whole-buffer" => Command::CopyWholeBuffer,
+            "copy-buffer-path" => Command::CopyBufferPath,
             "exchange-point-and-mark" => Command::ExchangePointAndMark,
             "mark-whole-buffer" => Command::MarkBuffer,
             "scroll-up" => Command::ScrollPageDown,
             "scroll-down" => Command::ScrollPageUp,
             "recenter-top-bottom" => Command::Recenter,
+            "recenter-top" => Command::RecenterTop,
+            "recenter-center" | "recenter" => Command::RecenterCenter,
+            "recenter-bottom" => Command::RecenterBottom,
             "scroll-left" => Command::ScrollLeft,
             "scroll-right" => Command::ScrollRight,
+            "toggle-fold" => Command::ToggleFold,
+            "auto-completion-mode" => Command::ToggleAutoCompletion,
+            "close-all-parens" => Command::CloseParens,
+            "quoted-insert" => Command::QuotedInsert,
+            "insert-char" => Command::InsertCharByCode,
+            "insert-parentheses" => Command::InsertPair('('),
+            "insert-brackets" => Command::InsertPair('['),
+            "insert-braces" => Command::InsertPair('{'),
+            "insert-quotes" => Command::InsertPair('"'),
+            "describe-function" | "describe-command" => Command::DescribeFunction,
+            "toggle-debug-on-error" => Command::ToggleDebugOnError,
+            "reset-alisp-env" => Command::ResetAlispEnv,
+            "just-one-space" => Command::JustOneSpace,
+            "delete-horizontal-space" => Command::DeleteHorizontalSpace,
+            "repeat-complex-command" => Command::RepeatComplex,
+            "toggle-char-case" => Command::ToggleCharCase,
+            "tabify" => Command::Tabify,
+            "untabify" => Command::Untabify,
+            "center-line" => Command::CenterLine,
+            "set-fill-column" => Command::SetFillColumn,
+            "fill-paragraph" => Command::FillParagraph,
+            "capitalize-dwim" => Command::CapitalizeDwim,
+            "toggle-word-wrap" => Command::ToggleWordWrap,
+            "toggle-truncate-lines" => Command::ToggleTruncateLines,
+            "cycle-control-char-display" => Command::CycleControlCharDisplay,
+            "toggle-keyword-highlight" => Command::ToggleKeywordHighlight,
+            "toggle-lazy-highlight" => Command::ToggleLazyHighlight,
+            "toggle-distraction-free" => Command::ToggleDistractionFree,
             "find-file" => Command::FindFile,
             "save-buffer" => Command::SaveBuffer,
             "write-file" => Command::WriteFile,
             "save-some-buffers" => Command::SaveAllBuffers,
+            "cd" => Command::CdBuffer,
+            "revert-buffer-with-coding-system" => Command::RevertBufferWithCodingSystem,
+            "export-html" | "htmlize-buffer" => Command::ExportHtml,
+            "save-session" => Command::SaveSession,
+            "restore-session" => Command::RestoreSession,
             "switch-to-buffer" => Command::SwitchToBuffer,
             "kill-buffer" => Command::KillBuffer,
             "list-buffers" => Command::ListBuffers,
+            "clone-buffer" => Command::CloneBuffer,
+            "toggle-read-only" | "read-only-mode" => Command::ToggleReadOnly,
             "split-window-below" => Command::SplitWindowBelow,
             "split-window-right" => Command::SplitWindowRight,
             "delete-other-windows" => Command::DeleteOtherWindows,
             "delete-window" => Command::DeleteWindow,
             "other-window" => Command::OtherWindow,
+            "other-window-same-buffer" => Command::OtherWindowSameBuffer,
+            "swap-window-buffers" => Command::SwapWindowBuffers,
             "save-buffers-kill-terminal" => Command::SaveBuffersKillTerminal,
             "quit" => Command::Quit,
             "execute-command" => Command::ExecuteCommand,
@@ -217,8 +329,23 @@ impl Command {
             "move-end-of-line" => Command::MoveLineEnd,
             "beginning-of-buffer" => Command::MoveBufferStart,
             "end-of-buffer" => Command::MoveBufferEnd,
+            "forward-paragraph" => Command::ForwardParagraph,
+            "backward-paragraph" => Command::BackwardParagraph,
             "query-replace" => Command::QueryReplace,
             "query-replace-regexp" => Command::RegexQueryReplace,
+            "how-many" | "count-matches" => Command::CountMatches,
+            "how-many-literal" => Command::CountMatchesLiteral,
+            "occur" => Command::Occur,
+            "occur-mode-goto-occurrence" => Command::OccurGotoOccurrence,
+            "occur-refresh" => Command::OccurRefresh,
+            "isearch-forward" => Command::IsearchForward,
+            "isearch-forward-from-top" => Command::IsearchForwardFromTop,
+            "isearch-forward-regexp" => Command::IsearchForwardRegexp,
+            "isearch-backward-regexp" => Command::IsearchBackwardRegexp,
+            "flush-lines" | "delete-matching-lines" => Command::FlushLines,
+            "keep-lines" | "delete-non-matching-lines" => Command::KeepLines,
+            "string-rectangle" => Command::StringRectangle,
+            "find-problematic-chars" => Command::FindProblematicChars,
             _ => Command::Unknown(cmd.to_string()),
         }
     }
@@ -241,35 +368,83 @@ impl Command {
             Command::Yank => "キルリングから貼り付け",
             Command::YankPop => "直前のヤンクを置き換え",
             Command::KeyboardQuit => "操作をキャンセル",
+            Command::UniversalArgument => "次のコマンドに数引数を渡す",
             Command::Undo => "直前の操作を取り消し",
             Command::Redo => "取り消した操作をやり直し",
             Command::SetMark => "マークを設定",
             Command::KillRegion => "リージョンを削除",
             Command::CopyRegion => "リージョンをコピー",
+            Command::CopyWholeBuffer => "バッファ全体をキルリングにコピー",
+            Command::CopyBufferPath => "バッファのファイルパスをキルリングにコピー",
             Command::ExchangePointAndMark => "カーソルとマークを交換",
             Command::MarkBuffer => "バッファ全体を選択",
             Command::ScrollPageDown => "画面を下にスクロール",
             Command::ScrollPageUp => "画面を上にスクロール",
             Command::Recenter => "画面を再配置",
+            Command::RecenterTop => "カーソル行をウィンドウの先頭に表示",
+            Command::RecenterCenter => "カーソル行をウィンドウの中央に表示",
+            Command::RecenterBottom => "カーソル行をウィンドウの末尾に表示",
             Command::ScrollLeft => "画面を左にスクロール",
             Command::ScrollRight => "画面を右にスクロール",
+            Command::ToggleFold => "ブロックの折りたたみを切り替え",
+            Command::ToggleAutoCompletion => "アイドル時の自動補完を切り替え",
+            Command::CloseParens => "閉じていない括弧をすべて閉じる",
+            Command::QuotedInsert => "次に押したキーをそのまま挿入",
+            Command::InsertCharByCode => "Unicode のコードポイントを指定して文字を挿入",
+            Command::InsertPair(_) => "リージョンを区切り文字の組で囲む（なければ空の組を挿入）",
+            Command::DescribeFunction => "コマンドや alisp 関数の説明を表示",
+            Command::ToggleDebugOnError => "alisp のエラー時にバックトレースを表示するか切り替え",
+            Command::ResetAlispEnv => "alisp の評価環境の定義を消して初期状態に戻す",
+            Command::JustOneSpace => "カーソル周辺の空白を1つにまとめる",
+            Command::DeleteHorizontalSpace => "カーソル周辺の空白とタブを削除",
+            Command::RepeatComplex => "直前のミニバッファ入力コマンドを編集して再実行",
+            Command::ToggleCharCase => "カーソル位置の文字の大文字・小文字を反転",
+            Command::Tabify => "リージョンの空白をタブにまとめる",
+            Command::Untabify => "リージョンのタブを空白に展開",
+            Command::CenterLine => "行（リージョンでは各行）を fill-column の中央に寄せる",
+            Command::SetFillColumn => {
+                "このバッファの fill-column をカーソルの桁（C-u で入力値）にする"
+            }
+            Command::FillParagraph => "段落を fill-column の幅で詰め直す",
+            Command::CapitalizeDwim => "リージョンまたは次の単語の先頭を大文字にする",
+            Command::ToggleWordWrap => "折り返しの単位を単語境界と文字境界で切り替え",
+            Command::ToggleTruncateLines => "長い行の切り詰め表示と折り返し表示を切り替え",
+            Command::CycleControlCharDisplay => "制御文字と見えない文字の表示方法を順に切り替え",
+            Command::ToggleKeywordHighlight => "TODO/FIXME などのキーワードのハイライトを切り替え",
+            Command::ToggleLazyHighlight => "検索中に現在の一致以外もハイライトするかを切り替え",
+            Command::ToggleDistractionFree => "モードラインを隠す集中モードを切り替え",
             Command::InsertNewline => "改行を挿入",
             Command::IndentForTab => "タブ幅に沿ってインデント",
             Command::NewlineAndIndent => "改行してインデント",
             Command::OpenLine => "カーソル位置に空行を開く",
             Command::GotoLine => "指定行へ移動",
+            Command::GotoLineOtherWindow => "別ウィンドウで指定行へ移動",
+            Command::ScrollLineToTop => "指定行がウィンドウの先頭になるようスクロール",
+            Command::GotoChar => "指定した文字位置へ移動",
+            Command::GotoColumn => "現在行の指定した表示桁へ移動",
+            Command::NextError => "次のエラー位置へ移動",
+            Command::PreviousError => "前のエラー位置へ移動",
             Command::FindFile => "ファイルを開く",
             Command::SaveBuffer => "バッファを保存",
             Command::WriteFile => "別名でファイルを保存",
             Command::SaveAllBuffers => "すべてのバッファを保存",
+            Command::CdBuffer => "バッファの既定ディレクトリを変更",
+            Command::RevertBufferWithCodingSystem => "文字コードを指定してファイルを読み直す",
+            Command::ExportHtml => "バッファをハイライト付きの HTML ファイルに書き出す",
+            Command::SaveSession => "セッションを保存",
+            Command::RestoreSession => "セッションを復元",
             Command::SwitchToBuffer => "バッファを切り替え",
             Command::KillBuffer => "バッファを削除",
             Command::ListBuffers => "バッファ一覧を表示",
+            Command::CloneBuffer => "バッファを複製",
+            Command::ToggleReadOnly => "バッファの読み取り専用を切り替え",
             Command::SplitWindowBelow => "ウィンドウを上下に分割",
             Command::SplitWindowRight => "ウィンドウを左右に分割",
             Command::DeleteOtherWindows => "現在のウィンドウのみ表示",
             Command::DeleteWindow => "現在のウィンドウを閉じる",
             Command::OtherWindow => "次のウィンドウに移動",
+            Command::OtherWindowSameBuffer => "同じバッファを表示している次のウィンドウに移動",
+            Command::SwapWindowBuffers => "現在のウィンドウと次のウィンドウのバッファを入れ替え",
             Command::SaveBuffersKillTerminal => "保存して終了",
             Command::Quit => "終了",
             Command::ExecuteCommand => "コマンドを実行",
@@ -278,8 +453,23 @@ impl Command {
             Command::MoveLineEnd => "行末に移動",
             Command::MoveBufferStart => "バッファ先頭に移動",
             Command::MoveBufferEnd => "バッファ末尾に移動",
+            Command::ForwardParagraph => "次の段落の終わりに移動",
+            Command::BackwardParagraph => "前の段落の始まりに移動",
             Command::QueryReplace => "クエリ置換を実行",
             Command::RegexQueryReplace => "正規表現クエリ置換を実行",
+            Command::CountMatches => "カーソル以降で正規表現に一致する箇所を数える",
+            Command::CountMatchesLiteral => "カーソル以降で文字列に一致する箇所を数える",
+            Command::Occur => "文字列に一致する箇所を *Occur* バッファに一覧表示",
+            Command::OccurGotoOccurrence => "*Occur* バッファで選んだ一致箇所へ移動",
+            Command::OccurRefresh => "*Occur* バッファの一覧を作り直す",
+            Command::IsearchForward => "カーソル位置からインクリメンタル検索",
+            Command::IsearchForwardFromTop => "バッファ先頭からインクリメンタル検索",
+            Command::IsearchForwardRegexp => "正規表現で前方へインクリメンタル検索",
+            Command::IsearchBackwardRegexp => "正規表現で後方へインクリメンタル検索",
+            Command::FlushLines => "正規表現に一致する行を削除",
+            Command::KeepLines => "正規表現に一致しない行を削除",
+            Command::StringRectangle => "矩形の各行を入力した文字列で置き換える",
+            Command::FindProblematicChars => "次の制御文字・幅 0 の見えない文字へ移動",
             Command::Unknown(_) => "不明なコマンド",
         }
     }
@@ -353,6 +543,8 @@ impl CommandProcessor {
                 change_tracker,
                 file_info: None,
                 read_only: false,
+                coding: Default::default(),
+                line_ending: Default::default(),
             });
         } else if let Some(ref mut buffer) = self.current_buffer {
             // バッファの内容を更新
@@ -446,24 +638,83 @@ impl CommandProcessor {
             Command::ScrollPageDown
             | Command::ScrollPageUp
             | Command::Recenter
+            | Command::RecenterTop
+            | Command::RecenterCenter
+            | Command::RecenterBottom
             | Command::ScrollLeft
             | Command::ScrollRight
+            | Command::ToggleFold
+            | Command::ToggleAutoCompletion
+            | Command::CloseParens
+            | Command::QuotedInsert
+            | Command::InsertCharByCode
+            | Command::InsertPair(_)
+            | Command::DescribeFunction
+            | Command::ToggleDebugOnError
+            | Command::ResetAlispEnv
+            | Command::JustOneSpace
+            | Command::DeleteHorizontalSpace
+            | Command::RepeatComplex
+            | Command::ToggleCharCase
+            | Command::Tabify
+            | Command::Untabify
+            | Command::CenterLine
+            | Command::SetFillColumn
+            | Command::FillParagraph
+            | Command::CapitalizeDwim
+            | Command::ToggleWordWrap
+            | Command::ToggleTruncateLines
+            | Command::CycleControlCharDisplay
+            | Command::ToggleKeywordHighlight
+            | Command::ToggleLazyHighlight
+            | Command::ToggleDistractionFree
             | Command::SplitWindowBelow
             | Command::SplitWindowRight
             | Command::DeleteOtherWindows
             | Command::DeleteWindow
             | Command::OtherWindow
+            | Command::OtherWindowSameBuffer
+            | Command::SwapWindowBuffers
             | Command::SwitchToBuffer
             | Command::KillBuffer
             | Command::ListBuffers
+            | Command::CloneBuffer
+            | Command::ToggleReadOnly
+            | Command::CdBuffer
+            | Command::RevertBufferWithCodingSystem
+            | Command::ExportHtml
+            | Command::SaveSession
+            | Command::RestoreSession
             | Command::SetMark
             | Command::KillRegion
             | Command::CopyRegion
+            | Command::UniversalArgument
+            | Command::CopyWholeBuffer
+            | Command::CopyBufferPath
             | Command::ExchangePointAndMark
             | Command::MarkBuffer
             | Command::GotoLine
+            | Command::GotoLineOtherWindow
+            | Command::ScrollLineToTop
+            | Command::GotoChar
+            | Command::GotoColumn
+            | Command::NextError
+            | Command::PreviousError
             | Command::QueryReplace
-            | Command::RegexQueryReplace => {
+            | Command::RegexQueryReplace
+            | Command::CountMatches
+            | Command::CountMatchesLiteral
+            | Command::Occur
+            | Command::OccurGotoOccurrence
+            | Command::OccurRefresh
+            | Command::IsearchForward
+            | Command::IsearchForwardFromTop
+            | Command::IsearchForwardRegexp
+            | Command::IsearchBackwardRegexp
+            | Command::FlushLines
+            | Command::KeepLines
+            | Command::StringRectangle
+            | Command::FindProblematicChars => {
                 CommandResult::error("このコマンドはアプリ側で処理します".to_string())
             }
             Command::FindFile => self.execute_find_file(),
@@ -478,6 +729,8 @@ impl CommandProcessor {
             Command::MoveLineEnd => self.navigate(NavigationAction::MoveLineEnd),
             Command::MoveBufferStart => self.navigate(NavigationAction::MoveBufferStart),
             Command::MoveBufferEnd => self.navigate(NavigationAction::MoveBufferEnd),
+            Command::ForwardParagraph => self.navigate(NavigationAction::MoveParagraphForward),
+            Command::BackwardParagraph => self.navigate(NavigationAction::MoveParagraphBackward),
             Command::Undo | Command::Redo => {
                 CommandResult::error("このコマンドはアプリ側で処理します".to_string())
             }
@@ -742,6 +995,13 @@ impl CommandProcessor {
     }
 
     fn execute_save_buffer(&mut self) -> CommandResult {
+        self.save_current_buffer().0
+    }
+
+    /// カレントバッファを保存し、結果と実際に書き込んだパスを返す
+    ///
+    /// 変更がないときや、ディスク上の内容と同じで書き込まなかったときのパスは `None`。
+    pub fn save_current_buffer(&mut self) -> (CommandResult, Option<PathBuf>) {
         self.reset_command_context();
         if let Some(ref mut buffer) = self.current_buffer {
             // エディタの内容をバッファに同期
@@ -749,23 +1009,39 @@ impl CommandProcessor {
 
             // 変更がない場合はスキップ
             if !buffer.is_modified() {
-                return CommandResult::success_with_message("変更なし".to_string());
+                return (
+                    CommandResult::success_with_message("変更なし".to_string()),
+                    None,
+                );
             }
 
-            // 保存実行
+            // 保存実行（ディスク上の内容と同じなら書き込まずに保存済みとして扱う）
             match self.file_manager.save_buffer(buffer) {
-                Ok(_) => CommandResult::success_with_message(format!(
-                    "バッファを保存しました: {}",
-                    buffer
-                        .path
-                        .as_ref()
-                        .map(|p| p.display().to_string())
-                        .unwrap_or_else(|| "未名".to_string())
-                )),
-                Err(err) => CommandResult::error(format!("保存エラー: {}", err)),
+                Ok(false) => (
+                    CommandResult::success_with_message("変更なし".to_string()),
+                    None,
+                ),
+                Ok(true) => (
+                    CommandResult::success_with_message(format!(
+                        "バッファを保存しました: {}",
+                        buffer
+                            .path
+                            .as_ref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_else(|| "未名".to_string())
+                    )),
+                    buffer.path.clone(),
+                ),
+                Err(err) => (
+                    CommandResult::error(format!("保存エラー: {}", describe_save_error(&err))),
+                    None,
+                ),
             }
         } else {
-            CommandResult::error("保存するバッファがありません".to_string())
+            (
+                CommandResult::error("保存するバッファがありません".to_string()),
+                None,
+            )
         }
     }
 
@@ -813,7 +1089,7 @@ impl CommandProcessor {
                     eprintln!("保存先パス: {}", expanded_path.display());
                     CommandResult::error(format!(
                         "保存エラー: {} (パス: {})",
-                        err,
+                        describe_save_error(&err),
                         expanded_path.display()
                     ))
                 }
@@ -847,6 +1123,14 @@ impl Default for CommandProcessor {
     }
 }
 
+/// 保存エラーの表示用文字列（ファイルエラーは原因まで日本語で表示する）
+fn describe_save_error(err: &AltreError) -> String {
+    match err {
+        AltreError::File(_) => ErrorDisplay::new(err).message,
+        other => other.to_string(),
+    }
+}
+
 /// コマンドの実行コンテキスト
 #[derive(Debug)]
 pub struct CommandContext {
@@ -972,6 +1256,24 @@ mod tests {
         assert!(quit.should_quit);
     }
 
+    #[test]
+    fn test_save_error_is_described_in_japanese() {
+        use crate::error::FileError;
+
+        let message = describe_save_error(&AltreError::File(FileError::DirectoryNotWritable {
+            path: "/tmp/readonly".to_string(),
+        }));
+        assert_eq!(
+            message,
+            "保存先ディレクトリに書き込み権限がありません: /tmp/readonly"
+        );
+
+        let message = describe_save_error(&AltreError::File(FileError::PermissionDenied {
+            path: "/tmp/a.txt".to_string(),
+        }));
+        assert_eq!(message, "アクセス権限がありません: /tmp/a.txt");
+    }
+
     #[test]
     fn test_command_processor() {
         let mut processor = CommandProcessor::new();
@@ -463,6 +463,22 @@ impl Key {
             && matches!(self.code, KeyCode::Char('g'))
     }
 
+    /// C-x に続いて C-x 8 プレフィックスを作る `8` キーかどうかを判定
+    pub fn is_cx8_prefix(&self) -> bool {
+        !self.modifiers.ctrl
+            && !self.modifiers.alt
+            && !self.modifiers.shift
+            && matches!(self.code, KeyCode::Char('8'))
+    }
+
+    /// C-x に続いて C-x r プレフィックスを作る `r` キーかどうかを判定
+    pub fn is_cxr_prefix(&self) -> bool {
+        !self.modifiers.ctrl
+            && !self.modifiers.alt
+            && !self.modifiers.shift
+            && matches!(self.code, KeyCode::Char('r'))
+    }
+
     /// 挿入可能な文字かどうかを判定
     pub fn is_insertable_char(&self) -> bool {
         matches!(self.code, KeyCode::Char(_)) && !self.modifiers.ctrl && !self.modifiers.alt
@@ -520,6 +536,18 @@ pub enum Action {
     OpenLine,
     /// 指定行へ移動
     GotoLine,
+    /// 別ウィンドウで指定行へ移動
+    GotoLineOtherWindow,
+    /// 指定行をウィンドウ先頭に表示
+    ScrollLineToTop,
+    /// 指定した文字位置へ移動
+    GotoChar,
+    /// 現在行の指定した表示桁へ移動
+    GotoColumn,
+    /// 次のエラー位置へ移動
+    NextError,
+    /// 前のエラー位置へ移動
+    PreviousError,
     /// 行キル
     KillLine,
     /// マーク設定
@@ -528,6 +556,10 @@ pub enum Action {
     KillRegion,
     /// リージョンコピー
     CopyRegion,
+    /// バッファ全体のコピー
+    CopyWholeBuffer,
+    /// バッファのファイルパスのコピー
+    CopyBufferPath,
     /// カーソルとマークの交換
     ExchangePointAndMark,
     /// バッファ全選択
@@ -538,16 +570,74 @@ pub enum Action {
     ScrollPageUp,
     /// 画面再配置
     Recenter,
+    /// カーソル行をウィンドウの先頭に表示
+    RecenterTop,
+    /// カーソル行をウィンドウの中央に表示
+    RecenterCenter,
+    /// カーソル行をウィンドウの末尾に表示
+    RecenterBottom,
     /// 横スクロール（左）
     ScrollHorizontalLeft,
     /// 横スクロール（右）
     ScrollHorizontalRight,
+    /// 折りたたみ切り替え
+    ToggleFold,
+    /// 自動補完切り替え
+    ToggleAutoCompletion,
+    /// 閉じ括弧の補完
+    CloseParens,
+    /// 次のキーをそのまま挿入
+    QuotedInsert,
+    /// コードポイントを指定して文字を挿入
+    InsertCharByCode,
+    /// リージョンを開き文字に対応する区切り文字の組で囲む
+    InsertPair(char),
+    /// コマンドや alisp 関数の説明を表示
+    DescribeFunction,
+    /// alisp のエラー時にバックトレースを表示するか切り替え
+    ToggleDebugOnError,
+    /// alisp の評価環境を初期化
+    ResetAlispEnv,
+    /// 空白を1つにまとめる
+    JustOneSpace,
+    /// 周辺の空白を削除
+    DeleteHorizontalSpace,
+    /// 直前のミニバッファ入力コマンドを再実行
+    RepeatComplex,
+    /// 文字の大文字・小文字を反転
+    ToggleCharCase,
+    /// リージョンの空白をタブにまとめる
+    Tabify,
+    /// リージョンのタブを空白に展開
+    Untabify,
+    /// 行を fill-column の中央に寄せる
+    CenterLine,
+    /// fill-column を設定（C-x f）
+    SetFillColumn,
+    /// 段落を fill-column の幅で詰め直す（M-q）
+    FillParagraph,
+    /// 単語またはリージョンを先頭大文字化
+    CapitalizeDwim,
+    /// 折り返し単位の切り替え
+    ToggleWordWrap,
+    /// 長い行の切り詰め表示と折り返し表示の切り替え
+    ToggleTruncateLines,
+    /// 制御文字と見えない文字の表示方法の切り替え
+    CycleControlCharDisplay,
+    /// キーワードハイライトの切り替え
+    ToggleKeywordHighlight,
+    /// 検索中の一致をすべてハイライトするかの切り替え
+    ToggleLazyHighlight,
+    /// 集中モード（モードラインを隠す）の切り替え
+    ToggleDistractionFree,
     /// ヤンク
     Yank,
     /// ヤンクポップ
     YankPop,
     /// キーボードキャンセル
     KeyboardQuit,
+    /// 数引数の入力開始
+    UniversalArgument,
     /// Undo
     Undo,
     /// Redo
@@ -557,26 +647,69 @@ pub enum Action {
     FileSave,
     WriteFile,      // C-x C-w
     SaveAllBuffers, // C-x s
+    /// バッファの既定ディレクトリを変更
+    CdBuffer,
+    /// 文字コードを指定してファイルを読み直す
+    RevertBufferWithCodingSystem,
+    /// バッファを HTML ファイルに書き出す
+    ExportHtml,
+    SaveSession,
+    RestoreSession,
     /// バッファ操作
     SwitchBuffer,
     KillBuffer,
     ListBuffers,
+    CloneBuffer,
+    /// バッファの読み取り専用を切り替え
+    ToggleReadOnly,
     /// ウィンドウ操作
     SplitWindowHorizontally, // C-x 2
     SplitWindowVertically, // C-x 3
     DeleteOtherWindows,    // C-x 1
     DeleteWindow,          // C-x 0
     FocusOtherWindow,      // C-x o
+    /// 同じバッファを表示している次のウィンドウに移動
+    FocusOtherWindowSameBuffer,
+    /// 現在のウィンドウと次のウィンドウのバッファを入れ替え
+    SwapWindowBuffers,
     /// アプリケーション制御
     Quit,
     /// コマンド実行
     ExecuteCommand,
+    /// 外部から登録したコマンドの実行
+    CustomCommand(String),
     /// alisp評価
     EvalExpression,
     /// クエリ置換
     QueryReplace,
     /// 正規表現クエリ置換
     RegexQueryReplace,
+    /// 正規表現に一致する箇所を数える
+    CountMatches,
+    /// 文字列に一致する箇所を数える
+    CountMatchesLiteral,
+    /// 文字列に一致する箇所を一覧表示
+    Occur,
+    /// 一覧で選んだ一致箇所へ移動
+    OccurGotoOccurrence,
+    /// 一致箇所の一覧を作り直す
+    OccurRefresh,
+    /// カーソル位置からインクリメンタル検索
+    IsearchForward,
+    /// バッファ先頭からインクリメンタル検索
+    IsearchForwardFromTop,
+    /// 正規表現で前方へインクリメンタル検索
+    IsearchForwardRegexp,
+    /// 正規表現で後方へインクリメンタル検索
+    IsearchBackwardRegexp,
+    /// 正規表現に一致する行を削除
+    FlushLines,
+    /// 正規表現に一致しない行を削除
+    KeepLines,
+    /// 矩形の各行を入力した文字列で置き換える
+    StringRectangle,
+    /// 次の制御文字・幅 0 の見えない文字へ移動
+    FindProblematicChars,
 }
 
 impl Action {
@@ -591,6 +724,8 @@ impl Action {
                 NavigationAction::MoveLineEnd => Some(Command::MoveLineEnd),
                 NavigationAction::MoveBufferStart => Some(Command::MoveBufferStart),
                 NavigationAction::MoveBufferEnd => Some(Command::MoveBufferEnd),
+                NavigationAction::MoveParagraphForward => Some(Command::ForwardParagraph),
+                NavigationAction::MoveParagraphBackward => Some(Command::BackwardParagraph),
                 NavigationAction::MoveWordForward => Some(Command::ForwardWord),
                 NavigationAction::MoveWordBackward => Some(Command::BackwardWord),
             },
@@ -604,39 +739,99 @@ impl Action {
             Action::NewlineAndIndent => Some(Command::NewlineAndIndent),
             Action::OpenLine => Some(Command::OpenLine),
             Action::GotoLine => Some(Command::GotoLine),
+            Action::GotoLineOtherWindow => Some(Command::GotoLineOtherWindow),
+            Action::ScrollLineToTop => Some(Command::ScrollLineToTop),
+            Action::GotoChar => Some(Command::GotoChar),
+            Action::GotoColumn => Some(Command::GotoColumn),
+            Action::NextError => Some(Command::NextError),
+            Action::PreviousError => Some(Command::PreviousError),
             Action::KillLine => Some(Command::KillLine),
             Action::SetMark => Some(Command::SetMark),
             Action::KillRegion => Some(Command::KillRegion),
             Action::CopyRegion => Some(Command::CopyRegion),
+            Action::CopyWholeBuffer => Some(Command::CopyWholeBuffer),
+            Action::CopyBufferPath => Some(Command::CopyBufferPath),
             Action::ExchangePointAndMark => Some(Command::ExchangePointAndMark),
             Action::MarkBuffer => Some(Command::MarkBuffer),
             Action::ScrollPageDown => Some(Command::ScrollPageDown),
             Action::ScrollPageUp => Some(Command::ScrollPageUp),
             Action::Recenter => Some(Command::Recenter),
+            Action::RecenterTop => Some(Command::RecenterTop),
+            Action::RecenterCenter => Some(Command::RecenterCenter),
+            Action::RecenterBottom => Some(Command::RecenterBottom),
             Action::ScrollHorizontalLeft => Some(Command::ScrollLeft),
             Action::ScrollHorizontalRight => Some(Command::ScrollRight),
+            Action::ToggleFold => Some(Command::ToggleFold),
+            Action::ToggleAutoCompletion => Some(Command::ToggleAutoCompletion),
+            Action::CloseParens => Some(Command::CloseParens),
+            Action::QuotedInsert => Some(Command::QuotedInsert),
+            Action::InsertCharByCode => Some(Command::InsertCharByCode),
+            Action::InsertPair(open) => Some(Command::InsertPair(*open)),
+            Action::DescribeFunction => Some(Command::DescribeFunction),
+            Action::ToggleDebugOnError => Some(Command::ToggleDebugOnError),
+            Action::ResetAlispEnv => Some(Command::ResetAlispEnv),
+            Action::JustOneSpace => Some(Command::JustOneSpace),
+            Action::DeleteHorizontalSpace => Some(Command::DeleteHorizontalSpace),
+            Action::RepeatComplex => Some(Command::RepeatComplex),
+            Action::ToggleCharCase => Some(Command::ToggleCharCase),
+            Action::Tabify => Some(Command::Tabify),
+            Action::Untabify => Some(Command::Untabify),
+            Action::CenterLine => Some(Command::CenterLine),
+            Action::SetFillColumn => Some(Command::SetFillColumn),
+            Action::FillParagraph => Some(Command::FillParagraph),
+            Action::CapitalizeDwim => Some(Command::CapitalizeDwim),
+            Action::ToggleWordWrap => Some(Command::ToggleWordWrap),
+            Action::ToggleTruncateLines => Some(Command::ToggleTruncateLines),
+            Action::CycleControlCharDisplay => Some(Command::CycleControlCharDisplay),
+            Action::ToggleKeywordHighlight => Some(Command::ToggleKeywordHighlight),
+            Action::ToggleLazyHighlight => Some(Command::ToggleLazyHighlight),
+            Action::ToggleDistractionFree => Some(Command::ToggleDistractionFree),
             Action::Yank => Some(Command::Yank),
             Action::YankPop => Some(Command::YankPop),
             Action::KeyboardQuit => Some(Command::KeyboardQuit),
+            Action::UniversalArgument => Some(Command::UniversalArgument),
             Action::Undo => Some(Command::Undo),
             Action::Redo => Some(Command::Redo),
             Action::FileOpen => Some(Command::FindFile),
             Action::FileSave => Some(Command::SaveBuffer),
             Action::WriteFile => Some(Command::WriteFile),
             Action::SaveAllBuffers => Some(Command::SaveAllBuffers),
+            Action::CdBuffer => Some(Command::CdBuffer),
+            Action::RevertBufferWithCodingSystem => Some(Command::RevertBufferWithCodingSystem),
+            Action::ExportHtml => Some(Command::ExportHtml),
+            Action::SaveSession => Some(Command::SaveSession),
+            Action::RestoreSession => Some(Command::RestoreSession),
             Action::SwitchBuffer => Some(Command::SwitchToBuffer),
             Action::KillBuffer => Some(Command::KillBuffer),
             Action::ListBuffers => Some(Command::ListBuffers),
+            Action::ToggleReadOnly => Some(Command::ToggleReadOnly),
+            Action::CloneBuffer => Some(Command::CloneBuffer),
             Action::SplitWindowHorizontally => Some(Command::SplitWindowBelow),
             Action::SplitWindowVertically => Some(Command::SplitWindowRight),
             Action::DeleteOtherWindows => Some(Command::DeleteOtherWindows),
             Action::DeleteWindow => Some(Command::DeleteWindow),
             Action::FocusOtherWindow => Some(Command::OtherWindow),
+            Action::FocusOtherWindowSameBuffer => Some(Command::OtherWindowSameBuffer),
+            Action::SwapWindowBuffers => Some(Command::SwapWindowBuffers),
             Action::Quit => Some(Command::SaveBuffersKillTerminal),
             Action::ExecuteCommand => Some(Command::ExecuteCommand),
+            Action::CustomCommand(name) => Some(Command::Unknown(name.clone())),
             Action::EvalExpression => Some(Command::EvalExpression),
             Action::QueryReplace => Some(Command::QueryReplace),
             Action::RegexQueryReplace => Some(Command::RegexQueryReplace),
+            Action::CountMatches => Some(Command::CountMatches),
+            Action::CountMatchesLiteral => Some(Command::CountMatchesLiteral),
+            Action::Occur => Some(Command::Occur),
+            Action::OccurGotoOccurrence => Some(Command::OccurGotoOccurrence),
+            Action::OccurRefresh => Some(Command::OccurRefresh),
+            Action::IsearchForward => Some(Command::IsearchForward),
+            Action::IsearchForwardFromTop => Some(Command::IsearchForwardFromTop),
+            Action::IsearchForwardRegexp => Some(Command::IsearchForwardRegexp),
+            Action::IsearchBackwardRegexp => Some(Command::IsearchBackwardRegexp),
+            Action::FlushLines => Some(Command::FlushLines),
+            Action::KeepLines => Some(Command::KeepLines),
+            Action::StringRectangle => Some(Command::StringRectangle),
+            Action::FindProblematicChars => Some(Command::FindProblematicChars),
         }
     }
 
@@ -650,6 +845,12 @@ impl Action {
             Command::MoveLineEnd => Some(Action::Navigate(NavigationAction::MoveLineEnd)),
             Command::MoveBufferStart => Some(Action::Navigate(NavigationAction::MoveBufferStart)),
             Command::MoveBufferEnd => Some(Action::Navigate(NavigationAction::MoveBufferEnd)),
+            Command::ForwardParagraph => {
+                Some(Action::Navigate(NavigationAction::MoveParagraphForward))
+            }
+            Command::BackwardParagraph => {
+                Some(Action::Navigate(NavigationAction::MoveParagraphBackward))
+            }
             Command::ForwardWord => Some(Action::Navigate(NavigationAction::MoveWordForward)),
             Command::BackwardWord => Some(Action::Navigate(NavigationAction::MoveWordBackward)),
             Command::InsertNewline => Some(Action::InsertNewline),
@@ -657,6 +858,12 @@ impl Action {
             Command::NewlineAndIndent => Some(Action::NewlineAndIndent),
             Command::OpenLine => Some(Action::OpenLine),
             Command::GotoLine => Some(Action::GotoLine),
+            Command::GotoLineOtherWindow => Some(Action::GotoLineOtherWindow),
+            Command::ScrollLineToTop => Some(Action::ScrollLineToTop),
+            Command::GotoChar => Some(Action::GotoChar),
+            Command::GotoColumn => Some(Action::GotoColumn),
+            Command::NextError => Some(Action::NextError),
+            Command::PreviousError => Some(Action::PreviousError),
             Command::DeleteBackwardChar => Some(Action::DeleteChar(DeleteDirection::Backward)),
             Command::DeleteChar => Some(Action::DeleteChar(DeleteDirection::Forward)),
             Command::KillWordForward => Some(Action::KillWord(KillDirection::Forward)),
@@ -665,35 +872,88 @@ impl Action {
             Command::SetMark => Some(Action::SetMark),
             Command::KillRegion => Some(Action::KillRegion),
             Command::CopyRegion => Some(Action::CopyRegion),
+            Command::CopyWholeBuffer => Some(Action::CopyWholeBuffer),
+            Command::CopyBufferPath => Some(Action::CopyBufferPath),
             Command::ExchangePointAndMark => Some(Action::ExchangePointAndMark),
             Command::MarkBuffer => Some(Action::MarkBuffer),
             Command::ScrollPageDown => Some(Action::ScrollPageDown),
             Command::ScrollPageUp => Some(Action::ScrollPageUp),
             Command::Recenter => Some(Action::Recenter),
+            Command::RecenterTop => Some(Action::RecenterTop),
+            Command::RecenterCenter => Some(Action::RecenterCenter),
+            Command::RecenterBottom => Some(Action::RecenterBottom),
             Command::ScrollLeft => Some(Action::ScrollHorizontalLeft),
             Command::ScrollRight => Some(Action::ScrollHorizontalRight),
+            Command::ToggleFold => Some(Action::ToggleFold),
+            Command::ToggleAutoCompletion => Some(Action::ToggleAutoCompletion),
+            Command::CloseParens => Some(Action::CloseParens),
+            Command::QuotedInsert => Some(Action::QuotedInsert),
+            Command::InsertCharByCode => Some(Action::InsertCharByCode),
+            Command::InsertPair(open) => Some(Action::InsertPair(*open)),
+            Command::DescribeFunction => Some(Action::DescribeFunction),
+            Command::ToggleDebugOnError => Some(Action::ToggleDebugOnError),
+            Command::ResetAlispEnv => Some(Action::ResetAlispEnv),
+            Command::JustOneSpace => Some(Action::JustOneSpace),
+            Command::DeleteHorizontalSpace => Some(Action::DeleteHorizontalSpace),
+            Command::RepeatComplex => Some(Action::RepeatComplex),
+            Command::ToggleCharCase => Some(Action::ToggleCharCase),
+            Command::Tabify => Some(Action::Tabify),
+            Command::Untabify => Some(Action::Untabify),
+            Command::CenterLine => Some(Action::CenterLine),
+            Command::SetFillColumn => Some(Action::SetFillColumn),
+            Command::FillParagraph => Some(Action::FillParagraph),
+            Command::CapitalizeDwim => Some(Action::CapitalizeDwim),
+            Command::ToggleWordWrap => Some(Action::ToggleWordWrap),
+            Command::ToggleTruncateLines => Some(Action::ToggleTruncateLines),
+            Command::CycleControlCharDisplay => Some(Action::CycleControlCharDisplay),
+            Command::ToggleKeywordHighlight => Some(Action::ToggleKeywordHighlight),
+            Command::ToggleLazyHighlight => Some(Action::ToggleLazyHighlight),
+            Command::ToggleDistractionFree => Some(Action::ToggleDistractionFree),
             Command::Yank => Some(Action::Yank),
             Command::YankPop => Some(Action::YankPop),
             Command::KeyboardQuit => Some(Action::KeyboardQuit),
+            Command::UniversalArgument => Some(Action::UniversalArgument),
             Command::Undo => Some(Action::Undo),
             Command::Redo => Some(Action::Redo),
             Command::FindFile => Some(Action::FileOpen),
             Command::SaveBuffer => Some(Action::FileSave),
             Command::WriteFile => Some(Action::WriteFile),
             Command::SaveAllBuffers => Some(Action::SaveAllBuffers),
+            Command::CdBuffer => Some(Action::CdBuffer),
+            Command::RevertBufferWithCodingSystem => Some(Action::RevertBufferWithCodingSystem),
+            Command::ExportHtml => Some(Action::ExportHtml),
+            Command::SaveSession => Some(Action::SaveSession),
+            Command::RestoreSession => Some(Action::RestoreSession),
             Command::SwitchToBuffer => Some(Action::SwitchBuffer),
             Command::KillBuffer => Some(Action::KillBuffer),
             Command::ListBuffers => Some(Action::ListBuffers),
+            Command::ToggleReadOnly => Some(Action::ToggleReadOnly),
+            Command::CloneBuffer => Some(Action::CloneBuffer),
             Command::SplitWindowBelow => Some(Action::SplitWindowHorizontally),
             Command::SplitWindowRight => Some(Action::SplitWindowVertically),
             Command::DeleteOtherWindows => Some(Action::DeleteOtherWindows),
             Command::DeleteWindow => Some(Action::DeleteWindow),
             Command::OtherWindow => Some(Action::FocusOtherWindow),
+            Command::OtherWindowSameBuffer => Some(Action::FocusOtherWindowSameBuffer),
+            Command::SwapWindowBuffers => Some(Action::SwapWindowBuffers),
             Command::SaveBuffersKillTerminal | Command::Quit => Some(Action::Quit),
             Command::ExecuteCommand => Some(Action::ExecuteCommand),
             Command::EvalExpression => Some(Action::EvalExpression),
             Command::QueryReplace => Some(Action::QueryReplace),
             Command::RegexQueryReplace => Some(Action::RegexQueryReplace),
+            Command::CountMatches => Some(Action::CountMatches),
+            Command::CountMatchesLiteral => Some(Action::CountMatchesLiteral),
+            Command::Occur => Some(Action::Occur),
+            Command::OccurGotoOccurrence => Some(Action::OccurGotoOccurrence),
+            Command::OccurRefresh => Some(Action::OccurRefresh),
+            Command::IsearchForward => Some(Action::IsearchForward),
+            Command::IsearchForwardFromTop => Some(Action::IsearchForwardFromTop),
+            Command::IsearchForwardRegexp => Some(Action::IsearchForwardRegexp),
+            Command::IsearchBackwardRegexp => Some(Action::IsearchBackwardRegexp),
+            Command::FlushLines => Some(Action::FlushLines),
+            Command::KeepLines => Some(Action::KeepLines),
+            Command::StringRectangle => Some(Action::StringRectangle),
+            Command::FindProblematicChars => Some(Action::FindProblematicChars),
             Command::InsertChar(_) | Command::Unknown(_) => None,
         }
     }
@@ -741,6 +1001,48 @@ pub struct KeySequence {
     pub keys: Vec<Key>,
 }
 
+impl fmt::Display for Key {
+    /// `KeySequence::parse` と同じ表記（例: `C-x`, `M-SPC`）
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.ctrl {
+            f.write_str("C-")?;
+        }
+        if self.modifiers.alt {
+            f.write_str("M-")?;
+        }
+        if self.modifiers.shift {
+            f.write_str("S-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("SPC"),
+            KeyCode::Char(ch) => write!(f, "{}", ch),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Delete"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Unknown => f.write_str("?"),
+        }
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, key) in self.keys.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
 /// 旧インターフェースとの互換性用
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LegacyKeySequence {
@@ -892,18 +1194,25 @@ impl ModernKeyMap {
         let mut single_key_bindings = HashMap::with_capacity(32);
         let mut cx_prefix_bindings = HashMap::with_capacity(8);
         let mut mg_prefix_bindings = HashMap::with_capacity(8);
+        let mut cx8_prefix_bindings = HashMap::with_capacity(1);
+        let mut cxr_prefix_bindings = HashMap::with_capacity(1);
 
         Self::register_mvp_bindings(
             &mut single_key_bindings,
             &mut cx_prefix_bindings,
             &mut mg_prefix_bindings,
+            &mut cx8_prefix_bindings,
+            &mut cxr_prefix_bindings,
         );
 
         Self {
             single_key_bindings,
             cx_prefix_bindings,
             mg_prefix_bindings,
+            cx8_prefix_bindings,
+            cxr_prefix_bindings,
             partial_match_state: PartialMatchState::None,
+            conflicts: Vec::new(),
         }
     }
 
@@ -911,6 +1220,9 @@ impl ModernKeyMap {
         self.single_key_bindings.clear();
         self.cx_prefix_bindings.clear();
         self.mg_prefix_bindings.clear();
+        self.cx8_prefix_bindings.clear();
+        self.cxr_prefix_bindings.clear();
+        self.conflicts.clear();
     }
 
     pub fn bind_command_sequence(
@@ -931,32 +1243,45 @@ impl ModernKeyMap {
         let parsed = KeySequence::parse(sequence)
             .map_err(|_| KeybindingUpdateError::UnsupportedSequence(sequence.to_string()))?;
 
-        match parsed.keys.len() {
-            1 => {
-                self.single_key_bindings
-                    .insert(parsed.keys[0].clone(), action);
-                Ok(())
+        let bindings = match parsed.keys.len() {
+            1 => &mut self.single_key_bindings,
+            2 if parsed.keys[0].is_ctrl_x() => &mut self.cx_prefix_bindings,
+            2 if parsed.keys[0].is_alt_g() => &mut self.mg_prefix_bindings,
+            3 if parsed.keys[0].is_ctrl_x() && parsed.keys[1].is_cx8_prefix() => {
+                &mut self.cx8_prefix_bindings
             }
-            2 => {
-                let prefix = &parsed.keys[0];
-                if prefix.is_ctrl_x() {
-                    self.cx_prefix_bindings
-                        .insert(parsed.keys[1].clone(), action);
-                    Ok(())
-                } else if prefix.is_alt_g() {
-                    self.mg_prefix_bindings
-                        .insert(parsed.keys[1].clone(), action);
-                    Ok(())
-                } else {
-                    Err(KeybindingUpdateError::UnsupportedSequence(
-                        sequence.to_string(),
-                    ))
-                }
+            3 if parsed.keys[0].is_ctrl_x() && parsed.keys[1].is_cxr_prefix() => {
+                &mut self.cxr_prefix_bindings
+            }
+            _ => {
+                return Err(KeybindingUpdateError::UnsupportedSequence(
+                    sequence.to_string(),
+                ))
+            }
+        };
+
+        let key = parsed
+            .keys
+            .last()
+            .cloned()
+            .expect("parsed sequence is not empty");
+        // 割り当ては常に反映し、別のアクションを上書きした場合だけ記録する
+        if let Some(previous) = bindings.insert(key, action.clone()) {
+            if previous != action {
+                self.conflicts.push(KeyMapError::Conflict(parsed));
             }
-            _ => Err(KeybindingUpdateError::UnsupportedSequence(
-                sequence.to_string(),
-            )),
         }
+        Ok(())
+    }
+
+    /// 記録済みの割り当ての上書きを参照
+    pub fn conflicts(&self) -> &[KeyMapError] {
+        &self.conflicts
+    }
+
+    /// 記録済みの割り当ての上書きを取り出して消去
+    pub fn take_conflicts(&mut self) -> Vec<KeyMapError> {
+        std::mem::take(&mut self.conflicts)
     }
 
     pub fn lookup_action(&self, sequence: &str) -> Option<Action> {
@@ -973,15 +1298,57 @@ impl ModernKeyMap {
                     None
                 }
             }
+            3 if parsed.keys[0].is_ctrl_x() && parsed.keys[1].is_cx8_prefix() => {
+                self.cx8_prefix_bindings.get(&parsed.keys[2]).cloned()
+            }
+            3 if parsed.keys[0].is_ctrl_x() && parsed.keys[1].is_cxr_prefix() => {
+                self.cxr_prefix_bindings.get(&parsed.keys[2]).cloned()
+            }
             _ => None,
         }
     }
 
+    /// アクションに割り当てたキーシーケンス（短い順・表記順）
+    pub fn sequences_for_action(&self, action: &Action) -> Vec<KeySequence> {
+        let plain = |ch| Key {
+            modifiers: KeyModifiers {
+                ctrl: false,
+                alt: false,
+                shift: false,
+            },
+            code: KeyCode::Char(ch),
+        };
+        let tables = [
+            (Vec::new(), &self.single_key_bindings),
+            (vec![Key::ctrl_x()], &self.cx_prefix_bindings),
+            (vec![Key::alt_g()], &self.mg_prefix_bindings),
+            (vec![Key::ctrl_x(), plain('8')], &self.cx8_prefix_bindings),
+            (vec![Key::ctrl_x(), plain('r')], &self.cxr_prefix_bindings),
+        ];
+        let mut sequences: Vec<KeySequence> = tables
+            .iter()
+            .flat_map(|(prefix, bindings)| {
+                bindings
+                    .iter()
+                    .filter(|(_, bound)| *bound == action)
+                    .map(move |(key, _)| {
+                        let mut keys = prefix.clone();
+                        keys.push(key.clone());
+                        KeySequence::multi(keys)
+                    })
+            })
+            .collect();
+        sequences.sort_by_cached_key(|sequence| (sequence.keys.len(), sequence.to_string()));
+        sequences
+    }
+
     /// MVPキーバインドの登録
     fn register_mvp_bindings(
         single: &mut HashMap<Key, Action>,
         cx_prefix: &mut HashMap<Key, Action>,
         mg_prefix: &mut HashMap<Key, Action>,
+        cx8_prefix: &mut HashMap<Key, Action>,
+        cxr_prefix: &mut HashMap<Key, Action>,
     ) {
         // 移動系
         single.insert(
@@ -1051,6 +1418,41 @@ impl ModernKeyMap {
             Action::Navigate(NavigationAction::MoveBufferEnd),
         );
 
+        // 段落移動（M-}, M-{）
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('}'),
+            },
+            Action::Navigate(NavigationAction::MoveParagraphForward),
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('{'),
+            },
+            Action::Navigate(NavigationAction::MoveParagraphBackward),
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('('),
+            },
+            Action::InsertPair('('),
+        );
+
         // 編集系
         single.insert(
             Key {
@@ -1083,7 +1485,73 @@ impl ModernKeyMap {
         single.insert(Key::ctrl_k(), Action::KillLine);
         single.insert(Key::ctrl_y(), Action::Yank);
         single.insert(Key::alt_y(), Action::YankPop);
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: true,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Char('q'),
+            },
+            Action::QuotedInsert,
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('q'),
+            },
+            Action::FillParagraph,
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char(' '),
+            },
+            Action::JustOneSpace,
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('\\'),
+            },
+            Action::DeleteHorizontalSpace,
+        );
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char('c'),
+            },
+            Action::CapitalizeDwim,
+        );
         single.insert(Key::ctrl_g(), Action::KeyboardQuit);
+        single.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: true,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Char('u'),
+            },
+            Action::UniversalArgument,
+        );
         single.insert(
             Key {
                 modifiers: KeyModifiers {
@@ -1255,6 +1723,17 @@ impl ModernKeyMap {
             Action::KillBuffer,
         );
         cx_prefix.insert(Key::ctrl_b(), Action::ListBuffers);
+        cx_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: true,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Char('q'),
+            },
+            Action::ToggleReadOnly,
+        );
         cx_prefix.insert(
             Key {
                 modifiers: KeyModifiers {
@@ -1347,6 +1826,54 @@ impl ModernKeyMap {
             },
             Action::MarkBuffer,
         );
+        cx_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Char('f'),
+            },
+            Action::SetFillColumn,
+        );
+        cx_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: true,
+                    shift: false,
+                },
+                code: KeyCode::Char(':'),
+            },
+            Action::RepeatComplex,
+        );
+
+        // C-x 8 プレフィックス
+        cx8_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Enter,
+            },
+            Action::InsertCharByCode,
+        );
+
+        // C-x r プレフィックス（矩形）
+        cxr_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Char('t'),
+            },
+            Action::StringRectangle,
+        );
 
         // M-gプレフィックス
         mg_prefix.insert(
@@ -1361,6 +1888,34 @@ impl ModernKeyMap {
             Action::GotoLine,
         );
         mg_prefix.insert(Key::alt_g(), Action::GotoLine);
+        mg_prefix.insert(
+            Key {
+                modifiers: KeyModifiers {
+                    ctrl: false,
+                    alt: false,
+                    shift: false,
+                },
+                code: KeyCode::Tab,
+            },
+            Action::GotoColumn,
+        );
+        for (ch, action) in [
+            ('c', Action::GotoChar),
+            ('n', Action::NextError),
+            ('p', Action::PreviousError),
+        ] {
+            mg_prefix.insert(
+                Key {
+                    modifiers: KeyModifiers {
+                        ctrl: false,
+                        alt: false,
+                        shift: false,
+                    },
+                    code: KeyCode::Char(ch),
+                },
+                action,
+            );
+        }
 
         // コマンド実行
         single.insert(Key::alt_x(), Action::ExecuteCommand);
@@ -1396,6 +1951,8 @@ impl ModernKeyMap {
             PartialMatchState::None => self.process_initial_key(key),
             PartialMatchState::CxPrefix => self.process_cx_prefix_key(key),
             PartialMatchState::MgPrefix => self.process_mg_prefix_key(key),
+            PartialMatchState::Cx8Prefix => self.process_cx8_prefix_key(key),
+            PartialMatchState::CxrPrefix => self.process_cxr_prefix_key(key),
         }
     }
 
@@ -1439,6 +1996,18 @@ impl ModernKeyMap {
             return KeyProcessResult::Action(Action::KeyboardQuit);
         }
 
+        // C-x 8 の場合はさらに次のキーを待つ
+        if key.is_cx8_prefix() {
+            self.partial_match_state = PartialMatchState::Cx8Prefix;
+            return KeyProcessResult::PartialMatch;
+        }
+
+        // C-x r の場合もさらに次のキーを待つ
+        if key.is_cxr_prefix() {
+            self.partial_match_state = PartialMatchState::CxrPrefix;
+            return KeyProcessResult::PartialMatch;
+        }
+
         // C-xプレフィックス用のマッピングを確認
         if let Some(action) = self.cx_prefix_bindings.get(&key) {
             return KeyProcessResult::Action(action.clone());
@@ -1462,6 +2031,34 @@ impl ModernKeyMap {
         KeyProcessResult::NoMatch
     }
 
+    fn process_cx8_prefix_key(&mut self, key: Key) -> KeyProcessResult {
+        self.partial_match_state = PartialMatchState::None;
+
+        if key == Key::ctrl_g() {
+            return KeyProcessResult::Action(Action::KeyboardQuit);
+        }
+
+        if let Some(action) = self.cx8_prefix_bindings.get(&key) {
+            return KeyProcessResult::Action(action.clone());
+        }
+
+        KeyProcessResult::NoMatch
+    }
+
+    fn process_cxr_prefix_key(&mut self, key: Key) -> KeyProcessResult {
+        self.partial_match_state = PartialMatchState::None;
+
+        if key == Key::ctrl_g() {
+            return KeyProcessResult::Action(Action::KeyboardQuit);
+        }
+
+        if let Some(action) = self.cxr_prefix_bindings.get(&key) {
+            return KeyProcessResult::Action(action.clone());
+        }
+
+        KeyProcessResult::NoMatch
+    }
+
     /// OS衝突の回避
     fn is_system_key(&self, key: &Key) -> bool {
         match (key.modifiers.ctrl, &key.code) {
@@ -1510,6 +2107,8 @@ impl ModernKeyMap {
         match self.partial_match_state {
             PartialMatchState::CxPrefix => Some("C-x"),
             PartialMatchState::MgPrefix => Some("M-g"),
+            PartialMatchState::Cx8Prefix => Some("C-x 8"),
+            PartialMatchState::CxrPrefix => Some("C-x r"),
             PartialMatchState::None => None,
         }
     }
@@ -1540,7 +2139,7 @@ pub enum KeyParseError {
 /// キーマップエラー
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum KeyMapError {
-    #[error("Key binding conflict: {0:?}")]
+    #[error("Key binding conflict: {0}")]
     Conflict(KeySequence),
 
     #[error("Invalid key sequence: {0}")]
@@ -1587,6 +2186,10 @@ enum PartialMatchState {
     CxPrefix,
     /// M-gプレフィックス待ち
     MgPrefix,
+    /// C-x 8 プレフィックス待ち
+    Cx8Prefix,
+    /// C-x r プレフィックス待ち
+    CxrPrefix,
 }
 
 /// キーマップ構造
@@ -1601,8 +2204,17 @@ pub struct ModernKeyMap {
     /// M-gプレフィックス用のマッピング
     mg_prefix_bindings: HashMap<Key, Action>,
 
+    /// C-x 8 プレフィックス用のマッピング
+    cx8_prefix_bindings: HashMap<Key, Action>,
+
+    /// C-x r プレフィックス用のマッピング
+    cxr_prefix_bindings: HashMap<Key, Action>,
+
     /// 部分マッチ状態の管理
     partial_match_state: PartialMatchState,
+
+    /// 既存の割り当てを別のアクションで上書きした記録
+    conflicts: Vec<KeyMapError>,
 }
 
 /// キーマップ管理（旧インターフェース）
@@ -2050,6 +2662,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_modern_keymap_mg_prefix_sub_keys_and_cancel() {
+        let mut keymap = ModernKeyMap::new();
+        let plain = |ch| Key {
+            modifiers: KeyModifiers {
+                ctrl: false,
+                alt: false,
+                shift: false,
+            },
+            code: KeyCode::Char(ch),
+        };
+
+        for (ch, action) in [
+            ('c', Action::GotoChar),
+            ('n', Action::NextError),
+            ('p', Action::PreviousError),
+        ] {
+            assert_eq!(
+                keymap.process_key(Key::alt_g()),
+                KeyProcessResult::PartialMatch
+            );
+            assert_eq!(
+                keymap.process_key(plain(ch)),
+                KeyProcessResult::Action(action)
+            );
+            assert_eq!(keymap.current_prefix_label(), None);
+        }
+
+        // C-g でプレフィックスを取り消すと、次のキーは通常どおり処理される
+        keymap.process_key(Key::alt_g());
+        assert_eq!(
+            keymap.process_key(Key::ctrl_g()),
+            KeyProcessResult::Action(Action::KeyboardQuit)
+        );
+        assert_eq!(keymap.current_prefix_label(), None);
+        assert_eq!(
+            keymap.process_key(plain('c')),
+            KeyProcessResult::Action(Action::InsertChar('c'))
+        );
+    }
+
+    #[test]
+    fn test_modern_keymap_cx8_prefix_sequence() {
+        let mut keymap = ModernKeyMap::new();
+        let key = |sequence| KeySequence::parse(sequence).unwrap().keys[0].clone();
+
+        assert_eq!(
+            keymap.process_key(Key::ctrl_x()),
+            KeyProcessResult::PartialMatch
+        );
+        assert_eq!(keymap.process_key(key("8")), KeyProcessResult::PartialMatch);
+        assert_eq!(keymap.current_prefix_label(), Some("C-x 8"));
+        assert_eq!(
+            keymap.process_key(key("Enter")),
+            KeyProcessResult::Action(Action::InsertCharByCode)
+        );
+        assert_eq!(keymap.current_prefix_label(), None);
+
+        keymap.clear_bindings();
+        keymap
+            .bind_action_sequence("C-x 8 Enter", Action::InsertCharByCode)
+            .unwrap();
+        assert_eq!(
+            keymap.lookup_action("C-x 8 Enter"),
+            Some(Action::InsertCharByCode)
+        );
+        assert!(keymap
+            .bind_action_sequence("C-x 9 Enter", Action::Undo)
+            .is_err());
+    }
+
+    #[test]
+    fn test_modern_keymap_cxr_prefix_sequence() {
+        let mut keymap = ModernKeyMap::new();
+        let key = |sequence| KeySequence::parse(sequence).unwrap().keys[0].clone();
+
+        assert_eq!(
+            keymap.process_key(Key::ctrl_x()),
+            KeyProcessResult::PartialMatch
+        );
+        assert_eq!(keymap.process_key(key("r")), KeyProcessResult::PartialMatch);
+        assert_eq!(keymap.current_prefix_label(), Some("C-x r"));
+        assert_eq!(
+            keymap.process_key(key("t")),
+            KeyProcessResult::Action(Action::StringRectangle)
+        );
+        assert_eq!(
+            keymap.lookup_action("C-x r t"),
+            Some(Action::StringRectangle)
+        );
+        assert_eq!(
+            keymap.sequences_for_action(&Action::StringRectangle)[0].to_string(),
+            "C-x r t"
+        );
+    }
+
+    #[test]
+    fn test_sequences_for_action_lists_all_prefixes() {
+        let mut keymap = ModernKeyMap::new();
+        keymap.clear_bindings();
+        for sequence in ["C-x 8 Enter", "M-g c", "C-x u", "C-/"] {
+            keymap.bind_action_sequence(sequence, Action::Undo).unwrap();
+        }
+        let sequences: Vec<String> = keymap
+            .sequences_for_action(&Action::Undo)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(sequences, vec!["C-/", "C-x u", "M-g c", "C-x 8 Enter"]);
+        assert!(keymap.sequences_for_action(&Action::Redo).is_empty());
+    }
+
     #[test]
     fn test_modern_keymap_kill_ring_bindings() {
         let mut keymap = ModernKeyMap::new();
@@ -2302,4 +3026,52 @@ mod tests {
             Some(Command::ExecuteCommand)
         );
     }
+
+    #[test]
+    fn rebinding_occupied_key_records_conflict_and_applies_binding() {
+        let mut keymap = ModernKeyMap::new();
+
+        // 同じアクションの再割り当てや空きキーへの割り当ては上書きとみなさない
+        keymap
+            .bind_command_sequence("C-n", &Command::NextLine)
+            .unwrap();
+        keymap
+            .bind_action_sequence("C-x j", Action::ToggleWordWrap)
+            .unwrap();
+        assert!(keymap.conflicts().is_empty());
+
+        keymap
+            .bind_action_sequence("C-n", Action::ToggleWordWrap)
+            .unwrap();
+        keymap
+            .bind_action_sequence("C-x C-s", Action::ToggleWordWrap)
+            .unwrap();
+        assert_eq!(keymap.lookup_action("C-n"), Some(Action::ToggleWordWrap));
+        assert_eq!(
+            keymap.lookup_action("C-x C-s"),
+            Some(Action::ToggleWordWrap)
+        );
+
+        let reported: Vec<String> = keymap
+            .take_conflicts()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                "Key binding conflict: C-n".to_string(),
+                "Key binding conflict: C-x C-s".to_string(),
+            ]
+        );
+        assert!(keymap.conflicts().is_empty());
+    }
+
+    #[test]
+    fn key_sequence_display_round_trips_through_parse() {
+        for text in ["C-x C-s", "M-SPC", "C-M-f", "M-g M-g", "Backspace"] {
+            let parsed = KeySequence::parse(text).unwrap();
+            assert_eq!(parsed.to_string(), text);
+        }
+    }
 }
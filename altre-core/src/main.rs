@@ -32,7 +32,7 @@ fn main() -> Result<()> {
     }
 
     if force_tui {
-        run_tui()?;
+        run_tui();
         return Ok(());
     }
 
@@ -41,17 +41,20 @@ fn main() -> Result<()> {
     }
 
     eprintln!("GUI の起動に失敗したため TUI モードへフォールバックします");
-    run_tui()?;
+    run_tui();
     Ok(())
 }
 
-fn run_tui() -> Result<()> {
+fn run_tui() {
     println!("altre - Modern Emacs-inspired text editor");
     println!("Version: {}", env!("CARGO_PKG_VERSION"));
     println!();
 
-    let mut app = TuiApplication::new()?;
-    app.run()
+    let result = TuiApplication::new().and_then(|mut app| app.run());
+    if let Err(err) = result {
+        error::report_error(&err, "TUI");
+        process::exit(1);
+    }
 }
 
 fn launch_gui() -> Result<bool> {
@@ -4,6 +4,7 @@
 
 use crate::error::Result;
 use crate::file::path::expand_path;
+use crate::input::commands::Command;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -12,6 +13,20 @@ pub trait CompletionEngine {
     /// 入力文字列に対する補完候補を取得
     fn complete(&self, input: &str) -> Result<Vec<String>>;
 
+    /// 補完候補と注釈（種類やサイズ、説明）の組を先頭から `limit` 件まで取得
+    fn complete_annotated(
+        &self,
+        input: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        Ok(self
+            .complete(input)?
+            .into_iter()
+            .take(limit)
+            .map(|candidate| (candidate, None))
+            .collect())
+    }
+
     /// 共通プレフィックスを取得
     fn common_prefix(&self, candidates: &[String]) -> String;
 
@@ -119,6 +134,45 @@ impl PathCompletion {
             .collect()
     }
 
+    /// 入力に一致する候補と、その実際のパス
+    fn matching_paths(&self, input: &str) -> Result<Vec<(String, PathBuf)>> {
+        if input.is_empty() {
+            // 空の場合は現在ディレクトリの内容を返す
+            let entries = self.get_directory_entries(Path::new("."))?;
+            return Ok(entries
+                .into_iter()
+                .map(|entry| {
+                    let path = Path::new(".").join(&entry);
+                    (entry, path)
+                })
+                .collect());
+        }
+
+        let (dir_path, file_prefix) = self.split_path(input)?;
+        let entries = self.get_directory_entries(&dir_path)?;
+        let filtered = self.filter_entries(&entries, &file_prefix);
+
+        // 完全なパスを構築
+        let mut results = Vec::new();
+        for entry in filtered {
+            let full_path = dir_path.join(&entry);
+            let formatted = self.format_path(&full_path, input);
+            results.push((formatted, full_path));
+        }
+
+        Ok(results)
+    }
+
+    /// パスの種類とサイズを表す注釈
+    fn annotate_path(&self, path: &Path) -> Option<String> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.is_dir() {
+            Some("ディレクトリ".to_string())
+        } else {
+            Some(format_file_size(metadata.len()))
+        }
+    }
+
     /// 絶対パスを相対パスまたは短縮形に変換
     fn format_path(&self, path: &Path, original_input: &str) -> String {
         let path_str = path.to_string_lossy();
@@ -153,25 +207,28 @@ impl Default for PathCompletion {
 
 impl CompletionEngine for PathCompletion {
     fn complete(&self, input: &str) -> Result<Vec<String>> {
-        if input.is_empty() {
-            // 空の場合は現在ディレクトリの内容を返す
-            let entries = self.get_directory_entries(Path::new("."))?;
-            return Ok(entries);
-        }
-
-        let (dir_path, file_prefix) = self.split_path(input)?;
-        let entries = self.get_directory_entries(&dir_path)?;
-        let filtered = self.filter_entries(&entries, &file_prefix);
-
-        // 完全なパスを構築
-        let mut results = Vec::new();
-        for entry in filtered {
-            let full_path = dir_path.join(&entry);
-            let formatted = self.format_path(&full_path, input);
-            results.push(formatted);
-        }
+        Ok(self
+            .matching_paths(input)?
+            .into_iter()
+            .map(|(candidate, _)| candidate)
+            .collect())
+    }
 
-        Ok(results)
+    fn complete_annotated(
+        &self,
+        input: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        // 表示しない候補のメタデータは読まない
+        Ok(self
+            .matching_paths(input)?
+            .into_iter()
+            .take(limit)
+            .map(|(candidate, path)| {
+                let annotation = self.annotate_path(&path);
+                (candidate, annotation)
+            })
+            .collect())
     }
 
     fn common_prefix(&self, candidates: &[String]) -> String {
@@ -225,6 +282,7 @@ impl CommandCompletion {
             "delete-char".to_string(),
             "find-file".to_string(),
             "save-buffer".to_string(),
+            "clone-buffer".to_string(),
             "save-buffers-kill-terminal".to_string(),
             "quit".to_string(),
         ];
@@ -269,6 +327,25 @@ impl CompletionEngine for CommandCompletion {
         Ok(filtered)
     }
 
+    fn complete_annotated(
+        &self,
+        input: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        Ok(self
+            .complete(input)?
+            .into_iter()
+            .take(limit)
+            .map(|name| {
+                let annotation = match Command::from_string(&name) {
+                    Command::Unknown(_) => None,
+                    command => Some(command.description().to_string()),
+                };
+                (name, annotation)
+            })
+            .collect())
+    }
+
     fn common_prefix(&self, candidates: &[String]) -> String {
         PathCompletion::new().common_prefix(candidates)
     }
@@ -278,6 +355,54 @@ impl CompletionEngine for CommandCompletion {
     }
 }
 
+/// 入力の文字を順に含む候補だけを残す（大文字小文字は区別しない）
+///
+/// 前方一致・部分一致・飛び飛びの一致の順に並べ、同じ種類の中では元の並びを保つ。
+pub fn fuzzy_filter(candidates: &[String], input: &str) -> Vec<String> {
+    let needle = input.to_lowercase();
+    let mut ranked: Vec<(u8, &String)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let haystack = candidate.to_lowercase();
+            let rank = if haystack.starts_with(&needle) {
+                0
+            } else if haystack.contains(&needle) {
+                1
+            } else if is_subsequence(&needle, &haystack) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, candidate))
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked
+        .into_iter()
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|ch| rest.any(|other| other == ch))
+}
+
+/// ファイルサイズを補完候補の注釈向けに整形
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,4 +501,73 @@ mod tests {
         let empty_prefix = completion.common_prefix(&empty);
         assert_eq!(empty_prefix, "");
     }
+
+    #[test]
+    fn path_candidates_carry_type_and_size() {
+        let temp_dir = tempdir().unwrap();
+        let temp_path = temp_dir.path();
+        fs::write(temp_path.join("small.txt"), "hello").unwrap();
+        fs::write(temp_path.join("large.bin"), vec![0u8; 1536]).unwrap();
+        fs::create_dir(temp_path.join("sub")).unwrap();
+
+        let completion = PathCompletion::new();
+        let input = format!("{}/", temp_path.display());
+        let annotated = completion.complete_annotated(&input, 50).unwrap();
+        let annotation = |suffix: &str| {
+            annotated
+                .iter()
+                .find(|(candidate, _)| candidate.ends_with(suffix))
+                .and_then(|(_, annotation)| annotation.clone())
+        };
+
+        assert_eq!(annotated.len(), 3);
+        assert_eq!(annotation("small.txt").as_deref(), Some("5 B"));
+        assert_eq!(annotation("large.bin").as_deref(), Some("1.5 KiB"));
+        assert_eq!(annotation("sub/").as_deref(), Some("ディレクトリ"));
+
+        let limited = completion.complete_annotated(&input, 2).unwrap();
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].0, annotated[0].0);
+        assert_eq!(limited[1].0, annotated[1].0);
+    }
+
+    #[test]
+    fn command_candidates_carry_descriptions() {
+        let mut completion = CommandCompletion::new();
+        completion.add_command("my-extension".to_string());
+
+        let annotated = completion.complete_annotated("f", 50).unwrap();
+        assert!(annotated.contains(&(
+            "find-file".to_string(),
+            Some(Command::FindFile.description().to_string())
+        )));
+        assert_eq!(
+            completion.complete_annotated("my-", 50).unwrap(),
+            vec![("my-extension".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn fuzzy_filter_ranks_prefix_then_substring_then_subsequence() {
+        let candidates: Vec<String> = ["notes.md", "main.rs", "domain.rs", "Makefile"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        assert_eq!(
+            fuzzy_filter(&candidates, "ma"),
+            vec!["main.rs", "Makefile", "domain.rs"]
+        );
+        assert_eq!(fuzzy_filter(&candidates, "nmd"), vec!["notes.md"]);
+        assert_eq!(fuzzy_filter(&candidates, ""), candidates);
+        assert!(fuzzy_filter(&candidates, "xyz").is_empty());
+    }
+
+    #[test]
+    fn file_size_uses_binary_units() {
+        assert_eq!(format_file_size(0), "0 B");
+        assert_eq!(format_file_size(1023), "1023 B");
+        assert_eq!(format_file_size(1024), "1.0 KiB");
+        assert_eq!(format_file_size(5 * 1024 * 1024), "5.0 MiB");
+    }
 }
@@ -7,6 +7,17 @@ use std::collections::VecDeque;
 /// セッション内履歴の最大保存数
 const MAX_HISTORY_SIZE: usize = 100;
 
+/// 独立して保持する履歴の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryRing {
+    /// ファイル名（find-file・write-file・cd）
+    File,
+    /// コマンドやバッファ名などその他の入力
+    Command,
+    /// 評価した式（eval-expression）
+    Expression,
+}
+
 /// セッション内でのコマンド履歴管理
 #[derive(Debug, Clone)]
 pub struct SessionHistory {
@@ -14,6 +25,8 @@ pub struct SessionHistory {
     entries: VecDeque<String>,
     /// 最大保存数
     max_size: usize,
+    /// 再利用したエントリを先頭へ移すか（無効なら直前と同じ入力だけを省く）
+    move_to_front: bool,
 }
 
 impl SessionHistory {
@@ -27,9 +40,23 @@ impl SessionHistory {
         Self {
             entries: VecDeque::with_capacity(max_size),
             max_size,
+            move_to_front: true,
         }
     }
 
+    /// 再利用したエントリを先頭へ移すかを取得
+    pub fn move_to_front(&self) -> bool {
+        self.move_to_front
+    }
+
+    /// 再利用したエントリを先頭へ移すかを設定
+    ///
+    /// 有効（既定）なら同じ入力の古いエントリを削除して先頭へ追加する。
+    /// 無効なら直前のエントリと同じ入力だけを追加せず、それ以前の重複は残す。
+    pub fn set_move_to_front(&mut self, enabled: bool) {
+        self.move_to_front = enabled;
+    }
+
     /// エントリを履歴に追加
     pub fn add_entry(&mut self, entry: String) {
         // 空文字列は追加しない
@@ -37,8 +64,12 @@ impl SessionHistory {
             return;
         }
 
-        // 重複を避けるため、既存のエントリを削除
-        self.entries.retain(|e| e != &entry);
+        if self.move_to_front {
+            // 重複を避けるため、既存のエントリを削除
+            self.entries.retain(|e| e != &entry);
+        } else if self.entries.front() == Some(&entry) {
+            return;
+        }
 
         // 先頭に追加
         self.entries.push_front(entry);
@@ -55,6 +86,21 @@ impl SessionHistory {
         self.entries.get(index)
     }
 
+    /// `from` より古いエントリのうち `current` と異なる最初のインデックス
+    ///
+    /// `from` が `None` なら最新のエントリから探す。
+    pub fn older_distinct(&self, from: Option<usize>, current: &str) -> Option<usize> {
+        let start = from.map_or(0, |index| index + 1);
+        (start..self.entries.len()).find(|&index| self.entries[index] != current)
+    }
+
+    /// `from` より新しいエントリのうち `current` と異なる最初のインデックス
+    pub fn newer_distinct(&self, from: usize, current: &str) -> Option<usize> {
+        (0..from.min(self.entries.len()))
+            .rev()
+            .find(|&index| self.entries[index] != current)
+    }
+
     /// 履歴のサイズを取得
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -134,6 +180,49 @@ mod tests {
         assert_eq!(history.get_entry(1), Some(&"command2".to_string()));
     }
 
+    #[test]
+    fn same_value_twice_is_stored_once_without_move_to_front() {
+        let mut history = SessionHistory::new();
+        history.set_move_to_front(false);
+
+        history.add_entry("command1".to_string());
+        history.add_entry("command1".to_string());
+        assert_eq!(history.len(), 1);
+
+        history.add_entry("command2".to_string());
+        history.add_entry("command1".to_string());
+        let entries: Vec<&String> = history.iter().collect();
+        assert_eq!(entries, vec!["command1", "command2", "command1"]);
+    }
+
+    #[test]
+    fn reused_entry_moves_to_front() {
+        let mut history = SessionHistory::new();
+        assert!(history.move_to_front());
+
+        history.add_entry("command1".to_string());
+        history.add_entry("command2".to_string());
+        history.add_entry("command3".to_string());
+        history.add_entry("command1".to_string());
+        let entries: Vec<&String> = history.iter().collect();
+        assert_eq!(entries, vec!["command1", "command3", "command2"]);
+    }
+
+    #[test]
+    fn distinct_navigation_skips_current_value() {
+        let mut history = SessionHistory::new();
+        history.set_move_to_front(false);
+        for entry in ["a", "b", "b2", "a"] {
+            history.add_entry(entry.to_string());
+        }
+
+        assert_eq!(history.older_distinct(None, "a"), Some(1));
+        assert_eq!(history.older_distinct(Some(0), "b2"), Some(2));
+        assert_eq!(history.older_distinct(Some(2), "a"), None);
+        assert_eq!(history.newer_distinct(3, "a"), Some(2));
+        assert_eq!(history.newer_distinct(1, "a"), None);
+    }
+
     #[test]
     fn test_empty_entry_ignored() {
         let mut history = SessionHistory::new();
@@ -3,6 +3,7 @@
 //! Emacs風のコマンド入力インターフェース、ファイル操作、補完機能を提供
 
 use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub mod commands;
 pub mod completion;
@@ -63,6 +64,32 @@ pub enum MinibufferMode {
     QueryReplaceReplacement,
     /// 行番号入力
     GotoLine,
+    /// ウィンドウ先頭に表示する行番号の入力
+    ScrollLineToTop,
+    /// 文字位置入力
+    GotoChar,
+    /// 表示桁入力
+    GotoColumn,
+    /// fill-column の入力
+    SetFillColumn,
+    /// 既定ディレクトリ入力
+    ChangeDirectory,
+    /// 文字コード名入力
+    CodingSystem,
+    /// 挿入する文字のコードポイント入力
+    InsertChar,
+    /// 説明を表示する関数名の入力
+    DescribeFunction,
+    /// 一致数を数える検索語の入力
+    CountMatches { is_regex: bool },
+    /// 一覧表示する一致の検索語の入力
+    Occur,
+    /// 行を絞り込む正規表現の入力
+    FilterLines { keep: bool },
+    /// 矩形を置き換える文字列の入力
+    StringRectangle,
+    /// y/n の確認
+    YesOrNo,
 }
 
 /// ミニバッファの状態
@@ -78,20 +105,59 @@ pub struct MinibufferState {
     pub prompt: String,
     /// 補完候補
     pub completions: Vec<String>,
+    /// 補完候補ごとの注釈（`completions` と同じ並び）
+    pub completion_annotations: Vec<Option<String>>,
     /// 選択中の補完候補インデックス
     pub selected_completion: Option<usize>,
-    /// 履歴（セッション内のみ）
+    /// コマンドなどの履歴（セッション内のみ）
     pub history: history::SessionHistory,
+    /// ファイル名の履歴（セッション内のみ）
+    pub file_history: history::SessionHistory,
+    /// 評価した式の履歴（セッション内のみ）
+    pub expression_history: history::SessionHistory,
     /// 履歴ナビゲーション位置
     pub history_index: Option<usize>,
     /// 置換プロンプト状態
     pub(crate) pending_replace: Option<ReplacePromptState>,
     /// 行番号入力状態
     pub(crate) pending_goto_line: Option<GotoLineState>,
+    /// 履歴検索（C-r）の状態
+    pub(crate) history_search: Option<HistorySearchState>,
     /// ステータスメッセージ
     pub status_message: Option<String>,
 }
 
+impl MinibufferState {
+    /// 指定した補完候補の注釈
+    pub fn completion_annotation(&self, index: usize) -> Option<&str> {
+        self.completion_annotations
+            .get(index)
+            .and_then(|annotation| annotation.as_deref())
+    }
+
+    /// 指定した種類の履歴
+    pub fn history_ring(&self, ring: history::HistoryRing) -> &history::SessionHistory {
+        match ring {
+            history::HistoryRing::File => &self.file_history,
+            history::HistoryRing::Command => &self.history,
+            history::HistoryRing::Expression => &self.expression_history,
+        }
+    }
+
+    fn history_ring_mut(&mut self, ring: history::HistoryRing) -> &mut history::SessionHistory {
+        match ring {
+            history::HistoryRing::File => &mut self.file_history,
+            history::HistoryRing::Command => &mut self.history,
+            history::HistoryRing::Expression => &mut self.expression_history,
+        }
+    }
+
+    /// 全種類の履歴の合計件数
+    pub fn history_len(&self) -> usize {
+        self.history.len() + self.file_history.len() + self.expression_history.len()
+    }
+}
+
 impl Default for MinibufferState {
     fn default() -> Self {
         Self {
@@ -100,11 +166,15 @@ impl Default for MinibufferState {
             cursor_pos: 0,
             prompt: String::new(),
             completions: Vec::new(),
+            completion_annotations: Vec::new(),
             selected_completion: None,
             history: history::SessionHistory::new(),
+            file_history: history::SessionHistory::new(),
+            expression_history: history::SessionHistory::new(),
             history_index: None,
             pending_replace: None,
             pending_goto_line: None,
+            history_search: None,
             status_message: None,
         }
     }
@@ -144,6 +214,16 @@ impl Default for MinibufferStyle {
 pub(crate) struct ReplacePromptState {
     pattern: String,
     is_regex: bool,
+    /// 置換後テキストの初期入力（再実行時）
+    replacement: Option<String>,
+}
+
+/// 履歴検索中に退避しておく元の入力
+#[derive(Debug, Clone)]
+pub(crate) struct HistorySearchState {
+    prompt: String,
+    input: String,
+    cursor_pos: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -171,6 +251,8 @@ pub enum MinibufferEvent {
     /// 履歴ナビゲーション
     HistoryPrevious,
     HistoryNext,
+    /// 履歴検索（C-r）
+    HistorySearch,
     /// 補完候補ナビゲーション
     CompletionNext,
     CompletionPrevious,
@@ -184,6 +266,47 @@ pub enum CursorDirection {
     End,
 }
 
+/// ミニバッファで引数を読み取って実行したコマンド（repeat-complex-command 用）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComplexCommand {
+    /// ファイルを開く
+    FindFile(String),
+    /// M-x で入力したコマンド
+    Execute(String),
+    /// 式評価
+    EvalExpression(String),
+    /// 名前を付けて保存
+    WriteFile(String),
+    /// バッファ切り替え
+    SwitchBuffer(String),
+    /// バッファ削除
+    KillBuffer(String),
+    /// 行番号ジャンプ
+    GotoLine(usize),
+    /// 指定行をウィンドウ先頭に表示
+    ScrollLineToTop(usize),
+    /// 文字位置ジャンプ
+    GotoChar(usize),
+    /// 既定ディレクトリの変更
+    ChangeDirectory(String),
+    /// 文字コードを指定して読み直し
+    RevertWithCodingSystem(String),
+    /// コードポイントを指定して文字を挿入
+    InsertChar(String),
+    /// 一致数を数える
+    CountMatches { pattern: String, is_regex: bool },
+    /// 一致箇所の一覧表示
+    Occur(String),
+    /// 行の絞り込み
+    FilterLines { pattern: String, keep: bool },
+    /// クエリ置換
+    QueryReplace {
+        pattern: String,
+        replacement: String,
+        is_regex: bool,
+    },
+}
+
 /// 入力処理の結果
 #[derive(Debug, Clone, PartialEq)]
 pub enum MinibufferResult {
@@ -201,12 +324,38 @@ pub enum MinibufferResult {
     SaveFileAs(String),
     /// 行番号入力結果
     GotoLine(usize),
+    /// ウィンドウ先頭に表示する行番号
+    ScrollLineToTop(usize),
+    /// 移動先の文字位置（1始まり）
+    GotoChar(usize),
+    /// 移動先の表示桁（1始まり）
+    GotoColumn(usize),
+    /// 新しい fill-column
+    SetFillColumn(usize),
+    /// 新しい既定ディレクトリ
+    ChangeDirectory(String),
+    /// 読み直しに使う文字コード名
+    CodingSystem(String),
+    /// 挿入する文字のコードポイント（16進数の文字列）
+    InsertChar(String),
+    /// 説明を表示する関数名
+    DescribeFunction(String),
+    /// 一致数を数える検索語
+    CountMatches { pattern: String, is_regex: bool },
+    /// 一致箇所を一覧表示する検索語
+    Occur(String),
+    /// 行を絞り込む正規表現
+    FilterLines { pattern: String, keep: bool },
+    /// 矩形を置き換える文字列（空なら矩形の内容を削除）
+    StringRectangle(String),
     /// クエリ置換入力完了
     QueryReplace {
         pattern: String,
         replacement: String,
         is_regex: bool,
     },
+    /// y/n 確認の回答（`true` が y）
+    YesOrNo(bool),
     /// キャンセル
     Cancel,
     /// 無効な操作
@@ -303,8 +452,16 @@ pub struct ModernMinibuffer {
     completion_engine: Box<dyn completion::CompletionEngine>,
     /// コマンド実行者
     command_executor: Option<Box<dyn CommandExecutor>>,
-    /// バッファ名候補
+    /// 名前補完の候補（バッファ名・文字コード名）
     buffer_candidates: Vec<String>,
+    /// バッファ名をあいまい一致で補完するか（無効なら前方一致）
+    fuzzy_buffer_completion: bool,
+    /// 入力のたびにパス補完の候補を更新するか（無効なら Tab を押したときだけ）
+    auto_completion: bool,
+    /// パス補完の候補を自動で更新する最小の入力文字数
+    auto_completion_min_chars: usize,
+    /// 最後に実行したミニバッファ入力コマンド
+    last_command: Option<ComplexCommand>,
 }
 
 impl std::fmt::Debug for ModernMinibuffer {
@@ -318,6 +475,7 @@ impl std::fmt::Debug for ModernMinibuffer {
                 &self.command_executor.as_ref().map(|_| "<CommandExecutor>"),
             )
             .field("buffer_candidates", &self.buffer_candidates)
+            .field("last_command", &self.last_command)
             .finish()
     }
 }
@@ -331,9 +489,43 @@ impl ModernMinibuffer {
             completion_engine: Box::new(completion::PathCompletion::new()),
             command_executor: None,
             buffer_candidates: Vec::new(),
+            fuzzy_buffer_completion: true,
+            auto_completion: true,
+            auto_completion_min_chars: 2,
+            last_command: None,
         }
     }
 
+    /// バッファ名の補完にあいまい一致を使うか
+    pub fn fuzzy_buffer_completion(&self) -> bool {
+        self.fuzzy_buffer_completion
+    }
+
+    /// バッファ名の補完方式を設定（`false` で前方一致）
+    pub fn set_fuzzy_buffer_completion(&mut self, enabled: bool) {
+        self.fuzzy_buffer_completion = enabled;
+    }
+
+    /// パス補完を入力のたびに行うかと、そのときの最小入力文字数を設定
+    ///
+    /// `enabled` が `false` なら入力中は候補を作らず、Tab を押したときだけファイル一覧を読む。
+    pub fn set_auto_completion(&mut self, enabled: bool, min_chars: usize) {
+        self.auto_completion = enabled;
+        self.auto_completion_min_chars = min_chars;
+    }
+
+    /// 再利用した履歴を先頭へ移すか
+    pub fn history_move_to_front(&self) -> bool {
+        self.state.history.move_to_front()
+    }
+
+    /// 再利用した履歴を先頭へ移すかを設定（`false` なら直前と同じ入力だけを省く）
+    pub fn set_history_move_to_front(&mut self, enabled: bool) {
+        self.state.history.set_move_to_front(enabled);
+        self.state.file_history.set_move_to_front(enabled);
+        self.state.expression_history.set_move_to_front(enabled);
+    }
+
     /// ファイル検索を開始
     pub fn start_find_file(&mut self, initial_path: Option<&str>) {
         self.state.mode = MinibufferMode::FindFile;
@@ -364,10 +556,112 @@ impl ModernMinibuffer {
         self.update_completions();
     }
 
+    /// 既定ディレクトリの入力を開始
+    pub fn start_change_directory(&mut self, initial_path: Option<&str>) {
+        self.state.mode = MinibufferMode::ChangeDirectory;
+        self.state.prompt = "Change default directory: ".to_string();
+        self.state.input = initial_path.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.update_completions();
+    }
+
+    /// 一致数を数える検索語の入力を開始
+    pub fn start_count_matches(&mut self, is_regex: bool, initial: Option<&str>) {
+        self.state.mode = MinibufferMode::CountMatches { is_regex };
+        self.state.prompt = if is_regex {
+            "How many matches for regexp: ".to_string()
+        } else {
+            "How many matches for string: ".to_string()
+        };
+        self.state.input = initial.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.update_completions();
+    }
+
+    /// 一致箇所を一覧表示する検索語の入力を開始
+    pub fn start_occur(&mut self, initial: Option<&str>) {
+        self.state.mode = MinibufferMode::Occur;
+        self.state.prompt = "List lines matching string: ".to_string();
+        self.state.input = initial.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.update_completions();
+    }
+
+    /// 行を絞り込む正規表現の入力を開始（`keep` なら一致しない行を削除）
+    pub fn start_filter_lines(&mut self, keep: bool, initial: Option<&str>) {
+        self.state.mode = MinibufferMode::FilterLines { keep };
+        self.state.prompt = if keep {
+            "Keep lines containing match for regexp: ".to_string()
+        } else {
+            "Flush lines containing match for regexp: ".to_string()
+        };
+        self.state.input = initial.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.update_completions();
+    }
+
+    /// 矩形を置き換える文字列の入力を開始
+    pub fn start_string_rectangle(&mut self) {
+        self.state.mode = MinibufferMode::StringRectangle;
+        self.state.prompt = "String rectangle: ".to_string();
+        self.state.input.clear();
+        self.state.cursor_pos = 0;
+        self.update_completions();
+    }
+
+    /// 読み直しに使う文字コード名の入力を開始
+    pub fn start_coding_system(&mut self, initial: Option<&str>) {
+        self.state.mode = MinibufferMode::CodingSystem;
+        self.state.prompt = "Coding system: ".to_string();
+        self.state.input = initial.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.buffer_candidates = crate::file::CodingSystem::ALL
+            .iter()
+            .map(|coding| coding.name().to_string())
+            .collect();
+        self.update_completions();
+    }
+
+    /// 挿入する文字のコードポイント入力を開始
+    pub fn start_insert_char(&mut self, initial: Option<&str>) {
+        self.state.mode = MinibufferMode::InsertChar;
+        self.state.prompt = "Insert character (Unicode hex or name): ".to_string();
+        self.state.input = initial.unwrap_or("").to_string();
+        self.state.cursor_pos = self.state.input.chars().count();
+        self.update_completions();
+    }
+
+    /// 説明を表示する関数名の入力を開始（`functions` は補完候補）
+    pub fn start_describe_function(&mut self, functions: &[String]) {
+        self.state.mode = MinibufferMode::DescribeFunction;
+        self.state.prompt = "Describe function: ".to_string();
+        self.state.input.clear();
+        self.state.cursor_pos = 0;
+        self.buffer_candidates = functions.to_vec();
+        self.update_completions();
+    }
+
+    /// y/n の確認を開始（y か n の1キーで答える）
+    pub fn start_yes_or_no(&mut self, question: &str) {
+        self.deactivate();
+        self.state.mode = MinibufferMode::YesOrNo;
+        self.state.prompt = format!("{} (y/n) ", question);
+    }
+
     /// バッファ切り替えを開始
-    pub fn start_switch_buffer(&mut self, buffers: &[String], initial: Option<&str>) {
+    ///
+    /// `buffers` は最近表示した順に渡す。`default` は空入力で確定したときの切り替え先。
+    pub fn start_switch_buffer(
+        &mut self,
+        buffers: &[String],
+        initial: Option<&str>,
+        default: Option<&str>,
+    ) {
         self.state.mode = MinibufferMode::SwitchBuffer;
-        self.state.prompt = "Switch to buffer: ".to_string();
+        self.state.prompt = match default {
+            Some(name) => format!("Switch to buffer (default {}): ", name),
+            None => "Switch to buffer: ".to_string(),
+        };
         self.state.input = initial.unwrap_or("").to_string();
         self.state.cursor_pos = self.state.input.chars().count();
         self.buffer_candidates = buffers.to_vec();
@@ -407,6 +701,7 @@ impl ModernMinibuffer {
         self.state.pending_replace = Some(ReplacePromptState {
             pattern: initial_text.to_string(),
             is_regex,
+            replacement: None,
         });
         self.state.status_message = None;
         self.update_completions();
@@ -414,11 +709,77 @@ impl ModernMinibuffer {
 
     /// 行番号ジャンプを開始
     pub fn start_goto_line(&mut self, default_line: usize, max_line: usize) {
+        self.start_line_prompt(
+            MinibufferMode::GotoLine,
+            "Goto line: ",
+            default_line,
+            max_line,
+        );
+    }
+
+    /// ウィンドウ先頭に表示する行番号の入力を開始
+    pub fn start_scroll_line_to_top(&mut self, default_line: usize, max_line: usize) {
+        self.start_line_prompt(
+            MinibufferMode::ScrollLineToTop,
+            "Scroll line to top: ",
+            default_line,
+            max_line,
+        );
+    }
+
+    /// 文字位置ジャンプを開始（位置は1始まり）
+    pub fn start_goto_char(&mut self, default_pos: usize, max_pos: usize) {
+        self.start_line_prompt(
+            MinibufferMode::GotoChar,
+            "Goto char: ",
+            default_pos,
+            max_pos,
+        );
+        self.state.status_message = Some(format!(
+            "文字位置範囲: 1-{} (現在: {})",
+            max_pos.max(1),
+            default_pos.max(1)
+        ));
+    }
+
+    /// 表示桁ジャンプを開始（桁は1始まり、`max_column` は行末の表示桁）
+    pub fn start_goto_column(&mut self, default_column: usize, max_column: usize) {
+        self.start_line_prompt(
+            MinibufferMode::GotoColumn,
+            "Goto column: ",
+            default_column,
+            max_column,
+        );
+        self.state.status_message = Some(format!(
+            "表示桁範囲: 1-{} (現在: {})",
+            max_column.max(1),
+            default_column.max(1)
+        ));
+    }
+
+    /// fill-column の入力を開始（現在の値を初期入力にする）
+    pub fn start_set_fill_column(&mut self, current: usize) {
+        self.start_line_prompt(
+            MinibufferMode::SetFillColumn,
+            "Set fill-column to: ",
+            current,
+            current,
+        );
+        self.state.status_message = Some(format!("現在の fill-column: {}", current));
+    }
+
+    fn start_line_prompt(
+        &mut self,
+        mode: MinibufferMode,
+        prompt: &str,
+        default_line: usize,
+        max_line: usize,
+    ) {
         let normalized_default = default_line.max(1);
         let normalized_max = max_line.max(1);
 
-        self.state.mode = MinibufferMode::GotoLine;
-        self.state.prompt = "Goto line: ".to_string();
+        self.state.mode = mode;
+        self.state.prompt = prompt.to_string();
         self.state.input = normalized_default.to_string();
         self.state.cursor_pos = self.state.input.chars().count();
         self.state.pending_goto_line = Some(GotoLineState {
@@ -429,10 +790,105 @@ impl ModernMinibuffer {
             normalized_max, normalized_default
         ));
         self.state.completions.clear();
+        self.state.completion_annotations.clear();
         self.state.selected_completion = None;
         self.state.history_index = None;
     }
 
+    /// 最後に実行したミニバッファ入力コマンド
+    pub fn last_command(&self) -> Option<&ComplexCommand> {
+        self.last_command.as_ref()
+    }
+
+    /// 最後のミニバッファ入力コマンドを、前回の入力を編集できる状態で再表示
+    ///
+    /// 記録がなければ `false` を返す。確定すると通常の入力と同じ結果を返す。
+    pub fn start_repeat_command(&mut self, buffers: &[String], max_line: usize) -> bool {
+        let Some(command) = self.last_command.clone() else {
+            return false;
+        };
+        match command {
+            ComplexCommand::FindFile(path) => self.start_find_file(Some(&path)),
+            ComplexCommand::Execute(name) => {
+                self.start_execute_command();
+                self.set_input(name);
+            }
+            ComplexCommand::EvalExpression(expr) => {
+                self.start_eval_expression();
+                self.set_input(expr);
+            }
+            ComplexCommand::WriteFile(path) => self.start_write_file(Some(&path)),
+            ComplexCommand::SwitchBuffer(name) => {
+                self.start_switch_buffer(buffers, Some(&name), None)
+            }
+            ComplexCommand::KillBuffer(name) => self.start_kill_buffer(buffers, Some(&name)),
+            ComplexCommand::GotoLine(line) => self.start_goto_line(line, max_line),
+            ComplexCommand::ScrollLineToTop(line) => self.start_scroll_line_to_top(line, max_line),
+            ComplexCommand::GotoChar(pos) => {
+                // 再実行時は文字数の上限が分からないため範囲は表示しない
+                self.start_goto_char(pos, pos);
+                self.state.status_message = None;
+            }
+            ComplexCommand::ChangeDirectory(path) => self.start_change_directory(Some(&path)),
+            ComplexCommand::RevertWithCodingSystem(name) => self.start_coding_system(Some(&name)),
+            ComplexCommand::InsertChar(code) => self.start_insert_char(Some(&code)),
+            ComplexCommand::CountMatches { pattern, is_regex } => {
+                self.start_count_matches(is_regex, Some(&pattern))
+            }
+            ComplexCommand::Occur(pattern) => self.start_occur(Some(&pattern)),
+            ComplexCommand::FilterLines { pattern, keep } => {
+                self.start_filter_lines(keep, Some(&pattern))
+            }
+            ComplexCommand::QueryReplace {
+                pattern,
+                replacement,
+                is_regex,
+            } => {
+                self.start_query_replace(is_regex, Some(&pattern));
+                if let Some(state) = self.state.pending_replace.as_mut() {
+                    state.replacement = Some(replacement);
+                }
+            }
+        }
+        self.state.prompt = format!("Redo {}", self.state.prompt);
+        true
+    }
+
+    /// 行番号入力を確定（空なら既定値、不正な入力ならエラーを表示して `None`）
+    fn submit_line_input(&mut self, input: &str) -> Option<usize> {
+        let state = self
+            .state
+            .pending_goto_line
+            .clone()
+            .unwrap_or(GotoLineState { default_line: 1 });
+
+        let trimmed = input.trim();
+        let line_value = if trimmed.is_empty() {
+            state.default_line
+        } else {
+            match trimmed.parse::<usize>() {
+                Ok(value) if value >= 1 => value,
+                _ => {
+                    self.show_error("正の整数を入力してください".to_string());
+                    return None;
+                }
+            }
+        };
+
+        if !trimmed.is_empty() {
+            self.add_to_history(trimmed.to_string());
+        }
+
+        self.deactivate();
+        Some(line_value)
+    }
+
+    fn set_input(&mut self, input: String) {
+        self.state.cursor_pos = input.chars().count();
+        self.state.input = input;
+        self.update_completions();
+    }
+
     /// エラーメッセージを表示
     pub fn show_error(&mut self, message: String) {
         let expires_at = Instant::now() + Duration::from_secs(5); // QA.mdの回答
@@ -468,21 +924,67 @@ impl ModernMinibuffer {
                 self.deactivate();
                 MinibufferResult::Continue
             }
+            MinibufferMode::YesOrNo => self.handle_yes_or_no_key(key),
             _ => self.handle_input_key(key),
         }
     }
 
+    /// 貼り付けた文字列を入力位置に挿入する（改行は取り除く）
+    ///
+    /// 入力中のプロンプトがなければ何もせず偽を返す。y/n の確認中は文字列を入力しない。
+    pub fn insert_text(&mut self, text: &str) -> bool {
+        match self.state.mode {
+            MinibufferMode::Inactive
+            | MinibufferMode::ErrorDisplay { .. }
+            | MinibufferMode::InfoDisplay { .. } => return false,
+            MinibufferMode::YesOrNo => {
+                self.state.status_message = Some("y か n で答えてください".to_string());
+                return true;
+            }
+            _ => {}
+        }
+        for ch in text.chars().filter(|ch| !matches!(ch, '\n' | '\r')) {
+            self.insert_char(ch);
+        }
+        if self.state.history_search.is_some() {
+            self.filter_history_candidates();
+        } else {
+            self.update_completions();
+        }
+        true
+    }
+
+    fn handle_yes_or_no_key(&mut self, key: Key) -> MinibufferResult {
+        match self.key_to_event(key) {
+            MinibufferEvent::Input('y' | 'Y') => {
+                self.deactivate();
+                MinibufferResult::YesOrNo(true)
+            }
+            MinibufferEvent::Input('n' | 'N') => {
+                self.deactivate();
+                MinibufferResult::YesOrNo(false)
+            }
+            MinibufferEvent::Cancel => self.cancel(),
+            _ => {
+                self.state.status_message = Some("y か n で答えてください".to_string());
+                MinibufferResult::Continue
+            }
+        }
+    }
+
     /// 非アクティブ化
     pub fn deactivate(&mut self) {
         self.state.mode = MinibufferMode::Inactive;
         self.state.input.clear();
         self.state.completions.clear();
+        self.state.completion_annotations.clear();
         self.state.selected_completion = None;
         self.state.cursor_pos = 0;
         self.state.history_index = None;
         self.buffer_candidates.clear();
         self.state.pending_replace = None;
         self.state.pending_goto_line = None;
+        self.state.history_search = None;
         self.state.status_message = None;
     }
 
@@ -531,6 +1033,9 @@ impl ModernMinibuffer {
     // 内部メソッド
     fn handle_input_key(&mut self, key: Key) -> MinibufferResult {
         let event = self.key_to_event(key);
+        if self.state.history_search.is_some() {
+            return self.handle_history_search_event(event);
+        }
 
         match event {
             MinibufferEvent::Input(ch) => {
@@ -574,6 +1079,101 @@ impl ModernMinibuffer {
                 self.history_next();
                 MinibufferResult::Continue
             }
+            MinibufferEvent::HistorySearch => {
+                self.start_history_search();
+                MinibufferResult::Continue
+            }
+        }
+    }
+
+    /// 履歴検索中のキー処理
+    ///
+    /// 入力した部分文字列を含む履歴を候補に並べ、Enter で選んだ候補を入力欄へ戻す。
+    fn handle_history_search_event(&mut self, event: MinibufferEvent) -> MinibufferResult {
+        match event {
+            MinibufferEvent::Input(ch) => {
+                self.insert_char(ch);
+                self.filter_history_candidates();
+            }
+            MinibufferEvent::Backspace => {
+                self.delete_backward();
+                self.filter_history_candidates();
+            }
+            MinibufferEvent::HistorySearch | MinibufferEvent::CompletionNext => {
+                self.select_next_completion();
+            }
+            MinibufferEvent::CompletionPrevious => self.select_previous_completion(),
+            MinibufferEvent::Submit => {
+                let selected = self
+                    .state
+                    .selected_completion
+                    .and_then(|index| self.state.completions.get(index).cloned());
+                self.finish_history_search(selected);
+            }
+            MinibufferEvent::Cancel => self.finish_history_search(None),
+            _ => {}
+        }
+        MinibufferResult::Continue
+    }
+
+    /// 現在のモードに対応する履歴から検索を始める（入力は検索語として使う）
+    fn start_history_search(&mut self) {
+        self.state.history_search = Some(HistorySearchState {
+            prompt: std::mem::take(&mut self.state.prompt),
+            input: std::mem::take(&mut self.state.input),
+            cursor_pos: self.state.cursor_pos,
+        });
+        self.state.prompt = "History search: ".to_string();
+        self.state.cursor_pos = 0;
+        self.state.history_index = None;
+        self.filter_history_candidates();
+    }
+
+    /// 検索語を含む履歴を新しい順に候補へ並べる
+    fn filter_history_candidates(&mut self) {
+        let ring = self.current_history_ring();
+        self.state.completions = self
+            .state
+            .history_ring(ring)
+            .search(&self.state.input)
+            .into_iter()
+            .map(|(_, entry)| entry.clone())
+            .collect();
+        self.state.completion_annotations.clear();
+        self.state.selected_completion = if self.state.completions.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+    }
+
+    /// 履歴検索を終了し、選んだ履歴（なければ検索前の入力）を入力欄へ戻す
+    fn finish_history_search(&mut self, selected: Option<String>) {
+        let Some(saved) = self.state.history_search.take() else {
+            return;
+        };
+        self.state.prompt = saved.prompt;
+        match selected {
+            Some(entry) => {
+                self.state.input = entry;
+                self.state.cursor_pos = self.state.input.chars().count();
+            }
+            None => {
+                self.state.input = saved.input;
+                self.state.cursor_pos = saved.cursor_pos;
+            }
+        }
+        self.update_completions();
+    }
+
+    /// 現在のモードで読み書きする履歴の種類
+    fn current_history_ring(&self) -> history::HistoryRing {
+        match self.state.mode {
+            MinibufferMode::FindFile
+            | MinibufferMode::WriteFile
+            | MinibufferMode::ChangeDirectory => history::HistoryRing::File,
+            MinibufferMode::EvalExpression => history::HistoryRing::Expression,
+            _ => history::HistoryRing::Command,
         }
     }
 
@@ -599,6 +1199,7 @@ impl ModernMinibuffer {
             KeyCode::Up => MinibufferEvent::CompletionPrevious,
             KeyCode::Char('p') if key.modifiers.ctrl => MinibufferEvent::HistoryPrevious,
             KeyCode::Char('n') if key.modifiers.ctrl => MinibufferEvent::HistoryNext,
+            KeyCode::Char('r') if key.modifiers.ctrl => MinibufferEvent::HistorySearch,
             _ => MinibufferEvent::Input('\0'), // 無効な入力として扱う
         }
     }
@@ -618,17 +1219,12 @@ impl ModernMinibuffer {
             return;
         }
 
-        let byte_pos = self.cursor_byte_pos();
-        if byte_pos > 0 {
-            // 前の文字の境界を見つける
-            let mut char_start = byte_pos - 1;
-            while char_start > 0 && !self.state.input.is_char_boundary(char_start) {
-                char_start -= 1;
-            }
-
-            self.state.input.drain(char_start..byte_pos);
-            self.state.cursor_pos -= 1;
-        }
+        // 結合文字や絵文字の修飾子を含む書記素クラスタをまとめて削除する
+        let start = self.previous_grapheme_boundary();
+        let start_byte = self.char_to_byte_pos(start);
+        let end_byte = self.cursor_byte_pos();
+        self.state.input.drain(start_byte..end_byte);
+        self.state.cursor_pos = start;
     }
 
     fn delete_forward(&mut self) {
@@ -637,27 +1233,17 @@ impl ModernMinibuffer {
             return;
         }
 
-        // 次の文字の境界を見つける
-        let mut char_end = byte_pos + 1;
-        while char_end < self.state.input.len() && !self.state.input.is_char_boundary(char_end) {
-            char_end += 1;
-        }
-
-        self.state.input.drain(byte_pos..char_end);
+        let end_byte = self.char_to_byte_pos(self.next_grapheme_boundary());
+        self.state.input.drain(byte_pos..end_byte);
     }
 
     fn move_cursor(&mut self, direction: CursorDirection) {
         match direction {
             CursorDirection::Left => {
-                if self.state.cursor_pos > 0 {
-                    self.state.cursor_pos -= 1;
-                }
+                self.state.cursor_pos = self.previous_grapheme_boundary();
             }
             CursorDirection::Right => {
-                let char_count = self.state.input.chars().count();
-                if self.state.cursor_pos < char_count {
-                    self.state.cursor_pos += 1;
-                }
+                self.state.cursor_pos = self.next_grapheme_boundary();
             }
             CursorDirection::Home => {
                 self.state.cursor_pos = 0;
@@ -669,38 +1255,93 @@ impl ModernMinibuffer {
     }
 
     fn cursor_byte_pos(&self) -> usize {
+        self.char_to_byte_pos(self.state.cursor_pos)
+    }
+
+    fn char_to_byte_pos(&self, char_pos: usize) -> usize {
         self.state
             .input
             .char_indices()
-            .nth(self.state.cursor_pos)
+            .nth(char_pos)
             .map(|(i, _)| i)
             .unwrap_or(self.state.input.len())
     }
 
+    /// カーソルより前で最も近い書記素クラスタの境界（文字位置）
+    fn previous_grapheme_boundary(&self) -> usize {
+        let mut boundary = 0;
+        let mut pos = 0;
+        for grapheme in self.state.input.graphemes(true) {
+            if pos >= self.state.cursor_pos {
+                break;
+            }
+            boundary = pos;
+            pos += grapheme.chars().count();
+        }
+        boundary
+    }
+
+    /// カーソルより後で最も近い書記素クラスタの境界（文字位置）
+    fn next_grapheme_boundary(&self) -> usize {
+        let mut pos = 0;
+        for grapheme in self.state.input.graphemes(true) {
+            pos += grapheme.chars().count();
+            if pos > self.state.cursor_pos {
+                return pos;
+            }
+        }
+        pos
+    }
+
     fn update_completions(&mut self) {
-        // パス補完時のみ入力長による制限を適用
+        // パス補完時のみ設定（Tab のみ・最小入力文字数）による制限を適用
+        // 入力文字数はディレクトリ部分を除いた最後の要素で数える
+        let file_name = self.state.input.rsplit('/').next().unwrap_or_default();
         if matches!(
             self.state.mode,
-            MinibufferMode::FindFile | MinibufferMode::WriteFile
-        ) && self.state.input.len() < 2
+            MinibufferMode::FindFile | MinibufferMode::WriteFile | MinibufferMode::ChangeDirectory
+        ) && (!self.auto_completion
+            || file_name.chars().count() < self.auto_completion_min_chars)
         {
             self.state.completions.clear();
+            self.state.completion_annotations.clear();
             self.state.selected_completion = None;
             return;
         }
 
+        self.compute_completions();
+    }
+
+    fn compute_completions(&mut self) {
+        self.state.completion_annotations.clear();
         match self.state.mode {
-            MinibufferMode::FindFile | MinibufferMode::WriteFile => {
-                let completions = self.completion_engine.complete(&self.state.input);
-                let mut limited_completions = completions.unwrap_or_default();
-                limited_completions.truncate(50); // QA.mdの回答
-                self.state.completions = limited_completions;
+            MinibufferMode::FindFile
+            | MinibufferMode::WriteFile
+            | MinibufferMode::ChangeDirectory => {
+                // 候補数の上限は QA.mdの回答
+                let completions = self
+                    .completion_engine
+                    .complete_annotated(&self.state.input, 50)
+                    .unwrap_or_default();
+                (self.state.completions, self.state.completion_annotations) =
+                    completions.into_iter().unzip();
             }
-            MinibufferMode::SwitchBuffer | MinibufferMode::KillBuffer => {
+            MinibufferMode::SwitchBuffer
+            | MinibufferMode::KillBuffer
+            | MinibufferMode::CodingSystem
+            | MinibufferMode::DescribeFunction => {
+                let fuzzy = self.fuzzy_buffer_completion
+                    && matches!(
+                        self.state.mode,
+                        MinibufferMode::SwitchBuffer | MinibufferMode::KillBuffer
+                    );
                 if self.buffer_candidates.is_empty() {
                     self.state.completions.clear();
                 } else if self.state.input.is_empty() {
                     self.state.completions = self.buffer_candidates.clone();
+                } else if fuzzy {
+                    self.state.completions =
+                        completion::fuzzy_filter(&self.buffer_candidates, &self.state.input);
                 } else {
                     let needle = self.state.input.to_lowercase();
                     self.state.completions = self
@@ -719,6 +1360,9 @@ impl ModernMinibuffer {
                 self.state.completions.clear();
             }
         }
+        self.state
+            .completion_annotations
+            .resize(self.state.completions.len(), None);
 
         self.state.selected_completion = if self.state.completions.is_empty() {
             None
@@ -729,6 +1373,8 @@ impl ModernMinibuffer {
 
     fn handle_completion(&mut self) {
         if self.state.completions.is_empty() {
+            // 入力中に候補を作らない設定でも、Tab では候補を作って表示する
+            self.compute_completions();
             return;
         }
 
@@ -736,7 +1382,7 @@ impl ModernMinibuffer {
         if let Some(completion) = self.state.completions.first() {
             self.state.input = completion.clone();
             self.state.cursor_pos = self.state.input.chars().count();
-            self.update_completions();
+            self.compute_completions();
         }
     }
 
@@ -776,44 +1422,39 @@ impl ModernMinibuffer {
         }
     }
 
+    /// 1つ古い履歴を表示する（表示中の入力と同じエントリは飛ばす）
     fn history_previous(&mut self) {
-        if self.state.history.is_empty() {
+        let history = self.state.history_ring(self.current_history_ring());
+        let Some(next_index) = history.older_distinct(self.state.history_index, &self.state.input)
+        else {
             return;
-        }
-
-        let next_index = match self.state.history_index {
-            Some(index) => {
-                if index + 1 < self.state.history.len() {
-                    index + 1
-                } else {
-                    return;
-                }
-            }
-            None => 0,
         };
 
-        if let Some(entry) = self.state.history.get_entry(next_index) {
-            self.state.input = entry.clone();
+        if let Some(entry) = history.get_entry(next_index).cloned() {
+            self.state.input = entry;
             self.state.cursor_pos = self.state.input.chars().count();
             self.state.history_index = Some(next_index);
             self.update_completions();
         }
     }
 
+    /// 1つ新しい履歴を表示する（表示中の入力と同じエントリは飛ばし、なければ入力を空にする）
     fn history_next(&mut self) {
         match self.state.history_index {
             Some(index) => {
-                if index == 0 {
-                    self.state.input.clear();
-                    self.state.cursor_pos = 0;
-                    self.state.history_index = None;
-                } else {
-                    let next_index = index - 1;
-                    if let Some(entry) = self.state.history.get_entry(next_index) {
-                        self.state.input = entry.clone();
+                let history = self.state.history_ring(self.current_history_ring());
+                let newer = history.newer_distinct(index, &self.state.input);
+                match newer.and_then(|next| Some((next, history.get_entry(next)?.clone()))) {
+                    Some((next_index, entry)) => {
+                        self.state.input = entry;
                         self.state.cursor_pos = self.state.input.chars().count();
                         self.state.history_index = Some(next_index);
                     }
+                    None => {
+                        self.state.input.clear();
+                        self.state.cursor_pos = 0;
+                        self.state.history_index = None;
+                    }
                 }
                 self.update_completions();
             }
@@ -835,6 +1476,7 @@ impl ModernMinibuffer {
                     // 履歴に追加
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::FindFile(input.clone()));
                     MinibufferResult::Execute(format!("find-file {}", input))
                 }
             }
@@ -845,16 +1487,32 @@ impl ModernMinibuffer {
                 } else {
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    // 再実行コマンド自身は記録しない
+                    if input.trim() != "repeat-complex-command" {
+                        self.last_command = Some(ComplexCommand::Execute(input.clone()));
+                    }
                     MinibufferResult::Execute(input)
                 }
             }
             MinibufferMode::SwitchBuffer => {
+                // 候補に無い入力は、選択中または先頭の補完候補に切り替える
+                let input = if input.is_empty() || self.buffer_candidates.contains(&input) {
+                    input
+                } else {
+                    self.state
+                        .selected_completion
+                        .or(Some(0))
+                        .and_then(|index| self.state.completions.get(index))
+                        .cloned()
+                        .unwrap_or(input)
+                };
                 if input.is_empty() {
                     self.deactivate();
                     MinibufferResult::SwitchBuffer(String::new())
                 } else {
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::SwitchBuffer(input.clone()));
                     MinibufferResult::SwitchBuffer(input)
                 }
             }
@@ -865,6 +1523,7 @@ impl ModernMinibuffer {
                 } else {
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::KillBuffer(input.clone()));
                     MinibufferResult::KillBuffer(input)
                 }
             }
@@ -875,6 +1534,7 @@ impl ModernMinibuffer {
                 } else {
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::EvalExpression(input.clone()));
                     MinibufferResult::EvalExpression(input)
                 }
             }
@@ -890,8 +1550,8 @@ impl ModernMinibuffer {
                     } else {
                         format!("Replace {} with: ", input)
                     };
-                    self.state.input.clear();
-                    self.state.cursor_pos = 0;
+                    self.state.input = state.replacement.take().unwrap_or_default();
+                    self.state.cursor_pos = self.state.input.chars().count();
                     MinibufferResult::Continue
                 } else {
                     self.show_error("内部状態エラー".to_string());
@@ -904,6 +1564,11 @@ impl ModernMinibuffer {
                     let is_regex = state.is_regex;
                     let replacement_value = input.clone();
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::QueryReplace {
+                        pattern: pattern.clone(),
+                        replacement: replacement_value.clone(),
+                        is_regex,
+                    });
                     MinibufferResult::QueryReplace {
                         pattern,
                         replacement: replacement_value,
@@ -915,32 +1580,38 @@ impl ModernMinibuffer {
                 }
             }
             MinibufferMode::GotoLine => {
-                let state = self
-                    .state
-                    .pending_goto_line
-                    .clone()
-                    .unwrap_or(GotoLineState { default_line: 1 });
-
-                let trimmed = input.trim();
-                let line_value = if trimmed.is_empty() {
-                    state.default_line
-                } else {
-                    match trimmed.parse::<usize>() {
-                        Ok(value) if value >= 1 => value,
-                        _ => {
-                            self.show_error("正の整数を入力してください".to_string());
-                            return MinibufferResult::Continue;
-                        }
-                    }
+                let Some(line_value) = self.submit_line_input(&input) else {
+                    return MinibufferResult::Continue;
                 };
-
-                if !trimmed.is_empty() {
-                    self.add_to_history(trimmed.to_string());
-                }
-
-                self.deactivate();
+                self.last_command = Some(ComplexCommand::GotoLine(line_value));
                 MinibufferResult::GotoLine(line_value)
             }
+            MinibufferMode::ScrollLineToTop => {
+                let Some(line_value) = self.submit_line_input(&input) else {
+                    return MinibufferResult::Continue;
+                };
+                self.last_command = Some(ComplexCommand::ScrollLineToTop(line_value));
+                MinibufferResult::ScrollLineToTop(line_value)
+            }
+            MinibufferMode::GotoChar => {
+                let Some(position) = self.submit_line_input(&input) else {
+                    return MinibufferResult::Continue;
+                };
+                self.last_command = Some(ComplexCommand::GotoChar(position));
+                MinibufferResult::GotoChar(position)
+            }
+            MinibufferMode::GotoColumn => {
+                let Some(column) = self.submit_line_input(&input) else {
+                    return MinibufferResult::Continue;
+                };
+                MinibufferResult::GotoColumn(column)
+            }
+            MinibufferMode::SetFillColumn => {
+                let Some(column) = self.submit_line_input(&input) else {
+                    return MinibufferResult::Continue;
+                };
+                MinibufferResult::SetFillColumn(column)
+            }
             MinibufferMode::WriteFile => {
                 if input.is_empty() {
                     self.show_error("ファイル名を入力してください".to_string());
@@ -948,9 +1619,108 @@ impl ModernMinibuffer {
                 } else {
                     self.add_to_history(input.clone());
                     self.deactivate();
+                    self.last_command = Some(ComplexCommand::WriteFile(input.clone()));
                     MinibufferResult::SaveFileAs(input)
                 }
             }
+            MinibufferMode::CodingSystem => {
+                if input.trim().is_empty() {
+                    self.show_error("文字コードを入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::RevertWithCodingSystem(input.clone()));
+                    MinibufferResult::CodingSystem(input)
+                }
+            }
+            MinibufferMode::InsertChar => {
+                if input.trim().is_empty() {
+                    self.show_error("コードポイントを入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::InsertChar(input.clone()));
+                    MinibufferResult::InsertChar(input)
+                }
+            }
+            MinibufferMode::DescribeFunction => {
+                let name = input.trim().to_string();
+                if name.is_empty() {
+                    self.show_error("関数名を入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(name.clone());
+                    self.deactivate();
+                    MinibufferResult::DescribeFunction(name)
+                }
+            }
+            MinibufferMode::CountMatches { is_regex } => {
+                let is_regex = *is_regex;
+                if input.is_empty() {
+                    self.show_error("検索語を入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::CountMatches {
+                        pattern: input.clone(),
+                        is_regex,
+                    });
+                    MinibufferResult::CountMatches {
+                        pattern: input,
+                        is_regex,
+                    }
+                }
+            }
+            MinibufferMode::Occur => {
+                if input.is_empty() {
+                    self.show_error("検索語を入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::Occur(input.clone()));
+                    MinibufferResult::Occur(input)
+                }
+            }
+            MinibufferMode::FilterLines { keep } => {
+                let keep = *keep;
+                if input.is_empty() {
+                    self.show_error("正規表現を入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::FilterLines {
+                        pattern: input.clone(),
+                        keep,
+                    });
+                    MinibufferResult::FilterLines {
+                        pattern: input,
+                        keep,
+                    }
+                }
+            }
+            MinibufferMode::StringRectangle => {
+                if !input.is_empty() {
+                    self.add_to_history(input.clone());
+                }
+                self.deactivate();
+                MinibufferResult::StringRectangle(input)
+            }
+            MinibufferMode::ChangeDirectory => {
+                if input.is_empty() {
+                    self.show_error("ディレクトリを入力してください".to_string());
+                    MinibufferResult::Continue
+                } else {
+                    self.add_to_history(input.clone());
+                    self.deactivate();
+                    self.last_command = Some(ComplexCommand::ChangeDirectory(input.clone()));
+                    MinibufferResult::ChangeDirectory(input)
+                }
+            }
             _ => MinibufferResult::Continue,
         }
     }
@@ -962,7 +1732,8 @@ impl ModernMinibuffer {
 
     fn add_to_history(&mut self, entry: String) {
         if !entry.is_empty() {
-            self.state.history.add_entry(entry);
+            let ring = self.current_history_ring();
+            self.state.history_ring_mut(ring).add_entry(entry);
         }
     }
 
@@ -995,6 +1766,61 @@ impl ModernMinibuffer {
 mod tests {
     use super::*;
 
+    fn type_into(minibuffer: &mut ModernMinibuffer, text: &str) {
+        for ch in text.chars() {
+            minibuffer.insert_char(ch);
+        }
+    }
+
+    #[test]
+    fn backspace_deletes_whole_grapheme_cluster() {
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.start_find_file(None);
+        minibuffer.state.input.clear();
+        minibuffer.state.cursor_pos = 0;
+        type_into(&mut minibuffer, "cafe\u{301}");
+        assert_eq!(minibuffer.state.cursor_pos, 5);
+
+        minibuffer.delete_backward();
+        assert_eq!(minibuffer.state.input, "caf");
+        assert_eq!(minibuffer.state.cursor_pos, 3);
+
+        minibuffer.state.input.clear();
+        minibuffer.state.cursor_pos = 0;
+        type_into(&mut minibuffer, "a👍🏽b");
+        minibuffer.move_cursor(CursorDirection::Left);
+        minibuffer.delete_backward();
+        assert_eq!(minibuffer.state.input, "ab");
+        assert_eq!(minibuffer.state.cursor_pos, 1);
+    }
+
+    #[test]
+    fn cursor_moves_and_deletes_forward_by_grapheme_cluster() {
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.start_find_file(None);
+        minibuffer.state.input.clear();
+        minibuffer.state.cursor_pos = 0;
+        type_into(&mut minibuffer, "e\u{301}🚀🌟");
+
+        minibuffer.move_cursor(CursorDirection::Home);
+        minibuffer.move_cursor(CursorDirection::Right);
+        assert_eq!(minibuffer.state.cursor_pos, 2);
+        minibuffer.move_cursor(CursorDirection::Right);
+        assert_eq!(minibuffer.state.cursor_pos, 3);
+        minibuffer.move_cursor(CursorDirection::Left);
+        minibuffer.move_cursor(CursorDirection::Left);
+        assert_eq!(minibuffer.state.cursor_pos, 0);
+
+        minibuffer.delete_forward();
+        assert_eq!(minibuffer.state.input, "🚀🌟");
+
+        // 書記素クラスタの途中にカーソルを置いても、その後ろへ文字を挿入できる
+        minibuffer.state.input = "cafe\u{301}".to_string();
+        minibuffer.state.cursor_pos = 4;
+        minibuffer.insert_char('!');
+        assert_eq!(minibuffer.state.input, "cafe!\u{301}");
+    }
+
     #[test]
     fn query_replace_prefills_input() {
         let mut minibuffer = ModernMinibuffer::new();
@@ -1055,6 +1881,158 @@ mod tests {
         }
     }
 
+    #[test]
+    fn repeat_command_prefills_previous_query_replace() {
+        let mut minibuffer = ModernMinibuffer::new();
+        assert!(!minibuffer.start_repeat_command(&[], 1));
+
+        minibuffer.start_query_replace(false, Some("foo"));
+        minibuffer.submit();
+        minibuffer.state.input = "bar".to_string();
+        minibuffer.submit();
+
+        assert!(minibuffer.start_repeat_command(&[], 1));
+        assert_eq!(minibuffer.state.input, "foo");
+        assert_eq!(minibuffer.state.prompt, "Redo Query replace: ");
+        minibuffer.submit();
+        assert_eq!(minibuffer.state.input, "bar");
+        assert_eq!(
+            minibuffer.submit(),
+            MinibufferResult::QueryReplace {
+                pattern: "foo".to_string(),
+                replacement: "bar".to_string(),
+                is_regex: false,
+            }
+        );
+    }
+
+    #[test]
+    fn history_navigation_skips_duplicate_of_shown_input() {
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.set_history_move_to_front(false);
+        for input in ["a", "b", "b", "a"] {
+            minibuffer.start_execute_command();
+            minibuffer.state.input = input.to_string();
+            minibuffer.submit();
+        }
+        let stored: Vec<&String> = minibuffer.state.history.iter().collect();
+        assert_eq!(stored, vec!["a", "b", "a"]);
+
+        minibuffer.start_execute_command();
+        minibuffer.state.input = "a".to_string();
+        minibuffer.history_previous();
+        assert_eq!(minibuffer.state.input, "b");
+        minibuffer.history_previous();
+        assert_eq!(minibuffer.state.input, "a");
+        assert_eq!(minibuffer.state.history_index, Some(2));
+        minibuffer.history_next();
+        assert_eq!(minibuffer.state.input, "b");
+        minibuffer.history_next();
+        assert_eq!(minibuffer.state.input, "a");
+        minibuffer.history_next();
+        assert_eq!(minibuffer.state.input, "");
+        assert_eq!(minibuffer.state.history_index, None);
+    }
+
+    fn ctrl(ch: char) -> Key {
+        Key {
+            code: KeyCode::Char(ch),
+            modifiers: crate::input::keybinding::KeyModifiers {
+                ctrl: true,
+                alt: false,
+                shift: false,
+            },
+        }
+    }
+
+    fn plain(code: KeyCode) -> Key {
+        Key {
+            code,
+            modifiers: crate::input::keybinding::KeyModifiers {
+                ctrl: false,
+                alt: false,
+                shift: false,
+            },
+        }
+    }
+
+    #[test]
+    fn history_search_filters_entries_and_inserts_selection() {
+        let mut minibuffer = ModernMinibuffer::new();
+        for input in ["(+ 1 2)", "(message \"hi\")", "(+ 3 4)"] {
+            minibuffer.start_eval_expression();
+            minibuffer.state.input = input.to_string();
+            minibuffer.submit();
+        }
+
+        minibuffer.start_eval_expression();
+        minibuffer.state.input = "draft".to_string();
+        minibuffer.handle_key(ctrl('r'));
+        assert_eq!(minibuffer.state.prompt, "History search: ");
+        for ch in "+ ".chars() {
+            minibuffer.handle_key(plain(KeyCode::Char(ch)));
+        }
+        assert_eq!(minibuffer.state.completions, vec!["(+ 3 4)", "(+ 1 2)"]);
+
+        minibuffer.handle_key(ctrl('r'));
+        minibuffer.handle_key(plain(KeyCode::Enter));
+        assert_eq!(minibuffer.state.mode, MinibufferMode::EvalExpression);
+        assert_eq!(minibuffer.state.input, "(+ 1 2)");
+        assert_eq!(minibuffer.state.cursor_pos, 7);
+        assert_ne!(minibuffer.state.prompt, "History search: ");
+        assert!(minibuffer.state.history_search.is_none());
+    }
+
+    #[test]
+    fn history_search_cancel_restores_input() {
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.start_execute_command();
+        minibuffer.state.input = "goto-line".to_string();
+        minibuffer.submit();
+
+        minibuffer.start_execute_command();
+        minibuffer.state.input = "sa".to_string();
+        minibuffer.state.cursor_pos = 2;
+        minibuffer.handle_key(ctrl('r'));
+        minibuffer.handle_key(plain(KeyCode::Char('g')));
+        assert_eq!(minibuffer.state.completions, vec!["goto-line"]);
+        minibuffer.handle_key(ctrl('g'));
+        assert_eq!(minibuffer.state.mode, MinibufferMode::ExecuteCommand);
+        assert_eq!(minibuffer.state.prompt, "M-x ");
+        assert_eq!(minibuffer.state.input, "sa");
+        assert_eq!(minibuffer.state.cursor_pos, 2);
+    }
+
+    #[test]
+    fn history_rings_are_independent_per_mode() {
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.start_find_file(Some("/tmp/notes.txt"));
+        minibuffer.submit();
+        minibuffer.start_execute_command();
+        minibuffer.state.input = "save-buffer".to_string();
+        minibuffer.submit();
+        minibuffer.start_eval_expression();
+        minibuffer.state.input = "(+ 1 2)".to_string();
+        minibuffer.submit();
+
+        minibuffer.start_execute_command();
+        minibuffer.handle_key(ctrl('r'));
+        assert_eq!(minibuffer.state.completions, vec!["save-buffer"]);
+        minibuffer.handle_key(ctrl('g'));
+        minibuffer.deactivate();
+
+        minibuffer.start_find_file(Some(""));
+        minibuffer.handle_key(ctrl('r'));
+        assert_eq!(minibuffer.state.completions, vec!["/tmp/notes.txt"]);
+        minibuffer.deactivate();
+
+        minibuffer.start_eval_expression();
+        minibuffer.history_previous();
+        assert_eq!(minibuffer.state.input, "(+ 1 2)");
+        minibuffer.history_previous();
+        assert_eq!(minibuffer.state.input, "(+ 1 2)");
+    }
+
     #[test]
     fn goto_line_invalid_input_shows_error() {
         let mut minibuffer = ModernMinibuffer::new();
@@ -1067,6 +2045,86 @@ mod tests {
             MinibufferMode::ErrorDisplay { .. }
         ));
     }
+
+    fn type_keys(minibuffer: &mut ModernMinibuffer, text: &str) {
+        for ch in text.chars() {
+            minibuffer.handle_key(Key {
+                code: KeyCode::Char(ch),
+                modifiers: crate::input::keybinding::KeyModifiers {
+                    ctrl: false,
+                    alt: false,
+                    shift: false,
+                },
+            });
+        }
+    }
+
+    #[test]
+    fn tab_only_completion_populates_candidates_on_tab() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alpha.txt"), "").unwrap();
+        std::fs::write(dir.path().join("beta.txt"), "").unwrap();
+
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.set_auto_completion(false, 2);
+        minibuffer.start_find_file(Some(&format!("{}/", dir.path().display())));
+        assert!(minibuffer.state().completions.is_empty());
+        type_keys(&mut minibuffer, "al");
+        assert!(minibuffer.state().completions.is_empty());
+
+        minibuffer.handle_key(Key {
+            code: KeyCode::Tab,
+            modifiers: crate::input::keybinding::KeyModifiers {
+                ctrl: false,
+                alt: false,
+                shift: false,
+            },
+        });
+        let state = minibuffer.state();
+        assert_eq!(state.completions.len(), 1);
+        assert!(state.completions[0].ends_with("alpha.txt"));
+        assert_eq!(state.completion_annotations.len(), 1);
+    }
+
+    #[test]
+    fn auto_completion_threshold_controls_population_length() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alpha.txt"), "").unwrap();
+        let base = format!("{}/", dir.path().display());
+
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.set_auto_completion(true, 2);
+        minibuffer.start_find_file(Some(&base));
+        assert!(minibuffer.state().completions.is_empty());
+        type_keys(&mut minibuffer, "a");
+        assert!(minibuffer.state().completions.is_empty());
+        type_keys(&mut minibuffer, "l");
+        assert_eq!(minibuffer.state().completions.len(), 1);
+
+        // 長いディレクトリを入力し終えた直後は候補を出さない
+        type_keys(&mut minibuffer, "pha.txt/");
+        assert!(minibuffer.state().completions.is_empty());
+    }
+
+    #[test]
+    fn find_file_completions_keep_annotations_aligned() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "abc").unwrap();
+        std::fs::create_dir(dir.path().join("b")).unwrap();
+
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.set_auto_completion(true, 0);
+        minibuffer.start_find_file(Some(&format!("{}/", dir.path().display())));
+        let state = minibuffer.state();
+        assert_eq!(state.completions.len(), 2);
+        assert_eq!(state.completion_annotations.len(), 2);
+        assert!(state.completions[0].ends_with("a.txt"));
+        assert_eq!(state.completion_annotation(0), Some("3 B"));
+        assert_eq!(state.completion_annotation(1), Some("ディレクトリ"));
+
+        minibuffer.deactivate();
+        assert!(minibuffer.state().completion_annotations.is_empty());
+    }
 }
 
 impl Default for ModernMinibuffer {
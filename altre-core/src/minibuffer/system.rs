@@ -4,10 +4,10 @@
 
 use super::{
     completion::{CommandCompletion, PathCompletion},
-    MinibufferAction, MinibufferResult, ModernMinibuffer,
+    ComplexCommand, MinibufferAction, MinibufferResult, ModernMinibuffer,
 };
 use crate::alisp::integration::eval_in_minibuffer;
-use crate::alisp::Interpreter;
+use crate::alisp::{FunctionHelp, Interpreter};
 use crate::error::Result;
 use crate::input::keybinding::Key;
 use std::time::{Duration, Instant};
@@ -31,8 +31,10 @@ pub struct MinibufferSystem {
 /// ミニバッファシステムの設定
 #[derive(Debug, Clone)]
 pub struct MinibufferConfig {
-    /// 自動補完の有効化
+    /// 入力のたびにパス補完を行うか（無効なら Tab を押したときだけ候補を作る）
     pub auto_completion: bool,
+    /// パス補完を自動で行う最小の入力文字数
+    pub auto_completion_min_chars: usize,
     /// 補完候補の最大数
     pub max_completions: usize,
     /// エラーメッセージの表示時間
@@ -49,6 +51,7 @@ impl Default for MinibufferConfig {
     fn default() -> Self {
         Self {
             auto_completion: true,
+            auto_completion_min_chars: 2,
             max_completions: 50,
             error_display_duration: Duration::from_secs(5),
             info_display_duration: Duration::from_secs(3),
@@ -107,6 +110,37 @@ pub enum SystemResponse {
     ListBuffers,
     /// 行番号移動
     GotoLine(usize),
+    /// 指定行をウィンドウ先頭に表示
+    ScrollLineToTop(usize),
+    /// 文字位置移動
+    GotoChar(usize),
+    /// 表示桁移動
+    GotoColumn(usize),
+    /// fill-column の設定
+    SetFillColumn(usize),
+    /// y/n 確認の回答
+    YesOrNo(bool),
+    /// 既定ディレクトリの変更
+    ChangeDirectory(String),
+    /// 文字コードを指定して読み直し
+    RevertWithCodingSystem(String),
+    /// コードポイントを指定して文字を挿入
+    InsertChar(String),
+    /// 関数の説明を表示
+    DescribeFunction(String),
+    /// alisp のエラーとバックトレースを表示（debug-on-error が有効な場合）
+    Backtrace {
+        message: String,
+        frames: Vec<String>,
+    },
+    /// 一致数を数える
+    CountMatches { pattern: String, is_regex: bool },
+    /// 一致箇所の一覧表示
+    Occur(String),
+    /// 行の絞り込み
+    FilterLines { pattern: String, keep: bool },
+    /// 矩形を文字列で置き換える
+    StringRectangle(String),
     /// クエリ置換開始
     QueryReplace {
         pattern: String,
@@ -139,9 +173,11 @@ impl MinibufferSystem {
     /// 設定付きでミニバッファシステムを作成
     pub fn with_config(config: MinibufferConfig) -> Self {
         let path_completion = PathCompletion::new().with_hidden_files(config.show_hidden_files);
+        let mut minibuffer = ModernMinibuffer::new();
+        minibuffer.set_auto_completion(config.auto_completion, config.auto_completion_min_chars);
 
         Self {
-            minibuffer: ModernMinibuffer::new(),
+            minibuffer,
             path_completion,
             command_completion: CommandCompletion::new(),
             last_update: Instant::now(),
@@ -154,15 +190,27 @@ impl MinibufferSystem {
     pub fn state(&self) -> SystemState {
         match &self.minibuffer.state().mode {
             super::MinibufferMode::Inactive => SystemState::Inactive,
-            super::MinibufferMode::FindFile => SystemState::FindFile,
+            super::MinibufferMode::FindFile | super::MinibufferMode::ChangeDirectory => {
+                SystemState::FindFile
+            }
             super::MinibufferMode::ExecuteCommand => SystemState::ExecuteCommand,
             super::MinibufferMode::EvalExpression => SystemState::ExecuteCommand,
-            super::MinibufferMode::SwitchBuffer | super::MinibufferMode::KillBuffer => {
-                SystemState::ExecuteCommand
-            }
+            super::MinibufferMode::SwitchBuffer
+            | super::MinibufferMode::KillBuffer
+            | super::MinibufferMode::CodingSystem
+            | super::MinibufferMode::InsertChar
+            | super::MinibufferMode::DescribeFunction
+            | super::MinibufferMode::CountMatches { .. }
+            | super::MinibufferMode::Occur
+            | super::MinibufferMode::FilterLines { .. }
+            | super::MinibufferMode::StringRectangle => SystemState::ExecuteCommand,
             super::MinibufferMode::ErrorDisplay { .. } => SystemState::ErrorDisplay,
             super::MinibufferMode::InfoDisplay { .. } => SystemState::InfoDisplay,
-            super::MinibufferMode::GotoLine => SystemState::GotoLine,
+            super::MinibufferMode::GotoLine
+            | super::MinibufferMode::ScrollLineToTop
+            | super::MinibufferMode::GotoChar
+            | super::MinibufferMode::GotoColumn
+            | super::MinibufferMode::SetFillColumn => SystemState::GotoLine,
             _ => SystemState::Inactive,
         }
     }
@@ -180,6 +228,11 @@ impl MinibufferSystem {
         )
     }
 
+    /// 貼り付けた文字列を入力中のプロンプトへ挿入する（プロンプトがなければ偽）
+    pub fn insert_text(&mut self, text: &str) -> bool {
+        self.minibuffer.insert_text(text)
+    }
+
     /// 現在の入力内容を取得
     pub fn current_input(&self) -> &str {
         &self.minibuffer.state().input
@@ -236,6 +289,25 @@ impl MinibufferSystem {
                 Ok(SystemResponse::FileOperation(FileOperation::SaveAs(path)))
             }
             MinibufferResult::GotoLine(line) => Ok(SystemResponse::GotoLine(line)),
+            MinibufferResult::ScrollLineToTop(line) => Ok(SystemResponse::ScrollLineToTop(line)),
+            MinibufferResult::GotoChar(pos) => Ok(SystemResponse::GotoChar(pos)),
+            MinibufferResult::GotoColumn(column) => Ok(SystemResponse::GotoColumn(column)),
+            MinibufferResult::SetFillColumn(column) => Ok(SystemResponse::SetFillColumn(column)),
+            MinibufferResult::YesOrNo(answer) => Ok(SystemResponse::YesOrNo(answer)),
+            MinibufferResult::ChangeDirectory(path) => Ok(SystemResponse::ChangeDirectory(path)),
+            MinibufferResult::CodingSystem(name) => {
+                Ok(SystemResponse::RevertWithCodingSystem(name))
+            }
+            MinibufferResult::InsertChar(code) => Ok(SystemResponse::InsertChar(code)),
+            MinibufferResult::DescribeFunction(name) => Ok(SystemResponse::DescribeFunction(name)),
+            MinibufferResult::CountMatches { pattern, is_regex } => {
+                Ok(SystemResponse::CountMatches { pattern, is_regex })
+            }
+            MinibufferResult::Occur(pattern) => Ok(SystemResponse::Occur(pattern)),
+            MinibufferResult::FilterLines { pattern, keep } => {
+                Ok(SystemResponse::FilterLines { pattern, keep })
+            }
+            MinibufferResult::StringRectangle(text) => Ok(SystemResponse::StringRectangle(text)),
             MinibufferResult::QueryReplace {
                 pattern,
                 replacement,
@@ -267,6 +339,25 @@ impl MinibufferSystem {
                 Ok(SystemResponse::FileOperation(FileOperation::SaveAs(path)))
             }
             MinibufferResult::GotoLine(line) => Ok(SystemResponse::GotoLine(line)),
+            MinibufferResult::ScrollLineToTop(line) => Ok(SystemResponse::ScrollLineToTop(line)),
+            MinibufferResult::GotoChar(pos) => Ok(SystemResponse::GotoChar(pos)),
+            MinibufferResult::GotoColumn(column) => Ok(SystemResponse::GotoColumn(column)),
+            MinibufferResult::SetFillColumn(column) => Ok(SystemResponse::SetFillColumn(column)),
+            MinibufferResult::YesOrNo(answer) => Ok(SystemResponse::YesOrNo(answer)),
+            MinibufferResult::ChangeDirectory(path) => Ok(SystemResponse::ChangeDirectory(path)),
+            MinibufferResult::CodingSystem(name) => {
+                Ok(SystemResponse::RevertWithCodingSystem(name))
+            }
+            MinibufferResult::InsertChar(code) => Ok(SystemResponse::InsertChar(code)),
+            MinibufferResult::DescribeFunction(name) => Ok(SystemResponse::DescribeFunction(name)),
+            MinibufferResult::CountMatches { pattern, is_regex } => {
+                Ok(SystemResponse::CountMatches { pattern, is_regex })
+            }
+            MinibufferResult::Occur(pattern) => Ok(SystemResponse::Occur(pattern)),
+            MinibufferResult::FilterLines { pattern, keep } => {
+                Ok(SystemResponse::FilterLines { pattern, keep })
+            }
+            MinibufferResult::StringRectangle(text) => Ok(SystemResponse::StringRectangle(text)),
             MinibufferResult::QueryReplace {
                 pattern,
                 replacement,
@@ -351,16 +442,22 @@ impl MinibufferSystem {
 
         let outcome = eval_in_minibuffer(&mut self.alisp_interpreter, expression);
 
+        let mut message = outcome.output;
+        if !outcome.messages.is_empty() {
+            let extras = outcome.messages.join(" | ");
+            if !extras.is_empty() {
+                message = format!("{} ({})", message, extras);
+            }
+        }
         if outcome.is_error {
-            self.minibuffer.show_error(outcome.output);
-        } else {
-            let mut message = outcome.output;
-            if !outcome.messages.is_empty() {
-                let extras = outcome.messages.join(" | ");
-                if !extras.is_empty() {
-                    message = format!("{} ({})", message, extras);
-                }
+            self.minibuffer.show_error(message.clone());
+            if !outcome.backtrace.is_empty() {
+                return Ok(SystemResponse::Backtrace {
+                    message,
+                    frames: outcome.backtrace,
+                });
             }
+        } else {
             self.minibuffer.show_info(message);
         }
 
@@ -395,16 +492,38 @@ impl MinibufferSystem {
         Ok(SystemResponse::Continue)
     }
 
-    /// バッファ切り替えを開始
+    /// バッファ切り替えを開始（`buffers` は最近表示した順）
     pub fn start_switch_buffer(
         &mut self,
         buffers: &[String],
         initial: Option<&str>,
+        default: Option<&str>,
     ) -> Result<SystemResponse> {
-        self.minibuffer.start_switch_buffer(buffers, initial);
+        self.minibuffer
+            .start_switch_buffer(buffers, initial, default);
         Ok(SystemResponse::Continue)
     }
 
+    /// バッファ名の補完にあいまい一致を使うか
+    pub fn fuzzy_buffer_completion(&self) -> bool {
+        self.minibuffer.fuzzy_buffer_completion()
+    }
+
+    /// バッファ名の補完方式を設定（`false` で前方一致）
+    pub fn set_fuzzy_buffer_completion(&mut self, enabled: bool) {
+        self.minibuffer.set_fuzzy_buffer_completion(enabled);
+    }
+
+    /// 再利用した履歴を先頭へ移すか
+    pub fn history_move_to_front(&self) -> bool {
+        self.minibuffer.history_move_to_front()
+    }
+
+    /// 再利用した履歴を先頭へ移すかを設定（`false` なら直前と同じ入力だけを省く）
+    pub fn set_history_move_to_front(&mut self, enabled: bool) {
+        self.minibuffer.set_history_move_to_front(enabled);
+    }
+
     /// バッファ削除を開始
     pub fn start_kill_buffer(
         &mut self,
@@ -431,6 +550,153 @@ impl MinibufferSystem {
         Ok(SystemResponse::Continue)
     }
 
+    /// ウィンドウ先頭に表示する行番号の入力を開始
+    pub fn start_scroll_line_to_top(
+        &mut self,
+        default_line: usize,
+        max_line: usize,
+    ) -> Result<SystemResponse> {
+        self.minibuffer
+            .start_scroll_line_to_top(default_line, max_line);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 移動先の文字位置の入力を開始
+    pub fn start_goto_char(
+        &mut self,
+        default_pos: usize,
+        max_pos: usize,
+    ) -> Result<SystemResponse> {
+        self.minibuffer.start_goto_char(default_pos, max_pos);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 表示桁ジャンプを開始
+    pub fn start_goto_column(
+        &mut self,
+        default_column: usize,
+        max_column: usize,
+    ) -> Result<SystemResponse> {
+        self.minibuffer
+            .start_goto_column(default_column, max_column);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// fill-column の入力を開始
+    pub fn start_set_fill_column(&mut self, current: usize) -> Result<SystemResponse> {
+        self.minibuffer.start_set_fill_column(current);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// y/n の確認を開始
+    pub fn start_yes_or_no(&mut self, question: &str) -> Result<SystemResponse> {
+        self.minibuffer.start_yes_or_no(question);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 読み直しに使う文字コード名の入力を開始
+    pub fn start_coding_system(&mut self, initial: Option<&str>) -> Result<SystemResponse> {
+        self.minibuffer.start_coding_system(initial);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 挿入する文字のコードポイント入力を開始
+    pub fn start_insert_char(&mut self) -> Result<SystemResponse> {
+        self.minibuffer.start_insert_char(None);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 説明を表示する関数名の入力を開始（コマンド名と alisp の関数名を補完する）
+    pub fn start_describe_function(&mut self, commands: &[String]) -> Result<SystemResponse> {
+        let mut functions = commands.to_vec();
+        functions.extend(self.alisp_interpreter.function_names());
+        functions.sort();
+        functions.dedup();
+        self.minibuffer.start_describe_function(&functions);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// `M-:` の評価に使うインタプリタを差し替える（init ファイルを評価した環境を引き継ぐ）
+    pub fn set_alisp_interpreter(&mut self, mut interpreter: Interpreter) {
+        interpreter.set_debug_on_error(self.alisp_interpreter.debug_on_error());
+        self.alisp_interpreter = interpreter;
+    }
+
+    /// `M-:` の評価環境の定義を消して組み込み関数だけに戻す
+    pub fn reset_alisp_environment(&mut self) {
+        self.alisp_interpreter.reset_environment();
+    }
+
+    /// `M-:` の評価でエラー時にバックトレースを記録するか
+    pub fn debug_on_error(&self) -> bool {
+        self.alisp_interpreter.debug_on_error()
+    }
+
+    /// `M-:` の評価でエラー時にバックトレースを記録するかを設定
+    pub fn set_debug_on_error(&mut self, enabled: bool) {
+        self.alisp_interpreter.set_debug_on_error(enabled);
+    }
+
+    /// ミニバッファの評価環境にある alisp 関数の情報
+    pub fn describe_alisp_function(&mut self, name: &str) -> Option<FunctionHelp> {
+        self.alisp_interpreter.describe_function(name)
+    }
+
+    /// 一致数を数える検索語の入力を開始
+    pub fn start_count_matches(
+        &mut self,
+        is_regex: bool,
+        initial: Option<&str>,
+    ) -> Result<SystemResponse> {
+        self.minibuffer.start_count_matches(is_regex, initial);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 一致箇所を一覧表示する検索語の入力を開始
+    pub fn start_occur(&mut self, initial: Option<&str>) -> Result<SystemResponse> {
+        self.minibuffer.start_occur(initial);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 行を絞り込む正規表現の入力を開始
+    pub fn start_string_rectangle(&mut self) -> Result<SystemResponse> {
+        self.minibuffer.start_string_rectangle();
+        Ok(SystemResponse::Continue)
+    }
+
+    pub fn start_filter_lines(
+        &mut self,
+        keep: bool,
+        initial: Option<&str>,
+    ) -> Result<SystemResponse> {
+        self.minibuffer.start_filter_lines(keep, initial);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 既定ディレクトリの入力を開始
+    pub fn start_change_directory(&mut self, initial_path: Option<&str>) -> Result<SystemResponse> {
+        self.minibuffer.start_change_directory(initial_path);
+        Ok(SystemResponse::Continue)
+    }
+
+    /// 最後に実行したミニバッファ入力コマンド
+    pub fn last_command(&self) -> Option<&ComplexCommand> {
+        self.minibuffer.last_command()
+    }
+
+    /// 最後のミニバッファ入力コマンドを編集して再実行
+    pub fn start_repeat_command(
+        &mut self,
+        buffers: &[String],
+        max_line: usize,
+    ) -> Result<SystemResponse> {
+        if !self.minibuffer.start_repeat_command(buffers, max_line) {
+            self.minibuffer
+                .show_error("再実行できるコマンドがありません".to_string());
+        }
+        Ok(SystemResponse::Continue)
+    }
+
     /// エラーメッセージを表示
     pub fn show_error(&mut self, message: impl Into<String>) -> Result<SystemResponse> {
         self.minibuffer.show_error(message.into());
@@ -471,6 +737,10 @@ impl MinibufferSystem {
         // パス補完の設定を更新
         self.path_completion =
             PathCompletion::new().with_hidden_files(self.config.show_hidden_files);
+        self.minibuffer.set_auto_completion(
+            self.config.auto_completion,
+            self.config.auto_completion_min_chars,
+        );
     }
 
     /// 現在の設定を取得
@@ -494,7 +764,7 @@ impl MinibufferSystem {
             last_update: self.last_update,
             uptime: self.last_update.elapsed(),
             completions_count: self.minibuffer.state().completions.len(),
-            history_size: self.minibuffer.state().history.len(),
+            history_size: self.minibuffer.state().history_len(),
         }
     }
 }
@@ -537,6 +807,12 @@ impl MinibufferSystemBuilder {
         self
     }
 
+    /// パス補完を自動で行う最小の入力文字数を設定
+    pub fn auto_completion_min_chars(mut self, min_chars: usize) -> Self {
+        self.config.auto_completion_min_chars = min_chars;
+        self
+    }
+
     /// 最大補完候補数を設定
     pub fn max_completions(mut self, max: usize) -> Self {
         self.config.max_completions = max;
@@ -594,11 +870,13 @@ mod tests {
     fn test_system_builder() {
         let system = MinibufferSystemBuilder::new()
             .auto_completion(false)
+            .auto_completion_min_chars(4)
             .max_completions(25)
             .show_hidden_files(true)
             .build();
 
         assert!(!system.config().auto_completion);
+        assert_eq!(system.config().auto_completion_min_chars, 4);
         assert_eq!(system.config().max_completions, 25);
         assert!(system.config().show_hidden_files);
     }
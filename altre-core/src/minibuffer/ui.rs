@@ -8,6 +8,10 @@ use ratatui::{
     style::{Color, Modifier, Style},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 省略箇所に挿入する記号
+const ELLIPSIS: &str = "...";
 
 /// ミニバッファのUI設定
 #[derive(Debug, Clone)]
@@ -30,6 +34,8 @@ pub struct MinibufferUIConfig {
     pub selected_completion_style: Style,
     /// 通常の補完候補のスタイル
     pub completion_style: Style,
+    /// 補完候補の注釈のスタイル
+    pub annotation_style: Style,
 }
 
 impl Default for MinibufferUIConfig {
@@ -49,6 +55,9 @@ impl Default for MinibufferUIConfig {
                 .fg(Color::White)
                 .add_modifier(Modifier::BOLD),
             completion_style: Style::default().fg(Color::Gray),
+            annotation_style: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::DIM),
         }
     }
 }
@@ -178,6 +187,7 @@ impl MinibufferRenderer {
             return;
         }
 
+        let inner_width = area.width.saturating_sub(2) as usize;
         let items: Vec<ListItem<'_>> = state
             .completions
             .iter()
@@ -190,7 +200,12 @@ impl MinibufferRenderer {
                     self.config.completion_style
                 };
 
-                ListItem::new(completion.as_str()).style(style)
+                ListItem::new(self.completion_line(
+                    completion,
+                    state.completion_annotation(i),
+                    inner_width,
+                ))
+                .style(style)
             })
             .collect();
 
@@ -200,6 +215,27 @@ impl MinibufferRenderer {
         frame.render_widget(list, area);
     }
 
+    /// 補完候補と、その後ろに控えめに表示する注釈
+    ///
+    /// 候補が幅に収まらなければ中央を省略する（注釈より候補の表示を優先する）。
+    fn completion_line<'a>(
+        &self,
+        completion: &'a str,
+        annotation: Option<&'a str>,
+        width: usize,
+    ) -> Line<'a> {
+        let annotation_width = annotation.map_or(0, |text| text.width() + 2);
+        let budget = width.saturating_sub(annotation_width).max(width / 2);
+        let mut spans = vec![Span::raw(truncate_middle(completion, budget))];
+        if let Some(annotation) = annotation {
+            spans.push(Span::styled(
+                format!("  {}", annotation),
+                self.config.annotation_style,
+            ));
+        }
+        Line::from(spans)
+    }
+
     /// 補完候補リストの高さを計算
     fn calculate_completion_height(&self, state: &MinibufferState) -> u16 {
         if state.completions.is_empty() {
@@ -349,6 +385,9 @@ impl MinibufferStyles {
                 .fg(Color::White)
                 .add_modifier(Modifier::BOLD),
             completion_style: Style::default().fg(Color::DarkGray),
+            annotation_style: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::DIM),
         }
     }
 
@@ -369,6 +408,9 @@ impl MinibufferStyles {
                 .fg(Color::Black)
                 .add_modifier(Modifier::BOLD),
             completion_style: Style::default().fg(Color::Gray),
+            annotation_style: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::DIM),
         }
     }
 
@@ -394,10 +436,69 @@ impl MinibufferStyles {
                 .fg(background_color)
                 .add_modifier(Modifier::BOLD),
             completion_style: Style::default().fg(secondary_color),
+            annotation_style: Style::default()
+                .fg(secondary_color)
+                .add_modifier(Modifier::DIM),
         }
     }
 }
 
+/// 表示幅 `max_width` に収まるよう中央を `...` で省略する
+///
+/// パスならファイル名を残し、ディレクトリ部分を区切りの位置で切り詰める（`/very/long/.../file.rs`）。
+pub fn truncate_middle(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    let ellipsis_width = ELLIPSIS.width();
+    if max_width <= ellipsis_width {
+        return take_tail(text, max_width);
+    }
+    let budget = max_width - ellipsis_width;
+
+    if let Some(slash) = text.trim_end_matches('/').rfind('/') {
+        let tail = &text[slash..];
+        if tail.width() < budget {
+            let head = take_head(&text[..slash], budget - tail.width());
+            let head = match head.rfind('/') {
+                Some(index) => &head[..=index],
+                None => head.as_str(),
+            };
+            return format!("{}{}{}", head, ELLIPSIS, tail);
+        }
+    }
+
+    let head = take_head(text, budget / 2);
+    let tail = take_tail(text, budget - head.width());
+    format!("{}{}{}", head, ELLIPSIS, tail)
+}
+
+/// 先頭から表示幅 `width` に収まるだけ取り出す
+fn take_head(text: &str, width: usize) -> String {
+    let mut used = 0usize;
+    text.chars()
+        .take_while(|ch| {
+            used += ch.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
+/// 末尾から表示幅 `width` に収まるだけ取り出す
+fn take_tail(text: &str, width: usize) -> String {
+    let mut used = 0usize;
+    let mut tail: Vec<char> = text
+        .chars()
+        .rev()
+        .take_while(|ch| {
+            used += ch.width().unwrap_or(0);
+            used <= width
+        })
+        .collect();
+    tail.reverse();
+    tail.into_iter().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -517,4 +618,50 @@ mod tests {
             "Save"
         );
     }
+
+    #[test]
+    fn completion_line_appends_dimmed_annotation() {
+        let renderer = MinibufferRenderer::new();
+        let line = renderer.completion_line("src/", Some("ディレクトリ"), 80);
+        assert_eq!(line.spans.len(), 2);
+        assert_eq!(line.spans[1].content, "  ディレクトリ");
+        assert!(line.spans[1].style.add_modifier.contains(Modifier::DIM));
+
+        let plain = renderer.completion_line("main.rs", None, 80);
+        assert_eq!(plain.spans.len(), 1);
+    }
+
+    #[test]
+    fn truncate_middle_keeps_file_name_visible() {
+        let path = "/very/long/directory/structure/file.rs";
+        assert_eq!(truncate_middle(path, 80), path);
+        assert_eq!(truncate_middle(path, 24), "/very/long/.../file.rs");
+        assert_eq!(truncate_middle(path, 16), "/.../file.rs");
+    }
+
+    #[test]
+    fn truncate_middle_elides_long_names_by_width() {
+        assert_eq!(
+            truncate_middle("/a/averyveryverylongfilename.txt", 12),
+            "/a/a...e.txt"
+        );
+        assert_eq!(truncate_middle("abcdef", 3), "def");
+        let elided = truncate_middle("日本語のファイル名.txt", 10);
+        assert_eq!(elided, "日....txt");
+        assert!(elided.width() <= 10);
+    }
+
+    #[test]
+    fn completion_line_truncates_only_for_display() {
+        let renderer = MinibufferRenderer::new();
+        let candidate = "/very/long/directory/structure/file.rs";
+        let line = renderer.completion_line(candidate, Some("file"), 30);
+        let text: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(text, "/very/long/.../file.rs  file");
+        assert_eq!(candidate.width(), 38);
+    }
 }
@@ -1,5 +1,6 @@
 //! 検索用マッチャー
 
+use super::regex::{find_regex_matches, RegexError};
 use super::types::SearchMatch;
 
 /// 文字列マッチング戦略
@@ -72,6 +73,132 @@ impl StringMatcher for LiteralMatcher {
     }
 }
 
+impl<M: StringMatcher + ?Sized> StringMatcher for &M {
+    fn find_matches(&self, text: &str, pattern: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+        (**self).find_matches(text, pattern, case_sensitive)
+    }
+}
+
+/// 検索語の空白を緩く扱うマッチャー
+///
+/// 検索語中の連続した空白が、本文の 1 文字以上の空白（改行を除く）に一致する。
+/// 検索語に空白がなければ内側のマッチャーに任せる。
+#[derive(Debug, Default, Clone)]
+pub struct LaxWhitespaceMatcher<M> {
+    inner: M,
+}
+
+impl<M: StringMatcher> LaxWhitespaceMatcher<M> {
+    /// 内側のマッチャーを包んで作成
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+/// 空白を緩く扱う検索語の要素
+#[derive(Debug, Clone, Copy)]
+enum LaxToken {
+    Char(char),
+    Whitespace,
+}
+
+impl<M: StringMatcher> StringMatcher for LaxWhitespaceMatcher<M> {
+    fn find_matches(&self, text: &str, pattern: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+        if !pattern.contains(' ') {
+            return self.inner.find_matches(text, pattern, case_sensitive);
+        }
+
+        let mut tokens = Vec::new();
+        for ch in pattern.chars() {
+            match (ch, tokens.last()) {
+                (' ', Some(LaxToken::Whitespace)) => {}
+                (' ', _) => tokens.push(LaxToken::Whitespace),
+                (ch, _) => tokens.push(LaxToken::Char(ch)),
+            }
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let is_space = |ch: char| ch.is_whitespace() && ch != '\n';
+        let match_at = |start: usize| -> Option<usize> {
+            let mut pos = start;
+            for token in &tokens {
+                match token {
+                    LaxToken::Char(pat_ch) => {
+                        if pos >= chars.len() || !chars_equal(chars[pos], *pat_ch, case_sensitive) {
+                            return None;
+                        }
+                        pos += 1;
+                    }
+                    LaxToken::Whitespace => {
+                        let run = chars[pos..].iter().take_while(|ch| is_space(**ch)).count();
+                        if run == 0 {
+                            return None;
+                        }
+                        pos += run;
+                    }
+                }
+            }
+            Some(pos)
+        };
+
+        let mut line = 0usize;
+        let mut column = 0usize;
+        let mut matches = Vec::new();
+        for (start, ch) in chars.iter().enumerate() {
+            if let Some(end) = match_at(start) {
+                matches.push(SearchMatch {
+                    start,
+                    end,
+                    line,
+                    column,
+                });
+            }
+            if *ch == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+
+        matches
+    }
+}
+
+/// 正規表現マッチャー
+///
+/// 検索語を正規表現として扱う。不正な正規表現は一致なしとして扱うため、
+/// コンパイルの成否を知りたいときは [`RegexMatcher::try_find_matches`] を使う。
+#[derive(Debug, Default, Clone)]
+pub struct RegexMatcher;
+
+impl RegexMatcher {
+    /// インスタンスを作成
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 正規表現をコンパイルしてすべてのマッチを返す（不正な正規表現ならエラー）
+    pub fn try_find_matches(
+        &self,
+        text: &str,
+        pattern: &str,
+        case_sensitive: bool,
+    ) -> Result<Vec<SearchMatch>, RegexError> {
+        find_regex_matches(pattern, text, case_sensitive)
+    }
+}
+
+impl StringMatcher for RegexMatcher {
+    fn find_matches(&self, text: &str, pattern: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        self.try_find_matches(text, pattern, case_sensitive)
+            .unwrap_or_default()
+    }
+}
+
 fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
     if case_sensitive {
         return a == b;
@@ -85,7 +212,7 @@ fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{LiteralMatcher, StringMatcher};
+    use super::{LaxWhitespaceMatcher, LiteralMatcher, RegexMatcher, StringMatcher};
 
     #[test]
     fn finds_basic_matches() {
@@ -119,4 +246,31 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].start, 0);
     }
+
+    #[test]
+    fn regex_matcher_finds_matches_and_reports_invalid_patterns() {
+        let matcher = RegexMatcher::new();
+        let result = matcher.find_matches("a1 b22\nc333", "[0-9]+", true);
+        assert_eq!(result.len(), 3);
+        assert_eq!((result[2].line, result[2].column), (1, 1));
+        assert_eq!(matcher.find_matches("ABC", "b", false).len(), 1);
+        assert!(matcher.find_matches("abc", "b", false).len() == 1);
+
+        assert!(matcher.find_matches("a[b", "a[", true).is_empty());
+        assert!(matcher.try_find_matches("a[b", "a[", true).is_err());
+    }
+
+    #[test]
+    fn lax_whitespace_matches_runs_of_spaces_and_tabs() {
+        let matcher = LaxWhitespaceMatcher::new(LiteralMatcher::new());
+        let result = matcher.find_matches("foo  bar foo\tbar foobar", "foo bar", true);
+        assert_eq!(result.len(), 2);
+        assert_eq!((result[0].start, result[0].end), (0, 8));
+        assert_eq!((result[1].start, result[1].end), (9, 16));
+
+        assert!(matcher.find_matches("foo\nbar", "foo bar", true).is_empty());
+        assert!(LiteralMatcher::new()
+            .find_matches("foo  bar", "foo bar", true)
+            .is_empty());
+    }
 }
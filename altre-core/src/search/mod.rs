@@ -9,21 +9,39 @@ mod state;
 pub mod types;
 
 use crate::buffer::TextEditor;
-use matcher::{LiteralMatcher, StringMatcher};
+use matcher::{LaxWhitespaceMatcher, LiteralMatcher, RegexMatcher, StringMatcher};
+use regex::{find_regex_matches, lax_whitespace_regex};
 use state::SearchState;
 use types::SearchMatch;
 
-pub use regex::RegexError;
+pub use regex::{filter_lines, RegexError};
 pub use replace::{QueryReplaceController, ReplaceProgress, ReplaceStart, ReplaceSummary};
 pub use types::{HighlightKind, SearchDirection, SearchHighlight, SearchStatus, SearchUiState};
 
+/// 検索履歴に残す検索語の数
+pub const SEARCH_RING_MAX: usize = 32;
+
 /// 検索制御インターフェース
 #[derive(Debug)]
 pub struct SearchController<M: StringMatcher = LiteralMatcher> {
     matcher: M,
     state: SearchState,
     last_pattern: Option<String>,
+    /// 直近確定した正規表現検索の検索語（リテラル検索とは別に覚える）
+    last_regex_pattern: Option<String>,
+    /// 確定した検索語の履歴（古い順、リテラル検索用）
+    search_ring: Vec<String>,
+    /// 確定した検索語の履歴（古い順、正規表現検索用）
+    regex_search_ring: Vec<String>,
     case_sensitive: bool,
+    /// M-c で指定した大文字・小文字の区別（`None` なら検索語に大文字があるかで決める）
+    force_case: Option<bool>,
+    /// 検索開始時に空白を緩く扱うか
+    lax_whitespace_default: bool,
+    /// 失敗時に一致する最長の先頭部分の位置を示すか
+    failure_hint: bool,
+    /// 現在の一致以外もハイライトするか（無効なら現在の一致だけ）
+    lazy_highlight: bool,
     highlights: Vec<SearchHighlight>,
     ui_state: Option<SearchUiState>,
     text_cache: String,
@@ -43,7 +61,14 @@ impl<M: StringMatcher> SearchController<M> {
             matcher,
             state: SearchState::new(),
             last_pattern: None,
+            last_regex_pattern: None,
+            search_ring: Vec::new(),
+            regex_search_ring: Vec::new(),
             case_sensitive: true,
+            force_case: None,
+            lax_whitespace_default: false,
+            failure_hint: true,
+            lazy_highlight: true,
             highlights: Vec::new(),
             ui_state: None,
             text_cache: String::new(),
@@ -74,32 +99,194 @@ impl<M: StringMatcher> SearchController<M> {
         }
     }
 
+    /// 検索中で、入力済みの検索語が一致しない状態か
+    ///
+    /// 入力途中で正規表現として不正な間は、直前にコンパイルできた検索語で探すため失敗にしない。
+    pub fn is_failing(&self) -> bool {
+        self.state.active && !self.state.pattern.is_empty() && self.state.failed
+    }
+
+    /// 直近確定した検索語を忘れる（次の検索は空の検索語で始まる）
+    pub fn reset_last_pattern(&mut self) {
+        self.last_pattern = None;
+        self.last_regex_pattern = None;
+    }
+
+    /// 検索開始時に空白を緩く扱うかを設定
+    pub fn set_lax_whitespace_default(&mut self, enabled: bool) {
+        self.lax_whitespace_default = enabled;
+    }
+
+    /// 検索開始時に空白を緩く扱うか
+    pub fn lax_whitespace_default(&self) -> bool {
+        self.lax_whitespace_default
+    }
+
+    /// 失敗時に一致する最長の先頭部分の位置を示すかを設定
+    pub fn set_failure_hint(&mut self, enabled: bool) {
+        self.failure_hint = enabled;
+    }
+
+    /// 失敗時に一致する最長の先頭部分の位置を示すか
+    pub fn failure_hint(&self) -> bool {
+        self.failure_hint
+    }
+
+    /// 現在の一致以外もハイライトするかを設定（大きなバッファでは無効にすると軽くなる）
+    pub fn set_lazy_highlight(&mut self, enabled: bool) {
+        self.lazy_highlight = enabled;
+        if self.state.active {
+            self.rebuild_highlights();
+        }
+    }
+
+    /// 現在の一致以外もハイライトするか
+    pub fn lazy_highlight(&self) -> bool {
+        self.lazy_highlight
+    }
+
     /// 直近確定した検索語を取得
     pub fn last_pattern(&self) -> Option<&str> {
         self.last_pattern.as_deref()
     }
 
-    /// 検索を開始
+    /// 直近確定した正規表現検索の検索語を取得
+    pub fn last_regex_pattern(&self) -> Option<&str> {
+        self.last_regex_pattern.as_deref()
+    }
+
+    /// 確定した検索語の履歴（古い順）を取得
+    pub fn search_ring(&self, regex: bool) -> &[String] {
+        if regex {
+            &self.regex_search_ring
+        } else {
+            &self.search_ring
+        }
+    }
+
+    /// 検索中に 1 つ古い履歴の検索語へ置き換えて探し直す（最も古いものの次は最も新しいものへ戻る）
+    pub fn history_prev(&mut self, editor: &mut TextEditor) {
+        self.step_history(editor, false);
+    }
+
+    /// 検索中に 1 つ新しい履歴の検索語へ置き換えて探し直す（最も新しいものの次は最も古いものへ戻る）
+    pub fn history_next(&mut self, editor: &mut TextEditor) {
+        self.step_history(editor, true);
+    }
+
+    fn step_history(&mut self, editor: &mut TextEditor, newer: bool) {
+        if !self.state.active {
+            return;
+        }
+        let ring = self.search_ring(self.state.regex);
+        let len = ring.len();
+        if len == 0 {
+            return;
+        }
+        // 検索開始時に入る直前の検索語は、履歴の最新の位置にあるものとして扱う
+        let current = self
+            .state
+            .ring_index
+            .or_else(|| (ring[len - 1] == self.state.pattern).then_some(len - 1));
+        let index = match (current, newer) {
+            (Some(i), false) => (i + len - 1) % len,
+            (Some(i), true) => (i + 1) % len,
+            (None, false) => len - 1,
+            (None, true) => 0,
+        };
+        self.state.pattern = ring[index].clone();
+        self.state.ring_index = Some(index);
+        self.state.wrapped = false;
+        self.state.last_valid_regex = None;
+        self.update_case_sensitivity();
+        let text = editor.to_string();
+        self.recompute_matches(&text);
+        self.select_match_near_cursor(editor, self.state.start_char_index);
+    }
+
+    /// カーソル位置から検索を開始
     pub fn start(&mut self, editor: &mut TextEditor, direction: SearchDirection) {
+        let position = editor.cursor().char_pos;
+        self.start_from(editor, direction, position);
+    }
+
+    /// カーソル位置から正規表現検索を開始
+    pub fn start_regex(&mut self, editor: &mut TextEditor, direction: SearchDirection) {
+        let position = editor.cursor().char_pos;
+        self.begin(editor, direction, position, true);
+    }
+
+    /// 文字位置 `position` を起点に検索を開始する（キャンセル時はカーソル位置へ戻る）
+    ///
+    /// `position` を 0 にすると、カーソル位置に関係なくバッファ先頭から探す。
+    pub fn start_from(
+        &mut self,
+        editor: &mut TextEditor,
+        direction: SearchDirection,
+        position: usize,
+    ) {
+        self.begin(editor, direction, position, false);
+    }
+
+    fn begin(
+        &mut self,
+        editor: &mut TextEditor,
+        direction: SearchDirection,
+        position: usize,
+        regex: bool,
+    ) {
         let cursor = *editor.cursor();
         self.state.reset();
         self.state.active = true;
+        self.state.regex = regex;
         self.state.direction = direction;
         self.state.start_cursor = Some(cursor);
-        self.state.start_char_index = cursor.char_pos;
-        self.state.pattern = self.last_pattern.clone().unwrap_or_default();
+        self.state.start_char_index = position;
+        let last = if regex {
+            &self.last_regex_pattern
+        } else {
+            &self.last_pattern
+        };
+        self.state.pattern = last.clone().unwrap_or_default();
+        self.state.lax_whitespace = self.lax_whitespace_default;
+        self.force_case = None;
         self.update_case_sensitivity();
         self.state.failed = false;
 
         if !self.state.pattern.is_empty() {
             let text = editor.to_string();
             self.recompute_matches(&text);
-            self.select_match_near_cursor(editor, cursor.char_pos);
+            self.select_match_near_cursor(editor, position);
         } else {
             self.update_ui_state();
         }
     }
 
+    /// 文字位置 `position` 以降で `pattern` に最初に一致する箇所へカーソルを移す
+    ///
+    /// インクリメンタル検索の状態は変えず、カーソル位置にも依存しないため、
+    /// スクリプトから決まった結果を得たいときに使う。一致は確定した検索語として記録する。
+    pub fn search_from(
+        &mut self,
+        editor: &mut TextEditor,
+        pattern: &str,
+        position: usize,
+    ) -> Option<SearchMatch> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let case_sensitive = pattern.chars().any(|c| c.is_uppercase());
+        let text = editor.to_string();
+        let found = self
+            .matcher
+            .find_matches(&text, pattern, case_sensitive)
+            .into_iter()
+            .find(|m| m.start >= position)?;
+        let _ = editor.move_cursor_to_char(found.start);
+        self.last_pattern = Some(pattern.to_string());
+        Some(found)
+    }
+
     /// 文字を追加
     pub fn input_char(&mut self, editor: &mut TextEditor, ch: char) {
         if !self.state.active {
@@ -174,13 +361,91 @@ impl<M: StringMatcher> SearchController<M> {
         self.select_match_near_cursor(editor, self.state.start_char_index);
     }
 
+    /// 検索中に検索語の解釈（リテラル・正規表現）を切り替え、入力済みの検索語で探し直す
+    pub fn toggle_regex(&mut self, editor: &mut TextEditor) {
+        if !self.state.active {
+            return;
+        }
+        self.state.regex = !self.state.regex;
+        self.state.last_valid_regex = None;
+        self.update_case_sensitivity();
+        if self.state.pattern.is_empty() {
+            self.update_ui_state();
+            return;
+        }
+        let text = editor.to_string();
+        self.recompute_matches(&text);
+        self.select_match_near_cursor(editor, self.state.start_char_index);
+    }
+
+    /// 検索中に大文字・小文字を区別するかを切り替え、入力済みの検索語で探し直す
+    ///
+    /// 切り替えた設定はその検索の間だけ、検索語に大文字があるかどうかより優先する。
+    pub fn toggle_case_sensitivity(&mut self, editor: &mut TextEditor) {
+        if !self.state.active {
+            return;
+        }
+        self.force_case = Some(!self.case_sensitive);
+        self.update_case_sensitivity();
+        if self.state.pattern.is_empty() {
+            self.update_ui_state();
+            return;
+        }
+        let text = self.text_cache.clone();
+        self.recompute_matches(&text);
+        self.select_match_near_cursor(editor, self.state.start_char_index);
+    }
+
+    /// 大文字・小文字を区別して検索中か
+    pub fn is_case_sensitive(&self) -> bool {
+        self.state.active && self.case_sensitive
+    }
+
+    /// 検索中に空白の扱い（緩い・厳密）を切り替え、入力済みの検索語で探し直す
+    pub fn toggle_lax_whitespace(&mut self, editor: &mut TextEditor) {
+        if !self.state.active {
+            return;
+        }
+        self.state.lax_whitespace = !self.state.lax_whitespace;
+        if self.state.pattern.is_empty() {
+            self.update_ui_state();
+            return;
+        }
+        let text = editor.to_string();
+        self.recompute_matches(&text);
+        self.select_match_near_cursor(editor, self.state.start_char_index);
+    }
+
+    /// 空白を緩く扱う検索中か
+    pub fn is_lax_whitespace(&self) -> bool {
+        self.state.active && self.state.lax_whitespace
+    }
+
+    /// 正規表現検索中か
+    pub fn is_regex(&self) -> bool {
+        self.state.active && self.state.regex
+    }
+
     /// 検索を確定
     pub fn accept(&mut self) {
         if !self.state.active {
             return;
         }
         if !self.state.pattern.is_empty() {
-            self.last_pattern = Some(self.state.pattern.clone());
+            let pattern = self.state.pattern.clone();
+            let ring = if self.state.regex {
+                self.last_regex_pattern = Some(pattern.clone());
+                &mut self.regex_search_ring
+            } else {
+                self.last_pattern = Some(pattern.clone());
+                &mut self.search_ring
+            };
+            if ring.last() != Some(&pattern) {
+                ring.push(pattern);
+                if ring.len() > SEARCH_RING_MAX {
+                    ring.remove(0);
+                }
+            }
         }
         self.state.active = false;
         self.ui_state = None;
@@ -209,8 +474,8 @@ impl<M: StringMatcher> SearchController<M> {
         }
 
         if self.state.matches.is_empty() {
-            // パターンはあるが未マッチ
-            self.state.failed = true;
+            // パターンはあるが未マッチ（入力途中の不正な正規表現は失敗にしない）
+            self.state.failed = self.state.regex_error.is_none();
             self.update_ui_state();
             return;
         }
@@ -241,7 +506,7 @@ impl<M: StringMatcher> SearchController<M> {
     fn select_match_near_cursor(&mut self, editor: &mut TextEditor, start_char: usize) {
         if self.state.matches.is_empty() {
             self.state.current_index = None;
-            self.state.failed = true;
+            self.state.failed = self.state.regex_error.is_none();
             self.highlights.clear();
             self.update_ui_state();
             return;
@@ -295,16 +560,93 @@ impl<M: StringMatcher> SearchController<M> {
 
     fn recompute_matches(&mut self, text: &str) {
         self.text_cache = text.to_string();
-        self.state.matches =
-            self.matcher
-                .find_matches(text, &self.state.pattern, self.case_sensitive);
+        self.state.regex_error = None;
+        self.state.matches = if self.state.regex {
+            let pattern = self.state.pattern.clone();
+            match self.find_regex(text, &pattern) {
+                Ok(matches) => {
+                    self.state.last_valid_regex = Some(pattern);
+                    matches
+                }
+                Err(err) => {
+                    // 入力途中の不正な正規表現では、直前にコンパイルできた検索語で探す
+                    self.state.regex_error = Some(regex_error_summary(&err));
+                    match self.state.last_valid_regex.clone() {
+                        Some(valid) => self.find_regex(text, &valid).unwrap_or_default(),
+                        None => Vec::new(),
+                    }
+                }
+            }
+        } else {
+            self.find_literal(text, &self.state.pattern)
+        };
         self.state.current_index = None;
-        self.state.failed = self.state.matches.is_empty();
+        self.state.failed = self.state.matches.is_empty() && self.state.regex_error.is_none();
+        self.state.nearest_prefix = if self.state.failed && self.failure_hint && !self.state.regex {
+            self.nearest_prefix_match(text)
+        } else {
+            None
+        };
         self.state.wrapped = false;
         self.rebuild_highlights();
         self.update_ui_state();
     }
 
+    fn find_regex(&self, text: &str, pattern: &str) -> Result<Vec<SearchMatch>, RegexError> {
+        let pattern = if self.state.lax_whitespace {
+            lax_whitespace_regex(pattern)
+        } else {
+            pattern.to_string()
+        };
+        RegexMatcher::new().try_find_matches(text, &pattern, self.case_sensitive)
+    }
+
+    fn find_literal(&self, text: &str, pattern: &str) -> Vec<SearchMatch> {
+        if self.state.lax_whitespace {
+            LaxWhitespaceMatcher::new(&self.matcher).find_matches(
+                text,
+                pattern,
+                self.case_sensitive,
+            )
+        } else {
+            self.matcher
+                .find_matches(text, pattern, self.case_sensitive)
+        }
+    }
+
+    /// 一致しない検索語のうち一致する最長の先頭部分を探し、検索開始位置から
+    /// 検索方向に見て最も近い一致を返す（先頭部分が長いほど一致は減るため二分探索する）
+    fn nearest_prefix_match(&self, text: &str) -> Option<(usize, SearchMatch)> {
+        let chars: Vec<char> = self.state.pattern.chars().collect();
+        let (mut found, mut missing) = (0usize, chars.len());
+        let mut matches = Vec::new();
+        while missing - found > 1 {
+            let mid = (found + missing) / 2;
+            let prefix: String = chars[..mid].iter().collect();
+            let candidates = self.find_literal(text, &prefix);
+            if candidates.is_empty() {
+                missing = mid;
+            } else {
+                found = mid;
+                matches = candidates;
+            }
+        }
+
+        let start = self.state.start_char_index;
+        let nearest = match self.state.direction {
+            SearchDirection::Forward => matches
+                .iter()
+                .find(|m| m.start >= start)
+                .or_else(|| matches.first()),
+            SearchDirection::Backward => matches
+                .iter()
+                .rev()
+                .find(|m| m.start <= start)
+                .or_else(|| matches.last()),
+        };
+        nearest.map(|m| (found, m.clone()))
+    }
+
     fn rebuild_highlights(&mut self) {
         self.highlights.clear();
         if self.state.pattern.is_empty() {
@@ -312,6 +654,9 @@ impl<M: StringMatcher> SearchController<M> {
         }
 
         for (idx, m) in self.state.matches.iter().enumerate() {
+            if !self.lazy_highlight && Some(idx) != self.state.current_index {
+                continue;
+            }
             let span_len = self.highlight_span(m);
             if span_len == 0 {
                 continue;
@@ -343,7 +688,9 @@ impl<M: StringMatcher> SearchController<M> {
             return;
         }
 
-        let status = if self.state.failed {
+        let status = if self.state.regex_error.is_some() {
+            SearchStatus::InvalidPattern
+        } else if self.state.failed {
             SearchStatus::NotFound
         } else if self.state.wrapped {
             SearchStatus::Wrapped
@@ -352,16 +699,34 @@ impl<M: StringMatcher> SearchController<M> {
         };
 
         let current = self.state.current_index.map(|idx| idx + 1);
-        let message = if self.state.failed {
-            Some(format!("{} は見つかりません", self.state.pattern))
+        let message = if let Some(error) = &self.state.regex_error {
+            Some(match &self.state.last_valid_regex {
+                Some(valid) => format!("正規表現が不正です: {}（{} で検索中）", error, valid),
+                None => format!("正規表現が不正です: {}", error),
+            })
+        } else if self.state.failed {
+            Some(match &self.state.nearest_prefix {
+                Some((len, nearest)) => format!(
+                    "{} は見つかりません（'{}' は {} 行目に一致）",
+                    self.state.pattern,
+                    self.state.pattern.chars().take(*len).collect::<String>(),
+                    nearest.line + 1
+                ),
+                None => format!("{} は見つかりません", self.state.pattern),
+            })
         } else if self.state.wrapped {
             Some("検索が折り返しました".to_string())
         } else {
             None
         };
 
+        let label = self.state.direction.label();
         self.ui_state = Some(SearchUiState {
-            prompt_label: self.state.direction.label().to_string(),
+            prompt_label: if self.state.regex {
+                format!("{} regexp", label)
+            } else {
+                label.to_string()
+            },
             pattern: self.state.pattern.clone(),
             status,
             current_match: current,
@@ -369,11 +734,30 @@ impl<M: StringMatcher> SearchController<M> {
             wrapped: self.state.wrapped,
             message,
             direction: self.state.direction,
+            regex: self.state.regex,
+            case_sensitive: self.case_sensitive,
         });
     }
 
+    /// 検索語に大文字があれば大文字・小文字を区別する（M-c で指定されていればそれに従う）
+    ///
+    /// 正規表現では `\S` や `\W` のようにバックスラッシュに続く文字は数えない。
     fn update_case_sensitivity(&mut self) {
-        let has_upper = self.state.pattern.chars().any(|c| c.is_uppercase());
+        if let Some(forced) = self.force_case {
+            self.case_sensitive = forced;
+            return;
+        }
+        let mut escaped = false;
+        let mut has_upper = false;
+        for ch in self.state.pattern.chars() {
+            if escaped {
+                escaped = false;
+            } else if self.state.regex && ch == '\\' {
+                escaped = true;
+            } else if ch.is_uppercase() {
+                has_upper = true;
+            }
+        }
         self.case_sensitive = has_upper;
     }
 
@@ -410,6 +794,68 @@ impl<M: StringMatcher> SearchController<M> {
     }
 }
 
+/// `from` 以降とバッファ全体それぞれで、重ならない一致の数を数える
+///
+/// `from` 以降は `from` 以降で始まる最初の一致から数え直すため、`from` をまたぐ一致に隠れない。
+/// 検索語に大文字を含む場合だけ大文字小文字を区別する。
+pub fn count_matches(
+    text: &str,
+    pattern: &str,
+    is_regex: bool,
+    from: usize,
+) -> Result<(usize, usize), RegexError> {
+    let case_sensitive = pattern.chars().any(|c| c.is_uppercase());
+    // 行頭や単語境界が正しく判定されるよう、`from` で切らずにバッファ全体から探す
+    let matches = if is_regex {
+        find_regex_matches(pattern, text, case_sensitive)?
+    } else {
+        LiteralMatcher::new().find_matches(text, pattern, case_sensitive)
+    };
+    let count_separate = |start: usize| {
+        let mut last_end = start;
+        matches
+            .iter()
+            .filter(|m| {
+                let separate = m.start >= last_end;
+                if separate {
+                    last_end = m.end;
+                }
+                separate
+            })
+            .count()
+    };
+    Ok((count_separate(from), count_separate(0)))
+}
+
+/// occur 用にバッファ全体から重ならない一致をすべて集める
+///
+/// 検索語に大文字を含む場合だけ大文字小文字を区別する。
+pub fn occur_matches(text: &str, pattern: &str) -> Vec<SearchMatch> {
+    let case_sensitive = pattern.chars().any(|c| c.is_uppercase());
+    let mut last_end = 0usize;
+    LiteralMatcher::new()
+        .find_matches(text, pattern, case_sensitive)
+        .into_iter()
+        .filter(|m| {
+            let separate = m.start >= last_end;
+            if separate {
+                last_end = m.end;
+            }
+            separate
+        })
+        .collect()
+}
+
+/// 正規表現のコンパイルエラーから、ミニバッファに出す 1 行の説明を取り出す
+fn regex_error_summary(err: &RegexError) -> String {
+    let text = err.to_string();
+    text.lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("error:"))
+        .map(|line| line.trim().to_string())
+        .unwrap_or_else(|| text.trim().to_string())
+}
+
 // ジェネリックに対するデフォルト実装
 impl Default for SearchController<LiteralMatcher> {
     fn default() -> Self {
@@ -419,7 +865,7 @@ impl Default for SearchController<LiteralMatcher> {
 
 #[cfg(test)]
 mod tests {
-    use super::{SearchController, SearchDirection};
+    use super::{count_matches, occur_matches, SearchController, SearchDirection, SearchStatus};
     use crate::buffer::TextEditor;
 
     #[test]
@@ -468,4 +914,519 @@ mod tests {
         let ui = controller.ui_state().expect("ui state");
         assert_eq!(ui.pattern, "");
     }
+
+    #[test]
+    fn toggle_regex_recomputes_matches_with_same_pattern() {
+        let mut editor = TextEditor::from_str("a1 b22 c333");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "\\d+".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert!(controller.ui_state().expect("ui state").is_error());
+
+        controller.toggle_regex(&mut editor);
+        assert!(controller.is_regex());
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.prompt_label, "I-search regexp");
+        assert_eq!(ui.pattern, "\\d+");
+        assert_eq!(ui.total_matches, 3);
+        assert_eq!(editor.cursor().char_pos, 1);
+
+        controller.toggle_regex(&mut editor);
+        assert!(!controller.is_regex());
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.prompt_label, "I-search");
+        assert_eq!(ui.total_matches, 0);
+    }
+
+    #[test]
+    fn failing_pattern_reports_longest_matching_prefix() {
+        let mut editor = TextEditor::from_str("abc\nxyz\nabcd ab");
+        let mut controller = SearchController::new();
+
+        controller.start_from(&mut editor, SearchDirection::Forward, 5);
+        for ch in "abcde".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert!(ui.is_error());
+        assert_eq!(
+            ui.message.as_deref(),
+            Some("abcde は見つかりません（'abcd' は 3 行目に一致）")
+        );
+
+        controller.cancel(&mut editor);
+        controller.set_failure_hint(false);
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "abcde".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.message.as_deref(), Some("abcde は見つかりません"));
+    }
+
+    #[test]
+    fn failure_hint_prefers_prefix_match_after_search_start() {
+        let mut editor = TextEditor::from_str("foo\nfoo\nfoo");
+        let mut controller = SearchController::new();
+
+        controller.start_from(&mut editor, SearchDirection::Forward, 5);
+        for ch in "fox".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(
+            ui.message.as_deref(),
+            Some("fox は見つかりません（'fo' は 3 行目に一致）")
+        );
+
+        controller.cancel(&mut editor);
+        controller.start_from(&mut editor, SearchDirection::Backward, 5);
+        for ch in "fox".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(
+            ui.message.as_deref(),
+            Some("fox は見つかりません（'fo' は 2 行目に一致）")
+        );
+    }
+
+    #[test]
+    fn invalid_regex_shows_non_fatal_message() {
+        let mut editor = TextEditor::from_str("(a)");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        controller.input_char(&mut editor, '(');
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 1);
+
+        controller.toggle_regex(&mut editor);
+        let ui = controller.ui_state().expect("ui state");
+        assert!(!ui.is_error());
+        assert_eq!(ui.status, SearchStatus::InvalidPattern);
+        assert_eq!(ui.total_matches, 0);
+        assert_eq!(
+            ui.message.as_deref(),
+            Some("正規表現が不正です: unclosed group")
+        );
+
+        controller.input_char(&mut editor, 'a');
+        controller.input_char(&mut editor, ')');
+        let ui = controller.ui_state().expect("ui state");
+        assert!(!ui.is_error());
+        assert_eq!(ui.total_matches, 1);
+    }
+
+    #[test]
+    fn regex_search_falls_back_to_last_valid_pattern_while_typing() {
+        let mut editor = TextEditor::from_str("x a1 b2 a3");
+        let mut controller = SearchController::new();
+
+        controller.start_regex(&mut editor, SearchDirection::Forward);
+        assert!(controller.is_regex());
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.prompt_label, "I-search regexp");
+        assert!(ui.regex);
+
+        controller.input_char(&mut editor, 'a');
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 2);
+
+        // 閉じていない `[` の間は直前の `a` の一致を保つ
+        controller.input_char(&mut editor, '[');
+        let ui = controller.ui_state().expect("ui state");
+        assert!(!ui.is_error());
+        assert!(!controller.is_failing());
+        assert_eq!(ui.total_matches, 2);
+        assert_eq!(
+            ui.message.as_deref(),
+            Some("正規表現が不正です: unclosed character class（a で検索中）")
+        );
+        assert_eq!(editor.cursor().char_pos, 2);
+
+        for ch in "13]".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.pattern, "a[13]");
+        assert_eq!(ui.total_matches, 2);
+        assert_eq!(ui.message, None);
+
+        controller.input_char(&mut editor, 'z');
+        assert!(controller.ui_state().expect("ui state").is_error());
+    }
+
+    #[test]
+    fn regex_highlights_use_matched_span_length() {
+        let mut editor = TextEditor::from_str("a1 b22 c333");
+        let mut controller = SearchController::new();
+
+        controller.start_regex(&mut editor, SearchDirection::Forward);
+        for ch in "[0-9]+".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let spans: Vec<(usize, usize)> = controller
+            .highlights()
+            .iter()
+            .map(|h| (h.start_column, h.end_column))
+            .collect();
+        assert_eq!(spans, vec![(1, 2), (4, 6), (8, 11)]);
+    }
+
+    #[test]
+    fn last_pattern_is_kept_separately_for_literal_and_regex_search() {
+        let mut editor = TextEditor::from_str("a.c abc");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "a.c".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        controller.accept();
+
+        controller.start_regex(&mut editor, SearchDirection::Forward);
+        assert_eq!(controller.ui_state().expect("ui state").pattern, "");
+        for ch in "b.".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        controller.accept();
+        assert_eq!(controller.last_pattern(), Some("a.c"));
+        assert_eq!(controller.last_regex_pattern(), Some("b."));
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        assert_eq!(controller.ui_state().expect("ui state").pattern, "a.c");
+        controller.accept();
+        controller.start_regex(&mut editor, SearchDirection::Forward);
+        assert_eq!(controller.ui_state().expect("ui state").pattern, "b.");
+        controller.accept();
+
+        controller.reset_last_pattern();
+        assert_eq!(controller.last_regex_pattern(), None);
+    }
+
+    #[test]
+    fn search_ring_records_accepted_patterns_without_consecutive_duplicates() {
+        let mut editor = TextEditor::from_str("alpha beta gamma");
+        let mut controller = SearchController::new();
+
+        for pattern in ["alpha", "beta", "beta", "gamma"] {
+            controller.start(&mut editor, SearchDirection::Forward);
+            while controller.current_pattern().is_some() {
+                controller.delete_char(&mut editor);
+            }
+            for ch in pattern.chars() {
+                controller.input_char(&mut editor, ch);
+            }
+            controller.accept();
+        }
+        assert_eq!(controller.search_ring(false), ["alpha", "beta", "gamma"]);
+        assert!(controller.search_ring(true).is_empty());
+
+        for i in 0..super::SEARCH_RING_MAX + 5 {
+            controller.start(&mut editor, SearchDirection::Forward);
+            while controller.current_pattern().is_some() {
+                controller.delete_char(&mut editor);
+            }
+            for ch in format!("p{}", i).chars() {
+                controller.input_char(&mut editor, ch);
+            }
+            controller.accept();
+        }
+        let ring = controller.search_ring(false);
+        assert_eq!(ring.len(), super::SEARCH_RING_MAX);
+        assert_eq!(ring.last().map(String::as_str), Some("p36"));
+    }
+
+    #[test]
+    fn history_prev_and_next_cycle_through_search_ring() {
+        let mut editor = TextEditor::from_str("alpha beta gamma");
+        let mut controller = SearchController::new();
+        for pattern in ["alpha", "beta", "gamma"] {
+            controller.start(&mut editor, SearchDirection::Forward);
+            while controller.current_pattern().is_some() {
+                controller.delete_char(&mut editor);
+            }
+            for ch in pattern.chars() {
+                controller.input_char(&mut editor, ch);
+            }
+            controller.accept();
+        }
+        editor.move_cursor_to_char(0).unwrap();
+
+        // 開始時は直前の検索語（最新の履歴）が入っている
+        controller.start(&mut editor, SearchDirection::Forward);
+        assert_eq!(controller.current_pattern(), Some("gamma"));
+        controller.history_prev(&mut editor);
+        assert_eq!(controller.current_pattern(), Some("beta"));
+        assert_eq!(editor.cursor().char_pos, 6);
+        controller.history_prev(&mut editor);
+        assert_eq!(controller.current_pattern(), Some("alpha"));
+        controller.history_prev(&mut editor);
+        assert_eq!(controller.current_pattern(), Some("gamma"));
+        controller.history_next(&mut editor);
+        assert_eq!(controller.current_pattern(), Some("alpha"));
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.total_matches, 1);
+        controller.accept();
+
+        // 検索語を空にしてから M-p すると最新の履歴から始まる
+        controller.start(&mut editor, SearchDirection::Forward);
+        while controller.current_pattern().is_some() {
+            controller.delete_char(&mut editor);
+        }
+        controller.history_prev(&mut editor);
+        assert_eq!(controller.current_pattern(), Some("alpha"));
+    }
+
+    #[test]
+    fn lazy_highlight_off_highlights_only_current_match() {
+        let text = "ab ".repeat(100);
+        let mut editor = TextEditor::from_str(&text);
+        let mut controller = SearchController::new();
+        assert!(controller.lazy_highlight());
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        controller.input_char(&mut editor, 'a');
+        controller.input_char(&mut editor, 'b');
+        assert_eq!(controller.highlights().len(), 100);
+
+        controller.set_lazy_highlight(false);
+        assert_eq!(controller.highlights().len(), 1);
+        controller.repeat_forward(&mut editor);
+        let highlights = controller.highlights();
+        assert_eq!(highlights.len(), 1);
+        assert!(highlights[0].is_current);
+        assert_eq!(highlights[0].start_column, 3);
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 100);
+    }
+
+    #[test]
+    fn toggle_case_sensitivity_overrides_smart_case() {
+        let mut editor = TextEditor::from_str("Foo foo FOO");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "foo".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        let ui = controller.ui_state().expect("ui state");
+        assert_eq!(ui.total_matches, 3);
+        assert!(!ui.case_sensitive);
+
+        controller.toggle_case_sensitivity(&mut editor);
+        let ui = controller.ui_state().expect("ui state");
+        assert!(ui.case_sensitive);
+        assert_eq!(ui.total_matches, 1);
+        assert_eq!(editor.cursor().char_pos, 4);
+
+        // 検索語を変えても切り替えた設定を保つ
+        controller.delete_char(&mut editor);
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 1);
+        controller.input_char(&mut editor, 'o');
+
+        controller.toggle_case_sensitivity(&mut editor);
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 3);
+        controller.input_char(&mut editor, 'O');
+        controller.delete_char(&mut editor);
+        assert!(!controller.is_case_sensitive());
+        controller.accept();
+
+        // 次の検索では検索語の大文字で決める動作に戻る
+        controller.start(&mut editor, SearchDirection::Forward);
+        assert!(!controller.is_case_sensitive());
+    }
+
+    #[test]
+    fn regex_search_case_smartness_ignores_escapes() {
+        let mut editor = TextEditor::from_str("aFoo bfoo");
+        let mut controller = SearchController::new();
+
+        controller.start_regex(&mut editor, SearchDirection::Forward);
+        for ch in "\\Sfoo".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        // `\S` の大文字は数えないため大文字・小文字を区別しない
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 2);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+
+        for ch in "F.o".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 1);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+        controller.delete_char(&mut editor);
+        for ch in "f.o".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 2);
+    }
+
+    #[test]
+    fn count_matches_skips_overlaps_and_counts_from_point() {
+        assert_eq!(count_matches("aaaa", "aa", false, 0).unwrap(), (2, 2));
+        assert_eq!(count_matches("aaaa", "aa", false, 1).unwrap(), (1, 2));
+        // カーソルをまたぐ一致があってもカーソル位置から始まる一致を数える
+        assert_eq!(count_matches("aaa", "aa", false, 1).unwrap(), (1, 1));
+        assert_eq!(count_matches("aaaaa", "aa", false, 1).unwrap(), (2, 2));
+        assert_eq!(
+            count_matches("x1 y22 z333", "\\d+", true, 3).unwrap(),
+            (2, 3)
+        );
+        assert_eq!(count_matches("Foo foo", "foo", false, 0).unwrap(), (2, 2));
+        assert_eq!(count_matches("Foo foo", "Foo", false, 0).unwrap(), (1, 1));
+        assert!(count_matches("abc", "(", true, 0).is_err());
+        assert_eq!(count_matches("(a)", "(", false, 0).unwrap(), (1, 1));
+        // 行頭の判定はバッファ全体で行う
+        assert_eq!(count_matches("ab\nab", "^b", true, 1).unwrap(), (0, 0));
+        assert_eq!(count_matches("ab\nab", "^a", true, 1).unwrap(), (1, 2));
+    }
+
+    #[test]
+    fn occur_matches_collects_every_hit_with_position() {
+        let matches = occur_matches("aaa\nFoo aa", "aa");
+        let found: Vec<(usize, usize, usize)> = matches
+            .iter()
+            .map(|m| (m.start, m.line, m.column))
+            .collect();
+        assert_eq!(found, vec![(0, 0, 0), (8, 1, 4)]);
+        assert_eq!(occur_matches("Foo foo", "foo").len(), 2);
+        assert_eq!(occur_matches("Foo foo", "Foo").len(), 1);
+    }
+
+    #[test]
+    fn lax_whitespace_lets_space_match_whitespace_runs() {
+        let mut editor = TextEditor::from_str("foo  bar foo\tbar foo bar");
+        let mut controller = SearchController::new();
+        controller.set_lax_whitespace_default(true);
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "foo bar".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert!(controller.is_lax_whitespace());
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 3);
+        assert_eq!(editor.cursor().char_pos, 0);
+        controller.repeat_forward(&mut editor);
+        assert_eq!(editor.cursor().char_pos, 9);
+
+        controller.toggle_lax_whitespace(&mut editor);
+        assert!(!controller.is_lax_whitespace());
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 1);
+    }
+
+    #[test]
+    fn lax_whitespace_is_off_by_default() {
+        let mut editor = TextEditor::from_str("foo  bar");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "foo bar".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert!(!controller.is_lax_whitespace());
+        assert!(controller.ui_state().expect("ui state").is_error());
+    }
+
+    #[test]
+    fn search_from_start_ignores_point_and_from_point_finds_next() {
+        let mut editor = TextEditor::from_str("foo bar foo bar");
+        let mut controller = SearchController::new();
+        editor.move_cursor_to_char(10).unwrap();
+
+        let found = controller
+            .search_from(&mut editor, "foo", 0)
+            .expect("match");
+        assert_eq!(found.start, 0);
+        assert_eq!(editor.cursor().char_pos, 0);
+        assert_eq!(controller.last_pattern(), Some("foo"));
+
+        let found = controller
+            .search_from(&mut editor, "foo", 1)
+            .expect("match");
+        assert_eq!(found.start, 8);
+        assert!(controller.search_from(&mut editor, "foo", 9).is_none());
+        assert_eq!(editor.cursor().char_pos, 8);
+        assert!(!controller.is_active());
+    }
+
+    #[test]
+    fn start_from_top_selects_first_match_even_past_point() {
+        let mut editor = TextEditor::from_str("foo bar foo bar");
+        let mut controller = SearchController::new();
+        editor.move_cursor_to_char(10).unwrap();
+
+        controller.start_from(&mut editor, SearchDirection::Forward, 0);
+        for ch in "bar".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert_eq!(editor.cursor().char_pos, 4);
+        assert_eq!(
+            controller.ui_state().expect("ui state").current_match,
+            Some(1)
+        );
+
+        controller.cancel(&mut editor);
+        assert_eq!(editor.cursor().char_pos, 10);
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "bar".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert_eq!(editor.cursor().char_pos, 12);
+    }
+
+    #[test]
+    fn pattern_accessors_follow_search_lifecycle() {
+        let mut editor = TextEditor::from_str("alpha beta");
+        let mut controller = SearchController::new();
+        assert_eq!(controller.current_pattern(), None);
+        assert_eq!(controller.last_pattern(), None);
+        assert!(!controller.is_failing());
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        for ch in "beta".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert_eq!(controller.current_pattern(), Some("beta"));
+        assert!(!controller.is_failing());
+
+        controller.input_char(&mut editor, 'x');
+        assert!(controller.is_failing());
+        controller.delete_char(&mut editor);
+        assert!(!controller.is_failing());
+
+        controller.accept();
+        assert_eq!(controller.last_pattern(), Some("beta"));
+        assert!(!controller.is_failing());
+
+        controller.reset_last_pattern();
+        assert_eq!(controller.last_pattern(), None);
+        controller.start(&mut editor, SearchDirection::Forward);
+        assert_eq!(controller.current_pattern(), None);
+    }
+
+    #[test]
+    fn lax_whitespace_applies_to_regex_search() {
+        let mut editor = TextEditor::from_str("a\tb a  b ab");
+        let mut controller = SearchController::new();
+
+        controller.start(&mut editor, SearchDirection::Forward);
+        controller.toggle_regex(&mut editor);
+        for ch in "a b".chars() {
+            controller.input_char(&mut editor, ch);
+        }
+        assert!(controller.is_failing());
+
+        controller.toggle_lax_whitespace(&mut editor);
+        assert!(!controller.is_failing());
+        assert_eq!(controller.ui_state().expect("ui state").total_matches, 2);
+        assert_eq!(editor.cursor().char_pos, 0);
+    }
 }
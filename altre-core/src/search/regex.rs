@@ -1,3 +1,4 @@
+use super::types::SearchMatch;
 use regex::{Captures, RegexBuilder};
 use thiserror::Error;
 
@@ -137,6 +138,102 @@ pub fn build_regex_candidates(
     Ok(results)
 }
 
+/// 正規表現に一致する範囲を文字位置で列挙（空文字列への一致は除く）
+pub fn find_regex_matches(
+    pattern: &str,
+    text: &str,
+    case_sensitive: bool,
+) -> Result<Vec<SearchMatch>, RegexError> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .multi_line(true)
+        .dot_matches_new_line(false)
+        .build()?;
+
+    let mut matches = Vec::new();
+    let (mut byte, mut char_pos, mut line, mut column) = (0usize, 0usize, 0usize, 0usize);
+    for mat in regex.find_iter(text) {
+        if mat.start() == mat.end() {
+            continue;
+        }
+        for ch in text[byte..mat.start()].chars() {
+            char_pos += 1;
+            if ch == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+        byte = mat.start();
+        matches.push(SearchMatch {
+            start: char_pos,
+            end: char_pos + mat.as_str().chars().count(),
+            line,
+            column,
+        });
+    }
+    Ok(matches)
+}
+
+/// 空白を緩く扱う検索で、検索語の空白の並びに置き換える正規表現（改行以外の空白 1 文字以上）
+pub const SEARCH_WHITESPACE_REGEXP: &str = r"[^\S\n]+";
+
+/// 正規表現の検索語中の空白の並びを [`SEARCH_WHITESPACE_REGEXP`] に置き換える
+///
+/// `\ ` のようにエスケープした空白と、文字クラス `[...]` の中の空白はそのまま残す。
+pub fn lax_whitespace_regex(pattern: &str) -> String {
+    let mut output = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut class_depth = 0usize;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                output.push(ch);
+                if let Some(next) = chars.next() {
+                    output.push(next);
+                }
+            }
+            '[' => {
+                class_depth += 1;
+                output.push(ch);
+            }
+            ']' if class_depth > 0 => {
+                class_depth -= 1;
+                output.push(ch);
+            }
+            ' ' if class_depth == 0 => {
+                while chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+                output.push_str(SEARCH_WHITESPACE_REGEXP);
+            }
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+/// 正規表現に一致する行（`keep` なら一致しない行）を改行ごと取り除く
+///
+/// 残したテキストと削除した行数を返す。検索語に大文字を含む場合だけ大文字小文字を区別する。
+pub fn filter_lines(text: &str, pattern: &str, keep: bool) -> Result<(String, usize), RegexError> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(!pattern.chars().any(char::is_uppercase))
+        .build()?;
+
+    let mut kept = String::with_capacity(text.len());
+    let mut removed = 0usize;
+    for line in text.split_inclusive('\n') {
+        if regex.is_match(line.strip_suffix('\n').unwrap_or(line)) == keep {
+            kept.push_str(line);
+        } else {
+            removed += 1;
+        }
+    }
+    Ok((kept, removed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,4 +253,58 @@ mod tests {
         assert_eq!(candidates.len(), 2);
         assert_eq!(candidates[0].replacement, "[42]");
     }
+
+    #[test]
+    fn filter_lines_flushes_or_keeps_matching_lines() {
+        let text = "apple\n\nbanana\ncherry\n";
+        assert_eq!(
+            filter_lines(text, "an", false).unwrap(),
+            ("apple\n\ncherry\n".to_string(), 1)
+        );
+        assert_eq!(
+            filter_lines(text, "^$", false).unwrap(),
+            ("apple\nbanana\ncherry\n".to_string(), 1)
+        );
+        assert_eq!(
+            filter_lines(text, "^(a|c)", true).unwrap(),
+            ("apple\ncherry\n".to_string(), 2)
+        );
+        assert_eq!(
+            filter_lines("x\nlast", "LAST", false).unwrap(),
+            ("x\nlast".to_string(), 0)
+        );
+        assert!(filter_lines(text, "[", true).is_err());
+    }
+
+    #[test]
+    fn regex_matches_report_char_positions() {
+        let matches = find_regex_matches("b+", "あb\nxbb a*", true).unwrap();
+        let spans: Vec<_> = matches
+            .iter()
+            .map(|m| (m.start, m.end, m.line, m.column))
+            .collect();
+        assert_eq!(spans, vec![(1, 2, 0, 1), (4, 6, 1, 1)]);
+        assert!(find_regex_matches("x*", "abc", true).unwrap().is_empty());
+        assert!(find_regex_matches("(", "abc", true).is_err());
+    }
+
+    #[test]
+    fn lax_whitespace_regex_translates_unescaped_spaces() {
+        assert_eq!(lax_whitespace_regex("a  b"), r"a[^\S\n]+b");
+        assert_eq!(lax_whitespace_regex(r"a\ b[ x]"), r"a\ b[ x]");
+
+        let pattern = lax_whitespace_regex("a b");
+        assert_eq!(
+            find_regex_matches(&pattern, "a\tb a  b ab", true)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(find_regex_matches(&pattern, "a\nb", true)
+            .unwrap()
+            .is_empty());
+        assert!(find_regex_matches("a b", "a\tb a  b", true)
+            .unwrap()
+            .is_empty());
+    }
 }
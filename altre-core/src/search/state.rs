@@ -24,6 +24,18 @@ pub struct SearchState {
     pub start_cursor: Option<CursorPosition>,
     /// 検索開始時のカーソル位置（文字インデックス）
     pub start_char_index: usize,
+    /// 検索語を正規表現として扱うか
+    pub regex: bool,
+    /// 検索語が正規表現として不正なときのエラー内容
+    pub regex_error: Option<String>,
+    /// 最後に正規表現としてコンパイルできた検索語（不正な間はこれで探す）
+    pub last_valid_regex: Option<String>,
+    /// M-p / M-n で表示中の検索履歴の位置
+    pub ring_index: Option<usize>,
+    /// 検索語の空白を 1 文字以上の空白に一致させるか
+    pub lax_whitespace: bool,
+    /// 失敗時に一致する最長の先頭部分の文字数と、検索開始位置に最も近いその一致
+    pub nearest_prefix: Option<(usize, SearchMatch)>,
 }
 
 impl SearchState {
@@ -39,6 +51,12 @@ impl SearchState {
             failed: false,
             start_cursor: None,
             start_char_index: 0,
+            regex: false,
+            regex_error: None,
+            last_valid_regex: None,
+            ring_index: None,
+            lax_whitespace: false,
+            nearest_prefix: None,
         }
     }
 
@@ -53,6 +71,12 @@ impl SearchState {
         self.failed = false;
         self.start_cursor = None;
         self.start_char_index = 0;
+        self.regex = false;
+        self.regex_error = None;
+        self.last_valid_regex = None;
+        self.ring_index = None;
+        self.lax_whitespace = false;
+        self.nearest_prefix = None;
     }
 }
 
@@ -28,6 +28,8 @@ pub enum SearchStatus {
     NotFound,
     /// 折り返し検索が発生
     Wrapped,
+    /// 検索語が正規表現として不正
+    InvalidPattern,
 }
 
 /// 1件の検索マッチ情報
@@ -61,6 +63,10 @@ pub enum HighlightKind {
     Search,
     /// マークによる選択ハイライト
     Selection,
+    /// TODO/FIXME などのキーワード
+    Keyword,
+    /// 対応する括弧
+    MatchingParen,
 }
 
 /// UI描画用のハイライト情報
@@ -97,6 +103,10 @@ pub struct SearchUiState {
     pub message: Option<String>,
     /// 検索方向
     pub direction: SearchDirection,
+    /// 正規表現検索か
+    pub regex: bool,
+    /// 大文字・小文字を区別して検索しているか
+    pub case_sensitive: bool,
 }
 
 impl SearchUiState {
@@ -104,4 +114,19 @@ impl SearchUiState {
     pub fn is_error(&self) -> bool {
         matches!(self.status, SearchStatus::NotFound)
     }
+
+    /// 一致件数の表示（`現在/総数`、失敗時は `failing` を付ける）
+    ///
+    /// 検索語が空の間は表示しない。
+    pub fn match_counter(&self) -> Option<String> {
+        if self.pattern.is_empty() {
+            return None;
+        }
+        let counter = format!("{}/{}", self.current_match.unwrap_or(0), self.total_matches);
+        if self.is_error() {
+            Some(format!("{} failing", counter))
+        } else {
+            Some(counter)
+        }
+    }
 }
@@ -0,0 +1,165 @@
+//! バッファの HTML 書き出し
+//!
+//! シンタックスハイライトの範囲をテーマの色のインラインスタイルに変換し、空白を保ったまま `<pre>` に収める
+
+use crate::alisp::highlight::{SyntaxKind, SyntaxSpan};
+use crate::ui::gui_theme::GuiThemeConfig;
+use crate::ui::theme::{ComponentType, Theme};
+use ratatui::style::{Color, Modifier};
+
+/// HTML の特殊文字をエスケープ
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// テキストを 1 つの HTML 文書にする
+///
+/// 背景色と文字色は GUI テーマ、ハイライトの色と太字・斜体は `theme` のシンタックスの設定から取る。
+/// `spans` は先頭から順に並び重ならないこと。
+pub fn buffer_to_html(
+    title: &str,
+    text: &str,
+    spans: &[SyntaxSpan],
+    theme: &Theme,
+    gui_theme: &GuiThemeConfig,
+) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n</head>\n", escape_html(title)));
+    html.push_str(&format!(
+        "<body style=\"background-color:{};color:{}\">\n",
+        gui_theme.app_background, gui_theme.app_foreground
+    ));
+    html.push_str("<pre style=\"white-space:pre;font-family:monospace\">");
+    html.push_str(&highlighted_markup(text, spans, theme));
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// ハイライト範囲を `<span style="...">` で囲んだ本文
+pub fn highlighted_markup(text: &str, spans: &[SyntaxSpan], theme: &Theme) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut markup = String::new();
+    let mut index = 0usize;
+    for span in spans {
+        let start = span.start.clamp(index, chars.len());
+        let end = span.end.clamp(start, chars.len());
+        markup.push_str(&escape_html(
+            &chars[index..start].iter().collect::<String>(),
+        ));
+        let content = escape_html(&chars[start..end].iter().collect::<String>());
+        match span_style(span.kind, theme) {
+            Some(style) => {
+                markup.push_str(&format!("<span style=\"{}\">{}</span>", style, content))
+            }
+            None => markup.push_str(&content),
+        }
+        index = end;
+    }
+    markup.push_str(&escape_html(&chars[index..].iter().collect::<String>()));
+    markup
+}
+
+fn span_style(kind: SyntaxKind, theme: &Theme) -> Option<String> {
+    let component = match kind {
+        SyntaxKind::Keyword => ComponentType::SyntaxKeyword,
+        SyntaxKind::String => ComponentType::SyntaxString,
+        SyntaxKind::Comment => ComponentType::SyntaxComment,
+        SyntaxKind::Number => ComponentType::SyntaxNumber,
+    };
+    let scheme = theme.colors.get(&component)?;
+    let mut declarations = Vec::new();
+    if let Some(color) = css_color(scheme.foreground) {
+        declarations.push(format!("color:{}", color));
+    }
+    if scheme.modifiers.contains(Modifier::BOLD) {
+        declarations.push("font-weight:bold".to_string());
+    }
+    if scheme.modifiers.contains(Modifier::ITALIC) {
+        declarations.push("font-style:italic".to_string());
+    }
+    if scheme.modifiers.contains(Modifier::UNDERLINED) {
+        declarations.push("text-decoration:underline".to_string());
+    }
+    (!declarations.is_empty()).then(|| declarations.join(";"))
+}
+
+/// 端末の色を CSS の色に変換（xterm の既定パレット。端末既定色とパレット番号は変換しない）
+fn css_color(color: Color) -> Option<String> {
+    let hex = match color {
+        Color::Black => "#000000",
+        Color::Red => "#cd0000",
+        Color::Green => "#00cd00",
+        Color::Yellow => "#cdcd00",
+        Color::Blue => "#0000ee",
+        Color::Magenta => "#cd00cd",
+        Color::Cyan => "#00cdcd",
+        Color::Gray => "#e5e5e5",
+        Color::DarkGray => "#7f7f7f",
+        Color::LightRed => "#ff0000",
+        Color::LightGreen => "#00ff00",
+        Color::LightYellow => "#ffff00",
+        Color::LightBlue => "#5c5cff",
+        Color::LightMagenta => "#ff00ff",
+        Color::LightCyan => "#00ffff",
+        Color::White => "#ffffff",
+        Color::Rgb(r, g, b) => return Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        Color::Reset | Color::Indexed(_) => return None,
+    };
+    Some(hex.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alisp::highlight::syntax_spans;
+    use crate::ui::theme::ThemeType;
+
+    #[test]
+    fn escapes_html_special_characters() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & 'Jerry'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+        assert_eq!(escape_html("  日本語\tタブ\n"), "  日本語\tタブ\n");
+    }
+
+    #[test]
+    fn highlighted_alisp_sample_produces_styled_spans() {
+        let theme = Theme::new("light".to_string(), ThemeType::Light);
+        let source = "(define x \"<b>\") ; 1 < 2\n  (+ x 42)";
+        let markup = highlighted_markup(source, &syntax_spans(source), &theme);
+        assert_eq!(
+            markup,
+            "(<span style=\"color:#0000ee;font-weight:bold\">define</span> x \
+             <span style=\"color:#00cd00\">&quot;&lt;b&gt;&quot;</span>) \
+             <span style=\"color:#e5e5e5;font-style:italic\">; 1 &lt; 2</span>\n  \
+             (+ x <span style=\"color:#cd00cd\">42</span>)"
+        );
+    }
+
+    #[test]
+    fn document_uses_gui_theme_colors_and_preserves_whitespace() {
+        let theme = Theme::new("dark".to_string(), ThemeType::Dark);
+        let mut gui_theme = GuiThemeConfig::new();
+        gui_theme.app_background = "#101010".to_string();
+        gui_theme.app_foreground = "#f0f0f0".to_string();
+        let html = buffer_to_html("a&b.al", "x\n\ty  z", &[], &theme, &gui_theme);
+        assert!(html.contains("<title>a&amp;b.al</title>"));
+        assert!(html.contains("<body style=\"background-color:#101010;color:#f0f0f0\">"));
+        assert!(
+            html.contains("<pre style=\"white-space:pre;font-family:monospace\">x\n\ty  z</pre>")
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! 注目キーワードのハイライト
+//!
+//! TODO や FIXME などのキーワードを、シンタックスハイライトとは独立に強調表示する
+
+/// 既定で強調するキーワード
+pub const DEFAULT_HIGHLIGHT_KEYWORDS: [&str; 4] = ["TODO", "FIXME", "XXX", "NOTE"];
+
+/// キーワードハイライトの設定（既定では無効）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordHighlighter {
+    enabled: bool,
+    keywords: Vec<String>,
+}
+
+impl KeywordHighlighter {
+    /// 既定のキーワードで作成
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            keywords: DEFAULT_HIGHLIGHT_KEYWORDS
+                .iter()
+                .map(|keyword| keyword.to_string())
+                .collect(),
+        }
+    }
+
+    /// 有効かどうか
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 有効・無効を切り替える
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// 強調するキーワード
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// 強調するキーワードを設定（空文字列と重複は除く）
+    pub fn set_keywords<I, S>(&mut self, keywords: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keywords.clear();
+        for keyword in keywords {
+            let keyword = keyword.into();
+            if !keyword.is_empty() && !self.keywords.contains(&keyword) {
+                self.keywords.push(keyword);
+            }
+        }
+    }
+
+    /// 行内のキーワード範囲（無効なら空）
+    pub fn find_in_line(&self, line: &str) -> Vec<(usize, usize)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        keyword_ranges(line, &self.keywords)
+    }
+}
+
+impl Default for KeywordHighlighter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 行内でキーワードが現れる文字範囲 `start..end` を列挙
+///
+/// 単語の一部として現れるもの（`TODOS` など）は対象外。同じ位置では長いキーワードを優先する。
+pub fn keyword_ranges<S: AsRef<str>>(line: &str, keywords: &[S]) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut patterns: Vec<Vec<char>> = keywords
+        .iter()
+        .map(|keyword| keyword.as_ref().chars().collect::<Vec<char>>())
+        .filter(|pattern| !pattern.is_empty())
+        .collect();
+    patterns.sort_by_key(|pattern| std::cmp::Reverse(pattern.len()));
+
+    let mut ranges = Vec::new();
+    let mut index = 0usize;
+    while index < chars.len() {
+        let starts_word = index == 0 || !is_word_char(chars[index - 1]);
+        let matched = starts_word
+            .then(|| {
+                patterns.iter().find(|pattern| {
+                    let end = index + pattern.len();
+                    chars.get(index..end) == Some(pattern.as_slice())
+                        && chars.get(end).is_none_or(|ch| !is_word_char(*ch))
+                })
+            })
+            .flatten();
+        match matched {
+            Some(pattern) => {
+                ranges.push((index, index + pattern.len()));
+                index += pattern.len();
+            }
+            None => index += 1,
+        }
+    }
+    ranges
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_multiple_keywords_in_one_line() {
+        let line = "// TODO: fix this FIXME and NOTE(me)";
+        assert_eq!(
+            keyword_ranges(line, &DEFAULT_HIGHLIGHT_KEYWORDS),
+            vec![(3, 7), (18, 23), (28, 32)]
+        );
+    }
+
+    #[test]
+    fn ignores_keywords_inside_words() {
+        let line = "TODOS XXXL _NOTE NOTE_ XXX";
+        assert_eq!(
+            keyword_ranges(line, &DEFAULT_HIGHLIGHT_KEYWORDS),
+            vec![(23, 26)]
+        );
+        assert_eq!(keyword_ranges("日本語TODO", &["TODO"]), vec![]);
+        assert_eq!(keyword_ranges("日本語 TODO", &["TODO"]), vec![(4, 8)]);
+    }
+
+    #[test]
+    fn custom_keywords_replace_defaults() {
+        let mut highlighter = KeywordHighlighter::new();
+        assert!(highlighter.find_in_line("TODO").is_empty());
+
+        highlighter.set_enabled(true);
+        highlighter.set_keywords(["HACK", "", "HACK", "HACKME"]);
+        assert_eq!(highlighter.keywords(), &["HACK", "HACKME"]);
+        assert_eq!(
+            highlighter.find_in_line("TODO HACKME HACK"),
+            vec![(5, 11), (12, 16)]
+        );
+    }
+}
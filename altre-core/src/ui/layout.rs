@@ -138,11 +138,14 @@ impl LayoutManager {
     }
 
     /// 高性能レンダラー用のレイアウト計算
+    ///
+    /// `show_minibuffer` が `false` の場合はミニバッファ領域を確保せず、テキストエリアに譲る。
     pub fn calculate_areas(
         &self,
         area: Rect,
         minibuffer_active: bool,
         show_status_line: bool,
+        show_minibuffer: bool,
     ) -> HashMap<AreaType, Rect> {
         let mut areas = HashMap::new();
 
@@ -158,8 +161,10 @@ impl LayoutManager {
         let mut area_order = Vec::new();
 
         // ミニバッファを上部に固定
-        constraints.push(Constraint::Length(minibuffer_height));
-        area_order.push(AreaType::Minibuffer);
+        if show_minibuffer {
+            constraints.push(Constraint::Length(minibuffer_height));
+            area_order.push(AreaType::Minibuffer);
+        }
 
         // テキストエリアは残り全部
         constraints.push(Constraint::Min(1));
@@ -456,6 +461,26 @@ mod tests {
         assert_eq!(layout.modeline.y, 14);
     }
 
+    #[test]
+    fn test_calculate_areas_with_status_line_hidden() {
+        let manager = LayoutManager::new();
+        let area = Rect::new(0, 0, 80, 25);
+
+        let areas = manager.calculate_areas(area, false, false, true);
+        assert!(!areas.contains_key(&AreaType::StatusLine));
+        assert_eq!(areas[&AreaType::Minibuffer], Rect::new(0, 0, 80, 1));
+        assert_eq!(areas[&AreaType::TextArea], Rect::new(0, 1, 80, 24));
+
+        // ミニバッファも隠すと端末の高さをすべて使う
+        let areas = manager.calculate_areas(area, false, false, false);
+        assert!(!areas.contains_key(&AreaType::Minibuffer));
+        assert_eq!(areas[&AreaType::TextArea], area);
+
+        let areas = manager.calculate_areas(area, false, true, true);
+        assert_eq!(areas[&AreaType::TextArea].height, 23);
+        assert_eq!(areas[&AreaType::StatusLine], Rect::new(0, 24, 80, 1));
+    }
+
     #[test]
     fn test_minimum_size_check_qa_values() {
         let manager = LayoutManager::new();
@@ -0,0 +1,219 @@
+//! 行の折り返し位置の計算
+//!
+//! 論理行を表示幅に収まる表示行へ分割する位置を、単語境界または文字境界で求める
+
+use super::layout::char_width;
+
+/// 折り返しの単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    /// 空白の直後で折り返す（空白のない長いトークンは文字単位）
+    #[default]
+    Word,
+    /// 表示幅いっぱいの文字境界で折り返す
+    Char,
+}
+
+impl WrapMode {
+    /// もう一方のモード
+    pub fn toggled(self) -> Self {
+        match self {
+            WrapMode::Word => WrapMode::Char,
+            WrapMode::Char => WrapMode::Word,
+        }
+    }
+}
+
+/// 折り返す桁の決め方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrapColumn {
+    /// ウィンドウ幅で折り返す
+    #[default]
+    Window,
+    /// 指定した桁（fill-column）で折り返す。ウィンドウの方が狭ければウィンドウ幅
+    FillColumn(usize),
+}
+
+impl WrapColumn {
+    /// 表示幅 `window_width` のウィンドウで折り返しに使う幅
+    pub fn wrap_width(self, window_width: usize) -> usize {
+        match self {
+            WrapColumn::Window => window_width,
+            WrapColumn::FillColumn(column) => column.clamp(1, window_width.max(1)),
+        }
+    }
+
+    /// 本文を中央に寄せるときの左余白（`centered` が偽なら 0）
+    pub fn left_margin(self, window_width: usize, centered: bool) -> usize {
+        if !centered {
+            return 0;
+        }
+        (window_width - self.wrap_width(window_width).min(window_width)) / 2
+    }
+
+    /// `line` をこの設定で折り返したときの、2行目以降の表示行の開始位置（文字）
+    pub fn wrap_points(self, line: &str, window_width: usize, mode: WrapMode) -> Vec<usize> {
+        wrap_points(line, self.wrap_width(window_width), mode)
+    }
+}
+
+/// 長い行を折り返して表示するときの設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineWrap {
+    /// 折り返しの単位
+    pub mode: WrapMode,
+    /// 折り返す桁
+    pub column: WrapColumn,
+    /// fill-column で折り返すとき本文を中央に寄せるか
+    pub centered: bool,
+}
+
+impl LineWrap {
+    /// 表示幅 `window_width` のウィンドウで折り返しに使う幅
+    pub fn wrap_width(self, window_width: usize) -> usize {
+        self.column.wrap_width(window_width)
+    }
+
+    /// 本文の左余白
+    pub fn left_margin(self, window_width: usize) -> usize {
+        self.column.left_margin(window_width, self.centered)
+    }
+
+    /// `line` の2行目以降の表示行の開始位置（文字）
+    pub fn wrap_points(self, line: &str, window_width: usize) -> Vec<usize> {
+        self.column.wrap_points(line, window_width, self.mode)
+    }
+
+    /// `line` の `column` 文字目が表示される表示行（0始まり）と表示行内の桁
+    ///
+    /// 行末がちょうど折り返し幅に達したときは、次の表示行の先頭に置く。
+    pub fn position_of(self, line: &str, column: usize, window_width: usize) -> (usize, usize) {
+        let points = self.wrap_points(line, window_width);
+        let row = points.partition_point(|&point| point <= column);
+        let row_start = if row == 0 { 0 } else { points[row - 1] };
+        let offset: usize = line
+            .chars()
+            .skip(row_start)
+            .take(column.saturating_sub(row_start))
+            .map(char_width)
+            .sum();
+        if offset >= self.wrap_width(window_width) {
+            (row + 1, 0)
+        } else {
+            (row, offset)
+        }
+    }
+}
+
+/// `line` を表示幅 `width` で折り返したときの、2行目以降の表示行の開始位置（文字）
+///
+/// 全角文字など幅2の文字は途中で分割せず、次の表示行へ送る。
+pub fn wrap_points(line: &str, width: usize, mode: WrapMode) -> Vec<usize> {
+    if width == 0 {
+        return Vec::new();
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let widths: Vec<usize> = chars.iter().map(|&ch| char_width(ch)).collect();
+    let mut points = Vec::new();
+    let mut row_start = 0usize;
+    let mut row_width = 0usize;
+    let mut after_space: Option<usize> = None;
+
+    for (index, &ch_width) in widths.iter().enumerate() {
+        while row_width + ch_width > width && index > row_start {
+            let point = match (mode, after_space) {
+                (WrapMode::Word, Some(point)) if point > row_start => point,
+                _ => index,
+            };
+            points.push(point);
+            row_start = point;
+            row_width = widths[point..index].iter().sum();
+            after_space = None;
+        }
+        row_width += ch_width;
+        if chars[index].is_whitespace() {
+            after_space = Some(index + 1);
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_mode_breaks_after_spaces() {
+        assert_eq!(
+            wrap_points("hello brave world", 10, WrapMode::Word),
+            vec![6, 12]
+        );
+        assert_eq!(
+            wrap_points("hello brave world", 10, WrapMode::Char),
+            vec![10]
+        );
+        assert!(wrap_points("short", 10, WrapMode::Word).is_empty());
+    }
+
+    #[test]
+    fn long_unbroken_token_is_split_in_both_modes() {
+        let line = "see abcdefghijklmnop";
+        assert_eq!(wrap_points(line, 8, WrapMode::Word), vec![4, 12]);
+        assert_eq!(wrap_points(line, 8, WrapMode::Char), vec![8, 16]);
+    }
+
+    #[test]
+    fn wide_characters_are_not_split() {
+        // 幅2の文字が残り1桁に収まらない場合は次の行へ送る
+        assert_eq!(wrap_points("aあいう", 4, WrapMode::Char), vec![2]);
+        assert_eq!(wrap_points("あいうえお", 4, WrapMode::Word), vec![2, 4]);
+    }
+
+    #[test]
+    fn fill_column_wraps_regardless_of_window_width() {
+        let line = "the quick brown fox jumps";
+        let column = WrapColumn::FillColumn(10);
+        assert_eq!(column.wrap_points(line, 80, WrapMode::Word), vec![10, 20]);
+        assert_eq!(
+            column.wrap_points(line, 200, WrapMode::Word),
+            column.wrap_points(line, 80, WrapMode::Word)
+        );
+        // ウィンドウの方が狭ければウィンドウ幅で折り返す
+        assert_eq!(
+            column.wrap_points(line, 6, WrapMode::Char),
+            vec![6, 12, 18, 24]
+        );
+        assert_eq!(
+            WrapColumn::Window.wrap_points(line, 80, WrapMode::Word),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn position_of_column_follows_wrapped_rows() {
+        let wrap = LineWrap {
+            mode: WrapMode::Word,
+            column: WrapColumn::Window,
+            centered: false,
+        };
+        let line = "hello brave world";
+        assert_eq!(wrap.position_of(line, 3, 10), (0, 3));
+        assert_eq!(wrap.position_of(line, 6, 10), (1, 0));
+        assert_eq!(wrap.position_of(line, 14, 10), (2, 2));
+        // 全角文字は幅2で数える
+        assert_eq!(wrap.position_of("あいうえお", 3, 4), (1, 2));
+        // 幅いっぱいの行末は次の表示行の先頭
+        assert_eq!(wrap.position_of("abcd", 4, 4), (1, 0));
+    }
+
+    #[test]
+    fn centered_fill_column_leaves_equal_margins() {
+        let column = WrapColumn::FillColumn(70);
+        assert_eq!(column.left_margin(100, true), 15);
+        assert_eq!(column.left_margin(100, false), 0);
+        assert_eq!(column.left_margin(50, true), 0);
+        assert_eq!(WrapColumn::Window.left_margin(100, true), 0);
+    }
+}
@@ -3,9 +3,14 @@
 //! ratatuiベースのターミナルUI機能
 
 pub mod gui_theme;
+pub mod html_export;
+pub mod keyword_highlight;
 pub mod layout;
+pub mod line_wrap;
 pub mod minibuffer;
+pub mod paren_highlight;
 pub mod renderer;
+pub mod scrollbar;
 pub mod text_area;
 pub mod theme;
 pub mod viewport;
@@ -13,10 +18,17 @@ pub mod window_manager;
 
 // 公開API
 pub use gui_theme::{GuiThemeConfig, GuiThemeKey};
+pub use html_export::{buffer_to_html, escape_html};
+pub use keyword_highlight::{keyword_ranges, KeywordHighlighter, DEFAULT_HIGHLIGHT_KEYWORDS};
 pub use layout::{AppLayout, AreaType, LayoutManager};
+pub use line_wrap::{wrap_points, LineWrap, WrapColumn, WrapMode};
 pub use minibuffer::MinibufferRenderer;
-pub use renderer::{AdvancedRenderer, FrameRateStats, RenderStats, StatusLineInfo};
-pub use text_area::{TextArea, TextAreaRenderer};
+pub use paren_highlight::{bracket_pair_at, ParenHighlightMode, ParenHighlighter};
+pub use renderer::{AdvancedRenderer, FrameRateStats, RenderStats, StatusLineInfo, WindowEditors};
+pub use scrollbar::ScrollbarThumb;
+pub use text_area::{
+    is_invisible_char, is_problematic_char, ControlCharDisplay, TextArea, TextAreaRenderer,
+};
 pub use theme::{ComponentType, Theme, ThemeManager, ThemeType};
 pub use viewport::{ViewportManager, ViewportState};
-pub use window_manager::{SplitOrientation, WindowError, WindowId, WindowManager};
+pub use window_manager::{SplitOrientation, WindowError, WindowId, WindowLayout, WindowManager};
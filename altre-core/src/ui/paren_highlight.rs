@@ -0,0 +1,263 @@
+//! 対応する括弧のハイライト
+//!
+//! 閉じ括弧の入力直後や、カーソル位置の括弧に対応する括弧を強調表示する
+
+use std::time::{Duration, Instant};
+
+/// 対応括弧をハイライトする契機
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParenHighlightMode {
+    /// 閉じ括弧を入力した直後だけ（次のコマンドで消える）
+    #[default]
+    OnInsert,
+    /// カーソルが括弧の前後にある間は常に
+    Always,
+    /// ハイライトしない
+    Off,
+}
+
+impl ParenHighlightMode {
+    /// 設定名（`on-insert` / `always` / `off`）
+    pub fn name(self) -> &'static str {
+        match self {
+            ParenHighlightMode::OnInsert => "on-insert",
+            ParenHighlightMode::Always => "always",
+            ParenHighlightMode::Off => "off",
+        }
+    }
+
+    /// 設定名から取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "on-insert" => Some(ParenHighlightMode::OnInsert),
+            "always" => Some(ParenHighlightMode::Always),
+            "off" => Some(ParenHighlightMode::Off),
+            _ => None,
+        }
+    }
+}
+
+/// 対応括弧ハイライトの設定と状態
+#[derive(Debug, Clone)]
+pub struct ParenHighlighter {
+    mode: ParenHighlightMode,
+    delay: Duration,
+    /// 入力直後にハイライトする括弧の位置（開き, 閉じ）
+    flash: Option<(usize, usize)>,
+    last_activity: Instant,
+}
+
+impl ParenHighlighter {
+    /// 既定の設定（入力時のみ、遅延なし）で作成
+    pub fn new() -> Self {
+        Self {
+            mode: ParenHighlightMode::default(),
+            delay: Duration::ZERO,
+            flash: None,
+            last_activity: Instant::now(),
+        }
+    }
+
+    /// ハイライトの契機
+    pub fn mode(&self) -> ParenHighlightMode {
+        self.mode
+    }
+
+    /// ハイライトの契機を設定
+    pub fn set_mode(&mut self, mode: ParenHighlightMode) {
+        self.mode = mode;
+        self.flash = None;
+    }
+
+    /// 最後の操作からハイライトを表示するまでの遅延
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// 遅延を設定
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// コマンド実行の開始を記録（入力直後のハイライトは消える）
+    pub fn on_command(&mut self) {
+        self.flash = None;
+        self.last_activity = Instant::now();
+    }
+
+    /// 文字入力後に呼び出し、閉じ括弧なら対応する括弧を記録する
+    pub fn on_insert(&mut self, text: &str, cursor: usize) {
+        if self.mode != ParenHighlightMode::OnInsert {
+            return;
+        }
+        let chars: Vec<char> = text.chars().collect();
+        let Some(close) = cursor.checked_sub(1) else {
+            return;
+        };
+        if chars.get(close).is_some_and(|ch| is_closing(*ch)) {
+            self.flash = matching_open_bracket(&chars, close).map(|open| (open, close));
+        }
+    }
+
+    /// 表示すべき括弧の位置（開き, 閉じ）
+    pub fn highlight(&self, text: &str, cursor: usize) -> Option<(usize, usize)> {
+        if self.last_activity.elapsed() < self.delay {
+            return None;
+        }
+        match self.mode {
+            ParenHighlightMode::OnInsert => self.flash,
+            ParenHighlightMode::Always => bracket_pair_at(text, cursor),
+            ParenHighlightMode::Off => None,
+        }
+    }
+}
+
+impl Default for ParenHighlighter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// カーソル直前の閉じ括弧、またはカーソル位置の開き括弧と対応する括弧の組（開き, 閉じ）
+pub fn bracket_pair_at(text: &str, cursor: usize) -> Option<(usize, usize)> {
+    let chars: Vec<char> = text.chars().collect();
+    if let Some(close) = cursor.checked_sub(1) {
+        if chars.get(close).is_some_and(|ch| is_closing(*ch)) {
+            if let Some(open) = matching_open_bracket(&chars, close) {
+                return Some((open, close));
+            }
+        }
+    }
+    if chars
+        .get(cursor)
+        .is_some_and(|ch| closing_for(*ch).is_some())
+    {
+        return matching_close_bracket(&chars, cursor).map(|close| (cursor, close));
+    }
+    None
+}
+
+/// `close` の閉じ括弧に対応する開き括弧の位置（入れ子を考慮）
+pub fn matching_open_bracket(chars: &[char], close: usize) -> Option<usize> {
+    let target = opening_for(*chars.get(close)?)?;
+    let mut stack: Vec<char> = Vec::new();
+    for index in (0..close).rev() {
+        let ch = chars[index];
+        if let Some(open) = opening_for(ch) {
+            stack.push(open);
+        } else if closing_for(ch).is_some() {
+            match stack.pop() {
+                Some(expected) if expected != ch => return None,
+                Some(_) => {}
+                None if ch == target => return Some(index),
+                None => return None,
+            }
+        }
+    }
+    None
+}
+
+/// `open` の開き括弧に対応する閉じ括弧の位置（入れ子を考慮）
+pub fn matching_close_bracket(chars: &[char], open: usize) -> Option<usize> {
+    let target = closing_for(*chars.get(open)?)?;
+    let mut stack: Vec<char> = Vec::new();
+    for (index, &ch) in chars.iter().enumerate().skip(open + 1) {
+        if let Some(close) = closing_for(ch) {
+            stack.push(close);
+        } else if opening_for(ch).is_some() {
+            match stack.pop() {
+                Some(expected) if expected != ch => return None,
+                Some(_) => {}
+                None if ch == target => return Some(index),
+                None => return None,
+            }
+        }
+    }
+    None
+}
+
+fn is_closing(ch: char) -> bool {
+    opening_for(ch).is_some()
+}
+
+/// 閉じ括弧に対応する開き括弧
+fn opening_for(ch: char) -> Option<char> {
+    match ch {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+/// 開き括弧に対応する閉じ括弧
+fn closing_for(ch: char) -> Option<char> {
+    match ch {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn matches_nested_brackets_in_both_directions() {
+        let text = chars("(a [b {c}] d)");
+        assert_eq!(matching_open_bracket(&text, 12), Some(0));
+        assert_eq!(matching_open_bracket(&text, 9), Some(3));
+        assert_eq!(matching_close_bracket(&text, 0), Some(12));
+        assert_eq!(matching_close_bracket(&text, 6), Some(8));
+    }
+
+    #[test]
+    fn mismatched_or_unbalanced_brackets_have_no_match() {
+        assert_eq!(matching_open_bracket(&chars("(a]"), 2), None);
+        assert_eq!(matching_open_bracket(&chars("a)"), 1), None);
+        assert_eq!(matching_close_bracket(&chars("(a"), 0), None);
+        assert_eq!(matching_open_bracket(&chars("(a)"), 1), None);
+    }
+
+    #[test]
+    fn pair_prefers_closer_before_cursor() {
+        assert_eq!(bracket_pair_at("(a)(b)", 3), Some((0, 2)));
+        assert_eq!(bracket_pair_at("x (b)", 2), Some((2, 4)));
+        assert_eq!(bracket_pair_at("x (b)", 1), None);
+    }
+
+    #[test]
+    fn modes_control_when_pairs_are_reported() {
+        let mut highlighter = ParenHighlighter::new();
+        highlighter.on_insert("(a)", 3);
+        assert_eq!(highlighter.highlight("(a)", 3), Some((0, 2)));
+        highlighter.on_command();
+        assert_eq!(highlighter.highlight("(a)", 3), None);
+
+        highlighter.set_mode(ParenHighlightMode::Always);
+        assert_eq!(highlighter.highlight("(a)", 3), Some((0, 2)));
+        highlighter.set_mode(ParenHighlightMode::Off);
+        assert_eq!(highlighter.highlight("(a)", 3), None);
+
+        assert_eq!(
+            ParenHighlightMode::from_name("always"),
+            Some(ParenHighlightMode::Always)
+        );
+        assert_eq!(ParenHighlightMode::from_name("blink"), None);
+    }
+
+    #[test]
+    fn delay_hides_highlight_until_elapsed() {
+        let mut highlighter = ParenHighlighter::new();
+        highlighter.set_mode(ParenHighlightMode::Always);
+        highlighter.set_delay(Duration::from_secs(3600));
+        highlighter.on_command();
+        assert_eq!(highlighter.highlight("(a)", 3), None);
+    }
+}
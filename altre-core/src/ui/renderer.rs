@@ -2,13 +2,16 @@
 //!
 //! 60fps描画、差分更新、画面更新最適化を実現
 
-use crate::buffer::TextEditor;
+use crate::buffer::{BufferId, TextEditor};
+use crate::editor::CompletionPopup;
 use crate::minibuffer::MinibufferSystem;
 use crate::search::{SearchHighlight, SearchStatus, SearchUiState};
 use crate::ui::{
     layout::{AreaType, LayoutManager},
-    text_area::TextAreaRenderer,
-    theme::{ComponentType, ThemeManager},
+    line_wrap::LineWrap,
+    scrollbar::ScrollbarThumb,
+    text_area::{ControlCharDisplay, TextAreaRenderer},
+    theme::{ComponentType, Theme, ThemeManager},
     WindowManager,
 };
 use ratatui::{
@@ -122,6 +125,41 @@ pub struct StatusLineInfo<'a> {
     pub file_label: &'a str,
     /// バッファが変更されているか
     pub is_modified: bool,
+    /// 文字コードと改行コード（例: `UTF-8/LF`）
+    pub coding_label: &'a str,
+}
+
+/// ウィンドウごとに表示するエディタ
+#[derive(Clone, Copy)]
+pub struct WindowEditors<'a> {
+    /// カレントバッファ
+    pub current: &'a TextEditor,
+    /// カレント以外のバッファ（バッファID → エディタ）
+    pub others: Option<&'a HashMap<BufferId, TextEditor>>,
+}
+
+impl<'a> WindowEditors<'a> {
+    /// カレントバッファのみを表示する
+    pub fn single(current: &'a TextEditor) -> Self {
+        Self {
+            current,
+            others: None,
+        }
+    }
+
+    /// ウィンドウに割り当てられたバッファのエディタを取得（カレントなら `true` を返す）
+    pub fn editor_for(&self, buffer_id: Option<BufferId>) -> (&'a TextEditor, bool) {
+        buffer_id
+            .and_then(|id| self.others.and_then(|others| others.get(&id)))
+            .map(|editor| (editor, false))
+            .unwrap_or((self.current, true))
+    }
+}
+
+impl<'a> From<&'a TextEditor> for WindowEditors<'a> {
+    fn from(current: &'a TextEditor) -> Self {
+        Self::single(current)
+    }
 }
 
 /// 高性能レンダラー
@@ -150,6 +188,12 @@ pub struct AdvancedRenderer {
     differential_update: bool,
     /// デバッグモード
     debug_mode: bool,
+    /// 自動補完ポップアップ
+    completion_popup: Option<CompletionPopup>,
+    /// モードラインを表示するか
+    show_mode_line: bool,
+    /// 入力中以外もミニバッファ領域を確保するか
+    show_minibuffer: bool,
 }
 
 impl AdvancedRenderer {
@@ -183,6 +227,9 @@ impl AdvancedRenderer {
             vsync_enabled: true,
             differential_update: true,
             debug_mode: false,
+            completion_popup: None,
+            show_mode_line: true,
+            show_minibuffer: true,
         }
     }
 
@@ -191,22 +238,49 @@ impl AdvancedRenderer {
         self.text_area_renderer.set_show_line_numbers(visible);
     }
 
+    /// 制御文字（タブ・改行以外）の表示方法を設定
+    pub fn set_control_char_display(&mut self, display: ControlCharDisplay) {
+        self.text_area_renderer.set_control_char_display(display);
+    }
+
+    /// 長い行の折り返しを設定（`None` なら折り返さずに横スクロールする）
+    pub fn set_line_wrap(&mut self, wrap: Option<LineWrap>) {
+        self.text_area_renderer.set_line_wrap(wrap);
+    }
+
+    /// 次の描画で表示する自動補完ポップアップを設定
+    pub fn set_completion_popup(&mut self, popup: Option<CompletionPopup>) {
+        self.completion_popup = popup;
+    }
+
     /// 行番号表示状態を取得
     pub fn line_numbers_visible(&self) -> bool {
         self.text_area_renderer.show_line_numbers()
     }
 
+    /// モードラインとミニバッファ領域の表示を設定
+    ///
+    /// ミニバッファを隠していても入力中・検索中は表示し、メッセージはテキストエリア下端に重ねて出す。
+    pub fn set_chrome_visible(&mut self, mode_line: bool, minibuffer: bool) {
+        self.show_mode_line = mode_line;
+        self.show_minibuffer = minibuffer;
+    }
+
     /// メイン描画処理
-    pub fn render<B: Backend>(
+    ///
+    /// `TextEditor` を渡すと全ウィンドウにカレントバッファを表示する。
+    pub fn render<'e, B: Backend>(
         &mut self,
         terminal: &mut Terminal<B>,
-        editor: &TextEditor,
+        editors: impl Into<WindowEditors<'e>>,
         windows: &mut WindowManager,
         minibuffer: &MinibufferSystem,
         search_ui: Option<&SearchUiState>,
         search_highlights: &[SearchHighlight],
         status_info: StatusLineInfo<'_>,
     ) -> io::Result<()> {
+        let editors = editors.into();
+        let editor = editors.current;
         let frame_start = Instant::now();
 
         // フレームレート制御
@@ -218,15 +292,13 @@ impl AdvancedRenderer {
             let size = frame.area();
 
             // レイアウト計算
+            let minibuffer_active = minibuffer.is_active() || search_ui.is_some();
+            let prompting = minibuffer_active && !minibuffer.is_message_displayed();
             let areas = self.layout_manager.calculate_areas(
                 size,
-                minibuffer.is_active() || search_ui.is_some(),
-                true, // ステータスライン表示
-            );
-
-            debug_assert!(
-                areas.contains_key(&AreaType::Minibuffer),
-                "minibuffer area missing from layout"
+                minibuffer_active,
+                self.show_mode_line,
+                self.show_minibuffer || prompting,
             );
 
             // 差分更新の判定
@@ -235,7 +307,7 @@ impl AdvancedRenderer {
             // レンダリング実行
             self.render_frame(
                 frame,
-                editor,
+                editors,
                 windows,
                 minibuffer,
                 search_ui,
@@ -257,7 +329,7 @@ impl AdvancedRenderer {
     fn render_frame(
         &mut self,
         frame: &mut Frame<'_>,
-        editor: &TextEditor,
+        editors: WindowEditors<'_>,
         windows: &mut WindowManager,
         minibuffer: &MinibufferSystem,
         search_ui: Option<&SearchUiState>,
@@ -266,6 +338,7 @@ impl AdvancedRenderer {
         _diffs: &[AreaDiff],
         status_info: StatusLineInfo<'_>,
     ) {
+        let editor = editors.current;
         let theme = self.theme_manager.current_theme();
         let mut cursor_position: Option<(u16, u16)> = None;
         let search_active = search_ui.is_some();
@@ -286,22 +359,41 @@ impl AdvancedRenderer {
 
             for (window_id, area) in window_rects {
                 let is_focused = window_id == focused_id;
+                let (window_editor, is_current) = editors.editor_for(windows.buffer(window_id));
+                // 検索ハイライトはカレントバッファのみに適用する
+                let highlights = if is_current { search_highlights } else { &[] };
+                let total_lines = window_editor.line_count();
+                let (window_area, scrollbar_area) = Self::split_scrollbar_area(area, total_lines);
                 if let Some(viewport) = windows.viewport_mut(window_id) {
                     let text_cursor_pos = self.text_area_renderer.render(
                         frame,
-                        area,
-                        editor,
+                        window_area,
+                        window_editor,
                         viewport,
                         theme,
-                        search_highlights,
+                        highlights,
                         (minibuffer.is_active() || search_active) && is_focused,
                     );
 
+                    if let Some(bar_area) = scrollbar_area {
+                        Self::render_scrollbar(
+                            frame,
+                            bar_area,
+                            total_lines,
+                            viewport.top_line,
+                            theme,
+                        );
+                    }
+
                     if is_focused && !minibuffer.is_active() && !search_active {
                         cursor_position = text_cursor_pos;
                     }
                 }
             }
+
+            if let (Some(popup), Some(cursor)) = (self.completion_popup.as_ref(), cursor_position) {
+                Self::render_completion_popup(frame, text_area, cursor, popup, theme);
+            }
         }
 
         // ステータスライン描画
@@ -320,6 +412,16 @@ impl AdvancedRenderer {
             if let Some(position) = minibuffer_cursor_pos {
                 cursor_position = Some(position);
             }
+        } else if minibuffer.is_message_displayed() {
+            // ミニバッファを隠している間もメッセージはテキストエリア下端に重ねて表示する
+            if let Some(&text_area) = areas.get(&AreaType::TextArea) {
+                let overlay_area = Rect {
+                    y: text_area.bottom().saturating_sub(1),
+                    height: text_area.height.min(1),
+                    ..text_area
+                };
+                self.render_minibuffer(frame, overlay_area, minibuffer, None);
+            }
         }
 
         // カーソル位置設定
@@ -328,6 +430,113 @@ impl AdvancedRenderer {
         }
     }
 
+    /// 自動補完ポップアップ描画（カーソルの下、収まらなければ上に表示）
+    fn render_completion_popup(
+        frame: &mut Frame<'_>,
+        bounds: Rect,
+        cursor: (u16, u16),
+        popup: &CompletionPopup,
+        theme: &Theme,
+    ) {
+        let content_width = popup
+            .candidates
+            .iter()
+            .map(|candidate| unicode_width::UnicodeWidthStr::width(candidate.as_str()))
+            .max()
+            .unwrap_or(0) as u16;
+        let width = content_width.saturating_add(2).min(bounds.width);
+        let height = (popup.candidates.len() as u16)
+            .saturating_add(2)
+            .min(bounds.height);
+        if width < 3 || height < 3 {
+            return;
+        }
+
+        let (cursor_x, cursor_y) = cursor;
+        let bottom = bounds.y + bounds.height;
+        let y = if cursor_y + 1 + height <= bottom {
+            cursor_y + 1
+        } else {
+            cursor_y.saturating_sub(height).max(bounds.y)
+        };
+        let x = cursor_x.min(bounds.x + bounds.width - width).max(bounds.x);
+        let area = Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        let normal = theme.style(&ComponentType::Completion);
+        let selected = theme.style(&ComponentType::CompletionSelected);
+        let lines: Vec<Line<'_>> = popup
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let style = if index == popup.selected {
+                    selected
+                } else {
+                    normal
+                };
+                Line::styled(candidate.as_str(), style)
+            })
+            .collect();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.style(&ComponentType::Border));
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).style(normal).block(block), area);
+    }
+
+    /// スクロールバー用に右端1列を切り出す（バッファが収まる場合は切り出さない）
+    fn split_scrollbar_area(area: Rect, total_lines: usize) -> (Rect, Option<Rect>) {
+        if area.width < 2 || total_lines <= area.height as usize {
+            return (area, None);
+        }
+
+        let text_area = Rect {
+            width: area.width - 1,
+            ..area
+        };
+        let bar_area = Rect {
+            x: area.x + area.width - 1,
+            width: 1,
+            ..area
+        };
+        (text_area, Some(bar_area))
+    }
+
+    /// スクロールバー描画
+    fn render_scrollbar(
+        frame: &mut Frame<'_>,
+        area: Rect,
+        total_lines: usize,
+        top_line: usize,
+        theme: &Theme,
+    ) {
+        let height = area.height as usize;
+        let thumb = match ScrollbarThumb::compute(total_lines, height, top_line) {
+            Some(thumb) => thumb,
+            None => return,
+        };
+
+        let track_style = theme.style(&ComponentType::Scrollbar);
+        let thumb_style = theme.style(&ComponentType::ScrollbarThumb);
+        let lines: Vec<Line<'static>> = (0..height)
+            .map(|row| {
+                if thumb.contains(row) {
+                    Line::styled("█", thumb_style)
+                } else {
+                    Line::styled("│", track_style)
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
     /// ミニバッファ描画
     fn render_minibuffer(
         &self,
@@ -363,7 +572,19 @@ impl AdvancedRenderer {
             | crate::minibuffer::MinibufferMode::KillBuffer
             | crate::minibuffer::MinibufferMode::QueryReplacePattern
             | crate::minibuffer::MinibufferMode::QueryReplaceReplacement
-            | crate::minibuffer::MinibufferMode::GotoLine => {
+            | crate::minibuffer::MinibufferMode::GotoLine
+            | crate::minibuffer::MinibufferMode::ScrollLineToTop
+            | crate::minibuffer::MinibufferMode::GotoChar
+            | crate::minibuffer::MinibufferMode::GotoColumn
+            | crate::minibuffer::MinibufferMode::SetFillColumn
+            | crate::minibuffer::MinibufferMode::ChangeDirectory
+            | crate::minibuffer::MinibufferMode::CodingSystem
+            | crate::minibuffer::MinibufferMode::InsertChar
+            | crate::minibuffer::MinibufferMode::DescribeFunction
+            | crate::minibuffer::MinibufferMode::CountMatches { .. }
+            | crate::minibuffer::MinibufferMode::Occur
+            | crate::minibuffer::MinibufferMode::FilterLines { .. }
+            | crate::minibuffer::MinibufferMode::StringRectangle => {
                 lines.push(Line::from(vec![
                     Span::styled(state.prompt.clone(), prompt_style),
                     Span::styled(state.input.clone(), input_style),
@@ -379,7 +600,8 @@ impl AdvancedRenderer {
             crate::minibuffer::MinibufferMode::InfoDisplay { message, .. } => {
                 lines.push(Line::from(Span::styled(message.clone(), info_style)));
             }
-            crate::minibuffer::MinibufferMode::SaveConfirmation => {
+            crate::minibuffer::MinibufferMode::SaveConfirmation
+            | crate::minibuffer::MinibufferMode::YesOrNo => {
                 lines.push(Line::from(vec![
                     Span::styled(state.prompt.clone(), prompt_style),
                     Span::styled(state.input.clone(), input_style),
@@ -393,7 +615,15 @@ impl AdvancedRenderer {
             }
         }
 
-        if matches!(state.mode, crate::minibuffer::MinibufferMode::GotoLine) {
+        if matches!(
+            state.mode,
+            crate::minibuffer::MinibufferMode::GotoLine
+                | crate::minibuffer::MinibufferMode::ScrollLineToTop
+                | crate::minibuffer::MinibufferMode::GotoChar
+                | crate::minibuffer::MinibufferMode::GotoColumn
+                | crate::minibuffer::MinibufferMode::SetFillColumn
+                | crate::minibuffer::MinibufferMode::YesOrNo
+        ) {
             if let Some(status) = &state.status_message {
                 lines.push(Line::from(Span::styled(status.clone(), info_style)));
             }
@@ -425,16 +655,22 @@ impl AdvancedRenderer {
             SearchStatus::Active => Style::default().fg(Color::White),
             SearchStatus::Wrapped => Style::default().fg(Color::Yellow),
             SearchStatus::NotFound => Style::default().fg(Color::Red),
+            SearchStatus::InvalidPattern => Style::default().fg(Color::Magenta),
         };
 
         spans.push(Span::styled(search.pattern.clone(), pattern_style));
 
-        if search.total_matches > 0 {
-            let current = search.current_match.unwrap_or(0);
-            spans.push(Span::styled(
-                format!(" [{}/{}]", current, search.total_matches),
-                Style::default().fg(Color::Gray),
-            ));
+        if let Some(counter) = search.match_counter() {
+            let style = if search.is_error() {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            spans.push(Span::styled(format!(" [{}]", counter), style));
+        }
+
+        if search.case_sensitive {
+            spans.push(Span::styled(" [case]", Style::default().fg(Color::Cyan)));
         }
 
         if search.wrapped {
@@ -442,10 +678,10 @@ impl AdvancedRenderer {
         }
 
         if let Some(message) = &search.message {
-            let style = if search.is_error() {
-                Style::default().fg(Color::Red)
-            } else {
-                Style::default().fg(Color::Green)
+            let style = match search.status {
+                SearchStatus::NotFound => Style::default().fg(Color::Red),
+                SearchStatus::InvalidPattern => Style::default().fg(Color::Magenta),
+                _ => Style::default().fg(Color::Green),
             };
             spans.push(Span::styled(format!(" {}", message), style));
         }
@@ -479,9 +715,10 @@ impl AdvancedRenderer {
         };
 
         let status_text = format!(
-            " {} {}  Ln {}, Col {}  {} lines  {}",
+            " {} {}  {}  Ln {}, Col {}  {} lines  {}",
             if status_info.is_modified { "*" } else { " " },
             status_info.file_label,
+            status_info.coding_label,
             cursor.line + 1,
             cursor.column + 1,
             line_count,
@@ -691,6 +928,7 @@ impl Default for AdvancedRenderer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::SearchDirection;
 
     #[test]
     fn test_renderer_creation() {
@@ -741,4 +979,41 @@ mod tests {
         assert_eq!(renderer.render_stats.total_frames, 1);
         assert_eq!(renderer.frame_stats.avg_frame_time, frame_time);
     }
+
+    #[test]
+    fn search_line_shows_live_match_counter() {
+        let area = Rect::new(0, 0, 80, 1);
+        let mut search = SearchUiState {
+            prompt_label: "I-search".to_string(),
+            pattern: "ab".to_string(),
+            status: SearchStatus::Active,
+            current_match: Some(2),
+            total_matches: 3,
+            wrapped: false,
+            message: None,
+            direction: SearchDirection::Forward,
+            regex: false,
+            case_sensitive: false,
+        };
+        let text = |line: &Line<'static>| -> String {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+
+        let (line, _) = AdvancedRenderer::search_line(area, &search);
+        assert_eq!(text(&line), "I-search: ab [2/3]");
+
+        search.pattern = "abz".to_string();
+        search.status = SearchStatus::NotFound;
+        search.current_match = None;
+        search.total_matches = 0;
+        let (line, _) = AdvancedRenderer::search_line(area, &search);
+        assert!(text(&line).starts_with("I-search: abz [0/0 failing]"));
+
+        search.case_sensitive = true;
+        let (line, _) = AdvancedRenderer::search_line(area, &search);
+        assert!(text(&line).starts_with("I-search: abz [0/0 failing] [case]"));
+    }
 }
@@ -0,0 +1,78 @@
+//! スクロールバー
+//!
+//! ウィンドウ右端に表示するスクロールバーのつまみ位置を計算する
+
+/// スクロールバーのつまみ（ウィンドウ内の行オフセットと長さ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollbarThumb {
+    /// ウィンドウ上端からの開始位置
+    pub offset: usize,
+    /// つまみの長さ（1以上）
+    pub length: usize,
+}
+
+impl ScrollbarThumb {
+    /// つまみの位置と長さを計算
+    ///
+    /// バッファ全体がウィンドウに収まる場合はスクロールバーを表示しないため `None` を返す。
+    pub fn compute(total_lines: usize, height: usize, top_line: usize) -> Option<Self> {
+        if height == 0 || total_lines <= height {
+            return None;
+        }
+
+        let length = (height * height / total_lines).clamp(1, height);
+        let max_top = total_lines - height;
+        let track = height - length;
+        let top = top_line.min(max_top);
+        // 先頭では上端、末尾では下端に接するよう四捨五入で配置する
+        let offset = (top * track + max_top / 2) / max_top;
+
+        Some(Self { offset, length })
+    }
+
+    /// 指定行（ウィンドウ内の行番号）がつまみに含まれるか
+    pub fn contains(&self, row: usize) -> bool {
+        row >= self.offset && row < self.offset + self.length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_when_buffer_fits() {
+        assert_eq!(ScrollbarThumb::compute(10, 20, 0), None);
+        assert_eq!(ScrollbarThumb::compute(20, 20, 0), None);
+        assert_eq!(ScrollbarThumb::compute(100, 0, 0), None);
+    }
+
+    #[test]
+    fn thumb_size_is_proportional() {
+        let thumb = ScrollbarThumb::compute(100, 20, 0).unwrap();
+        assert_eq!(
+            thumb,
+            ScrollbarThumb {
+                offset: 0,
+                length: 4
+            }
+        );
+
+        let tiny = ScrollbarThumb::compute(100_000, 20, 0).unwrap();
+        assert_eq!(tiny.length, 1);
+    }
+
+    #[test]
+    fn thumb_position_follows_scroll_offset() {
+        let middle = ScrollbarThumb::compute(100, 20, 40).unwrap();
+        assert_eq!(middle.offset, 8);
+
+        let bottom = ScrollbarThumb::compute(100, 20, 80).unwrap();
+        assert_eq!(bottom.offset + bottom.length, 20);
+
+        let beyond = ScrollbarThumb::compute(100, 20, 500).unwrap();
+        assert_eq!(beyond, bottom);
+        assert!(bottom.contains(19));
+        assert!(!bottom.contains(15));
+    }
+}
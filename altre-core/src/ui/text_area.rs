@@ -4,8 +4,9 @@
 
 use std::collections::HashMap;
 
-use crate::buffer::TextEditor;
+use crate::buffer::{FoldState, TextEditor};
 use crate::search::{HighlightKind, SearchHighlight};
+use crate::ui::line_wrap::LineWrap;
 use crate::ui::theme::{ComponentType, Theme};
 use ratatui::{
     layout::Rect,
@@ -15,6 +16,82 @@ use ratatui::{
     Frame,
 };
 
+/// 幅 0 で見えない文字か（ゼロ幅スペース・BOM・双方向制御文字など）
+pub fn is_invisible_char(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{2028}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206F}'
+            | '\u{FEFF}'
+            | '\u{FFF9}'..='\u{FFFB}'
+    )
+}
+
+/// 編集の妨げになりやすい文字か（タブ・改行以外の制御文字と幅 0 の文字）
+pub fn is_problematic_char(ch: char) -> bool {
+    (ch.is_control() && ch != '\t' && ch != '\n') || is_invisible_char(ch)
+}
+
+/// 制御文字（タブ・改行以外）の表示方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlCharDisplay {
+    /// `^A` のようなキャレット表記（2 セル幅）
+    #[default]
+    Caret,
+    /// Unicode の置換文字 `\u{FFFD}`（1 セル幅）
+    Replacement,
+    /// `<200B>` のような符号位置表記。幅 0 の見えない文字も表示する
+    CodePoint,
+}
+
+impl ControlCharDisplay {
+    /// キャレット表記・置換文字・符号位置表記の順で次の表示方法
+    pub fn next(self) -> Self {
+        match self {
+            ControlCharDisplay::Caret => ControlCharDisplay::Replacement,
+            ControlCharDisplay::Replacement => ControlCharDisplay::CodePoint,
+            ControlCharDisplay::CodePoint => ControlCharDisplay::Caret,
+        }
+    }
+
+    /// 制御文字の表示文字列を返す（表示を置き換えない文字なら `None`）
+    ///
+    /// C1 制御文字はキャレット表記を持たないため、キャレット表記でも置換文字で表示する。
+    /// 幅 0 の見えない文字は符号位置表記のときだけ置き換える。
+    pub fn render(self, ch: char) -> Option<String> {
+        if self == ControlCharDisplay::CodePoint {
+            return is_problematic_char(ch).then(|| format!("<{:04X}>", ch as u32));
+        }
+        if !ch.is_control() || ch == '\t' || ch == '\n' {
+            return None;
+        }
+        let caret = match ch {
+            '\x00'..='\x1F' => Some((ch as u8 + 0x40) as char),
+            '\x7F' => Some('?'),
+            _ => None,
+        };
+        Some(match (self, caret) {
+            (ControlCharDisplay::Caret, Some(symbol)) => format!("^{}", symbol),
+            _ => '\u{FFFD}'.to_string(),
+        })
+    }
+
+    /// 行頭から `column` 文字目までの表示上の桁数（制御文字の置き換え分を含む）
+    pub fn display_column(self, line_text: &str, column: usize) -> usize {
+        line_text
+            .chars()
+            .take(column)
+            .map(|ch| self.render(ch).map_or(1, |shown| shown.chars().count()))
+            .sum()
+    }
+}
+
 /// テキストエリア描画器
 #[derive(Debug)]
 pub struct TextArea {
@@ -23,6 +100,8 @@ pub struct TextArea {
     /// カーソル位置
     cursor_line: usize,
     cursor_column: usize,
+    /// 制御文字の表示方法
+    control_chars: ControlCharDisplay,
 }
 
 impl TextArea {
@@ -32,6 +111,7 @@ impl TextArea {
             show_border: false,
             cursor_line: 0,
             cursor_column: 0,
+            control_chars: ControlCharDisplay::default(),
         }
     }
 
@@ -41,7 +121,12 @@ impl TextArea {
         self
     }
 
-    /// カーソル位置を設定
+    /// 制御文字の表示方法を設定
+    pub fn set_control_char_display(&mut self, display: ControlCharDisplay) {
+        self.control_chars = display;
+    }
+
+    /// カーソル位置を設定（列は表示上の桁）
     pub fn set_cursor(&mut self, line: usize, column: usize) {
         self.cursor_line = line;
         self.cursor_column = column;
@@ -93,12 +178,18 @@ impl TextArea {
             return lines;
         }
 
+        let control_style = theme.style(&ComponentType::ControlChar);
         for (idx, &line_text) in text_lines.iter().enumerate() {
-            if let Some(highlights) = grouped.get(&idx) {
-                lines.push(build_highlighted_line(line_text, highlights, theme));
+            let line = if let Some(highlights) = grouped.get(&idx) {
+                build_highlighted_line(line_text, highlights, theme)
             } else {
-                lines.push(Line::from(line_text.to_string()));
-            }
+                Line::from(line_text.to_string())
+            };
+            lines.push(expand_control_chars(
+                line,
+                self.control_chars,
+                control_style,
+            ));
         }
 
         lines
@@ -188,6 +279,10 @@ impl TextArea {
 pub struct TextAreaRenderer {
     /// 行番号表示
     show_line_numbers: bool,
+    /// 制御文字の表示方法
+    control_char_display: ControlCharDisplay,
+    /// 長い行の折り返し（`None` なら折り返さずに横スクロールする）
+    line_wrap: Option<LineWrap>,
 }
 
 impl TextAreaRenderer {
@@ -195,6 +290,8 @@ impl TextAreaRenderer {
     pub fn new() -> Self {
         Self {
             show_line_numbers: true,
+            control_char_display: ControlCharDisplay::default(),
+            line_wrap: None,
         }
     }
 
@@ -208,6 +305,26 @@ impl TextAreaRenderer {
         self.show_line_numbers
     }
 
+    /// 制御文字の表示方法を設定
+    pub fn set_control_char_display(&mut self, display: ControlCharDisplay) {
+        self.control_char_display = display;
+    }
+
+    /// 制御文字の表示方法を取得
+    pub fn control_char_display(&self) -> ControlCharDisplay {
+        self.control_char_display
+    }
+
+    /// 長い行の折り返しを設定
+    pub fn set_line_wrap(&mut self, wrap: Option<LineWrap>) {
+        self.line_wrap = wrap;
+    }
+
+    /// 長い行の折り返しを取得
+    pub fn line_wrap(&self) -> Option<LineWrap> {
+        self.line_wrap
+    }
+
     /// テキストエリアを描画
     pub fn render(
         &self,
@@ -222,19 +339,26 @@ impl TextAreaRenderer {
         let content = editor.to_string();
         let cursor_pos = editor.cursor();
 
+        let control_chars = self.control_char_display;
         let mut text_area = TextArea::new();
-        text_area.set_cursor(cursor_pos.line, cursor_pos.column);
+        text_area.set_control_char_display(control_chars);
+        let cursor_column = content
+            .lines()
+            .nth(cursor_pos.line)
+            .map_or(cursor_pos.column, |line| {
+                control_chars.display_column(line, cursor_pos.column)
+            });
+        text_area.set_cursor(cursor_pos.line, cursor_column);
 
-        let all_lines = text_area.prepare_lines(&content, highlights, theme);
+        let buffer_lines = text_area.prepare_lines(&content, highlights, theme);
 
         let total_lines = if content.is_empty() {
             1
         } else {
-            all_lines.len().max(1)
+            buffer_lines.len().max(1)
         };
 
-        let mut line_number_area: Option<Rect> = None;
-        let mut line_number_lines: Vec<Line<'static>> = Vec::new();
+        let mut line_number_area: Option<(Rect, usize)> = None;
         let mut text_area_rect = area;
 
         if self.show_line_numbers {
@@ -242,60 +366,116 @@ impl TextAreaRenderer {
             let reserved_width = (digits as u16).saturating_add(1);
 
             if area.width > reserved_width {
-                let text_width = area.width.saturating_sub(reserved_width);
-                if text_width > 0 {
-                    let number_rect = Rect {
-                        x: area.x,
-                        y: area.y,
-                        width: reserved_width,
-                        height: area.height,
-                    };
-                    let text_rect = Rect {
-                        x: area.x + reserved_width,
-                        y: area.y,
-                        width: text_width,
-                        height: area.height,
-                    };
-
-                    let number_style = theme.style(&ComponentType::LineNumber);
-                    let current_style = theme.style(&ComponentType::LineNumberActive);
-
-                    line_number_lines.reserve(total_lines);
-                    for (idx, _) in all_lines.iter().enumerate() {
-                        let style = if idx == cursor_pos.line {
-                            current_style
-                        } else {
-                            number_style
-                        };
-                        let label = format!("{:>width$} ", idx + 1, width = digits);
-                        line_number_lines.push(Line::styled(label, style));
-                    }
-
-                    line_number_area = Some(number_rect);
-                    text_area_rect = text_rect;
-                }
+                let number_rect = Rect {
+                    x: area.x,
+                    y: area.y,
+                    width: reserved_width,
+                    height: area.height,
+                };
+                let text_rect = Rect {
+                    x: area.x + reserved_width,
+                    y: area.y,
+                    width: area.width - reserved_width,
+                    height: area.height,
+                };
+                line_number_area = Some((number_rect, digits));
+                text_area_rect = text_rect;
             }
         }
 
+        viewport.update_dimensions(
+            text_area_rect.height as usize,
+            text_area_rect.width.max(1) as usize,
+        );
+
         let max_line_columns = content
             .lines()
-            .map(|line| line.chars().count())
+            .map(|line| control_chars.display_column(line, usize::MAX))
             .max()
             .unwrap_or(0);
 
-        viewport.update_dimensions(
-            text_area_rect.height as usize,
-            text_area_rect.width.max(1) as usize,
-        );
+        // 折り返す場合は各行を表示行に分け、カーソルの表示行と桁を求める
+        let mut cursor_row_offset = 0usize;
+        let mut cursor_x = cursor_column;
+        let line_segments: Vec<Vec<Line<'static>>> = match self.line_wrap {
+            Some(wrap) => {
+                let window_width = viewport.width;
+                let margin = wrap.left_margin(window_width) as u16;
+                text_area_rect.x += margin;
+                text_area_rect.width = text_area_rect.width.saturating_sub(margin);
+
+                let mut segments = Vec::with_capacity(buffer_lines.len());
+                for (idx, line) in buffer_lines.into_iter().enumerate() {
+                    let shown: String = line
+                        .spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect();
+                    let mut rows = split_line_at(line, &wrap.wrap_points(&shown, window_width));
+                    if idx == cursor_pos.line {
+                        (cursor_row_offset, cursor_x) =
+                            wrap.position_of(&shown, cursor_column, window_width);
+                        if cursor_row_offset >= rows.len() {
+                            rows.push(Line::from(""));
+                        }
+                    }
+                    segments.push(rows);
+                }
+                segments
+            }
+            None => buffer_lines.into_iter().map(|line| vec![line]).collect(),
+        };
+
+        let placeholder_style = theme.style(&ComponentType::LineNumber);
+        let (all_lines, row_lines, line_rows) =
+            fold_display_rows(line_segments, editor.folds(), placeholder_style);
+
+        let mut line_number_lines: Vec<Line<'static>> = Vec::new();
+        if let Some((_, digits)) = line_number_area {
+            let number_style = theme.style(&ComponentType::LineNumber);
+            let current_style = theme.style(&ComponentType::LineNumberActive);
+
+            line_number_lines.reserve(row_lines.len());
+            for row_line in &row_lines {
+                let line_number_line = match row_line {
+                    Some(idx) => {
+                        let style = if *idx == cursor_pos.line {
+                            current_style
+                        } else {
+                            number_style
+                        };
+                        let label = format!("{:>width$} ", idx + 1, width = digits);
+                        Line::styled(label, style)
+                    }
+                    None => Line::styled(" ".repeat(digits + 1), number_style),
+                };
+                line_number_lines.push(line_number_line);
+            }
+        }
 
         if minibuffer_active {
             viewport.top_line = cursor_pos.line.saturating_sub(viewport.height / 2);
         }
 
         viewport.clamp_vertical(total_lines);
-        viewport.clamp_horizontal(max_line_columns);
+        if self.line_wrap.is_some() {
+            viewport.scroll_x = 0;
+        } else {
+            viewport.clamp_horizontal(max_line_columns);
+        }
 
-        let scroll_y = viewport.top_line.min(u16::MAX as usize) as u16;
+        let row_of = |line: usize| line_rows.get(line).copied().unwrap_or(line);
+        let cursor_row = row_of(cursor_pos.line) + cursor_row_offset;
+        // 折り返した行でカーソルが画面の下にはみ出すなら、表示開始行を進める
+        while viewport.top_line < cursor_pos.line
+            && cursor_row >= row_of(viewport.top_line) + viewport.height
+        {
+            viewport.top_line += 1;
+        }
+        let top_row = row_of(viewport.top_line);
+        text_area.set_cursor(cursor_row, cursor_x);
+
+        let scroll_y = top_row.min(u16::MAX as usize) as u16;
         let scroll_x = viewport.scroll_x.min(u16::MAX as usize) as u16;
 
         let paragraph = Paragraph::new(all_lines)
@@ -304,7 +484,7 @@ impl TextAreaRenderer {
 
         frame.render_widget(Clear, area);
 
-        if let Some(number_rect) = line_number_area {
+        if let Some((number_rect, _)) = line_number_area {
             let line_numbers = Paragraph::new(line_number_lines)
                 .style(theme.style(&ComponentType::LineNumber))
                 .scroll((scroll_y, 0));
@@ -313,14 +493,77 @@ impl TextAreaRenderer {
 
         frame.render_widget(paragraph, text_area_rect);
 
-        text_area.calculate_cursor_screen_position(
-            text_area_rect,
-            viewport.top_line,
-            viewport.scroll_x,
-        )
+        text_area.calculate_cursor_screen_position(text_area_rect, top_row, viewport.scroll_x)
     }
 }
 
+/// 折りたたみを反映した表示行を組み立てる
+///
+/// `lines` はバッファ行ごとの表示行（折り返さなければ1つ）。
+/// 戻り値は（表示行、各表示行が対応するバッファ行、各バッファ行の先頭の表示行）。
+/// 隠された行は `...` の1行にまとめられ、この行と折り返しの2行目以降はバッファ行を持たない。
+fn fold_display_rows(
+    lines: Vec<Vec<Line<'static>>>,
+    folds: &FoldState,
+    placeholder_style: Style,
+) -> (Vec<Line<'static>>, Vec<Option<usize>>, Vec<usize>) {
+    let mut rows = Vec::with_capacity(lines.len());
+    let mut row_lines = Vec::with_capacity(lines.len());
+    let mut line_rows = Vec::with_capacity(lines.len());
+    let mut in_placeholder = false;
+
+    for (idx, segments) in lines.into_iter().enumerate() {
+        if folds.is_hidden(idx) {
+            if !in_placeholder {
+                rows.push(Line::styled("...", placeholder_style));
+                row_lines.push(None);
+                in_placeholder = true;
+            }
+            line_rows.push(rows.len() - 1);
+            continue;
+        }
+        in_placeholder = false;
+        line_rows.push(rows.len());
+        for (segment_index, segment) in segments.into_iter().enumerate() {
+            rows.push(segment);
+            row_lines.push((segment_index == 0).then_some(idx));
+        }
+    }
+
+    (rows, row_lines, line_rows)
+}
+
+/// 表示行を `points`（文字位置）で分割する（各断片は元のスタイルを保つ）
+fn split_line_at(line: Line<'static>, points: &[usize]) -> Vec<Line<'static>> {
+    if points.is_empty() {
+        return vec![line];
+    }
+
+    let mut rows = Vec::with_capacity(points.len() + 1);
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut points = points.iter().copied().peekable();
+    let mut index = 0usize;
+    for span in line.spans {
+        let mut piece = String::new();
+        for ch in span.content.chars() {
+            if points.peek() == Some(&index) {
+                points.next();
+                if !piece.is_empty() {
+                    current.push(Span::styled(std::mem::take(&mut piece), span.style));
+                }
+                rows.push(Line::from(std::mem::take(&mut current)));
+            }
+            piece.push(ch);
+            index += 1;
+        }
+        if !piece.is_empty() {
+            current.push(Span::styled(piece, span.style));
+        }
+    }
+    rows.push(Line::from(current));
+    rows
+}
+
 fn build_highlighted_line(
     line_text: &str,
     highlights: &[&SearchHighlight],
@@ -350,6 +593,8 @@ fn build_highlighted_line(
             let segment = substring_by_char(line_text, start, end);
             let style = match highlight.kind {
                 HighlightKind::Selection => theme.style(&ComponentType::Selection),
+                HighlightKind::Keyword => theme.style(&ComponentType::KeywordHighlight),
+                HighlightKind::MatchingParen => theme.style(&ComponentType::MatchingParen),
                 HighlightKind::Search => {
                     if highlight.is_current {
                         Style::default()
@@ -378,6 +623,41 @@ fn build_highlighted_line(
     }
 }
 
+/// 行内の制御文字を表示用の文字列に置き換え、元のスタイルに制御文字用のスタイルを重ねる
+fn expand_control_chars(
+    line: Line<'static>,
+    display: ControlCharDisplay,
+    control_style: Style,
+) -> Line<'static> {
+    let has_control = line
+        .spans
+        .iter()
+        .any(|span| span.content.chars().any(|ch| display.render(ch).is_some()));
+    if !has_control {
+        return line;
+    }
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for span in line.spans {
+        let mut plain = String::new();
+        for ch in span.content.chars() {
+            match display.render(ch) {
+                Some(shown) => {
+                    if !plain.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut plain), span.style));
+                    }
+                    spans.push(Span::styled(shown, span.style.patch(control_style)));
+                }
+                None => plain.push(ch),
+            }
+        }
+        if !plain.is_empty() {
+            spans.push(Span::styled(plain, span.style));
+        }
+    }
+    Line::from(spans)
+}
+
 fn substring_by_char(text: &str, start: usize, end: usize) -> String {
     text.chars()
         .skip(start)
@@ -466,4 +746,266 @@ mod tests {
         assert_eq!(start, 20); // 30 - 20/2
         assert_eq!(end, 40); // 20 + 20
     }
+
+    #[test]
+    fn control_chars_render_in_caret_notation() {
+        let caret = ControlCharDisplay::Caret;
+        assert_eq!(caret.render('\x01').as_deref(), Some("^A"));
+        assert_eq!(caret.render('\x1B').as_deref(), Some("^["));
+        assert_eq!(caret.render('\x7F').as_deref(), Some("^?"));
+        assert_eq!(caret.render('\u{85}').as_deref(), Some("\u{FFFD}"));
+        assert_eq!(caret.render('\t'), None);
+        assert_eq!(caret.render('a'), None);
+
+        let replacement = ControlCharDisplay::Replacement;
+        assert_eq!(replacement.render('\x01').as_deref(), Some("\u{FFFD}"));
+
+        let line = "a\x01b\x02";
+        assert_eq!(caret.display_column(line, 2), 3);
+        assert_eq!(caret.display_column(line, usize::MAX), 6);
+        assert_eq!(replacement.display_column(line, usize::MAX), 4);
+        assert_eq!(caret.display_column("a\tb", 3), 3);
+    }
+
+    #[test]
+    fn code_point_display_shows_invisible_chars() {
+        let code_point = ControlCharDisplay::CodePoint;
+        assert_eq!(code_point.render('\u{200B}').as_deref(), Some("<200B>"));
+        assert_eq!(code_point.render('\u{FEFF}').as_deref(), Some("<FEFF>"));
+        assert_eq!(code_point.render('\x01').as_deref(), Some("<0001>"));
+        assert_eq!(code_point.render('\t'), None);
+        assert_eq!(code_point.render('a'), None);
+        assert_eq!(ControlCharDisplay::Caret.render('\u{200B}'), None);
+
+        let line = "a\u{200B}b";
+        assert_eq!(code_point.display_column(line, 2), 7);
+        assert_eq!(code_point.display_column(line, usize::MAX), 8);
+    }
+
+    #[test]
+    fn code_point_display_renders_placeholder_and_shifts_cursor() {
+        use crate::buffer::NavigationAction;
+        use crate::ui::theme::ThemeManager;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut editor = TextEditor::from_str("a\u{200B}b");
+        editor.navigate(NavigationAction::MoveLineEnd).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 3)).unwrap();
+        let mut renderer = TextAreaRenderer::new();
+        renderer.set_show_line_numbers(false);
+        renderer.set_control_char_display(ControlCharDisplay::CodePoint);
+        let theme_manager = ThemeManager::new();
+        let theme = theme_manager.current_theme();
+        let mut viewport = crate::ui::ViewportState::new();
+        let mut cursor = None;
+        terminal
+            .draw(|frame| {
+                cursor = renderer.render(
+                    frame,
+                    frame.area(),
+                    &editor,
+                    &mut viewport,
+                    theme,
+                    &[],
+                    false,
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, 0)].symbol().to_string())
+            .collect();
+        assert_eq!(row.trim_end(), "a<200B>b");
+        let control_fg = theme.style(&ComponentType::ControlChar).fg;
+        assert_eq!(buffer[(1, 0)].style().fg, control_fg);
+        assert_eq!(cursor, Some((8, 0)));
+    }
+
+    #[test]
+    fn control_chars_are_styled_and_shift_cursor() {
+        use crate::buffer::NavigationAction;
+        use crate::ui::theme::ThemeManager;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut editor = TextEditor::from_str("a\x01b\x1Bc");
+        editor.navigate(NavigationAction::MoveLineEnd).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 3)).unwrap();
+        let mut renderer = TextAreaRenderer::new();
+        renderer.set_show_line_numbers(false);
+        let theme_manager = ThemeManager::new();
+        let theme = theme_manager.current_theme();
+        let mut viewport = crate::ui::ViewportState::new();
+        let mut cursor = None;
+        terminal
+            .draw(|frame| {
+                cursor = renderer.render(
+                    frame,
+                    frame.area(),
+                    &editor,
+                    &mut viewport,
+                    theme,
+                    &[],
+                    false,
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, 0)].symbol().to_string())
+            .collect();
+        assert_eq!(row.trim_end(), "a^Ab^[c");
+        let control_fg = theme.style(&ComponentType::ControlChar).fg;
+        assert_eq!(buffer[(1, 0)].style().fg, control_fg);
+        assert_eq!(buffer[(2, 0)].style().fg, control_fg);
+        assert_ne!(buffer[(3, 0)].style().fg, control_fg);
+        assert_eq!(cursor, Some((7, 0)));
+    }
+
+    #[test]
+    fn test_folded_lines_are_not_rendered() {
+        use crate::buffer::NavigationAction;
+        use crate::ui::theme::ThemeManager;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut editor = TextEditor::from_str("head\n  one\n  two\ntail");
+        editor.toggle_fold_at_cursor();
+        editor.navigate(NavigationAction::MoveLineDown).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+        let renderer = TextAreaRenderer::new();
+        let theme_manager = ThemeManager::new();
+        let mut viewport = crate::ui::ViewportState::new();
+        let mut cursor = None;
+        terminal
+            .draw(|frame| {
+                cursor = renderer.render(
+                    frame,
+                    frame.area(),
+                    &editor,
+                    &mut viewport,
+                    theme_manager.current_theme(),
+                    &[],
+                    false,
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol().to_string())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(row(0), "1 head");
+        assert_eq!(row(1), "  ...");
+        assert_eq!(row(2), "4 tail");
+        assert_eq!(row(3), "");
+        assert_eq!(cursor, Some((2, 2)));
+    }
+
+    /// 折り返しを設定して描画し、各行の文字列とカーソル位置を返す
+    fn render_wrapped(
+        editor: &TextEditor,
+        wrap: Option<LineWrap>,
+        size: (u16, u16),
+    ) -> (Vec<String>, Option<(u16, u16)>) {
+        use crate::ui::theme::ThemeManager;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut terminal = Terminal::new(TestBackend::new(size.0, size.1)).unwrap();
+        let mut renderer = TextAreaRenderer::new();
+        renderer.set_line_wrap(wrap);
+        let theme_manager = ThemeManager::new();
+        let mut viewport = crate::ui::ViewportState::new();
+        let mut cursor = None;
+        terminal
+            .draw(|frame| {
+                cursor = renderer.render(
+                    frame,
+                    frame.area(),
+                    editor,
+                    &mut viewport,
+                    theme_manager.current_theme(),
+                    &[],
+                    false,
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let rows = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol().to_string())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        (rows, cursor)
+    }
+
+    #[test]
+    fn long_lines_wrap_by_mode_and_cursor_follows_wrapped_row() {
+        use crate::buffer::NavigationAction;
+        use crate::ui::{WrapColumn, WrapMode};
+
+        let mut editor = TextEditor::from_str("hello brave world\nend");
+        editor.navigate(NavigationAction::MoveLineEnd).unwrap();
+
+        let word = LineWrap {
+            mode: WrapMode::Word,
+            column: WrapColumn::Window,
+            centered: false,
+        };
+        // 行番号 "1 " の 2 桁を除いた幅 10 で折り返す
+        let (rows, cursor) = render_wrapped(&editor, Some(word), (12, 5));
+        assert_eq!(rows, vec!["1 hello", "  brave", "  world", "2 end", ""]);
+        assert_eq!(cursor, Some((7, 2)));
+
+        let char_wrap = LineWrap {
+            mode: WrapMode::Char,
+            ..word
+        };
+        let (rows, _) = render_wrapped(&editor, Some(char_wrap), (12, 5));
+        assert_eq!(rows, vec!["1 hello brav", "  e world", "2 end", "", ""]);
+
+        let (rows, _) = render_wrapped(&editor, None, (12, 5));
+        assert_eq!(rows[1], "2 end");
+    }
+
+    #[test]
+    fn fill_column_wrap_is_rendered_at_column_and_centered() {
+        use crate::ui::{WrapColumn, WrapMode};
+
+        let editor = TextEditor::from_str("one two three four");
+        let fill = LineWrap {
+            mode: WrapMode::Word,
+            column: WrapColumn::FillColumn(8),
+            centered: false,
+        };
+        // ウィンドウ幅によらず 8 桁で折り返す
+        for width in [20, 40] {
+            let (rows, _) = render_wrapped(&editor, Some(fill), (width, 4));
+            assert_eq!(rows, vec!["1 one two", "  three", "  four", ""]);
+        }
+
+        // 本文幅 18 に対して 8 桁なので左右に 5 桁ずつ余白を取る
+        let centered = LineWrap {
+            centered: true,
+            ..fill
+        };
+        let (rows, cursor) = render_wrapped(&editor, Some(centered), (20, 4));
+        assert_eq!(
+            rows,
+            vec!["1      one two", "       three", "       four", ""]
+        );
+        assert_eq!(cursor, Some((7, 0)));
+    }
 }
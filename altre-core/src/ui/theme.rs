@@ -48,6 +48,10 @@ pub enum ComponentType {
     CompletionSelected,
     /// ウィンドウ間の区切り
     WindowDivider,
+    /// スクロールバーの溝
+    Scrollbar,
+    /// スクロールバーのつまみ
+    ScrollbarThumb,
     /// シンタックスハイライト - キーワード
     SyntaxKeyword,
     /// シンタックスハイライト - 文字列
@@ -58,6 +62,12 @@ pub enum ComponentType {
     SyntaxNumber,
     /// シンタックスハイライト - 演算子
     SyntaxOperator,
+    /// TODO/FIXME などの注目キーワード
+    KeywordHighlight,
+    /// 対応する括弧
+    MatchingParen,
+    /// キャレット表記などで表示する制御文字
+    ControlChar,
 }
 
 /// カラー設定
@@ -265,6 +275,14 @@ impl Theme {
             ComponentType::WindowDivider,
             ColorScheme::new(Color::Gray, Color::Gray),
         );
+        self.set_color(
+            ComponentType::Scrollbar,
+            ColorScheme::new(Color::Gray, Color::White),
+        );
+        self.set_color(
+            ComponentType::ScrollbarThumb,
+            ColorScheme::new(Color::DarkGray, Color::White),
+        );
 
         // シンタックスハイライト
         self.set_color(
@@ -287,6 +305,18 @@ impl Theme {
             ComponentType::SyntaxOperator,
             ColorScheme::new(Color::Red, Color::White),
         );
+        self.set_color(
+            ComponentType::KeywordHighlight,
+            ColorScheme::new(Color::Black, Color::Yellow).with_modifier(Modifier::BOLD),
+        );
+        self.set_color(
+            ComponentType::MatchingParen,
+            ColorScheme::new(Color::White, Color::Magenta).with_modifier(Modifier::BOLD),
+        );
+        self.set_color(
+            ComponentType::ControlChar,
+            ColorScheme::new(Color::Red, Color::White),
+        );
     }
 
     fn set_dark_colors(&mut self) {
@@ -347,6 +377,14 @@ impl Theme {
             ComponentType::WindowDivider,
             ColorScheme::new(Color::Black, Color::DarkGray),
         );
+        self.set_color(
+            ComponentType::Scrollbar,
+            ColorScheme::new(Color::DarkGray, Color::Black),
+        );
+        self.set_color(
+            ComponentType::ScrollbarThumb,
+            ColorScheme::new(Color::Gray, Color::Black),
+        );
 
         // シンタックスハイライト
         self.set_color(
@@ -369,6 +407,18 @@ impl Theme {
             ComponentType::SyntaxOperator,
             ColorScheme::new(Color::LightRed, Color::Black),
         );
+        self.set_color(
+            ComponentType::KeywordHighlight,
+            ColorScheme::new(Color::Black, Color::Yellow).with_modifier(Modifier::BOLD),
+        );
+        self.set_color(
+            ComponentType::MatchingParen,
+            ColorScheme::new(Color::White, Color::Magenta).with_modifier(Modifier::BOLD),
+        );
+        self.set_color(
+            ComponentType::ControlChar,
+            ColorScheme::new(Color::LightRed, Color::Black),
+        );
     }
 
     fn set_high_contrast_colors(&mut self) {
@@ -429,6 +479,14 @@ impl Theme {
             ComponentType::WindowDivider,
             ColorScheme::new(Color::White, Color::Black).with_modifier(Modifier::BOLD),
         );
+        self.set_color(
+            ComponentType::Scrollbar,
+            ColorScheme::new(Color::White, Color::Black),
+        );
+        self.set_color(
+            ComponentType::ScrollbarThumb,
+            ColorScheme::new(Color::White, Color::Black).with_modifier(Modifier::BOLD),
+        );
 
         // シンタックスハイライト - ハイコントラストでは色分けを最小限に
         self.set_color(
@@ -451,6 +509,18 @@ impl Theme {
             ComponentType::SyntaxOperator,
             ColorScheme::new(Color::White, Color::Black).with_modifier(Modifier::BOLD),
         );
+        self.set_color(
+            ComponentType::KeywordHighlight,
+            ColorScheme::new(Color::Black, Color::White).with_modifier(Modifier::BOLD),
+        );
+        self.set_color(
+            ComponentType::MatchingParen,
+            ColorScheme::new(Color::White, Color::Black).with_modifier(Modifier::REVERSED),
+        );
+        self.set_color(
+            ComponentType::ControlChar,
+            ColorScheme::new(Color::White, Color::Black).with_modifier(Modifier::BOLD),
+        );
     }
 }
 
@@ -5,6 +5,7 @@
 use std::collections::HashMap;
 
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
 
 use crate::buffer::BufferId;
 use crate::ui::ViewportState;
@@ -14,7 +15,8 @@ use crate::ui::ViewportState;
 pub struct WindowId(pub usize);
 
 /// 分割方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SplitOrientation {
     /// 上下方向への分割（Emacs の `C-x 2`）
     Horizontal,
@@ -31,6 +33,20 @@ pub enum WindowError {
     NotFound,
 }
 
+/// ウィンドウ分割構成のスナップショット（セッション保存用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowLayout {
+    /// 単一ウィンドウ
+    Leaf,
+    /// 分割
+    Split {
+        orientation: SplitOrientation,
+        first: Box<WindowLayout>,
+        second: Box<WindowLayout>,
+    },
+}
+
 #[derive(Debug, Clone)]
 struct WindowState {
     buffer_id: Option<BufferId>,
@@ -58,6 +74,21 @@ impl LayoutNode {
         }
     }
 
+    fn snapshot(&self) -> WindowLayout {
+        match self {
+            LayoutNode::Leaf(_) => WindowLayout::Leaf,
+            LayoutNode::Split {
+                orientation,
+                first,
+                second,
+            } => WindowLayout::Split {
+                orientation: *orientation,
+                first: Box::new(first.snapshot()),
+                second: Box::new(second.snapshot()),
+            },
+        }
+    }
+
     fn replace_leaf(&mut self, target: WindowId, replacement: LayoutNode) -> bool {
         match self {
             LayoutNode::Leaf(id) if *id == target => {
@@ -267,6 +298,27 @@ impl WindowManager {
         }
     }
 
+    /// 指定ウィンドウのバッファID
+    pub fn buffer(&self, id: WindowId) -> Option<BufferId> {
+        self.states.get(&id).and_then(|state| state.buffer_id)
+    }
+
+    /// 指定バッファを表示しているウィンドウがあるか
+    pub fn displays_buffer(&self, buffer_id: BufferId) -> bool {
+        self.states
+            .values()
+            .any(|state| state.buffer_id == Some(buffer_id))
+    }
+
+    /// 指定バッファを表示しているウィンドウの割り当てを置き換える
+    pub fn replace_buffer(&mut self, from: BufferId, to: Option<BufferId>) {
+        for state in self.states.values_mut() {
+            if state.buffer_id == Some(from) {
+                state.buffer_id = to;
+            }
+        }
+    }
+
     /// フォーカス中ウィンドウを分割
     pub fn split_focused(&mut self, orientation: SplitOrientation) -> WindowId {
         let new_id = WindowId(self.next_id);
@@ -344,6 +396,33 @@ impl WindowManager {
         }
     }
 
+    /// 指定バッファを表示しているウィンドウ（レイアウト順）
+    pub fn windows_showing(&self, buffer_id: BufferId) -> Vec<WindowId> {
+        self.leaf_order()
+            .into_iter()
+            .filter(|id| self.buffer(*id) == Some(buffer_id))
+            .collect()
+    }
+
+    /// フォーカス中ウィンドウと同じバッファを表示している次のウィンドウへ移動
+    ///
+    /// 末尾の次は先頭に戻る。該当するウィンドウが他になければ `false` を返す。
+    pub fn focus_next_showing_same_buffer(&mut self) -> bool {
+        let Some(buffer_id) = self.focused_buffer() else {
+            return false;
+        };
+        let windows = self.windows_showing(buffer_id);
+        if windows.len() <= 1 {
+            return false;
+        }
+        let pos = windows
+            .iter()
+            .position(|&id| id == self.focused)
+            .unwrap_or(0);
+        self.focused = windows[(pos + 1) % windows.len()];
+        true
+    }
+
     /// レイアウト順のウィンドウID一覧
     pub fn leaf_order(&self) -> Vec<WindowId> {
         let mut leaves = Vec::new();
@@ -358,6 +437,64 @@ impl WindowManager {
         rects
     }
 
+    /// 現在の分割構成を取得
+    pub fn layout_snapshot(&self) -> WindowLayout {
+        self.layout.snapshot()
+    }
+
+    /// フォーカス中ウィンドウのレイアウト順での位置
+    pub fn focused_index(&self) -> usize {
+        self.leaf_order()
+            .iter()
+            .position(|&id| id == self.focused)
+            .unwrap_or(0)
+    }
+
+    /// 分割構成から再構築する（ビューポートは初期化される）
+    pub fn from_layout(layout: &WindowLayout, focused_index: usize) -> Self {
+        let mut manager = Self {
+            layout: LayoutNode::Leaf(WindowId(0)),
+            states: HashMap::new(),
+            focused: WindowId(0),
+            next_id: 0,
+        };
+        manager.layout = manager.build_layout(layout);
+
+        let leaves = manager.leaf_order();
+        manager.focused = leaves
+            .get(focused_index)
+            .or_else(|| leaves.first())
+            .copied()
+            .unwrap_or(WindowId(0));
+        manager
+    }
+
+    fn build_layout(&mut self, layout: &WindowLayout) -> LayoutNode {
+        match layout {
+            WindowLayout::Leaf => {
+                let id = WindowId(self.next_id);
+                self.next_id += 1;
+                self.states.insert(
+                    id,
+                    WindowState {
+                        buffer_id: None,
+                        viewport: ViewportState::new(),
+                    },
+                );
+                LayoutNode::Leaf(id)
+            }
+            WindowLayout::Split {
+                orientation,
+                first,
+                second,
+            } => LayoutNode::Split {
+                orientation: *orientation,
+                first: Box::new(self.build_layout(first)),
+                second: Box::new(self.build_layout(second)),
+            },
+        }
+    }
+
     /// ウィンドウ矩形と区切り線の領域を取得
     pub fn layout_rects_with_dividers(&self, area: Rect) -> (Vec<(WindowId, Rect)>, Vec<Rect>) {
         let mut rects = Vec::new();
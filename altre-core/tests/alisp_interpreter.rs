@@ -1,5 +1,5 @@
 use altre::alisp::integration::eval_in_minibuffer;
-use altre::alisp::Interpreter;
+use altre::alisp::{Arity, Interpreter};
 
 #[test]
 fn eval_arithmetic() {
@@ -16,6 +16,23 @@ fn define_function_and_call() {
     assert_eq!(result.display, "9");
 }
 
+#[test]
+fn reset_environment_forgets_definitions() {
+    let mut interp = Interpreter::new();
+    interp.eval("(define greeting \"hi\")").unwrap();
+    interp.eval("(define (twice x) (* x 2))").unwrap();
+    assert_eq!(interp.eval("(twice 21)").unwrap().display, "42");
+
+    interp.reset_environment();
+    assert!(interp.eval("greeting").is_err());
+    assert!(interp.describe_function("twice").is_none());
+    assert_eq!(interp.eval("(+ 1 2)").unwrap().display, "3");
+    assert_eq!(
+        interp.eval("(string-append \"a\" \"b\")").unwrap().display,
+        "ab"
+    );
+}
+
 #[test]
 fn let_scoping() {
     let mut interp = Interpreter::new();
@@ -43,6 +60,52 @@ fn print_emits_message() {
     assert!(outcome.messages.contains(&"hello".to_string()));
 }
 
+#[test]
+fn outcome_separates_value_and_messages() {
+    let mut interp = Interpreter::new();
+    let outcome = interp.eval_outcome("(begin (print \"a\") (print \"b\") (+ 1 2))");
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.value.type_name(), "integer");
+    assert_eq!(outcome.display, "3");
+    assert_eq!(outcome.messages, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(outcome.to_string(), "3 (a | b)");
+
+    let next = interp.eval_outcome("(+ 1 1)");
+    assert!(next.messages.is_empty());
+}
+
+#[test]
+fn outcome_keeps_messages_and_backtrace_on_error() {
+    let mut interp = Interpreter::new();
+    interp.eval("(define (inner x) (/ x 0))").unwrap();
+    interp.eval("(define (outer x) (inner x))").unwrap();
+    let outcome = interp.eval_outcome("(begin (print \"before\") (outer 1))");
+    let err = outcome.error.as_ref().expect("evaluation fails");
+    assert_eq!(err.backtrace, vec!["/", "inner", "outer"]);
+    assert_eq!(outcome.messages, vec!["before".to_string()]);
+    assert!(!outcome.is_ok());
+    assert!(outcome.to_string().ends_with("(before)"));
+}
+
+#[test]
+fn debug_on_error_records_calling_forms() {
+    let mut interp = Interpreter::new();
+    interp.eval("(define (inner x) (/ x 0))").unwrap();
+    interp
+        .eval("(define (outer s) (inner (string-length s)))")
+        .unwrap();
+    interp.set_debug_on_error(true);
+    let err = interp.eval("(outer \"abc\")").unwrap_err();
+    assert_eq!(
+        err.backtrace,
+        vec!["(/ 3 0)", "(inner 3)", "(outer \"abc\")"]
+    );
+
+    interp.set_debug_on_error(false);
+    let err = interp.eval("(outer \"abc\")").unwrap_err();
+    assert_eq!(err.backtrace, vec!["/", "inner", "outer"]);
+}
+
 #[test]
 fn boolean_logic() {
     let mut interp = Interpreter::new();
@@ -50,6 +113,54 @@ fn boolean_logic() {
     assert_eq!(interp.eval("(or #f #t)").unwrap().display, "#t");
 }
 
+#[test]
+fn nil_and_empty_list_are_null() {
+    let mut interp = Interpreter::new();
+    assert_eq!(interp.eval("nil").unwrap().display, "()");
+    assert_eq!(interp.eval("()").unwrap().display, "()");
+    assert_eq!(interp.eval("'()").unwrap().display, "()");
+    assert_eq!(interp.eval("(null? nil)").unwrap().display, "#t");
+    assert_eq!(interp.eval("(null? '())").unwrap().display, "#t");
+    assert_eq!(interp.eval("(null? 0)").unwrap().display, "#f");
+    assert_eq!(interp.eval("(type-of nil)").unwrap().display, "list");
+    assert!(interp.eval("'foo").is_err());
+}
+
+#[test]
+fn side_effect_results_are_not_the_empty_list() {
+    let mut interp = Interpreter::new();
+    assert_eq!(interp.eval("(null? (print \"x\"))").unwrap().display, "#f");
+    assert_eq!(interp.eval("(null? (define x 1))").unwrap().display, "#f");
+    assert_eq!(interp.eval("(null? (begin))").unwrap().display, "#f");
+    assert_eq!(
+        interp.eval("(type-of (print \"x\"))").unwrap().display,
+        "unit"
+    );
+}
+
+#[test]
+fn describe_function_reports_arity_and_doc() {
+    let mut interp = Interpreter::new();
+    let help = interp.describe_function("string-length").expect("builtin");
+    assert!(help.builtin);
+    assert_eq!(help.arity, Arity::Exact(1));
+    assert!(help.doc.is_some());
+
+    interp
+        .eval("(define (greet name) \"挨拶を返す\" (string-append \"hi \" name))")
+        .unwrap();
+    interp.eval("(define (bare x) x)").unwrap();
+    let help = interp.describe_function("greet").expect("defun");
+    assert!(!help.builtin);
+    assert_eq!(help.params, vec!["name".to_string()]);
+    assert_eq!(help.doc.as_deref(), Some("挨拶を返す"));
+    assert_eq!(interp.eval("(greet \"you\")").unwrap().display, "hi you");
+    assert_eq!(interp.describe_function("bare").unwrap().doc, None);
+
+    assert!(interp.describe_function("no-such-function").is_none());
+    assert!(interp.function_names().contains(&"greet".to_string()));
+}
+
 #[test]
 fn error_for_unknown_symbol() {
     let mut interp = Interpreter::new();
@@ -89,10 +89,10 @@ fn test_goto_line() -> Result<()> {
     assert_eq!(app.get_cursor_position().line, 2);
     assert_eq!(app.get_cursor_position().column, 0);
 
-    // 行数を超えた場合は末尾へ移動
+    // 行数を超えた場合は最終行の行頭へ移動
     app.goto_line(10)?;
     assert_eq!(app.get_cursor_position().line, 2);
-    assert_eq!(app.get_cursor_position().column, "three".chars().count());
+    assert_eq!(app.get_cursor_position().column, 0);
 
     // 無効な行番号はエラー
     assert!(app.goto_line(0).is_err());
@@ -12,6 +12,7 @@ use altre::minibuffer::{
 pub use altre::minibuffer::{MinibufferMode, SystemResponse};
 use std::fs;
 use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 use tempfile::TempDir;
 
 pub mod unit_tests;
@@ -206,9 +207,16 @@ pub fn unicode_samples() -> &'static [&'static str] {
         "🚀🌟💻",
         "naïve",
         "café",
+        "cafe\u{301}",
+        "👍🏽",
     ]
 }
 
+/// サンプル文字列を書記素クラスタへ分割
+pub fn unicode_graphemes(sample: &str) -> Vec<&str> {
+    sample.graphemes(true).collect()
+}
+
 /// 長いパス入力サンプル
 pub fn long_path_input() -> String {
     let dir = "a".repeat(120);
@@ -3,8 +3,8 @@
 //! 文字入力・カーソル操作・削除・キャンセル・エラーメッセージの基本挙動を検証
 
 use super::{
-    is_continue, key_char, key_plain, key_ctrl, long_path_input, unicode_samples,
-    MinibufferMode, MinibufferTestHelper,
+    is_continue, key_char, key_plain, key_ctrl, long_path_input, unicode_graphemes,
+    unicode_samples, MinibufferMode, MinibufferTestHelper,
 };
 use altre::input::keybinding::KeyCode;
 
@@ -53,6 +53,33 @@ fn test_backspace_and_delete_remove_expected_characters() {
     assert_eq!(helper.input(), "ell");
 }
 
+#[test]
+fn test_backspace_and_cursor_step_by_grapheme_cluster() {
+    let mut helper = MinibufferTestHelper::new();
+
+    for sample in unicode_samples() {
+        helper.start_find_file(None);
+        helper.type_text(sample);
+
+        // 末尾から 1 書記素ずつ左へ動き、先頭で止まる
+        let mut steps = 0;
+        while helper.state().cursor_pos > 0 {
+            helper.press_arrow(KeyCode::Left);
+            steps += 1;
+        }
+        assert_eq!(steps, unicode_graphemes(sample).len());
+
+        // 末尾での backspace は最後の書記素をまとめて削除する
+        helper.press_ctrl('e');
+        helper.press_backspace();
+        let graphemes = unicode_graphemes(sample);
+        let expected: String = graphemes[..graphemes.len() - 1].concat();
+        assert_eq!(helper.input(), expected);
+        assert_eq!(helper.state().cursor_pos, expected.chars().count());
+        helper.system().deactivate();
+    }
+}
+
 #[test]
 fn test_cursor_movement_with_arrows_and_shortcuts() {
     let mut helper = MinibufferTestHelper::new();
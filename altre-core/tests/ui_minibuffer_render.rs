@@ -35,7 +35,8 @@ fn query_replace_prompt_is_rendered() {
     );
 
     let layout = LayoutManager::new();
-    let area_map = layout.calculate_areas(Rect::new(0, 0, 80, 20), minibuffer.is_active(), true);
+    let area_map =
+        layout.calculate_areas(Rect::new(0, 0, 80, 20), minibuffer.is_active(), true, true);
     let minibuffer_rect = area_map
         .get(&altre::ui::layout::AreaType::Minibuffer)
         .copied()
@@ -52,6 +53,7 @@ fn query_replace_prompt_is_rendered() {
             StatusLineInfo {
                 file_label: "test",
                 is_modified: false,
+                coding_label: "UTF-8/LF",
             },
         )
         .unwrap();
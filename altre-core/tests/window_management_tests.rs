@@ -118,3 +118,35 @@ fn test_window_manager_delete_others() {
     manager.delete_others().expect("delete others");
     assert_eq!(manager.window_count(), 1);
 }
+
+#[test]
+fn test_focus_cycles_only_windows_showing_same_buffer() {
+    let mut manager = WindowManager::new();
+    let first = manager.focused_window();
+    let second = manager.split_focused(SplitOrientation::Horizontal);
+    let third = manager.split_focused(SplitOrientation::Vertical);
+    manager.set_buffer(first, Some(1));
+    manager.set_buffer(second, Some(1));
+    manager.set_buffer(third, Some(2));
+
+    let showing = manager.windows_showing(1);
+    assert_eq!(showing.len(), 2);
+    assert!(showing.contains(&first) && showing.contains(&second));
+
+    let mut visited = Vec::new();
+    for _ in 0..4 {
+        assert!(manager.focus_next_showing_same_buffer());
+        visited.push(manager.focused_window());
+    }
+    assert!(!visited.contains(&third));
+    assert_eq!(visited[0], visited[2]);
+    assert_eq!(visited[1], visited[3]);
+    assert_ne!(visited[0], visited[1]);
+
+    manager.focus_next();
+    while manager.focused_window() != third {
+        manager.focus_next();
+    }
+    assert!(!manager.focus_next_showing_same_buffer());
+    assert_eq!(manager.focused_window(), third);
+}
@@ -140,6 +140,39 @@ impl BackendController {
         Ok(())
     }
 
+    /// アクティブバッファの `start_line` 行から `end_line` 行の手前まで（0始まり）のテキスト
+    ///
+    /// 範囲はバッファの行数に収める。`start_line` が `end_line` より大きい場合はエラー。
+    pub fn buffer_text_range(&mut self, start_line: usize, end_line: usize) -> Result<Vec<String>> {
+        if start_line > end_line {
+            return Err(AltreError::Application(format!(
+                "行範囲が不正です: {start_line}..{end_line}"
+            )));
+        }
+        let text = self.backend.render_view().editor.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+        let end = end_line.min(lines.len());
+        let start = start_line.min(end);
+        Ok(lines[start..end]
+            .iter()
+            .map(|line| line.to_string())
+            .collect())
+    }
+
+    /// アクティブバッファの行・桁（0始まり、桁は文字単位）に対応する文字オフセット
+    ///
+    /// 行は最終行に、桁は行末に収める（クリック位置がテキストの外でも行末などを返す）。
+    pub fn char_offset_at(&mut self, line: usize, column: usize) -> usize {
+        let text = self.backend.render_view().editor.to_string();
+        let lines: Vec<&str> = text.split('\n').collect();
+        let line = line.min(lines.len() - 1);
+        let line_start: usize = lines[..line]
+            .iter()
+            .map(|line| line.chars().count() + 1)
+            .sum();
+        line_start + column.min(lines[line].chars().count())
+    }
+
     fn create_snapshot(&mut self) -> Result<EditorSnapshot> {
         let gui_theme = self.backend.gui_theme();
         let metadata = self.backend.render_metadata();
@@ -271,6 +304,49 @@ mod tests {
         assert!(!snapshot.status.label.is_empty());
     }
 
+    fn controller_with_text(text: &str) -> BackendController {
+        let temp = tempdir().unwrap();
+        let options = BackendOptions {
+            debug_log_path: Some(temp.path().join("log.jsonl")),
+            ..Default::default()
+        };
+        let mut controller = BackendController::new(options).unwrap();
+        let events: Vec<KeyEvent> = text
+            .chars()
+            .map(|ch| match ch {
+                '\n' => KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                ch => KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+            })
+            .collect();
+        controller.handle_key_events(&events).unwrap();
+        controller
+    }
+
+    #[test]
+    fn buffer_text_range_returns_clamped_lines() {
+        let mut controller = controller_with_text("one\ntwo\nthree");
+        assert_eq!(
+            controller.buffer_text_range(0, 2).unwrap(),
+            vec!["one".to_string(), "two".to_string()]
+        );
+        assert_eq!(
+            controller.buffer_text_range(1, 100).unwrap(),
+            vec!["two".to_string(), "three".to_string()]
+        );
+        assert!(controller.buffer_text_range(5, 9).unwrap().is_empty());
+        assert!(controller.buffer_text_range(2, 1).is_err());
+    }
+
+    #[test]
+    fn char_offset_at_maps_and_clamps_positions() {
+        let mut controller = controller_with_text("ab\nあいう\nc");
+        assert_eq!(controller.char_offset_at(0, 0), 0);
+        assert_eq!(controller.char_offset_at(1, 2), 5);
+        assert_eq!(controller.char_offset_at(1, 10), 6);
+        assert_eq!(controller.char_offset_at(2, 1), 8);
+        assert_eq!(controller.char_offset_at(9, 9), 8);
+    }
+
     #[test]
     fn save_active_buffer_writes_file() {
         let temp = tempdir().unwrap();
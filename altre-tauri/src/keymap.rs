@@ -54,8 +54,7 @@ impl KeySequencePayload {
 
 impl KeyStrokePayload {
     pub fn to_key_event(self) -> std::result::Result<KeyEvent, KeyConversionError> {
-        let code = parse_key_code(&self.key)?;
-        let mut modifiers = CrosstermModifiers::empty();
+        let (code, mut modifiers) = translate_key(&self.key)?;
         if self.ctrl {
             modifiers |= CrosstermModifiers::CONTROL;
         }
@@ -69,31 +68,100 @@ impl KeyStrokePayload {
     }
 }
 
-fn parse_key_code(raw: &str) -> std::result::Result<CrosstermKeyCode, KeyConversionError> {
+/// 名前付きキー（小文字で比較する。`Arrow*` はブラウザの `KeyboardEvent.key`）
+const NAMED_KEYS: &[(&str, CrosstermKeyCode)] = &[
+    ("enter", CrosstermKeyCode::Enter),
+    ("backspace", CrosstermKeyCode::Backspace),
+    ("delete", CrosstermKeyCode::Delete),
+    ("insert", CrosstermKeyCode::Insert),
+    ("tab", CrosstermKeyCode::Tab),
+    ("escape", CrosstermKeyCode::Esc),
+    ("esc", CrosstermKeyCode::Esc),
+    ("up", CrosstermKeyCode::Up),
+    ("arrowup", CrosstermKeyCode::Up),
+    ("down", CrosstermKeyCode::Down),
+    ("arrowdown", CrosstermKeyCode::Down),
+    ("left", CrosstermKeyCode::Left),
+    ("arrowleft", CrosstermKeyCode::Left),
+    ("right", CrosstermKeyCode::Right),
+    ("arrowright", CrosstermKeyCode::Right),
+    ("home", CrosstermKeyCode::Home),
+    ("end", CrosstermKeyCode::End),
+    ("pageup", CrosstermKeyCode::PageUp),
+    ("pagedown", CrosstermKeyCode::PageDown),
+];
+
+/// 単独で届く制御文字と、端末と同じ解釈のキー
+const CONTROL_CHARS: &[(char, CrosstermKeyCode)] = &[
+    ('\u{0008}', CrosstermKeyCode::Backspace),
+    ('\t', CrosstermKeyCode::Tab),
+    ('\n', CrosstermKeyCode::Enter),
+    ('\r', CrosstermKeyCode::Enter),
+    ('\u{001B}', CrosstermKeyCode::Esc),
+    ('\u{007F}', CrosstermKeyCode::Backspace),
+];
+
+/// Ctrl と組み合わせた記号として扱う制御文字
+const CONTROL_SYMBOLS: &[(char, char)] = &[
+    ('\u{0000}', ' '),
+    ('\u{001C}', '\\'),
+    ('\u{001D}', ']'),
+    ('\u{001E}', '^'),
+    ('\u{001F}', '_'),
+];
+
+/// 対応するファンクションキーの上限
+const MAX_FUNCTION_KEY: u8 = 12;
+
+/// GUI から届いたキー名を crossterm のキーと、キー名が含意する修飾キーへ変換
+///
+/// 単独の制御文字（`\u{0001}` など）は Ctrl+文字として解釈する。
+pub fn translate_key(
+    raw: &str,
+) -> std::result::Result<(CrosstermKeyCode, CrosstermModifiers), KeyConversionError> {
+    let none = CrosstermModifiers::empty();
     if raw == " " {
-        return Ok(CrosstermKeyCode::Char(' '));
+        return Ok((CrosstermKeyCode::Char(' '), none));
+    }
+
+    let mut chars = raw.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Ok(translate_char(ch));
     }
 
     let key = raw.trim();
-    if key.len() == 1 {
-        let ch = key.chars().next().unwrap();
-        return Ok(CrosstermKeyCode::Char(ch));
-    }
-
-    let code = match key.to_ascii_lowercase().as_str() {
-        "enter" => CrosstermKeyCode::Enter,
-        "backspace" => CrosstermKeyCode::Backspace,
-        "delete" => CrosstermKeyCode::Delete,
-        "tab" => CrosstermKeyCode::Tab,
-        "escape" | "esc" => CrosstermKeyCode::Esc,
-        "up" => CrosstermKeyCode::Up,
-        "down" => CrosstermKeyCode::Down,
-        "left" => CrosstermKeyCode::Left,
-        "right" => CrosstermKeyCode::Right,
-        _ => return Err(KeyConversionError::UnsupportedKey(key.to_string())),
-    };
-
-    Ok(code)
+    let mut chars = key.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Ok(translate_char(ch));
+    }
+
+    let lower = key.to_ascii_lowercase();
+    if let Some((_, code)) = NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+        return Ok((*code, none));
+    }
+    if let Some(number) = lower
+        .strip_prefix('f')
+        .and_then(|digits| digits.parse::<u8>().ok())
+        .filter(|number| (1..=MAX_FUNCTION_KEY).contains(number))
+    {
+        return Ok((CrosstermKeyCode::F(number), none));
+    }
+
+    Err(KeyConversionError::UnsupportedKey(key.to_string()))
+}
+
+fn translate_char(ch: char) -> (CrosstermKeyCode, CrosstermModifiers) {
+    if let Some((_, code)) = CONTROL_CHARS.iter().find(|(control, _)| *control == ch) {
+        return (*code, CrosstermModifiers::empty());
+    }
+    if let Some((_, symbol)) = CONTROL_SYMBOLS.iter().find(|(control, _)| *control == ch) {
+        return (CrosstermKeyCode::Char(*symbol), CrosstermModifiers::CONTROL);
+    }
+    if ('\u{0001}'..='\u{001A}').contains(&ch) {
+        let letter = char::from(b'a' + (ch as u8 - 1));
+        return (CrosstermKeyCode::Char(letter), CrosstermModifiers::CONTROL);
+    }
+    (CrosstermKeyCode::Char(ch), CrosstermModifiers::empty())
 }
 
 #[cfg(test)]
@@ -174,4 +242,110 @@ mod tests {
         assert!(events[0].modifiers.contains(CrosstermModifiers::CONTROL));
         assert_eq!(events[1].code, CrosstermKeyCode::Char('f'));
     }
+
+    #[test]
+    fn translates_named_and_arrow_keys() {
+        let none = CrosstermModifiers::empty();
+        assert_eq!(
+            translate_key("ArrowUp").unwrap(),
+            (CrosstermKeyCode::Up, none)
+        );
+        assert_eq!(
+            translate_key("down").unwrap(),
+            (CrosstermKeyCode::Down, none)
+        );
+        assert_eq!(
+            translate_key("ArrowLeft").unwrap(),
+            (CrosstermKeyCode::Left, none)
+        );
+        assert_eq!(
+            translate_key("Right").unwrap(),
+            (CrosstermKeyCode::Right, none)
+        );
+        assert_eq!(
+            translate_key("PageDown").unwrap(),
+            (CrosstermKeyCode::PageDown, none)
+        );
+        assert_eq!(translate_key("Esc").unwrap(), (CrosstermKeyCode::Esc, none));
+    }
+
+    #[test]
+    fn translates_function_keys_up_to_f12() {
+        let none = CrosstermModifiers::empty();
+        assert_eq!(translate_key("F1").unwrap(), (CrosstermKeyCode::F(1), none));
+        assert_eq!(
+            translate_key("f12").unwrap(),
+            (CrosstermKeyCode::F(12), none)
+        );
+        assert!(translate_key("F0").is_err());
+        assert!(translate_key("F13").is_err());
+    }
+
+    #[test]
+    fn translates_control_characters_to_ctrl_keys() {
+        let ctrl = CrosstermModifiers::CONTROL;
+        assert_eq!(
+            translate_key("\u{0001}").unwrap(),
+            (CrosstermKeyCode::Char('a'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{0007}").unwrap(),
+            (CrosstermKeyCode::Char('g'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{001A}").unwrap(),
+            (CrosstermKeyCode::Char('z'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{0000}").unwrap(),
+            (CrosstermKeyCode::Char(' '), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{001C}").unwrap(),
+            (CrosstermKeyCode::Char('\\'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{001D}").unwrap(),
+            (CrosstermKeyCode::Char(']'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{001E}").unwrap(),
+            (CrosstermKeyCode::Char('^'), ctrl)
+        );
+        assert_eq!(
+            translate_key("\u{001F}").unwrap(),
+            (CrosstermKeyCode::Char('_'), ctrl)
+        );
+
+        let none = CrosstermModifiers::empty();
+        assert_eq!(translate_key("\t").unwrap(), (CrosstermKeyCode::Tab, none));
+        assert_eq!(
+            translate_key("\r").unwrap(),
+            (CrosstermKeyCode::Enter, none)
+        );
+        assert_eq!(
+            translate_key("\u{001B}").unwrap(),
+            (CrosstermKeyCode::Esc, none)
+        );
+        assert_eq!(
+            translate_key("\u{007F}").unwrap(),
+            (CrosstermKeyCode::Backspace, none)
+        );
+    }
+
+    #[test]
+    fn control_character_payload_keeps_extra_modifiers() {
+        let payload = KeyStrokePayload {
+            key: "\u{0018}".into(),
+            ctrl: false,
+            alt: true,
+            shift: false,
+        };
+        let event = payload.to_key_event().unwrap();
+        assert_eq!(event.code, CrosstermKeyCode::Char('x'));
+        assert_eq!(
+            event.modifiers,
+            CrosstermModifiers::CONTROL | CrosstermModifiers::ALT
+        );
+    }
 }
@@ -6,7 +6,7 @@ pub mod snapshot;
 
 pub use altre::error::{AltreError, Result as BackendResult};
 pub use controller::{BackendController, SaveResponse};
-pub use keymap::{KeySequencePayload, KeyStrokePayload};
+pub use keymap::{translate_key, KeySequencePayload, KeyStrokePayload};
 pub use options::BackendOptions;
 pub use snapshot::{
     BufferSnapshot, CursorSnapshot, EditorSnapshot, MinibufferSnapshot, StatusSnapshot,
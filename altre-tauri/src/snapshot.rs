@@ -1,11 +1,11 @@
 use altre::buffer::CursorPosition;
 use altre::core::RenderMetadata;
 use altre::minibuffer::{MinibufferMode, MinibufferSystem};
+use altre::search::{HighlightKind, SearchHighlight};
+use altre::search::{SearchDirection, SearchStatus, SearchUiState};
 use altre::ui::viewport::ViewportState;
 use altre::ui::GuiThemeConfig;
 use serde::{Deserialize, Serialize};
-use altre::search::{HighlightKind, SearchHighlight};
-use altre::search::{SearchDirection, SearchStatus, SearchUiState};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EditorSnapshot {
@@ -37,6 +37,7 @@ pub struct MinibufferSnapshot {
     pub prompt: String,
     pub input: String,
     pub completions: Vec<String>,
+    pub completion_annotations: Vec<Option<String>>,
     pub message: Option<String>,
 }
 
@@ -44,6 +45,7 @@ pub struct MinibufferSnapshot {
 pub struct StatusSnapshot {
     pub label: String,
     pub is_modified: bool,
+    pub coding: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -89,6 +91,7 @@ impl EditorSnapshot {
             status: StatusSnapshot {
                 label: metadata.status_label.clone(),
                 is_modified: metadata.is_modified,
+                coding: metadata.coding_label.clone(),
             },
             viewport: ViewportSnapshot::from(viewport),
             theme: GuiThemeSnapshot::from(gui_theme),
@@ -122,6 +125,8 @@ impl From<&SearchHighlight> for HighlightSnapshot {
             kind: match h.kind {
                 HighlightKind::Search => "search".to_string(),
                 HighlightKind::Selection => "selection".to_string(),
+                HighlightKind::Keyword => "keyword".to_string(),
+                HighlightKind::MatchingParen => "matching-paren".to_string(),
             },
         }
     }
@@ -138,6 +143,7 @@ pub struct SearchUISnapshot {
     pub wrapped: bool,
     pub message: Option<String>,
     pub direction: String,
+    pub case_sensitive: bool,
 }
 
 impl From<&SearchUiState> for SearchUISnapshot {
@@ -149,6 +155,7 @@ impl From<&SearchUiState> for SearchUISnapshot {
                 SearchStatus::Active => "active".to_string(),
                 SearchStatus::NotFound => "not-found".to_string(),
                 SearchStatus::Wrapped => "wrapped".to_string(),
+                SearchStatus::InvalidPattern => "invalid-pattern".to_string(),
             },
             current_match: s.current_match,
             total_matches: s.total_matches,
@@ -158,6 +165,7 @@ impl From<&SearchUiState> for SearchUISnapshot {
                 SearchDirection::Forward => "forward".to_string(),
                 SearchDirection::Backward => "backward".to_string(),
             },
+            case_sensitive: s.case_sensitive,
         }
     }
 }
@@ -197,6 +205,7 @@ impl MinibufferSnapshot {
             prompt: state.prompt.clone(),
             input: state.input.clone(),
             completions: state.completions.clone(),
+            completion_annotations: state.completion_annotations.clone(),
             message: state.status_message.clone(),
         }
     }
@@ -239,5 +248,19 @@ fn describe_mode(mode: &MinibufferMode) -> &'static str {
         QueryReplacePattern => "query-replace-pattern",
         QueryReplaceReplacement => "query-replace-replacement",
         GotoLine => "goto-line",
+        ScrollLineToTop => "scroll-line-to-top",
+        GotoChar => "goto-char",
+        GotoColumn => "goto-column",
+        SetFillColumn => "set-fill-column",
+        ChangeDirectory => "change-directory",
+        CodingSystem => "coding-system",
+        InsertChar => "insert-char",
+        DescribeFunction => "describe-function",
+        CountMatches { .. } => "count-matches",
+        Occur => "occur",
+        FilterLines { keep: false } => "flush-lines",
+        FilterLines { keep: true } => "keep-lines",
+        StringRectangle => "string-rectangle",
+        YesOrNo => "yes-or-no",
     }
 }
@@ -184,11 +184,12 @@ export function App() {
 
     const modifiedFlag = snapshot.status.isModified ? '*' : ' ';
     const label = snapshot.status.label || 'scratch';
+    const coding = snapshot.status.coding ? `  ${snapshot.status.coding}` : '';
     const line = snapshot.buffer.cursor.line + 1;
     const column = snapshot.buffer.cursor.column + 1;
     const fpsDisplay = '--';
 
-    return ` ${modifiedFlag} ${label}  Ln ${line}, Col ${column}  ${lineCount} lines  FPS: ${fpsDisplay}`;
+    return ` ${modifiedFlag} ${label}${coding}  Ln ${line}, Col ${column}  ${lineCount} lines  FPS: ${fpsDisplay}`;
   }, [snapshot, lineCount]);
 
   const minibufferLines = useMemo(() => {
@@ -215,16 +216,17 @@ export function App() {
 
     const search = snapshot.searchUi;
     if (search && typeof search.pattern === 'string') {
-      const isError = search.status === 'not-found';
+      const isError = search.status === 'not-found' || search.status === 'invalid-pattern';
       const label = search.promptLabel && search.promptLabel.trim().length > 0
         ? search.promptLabel
         : (search.direction === 'backward' ? 'I-search backward' : 'I-search');
       const prompt = `${label}: `;
       const input = search.pattern.length > 0 ? search.pattern : '\u00a0';
-      const suffix =
+      const counter =
         typeof search.totalMatches === 'number'
           ? ` (${typeof search.currentMatch === 'number' ? search.currentMatch : 0}/${search.totalMatches})`
           : undefined;
+      const suffix = search.caseSensitive ? `${counter ?? ''} [case]` : counter;
       lines.push({
         key: 'isearch',
         // 常にプロンプト形式で表示し、件数はサフィックスに出す
@@ -32,12 +32,14 @@ export interface MinibufferSnapshot {
   prompt: string;
   input: string;
   completions: string[];
+  completion_annotations?: (string | null)[];
   message?: string | null;
 }
 
 export interface StatusSnapshot {
   label: string;
   isModified: boolean;
+  coding?: string;
 }
 
 export interface GuiThemeSnapshot {
@@ -60,12 +62,13 @@ export interface GuiThemeSnapshot {
 export interface SearchUISnapshot {
   promptLabel: string;
   pattern: string;
-  status: 'active' | 'not-found' | 'wrapped';
+  status: 'active' | 'not-found' | 'wrapped' | 'invalid-pattern';
   currentMatch?: number | null;
   totalMatches: number;
   wrapped: boolean;
   message?: string | null;
   direction: 'forward' | 'backward';
+  caseSensitive?: boolean;
 }
 
 export interface HighlightSnapshot {